@@ -11,6 +11,16 @@
 //!   formats the information for the `ScreenInfo` message that is sent to the
 //!   master after connecting.  The master uses this to correctly size the
 //!   client screen in the virtual layout editor.
+//!
+//! - **`pair_with_master`** – Turns the PIN the user types into a
+//!   `PairingResponse` and checks the master's `PairingConfirm`, so the client
+//!   only trusts a master that knows the PIN.
+//!
+//! - **`sync_clipboard`** – Writes clipboard content received from the master
+//!   into the local clipboard and notices local copies that should be sent
+//!   back, using a `ClipboardProvider` injected at construction time.
 
 pub mod emulate_input;
+pub mod pair_with_master;
 pub mod report_screens;
+pub mod sync_clipboard;
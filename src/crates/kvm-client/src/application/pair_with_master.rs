@@ -0,0 +1,310 @@
+//! PairWithMasterUseCase: the client's side of PIN pairing.
+//!
+//! # Purpose
+//!
+//! The first time this client connects, the master shows a 6-digit PIN on its
+//! screen and sends a `PairingRequest`.  The user types the PIN on the client;
+//! this module turns that PIN into a `PairingResponse` and later checks the
+//! master's `PairingConfirm`.
+//!
+//! # Data flow
+//!
+//! ```text
+//! PairingRequest { session, master share }
+//!   └─ ClientPairing::new(request, own cert, master cert)
+//!        └─ user types PIN
+//!             └─ respond(pin) ──► PairingResponse { client share, client MAC }
+//!                                   │
+//! PairingConfirm { master MAC } ◄───┘  (or Error(PairingFailed): wrong PIN,
+//!   └─ confirm(msg)                     then a new PairingRequest to answer)
+//!        └─ Ok: the master knows the PIN, and no one sits between us
+//! ```
+//!
+//! # Why check the master's confirmation? (for beginners)
+//!
+//! Without it the client would accept *any* peer that answers on the master's
+//! address.  The master's MAC can only be computed by someone who used the
+//! same PIN *and* saw the same TLS certificates, so a valid confirmation means
+//! the machine showing the PIN is the one on the other end of this connection.
+//! The cryptography lives in `kvm_core::crypto::pairing`.
+
+use kvm_core::crypto::pairing::{channel_binding, PairingKeys, PakeError, Role, Spake2};
+use kvm_core::crypto::tls::CertFingerprint;
+use kvm_core::protocol::messages::{
+    PairingConfirmMessage, PairingRequestMessage, PairingResponseMessage,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Error type for the client side of pairing.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ClientPairingError {
+    /// A `PairingConfirm` arrived for a different pairing session.
+    #[error("confirmation is for pairing session {0}, not ours")]
+    WrongSession(Uuid),
+
+    /// A `PairingConfirm` arrived before we sent a response.
+    #[error("no PIN has been submitted for this pairing session")]
+    NotResponded,
+
+    /// The key exchange failed: the master's share is invalid or its
+    /// confirmation does not verify.
+    #[error(transparent)]
+    KeyExchange(#[from] PakeError),
+}
+
+/// One pairing attempt, from `PairingRequest` to `PairingConfirm`.
+pub struct ClientPairing {
+    request: PairingRequestMessage,
+    binding: Vec<u8>,
+    /// Keys from the most recent `respond`, needed to check the confirmation.
+    keys: Option<PairingKeys>,
+}
+
+impl ClientPairing {
+    /// Starts pairing for `request`.
+    ///
+    /// `own_certificate` is this client's TLS fingerprint and
+    /// `master_certificate` the one the master presented on this connection;
+    /// pass `None` for either on a plaintext connection.
+    pub fn new(
+        request: PairingRequestMessage,
+        own_certificate: Option<CertFingerprint>,
+        master_certificate: Option<CertFingerprint>,
+    ) -> Self {
+        let binding = channel_binding(
+            own_certificate.as_ref().map(CertFingerprint::as_bytes),
+            master_certificate.as_ref().map(CertFingerprint::as_bytes),
+        );
+        Self {
+            request,
+            binding,
+            keys: None,
+        }
+    }
+
+    /// The pairing session this attempt belongs to.
+    pub fn session_id(&self) -> Uuid {
+        self.request.pairing_session_id
+    }
+
+    /// Returns `true` once `now_secs` (Unix time) is past the request's expiry.
+    pub fn is_expired(&self, now_secs: u64) -> bool {
+        now_secs > self.request.expires_at_secs
+    }
+
+    /// Builds the `PairingResponse` for `pin`.
+    ///
+    /// After a wrong PIN the master starts a fresh exchange and sends a new
+    /// `PairingRequest`; answer the corrected PIN through a `ClientPairing`
+    /// for that request, not this one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientPairingError::KeyExchange`] if the master's share in the
+    /// request is not a valid group element.
+    pub fn respond(&mut self, pin: &str) -> Result<PairingResponseMessage, ClientPairingError> {
+        let session_id = self.session_id();
+        let spake = Spake2::start(Role::Client, pin.trim(), session_id, &self.binding);
+        let spake_message = spake.message();
+        let keys = spake.finish(&self.request.spake_message)?;
+        let confirmation = keys.confirmation(Role::Client);
+        self.keys = Some(keys);
+        Ok(PairingResponseMessage {
+            pairing_session_id: session_id,
+            spake_message,
+            confirmation,
+            accepted: true,
+        })
+    }
+
+    /// Builds the `PairingResponse` telling the master the user dismissed the
+    /// PIN prompt.
+    pub fn decline(&self) -> PairingResponseMessage {
+        PairingResponseMessage {
+            pairing_session_id: self.session_id(),
+            spake_message: [0; 32],
+            confirmation: [0; 32],
+            accepted: false,
+        }
+    }
+
+    /// Checks the master's `PairingConfirm` against the last response.
+    ///
+    /// On success returns the shared key both sides derived.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientPairingError::WrongSession`] or
+    /// [`ClientPairingError::NotResponded`] for a confirmation we did not ask
+    /// for, and [`ClientPairingError::KeyExchange`] if the MAC does not verify.
+    pub fn confirm(&self, msg: &PairingConfirmMessage) -> Result<[u8; 32], ClientPairingError> {
+        if msg.pairing_session_id != self.session_id() {
+            return Err(ClientPairingError::WrongSession(msg.pairing_session_id));
+        }
+        let keys = self.keys.as_ref().ok_or(ClientPairingError::NotResponded)?;
+        keys.verify_confirmation(Role::Master, &msg.confirmation)?;
+        Ok(*keys.shared_key())
+    }
+}
+
+impl std::fmt::Debug for ClientPairing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientPairing")
+            .field("session_id", &self.session_id())
+            .field("responded", &self.keys.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The master's side of a session: its request and the keys it would
+    /// derive from a client share.
+    struct FakeMaster {
+        spake: Spake2,
+        request: PairingRequestMessage,
+    }
+
+    impl FakeMaster {
+        fn new(pin: &str, client_cert: CertFingerprint, master_cert: CertFingerprint) -> Self {
+            let session = Uuid::new_v4();
+            let binding =
+                channel_binding(Some(client_cert.as_bytes()), Some(master_cert.as_bytes()));
+            let spake = Spake2::start(Role::Master, pin, session, &binding);
+            let request = PairingRequestMessage {
+                pairing_session_id: session,
+                expires_at_secs: 1_000,
+                spake_message: spake.message(),
+            };
+            Self { spake, request }
+        }
+
+        /// Verifies the client's response and returns the master's confirmation.
+        fn answer(self, response: &PairingResponseMessage) -> Option<PairingConfirmMessage> {
+            let keys = self.spake.finish(&response.spake_message).ok()?;
+            keys.verify_confirmation(Role::Client, &response.confirmation)
+                .ok()?;
+            Some(PairingConfirmMessage {
+                pairing_session_id: response.pairing_session_id,
+                confirmation: keys.confirmation(Role::Master),
+            })
+        }
+    }
+
+    fn certs() -> (CertFingerprint, CertFingerprint) {
+        (
+            CertFingerprint::of(b"client certificate"),
+            CertFingerprint::of(b"master certificate"),
+        )
+    }
+
+    #[test]
+    fn test_correct_pin_completes_pairing() {
+        // Arrange
+        let (client_cert, master_cert) = certs();
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let mut pairing =
+            ClientPairing::new(master.request.clone(), Some(client_cert), Some(master_cert));
+
+        // Act
+        let response = pairing.respond("246810").unwrap();
+        let confirm = master
+            .answer(&response)
+            .expect("master must accept the PIN");
+
+        // Assert
+        assert!(response.accepted);
+        assert!(pairing.confirm(&confirm).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_pin_is_rejected_by_master_and_can_be_retried() {
+        // Arrange
+        let (client_cert, master_cert) = certs();
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let mut pairing =
+            ClientPairing::new(master.request.clone(), Some(client_cert), Some(master_cert));
+        let wrong = pairing.respond("135791").unwrap();
+        assert!(master.answer(&wrong).is_none());
+
+        // Act: the master follows the failure with a fresh request.
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let mut retry =
+            ClientPairing::new(master.request.clone(), Some(client_cert), Some(master_cert));
+        let right = retry.respond(" 246810\n").unwrap();
+
+        // Assert: whitespace around the typed PIN is ignored.
+        let confirm = master.answer(&right).expect("retry must succeed");
+        assert!(retry.confirm(&confirm).is_ok());
+    }
+
+    #[test]
+    fn test_confirmation_from_impostor_master_is_rejected() {
+        // Arrange: the client sees a different master certificate than the
+        // real master's, as it would behind a TLS man-in-the-middle.
+        let (client_cert, master_cert) = certs();
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let impostor_cert = CertFingerprint::of(b"impostor certificate");
+        let mut pairing = ClientPairing::new(
+            master.request.clone(),
+            Some(client_cert),
+            Some(impostor_cert),
+        );
+
+        // Act
+        let response = pairing.respond("246810").unwrap();
+
+        // Assert: the real master cannot verify the response either.
+        assert!(master.answer(&response).is_none());
+    }
+
+    #[test]
+    fn test_confirm_rejects_tampered_mac() {
+        let (client_cert, master_cert) = certs();
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let mut pairing =
+            ClientPairing::new(master.request.clone(), Some(client_cert), Some(master_cert));
+        let mut confirm = master.answer(&pairing.respond("246810").unwrap()).unwrap();
+        confirm.confirmation[0] ^= 1;
+
+        assert_eq!(
+            pairing.confirm(&confirm),
+            Err(ClientPairingError::KeyExchange(
+                PakeError::ConfirmationMismatch
+            ))
+        );
+    }
+
+    #[test]
+    fn test_confirm_before_respond_is_rejected() {
+        let (client_cert, master_cert) = certs();
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let pairing =
+            ClientPairing::new(master.request.clone(), Some(client_cert), Some(master_cert));
+        let confirm = PairingConfirmMessage {
+            pairing_session_id: pairing.session_id(),
+            confirmation: [0; 32],
+        };
+
+        assert_eq!(
+            pairing.confirm(&confirm),
+            Err(ClientPairingError::NotResponded)
+        );
+    }
+
+    #[test]
+    fn test_decline_and_expiry() {
+        let (client_cert, master_cert) = certs();
+        let master = FakeMaster::new("246810", client_cert, master_cert);
+        let pairing = ClientPairing::new(master.request.clone(), None, None);
+
+        assert!(!pairing.decline().accepted);
+        assert!(!pairing.is_expired(1_000));
+        assert!(pairing.is_expired(1_001));
+    }
+}
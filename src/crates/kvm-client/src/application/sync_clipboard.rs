@@ -0,0 +1,206 @@
+//! SyncClipboardUseCase: keeps the client's clipboard in step with the master.
+//!
+//! # Two directions
+//!
+//! - **Master → client.** When the cursor crosses onto this client the master
+//!   sends its clipboard as one or more `ClipboardData` fragments.
+//!   [`SyncClipboardUseCase::handle_fragment`] reassembles them and writes the
+//!   result into the local clipboard, so the user can paste right away.
+//! - **Client → master.** The client has no way to know when the user is
+//!   about to leave it, so it polls its clipboard instead.
+//!   [`SyncClipboardUseCase::poll_local_change`] returns the fragments to send
+//!   whenever the local content differs from what was last synced.
+//!
+//! Content written on behalf of the master counts as synced, so it is not
+//! echoed straight back on the next poll.
+//!
+//! Local content is downgraded to what the master negotiated before it is
+//! sent (HTML becomes text, images are kept local for a text-only master);
+//! see `ClipboardContent::for_peer`.
+
+use std::sync::Arc;
+
+use kvm_core::clipboard::{
+    chunk, ClipboardContent, ClipboardError, ClipboardProvider, ClipboardReassembler,
+    DEFAULT_MAX_CLIPBOARD_LEN,
+};
+use kvm_core::protocol::messages::ClipboardDataMessage;
+
+/// Reassembles incoming clipboard transfers and detects local changes.
+pub struct SyncClipboardUseCase {
+    provider: Arc<dyn ClipboardProvider>,
+    reassembler: ClipboardReassembler,
+    /// The content both sides are known to have.
+    last_synced: Option<ClipboardContent>,
+}
+
+impl SyncClipboardUseCase {
+    /// Creates a use case that reads and writes `provider`.
+    pub fn new(provider: Arc<dyn ClipboardProvider>) -> Self {
+        Self {
+            provider,
+            reassembler: ClipboardReassembler::new(DEFAULT_MAX_CLIPBOARD_LEN),
+            last_synced: None,
+        }
+    }
+
+    /// Feeds one `ClipboardData` fragment received from the master.
+    ///
+    /// Once the last fragment of a transfer arrives the content is written to
+    /// the local clipboard.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::TooLarge`] if the transfer exceeds the size
+    /// cap, or the provider's error if the clipboard cannot be written.
+    pub fn handle_fragment(
+        &mut self,
+        fragment: ClipboardDataMessage,
+    ) -> Result<(), ClipboardError> {
+        if let Some(content) = self.reassembler.push(fragment)? {
+            self.provider.write(&content)?;
+            self.last_synced = Some(content);
+        }
+        Ok(())
+    }
+
+    /// Checks the local clipboard and returns the fragments to send to the
+    /// master if it changed since the last sync; an empty `Vec` otherwise.
+    ///
+    /// `master_capabilities` are the capabilities negotiated with the master;
+    /// the content is sent in the richest format they allow.
+    ///
+    /// # Errors
+    ///
+    /// Returns the provider's error if the clipboard cannot be read,
+    /// [`ClipboardError::TooLarge`] if the new content exceeds the size cap,
+    /// or [`ClipboardError::Malformed`] if it cannot be downgraded.  Failing
+    /// content is reported once, not on every poll.
+    pub fn poll_local_change(
+        &mut self,
+        master_capabilities: u32,
+    ) -> Result<Vec<ClipboardDataMessage>, ClipboardError> {
+        let Some(content) = self.provider.read()? else {
+            return Ok(Vec::new());
+        };
+        if self.last_synced.as_ref() == Some(&content) {
+            return Ok(Vec::new());
+        }
+        let outgoing = content.for_peer(master_capabilities);
+        self.last_synced = Some(content);
+        match outgoing? {
+            Some(outgoing) => chunk(&outgoing, DEFAULT_MAX_CLIPBOARD_LEN),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Drops any half-received transfer; call when the connection is lost.
+    pub fn reset_transfer(&mut self) {
+        self.reassembler = ClipboardReassembler::new(DEFAULT_MAX_CLIPBOARD_LEN);
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvm_core::clipboard::mock::MockClipboardProvider;
+    use kvm_core::clipboard::HtmlClipboard;
+    use kvm_core::clipboard::FRAGMENT_LEN;
+    use kvm_core::protocol::messages::{capabilities, ClipboardFormat};
+
+    const ALL_FORMATS: u32 = capabilities::CLIPBOARD_SHARING
+        | capabilities::CLIPBOARD_HTML
+        | capabilities::CLIPBOARD_IMAGE;
+
+    fn make_use_case() -> (SyncClipboardUseCase, Arc<MockClipboardProvider>) {
+        let provider = Arc::new(MockClipboardProvider::new());
+        let uc = SyncClipboardUseCase::new(Arc::clone(&provider) as Arc<dyn ClipboardProvider>);
+        (uc, provider)
+    }
+
+    #[test]
+    fn test_fragments_from_master_are_written_once_complete() {
+        // Arrange
+        let (mut uc, provider) = make_use_case();
+        let image = ClipboardContent {
+            format: ClipboardFormat::Image,
+            data: vec![7; FRAGMENT_LEN + 1],
+        };
+        let fragments = chunk(&image, DEFAULT_MAX_CLIPBOARD_LEN).unwrap();
+
+        // Act
+        uc.handle_fragment(fragments[0].clone()).unwrap();
+        let written_after_first = provider.writes.lock().unwrap().len();
+        uc.handle_fragment(fragments[1].clone()).unwrap();
+
+        // Assert
+        assert_eq!(written_after_first, 0);
+        assert_eq!(*provider.writes.lock().unwrap(), vec![image]);
+    }
+
+    #[test]
+    fn test_content_from_master_is_not_echoed_back() {
+        // Arrange
+        let (mut uc, _provider) = make_use_case();
+        let text = chunk(&ClipboardContent::text("from master"), usize::MAX).unwrap();
+
+        // Act
+        uc.handle_fragment(text[0].clone()).unwrap();
+        let outgoing = uc.poll_local_change(ALL_FORMATS).unwrap();
+
+        // Assert
+        assert!(outgoing.is_empty());
+    }
+
+    #[test]
+    fn test_local_copy_is_sent_once() {
+        // Arrange
+        let (mut uc, provider) = make_use_case();
+        provider.set(ClipboardContent::text("copied on client"));
+
+        // Act
+        let first = uc.poll_local_change(ALL_FORMATS).unwrap();
+        let second = uc.poll_local_change(ALL_FORMATS).unwrap();
+
+        // Assert
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].data, b"copied on client");
+        assert!(second.is_empty());
+    }
+
+    #[test]
+    fn test_html_copy_is_sent_as_text_to_a_text_only_master() {
+        // Arrange
+        let (mut uc, provider) = make_use_case();
+        provider.set(HtmlClipboard::from_html("<p>a &lt; b</p>").to_content());
+
+        // Act
+        let outgoing = uc
+            .poll_local_change(capabilities::CLIPBOARD_SHARING)
+            .unwrap();
+        let again = uc
+            .poll_local_change(capabilities::CLIPBOARD_SHARING)
+            .unwrap();
+
+        // Assert
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].format, ClipboardFormat::Utf8Text);
+        assert_eq!(outgoing[0].data, b"a < b");
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn test_read_failure_is_reported() {
+        let mut uc = SyncClipboardUseCase::new(Arc::new(MockClipboardProvider {
+            should_fail: true,
+            ..MockClipboardProvider::default()
+        }));
+
+        assert!(matches!(
+            uc.poll_local_change(ALL_FORMATS),
+            Err(ClipboardError::Unavailable(_))
+        ));
+    }
+}
@@ -0,0 +1,777 @@
+//! Linux clipboard access through the X11 `CLIPBOARD` selection.
+//!
+//! # How the X11 clipboard works (for beginners)
+//!
+//! X11 has no central clipboard store.  "Copying" only makes an application
+//! the *owner* of the `CLIPBOARD` selection; the data stays inside that
+//! application.  To paste, another application asks the X server to convert
+//! the selection to a *target* type (`UTF8_STRING`, `text/html`,
+//! `image/png`, …):
+//!
+//! ```text
+//! reader                      X server                      owner
+//!   XConvertSelection ──────────►  SelectionRequest ─────────►
+//!                                                   XChangeProperty(reader window)
+//!   SelectionNotify  ◄──────────── XSendEvent ◄───────────────
+//!   XGetWindowProperty
+//! ```
+//!
+//! Writing therefore means becoming the owner and answering every
+//! `SelectionRequest` until another application takes ownership (we get a
+//! `SelectionClear`).  That needs an event loop, so [`X11ClipboardProvider`]
+//! runs a worker thread with its own display connection and an invisible
+//! window; `read` and `write` are requests to that thread.
+//!
+//! # Formats
+//!
+//! A read first asks the owner for its `TARGETS` and takes the richest one
+//! the wire has a canonical encoding for:
+//!
+//! | Owner offers         | Read as                                             |
+//! |----------------------|-----------------------------------------------------|
+//! | `image/png`          | [`ClipboardFormat::Image`], PNG bytes passed through |
+//! | `text/html`          | [`ClipboardFormat::Html`], with `UTF8_STRING` as the text part (or text derived from the HTML) |
+//! | `UTF8_STRING`        | [`ClipboardFormat::Utf8Text`]                        |
+//!
+//! Writing offers the same targets back, plus `STRING` and
+//! `text/plain;charset=utf-8` for text.  Images in other formats
+//! (`image/bmp`, `image/jpeg`, …) are not read.
+//!
+//! Clients poll the clipboard, so re-reading a large image every time would
+//! be wasteful.  When the owner reports the `TIMESTAMP` at which it took the
+//! selection, a read with an unchanged owner and timestamp returns the
+//! previous result without converting anything.
+//!
+//! # Limitations
+//!
+//! - Large selections arrive through the incremental `INCR` protocol, which
+//!   reads support.  When serving, content larger than one X request is
+//!   refused instead of being sent incrementally; with the BIG-REQUESTS
+//!   extension that limit is far above the clipboard size cap.
+//! - Opening the provider installs an X error handler that ignores errors
+//!   (for example a requestor window that vanished mid-transfer) instead of
+//!   Xlib's default handler, which exits the process.  The handler is
+//!   process-wide.
+
+use std::os::raw::{c_char, c_int, c_long, c_uchar, c_ulong};
+use std::sync::{mpsc, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use kvm_core::clipboard::{
+    inspect_png, ClipboardContent, ClipboardError, ClipboardProvider, HtmlClipboard,
+    DEFAULT_MAX_CLIPBOARD_LEN,
+};
+use kvm_core::protocol::messages::ClipboardFormat;
+use x11::xlib;
+
+/// How long a read waits for the selection owner to answer (and, for `INCR`
+/// transfers, for each further chunk).
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How often the worker checks for X events while idle.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A request handled by the worker thread.
+enum Command {
+    Read(mpsc::Sender<Result<Option<ClipboardContent>, ClipboardError>>),
+    Write(ClipboardContent, mpsc::Sender<Result<(), ClipboardError>>),
+}
+
+/// [`ClipboardProvider`] backed by the X11 `CLIPBOARD` selection.
+///
+/// Dropping the provider stops its worker thread and closes its display
+/// connection; anything it still owned disappears from the clipboard.
+pub struct X11ClipboardProvider {
+    commands: mpsc::Sender<Command>,
+}
+
+impl X11ClipboardProvider {
+    /// Connects to the display named by `DISPLAY` and starts the worker.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::Unavailable`] if the display cannot be opened.
+    pub fn open() -> Result<Self, ClipboardError> {
+        let (commands, requests) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("x11-clipboard".to_string())
+            .spawn(move || match Worker::open() {
+                Ok(mut worker) => {
+                    let _ = ready_tx.send(Ok(()));
+                    worker.run(requests);
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                }
+            })
+            .map_err(|e| ClipboardError::Unavailable(format!("cannot start worker: {e}")))?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| ClipboardError::Unavailable("clipboard worker died".to_string()))??;
+        Ok(Self { commands })
+    }
+
+    /// Sends `command` to the worker and waits for its reply.
+    fn request<T>(
+        &self,
+        command: impl FnOnce(mpsc::Sender<Result<T, ClipboardError>>) -> Command,
+    ) -> Result<T, ClipboardError> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let stopped = || ClipboardError::Unavailable("clipboard worker stopped".to_string());
+        self.commands
+            .send(command(reply_tx))
+            .map_err(|_| stopped())?;
+        reply_rx.recv().map_err(|_| stopped())?
+    }
+}
+
+impl ClipboardProvider for X11ClipboardProvider {
+    fn read(&self) -> Result<Option<ClipboardContent>, ClipboardError> {
+        self.request(Command::Read)
+    }
+
+    fn write(&self, content: &ClipboardContent) -> Result<(), ClipboardError> {
+        let content = content.clone();
+        self.request(|reply| Command::Write(content, reply))
+    }
+}
+
+/// Atoms interned once per display connection.
+struct Atoms {
+    clipboard: xlib::Atom,
+    utf8_string: xlib::Atom,
+    text_plain_utf8: xlib::Atom,
+    html: xlib::Atom,
+    png: xlib::Atom,
+    targets: xlib::Atom,
+    timestamp: xlib::Atom,
+    incr: xlib::Atom,
+    /// Property on our window that owners write converted data into.
+    transfer: xlib::Atom,
+}
+
+/// Owns the display connection; lives on the worker thread only.
+struct Worker {
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    atoms: Atoms,
+    /// Largest property we can set in one request.
+    max_property_len: usize,
+    /// What we currently own the selection with.
+    owned: Option<Owned>,
+    /// The last read of another application's selection, keyed by owner
+    /// window and selection timestamp.
+    cache: Option<((xlib::Window, c_ulong), Option<ClipboardContent>)>,
+}
+
+/// Content we own the selection with, and the bytes served per target.
+struct Owned {
+    content: ClipboardContent,
+    offers: Vec<(xlib::Atom, Vec<u8>)>,
+}
+
+/// A property read back from our window.
+#[derive(Default)]
+struct Property {
+    /// Items of a format-8 property.
+    bytes: Vec<u8>,
+    /// Items of a format-32 property (Xlib returns them as C longs).
+    longs: Vec<c_ulong>,
+    /// Whether the owner started an incremental transfer.
+    incr: bool,
+}
+
+impl Worker {
+    fn open() -> Result<Self, ClipboardError> {
+        static ERROR_HANDLER: Once = Once::new();
+        // SAFETY: installing a handler has no preconditions; the handler
+        // itself only returns.
+        ERROR_HANDLER.call_once(|| unsafe {
+            xlib::XSetErrorHandler(Some(ignore_x_error));
+        });
+
+        // SAFETY: null means "use the DISPLAY environment variable".
+        let display = unsafe { xlib::XOpenDisplay(std::ptr::null()) };
+        if display.is_null() {
+            let display_env = std::env::var("DISPLAY").unwrap_or_else(|_| "<unset>".to_string());
+            return Err(ClipboardError::Unavailable(format!(
+                "XOpenDisplay failed; DISPLAY={display_env}"
+            )));
+        }
+
+        // SAFETY: `display` is a valid connection.  The window is never
+        // mapped; it only exists to own the selection and receive properties.
+        let window = unsafe {
+            xlib::XCreateSimpleWindow(
+                display,
+                xlib::XDefaultRootWindow(display),
+                0,
+                0,
+                1,
+                1,
+                0,
+                0,
+                0,
+            )
+        };
+        // SAFETY: as above.  Property events drive INCR transfers.
+        unsafe { xlib::XSelectInput(display, window, xlib::PropertyChangeMask) };
+        // SAFETY: `display` is a valid connection.
+        let max_request_units = unsafe {
+            match xlib::XExtendedMaxRequestSize(display) {
+                0 => xlib::XMaxRequestSize(display),
+                units => units,
+            }
+        };
+        let intern = |name: &[u8]| {
+            // SAFETY: every name passed below is NUL-terminated.
+            unsafe { xlib::XInternAtom(display, name.as_ptr() as *const c_char, xlib::False) }
+        };
+        let atoms = Atoms {
+            clipboard: intern(b"CLIPBOARD\0"),
+            utf8_string: intern(b"UTF8_STRING\0"),
+            text_plain_utf8: intern(b"text/plain;charset=utf-8\0"),
+            html: intern(b"text/html\0"),
+            png: intern(b"image/png\0"),
+            targets: intern(b"TARGETS\0"),
+            timestamp: intern(b"TIMESTAMP\0"),
+            incr: intern(b"INCR\0"),
+            transfer: intern(b"KVM_CLIPBOARD_TRANSFER\0"),
+        };
+
+        Ok(Self {
+            display,
+            window,
+            atoms,
+            // Leave room for the ChangeProperty request header.
+            max_property_len: (max_request_units as usize * 4).saturating_sub(64),
+            owned: None,
+            cache: None,
+        })
+    }
+
+    /// Serves X events and requests until the provider is dropped.
+    fn run(&mut self, requests: mpsc::Receiver<Command>) {
+        loop {
+            self.drain_events();
+            match requests.recv_timeout(EVENT_POLL_INTERVAL) {
+                Ok(Command::Read(reply)) => {
+                    let _ = reply.send(self.read());
+                }
+                Ok(Command::Write(content, reply)) => {
+                    let _ = reply.send(self.write(content));
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    /// Handles every queued event without blocking.
+    fn drain_events(&mut self) {
+        while let Some(event) = self.next_event() {
+            self.handle_event(&event);
+        }
+    }
+
+    fn next_event(&self) -> Option<xlib::XEvent> {
+        // SAFETY: `display` is valid for the worker's lifetime; XNextEvent
+        // only blocks when nothing is pending, which XPending rules out.
+        unsafe {
+            if xlib::XPending(self.display) == 0 {
+                return None;
+            }
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            xlib::XNextEvent(self.display, &mut event);
+            Some(event)
+        }
+    }
+
+    fn handle_event(&mut self, event: &xlib::XEvent) {
+        match event.get_type() {
+            // SAFETY: the union field matches the event type.
+            xlib::SelectionRequest => self.serve(unsafe { &event.selection_request }),
+            xlib::SelectionClear => self.owned = None,
+            _ => {}
+        }
+    }
+
+    /// Answers another application's paste request.
+    fn serve(&self, request: &xlib::XSelectionRequestEvent) {
+        // Obsolete clients leave the property unset and expect the target.
+        let mut property = if request.property == 0 {
+            request.target
+        } else {
+            request.property
+        };
+
+        let answered = match &self.owned {
+            Some(_) if request.selection != self.atoms.clipboard => false,
+            Some(owned) if request.target == self.atoms.targets => {
+                let targets: Vec<xlib::Atom> = std::iter::once(self.atoms.targets)
+                    .chain(owned.offers.iter().map(|(target, _)| *target))
+                    .collect();
+                // SAFETY: format 32 properties are arrays of C longs, which
+                // is what `Atom` is.
+                unsafe {
+                    xlib::XChangeProperty(
+                        self.display,
+                        request.requestor,
+                        property,
+                        xlib::XA_ATOM,
+                        32,
+                        xlib::PropModeReplace,
+                        targets.as_ptr() as *const c_uchar,
+                        targets.len() as c_int,
+                    );
+                }
+                true
+            }
+            Some(owned) => match owned.offers.iter().find(|(t, _)| *t == request.target) {
+                Some((_, data)) if data.len() <= self.max_property_len => {
+                    // SAFETY: `data` outlives the call, which copies it.
+                    unsafe {
+                        xlib::XChangeProperty(
+                            self.display,
+                            request.requestor,
+                            property,
+                            request.target,
+                            8,
+                            xlib::PropModeReplace,
+                            data.as_ptr(),
+                            data.len() as c_int,
+                        );
+                    }
+                    true
+                }
+                _ => false,
+            },
+            None => false,
+        };
+        if !answered {
+            property = 0;
+        }
+
+        let mut notify = xlib::XEvent {
+            selection: xlib::XSelectionEvent {
+                type_: xlib::SelectionNotify,
+                serial: 0,
+                send_event: xlib::True,
+                display: self.display,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                property,
+                time: request.time,
+            },
+        };
+        // SAFETY: `notify` is a fully initialised SelectionNotify event.
+        unsafe {
+            xlib::XSendEvent(self.display, request.requestor, xlib::False, 0, &mut notify);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    fn read(&mut self) -> Result<Option<ClipboardContent>, ClipboardError> {
+        if let Some(owned) = &self.owned {
+            return Ok(Some(owned.content.clone()));
+        }
+
+        // SAFETY: `display` is valid for the worker's lifetime.
+        let owner = unsafe { xlib::XGetSelectionOwner(self.display, self.atoms.clipboard) };
+        if owner == 0 {
+            self.cache = None;
+            return Ok(None);
+        }
+
+        // Owners that predate TARGETS still convert to UTF8_STRING.
+        let targets: Vec<xlib::Atom> = self
+            .convert(self.atoms.targets)?
+            .map(|p| p.longs)
+            .unwrap_or_default();
+        let stamp = if targets.contains(&self.atoms.timestamp) {
+            self.convert(self.atoms.timestamp)?
+                .and_then(|p| p.longs.first().copied())
+                .filter(|&time| time != 0)
+                .map(|time| (owner, time))
+        } else {
+            None
+        };
+        if let (Some(stamp), Some((cached, content))) = (stamp, &self.cache) {
+            if stamp == *cached {
+                return Ok(content.clone());
+            }
+        }
+
+        let content = self.read_content(&targets)?;
+        self.cache = stamp.map(|stamp| (stamp, content.clone()));
+        Ok(content)
+    }
+
+    /// Converts the selection to the richest of `targets` we support.
+    fn read_content(
+        &mut self,
+        targets: &[xlib::Atom],
+    ) -> Result<Option<ClipboardContent>, ClipboardError> {
+        let offers = |target| targets.contains(&target);
+
+        if offers(self.atoms.png) {
+            if let Some(png) = self.convert(self.atoms.png)? {
+                // A broken or oversized image falls back to the text below.
+                if let Ok(content) = ClipboardContent::png(png.bytes) {
+                    return Ok(Some(content));
+                }
+            }
+        }
+
+        let text = if targets.is_empty() || offers(self.atoms.utf8_string) {
+            self.convert(self.atoms.utf8_string)?.map(|p| p.bytes)
+        } else {
+            None
+        };
+
+        if offers(self.atoms.html) {
+            if let Some(html) = self.convert(self.atoms.html)? {
+                let html = decode_html_target(&html.bytes);
+                let clipboard = match text {
+                    Some(text) => HtmlClipboard {
+                        html,
+                        text: String::from_utf8_lossy(&text).into_owned(),
+                    },
+                    None => HtmlClipboard::from_html(html),
+                };
+                return Ok(Some(clipboard.to_content()));
+            }
+        }
+
+        Ok(text.map(|data| ClipboardContent {
+            format: ClipboardFormat::Utf8Text,
+            data,
+        }))
+    }
+
+    /// Asks the owner to convert the selection to `target` and returns the
+    /// result, or `None` if the owner refused.
+    fn convert(&mut self, target: xlib::Atom) -> Result<Option<Property>, ClipboardError> {
+        // SAFETY: `display` and `window` are valid for the worker's lifetime.
+        unsafe {
+            xlib::XConvertSelection(
+                self.display,
+                self.atoms.clipboard,
+                target,
+                self.atoms.transfer,
+                self.window,
+                xlib::CurrentTime,
+            );
+            xlib::XFlush(self.display);
+        }
+
+        let (window, clipboard) = (self.window, self.atoms.clipboard);
+        let event = self.wait_for(|event| {
+            // SAFETY: the union field matches the event type.
+            event.get_type() == xlib::SelectionNotify && {
+                let notify = unsafe { event.selection };
+                notify.requestor == window
+                    && notify.selection == clipboard
+                    && notify.target == target
+            }
+        })?;
+        // SAFETY: `wait_for` only returns SelectionNotify events.
+        if unsafe { event.selection }.property == 0 {
+            return Ok(None);
+        }
+
+        let property = self.take_property()?;
+        if property.incr {
+            return self.read_incr().map(Some);
+        }
+        Ok(Some(property))
+    }
+
+    /// Collects an `INCR` transfer: the owner writes the data in chunks,
+    /// each one after we delete the previous, and ends with an empty chunk.
+    fn read_incr(&mut self) -> Result<Property, ClipboardError> {
+        let mut data = Vec::new();
+        loop {
+            let (window, transfer) = (self.window, self.atoms.transfer);
+            self.wait_for(|event| {
+                // SAFETY: the union field matches the event type.
+                event.get_type() == xlib::PropertyNotify && {
+                    let notify = unsafe { event.property };
+                    notify.window == window
+                        && notify.atom == transfer
+                        && notify.state == xlib::PropertyNewValue
+                }
+            })?;
+            let chunk = self.take_property()?;
+            if chunk.bytes.is_empty() {
+                return Ok(Property {
+                    bytes: data,
+                    ..Property::default()
+                });
+            }
+            if data.len() + chunk.bytes.len() > DEFAULT_MAX_CLIPBOARD_LEN {
+                return Err(ClipboardError::TooLarge {
+                    limit: DEFAULT_MAX_CLIPBOARD_LEN,
+                });
+            }
+            data.extend_from_slice(&chunk.bytes);
+        }
+    }
+
+    /// Handles events until one satisfies `wanted`, which is returned.
+    fn wait_for(
+        &mut self,
+        mut wanted: impl FnMut(&xlib::XEvent) -> bool,
+    ) -> Result<xlib::XEvent, ClipboardError> {
+        let deadline = Instant::now() + READ_TIMEOUT;
+        loop {
+            while let Some(event) = self.next_event() {
+                if wanted(&event) {
+                    return Ok(event);
+                }
+                self.handle_event(&event);
+            }
+            if Instant::now() >= deadline {
+                return Err(ClipboardError::Unavailable(
+                    "clipboard owner did not answer".to_string(),
+                ));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Reads and deletes the property the owner wrote into.  Deleting it is
+    /// also what asks an `INCR` owner for the next chunk.
+    fn take_property(&self) -> Result<Property, ClipboardError> {
+        let mut actual_type: xlib::Atom = 0;
+        let mut actual_format: c_int = 0;
+        let mut item_count: c_ulong = 0;
+        let mut bytes_after: c_ulong = 0;
+        let mut data: *mut c_uchar = std::ptr::null_mut();
+        // SAFETY: all out-pointers are valid; `data` is freed below.  The
+        // length argument is in 32-bit units.
+        let status = unsafe {
+            xlib::XGetWindowProperty(
+                self.display,
+                self.window,
+                self.atoms.transfer,
+                0,
+                (DEFAULT_MAX_CLIPBOARD_LEN / 4) as c_long,
+                xlib::True,
+                xlib::AnyPropertyType as xlib::Atom,
+                &mut actual_type,
+                &mut actual_format,
+                &mut item_count,
+                &mut bytes_after,
+                &mut data,
+            )
+        };
+        if status != xlib::Success as c_int {
+            return Err(ClipboardError::Unavailable(
+                "XGetWindowProperty failed".to_string(),
+            ));
+        }
+
+        let result = if bytes_after > 0 {
+            Err(ClipboardError::TooLarge {
+                limit: DEFAULT_MAX_CLIPBOARD_LEN,
+            })
+        } else if data.is_null() {
+            Ok(Property::default())
+        } else {
+            let count = item_count as usize;
+            // SAFETY: Xlib returns `item_count` items of the reported format;
+            // format 32 items are C longs.
+            Ok(match actual_format {
+                8 => Property {
+                    bytes: unsafe { std::slice::from_raw_parts(data, count) }.to_vec(),
+                    ..Property::default()
+                },
+                32 => Property {
+                    longs: unsafe { std::slice::from_raw_parts(data as *const c_ulong, count) }
+                        .to_vec(),
+                    incr: actual_type == self.atoms.incr,
+                    ..Property::default()
+                },
+                _ => Property::default(),
+            })
+        };
+
+        if !data.is_null() {
+            // SAFETY: `data` was allocated by Xlib in XGetWindowProperty.
+            unsafe { xlib::XFree(data.cast()) };
+        }
+        result
+    }
+
+    /// Lists the targets we serve `content` as, with the bytes for each.
+    fn offers_for(
+        &self,
+        content: &ClipboardContent,
+    ) -> Result<Vec<(xlib::Atom, Vec<u8>)>, ClipboardError> {
+        let text_targets = [
+            self.atoms.utf8_string,
+            self.atoms.text_plain_utf8,
+            xlib::XA_STRING,
+        ];
+        let text_offers = |text: &[u8]| -> Vec<(xlib::Atom, Vec<u8>)> {
+            text_targets
+                .iter()
+                .map(|&target| (target, text.to_vec()))
+                .collect()
+        };
+        Ok(match content.format {
+            ClipboardFormat::Utf8Text => text_offers(&content.data),
+            ClipboardFormat::Html => {
+                let html = HtmlClipboard::from_content(content)?;
+                let mut offers = vec![(self.atoms.html, html.html.into_bytes())];
+                offers.extend(text_offers(html.text.as_bytes()));
+                offers
+            }
+            ClipboardFormat::Image => {
+                inspect_png(&content.data)?;
+                vec![(self.atoms.png, content.data.clone())]
+            }
+        })
+    }
+
+    fn write(&mut self, content: ClipboardContent) -> Result<(), ClipboardError> {
+        let offers = self.offers_for(&content)?;
+        self.owned = Some(Owned { content, offers });
+        // SAFETY: `display` and `window` are valid for the worker's lifetime.
+        let owner = unsafe {
+            xlib::XSetSelectionOwner(
+                self.display,
+                self.atoms.clipboard,
+                self.window,
+                xlib::CurrentTime,
+            );
+            xlib::XFlush(self.display);
+            xlib::XGetSelectionOwner(self.display, self.atoms.clipboard)
+        };
+        if owner != self.window {
+            self.owned = None;
+            return Err(ClipboardError::Unavailable(
+                "could not take ownership of the CLIPBOARD selection".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `text/html` selection data.  Most applications send UTF-8, but
+/// some (older Firefox and Chromium builds) send UTF-16 with a byte-order
+/// mark.
+fn decode_html_target(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|b| from([b[0], b[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    let html = match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    };
+    html.trim_end_matches('\0').to_string()
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // SAFETY: both were created in `open` and are not used afterwards.
+        unsafe {
+            xlib::XDestroyWindow(self.display, self.window);
+            xlib::XCloseDisplay(self.display);
+        }
+    }
+}
+
+/// X error handler that ignores the error instead of exiting the process.
+unsafe extern "C" fn ignore_x_error(
+    _display: *mut xlib::Display,
+    _event: *mut xlib::XErrorEvent,
+) -> c_int {
+    0
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// If a DISPLAY is available, text written must read back; otherwise
+    /// opening must fail cleanly.
+    #[test]
+    fn test_x11_clipboard_round_trip_when_display_available() {
+        let provider = match X11ClipboardProvider::open() {
+            Ok(provider) => provider,
+            Err(e) => {
+                assert!(
+                    std::env::var("DISPLAY").is_err(),
+                    "open must succeed when DISPLAY is set: {e}"
+                );
+                return;
+            }
+        };
+
+        provider
+            .write(&ClipboardContent::text("kvm clipboard test"))
+            .unwrap();
+
+        assert_eq!(
+            provider.read().unwrap(),
+            Some(ClipboardContent::text("kvm clipboard test"))
+        );
+    }
+
+    #[test]
+    fn test_x11_clipboard_refuses_malformed_images() {
+        let Ok(provider) = X11ClipboardProvider::open() else {
+            return;
+        };
+
+        let result = provider.write(&ClipboardContent {
+            format: ClipboardFormat::Image,
+            data: vec![0x89, b'P', b'N', b'G'],
+        });
+
+        assert!(matches!(result, Err(ClipboardError::Malformed { .. })));
+    }
+
+    /// A second display connection reads what the first one owns, so the
+    /// content really goes through the X server's selection conversion.
+    #[test]
+    fn test_x11_rich_formats_cross_display_connections() {
+        let (Ok(owner), Ok(reader)) = (X11ClipboardProvider::open(), X11ClipboardProvider::open())
+        else {
+            return;
+        };
+        let html = HtmlClipboard {
+            html: "<b>bold</b>".to_string(),
+            text: "bold".to_string(),
+        }
+        .to_content();
+        let image = ClipboardContent::image(
+            &kvm_core::clipboard::RgbaImage::new(2, 1, vec![1, 2, 3, 255, 4, 5, 6, 128]).unwrap(),
+        );
+
+        owner.write(&html).unwrap();
+        let read_html = reader.read().unwrap();
+        owner.write(&image).unwrap();
+        let read_image = reader.read().unwrap();
+
+        assert_eq!(read_html, Some(html));
+        assert_eq!(read_image, Some(image));
+    }
+
+    #[test]
+    fn test_utf16_html_is_decoded() {
+        let bytes = [0xFF, 0xFE, b'<', 0, b'b', 0, b'>', 0, 0, 0];
+
+        assert_eq!(decode_html_target(&bytes), "<b>");
+    }
+}
@@ -0,0 +1,12 @@
+//! Platform-specific clipboard providers.
+//!
+//! Each module implements `kvm_core::clipboard::ClipboardProvider` for one
+//! OS and is selected at compile time, like the input emulators.  Tests use
+//! `kvm_core::clipboard::mock::MockClipboardProvider` instead.
+//!
+//! | Module  | OS    | API used                                             |
+//! |---------|-------|------------------------------------------------------|
+//! | `linux` | Linux | X11 `CLIPBOARD` selection (`XConvertSelection`, …)   |
+
+#[cfg(target_os = "linux")]
+pub mod linux;
@@ -17,6 +17,7 @@
 //! |-----------|----------|------------------------------------------------|
 //! | `windows` | Windows  | `SendInput` Win32 API                          |
 //! | `linux`   | Linux    | X11 XTest extension (`XTestFakeKeyEvent`)      |
+//! | `uinput`  | Linux    | Kernel virtual devices (`/dev/uinput`)         |
 //! | `macos`   | macOS    | CoreGraphics (`CGEventCreateKeyboardEvent`)    |
 //! | `mock`    | any      | Records events in a `Vec` (for tests)          |
 
@@ -28,5 +29,8 @@ pub mod windows;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+#[cfg(target_os = "linux")]
+pub mod uinput;
+
 #[cfg(target_os = "macos")]
 pub mod macos;
@@ -0,0 +1,668 @@
+//! Linux input emulation through `/dev/uinput` virtual devices.
+//!
+//! Creates two kernel-level virtual devices and writes evdev events to them:
+//!
+//! - a **keyboard** that can press every key in [`KeyMapper::hid_to_linux_evdev`],
+//! - an **absolute pointer** whose `ABS_X`/`ABS_Y` axes span the bounding box
+//!   of the client's monitors, with five buttons and a (high-resolution)
+//!   scroll wheel.
+//!
+//! # Why uinput instead of XTest? (for beginners)
+//!
+//! XTest injects events into one X11 server, so it does nothing under
+//! Wayland.  uinput works a level lower: the kernel presents our virtual
+//! devices to the system exactly like plugged-in hardware, and whatever sits
+//! on top (X11, any Wayland compositor, even a text console) reads them
+//! through libinput as usual.
+//!
+//! # The event stream
+//!
+//! Each write to a uinput device is a sequence of `struct input_event`
+//! records, terminated by a `SYN_REPORT` that tells readers "this group of
+//! changes belongs together":
+//!
+//! ```text
+//! key A down:    EV_KEY KEY_A 1,  EV_SYN SYN_REPORT 0
+//! move to (x,y): EV_ABS ABS_X x,  EV_ABS ABS_Y y,  EV_SYN SYN_REPORT 0
+//! ```
+//!
+//! [`UinputEmulator`] is generic over the device writer, so tests substitute
+//! an in-memory buffer and check those bytes directly.
+//!
+//! # Absolute coordinates
+//!
+//! The protocol sends cursor positions in the client's desktop coordinates,
+//! where a monitor left of the primary has a negative `x_offset`.  The axes
+//! start at 0, so positions are shifted by the top-left corner of the
+//! monitors' bounding box ([`PointerBounds`]) and clamped to it.  The
+//! compositor maps the full axis range onto its whole output layout.
+//!
+//! # Permissions
+//!
+//! `/dev/uinput` is normally writable by root only.  Grant the user access
+//! with a udev rule, e.g. `KERNEL=="uinput", GROUP="input", MODE="0660"`,
+//! and add the user to the `input` group.  Without it [`UinputEmulator::open`]
+//! fails with a `Platform` error.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::mem::size_of;
+use std::os::fd::AsRawFd;
+use std::os::raw::{c_int, c_long};
+use std::sync::Mutex;
+
+use kvm_core::{
+    keymap::{hid::HidKeyCode, KeyMapper},
+    protocol::messages::{ModifierFlags, MouseButton, ScreenInfoMessage},
+};
+
+use crate::application::emulate_input::{EmulationError, PlatformInputEmulator};
+
+// ── evdev constants (linux/input-event-codes.h) ───────────────────────────────
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0x00;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+const REL_WHEEL_HI_RES: u16 = 0x0B;
+const REL_HWHEEL_HI_RES: u16 = 0x0C;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BTN_SIDE: u16 = 0x113;
+const BTN_EXTRA: u16 = 0x114;
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// One scroll-wheel notch, in both the protocol's and `REL_*_HI_RES` units.
+const WHEEL_DELTA: i16 = 120;
+
+/// Size of `struct input_event`: a `struct timeval` (two C longs), then
+/// type, code and value.
+pub const INPUT_EVENT_SIZE: usize = 2 * size_of::<c_long>() + 8;
+
+// ── uinput ioctls (linux/uinput.h) ────────────────────────────────────────────
+
+const UINPUT_MAX_NAME_SIZE: usize = 80;
+
+#[repr(C)]
+struct InputId {
+    bustype: u16,
+    vendor: u16,
+    product: u16,
+    version: u16,
+}
+
+#[repr(C)]
+struct UinputSetup {
+    id: InputId,
+    name: [u8; UINPUT_MAX_NAME_SIZE],
+    ff_effects_max: u32,
+}
+
+#[repr(C)]
+struct InputAbsinfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+#[repr(C)]
+struct UinputAbsSetup {
+    code: u16,
+    absinfo: InputAbsinfo,
+}
+
+/// `_IO('U', nr)`.
+const fn io(nr: u32) -> u32 {
+    (b'U' as u32) << 8 | nr
+}
+
+/// `_IOW('U', nr, T)` for a `T` of `size` bytes.
+const fn iow(nr: u32, size: usize) -> u32 {
+    1 << 30 | (size as u32) << 16 | io(nr)
+}
+
+const UI_DEV_CREATE: u32 = io(1);
+const UI_DEV_DESTROY: u32 = io(2);
+const UI_DEV_SETUP: u32 = iow(3, size_of::<UinputSetup>());
+const UI_ABS_SETUP: u32 = iow(4, size_of::<UinputAbsSetup>());
+const UI_SET_EVBIT: u32 = iow(100, size_of::<c_int>());
+const UI_SET_KEYBIT: u32 = iow(101, size_of::<c_int>());
+const UI_SET_RELBIT: u32 = iow(102, size_of::<c_int>());
+const UI_SET_ABSBIT: u32 = iow(103, size_of::<c_int>());
+
+// ── Pointer bounds ────────────────────────────────────────────────────────────
+
+/// The desktop area the absolute pointer covers: the bounding box of all
+/// monitors, in client desktop coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerBounds {
+    /// Left edge (the smallest monitor `x_offset`).
+    pub x: i32,
+    /// Top edge (the smallest monitor `y_offset`).
+    pub y: i32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+impl PointerBounds {
+    /// The bounding box of the monitors in `screen`, or `None` if it has no
+    /// monitor with a non-zero size.
+    pub fn from_screen_info(screen: &ScreenInfoMessage) -> Option<Self> {
+        let monitors = screen
+            .monitors
+            .iter()
+            .filter(|m| m.width > 0 && m.height > 0);
+        let (mut left, mut top) = (i64::MAX, i64::MAX);
+        let (mut right, mut bottom) = (i64::MIN, i64::MIN);
+        for m in monitors {
+            left = left.min(i64::from(m.x_offset));
+            top = top.min(i64::from(m.y_offset));
+            right = right.max(i64::from(m.x_offset) + i64::from(m.width));
+            bottom = bottom.max(i64::from(m.y_offset) + i64::from(m.height));
+        }
+        if left > right {
+            return None;
+        }
+        Some(Self {
+            x: left as i32,
+            y: top as i32,
+            width: u32::try_from(right - left).unwrap_or(u32::MAX),
+            height: u32::try_from(bottom - top).unwrap_or(u32::MAX),
+        })
+    }
+
+    /// Largest `ABS_X` value.
+    fn max_x(&self) -> i32 {
+        i32::try_from(self.width.saturating_sub(1)).unwrap_or(i32::MAX)
+    }
+
+    /// Largest `ABS_Y` value.
+    fn max_y(&self) -> i32 {
+        i32::try_from(self.height.saturating_sub(1)).unwrap_or(i32::MAX)
+    }
+
+    /// Converts a desktop position to axis values, clamped to the bounds.
+    fn to_axes(self, x: i32, y: i32) -> (i32, i32) {
+        let ax = (i64::from(x) - i64::from(self.x)).clamp(0, i64::from(self.max_x()));
+        let ay = (i64::from(y) - i64::from(self.y)).clamp(0, i64::from(self.max_y()));
+        (ax as i32, ay as i32)
+    }
+}
+
+// ── uinput device ─────────────────────────────────────────────────────────────
+
+/// A virtual input device created through `/dev/uinput`.  The kernel removes
+/// it when this is dropped.
+pub struct UinputDevice {
+    file: File,
+}
+
+impl UinputDevice {
+    /// Creates a device named `name` that can press `keys` and, if `pointer`
+    /// is given, also has absolute axes over it and scroll wheels.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if `/dev/uinput` cannot be opened (usually a
+    /// permissions problem) or an ioctl is refused.
+    pub fn create(name: &str, keys: &[u16], pointer: Option<PointerBounds>) -> io::Result<Self> {
+        let file = OpenOptions::new().write(true).open("/dev/uinput")?;
+        // From here on, dropping `device` destroys whatever was created.
+        let device = Self { file };
+
+        device.set_bit(UI_SET_EVBIT, EV_KEY)?;
+        for &key in keys {
+            device.set_bit(UI_SET_KEYBIT, key)?;
+        }
+        if let Some(bounds) = pointer {
+            device.set_bit(UI_SET_EVBIT, EV_ABS)?;
+            for (axis, maximum) in [(ABS_X, bounds.max_x()), (ABS_Y, bounds.max_y())] {
+                device.set_bit(UI_SET_ABSBIT, axis)?;
+                let setup = UinputAbsSetup {
+                    code: axis,
+                    absinfo: InputAbsinfo {
+                        value: 0,
+                        minimum: 0,
+                        maximum,
+                        fuzz: 0,
+                        flat: 0,
+                        resolution: 0,
+                    },
+                };
+                device.ioctl(UI_ABS_SETUP, &setup as *const _ as libc::c_ulong)?;
+            }
+            device.set_bit(UI_SET_EVBIT, EV_REL)?;
+            for axis in [REL_WHEEL, REL_HWHEEL, REL_WHEEL_HI_RES, REL_HWHEEL_HI_RES] {
+                device.set_bit(UI_SET_RELBIT, axis)?;
+            }
+        }
+
+        let mut setup = UinputSetup {
+            id: InputId {
+                bustype: BUS_VIRTUAL,
+                vendor: 0,
+                product: 0,
+                version: 1,
+            },
+            name: [0; UINPUT_MAX_NAME_SIZE],
+            ff_effects_max: 0,
+        };
+        // Leave at least one NUL terminator.
+        let len = name.len().min(UINPUT_MAX_NAME_SIZE - 1);
+        setup.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        device.ioctl(UI_DEV_SETUP, &setup as *const _ as libc::c_ulong)?;
+        device.ioctl(UI_DEV_CREATE, 0)?;
+        Ok(device)
+    }
+
+    fn set_bit(&self, request: u32, bit: u16) -> io::Result<()> {
+        self.ioctl(request, libc::c_ulong::from(bit))
+    }
+
+    fn ioctl(&self, request: u32, arg: libc::c_ulong) -> io::Result<()> {
+        // SAFETY: the fd is open for the lifetime of `self`, and every request
+        // used here takes either an integer or a pointer to a live `repr(C)`
+        // struct of the size encoded in the request number.
+        let rc = unsafe { libc::ioctl(self.file.as_raw_fd(), request as libc::Ioctl, arg) };
+        if rc < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Write for UinputDevice {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        // Fails harmlessly if the device was never created.
+        let _ = self.ioctl(UI_DEV_DESTROY, 0);
+    }
+}
+
+// ── Emulator ──────────────────────────────────────────────────────────────────
+
+/// Linux input emulator writing evdev events to a virtual keyboard and a
+/// virtual absolute pointer.
+///
+/// `W` is [`UinputDevice`] in production; tests use an in-memory writer.
+pub struct UinputEmulator<W = UinputDevice> {
+    keyboard: Mutex<W>,
+    pointer: Mutex<W>,
+    bounds: PointerBounds,
+}
+
+impl UinputEmulator<UinputDevice> {
+    /// Creates the virtual devices, sizing the pointer axes from the
+    /// monitors in `screen`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EmulationError::Platform` if `screen` has no monitors or the
+    /// devices cannot be created (see the module docs on permissions).
+    pub fn open(screen: &ScreenInfoMessage) -> Result<Self, EmulationError> {
+        let bounds = PointerBounds::from_screen_info(screen)
+            .ok_or_else(|| EmulationError::Platform("no monitors to size the pointer".into()))?;
+        let keys: Vec<u16> = (0..=u16::from(u8::MAX))
+            .filter_map(|v| KeyMapper::hid_to_linux_evdev(HidKeyCode::from_u16(v)))
+            .collect();
+        let buttons = [BTN_LEFT, BTN_RIGHT, BTN_MIDDLE, BTN_SIDE, BTN_EXTRA];
+        let keyboard = UinputDevice::create("KVM-Over-IP keyboard", &keys, None)
+            .map_err(|e| EmulationError::Platform(format!("uinput keyboard: {e}")))?;
+        let pointer = UinputDevice::create("KVM-Over-IP pointer", &buttons, Some(bounds))
+            .map_err(|e| EmulationError::Platform(format!("uinput pointer: {e}")))?;
+        Ok(Self::with_devices(keyboard, pointer, bounds))
+    }
+}
+
+impl<W: Write + Send> UinputEmulator<W> {
+    /// Wraps already-created devices.  `bounds` must match the pointer's axes.
+    pub fn with_devices(keyboard: W, pointer: W, bounds: PointerBounds) -> Self {
+        Self {
+            keyboard: Mutex::new(keyboard),
+            pointer: Mutex::new(pointer),
+            bounds,
+        }
+    }
+
+    /// Writes `events` and a closing `SYN_REPORT` to `device` in one write.
+    fn report(device: &Mutex<W>, events: &[(u16, u16, i32)]) -> Result<(), EmulationError> {
+        let mut buf = Vec::with_capacity((events.len() + 1) * INPUT_EVENT_SIZE);
+        for &(kind, code, value) in events {
+            push_event(&mut buf, kind, code, value);
+        }
+        push_event(&mut buf, EV_SYN, SYN_REPORT, 0);
+        device
+            .lock()
+            .map_err(|_| EmulationError::Platform("uinput device lock poisoned".into()))?
+            .write_all(&buf)
+            .map_err(|e| EmulationError::Platform(format!("uinput write failed: {e}")))
+    }
+
+    fn emit_key(&self, key: HidKeyCode, pressed: bool) -> Result<(), EmulationError> {
+        let code = KeyMapper::hid_to_linux_evdev(key).ok_or(EmulationError::InvalidKeyCode(key))?;
+        Self::report(&self.keyboard, &[(EV_KEY, code, i32::from(pressed))])
+    }
+}
+
+/// Appends one `struct input_event` in native byte order.  The kernel stamps
+/// injected events itself, so the time is left zero.
+fn push_event(buf: &mut Vec<u8>, kind: u16, code: u16, value: i32) {
+    buf.resize(buf.len() + 2 * size_of::<c_long>(), 0);
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(&code.to_ne_bytes());
+    buf.extend_from_slice(&value.to_ne_bytes());
+}
+
+/// Legacy wheel notches for a high-resolution delta.  A partial notch still
+/// counts as one, so slow scrolling is not lost on legacy-only readers.
+fn notches(delta: i16) -> i32 {
+    let whole = i32::from(delta / WHEEL_DELTA);
+    if whole == 0 {
+        i32::from(delta.signum())
+    } else {
+        whole
+    }
+}
+
+impl<W: Write + Send> PlatformInputEmulator for UinputEmulator<W> {
+    fn emit_key_down(
+        &self,
+        key: HidKeyCode,
+        _modifiers: ModifierFlags,
+    ) -> Result<(), EmulationError> {
+        self.emit_key(key, true)
+    }
+
+    fn emit_key_up(
+        &self,
+        key: HidKeyCode,
+        _modifiers: ModifierFlags,
+    ) -> Result<(), EmulationError> {
+        self.emit_key(key, false)
+    }
+
+    fn emit_mouse_move(&self, x: i32, y: i32) -> Result<(), EmulationError> {
+        let (ax, ay) = self.bounds.to_axes(x, y);
+        Self::report(&self.pointer, &[(EV_ABS, ABS_X, ax), (EV_ABS, ABS_Y, ay)])
+    }
+
+    fn emit_mouse_button(
+        &self,
+        button: MouseButton,
+        pressed: bool,
+        x: i32,
+        y: i32,
+    ) -> Result<(), EmulationError> {
+        let code = match button {
+            MouseButton::Left => BTN_LEFT,
+            MouseButton::Right => BTN_RIGHT,
+            MouseButton::Middle => BTN_MIDDLE,
+            MouseButton::Button4 => BTN_SIDE,
+            MouseButton::Button5 => BTN_EXTRA,
+        };
+        // Move first so the click lands where the master saw it.
+        let (ax, ay) = self.bounds.to_axes(x, y);
+        Self::report(
+            &self.pointer,
+            &[
+                (EV_ABS, ABS_X, ax),
+                (EV_ABS, ABS_Y, ay),
+                (EV_KEY, code, i32::from(pressed)),
+            ],
+        )
+    }
+
+    fn emit_mouse_scroll(&self, delta_x: i16, delta_y: i16) -> Result<(), EmulationError> {
+        // Positive delta_y scrolls up and positive delta_x right, matching
+        // the sign convention of REL_WHEEL and REL_HWHEEL.
+        let mut events = Vec::with_capacity(4);
+        if delta_y != 0 {
+            events.push((EV_REL, REL_WHEEL_HI_RES, i32::from(delta_y)));
+            events.push((EV_REL, REL_WHEEL, notches(delta_y)));
+        }
+        if delta_x != 0 {
+            events.push((EV_REL, REL_HWHEEL_HI_RES, i32::from(delta_x)));
+            events.push((EV_REL, REL_HWHEEL, notches(delta_x)));
+        }
+        if events.is_empty() {
+            return Ok(());
+        }
+        Self::report(&self.pointer, &events)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use kvm_core::protocol::messages::MonitorInfo;
+
+    use super::*;
+
+    /// An in-memory device whose bytes the test can still read after handing
+    /// it to the emulator.
+    #[derive(Clone, Default)]
+    struct FakeDevice(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for FakeDevice {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl FakeDevice {
+        /// Decodes the written `input_event`s as (type, code, value).
+        fn events(&self) -> Vec<(u16, u16, i32)> {
+            let bytes = self.0.lock().unwrap();
+            assert_eq!(bytes.len() % INPUT_EVENT_SIZE, 0, "partial input_event");
+            bytes
+                .chunks(INPUT_EVENT_SIZE)
+                .map(|e| {
+                    let t = 2 * size_of::<c_long>();
+                    assert!(e[..t].iter().all(|b| *b == 0), "timestamp must be zero");
+                    (
+                        u16::from_ne_bytes([e[t], e[t + 1]]),
+                        u16::from_ne_bytes([e[t + 2], e[t + 3]]),
+                        i32::from_ne_bytes([e[t + 4], e[t + 5], e[t + 6], e[t + 7]]),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    const SYN: (u16, u16, i32) = (EV_SYN, SYN_REPORT, 0);
+
+    fn monitor(x_offset: i32, y_offset: i32, width: u32, height: u32) -> MonitorInfo {
+        MonitorInfo {
+            monitor_id: 0,
+            x_offset,
+            y_offset,
+            width,
+            height,
+            scale_factor: 100,
+            is_primary: x_offset == 0 && y_offset == 0,
+        }
+    }
+
+    /// A 1920x1080 primary with a 1280x1024 monitor to its left.
+    fn two_monitors() -> ScreenInfoMessage {
+        ScreenInfoMessage {
+            monitors: vec![monitor(0, 0, 1920, 1080), monitor(-1280, 0, 1280, 1024)],
+        }
+    }
+
+    fn make_emulator() -> (UinputEmulator<FakeDevice>, FakeDevice, FakeDevice) {
+        let (keyboard, pointer) = (FakeDevice::default(), FakeDevice::default());
+        let bounds = PointerBounds::from_screen_info(&two_monitors()).unwrap();
+        let emulator = UinputEmulator::with_devices(keyboard.clone(), pointer.clone(), bounds);
+        (emulator, keyboard, pointer)
+    }
+
+    #[test]
+    fn test_input_event_has_kernel_layout() {
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(INPUT_EVENT_SIZE, 24);
+        assert_eq!(size_of::<UinputSetup>(), 92);
+        assert_eq!(size_of::<UinputAbsSetup>(), 28);
+        // Values from linux/uinput.h.
+        assert_eq!(UI_DEV_CREATE, 0x5501);
+        assert_eq!(UI_DEV_SETUP, 0x405C_5503);
+        assert_eq!(UI_ABS_SETUP, 0x401C_5504);
+        assert_eq!(UI_SET_KEYBIT, 0x4004_5565);
+    }
+
+    #[test]
+    fn test_key_down_and_up_emit_evdev_code_then_syn() {
+        // Arrange
+        let (emulator, keyboard, pointer) = make_emulator();
+
+        // Act
+        emulator
+            .emit_key_down(HidKeyCode::KeyA, ModifierFlags::default())
+            .unwrap();
+        emulator
+            .emit_key_up(HidKeyCode::KeyA, ModifierFlags::default())
+            .unwrap();
+
+        // Assert – KEY_A is 30.
+        assert_eq!(
+            keyboard.events(),
+            [(EV_KEY, 30, 1), SYN, (EV_KEY, 30, 0), SYN]
+        );
+        assert!(pointer.events().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected_without_writing() {
+        let (emulator, keyboard, _) = make_emulator();
+
+        let result = emulator.emit_key_down(HidKeyCode::Unknown, ModifierFlags::default());
+
+        assert!(matches!(
+            result,
+            Err(EmulationError::InvalidKeyCode(HidKeyCode::Unknown))
+        ));
+        assert!(keyboard.events().is_empty());
+    }
+
+    #[test]
+    fn test_mouse_move_is_shifted_into_axis_range_and_clamped() {
+        // Arrange – the bounding box starts at x = -1280.
+        let (emulator, _, pointer) = make_emulator();
+
+        // Act
+        emulator.emit_mouse_move(-1280, 0).unwrap();
+        emulator.emit_mouse_move(100, 200).unwrap();
+        emulator.emit_mouse_move(5000, -50).unwrap();
+
+        // Assert
+        assert_eq!(
+            pointer.events(),
+            [
+                (EV_ABS, ABS_X, 0),
+                (EV_ABS, ABS_Y, 0),
+                SYN,
+                (EV_ABS, ABS_X, 1380),
+                (EV_ABS, ABS_Y, 200),
+                SYN,
+                (EV_ABS, ABS_X, 3199),
+                (EV_ABS, ABS_Y, 0),
+                SYN,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mouse_button_moves_then_presses() {
+        let (emulator, _, pointer) = make_emulator();
+
+        emulator
+            .emit_mouse_button(MouseButton::Button4, true, 10, 20)
+            .unwrap();
+
+        assert_eq!(
+            pointer.events(),
+            [
+                (EV_ABS, ABS_X, 1290),
+                (EV_ABS, ABS_Y, 20),
+                (EV_KEY, BTN_SIDE, 1),
+                SYN,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scroll_emits_hi_res_and_legacy_wheel() {
+        let (emulator, _, pointer) = make_emulator();
+
+        emulator.emit_mouse_scroll(0, -240).unwrap();
+        emulator.emit_mouse_scroll(30, 0).unwrap();
+        emulator.emit_mouse_scroll(0, 0).unwrap();
+
+        assert_eq!(
+            pointer.events(),
+            [
+                (EV_REL, REL_WHEEL_HI_RES, -240),
+                (EV_REL, REL_WHEEL, -2),
+                SYN,
+                (EV_REL, REL_HWHEEL_HI_RES, 30),
+                (EV_REL, REL_HWHEEL, 1),
+                SYN,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pointer_bounds_span_all_monitors() {
+        // Arrange – a portrait monitor above and right of the primary.
+        let screen = ScreenInfoMessage {
+            monitors: vec![monitor(0, 0, 1920, 1080), monitor(1920, -400, 1080, 1920)],
+        };
+
+        // Act
+        let bounds = PointerBounds::from_screen_info(&screen).unwrap();
+
+        // Assert
+        assert_eq!(
+            bounds,
+            PointerBounds {
+                x: 0,
+                y: -400,
+                width: 3000,
+                height: 1920,
+            }
+        );
+        assert_eq!(
+            PointerBounds::from_screen_info(&ScreenInfoMessage { monitors: vec![] }),
+            None
+        );
+    }
+}
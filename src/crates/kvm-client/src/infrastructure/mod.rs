@@ -8,6 +8,9 @@
 //!
 //! # Sub-modules
 //!
+//! - **`clipboard`** – OS-specific implementations of `ClipboardProvider`.  On
+//!   Linux it owns and converts X11 selections.
+//!
 //! - **`input_emulation`** – OS-specific implementations of `PlatformInputEmulator`.
 //!   The correct implementation is selected at compile time using `#[cfg(target_os)]`.
 //!   A `MockInputEmulator` is also provided for tests.
@@ -19,10 +22,16 @@
 //! - **`screen_info`** – OS-specific monitor enumeration.  On Windows it calls
 //!   `EnumDisplayMonitors`; on Linux it queries Xlib; on macOS it uses `CGDisplay`.
 //!
+//! - **`storage`** – The client's TOML config file: its persistent
+//!   `client_id`, the master address and pinned certificate, and reconnect
+//!   settings.
+//!
 //! - **`ui_bridge`** – Tauri command handlers that expose client state (connection
 //!   status, settings) to the React UI.
 
+pub mod clipboard;
 pub mod input_emulation;
 pub mod network;
 pub mod screen_info;
+pub mod storage;
 pub mod ui_bridge;
@@ -0,0 +1,216 @@
+//! Connection state machine and reconnect backoff for the control channel.
+//!
+//! [`ClientConnection`](super::ClientConnection) moves through these states
+//! and reports every change as [`NetworkEvent::StateChanged`](super::NetworkEvent::StateChanged):
+//!
+//! ```text
+//!                 ┌──────────────────────────────────────────────┐
+//!                 v                                              │
+//! Disconnected ─> Connecting ─> Handshaking ─> Active <─> Pairing │
+//!                 │             │              │          │      │
+//!                 └─────────────┴──────────────┴──────────┴─> Backoff
+//! ```
+//!
+//! - `Connecting`: TCP connect and, if configured, the TLS handshake.
+//! - `Handshaking`: `Hello` sent, waiting for the master's `HelloAck`.
+//! - `Active`: the master accepted us; input may arrive.
+//! - `Pairing`: the master sent a `PairingRequest`; back to `Active` once it
+//!   sends `PairingConfirm`.
+//! - `Backoff`: the connection failed or dropped; waiting before the next
+//!   attempt.
+//!
+//! Any state may go to `Disconnected` when the client shuts down.
+//!
+//! # Backoff (for beginners)
+//!
+//! Retrying a dead master at a fixed interval forever wastes effort, and when
+//! a master restarts, every client retrying on the same beat hits it at the
+//! same moment.  [`Backoff`] doubles the wait after each failed attempt, up
+//! to [`BackoffPolicy::max`], and picks a random point in the upper half of
+//! that wait ("jitter") so clients spread out.  Once a session is accepted
+//! the wait starts again from [`BackoffPolicy::initial`].
+
+use std::time::Duration;
+
+/// Where the control-channel connection is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Not connected and not trying to (before start and after shutdown).
+    Disconnected,
+    /// TCP connect (and TLS handshake) in progress.
+    Connecting,
+    /// `Hello` sent; waiting for the `HelloAck`.
+    Handshaking,
+    /// A `PairingRequest` from the master is pending.
+    Pairing,
+    /// Accepted by the master and ready to receive input.
+    Active,
+    /// Waiting before the next connection attempt.
+    Backoff,
+}
+
+impl ConnectionState {
+    /// Whether the state machine allows moving from `self` to `next`.
+    pub fn can_transition_to(self, next: ConnectionState) -> bool {
+        use ConnectionState::*;
+        matches!(
+            (self, next),
+            (_, Disconnected)
+                | (Disconnected | Backoff, Connecting)
+                | (Connecting, Handshaking)
+                | (Handshaking | Pairing, Active)
+                | (Handshaking | Active, Pairing)
+                | (Connecting | Handshaking | Pairing | Active, Backoff)
+        )
+    }
+}
+
+/// Capped exponential backoff settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackoffPolicy {
+    /// Wait before the first retry.
+    pub initial: Duration,
+    /// Upper bound for the wait, however many attempts have failed.
+    pub max: Duration,
+}
+
+impl BackoffPolicy {
+    /// The wait before retry number `attempt` (0-based), with `jitter` in
+    /// `[0, 1]` choosing a point between half the capped delay and all of it.
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let capped = self
+            .initial
+            .checked_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .unwrap_or(self.max)
+            .min(self.max);
+        let half = capped / 2;
+        half + half.mul_f64(jitter.clamp(0.0, 1.0))
+    }
+}
+
+/// Tracks failed attempts and hands out the next wait under a [`BackoffPolicy`].
+#[derive(Debug)]
+pub struct Backoff {
+    policy: BackoffPolicy,
+    attempt: u32,
+}
+
+impl Backoff {
+    /// Creates a backoff with no failed attempts.
+    pub fn new(policy: BackoffPolicy) -> Self {
+        Self { policy, attempt: 0 }
+    }
+
+    /// Returns the wait before the next attempt and counts this one as failed.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.policy.delay(self.attempt, random_unit());
+        self.attempt = self.attempt.saturating_add(1);
+        delay
+    }
+
+    /// Starts again from the initial wait, after a successful session.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Number of consecutive failed attempts.
+    pub fn attempts(&self) -> u32 {
+        self.attempt
+    }
+}
+
+/// A uniformly random value in `[0, 1]` from the OS generator.
+fn random_unit() -> f64 {
+    let mut bytes = [0u8; 4];
+    getrandom::fill(&mut bytes).expect("OS random number generator unavailable");
+    f64::from(u32::from_le_bytes(bytes)) / f64::from(u32::MAX)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: BackoffPolicy = BackoffPolicy {
+        initial: Duration::from_secs(1),
+        max: Duration::from_secs(30),
+    };
+
+    #[test]
+    fn test_delay_doubles_per_attempt_until_capped() {
+        // Arrange / Act – full jitter yields the whole capped delay.
+        let delays: Vec<Duration> = (0..8).map(|n| POLICY.delay(n, 1.0)).collect();
+
+        // Assert
+        assert_eq!(
+            delays,
+            [1, 2, 4, 8, 16, 30, 30, 30].map(Duration::from_secs)
+        );
+        assert_eq!(POLICY.delay(u32::MAX, 1.0), POLICY.max);
+    }
+
+    #[test]
+    fn test_delay_jitter_stays_in_upper_half() {
+        assert_eq!(POLICY.delay(3, 0.0), Duration::from_secs(4));
+        assert_eq!(POLICY.delay(3, 0.5), Duration::from_secs(6));
+        assert_eq!(POLICY.delay(3, 7.0), Duration::from_secs(8), "clamped");
+    }
+
+    #[test]
+    fn test_backoff_grows_and_resets() {
+        // Arrange
+        let mut backoff = Backoff::new(POLICY);
+
+        // Act
+        let first = backoff.next_delay();
+        let later: Vec<Duration> = (0..10).map(|_| backoff.next_delay()).collect();
+
+        // Assert
+        assert!(first >= POLICY.initial / 2 && first <= POLICY.initial);
+        assert!(later.iter().all(|d| *d <= POLICY.max));
+        assert!(later[9] >= POLICY.max / 2);
+        assert_eq!(backoff.attempts(), 11);
+        backoff.reset();
+        assert_eq!(backoff.attempts(), 0);
+    }
+
+    #[test]
+    fn test_state_machine_allows_the_documented_lifecycle() {
+        use ConnectionState::*;
+        let path = [
+            Disconnected,
+            Connecting,
+            Handshaking,
+            Active,
+            Pairing,
+            Active,
+            Backoff,
+            Connecting,
+            Backoff,
+            Connecting,
+            Handshaking,
+            Pairing,
+            Backoff,
+            Disconnected,
+        ];
+        for pair in path.windows(2) {
+            assert!(
+                pair[0].can_transition_to(pair[1]),
+                "{:?} -> {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_state_machine_rejects_skipped_steps() {
+        use ConnectionState::*;
+        assert!(!Disconnected.can_transition_to(Active));
+        assert!(!Connecting.can_transition_to(Active));
+        assert!(!Backoff.can_transition_to(Handshaking));
+        assert!(!Disconnected.can_transition_to(Backoff));
+        assert!(!Active.can_transition_to(Connecting));
+    }
+}
@@ -0,0 +1,410 @@
+//! Finding the master on the LAN, with `Announce` broadcasts or by browsing
+//! for its mDNS / DNS-SD advertisement.
+//!
+//! # How discovery works (for beginners)
+//!
+//! The client does not need to know the master's IP address in advance.  It
+//! shouts into the local network and waits for an answer:
+//!
+//! ```text
+//! Client                                        Master(s)
+//!   |--- ANNOUNCE (UDP broadcast :24802) ------->|   every resend_interval,
+//!   |<-- ANNOUNCE_RESPONSE (unicast) ------------|   until the timeout
+//!   |                                            |
+//!   └─ control address = response source IP + master_control_port
+//! ```
+//!
+//! UDP is unreliable, so the `Announce` is repeated a few times while the
+//! client listens; duplicate answers from the same master are merged.  The
+//! source address of the response tells the client *where* the master is,
+//! and the response itself tells it *which port* to connect to and whether
+//! the master already knows this client.
+//!
+//! # Browsing with mDNS
+//!
+//! With [`DiscoveryBackend::Mdns`] the client instead asks the LAN for
+//! `_kvm-over-ip._tcp` services and reads the control port from each
+//! answer's TXT record (see `kvm_core::protocol::discovery`).  Masters whose
+//! protocol versions do not overlap with ours are skipped.  An mDNS answer is
+//! the same for every client, so `already_paired` is always `false`.
+//!
+//! # Several masters
+//!
+//! A LAN can have more than one master.  [`choose_master`] prefers one that
+//! reports `already_paired` (it will accept the client without a PIN), and
+//! otherwise picks the only master that answered.  When several unpaired
+//! masters answer there is no right guess, so it returns `None` and the
+//! caller should offer the list to the user.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use kvm_core::protocol::codec::{decode_message, encode_message};
+use kvm_core::protocol::discovery::{DiscoveryBackend, MasterTxt, SERVICE_TYPE};
+use kvm_core::protocol::messages::{AnnounceMessage, KvmMessage};
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent, ServiceInfo};
+use thiserror::Error;
+use tokio::net::UdpSocket;
+use tokio::time::{self, Instant};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::{current_timestamp_us, native_platform_id};
+
+/// UDP port the master's discovery responder listens on.
+pub const DEFAULT_DISCOVERY_PORT: u16 = 24802;
+
+/// Errors that stop a discovery round.
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    /// The UDP socket could not be set up.
+    #[error("discovery socket error: {0}")]
+    Socket(#[from] std::io::Error),
+    /// The `Announce` could not be encoded.
+    #[error("failed to encode Announce: {0}")]
+    Encode(String),
+    /// The mDNS daemon could not be started or refused the browse.
+    #[error("mDNS error: {0}")]
+    Mdns(#[from] mdns_sd::Error),
+}
+
+/// Settings for one discovery round.
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+    /// This client's UUID, so masters can tell whether it is paired.
+    pub client_id: Uuid,
+    /// Human-readable name shown in the master's client list.
+    pub client_name: String,
+    /// Where to send the `Announce`.  Defaults to the limited broadcast
+    /// address on [`DEFAULT_DISCOVERY_PORT`]; tests use a loopback address.
+    pub targets: Vec<SocketAddr>,
+    /// How long to collect answers.
+    pub timeout: Duration,
+    /// How often the `Announce` is repeated while waiting.
+    pub resend_interval: Duration,
+    /// Broadcast `Announce`s or browse mDNS.  `targets` and
+    /// `resend_interval` only apply to the broadcast backend.
+    pub backend: DiscoveryBackend,
+    /// Let the mDNS browse use loopback interfaces too, for a master on the
+    /// same machine.
+    pub include_loopback: bool,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            client_id: Uuid::nil(),
+            client_name: "kvm-client".to_string(),
+            targets: vec![SocketAddr::from((
+                Ipv4Addr::BROADCAST,
+                DEFAULT_DISCOVERY_PORT,
+            ))],
+            timeout: Duration::from_secs(2),
+            resend_interval: Duration::from_millis(500),
+            backend: DiscoveryBackend::Broadcast,
+            include_loopback: false,
+        }
+    }
+}
+
+/// A master that answered an `Announce` or was found by an mDNS browse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredMaster {
+    /// Address of the master's TCP control port; use it as
+    /// `ClientConnectionConfig::master_addr`.
+    pub control_addr: SocketAddr,
+    /// Whether the master has already paired with this client.
+    pub already_paired: bool,
+}
+
+/// Collects the masters found within `config.timeout` with the configured
+/// backend, in the order they were first found.
+///
+/// An empty list means no master answered; it is not an error.
+///
+/// # Errors
+///
+/// Returns [`DiscoveryError::Socket`] if the socket cannot be bound or the
+/// `Announce` cannot be sent to any target, and [`DiscoveryError::Mdns`] if
+/// the mDNS daemon cannot start.
+pub async fn discover_masters(
+    config: &DiscoveryConfig,
+) -> Result<Vec<DiscoveredMaster>, DiscoveryError> {
+    match config.backend {
+        DiscoveryBackend::Broadcast => broadcast_discovery(config).await,
+        DiscoveryBackend::Mdns => {
+            let (timeout, include_loopback) = (config.timeout, config.include_loopback);
+            tokio::task::spawn_blocking(move || browse_mdns(timeout, include_loopback))
+                .await
+                .expect("mDNS browse task panicked")
+        }
+    }
+}
+
+/// Broadcasts `Announce` and collects the answers.
+async fn broadcast_discovery(
+    config: &DiscoveryConfig,
+) -> Result<Vec<DiscoveredMaster>, DiscoveryError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_broadcast(true)?;
+
+    let announce = KvmMessage::Announce(AnnounceMessage {
+        client_id: config.client_id,
+        platform_id: native_platform_id(),
+        // The client accepts no connections; the master dials nobody back.
+        control_port: 0,
+        client_name: config.client_name.clone(),
+    });
+
+    let deadline = Instant::now() + config.timeout;
+    let mut resend = time::interval(config.resend_interval);
+    let mut masters: Vec<DiscoveredMaster> = Vec::new();
+    let mut sequence = 0u64;
+    let mut buf = [0u8; 1024];
+
+    loop {
+        tokio::select! {
+            _ = resend.tick() => {
+                let datagram = encode_message(&announce, sequence, current_timestamp_us())
+                    .map_err(|e| DiscoveryError::Encode(e.to_string()))?;
+                sequence += 1;
+                send_to_targets(&socket, &datagram, &config.targets).await?;
+            }
+            received = socket.recv_from(&mut buf) => {
+                let (len, src) = received?;
+                if let Some(master) = parse_response(&buf[..len], src) {
+                    if !masters.contains(&master) {
+                        info!("master found at {} (paired: {})", master.control_addr, master.already_paired);
+                        masters.push(master);
+                    }
+                }
+            }
+            () = time::sleep_until(deadline) => break,
+        }
+    }
+    Ok(masters)
+}
+
+/// Reads a discovery answer that arrived from `src`.
+fn parse_response(datagram: &[u8], src: SocketAddr) -> Option<DiscoveredMaster> {
+    match decode_message(datagram) {
+        Ok((KvmMessage::AnnounceResponse(response), _)) => Some(DiscoveredMaster {
+            control_addr: SocketAddr::new(src.ip(), response.master_control_port),
+            already_paired: response.already_paired,
+        }),
+        Ok((other, _)) => {
+            debug!(
+                "ignoring {:?} from {src} during discovery",
+                other.message_type()
+            );
+            None
+        }
+        Err(e) => {
+            debug!("undecodable datagram from {src} during discovery: {e}");
+            None
+        }
+    }
+}
+
+/// Browses for advertised masters until `timeout` has passed.
+///
+/// `mdns-sd` delivers its events on a blocking channel, so this runs on a
+/// blocking thread.
+fn browse_mdns(
+    timeout: Duration,
+    include_loopback: bool,
+) -> Result<Vec<DiscoveredMaster>, DiscoveryError> {
+    let daemon = ServiceDaemon::new()?;
+    if include_loopback {
+        daemon.enable_interface(IfKind::LoopbackV4)?;
+    }
+    let events = daemon.browse(SERVICE_TYPE)?;
+
+    let deadline = std::time::Instant::now() + timeout;
+    let mut masters: Vec<DiscoveredMaster> = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        let Ok(event) = events.recv_timeout(remaining) else {
+            break;
+        };
+        if let ServiceEvent::ServiceResolved(info) = event {
+            if let Some(master) = parse_service(&info) {
+                if !masters.contains(&master) {
+                    info!(
+                        "master {} found at {} via mDNS",
+                        info.get_fullname(),
+                        master.control_addr
+                    );
+                    masters.push(master);
+                }
+            }
+        }
+    }
+
+    if let Err(e) = daemon.shutdown() {
+        debug!("mDNS daemon shutdown failed: {e}");
+    }
+    Ok(masters)
+}
+
+/// Reads a resolved DNS-SD service, skipping masters we cannot talk to.
+fn parse_service(info: &ServiceInfo) -> Option<DiscoveredMaster> {
+    let Some(txt) = MasterTxt::from_properties(|key| info.get_property_val_str(key)) else {
+        debug!("ignoring {} with malformed TXT record", info.get_fullname());
+        return None;
+    };
+    if !txt.is_compatible() {
+        warn!(
+            "ignoring {}: protocol versions {}..={} are not supported",
+            info.get_fullname(),
+            txt.min_protocol_version,
+            txt.protocol_version
+        );
+        return None;
+    }
+    let ip = pick_address(info.get_addresses_v4().into_iter().copied())?;
+    Some(DiscoveredMaster {
+        control_addr: SocketAddr::from((ip, txt.control_port)),
+        already_paired: false,
+    })
+}
+
+/// Picks the IPv4 address to dial from those a master advertised, preferring
+/// a LAN address over loopback.  (The master binds `0.0.0.0` by default, so
+/// IPv6 addresses are not considered.)
+fn pick_address(addresses: impl Iterator<Item = Ipv4Addr>) -> Option<Ipv4Addr> {
+    addresses.min_by_key(Ipv4Addr::is_loopback)
+}
+
+/// Sends `datagram` to every target, failing only if no send succeeded.
+///
+/// A machine without a broadcast route rejects the broadcast target, which
+/// must not stop a unicast target from being tried.
+async fn send_to_targets(
+    socket: &UdpSocket,
+    datagram: &[u8],
+    targets: &[SocketAddr],
+) -> Result<(), DiscoveryError> {
+    let mut last_error = None;
+    let mut sent = false;
+    for target in targets {
+        match socket.send_to(datagram, target).await {
+            Ok(_) => sent = true,
+            Err(e) => {
+                debug!("Announce to {target} failed: {e}");
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if !sent => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Picks the master to connect to from a discovery result.
+///
+/// Returns the first master that has already paired with this client, or
+/// else the only master found.  Returns `None` when nothing was found or when
+/// several unpaired masters answered and the user should choose.
+pub fn choose_master(found: &[DiscoveredMaster]) -> Option<&DiscoveredMaster> {
+    found.iter().find(|m| m.already_paired).or(match found {
+        [only] => Some(only),
+        _ => None,
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvm_core::protocol::messages::AnnounceResponseMessage;
+
+    fn master(addr: &str, already_paired: bool) -> DiscoveredMaster {
+        DiscoveredMaster {
+            control_addr: addr.parse().unwrap(),
+            already_paired,
+        }
+    }
+
+    #[test]
+    fn test_pick_address_prefers_lan_over_loopback() {
+        let lan = Ipv4Addr::new(192, 168, 1, 5);
+
+        assert_eq!(
+            pick_address([Ipv4Addr::LOCALHOST, lan].into_iter()),
+            Some(lan)
+        );
+        assert_eq!(
+            pick_address([Ipv4Addr::LOCALHOST].into_iter()),
+            Some(Ipv4Addr::LOCALHOST)
+        );
+        assert_eq!(pick_address(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_choose_master_prefers_a_paired_master() {
+        let found = [
+            master("192.168.1.2:24800", false),
+            master("192.168.1.3:24800", true),
+        ];
+
+        assert_eq!(choose_master(&found), Some(&found[1]));
+    }
+
+    #[test]
+    fn test_choose_master_takes_the_only_master() {
+        let found = [master("192.168.1.2:24800", false)];
+
+        assert_eq!(choose_master(&found), Some(&found[0]));
+    }
+
+    #[test]
+    fn test_choose_master_leaves_several_unpaired_masters_to_the_user() {
+        let found = [
+            master("192.168.1.2:24800", false),
+            master("192.168.1.3:24800", false),
+        ];
+
+        assert_eq!(choose_master(&found), None);
+        assert_eq!(choose_master(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_answers_from_one_master_are_merged() {
+        // Arrange – a fake master that answers every Announce
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = responder.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            while let Ok((len, src)) = responder.recv_from(&mut buf).await {
+                let Ok((KvmMessage::Announce(_), _)) = decode_message(&buf[..len]) else {
+                    continue;
+                };
+                let reply = KvmMessage::AnnounceResponse(AnnounceResponseMessage {
+                    master_control_port: 4000,
+                    already_paired: true,
+                });
+                let _ = responder
+                    .send_to(&encode_message(&reply, 0, 0).unwrap(), src)
+                    .await;
+            }
+        });
+        let config = DiscoveryConfig {
+            targets: vec![target],
+            timeout: Duration::from_millis(300),
+            resend_interval: Duration::from_millis(50),
+            ..DiscoveryConfig::default()
+        };
+
+        // Act
+        let found = discover_masters(&config).await.unwrap();
+
+        // Assert
+        assert_eq!(found, vec![master("127.0.0.1:4000", true)]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Client side of the encrypted UDP input channel.
+//!
+//! Once the master has accepted our `Hello`, [`run`] opens a UDP socket towards
+//! the master's input port and keeps the path alive:
+//!
+//! - Every [`PING_INTERVAL`] it sends a `Ping`.  This registers our UDP address
+//!   with the master (and keeps NAT mappings open).
+//! - It answers the master's `Ping` probes with a `Pong`; only after seeing
+//!   one does the master start sending input over UDP.
+//! - Input datagrams are handed to the application through the same
+//!   [`NetworkEvent::MessageReceived`] channel as control-channel messages, so
+//!   the rest of the client does not care which path an event took.
+//!
+//! Datagrams are sealed and opened with `kvm_core::crypto::input_channel`,
+//! keyed from the `HelloAck` session token, and filtered through a replay
+//! window.  Only input messages are accepted on this path; everything else
+//! must come over the authenticated control channel.
+//!
+//! If the master never answers (UDP blocked, or the master has the channel
+//! turned off) the pings slow down to one every [`RETRY_INTERVAL`]; the master
+//! keeps sending over TCP in the meantime.
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use kvm_core::crypto::input_channel::{
+    Endpoint, InputChannelCipher, ReplayWindow, MAX_DATAGRAM_LEN,
+};
+use kvm_core::protocol::messages::KvmMessage;
+use kvm_core::protocol::SequenceCounter;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::time;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::{current_timestamp_us, NetworkEvent};
+
+/// How often a `Ping` is sent while the master is answering.
+pub const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a `Ping` is sent once the master has stopped answering.
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Unanswered pings after which we fall back to [`RETRY_INTERVAL`].
+const MAX_UNANSWERED_PINGS: u32 = 5;
+
+/// Runs the input channel towards `master` until the task is aborted or the
+/// event receiver is dropped.
+pub(super) async fn run(
+    master: SocketAddr,
+    client_id: Uuid,
+    session_token: [u8; 32],
+    version: u8,
+    tx: mpsc::Sender<NetworkEvent>,
+) {
+    let local: SocketAddr = if master.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = match UdpSocket::bind(local).await {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("input channel disabled: cannot bind UDP socket: {e}");
+            return;
+        }
+    };
+    if let Err(e) = socket.connect(master).await {
+        warn!("input channel disabled: cannot reach {master}: {e}");
+        return;
+    }
+
+    let cipher =
+        InputChannelCipher::new(&session_token, client_id, Endpoint::Client).with_version(version);
+    let sequence = SequenceCounter::new();
+    let mut replay = ReplayWindow::new();
+    let mut unanswered = 0u32;
+    let mut ticks_since_ping = 0u32;
+    let retry_every = (RETRY_INTERVAL.as_millis() / PING_INTERVAL.as_millis()) as u32;
+    let mut ticker = time::interval(PING_INTERVAL);
+    let mut buf = vec![0u8; MAX_DATAGRAM_LEN + 1];
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                ticks_since_ping += 1;
+                let backing_off = unanswered >= MAX_UNANSWERED_PINGS;
+                if backing_off && ticks_since_ping < retry_every {
+                    continue;
+                }
+                if unanswered == MAX_UNANSWERED_PINGS {
+                    info!("no answer on the UDP input channel; input stays on TCP");
+                }
+                ticks_since_ping = 0;
+                unanswered = unanswered.saturating_add(1);
+                let seq = sequence.next();
+                send(&socket, &cipher, &KvmMessage::Ping(seq), seq).await;
+            }
+            received = socket.recv(&mut buf) => {
+                let len = match received {
+                    Ok(len) => len,
+                    Err(e) => {
+                        // E.g. ICMP "port unreachable" when nothing listens.
+                        debug!("input channel receive error: {e}");
+                        continue;
+                    }
+                };
+                let (seq, msg) = match cipher.open(&buf[..len]) {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        debug!("dropping input datagram: {e}");
+                        continue;
+                    }
+                };
+                if !replay.accept(seq) {
+                    debug!("dropping replayed input datagram {seq}");
+                    continue;
+                }
+                unanswered = 0;
+                match msg {
+                    KvmMessage::Ping(token) => {
+                        send(&socket, &cipher, &KvmMessage::Pong(token), sequence.next()).await;
+                    }
+                    KvmMessage::KeyEvent(_)
+                    | KvmMessage::MouseMove(_)
+                    | KvmMessage::MouseButton(_)
+                    | KvmMessage::MouseScroll(_)
+                    | KvmMessage::InputBatch(_) => {
+                        if tx.send(NetworkEvent::MessageReceived(msg)).await.is_err() {
+                            return;
+                        }
+                    }
+                    other => debug!(
+                        "ignoring {:?} on the input channel",
+                        other.message_type()
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Seals and sends one datagram; failures are logged and otherwise ignored,
+/// as the next ping will try again.
+async fn send(socket: &UdpSocket, cipher: &InputChannelCipher, msg: &KvmMessage, seq: u64) {
+    match cipher.seal(msg, seq, current_timestamp_us()) {
+        Ok(datagram) => {
+            if let Err(e) = socket.send(&datagram).await {
+                debug!("input channel send failed: {e}");
+            }
+        }
+        Err(e) => warn!("cannot seal input channel datagram: {e}"),
+    }
+}
@@ -4,26 +4,50 @@
 //! inbound [`KvmMessage`]s to the application layer.
 //!
 //! Architecture:
-//! - `ClientConnection` owns a TCP stream (control channel).
+//! - `ClientConnection` owns a TCP stream (control channel), wrapped in TLS
+//!   when [`ClientConnectionConfig::tls`] is set.
 //! - Inbound messages are decoded and forwarded on an `mpsc` channel.
 //! - Outbound messages (e.g. `ScreenInfo`, `Ping`) are sent through the
-//!   connection.
+//!   connection, encoded with the protocol version agreed in the `HelloAck`.
+//!   Messages needing a capability the master did not agree to (see
+//!   `kvm_core::protocol::negotiation`) are refused with
+//!   [`ClientNetworkError::NotNegotiated`] instead of being sent.
+//! - When [`ClientConnectionConfig::input_port`] is set, an encrypted UDP
+//!   input channel (`input_channel`) runs alongside each accepted session.
+//!   The master sends high-frequency input over it while it works and falls
+//!   back to the control channel when it does not.
+//! - [`discovery`] finds the master's control address by broadcasting
+//!   `Announce`, for when no address is configured.
+//! - [`connection_state`] defines the [`ConnectionState`] machine the
+//!   connection moves through and the reconnect [`Backoff`].
 //!
 //! # Connection lifecycle (for beginners)
 //!
 //! ```text
 //! ClientConnection::start()
-//!   ├─ Try to connect to master_addr via TCP
-//!   │    └─ if failed: wait reconnect_interval, retry forever
+//!   ├─ Connecting: connect to master_addr via TCP
+//!   │    └─ if failed: Backoff, wait (doubling up to the cap), retry
+//!   ├─ If TLS is configured: run the handshake (may fail on a pin mismatch)
 //!   ├─ On success: emit NetworkEvent::Connected
+//!   ├─ Handshaking: send Hello, carrying the last session token if any
 //!   ├─ Start read_loop (background Tokio task)
 //!   │    ├─ Read bytes from TCP stream
 //!   │    ├─ Accumulate in a buffer until a complete message is available
-//!   │    ├─ Call decode_message() to parse the header + payload
+//!   │    ├─ KvmCodec parses the header + payload
+//!   │    ├─ HelloAck → Active; PairingRequest → Pairing; PairingConfirm → Active
 //!   │    └─ Emit NetworkEvent::MessageReceived(msg)
+//!   ├─ On disconnect: Backoff, then reconnect
 //!   └─ Return the mpsc receiver to the caller
 //! ```
 //!
+//! # Session resumption
+//!
+//! The `session_token` of the last accepted `HelloAck` is kept across
+//! reconnects and offered as `Hello.resume_token`.  If the master still
+//! remembers that session it answers with `HelloAck.resumed` set, and the
+//! client's pairing and reported layout carry over: the application does not
+//! need to send `ScreenInfo` again.
+//!
 //! # Message framing
 //!
 //! TCP is a *stream* protocol — bytes arrive in order but not necessarily in
@@ -31,34 +55,51 @@
 //! guarantee one `recv()` on the client; the OS may merge or split packets.
 //!
 //! The KVM protocol handles this with an explicit 24-byte header that includes
-//! the `payload_length` field.  The read loop:
+//! the `payload_length` field.  The read loop wraps the socket in a
+//! `FramedRead` driven by kvm-core's [`KvmCodec`], which:
 //!
-//! 1. Reads bytes until the buffer contains at least 24 bytes (the header).
-//! 2. Reads the `payload_length` field to know how many more bytes to wait for.
-//! 3. When `buffer.len() >= 24 + payload_length`, calls `decode_message()`.
-//! 4. Advances the buffer by the consumed byte count and repeats.
+//! 1. Buffers bytes until the buffer contains at least 24 bytes (the header).
+//! 2. Reads the `payload_length` field and rejects it if it exceeds the
+//!    configured maximum (so a bogus header cannot make us allocate gigabytes).
+//! 3. When `buffer.len() >= 24 + payload_length`, decodes the message.
+//! 4. Advances the buffer past the frame and repeats.
 //!
 //! This process is called "message framing" and it is a standard pattern for
-//! binary TCP protocols.
+//! binary TCP protocols.  The master and the web bridge use the same codec.
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use kvm_core::{
-    decode_message, encode_message,
-    protocol::messages::{capabilities, HelloMessage, KvmMessage, PlatformId, ScreenInfoMessage},
+use futures_util::StreamExt;
+use kvm_core::crypto::tls::{rustls::ClientConfig, server_name, CertFingerprint};
+use kvm_core::protocol::{
+    codec::encode_message_with_version,
+    messages::{
+        capabilities, HelloMessage, KvmMessage, PlatformId, ScreenInfoMessage,
+        MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+    },
+    negotiation::{is_supported_version, Negotiated, NegotiationError},
+    KvmCodec,
 };
 use thiserror::Error;
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
     net::TcpStream,
     sync::{mpsc, Mutex},
     time,
 };
+use tokio_rustls::TlsConnector;
+use tokio_util::codec::FramedRead;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+pub mod connection_state;
+pub mod discovery;
+mod input_channel;
+
+pub use connection_state::{Backoff, BackoffPolicy, ConnectionState};
+
 /// Errors that can occur in the client network layer.
 #[derive(Debug, Error)]
 pub enum ClientNetworkError {
@@ -69,6 +110,14 @@ pub enum ClientNetworkError {
         #[source]
         source: std::io::Error,
     },
+    /// The TLS handshake with the master failed, e.g. because its
+    /// certificate does not match the pinned fingerprint.
+    #[error("TLS handshake with master at {addr} failed: {source}")]
+    TlsHandshake {
+        addr: SocketAddr,
+        #[source]
+        source: std::io::Error,
+    },
     /// An I/O error occurred on the established connection.
     #[error("connection I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -78,8 +127,22 @@ pub enum ClientNetworkError {
     /// The connection was closed by the remote side.
     #[error("connection closed by master")]
     Closed,
+    /// There is no connection to send on (not yet connected, or reconnecting).
+    #[error("not connected to the master")]
+    NotConnected,
+    /// The master did not agree to receive this kind of message.
+    #[error("not negotiated with the master: {0}")]
+    NotNegotiated(#[from] NegotiationError),
 }
 
+/// Capabilities this client advertises in its `Hello`.
+pub const CLIENT_CAPABILITIES: u32 = capabilities::KEYBOARD_EMULATION
+    | capabilities::MOUSE_EMULATION
+    | capabilities::CLIPBOARD_SHARING
+    | capabilities::CLIPBOARD_HTML
+    | capabilities::CLIPBOARD_IMAGE
+    | capabilities::MULTI_MONITOR;
+
 /// Configuration for the client's network connection.
 #[derive(Debug, Clone)]
 pub struct ClientConnectionConfig {
@@ -89,8 +152,19 @@ pub struct ClientConnectionConfig {
     pub client_id: Uuid,
     /// Human-readable name advertised to the master.
     pub client_name: String,
-    /// Reconnect interval when the connection drops.
+    /// Wait before the first reconnect attempt.  It doubles with each
+    /// failed attempt, up to `max_reconnect_interval`.
     pub reconnect_interval: Duration,
+    /// Upper bound for the wait between reconnect attempts.
+    pub max_reconnect_interval: Duration,
+    /// TLS settings for the control channel; `None` connects in plaintext.
+    ///
+    /// Build this with `TlsIdentity::client_config`.  The master only accepts
+    /// plaintext when it was started without TLS, which is meant for tests.
+    pub tls: Option<Arc<ClientConfig>>,
+    /// The master's UDP input port (on `master_addr`'s IP), or `None` to
+    /// receive all input over the control channel.
+    pub input_port: Option<u16>,
 }
 
 impl Default for ClientConnectionConfig {
@@ -100,6 +174,9 @@ impl Default for ClientConnectionConfig {
             client_id: Uuid::nil(),
             client_name: "kvm-client".to_string(),
             reconnect_interval: Duration::from_secs(5),
+            max_reconnect_interval: Duration::from_secs(60),
+            tls: None,
+            input_port: None,
         }
     }
 }
@@ -110,9 +187,16 @@ pub enum NetworkEvent {
     /// A message was received from the master.
     MessageReceived(KvmMessage),
     /// The TCP connection was established.
-    Connected { master_addr: SocketAddr },
+    Connected {
+        master_addr: SocketAddr,
+        /// Fingerprint of the certificate the master presented, or `None` on
+        /// a plaintext connection.  Pairing binds to it.
+        master_fingerprint: Option<CertFingerprint>,
+    },
     /// The TCP connection was lost.
     Disconnected,
+    /// The connection moved to a new [`ConnectionState`].
+    StateChanged(ConnectionState),
 }
 
 /// Sequence counter for outbound messages.
@@ -142,10 +226,21 @@ fn current_timestamp_us() -> u64 {
         .unwrap_or(0)
 }
 
+/// Read half of the control channel: a TCP or TLS stream.
+type ControlReader = Box<dyn AsyncRead + Send + Unpin>;
+/// Write half of the control channel: a TCP or TLS stream.
+type ControlWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
 /// Manages the TCP control-channel connection from the client to the master.
 pub struct ClientConnection {
     config: ClientConnectionConfig,
-    write_half: Arc<Mutex<Option<tokio::net::tcp::OwnedWriteHalf>>>,
+    write_half: Arc<Mutex<Option<ControlWriter>>>,
+    /// Terms from the current session's `HelloAck`; `None` until it arrives.
+    negotiated: std::sync::Mutex<Option<Negotiated>>,
+    /// Where the connection is in its lifecycle.
+    state: std::sync::Mutex<ConnectionState>,
+    /// Token of the last accepted session, offered to resume it on reconnect.
+    resume_token: std::sync::Mutex<Option<[u8; 32]>>,
 }
 
 impl ClientConnection {
@@ -154,13 +249,17 @@ impl ClientConnection {
         Self {
             config,
             write_half: Arc::new(Mutex::new(None)),
+            negotiated: std::sync::Mutex::new(None),
+            state: std::sync::Mutex::new(ConnectionState::Disconnected),
+            resume_token: std::sync::Mutex::new(None),
         }
     }
 
     /// Connects to the master and begins reading messages.
     ///
     /// Returns a channel receiver that delivers [`NetworkEvent`]s to the caller.
-    /// Runs a continuous reconnect loop until `running` is set to false.
+    /// Runs a continuous reconnect loop, with capped exponential backoff,
+    /// until `running` is set to false.
     pub async fn start(
         self: Arc<Self>,
         running: Arc<std::sync::atomic::AtomicBool>,
@@ -169,156 +268,293 @@ impl ClientConnection {
         let this = Arc::clone(&self);
 
         tokio::spawn(async move {
+            let mut backoff = Backoff::new(BackoffPolicy {
+                initial: this.config.reconnect_interval,
+                max: this.config.max_reconnect_interval,
+            });
             while running.load(std::sync::atomic::Ordering::Relaxed) {
-                match TcpStream::connect(this.config.master_addr).await {
-                    Ok(stream) => {
+                this.set_state(ConnectionState::Connecting, &tx).await;
+                match this.connect().await {
+                    Ok((read_half, write_half_owned, master_fingerprint)) => {
                         info!("connected to master at {}", this.config.master_addr);
                         let addr = this.config.master_addr;
-                        let _ = tx.send(NetworkEvent::Connected { master_addr: addr }).await;
+                        let _ = tx
+                            .send(NetworkEvent::Connected {
+                                master_addr: addr,
+                                master_fingerprint,
+                            })
+                            .await;
 
-                        let (read_half, write_half_owned) = stream.into_split();
                         {
                             let mut guard = this.write_half.lock().await;
                             *guard = Some(write_half_owned);
                         }
 
                         // Send Hello handshake
+                        this.set_state(ConnectionState::Handshaking, &tx).await;
                         this.send_hello().await;
 
                         // Drive the read loop
-                        this.read_loop(read_half, &tx).await;
+                        if this.read_loop(read_half, &tx).await {
+                            backoff.reset();
+                        }
 
                         {
                             let mut guard = this.write_half.lock().await;
                             *guard = None;
                         }
+                        this.set_negotiated(None);
                         let _ = tx.send(NetworkEvent::Disconnected).await;
-                        info!(
-                            "disconnected from master; reconnecting in {:?}",
-                            this.config.reconnect_interval
-                        );
-                    }
-                    Err(e) => {
-                        warn!(
-                            "could not connect to master at {}: {e}",
-                            this.config.master_addr
-                        );
                     }
+                    Err(e) => warn!("{e}"),
                 }
 
-                if running.load(std::sync::atomic::Ordering::Relaxed) {
-                    time::sleep(this.config.reconnect_interval).await;
+                if !running.load(std::sync::atomic::Ordering::Relaxed) {
+                    break;
                 }
+                let delay = backoff.next_delay();
+                info!(
+                    "reconnecting to master in {delay:?} (attempt {})",
+                    backoff.attempts()
+                );
+                this.set_state(ConnectionState::Backoff, &tx).await;
+                time::sleep(delay).await;
             }
+            this.set_state(ConnectionState::Disconnected, &tx).await;
         });
 
         rx
     }
 
+    /// Where the connection currently is in its lifecycle.
+    pub fn state(&self) -> ConnectionState {
+        *self.state.lock().expect("state lock poisoned")
+    }
+
+    /// Moves to `next` and reports it on `tx`, if the state machine allows
+    /// the transition; otherwise leaves the state unchanged.
+    async fn set_state(&self, next: ConnectionState, tx: &mpsc::Sender<NetworkEvent>) {
+        {
+            let mut state = self.state.lock().expect("state lock poisoned");
+            if *state == next {
+                return;
+            }
+            if !state.can_transition_to(next) {
+                debug!("ignoring connection state change {:?} -> {next:?}", *state);
+                return;
+            }
+            *state = next;
+        }
+        debug!("connection state: {next:?}");
+        let _ = tx.send(NetworkEvent::StateChanged(next)).await;
+    }
+
+    /// Opens the TCP connection and, if configured, completes the TLS
+    /// handshake.  Also returns the master's certificate fingerprint when TLS
+    /// is in use.
+    async fn connect(
+        &self,
+    ) -> Result<(ControlReader, ControlWriter, Option<CertFingerprint>), ClientNetworkError> {
+        let addr = self.config.master_addr;
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(|source| ClientNetworkError::ConnectFailed { addr, source })?;
+
+        let Some(tls) = &self.config.tls else {
+            let (r, w) = stream.into_split();
+            return Ok((Box::new(r), Box::new(w), None));
+        };
+        let stream = TlsConnector::from(Arc::clone(tls))
+            .connect(server_name(), stream)
+            .await
+            .map_err(|source| ClientNetworkError::TlsHandshake { addr, source })?;
+        let master_fingerprint = CertFingerprint::of_peer(stream.get_ref().1);
+        let (r, w) = tokio::io::split(stream);
+        Ok((Box::new(r), Box::new(w), master_fingerprint))
+    }
+
     /// Sends the `Hello` handshake message.
+    ///
+    /// It offers the newest version we speak; no version has been agreed yet,
+    /// so the frame itself goes out as [`MIN_PROTOCOL_VERSION`].  After an
+    /// earlier accepted session it also offers that session's token, so the
+    /// master can resume it.
     async fn send_hello(&self) {
         let msg = KvmMessage::Hello(HelloMessage {
             client_id: self.config.client_id,
             client_name: self.config.client_name.clone(),
-            protocol_version: kvm_core::protocol::messages::PROTOCOL_VERSION,
+            protocol_version: PROTOCOL_VERSION,
             platform_id: native_platform_id(),
-            capabilities: capabilities::KEYBOARD_EMULATION
-                | capabilities::MOUSE_EMULATION
-                | capabilities::MULTI_MONITOR,
+            capabilities: CLIENT_CAPABILITIES,
+            resume_token: *self
+                .resume_token
+                .lock()
+                .expect("resume token lock poisoned"),
         });
-        self.send_message(&msg).await;
+        if let Err(e) = self.send_message(&msg).await {
+            error!("failed to send Hello: {e}");
+        }
+    }
+
+    /// Protocol terms agreed with the master, or `None` before the
+    /// `HelloAck` (and while disconnected).
+    pub fn negotiated(&self) -> Option<Negotiated> {
+        *self.negotiated.lock().expect("negotiated lock poisoned")
+    }
+
+    fn set_negotiated(&self, terms: Option<Negotiated>) {
+        *self.negotiated.lock().expect("negotiated lock poisoned") = terms;
     }
 
     /// Reads messages from the TCP stream and forwards them on `tx`.
-    async fn read_loop(
-        &self,
-        mut reader: tokio::net::tcp::OwnedReadHalf,
-        tx: &mpsc::Sender<NetworkEvent>,
-    ) {
-        // We need a length-prefixed framing layer.  The protocol header contains
-        // payload_len at bytes 4..8, so we first read the 24-byte header, then
-        // payload_len more bytes.
-        const HEADER_SIZE: usize = kvm_core::protocol::messages::HEADER_SIZE;
-
-        loop {
-            let mut header_buf = vec![0u8; HEADER_SIZE];
-            if let Err(e) = reader.read_exact(&mut header_buf).await {
-                if e.kind() != std::io::ErrorKind::UnexpectedEof {
+    ///
+    /// Framing is done by kvm-core's [`KvmCodec`], which also rejects frames
+    /// larger than [`DEFAULT_MAX_PAYLOAD_LEN`](kvm_core::protocol::DEFAULT_MAX_PAYLOAD_LEN)
+    /// before buffering them.
+    ///
+    /// Also starts the UDP input channel once the `HelloAck` arrives, and stops
+    /// it when the control channel closes: its keys are only valid for this
+    /// session.
+    ///
+    /// Returns whether the master accepted the session, which resets the
+    /// reconnect backoff.
+    async fn read_loop(&self, reader: ControlReader, tx: &mpsc::Sender<NetworkEvent>) -> bool {
+        let mut frames = FramedRead::new(reader, KvmCodec::new());
+        let mut udp_task: Option<tokio::task::JoinHandle<()>> = None;
+        let mut accepted = false;
+
+        while let Some(result) = frames.next().await {
+            let msg = match result {
+                Ok(frame) => frame.message,
+                Err(e) => {
                     error!("read error on control channel: {e}");
+                    break;
+                }
+            };
+            debug!("received {:?}", std::mem::discriminant(&msg));
+
+            if let KvmMessage::HelloAck(ack) = &msg {
+                if ack.accepted {
+                    if !is_supported_version(ack.server_version) {
+                        error!(
+                            "master chose protocol version {}, which we do not speak",
+                            ack.server_version
+                        );
+                        break;
+                    }
+                    let terms =
+                        Negotiated::new(ack.server_version, CLIENT_CAPABILITIES, ack.capabilities);
+                    self.set_negotiated(Some(terms));
+                    if ack.resumed {
+                        info!("master resumed the previous session");
+                    }
+                    *self
+                        .resume_token
+                        .lock()
+                        .expect("resume token lock poisoned") = Some(ack.session_token);
+                    accepted = true;
+                    self.set_state(ConnectionState::Active, tx).await;
+                    if let (Some(port), None) = (self.config.input_port, &udp_task) {
+                        let master = SocketAddr::new(self.config.master_addr.ip(), port);
+                        udp_task = Some(tokio::spawn(input_channel::run(
+                            master,
+                            self.config.client_id,
+                            ack.session_token,
+                            terms.version(),
+                            tx.clone(),
+                        )));
+                    }
                 }
-                break;
             }
 
-            // Payload length is at bytes 4..8 (big-endian u32)
-            let payload_len = u32::from_be_bytes(header_buf[4..8].try_into().unwrap()) as usize;
-
-            let mut full_msg = header_buf;
-            full_msg.extend(vec![0u8; payload_len]);
-            if payload_len > 0 {
-                if let Err(e) = reader.read_exact(&mut full_msg[HEADER_SIZE..]).await {
-                    error!("read payload error: {e}");
-                    break;
+            match &msg {
+                KvmMessage::PairingRequest(_) => {
+                    self.set_state(ConnectionState::Pairing, tx).await;
+                }
+                KvmMessage::PairingConfirm(_) => {
+                    self.set_state(ConnectionState::Active, tx).await;
                 }
+                _ => {}
             }
 
-            match decode_message(&full_msg) {
-                Ok((msg, _)) => {
-                    debug!("received {:?}", std::mem::discriminant(&msg));
-
-                    // Auto-respond to Ping with Pong carrying the same sequence number.
-                    if let KvmMessage::Ping(seq) = msg {
-                        let pong = KvmMessage::Pong(seq);
-                        self.send_message(&pong).await;
-                        // Forward the Ping to the application layer so it can track latency.
-                        if tx
-                            .send(NetworkEvent::MessageReceived(KvmMessage::Ping(seq)))
-                            .await
-                            .is_err()
-                        {
-                            break;
-                        }
-                    } else if tx.send(NetworkEvent::MessageReceived(msg)).await.is_err() {
-                        break;
-                    }
+            // Auto-respond to Ping with Pong carrying the same sequence number.
+            if let KvmMessage::Ping(seq) = msg {
+                let pong = KvmMessage::Pong(seq);
+                if let Err(e) = self.send_message(&pong).await {
+                    warn!("failed to answer Ping: {e}");
                 }
-                Err(e) => {
-                    warn!("failed to decode inbound message: {e}");
+                // Forward the Ping to the application layer so it can track latency.
+                if tx
+                    .send(NetworkEvent::MessageReceived(KvmMessage::Ping(seq)))
+                    .await
+                    .is_err()
+                {
+                    break;
                 }
+            } else if tx.send(NetworkEvent::MessageReceived(msg)).await.is_err() {
+                break;
             }
         }
+
+        if let Some(task) = udp_task {
+            task.abort();
+        }
+        accepted
     }
 
     /// Encodes and sends a message on the control channel.
-    pub async fn send_message(&self, msg: &KvmMessage) {
-        let ts = current_timestamp_us();
-        match encode_message(msg, next_seq(), ts) {
-            Ok(bytes) => {
-                let mut guard = self.write_half.lock().await;
-                if let Some(ref mut w) = *guard {
-                    if let Err(e) = w.write_all(&bytes).await {
-                        error!("failed to send message: {e}");
-                    }
-                }
-            }
-            Err(e) => error!("failed to encode message: {e}"),
-        }
+    ///
+    /// # Errors
+    ///
+    /// - [`ClientNetworkError::NotNegotiated`] if the master did not agree to
+    ///   receive this kind of message (nothing is sent).
+    /// - [`ClientNetworkError::NotConnected`] if there is no connection.
+    /// - [`ClientNetworkError::Protocol`] or [`ClientNetworkError::Io`] if
+    ///   encoding or writing fails.
+    pub async fn send_message(&self, msg: &KvmMessage) -> Result<(), ClientNetworkError> {
+        // Before the HelloAck nothing beyond the handshake has been agreed.
+        let terms = self
+            .negotiated()
+            .unwrap_or(Negotiated::new(MIN_PROTOCOL_VERSION, 0, 0));
+        terms.check(msg)?;
+        let bytes =
+            encode_message_with_version(msg, terms.version(), next_seq(), current_timestamp_us())
+                .map_err(|e| ClientNetworkError::Protocol(e.to_string()))?;
+
+        let mut guard = self.write_half.lock().await;
+        let w = guard.as_mut().ok_or(ClientNetworkError::NotConnected)?;
+        w.write_all(&bytes).await?;
+        // TLS buffers records until flushed.
+        w.flush().await?;
+        Ok(())
     }
 
     /// Sends a `ScreenInfo` report to the master.
     ///
     /// The `ScreenInfoMessage` is built by the `screen_info` infrastructure module.
-    pub async fn send_screen_info(&self, screen_info: ScreenInfoMessage) {
+    ///
+    /// # Errors
+    ///
+    /// As for [`send_message`](Self::send_message).
+    pub async fn send_screen_info(
+        &self,
+        screen_info: ScreenInfoMessage,
+    ) -> Result<(), ClientNetworkError> {
         self.send_message(&KvmMessage::ScreenInfo(screen_info))
-            .await;
+            .await
     }
 
     /// Sends a `Ping` to measure round-trip latency.
     ///
     /// The sequence number in the `Ping` payload is used to match
     /// the corresponding `Pong` response.
-    pub async fn send_ping(&self) {
+    ///
+    /// # Errors
+    ///
+    /// As for [`send_message`](Self::send_message).
+    pub async fn send_ping(&self) -> Result<(), ClientNetworkError> {
         let seq = next_seq();
-        self.send_message(&KvmMessage::Ping(seq)).await;
+        self.send_message(&KvmMessage::Ping(seq)).await
     }
 }
 
@@ -343,6 +579,18 @@ mod tests {
         assert_eq!(cfg.reconnect_interval, Duration::from_secs(5));
     }
 
+    #[test]
+    fn test_client_connection_config_default_is_plaintext() {
+        let cfg = ClientConnectionConfig::default();
+        assert!(cfg.tls.is_none());
+    }
+
+    #[test]
+    fn test_client_connection_config_default_has_no_udp_input() {
+        let cfg = ClientConnectionConfig::default();
+        assert!(cfg.input_port.is_none());
+    }
+
     #[test]
     fn test_next_seq_increments_monotonically() {
         // Arrange
@@ -410,4 +658,108 @@ mod tests {
         // Assert – the receiver was created (we can check it exists)
         drop(rx);
     }
+
+    #[tokio::test]
+    async fn test_send_refuses_message_type_not_negotiated() {
+        // Arrange – no HelloAck yet, so no capabilities have been agreed.
+        let conn = ClientConnection::new(ClientConnectionConfig::default());
+        let clipboard = KvmMessage::ClipboardData(kvm_core::protocol::ClipboardDataMessage {
+            format: kvm_core::protocol::ClipboardFormat::Utf8Text,
+            data: b"copied".to_vec(),
+            has_more_fragments: false,
+        });
+
+        // Act
+        let refused = conn.send_message(&clipboard).await;
+        let unsent_ping = conn.send_ping().await;
+
+        // Assert – the clipboard is refused before the connection is even
+        // looked at; a Ping needs no capability and only fails for lack of one.
+        assert!(matches!(
+            refused,
+            Err(ClientNetworkError::NotNegotiated(
+                NegotiationError::CapabilityNotNegotiated { .. }
+            ))
+        ));
+        assert!(matches!(unsent_ping, Err(ClientNetworkError::NotConnected)));
+        assert!(conn.negotiated().is_none());
+    }
+
+    /// Accepts one connection on `listener`, reads its `Hello`, answers with
+    /// an accepted `HelloAck` carrying `token`, and closes the connection.
+    async fn serve_one_hello(listener: &tokio::net::TcpListener, token: [u8; 32]) -> HelloMessage {
+        use kvm_core::protocol::codec::encode_message;
+        use kvm_core::protocol::messages::HelloAckMessage;
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let (r, mut w) = stream.into_split();
+        let mut frames = FramedRead::new(r, KvmCodec::new());
+        let hello = match frames.next().await {
+            Some(Ok(frame)) => match frame.message {
+                KvmMessage::Hello(hello) => hello,
+                other => panic!("expected Hello, got {other:?}"),
+            },
+            other => panic!("expected a frame, got {other:?}"),
+        };
+        let ack = KvmMessage::HelloAck(HelloAckMessage {
+            session_token: token,
+            server_version: PROTOCOL_VERSION,
+            accepted: true,
+            reject_reason: 0,
+            capabilities: capabilities::BASELINE,
+            resumed: hello.resume_token == Some(token),
+        });
+        w.write_all(&encode_message(&ack, 0, 0).unwrap())
+            .await
+            .unwrap();
+        hello
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_offers_previous_session_token_and_reports_states() {
+        // Arrange – a fake master that accepts, then drops, every connection.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let cfg = ClientConnectionConfig {
+            master_addr: listener.local_addr().unwrap(),
+            reconnect_interval: Duration::from_millis(10),
+            max_reconnect_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let conn = Arc::new(ClientConnection::new(cfg));
+        let mut rx = Arc::clone(&conn).start(Arc::clone(&running)).await;
+
+        // Act
+        let first = serve_one_hello(&listener, [1; 32]).await;
+        let second = serve_one_hello(&listener, [2; 32]).await;
+        let third = serve_one_hello(&listener, [3; 32]).await;
+        running.store(false, std::sync::atomic::Ordering::Relaxed);
+
+        // Assert – each Hello offers the token of the session before it.
+        assert_eq!(first.resume_token, None);
+        assert_eq!(second.resume_token, Some([1; 32]));
+        assert_eq!(third.resume_token, Some([2; 32]));
+
+        let mut states = Vec::new();
+        while states.len() < 8 {
+            match time::timeout(Duration::from_secs(5), rx.recv()).await {
+                Ok(Some(NetworkEvent::StateChanged(state))) => states.push(state),
+                Ok(Some(_)) => {}
+                other => panic!("event stream ended early: {other:?}"),
+            }
+        }
+        assert_eq!(
+            states,
+            [
+                ConnectionState::Connecting,
+                ConnectionState::Handshaking,
+                ConnectionState::Active,
+                ConnectionState::Backoff,
+                ConnectionState::Connecting,
+                ConnectionState::Handshaking,
+                ConnectionState::Active,
+                ConnectionState::Backoff,
+            ]
+        );
+    }
 }
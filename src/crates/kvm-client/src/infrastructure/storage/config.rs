@@ -0,0 +1,471 @@
+//! TOML-based configuration persistence for the client application.
+//!
+//! Reads and writes `AppConfig` to the platform-appropriate config file:
+//! - Windows:  `%APPDATA%\KVMOverIP\Client\config.toml`
+//! - Linux:    `~/.config/kvmoverip/client/config.toml`
+//! - macOS:    `~/Library/Application Support/KVMOverIP/Client/config.toml`
+//!
+//! The client gets its own directory so that a machine running both the
+//! master and the client keeps two configs and two TLS identities.  The
+//! client's certificate and key (see `TlsIdentity::load_or_generate`) live
+//! next to `config.toml`.
+//!
+//! # Example file
+//!
+//! ```toml
+//! [client]
+//! client_id = "3f2504e0-4f89-11d3-9a0c-0305e82c3301"
+//! name = "dev-laptop"
+//!
+//! [master]
+//! address = "192.168.1.10:24800"   # empty: find the master on the LAN
+//! discovery = "broadcast"          # or "mdns"
+//! input_port = 24801               # master's UDP input port; 0 = TCP only
+//! fingerprint = "sha256:9c1f…"     # written after pairing
+//!
+//! [reconnect]
+//! interval_secs = 5       # first retry; doubles per failed attempt…
+//! max_interval_secs = 60  # …up to this
+//! ```
+//!
+//! # Why the client ID must persist (for beginners)
+//!
+//! The master remembers a client by its `client_id`: the certificate pinned
+//! at pairing time and the client's place in the screen layout are both
+//! stored under it.  A client that invented a new ID on every start would
+//! look like a brand-new, unpaired machine after each restart.  So the ID is
+//! generated once, when the config file is first created, and read back
+//! from then on.
+//!
+//! # Atomic saves
+//!
+//! [`save_config_at`] writes the new content to a temporary file in the same
+//! directory and then renames it over `config.toml`.  A rename within one
+//! directory replaces the file in a single step, so a reader (or a crash)
+//! sees either the old file or the new one, never half of each.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use kvm_core::crypto::tls::{CertFingerprint, TlsError};
+use kvm_core::protocol::discovery::DiscoveryBackend;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Error type for configuration file operations.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The platform config directory could not be determined.
+    #[error("could not determine platform config directory")]
+    NoPlatformConfigDir,
+
+    /// A file system I/O error occurred.
+    #[error("I/O error accessing config at {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The TOML content could not be parsed.
+    #[error("failed to parse config TOML: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    /// The config could not be serialized to TOML.
+    #[error("failed to serialize config: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+// ── Config schema types ───────────────────────────────────────────────────────
+
+/// Top-level client configuration stored on disk.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub client: ClientIdentityConfig,
+    #[serde(default)]
+    pub master: MasterConfig,
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+}
+
+/// Who this client is.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClientIdentityConfig {
+    /// Stable identity presented to the master in `Hello` and `Announce`.
+    #[serde(default = "Uuid::new_v4")]
+    pub client_id: Uuid,
+    /// Human-readable name shown in the master's client list.
+    #[serde(default = "default_client_name")]
+    pub name: String,
+}
+
+/// Which master to connect to and how to trust it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MasterConfig {
+    /// The master's control address (e.g. `"192.168.1.10:24800"`).  Empty
+    /// means the master is found on the LAN with `discovery`.
+    #[serde(default)]
+    pub address: String,
+    /// How to look for the master when `address` is empty.
+    #[serde(default)]
+    pub discovery: DiscoveryBackend,
+    /// The master's UDP input port (its `network.input_port`).  `0` keeps
+    /// all input on the control channel.
+    #[serde(default = "default_input_port")]
+    pub input_port: u16,
+    /// Certificate fingerprint of the master this client paired with
+    /// (`sha256:…`).  Once set, the TLS handshake fails against any other
+    /// certificate; clear it to pair with a different master.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+/// Reconnect behaviour after the control channel drops.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReconnectConfig {
+    /// Seconds to wait before the first reconnect attempt.
+    #[serde(default = "default_reconnect_interval_secs")]
+    pub interval_secs: u64,
+    /// Upper bound, in seconds, for the wait between attempts.
+    #[serde(default = "default_reconnect_max_interval_secs")]
+    pub max_interval_secs: u64,
+}
+
+impl AppConfig {
+    /// The pinned master fingerprint, if one is stored and well-formed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the parse error for a malformed stored fingerprint, so the
+    /// caller can report it instead of silently trusting any master.
+    pub fn pinned_master(&self) -> Result<Option<CertFingerprint>, TlsError> {
+        self.master
+            .fingerprint
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+    }
+
+    /// The master's UDP input port, or `None` when the channel is turned off.
+    pub fn input_port(&self) -> Option<u16> {
+        (self.master.input_port != 0).then_some(self.master.input_port)
+    }
+}
+
+// ── Default helpers ───────────────────────────────────────────────────────────
+
+/// Returns the machine's hostname for use as the default client name.
+///
+/// Tries `COMPUTERNAME` (Windows) first, then `HOSTNAME` (Unix), and falls
+/// back to the literal string `"kvm-client"` if neither is set.
+fn default_client_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "kvm-client".to_string())
+}
+fn default_input_port() -> u16 {
+    24801
+}
+fn default_reconnect_interval_secs() -> u64 {
+    5
+}
+fn default_reconnect_max_interval_secs() -> u64 {
+    60
+}
+
+impl Default for ClientIdentityConfig {
+    fn default() -> Self {
+        Self {
+            client_id: Uuid::new_v4(),
+            name: default_client_name(),
+        }
+    }
+}
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            discovery: DiscoveryBackend::default(),
+            input_port: default_input_port(),
+            fingerprint: None,
+        }
+    }
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_reconnect_interval_secs(),
+            max_interval_secs: default_reconnect_max_interval_secs(),
+        }
+    }
+}
+
+// ── Config repository ─────────────────────────────────────────────────────────
+
+/// Determines the platform-appropriate directory for the client's config
+/// file and TLS identity.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::NoPlatformConfigDir`] when the platform config base
+/// directory cannot be determined from the environment.
+pub fn config_dir() -> Result<PathBuf, ConfigError> {
+    platform_config_dir().ok_or(ConfigError::NoPlatformConfigDir)
+}
+
+/// Resolves the full path to the config file.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::NoPlatformConfigDir`] if the base directory cannot be
+/// determined.
+pub fn config_file_path() -> Result<PathBuf, ConfigError> {
+    Ok(config_dir()?.join("config.toml"))
+}
+
+/// Loads the config file at `path`, creating it on first run.
+///
+/// A missing file is created from defaults, which generates the client ID.
+/// A file without a `client_id` (for example one written by hand) gets one
+/// and is saved back, so the ID is fixed from the first start on.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Io`] for file-system errors other than "not found",
+/// [`ConfigError::Parse`] if the TOML is malformed, and any error from
+/// [`save_config_at`].
+pub fn load_or_init_config_at(path: &Path) -> Result<AppConfig, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            let config: AppConfig = toml::from_str(&content)?;
+            let table: toml::Table = toml::from_str(&content)?;
+            let has_id = table
+                .get("client")
+                .and_then(|client| client.get("client_id"))
+                .is_some();
+            if !has_id {
+                save_config_at(path, &config)?;
+            }
+            Ok(config)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let config = AppConfig::default();
+            save_config_at(path, &config)?;
+            Ok(config)
+        }
+        Err(source) => Err(ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// [`load_or_init_config_at`] for the platform config file.
+///
+/// # Errors
+///
+/// As [`load_or_init_config_at`], plus [`ConfigError::NoPlatformConfigDir`].
+pub fn load_or_init_config() -> Result<AppConfig, ConfigError> {
+    load_or_init_config_at(&config_file_path()?)
+}
+
+/// Atomically replaces the config file at `path` with `config`.
+///
+/// Creates the directory if needed.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Io`] for file-system failures or
+/// [`ConfigError::Serialize`] if serialization fails.
+pub fn save_config_at(path: &Path, config: &AppConfig) -> Result<(), ConfigError> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| ConfigError::Io { path, source }
+    };
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir).map_err(io_error(dir))?;
+
+    let content = toml::to_string_pretty(config)?;
+    let tmp = path.with_extension("toml.tmp");
+    let mut file = std::fs::File::create(&tmp).map_err(io_error(&tmp))?;
+    file.write_all(content.as_bytes())
+        .and_then(|()| file.sync_all())
+        .map_err(io_error(&tmp))?;
+    drop(file);
+    std::fs::rename(&tmp, path).map_err(io_error(path))
+}
+
+/// [`save_config_at`] for the platform config file.
+///
+/// # Errors
+///
+/// As [`save_config_at`], plus [`ConfigError::NoPlatformConfigDir`].
+pub fn save_config(config: &AppConfig) -> Result<(), ConfigError> {
+    save_config_at(&config_file_path()?, config)
+}
+
+/// Resolves the client's config directory.
+fn platform_config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        // %APPDATA% e.g. C:\Users\<user>\AppData\Roaming
+        std::env::var_os("APPDATA").map(|p| PathBuf::from(p).join("KVMOverIP").join("Client"))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // XDG_CONFIG_HOME or ~/.config
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+        Some(base.join("kvmoverip").join("client"))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // ~/Library/Application Support/KVMOverIP/Client
+        std::env::var_os("HOME").map(|h| {
+            PathBuf::from(h)
+                .join("Library")
+                .join("Application Support")
+                .join("KVMOverIP")
+                .join("Client")
+        })
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config_path() -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("kvm_client_test_{}", Uuid::new_v4()));
+        let path = dir.join("config.toml");
+        (dir, path)
+    }
+
+    #[test]
+    fn test_first_run_creates_file_and_keeps_the_client_id() {
+        // Arrange
+        let (dir, path) = temp_config_path();
+
+        // Act
+        let first = load_or_init_config_at(&path).unwrap();
+        let second = load_or_init_config_at(&path).unwrap();
+
+        // Assert
+        assert!(path.exists());
+        assert_ne!(first.client.client_id, Uuid::nil());
+        assert_eq!(first.client.client_id, second.client.client_id);
+        assert_eq!(first, second);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_file_without_client_id_gets_one_that_sticks() {
+        // Arrange – a hand-written file that only sets the master address
+        let (dir, path) = temp_config_path();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "[master]\naddress = \"10.0.0.2:24800\"\n").unwrap();
+
+        // Act
+        let first = load_or_init_config_at(&path).unwrap();
+        let second = load_or_init_config_at(&path).unwrap();
+
+        // Assert
+        assert_eq!(first.master.address, "10.0.0.2:24800");
+        assert_eq!(first.client.client_id, second.client.client_id);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_save_replaces_the_file_and_leaves_no_temp_file() {
+        // Arrange
+        let (dir, path) = temp_config_path();
+        let mut config = load_or_init_config_at(&path).unwrap();
+        config.master.fingerprint = Some(format!("sha256:{}", "ab".repeat(32)));
+        config.master.discovery = DiscoveryBackend::Mdns;
+        config.reconnect.interval_secs = 30;
+
+        // Act
+        save_config_at(&path, &config).unwrap();
+
+        // Assert
+        assert_eq!(load_or_init_config_at(&path).unwrap(), config);
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1, "temporary file left behind");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_minimal_toml_uses_defaults() {
+        let config: AppConfig = toml::from_str("").unwrap();
+
+        assert!(config.master.address.is_empty());
+        assert_eq!(config.master.discovery, DiscoveryBackend::Broadcast);
+        assert_eq!(config.master.fingerprint, None);
+        assert_eq!(config.input_port(), Some(24801));
+        assert_eq!(config.reconnect.interval_secs, 5);
+        assert_eq!(config.reconnect.max_interval_secs, 60);
+        assert!(!config.client.name.is_empty());
+    }
+
+    #[test]
+    fn test_pinned_master_parses_the_stored_fingerprint() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.pinned_master().unwrap(), None);
+
+        config.master.fingerprint = Some(format!("sha256:{}", "0f".repeat(32)));
+        assert!(config.pinned_master().unwrap().is_some());
+
+        config.master.fingerprint = Some("not-a-fingerprint".to_string());
+        assert!(config.pinned_master().is_err());
+    }
+
+    #[test]
+    fn test_input_port_is_read_and_zero_turns_udp_off() {
+        let config: AppConfig = toml::from_str("[master]\ninput_port = 4001").unwrap();
+        assert_eq!(config.input_port(), Some(4001));
+
+        let config: AppConfig = toml::from_str("[master]\ninput_port = 0").unwrap();
+        assert_eq!(config.input_port(), None);
+    }
+
+    #[test]
+    fn test_malformed_file_is_a_parse_error() {
+        // Arrange
+        let (dir, path) = temp_config_path();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "[[[ not valid toml").unwrap();
+
+        // Act
+        let result = load_or_init_config_at(&path);
+
+        // Assert
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_config_file_path_ends_with_config_toml() {
+        if let Ok(path) = config_file_path() {
+            assert!(path.ends_with("config.toml"));
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Storage infrastructure: configuration file persistence for the client.
+//!
+//! The `config` sub-module is the client counterpart of the master's
+//! `storage::config`:
+//!
+//! - Reading the TOML configuration file from the platform-appropriate directory.
+//! - Writing changes back to disk atomically, so a crash mid-write never
+//!   leaves a truncated file behind.
+//! - Creating the file with a fresh `client_id` on first run.  The ID must
+//!   then stay the same forever: the master keys pairings and screen layout
+//!   entries by it.
+
+pub mod config;
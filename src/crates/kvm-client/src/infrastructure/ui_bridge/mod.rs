@@ -52,30 +52,49 @@
 //! guard across an `.await` point would block the Tokio thread pool; using
 //! `tokio::sync::Mutex` correctly suspends the task instead of blocking.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 
+use crate::infrastructure::network::ConnectionState;
 use crate::infrastructure::screen_info::{build_screen_info, MockScreenEnumerator};
+use crate::infrastructure::storage::config::{save_config_at, AppConfig, ConfigError};
 
 // ── Shared application state ──────────────────────────────────────────────────
 
 /// Connection status of the client as seen by the UI.
 ///
 /// The UI displays these states as status indicators (e.g., a coloured dot).
+/// Each variant mirrors a [`ConnectionState`] of the network layer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientConnectionStatus {
     /// Not yet attempting to connect.
     Disconnected,
     /// Actively trying to reach the master (TCP connect in progress).
     Connecting,
-    /// Control channel established; awaiting pairing or `HelloAck`.
-    Connected,
+    /// Control channel established; `Hello` sent, awaiting the `HelloAck`.
+    Handshaking,
     /// Fully paired and receiving input events from the master.
     Active,
     /// Pairing is in progress (waiting for the user to enter the PIN).
     Pairing,
+    /// The connection failed or dropped; waiting before the next attempt.
+    Backoff,
+}
+
+impl From<ConnectionState> for ClientConnectionStatus {
+    fn from(state: ConnectionState) -> Self {
+        match state {
+            ConnectionState::Disconnected => Self::Disconnected,
+            ConnectionState::Connecting => Self::Connecting,
+            ConnectionState::Handshaking => Self::Handshaking,
+            ConnectionState::Pairing => Self::Pairing,
+            ConnectionState::Active => Self::Active,
+            ConnectionState::Backoff => Self::Backoff,
+        }
+    }
 }
 
 /// Runtime state shared between Tauri commands.
@@ -86,38 +105,77 @@ pub enum ClientConnectionStatus {
 pub struct ClientAppState {
     /// The current connection state reported to the UI.
     pub connection_status: Mutex<ClientConnectionStatus>,
-    /// The master's IP address and port (e.g., "192.168.1.10:24800").
+    /// The address of the master in use (e.g., "192.168.1.10:24800"),
+    /// configured or discovered.
     pub master_address: Mutex<String>,
     /// The human-readable name that identifies this client to the master.
     pub client_name: Mutex<String>,
     /// Number of monitors detected on this machine (updated by `get_monitor_count`).
     pub monitor_count: Mutex<u8>,
+    /// The persisted configuration.  Change it through
+    /// [`update_config`](Self::update_config) so the file stays in sync.
+    pub config: Mutex<AppConfig>,
+    /// Where `config` is saved; `None` keeps it in memory only.
+    config_path: Option<PathBuf>,
+    /// PINs typed into the pairing dialog, forwarded to the network loop.
+    pairing_pin_tx: mpsc::Sender<String>,
+    /// Receiving end of `pairing_pin_tx`; taken once by the network loop.
+    pairing_pin_rx: Mutex<Option<mpsc::Receiver<String>>>,
 }
 
 impl ClientAppState {
-    /// Creates a new `ClientAppState` with all fields at their defaults.
+    /// Creates a new `ClientAppState` with a default configuration that is
+    /// kept in memory only.
     ///
     /// The `client_name` is initialised from the machine's hostname so that
     /// the master can identify this client without requiring manual configuration.
     pub fn new() -> Arc<Self> {
+        Self::with_config(AppConfig::default(), None)
+    }
+
+    /// Creates the state from a loaded configuration.  Changes made with
+    /// [`update_config`](Self::update_config) are written back to
+    /// `config_path`.
+    pub fn with_config(config: AppConfig, config_path: Option<PathBuf>) -> Arc<Self> {
+        // A handful of slots is plenty: the user types one PIN at a time.
+        let (pairing_pin_tx, pairing_pin_rx) = mpsc::channel(4);
         Arc::new(Self {
             connection_status: Mutex::new(ClientConnectionStatus::Disconnected),
-            master_address: Mutex::new(String::new()),
-            client_name: Mutex::new(hostname()),
+            master_address: Mutex::new(config.master.address.clone()),
+            client_name: Mutex::new(config.client.name.clone()),
             monitor_count: Mutex::new(0),
+            config: Mutex::new(config),
+            config_path,
+            pairing_pin_tx,
+            pairing_pin_rx: Mutex::new(Some(pairing_pin_rx)),
         })
     }
-}
 
-impl Default for ClientAppState {
-    fn default() -> Self {
-        Self {
-            connection_status: Mutex::new(ClientConnectionStatus::Disconnected),
-            master_address: Mutex::new(String::new()),
-            client_name: Mutex::new(hostname()),
-            monitor_count: Mutex::new(0),
+    /// Applies `change` to the configuration and saves it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ConfigError`] if the file cannot be written; the
+    /// in-memory configuration is changed regardless.
+    pub async fn update_config(
+        &self,
+        change: impl FnOnce(&mut AppConfig),
+    ) -> Result<(), ConfigError> {
+        let mut config = self.config.lock().await;
+        change(&mut config);
+        match &self.config_path {
+            Some(path) => save_config_at(path, &config),
+            None => Ok(()),
         }
     }
+
+    /// Hands the stream of submitted pairing PINs to the caller.
+    ///
+    /// Returns `None` after the first call; only one consumer (the network
+    /// loop in `main`) may own it.
+    pub async fn take_pairing_pins(&self) -> Option<mpsc::Receiver<String>> {
+        self.pairing_pin_rx.lock().await.take()
+    }
 }
 
 // ── DTOs ──────────────────────────────────────────────────────────────────────
@@ -201,25 +259,26 @@ pub async fn get_client_status(state: Arc<ClientAppState>) -> ClientCommandResul
     })
 }
 
-/// Returns the current client settings.
+/// Returns the configured client settings.
 ///
-/// Used by the settings panel to populate its form fields on open.
+/// Used by the settings panel to populate its form fields on open.  An empty
+/// `master_address` means the master is discovered on the LAN.
 pub async fn get_client_settings(
     state: Arc<ClientAppState>,
 ) -> ClientCommandResult<ClientSettingsDto> {
-    let master = state.master_address.lock().await;
-    let name = state.client_name.lock().await;
+    let config = state.config.lock().await;
 
     ClientCommandResult::ok(ClientSettingsDto {
-        master_address: master.clone(),
-        client_name: name.clone(),
+        master_address: config.master.address.clone(),
+        client_name: config.client.name.clone(),
     })
 }
 
-/// Applies new client settings submitted by the user.
+/// Applies and persists new client settings submitted by the user.
 ///
 /// Validates that `client_name` is not blank before writing, because the
-/// master uses the client name to identify machines in its UI.
+/// master uses the client name to identify machines in its UI.  A new master
+/// address takes effect on the next start.
 pub async fn update_client_settings(
     state: Arc<ClientAppState>,
     settings: ClientSettingsDto,
@@ -228,18 +287,18 @@ pub async fn update_client_settings(
         return ClientCommandResult::err("client_name must not be empty");
     }
 
-    // Acquire each lock in a separate block so they are released immediately
-    // after the write.  This minimises the time locks are held.
-    {
-        let mut master = state.master_address.lock().await;
-        *master = settings.master_address.clone();
-    }
-    {
-        let mut name = state.client_name.lock().await;
-        *name = settings.client_name.clone();
-    }
+    let saved = state
+        .update_config(|config| {
+            config.master.address = settings.master_address.trim().to_string();
+            config.client.name = settings.client_name.clone();
+        })
+        .await;
+    *state.client_name.lock().await = settings.client_name;
 
-    ClientCommandResult::ok(())
+    match saved {
+        Ok(()) => ClientCommandResult::ok(()),
+        Err(e) => ClientCommandResult::err(format!("failed to save config: {e}")),
+    }
 }
 
 /// Returns the number of monitors detected on this client machine.
@@ -262,16 +321,28 @@ pub async fn get_monitor_count(state: Arc<ClientAppState>) -> ClientCommandResul
     }
 }
 
-// ── Platform helpers ──────────────────────────────────────────────────────────
-
-/// Returns the machine's hostname for use as the default client name.
+/// Submits the PIN the user read off the master's screen.
 ///
-/// Tries `COMPUTERNAME` (Windows) first, then `HOSTNAME` (Unix), and falls
-/// back to the literal string `"kvm-client"` if neither is set.
-fn hostname() -> String {
-    std::env::var("COMPUTERNAME")
-        .or_else(|_| std::env::var("HOSTNAME"))
-        .unwrap_or_else(|_| "kvm-client".to_string())
+/// Only checks the format (six digits); whether the PIN is *correct* is
+/// decided by the key exchange with the master, and a wrong PIN shows up later
+/// as a pairing error.
+pub async fn submit_pairing_pin(
+    state: Arc<ClientAppState>,
+    pin: String,
+) -> ClientCommandResult<()> {
+    let pin = pin.trim();
+    if pin.len() != kvm_core::crypto::pairing::PIN_LENGTH
+        || !pin.bytes().all(|b| b.is_ascii_digit())
+    {
+        return ClientCommandResult::err("PIN must be 6 digits");
+    }
+    if *state.connection_status.lock().await != ClientConnectionStatus::Pairing {
+        return ClientCommandResult::err("no pairing request is pending");
+    }
+    match state.pairing_pin_tx.send(pin.to_string()).await {
+        Ok(()) => ClientCommandResult::ok(()),
+        Err(_) => ClientCommandResult::err("client is shutting down"),
+    }
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -332,6 +403,32 @@ mod tests {
         assert_eq!(dto.client_name, "my-laptop");
     }
 
+    #[tokio::test]
+    async fn test_update_client_settings_saves_the_config_file() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("kvm_ui_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("config.toml");
+        let config = AppConfig::default();
+        let client_id = config.client.client_id;
+        let state = ClientAppState::with_config(config, Some(path.clone()));
+        let settings = ClientSettingsDto {
+            master_address: "10.0.0.7:24800".to_string(),
+            client_name: "desk".to_string(),
+        };
+
+        // Act
+        let result = update_client_settings(state, settings).await;
+
+        // Assert – the new settings are on disk, the client ID unchanged
+        assert!(result.success);
+        let saved: AppConfig = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.master.address, "10.0.0.7:24800");
+        assert_eq!(saved.client.name, "desk");
+        assert_eq!(saved.client.client_id, client_id);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[tokio::test]
     async fn test_update_client_settings_rejects_empty_name() {
         // Arrange
@@ -362,6 +459,41 @@ mod tests {
         assert!(result.data.unwrap() >= 1);
     }
 
+    #[tokio::test]
+    async fn test_submit_pairing_pin_forwards_pin_while_pairing() {
+        // Arrange
+        let state = make_state();
+        let mut pins = state.take_pairing_pins().await.unwrap();
+        *state.connection_status.lock().await = ClientConnectionStatus::Pairing;
+
+        // Act
+        let result = submit_pairing_pin(Arc::clone(&state), " 012345 ".to_string()).await;
+
+        // Assert
+        assert!(result.success);
+        assert_eq!(pins.recv().await.as_deref(), Some("012345"));
+        assert!(state.take_pairing_pins().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_submit_pairing_pin_rejects_malformed_or_unexpected_pin() {
+        let state = make_state();
+        *state.connection_status.lock().await = ClientConnectionStatus::Pairing;
+        assert!(
+            !submit_pairing_pin(Arc::clone(&state), "12345".into())
+                .await
+                .success
+        );
+        assert!(
+            !submit_pairing_pin(Arc::clone(&state), "12345a".into())
+                .await
+                .success
+        );
+
+        *state.connection_status.lock().await = ClientConnectionStatus::Active;
+        assert!(!submit_pairing_pin(state, "123456".into()).await.success);
+    }
+
     #[test]
     fn test_client_command_result_ok_sets_success_true() {
         let r: ClientCommandResult<u32> = ClientCommandResult::ok(99);
@@ -377,9 +509,4 @@ mod tests {
         assert!(r.data.is_none());
         assert_eq!(r.error.unwrap(), "oops");
     }
-
-    #[test]
-    fn test_hostname_returns_non_empty() {
-        assert!(!hostname().is_empty());
-    }
 }
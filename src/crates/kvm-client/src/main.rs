@@ -7,11 +7,20 @@
 //!
 //! ```text
 //! main()
-//!  └─ ClientAppState::new()    -- initialises shared state
-//!  └─ ClientConnection::start() -- TCP reconnect loop
+//!  └─ load_or_init_config()     -- client ID, master address and pin
+//!  └─ ClientAppState::with_config()
+//!  └─ TlsIdentity               -- loaded from (or created in) the config dir
+//!  └─ find_master()             -- broadcast or mDNS, if no address is set
+//!  └─ ClientConnection::start() -- TLS-over-TCP reconnect loop with backoff
 //!  └─ message dispatch loop
+//!       ├─ StateChanged                 -> UI connection status
+//!       ├─ HelloAck (not resumed)       -> report screens
 //!       ├─ KeyEvent / MouseMove / etc.  -> EmulateInputUseCase
+//!       ├─ ClipboardData                -> SyncClipboardUseCase
+//!       ├─ clipboard poll tick          -> send local copies to master
 //!       ├─ ScreenInfoAck                -> re-enumerate monitors
+//!       ├─ PairingRequest / typed PIN /
+//!       │  PairingConfirm               -> ClientPairing
 //!       └─ Disconnect                   -> reconnect
 //! ```
 //!
@@ -20,37 +29,58 @@
 //! The `while let Some(event) = network_rx.recv().await` loop is the heart
 //! of the client.  It processes one network event at a time:
 //!
-//! - `NetworkEvent::Connected` – update the UI status to "Connected".
-//! - `NetworkEvent::Disconnected` – update status to "Disconnected";
-//!   the `ClientConnection` handles automatic reconnection.
+//! - `NetworkEvent::StateChanged` – mirror the connection state machine
+//!   (Connecting, Handshaking, Active, Pairing, Backoff…) in the UI status.
+//! - `NetworkEvent::Connected` – remember the master's certificate.
+//! - `NetworkEvent::Disconnected` – drop per-connection state; the
+//!   `ClientConnection` handles automatic reconnection.
 //! - `NetworkEvent::MessageReceived(msg)` – route the message to the
 //!   appropriate handler (key emulation, mouse emulation, etc.).
 //!
 //! # Platform input emulator
 //!
-//! The `MockInputEmulator` used here records all injected events rather than
-//! actually synthesising OS input.  In a production build it is replaced by:
+//! On Linux the client injects input through `UinputEmulator` (kernel
+//! virtual devices, so it works under both X11 and Wayland).  Elsewhere, or
+//! when `/dev/uinput` is not accessible, the `MockInputEmulator` records all
+//! injected events rather than actually synthesising OS input.  The other
+//! platform emulators are:
 //! - `WindowsInputEmulator` (calls `SendInput` Win32 API)
-//! - `LinuxXTestEmulator`   (calls XTest extension)
 //! - `MacosInputEmulator`   (calls CoreGraphics framework)
 
+use std::net::SocketAddr;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::time::Duration;
 
 use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 use uuid::Uuid;
 
-use kvm_client::application::emulate_input::EmulateInputUseCase;
+use kvm_client::application::emulate_input::{EmulateInputUseCase, PlatformInputEmulator};
+use kvm_client::application::pair_with_master::ClientPairing;
+use kvm_client::application::sync_clipboard::SyncClipboardUseCase;
 use kvm_client::infrastructure::{
     input_emulation::mock::MockInputEmulator,
+    network::discovery::{choose_master, discover_masters, DiscoveryConfig},
     network::{ClientConnection, ClientConnectionConfig, NetworkEvent},
-    screen_info::{build_screen_info, MockScreenEnumerator},
-    ui_bridge::{ClientAppState, ClientConnectionStatus},
+    screen_info::{build_screen_info, MockScreenEnumerator, ScreenInfoError},
+    storage::config::{config_file_path, load_or_init_config_at, AppConfig},
+    ui_bridge::{submit_pairing_pin, ClientAppState, ClientConnectionStatus},
 };
-use kvm_core::protocol::messages::{InputEvent, KvmMessage};
+use kvm_core::clipboard::{mock::MockClipboardProvider, ClipboardProvider};
+use kvm_core::crypto::tls::TlsIdentity;
+use kvm_core::protocol::discovery::DiscoveryBackend;
+use kvm_core::protocol::messages::{
+    capabilities, DisconnectReason, InputEvent, KvmMessage, ProtocolErrorCode, ScreenInfoMessage,
+};
+use tokio::io::AsyncBufReadExt;
+
+/// How often the local clipboard is checked for new copies.
+const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Pause between discovery rounds while no master answers.
+const DISCOVERY_RETRY_INTERVAL: Duration = Duration::from_secs(3);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -63,55 +93,82 @@ async fn main() -> anyhow::Result<()> {
 
     info!("KVM-Over-IP Client starting");
 
+    // ── Configuration ─────────────────────────────────────────────────────────
+    // Created on first run; the client ID in it must never change, or the
+    // master would forget this client's pairing and layout.
+    let (config, config_path) = match config_file_path() {
+        Ok(path) => (load_or_init_config_at(&path)?, Some(path)),
+        Err(e) => {
+            warn!("{e}; the client ID and settings will not survive a restart");
+            (AppConfig::default(), None)
+        }
+    };
+    let client_id = config.client.client_id;
+    info!("client ID {client_id}");
+    let pinned_master = config.pinned_master()?;
+    let reconnect_interval = Duration::from_secs(config.reconnect.interval_secs);
+    let max_reconnect_interval = Duration::from_secs(config.reconnect.max_interval_secs);
+    let discovery = config.master.discovery;
+    let input_port = config.input_port();
+    let identity_dir = config_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(|dir| dir.to_path_buf());
+
     // Shared UI state.
-    let app_state = ClientAppState::new();
+    let app_state = ClientAppState::with_config(config, config_path);
 
     // Shutdown flag.
     let running = Arc::new(AtomicBool::new(true));
 
     // ── Platform input emulator ───────────────────────────────────────────────
-    // In production: replace MockInputEmulator with WindowsInputEmulator,
-    // LinuxXTestEmulator, or MacosInputEmulator based on compile target.
-    let emulator = Arc::new(MockInputEmulator::new());
+    let emulator = open_input_emulator();
     // EmulateInputUseCase has mutable methods (dedup filter), so we wrap in Mutex.
     let emulate_use_case = Arc::new(tokio::sync::Mutex::new(EmulateInputUseCase::new(emulator)));
 
+    // ── Clipboard ─────────────────────────────────────────────────────────────
+    let mut clipboard_sync = SyncClipboardUseCase::new(open_clipboard());
+    let mut clipboard_poll = tokio::time::interval(CLIPBOARD_POLL_INTERVAL);
+
     // ── Network connection ────────────────────────────────────────────────────
-    let client_id = Uuid::new_v4();
-    let master_addr = {
-        let guard = app_state.master_address.lock().await;
-        if guard.is_empty() {
-            "127.0.0.1:24800".to_string()
-        } else {
-            guard.clone()
+    let configured = app_state.master_address.lock().await.parse().ok();
+    let master_addr = match configured {
+        Some(addr) => addr,
+        None => {
+            let name = app_state.client_name.lock().await.clone();
+            let addr = find_master(client_id, name, discovery).await;
+            *app_state.master_address.lock().await = addr.to_string();
+            addr
         }
     };
 
+    // The master pins this certificate when the client is paired, so it is
+    // kept next to the config file and reused.
+    let identity = match &identity_dir {
+        Some(dir) => TlsIdentity::load_or_generate(dir, "kvm-client")?,
+        None => TlsIdentity::generate("kvm-client")?,
+    };
+    info!("client certificate fingerprint: {}", identity.fingerprint());
+    // After pairing, only the master we paired with is trusted.
+    if let Some(pin) = pinned_master {
+        info!("trusting only the paired master {pin}; clear master.fingerprint in the config to pair with another");
+    }
+
     let net_cfg = ClientConnectionConfig {
-        master_addr: master_addr
-            .parse()
-            .unwrap_or_else(|_| "127.0.0.1:24800".parse().unwrap()),
+        master_addr,
         client_id,
         client_name: app_state.client_name.lock().await.clone(),
-        ..Default::default()
+        reconnect_interval,
+        max_reconnect_interval,
+        tls: Some(identity.client_config(pinned_master)?),
+        // The master's encrypted UDP input channel; input falls back to TCP
+        // if it is unreachable.
+        input_port,
     };
 
     let connection = Arc::new(ClientConnection::new(net_cfg));
     let mut network_rx = connection.clone().start(Arc::clone(&running)).await;
 
-    // ── Initial screen report ─────────────────────────────────────────────────
-    {
-        let enumerator = MockScreenEnumerator::single_1080p();
-        if let Ok(screen_info) = build_screen_info(&enumerator) {
-            let count = screen_info.monitors.len() as u8;
-            {
-                let mut guard = app_state.monitor_count.lock().await;
-                *guard = count;
-            }
-            connection.send_screen_info(screen_info).await;
-        }
-    }
-
     // ── Ctrl-C handler ────────────────────────────────────────────────────────
     let running_clone = Arc::clone(&running);
     tokio::spawn(async move {
@@ -121,25 +178,102 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
+    // ── Pairing PIN entry ─────────────────────────────────────────────────────
+    // In a full Tauri build the PIN dialog calls `submit_pairing_pin`; the
+    // headless binary reads the PIN from standard input instead.
+    let mut pairing_pins = app_state
+        .take_pairing_pins()
+        .await
+        .expect("pairing PIN receiver is only taken here");
+    let stdin_state = Arc::clone(&app_state);
+    tokio::spawn(async move {
+        let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let result = submit_pairing_pin(Arc::clone(&stdin_state), line).await;
+            if let Some(e) = result.error {
+                warn!("PIN not submitted: {e}");
+            }
+        }
+    });
+    let own_fingerprint = identity.fingerprint();
+    let mut master_fingerprint = None;
+    let mut pairing: Option<ClientPairing> = None;
+
     // ── Main message dispatch loop ────────────────────────────────────────────
     info!("KVM-Over-IP Client ready. Connecting to master…");
 
-    while let Some(event) = network_rx.recv().await {
+    loop {
+        let event = tokio::select! {
+            event = network_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            Some(pin) = pairing_pins.recv() => {
+                let Some(attempt) = pairing.as_mut() else {
+                    warn!("ignoring PIN: no pairing request is pending");
+                    continue;
+                };
+                match attempt.respond(&pin) {
+                    Ok(response) => {
+                        if let Err(e) = connection
+                            .send_message(&KvmMessage::PairingResponse(response))
+                            .await
+                        {
+                            error!("cannot send pairing response: {e}");
+                        }
+                    }
+                    Err(e) => error!("cannot answer pairing request: {e}"),
+                }
+                continue;
+            }
+            _ = clipboard_poll.tick() => {
+                let Some(terms) = connection
+                    .negotiated()
+                    .filter(|terms| terms.supports(capabilities::CLIPBOARD_SHARING))
+                else {
+                    continue;
+                };
+                match clipboard_sync.poll_local_change(terms.capabilities()) {
+                    Ok(fragments) => {
+                        for fragment in fragments {
+                            if let Err(e) = connection
+                                .send_message(&KvmMessage::ClipboardData(fragment))
+                                .await
+                            {
+                                warn!("clipboard not sent to master: {e}");
+                                break;
+                            }
+                        }
+                    }
+                    Err(e) => warn!("cannot read local clipboard: {e}"),
+                }
+                continue;
+            }
+        };
         if !running.load(Ordering::Relaxed) {
             break;
         }
 
         match event {
-            NetworkEvent::Connected { master_addr } => {
+            NetworkEvent::Connected {
+                master_addr,
+                master_fingerprint: fingerprint,
+            } => {
                 info!("control channel connected to {master_addr}");
+                master_fingerprint = fingerprint;
+            }
+
+            NetworkEvent::StateChanged(state) => {
                 let mut status = app_state.connection_status.lock().await;
-                *status = ClientConnectionStatus::Connected;
+                *status = ClientConnectionStatus::from(state);
             }
 
             NetworkEvent::Disconnected => {
                 warn!("control channel disconnected; reconnect in progress");
-                let mut status = app_state.connection_status.lock().await;
-                *status = ClientConnectionStatus::Disconnected;
+                // A pairing exchange is bound to the connection it started on,
+                // and so is a half-received clipboard transfer.
+                pairing = None;
+                clipboard_sync.reset_transfer();
             }
 
             NetworkEvent::MessageReceived(msg) => match msg {
@@ -182,11 +316,17 @@ async fn main() -> anyhow::Result<()> {
                         }
                     }
                 }
+                KvmMessage::ClipboardData(fragment) => {
+                    if let Err(e) = clipboard_sync.handle_fragment(fragment) {
+                        warn!("clipboard from master not applied: {e}");
+                    }
+                }
                 KvmMessage::HelloAck(ack) => {
-                    if ack.accepted {
+                    if ack.accepted && ack.resumed {
+                        info!("master resumed the session; layout kept");
+                    } else if ack.accepted {
                         info!("master accepted connection");
-                        let mut status = app_state.connection_status.lock().await;
-                        *status = ClientConnectionStatus::Active;
+                        report_screens(&connection, &app_state).await;
                     } else {
                         warn!(
                             "master rejected connection (reason code {})",
@@ -195,12 +335,55 @@ async fn main() -> anyhow::Result<()> {
                     }
                 }
                 KvmMessage::PairingRequest(req) => {
+                    let attempt =
+                        ClientPairing::new(req, Some(own_fingerprint), master_fingerprint);
+                    if attempt.is_expired(unix_now_secs()) {
+                        warn!("ignoring expired pairing request {}", attempt.session_id());
+                        continue;
+                    }
                     info!(
-                        "pairing requested (session {}); PIN display not yet implemented",
-                        req.pairing_session_id
+                        "pairing requested (session {}); enter the PIN shown on the master",
+                        attempt.session_id()
                     );
-                    let mut status = app_state.connection_status.lock().await;
-                    *status = ClientConnectionStatus::Pairing;
+                    pairing = Some(attempt);
+                }
+                KvmMessage::PairingConfirm(confirm) => {
+                    let Some(attempt) = pairing.as_ref() else {
+                        warn!("ignoring unexpected PairingConfirm");
+                        continue;
+                    };
+                    match attempt.confirm(&confirm) {
+                        Ok(_) => {
+                            info!("paired with master");
+                            pairing = None;
+                            if let Some(fingerprint) = master_fingerprint {
+                                let saved = app_state
+                                    .update_config(|config| {
+                                        config.master.fingerprint = Some(fingerprint.to_string());
+                                    })
+                                    .await;
+                                if let Err(e) = saved {
+                                    warn!(
+                                        "master certificate not saved, it will not be pinned: {e}"
+                                    );
+                                }
+                            }
+                        }
+                        Err(e) => error!("master failed pairing confirmation: {e}"),
+                    }
+                }
+                KvmMessage::Error(err)
+                    if err.error_code == ProtocolErrorCode::PairingFailed && pairing.is_some() =>
+                {
+                    warn!("pairing failed: {}; enter the PIN again", err.description);
+                }
+                KvmMessage::Disconnect {
+                    reason: DisconnectReason::Timeout,
+                } => {
+                    // The link stalled, nobody asked us to leave: the
+                    // connection reconnects and offers the session token, so
+                    // the master resumes our pairing and layout.
+                    warn!("master timed out the session; reconnecting");
                 }
                 KvmMessage::Disconnect { reason } => {
                     info!("master sent disconnect: {reason:?}");
@@ -215,3 +398,102 @@ async fn main() -> anyhow::Result<()> {
     info!("KVM-Over-IP Client stopped");
     Ok(())
 }
+
+/// Sends this machine's monitor layout to the master and records the monitor
+/// count for the UI.
+async fn report_screens(connection: &ClientConnection, app_state: &ClientAppState) {
+    let Ok(screen_info) = local_screen_info() else {
+        return;
+    };
+    *app_state.monitor_count.lock().await = screen_info.monitors.len() as u8;
+    if let Err(e) = connection.send_screen_info(screen_info).await {
+        warn!("screen report not sent: {e}");
+    }
+}
+
+/// Looks for a master with `backend` until one answers and returns its
+/// control address.
+///
+/// A master that has already paired with us wins; if several unpaired masters
+/// answer, the headless client cannot ask, so it takes the first one and logs
+/// the others (set the master address to choose).
+async fn find_master(
+    client_id: Uuid,
+    client_name: String,
+    backend: DiscoveryBackend,
+) -> SocketAddr {
+    let config = DiscoveryConfig {
+        client_id,
+        client_name,
+        backend,
+        ..DiscoveryConfig::default()
+    };
+    loop {
+        info!("looking for a master on the local network…");
+        match discover_masters(&config).await {
+            Ok(found) if !found.is_empty() => {
+                let chosen = choose_master(&found).unwrap_or_else(|| {
+                    let all: Vec<String> =
+                        found.iter().map(|m| m.control_addr.to_string()).collect();
+                    warn!(
+                        "several masters answered ({}); using the first",
+                        all.join(", ")
+                    );
+                    &found[0]
+                });
+                info!("using master at {}", chosen.control_addr);
+                return chosen.control_addr;
+            }
+            Ok(_) => info!("no master answered"),
+            Err(e) => warn!("master discovery failed: {e}"),
+        }
+        tokio::time::sleep(DISCOVERY_RETRY_INTERVAL).await;
+    }
+}
+
+/// The monitor layout reported to the master.
+fn local_screen_info() -> Result<ScreenInfoMessage, ScreenInfoError> {
+    build_screen_info(&MockScreenEnumerator::single_1080p())
+}
+
+/// Opens the uinput emulator on Linux, with its pointer sized to the monitors
+/// reported to the master.  Elsewhere, or without access to `/dev/uinput`,
+/// events are only recorded, so nothing reaches local applications.
+fn open_input_emulator() -> Arc<dyn PlatformInputEmulator> {
+    #[cfg(target_os = "linux")]
+    {
+        use kvm_client::application::emulate_input::EmulationError;
+        use kvm_client::infrastructure::input_emulation::uinput::UinputEmulator;
+        match local_screen_info()
+            .map_err(|e| EmulationError::Platform(e.to_string()))
+            .and_then(|screen| UinputEmulator::open(&screen))
+        {
+            Ok(emulator) => return Arc::new(emulator),
+            Err(e) => warn!("uinput unavailable, remote input will not be injected: {e}"),
+        }
+    }
+    Arc::new(MockInputEmulator::new())
+}
+
+/// Opens the X11 clipboard on Linux.  Elsewhere, or without a display, an
+/// in-memory clipboard stands in so the rest of the client runs unchanged;
+/// nothing is then shared with local applications.
+fn open_clipboard() -> Arc<dyn ClipboardProvider> {
+    #[cfg(target_os = "linux")]
+    {
+        use kvm_client::infrastructure::clipboard::linux::X11ClipboardProvider;
+        match X11ClipboardProvider::open() {
+            Ok(provider) => return Arc::new(provider),
+            Err(e) => warn!("X11 clipboard unavailable, clipboard sharing is local only: {e}"),
+        }
+    }
+    Arc::new(MockClipboardProvider::new())
+}
+
+/// Current wall-clock time in seconds since the Unix epoch.
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
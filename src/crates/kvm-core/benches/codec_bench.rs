@@ -142,6 +142,7 @@ fn make_hello() -> KvmMessage {
         client_name: "benchmark-client".to_string(),
         capabilities: kvm_core::protocol::messages::capabilities::KEYBOARD_EMULATION
             | kvm_core::protocol::messages::capabilities::MOUSE_EMULATION,
+        resume_token: None,
     })
 }
 
@@ -155,6 +156,8 @@ fn make_hello_ack() -> KvmMessage {
         server_version: 1,
         accepted: true,
         reject_reason: 0,
+        capabilities: 0x03,
+        resumed: false,
     })
 }
 
@@ -229,6 +232,7 @@ fn make_pairing_request() -> KvmMessage {
     KvmMessage::PairingRequest(PairingRequestMessage {
         pairing_session_id: Uuid::new_v4(),
         expires_at_secs: 1_700_000_000,
+        spake_message: [0xAB; 32],
     })
 }
 
@@ -236,7 +240,8 @@ fn make_pairing_request() -> KvmMessage {
 fn make_pairing_response() -> KvmMessage {
     KvmMessage::PairingResponse(PairingResponseMessage {
         pairing_session_id: Uuid::new_v4(),
-        pin_hash: "abcdef1234567890abcdef1234567890".to_string(),
+        spake_message: [0xCD; 32],
+        confirmation: [0xEF; 32],
         accepted: true,
     })
 }
@@ -17,6 +17,8 @@
 //! | Windows VK → HID        | Direct array index | < 1 µs         |
 //! | HID → Windows VK        | Linear scan        | < 5 µs         |
 //! | HID → X11 KeySym        | Direct array index | < 1 µs         |
+//! | HID → Linux evdev       | Direct array index | < 1 µs         |
+//! | Linux evdev → HID       | Direct array index | < 1 µs         |
 //! | HID → macOS CGKeyCode   | Direct array index | < 1 µs         |
 //! | HID → DOM code          | Direct array index | < 1 µs         |
 //!
@@ -172,6 +174,34 @@ fn bench_hid_to_windows_vk(c: &mut Criterion) {
     group.finish();
 }
 
+/// A slice of Linux evdev `KEY_*` codes that map to common keys.
+///
+/// These are what the Linux master reads from `/dev/input/event*`.  `0x110`
+/// (`BTN_LEFT`) is above the 256-entry table and exercises the fallback
+/// path that returns `HidKeyCode::Unknown`.
+const BENCH_EVDEV_CODES: &[u16] = &[
+    30,    // KEY_A
+    44,    // KEY_Z
+    28,    // KEY_ENTER
+    1,     // KEY_ESC
+    14,    // KEY_BACKSPACE
+    15,    // KEY_TAB
+    57,    // KEY_SPACE
+    59,    // KEY_F1
+    88,    // KEY_F12
+    29,    // KEY_LEFTCTRL
+    42,    // KEY_LEFTSHIFT
+    56,    // KEY_LEFTALT
+    125,   // KEY_LEFTMETA
+    105,   // KEY_LEFT
+    106,   // KEY_RIGHT
+    103,   // KEY_UP
+    108,   // KEY_DOWN
+    2,     // KEY_1
+    11,    // KEY_0
+    0x110, // BTN_LEFT (returns HidKeyCode::Unknown)
+];
+
 // ── Benchmarks: X11 KeySym translation ───────────────────────────────────────
 
 /// Benchmarks `KeyMapper::hid_to_x11_keysym` for single and batch lookups.
@@ -200,6 +230,47 @@ fn bench_hid_to_x11_keysym(c: &mut Criterion) {
     group.finish();
 }
 
+// ── Benchmarks: Linux evdev translation ──────────────────────────────────────
+
+/// Benchmarks both directions of the Linux evdev table.
+///
+/// The client's uinput emulator uses `hid_to_linux_evdev` for every injected
+/// key, and the Linux master uses `linux_evdev_to_hid` for every captured one.
+/// The reverse direction is an array built at compile time, so both should
+/// cost the same as the other direct lookups.
+fn bench_linux_evdev(c: &mut Criterion) {
+    let mut group = c.benchmark_group("keymap_evdev");
+
+    group.bench_function("hid_to_evdev_single", |b| {
+        b.iter(|| KeyMapper::hid_to_linux_evdev(black_box(HidKeyCode::KeyA)))
+    });
+
+    group.bench_function("hid_to_evdev_batch_20", |b| {
+        b.iter(|| {
+            BENCH_HID_CODES
+                .iter()
+                .map(|&hid| KeyMapper::hid_to_linux_evdev(black_box(hid)))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.bench_function("evdev_to_hid_single", |b| {
+        // 30 = KEY_A.
+        b.iter(|| KeyMapper::linux_evdev_to_hid(black_box(30)))
+    });
+
+    group.bench_function("evdev_to_hid_batch_20", |b| {
+        b.iter(|| {
+            BENCH_EVDEV_CODES
+                .iter()
+                .map(|&code| KeyMapper::linux_evdev_to_hid(black_box(code)))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.finish();
+}
+
 // ── Benchmarks: macOS CGKeyCode translation ───────────────────────────────────
 
 /// Benchmarks `KeyMapper::hid_to_macos_cgkeycode` for single and batch lookups.
@@ -257,7 +328,7 @@ fn bench_hid_to_dom_code(c: &mut Criterion) {
 
 // ── Criterion entry point ─────────────────────────────────────────────────────
 //
-// `criterion_group!` collects all five benchmark functions under the group
+// `criterion_group!` collects all six benchmark functions under the group
 // name `benches`.  `criterion_main!` generates the `main` function.
 
 criterion_group!(
@@ -265,6 +336,7 @@ criterion_group!(
     bench_windows_vk_to_hid,
     bench_hid_to_windows_vk,
     bench_hid_to_x11_keysym,
+    bench_linux_evdev,
     bench_hid_to_macos_cgkeycode,
     bench_hid_to_dom_code,
 );
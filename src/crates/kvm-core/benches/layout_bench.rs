@@ -69,17 +69,17 @@ fn build_layout_with_n_clients(n: usize) -> (VirtualLayout, Vec<Uuid>) {
         let id = Uuid::new_v4();
         ids.push(id);
         layout
-            .add_client(ClientScreen {
-                client_id: id,
-                region: ScreenRegion {
+            .add_client(ClientScreen::new(
+                id,
+                format!("client-{}", i),
+                ScreenRegion {
                     // Each client is 1920 pixels to the right of the previous one.
                     virtual_x: 1920 * (i as i32 + 1),
                     virtual_y: 0,
                     width: 1920,
                     height: 1080,
                 },
-                name: format!("client-{}", i),
-            })
+            ))
             .expect("non-overlapping clients must be added without error");
     }
 
@@ -91,6 +91,7 @@ fn build_layout_with_n_clients(n: usize) -> (VirtualLayout, Vec<Uuid>) {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(ids[0]),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .expect("adjacency must be valid");
 
@@ -102,6 +103,7 @@ fn build_layout_with_n_clients(n: usize) -> (VirtualLayout, Vec<Uuid>) {
                     from_edge: Edge::Right,
                     to_screen: ScreenId::Client(ids[i + 1]),
                     to_edge: Edge::Left,
+                    segment: None,
                 })
                 .expect("adjacency must be valid");
         }
@@ -0,0 +1,276 @@
+//! Splitting clipboard content into `ClipboardData` frames and joining it back.
+//!
+//! # Wire format (for beginners)
+//!
+//! A clipboard transfer is a run of consecutive `ClipboardData` messages with
+//! the same `format`.  Every fragment except the last has
+//! `has_more_fragments = true`:
+//!
+//! ```text
+//! ClipboardData { format: Image, data: [0 .. 64 KiB),       has_more_fragments: true  }
+//! ClipboardData { format: Image, data: [64 KiB .. 128 KiB), has_more_fragments: true  }
+//! ClipboardData { format: Image, data: [128 KiB .. 150 KiB), has_more_fragments: false }
+//! ```
+//!
+//! Fragments travel over the control channel, which is an ordered stream, so
+//! the receiver simply appends them until it sees the last one.  Content that
+//! fits into one fragment (the usual case for text) is a single message with
+//! `has_more_fragments = false`.
+
+use crate::protocol::messages::{ClipboardDataMessage, ClipboardFormat};
+
+use super::{ClipboardContent, ClipboardError};
+
+/// Largest `data` slice carried by one fragment.
+///
+/// Small enough that a clipboard transfer never holds up input events on the
+/// control channel for long, and far below the frame decoder's payload limit.
+pub const FRAGMENT_LEN: usize = 64 * 1024;
+
+/// Default cap on the size of one complete clipboard entry.
+pub const DEFAULT_MAX_CLIPBOARD_LEN: usize = 10 * 1024 * 1024;
+
+/// Splits `content` into `ClipboardData` fragments of at most
+/// [`FRAGMENT_LEN`] bytes each.
+///
+/// Empty content yields a single, empty fragment so the peer still learns
+/// that the clipboard was cleared.
+///
+/// # Errors
+///
+/// Returns [`ClipboardError::TooLarge`] if `content.data` is longer than
+/// `max_len`; nothing is sent in that case.
+pub fn chunk(
+    content: &ClipboardContent,
+    max_len: usize,
+) -> Result<Vec<ClipboardDataMessage>, ClipboardError> {
+    if content.data.len() > max_len {
+        return Err(ClipboardError::TooLarge { limit: max_len });
+    }
+    if content.data.is_empty() {
+        return Ok(vec![ClipboardDataMessage {
+            format: content.format,
+            data: Vec::new(),
+            has_more_fragments: false,
+        }]);
+    }
+
+    let count = content.data.len().div_ceil(FRAGMENT_LEN);
+    Ok(content
+        .data
+        .chunks(FRAGMENT_LEN)
+        .enumerate()
+        .map(|(i, slice)| ClipboardDataMessage {
+            format: content.format,
+            data: slice.to_vec(),
+            has_more_fragments: i + 1 < count,
+        })
+        .collect())
+}
+
+/// Joins `ClipboardData` fragments back into complete [`ClipboardContent`].
+///
+/// Keep one reassembler per connection and feed it every `ClipboardData`
+/// message received on that connection, in order.
+#[derive(Debug)]
+pub struct ClipboardReassembler {
+    max_len: usize,
+    state: State,
+}
+
+#[derive(Debug)]
+enum State {
+    /// No transfer in progress.
+    Idle,
+    /// Collecting the fragments of a transfer.
+    Collecting {
+        format: ClipboardFormat,
+        data: Vec<u8>,
+    },
+    /// The current transfer went over the cap; its remaining fragments are
+    /// dropped until the last one arrives.
+    Discarding,
+}
+
+impl ClipboardReassembler {
+    /// Creates a reassembler that refuses content longer than `max_len`.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            max_len,
+            state: State::Idle,
+        }
+    }
+
+    /// Adds one fragment.
+    ///
+    /// Returns the complete content once the last fragment of a transfer has
+    /// arrived, and `None` while more fragments are expected.
+    ///
+    /// A fragment whose format differs from the transfer in progress means
+    /// the sender abandoned that transfer; the partial data is dropped and a
+    /// new transfer starts with this fragment.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::TooLarge`] as soon as a transfer exceeds the
+    /// cap.  The rest of that transfer is ignored; the next transfer is
+    /// accepted normally.
+    pub fn push(
+        &mut self,
+        fragment: ClipboardDataMessage,
+    ) -> Result<Option<ClipboardContent>, ClipboardError> {
+        let last = !fragment.has_more_fragments;
+        let state = std::mem::replace(&mut self.state, State::Idle);
+
+        let mut data = match state {
+            State::Discarding => {
+                if !last {
+                    self.state = State::Discarding;
+                }
+                return Ok(None);
+            }
+            State::Collecting { format, data } if format == fragment.format => data,
+            State::Collecting { .. } | State::Idle => Vec::new(),
+        };
+
+        if data.len() + fragment.data.len() > self.max_len {
+            if !last {
+                self.state = State::Discarding;
+            }
+            return Err(ClipboardError::TooLarge {
+                limit: self.max_len,
+            });
+        }
+        data.extend_from_slice(&fragment.data);
+
+        if last {
+            return Ok(Some(ClipboardContent {
+                format: fragment.format,
+                data,
+            }));
+        }
+        self.state = State::Collecting {
+            format: fragment.format,
+            data,
+        };
+        Ok(None)
+    }
+}
+
+impl Default for ClipboardReassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CLIPBOARD_LEN)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(len: usize) -> ClipboardContent {
+        ClipboardContent {
+            format: ClipboardFormat::Image,
+            data: (0..len).map(|i| (i % 251) as u8).collect(),
+        }
+    }
+
+    #[test]
+    fn test_small_text_is_a_single_final_fragment() {
+        let fragments = chunk(&ClipboardContent::text("hello"), DEFAULT_MAX_CLIPBOARD_LEN).unwrap();
+
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].data, b"hello");
+        assert!(!fragments[0].has_more_fragments);
+    }
+
+    #[test]
+    fn test_empty_content_still_produces_one_fragment() {
+        let empty = ClipboardContent::text("");
+
+        let fragments = chunk(&empty, DEFAULT_MAX_CLIPBOARD_LEN).unwrap();
+
+        assert_eq!(fragments.len(), 1);
+        let mut reassembler = ClipboardReassembler::default();
+        assert_eq!(reassembler.push(fragments[0].clone()).unwrap(), Some(empty));
+    }
+
+    #[test]
+    fn test_large_content_round_trips_through_fragments() {
+        // Arrange – two full fragments plus a partial one
+        let original = image(2 * FRAGMENT_LEN + 100);
+
+        // Act
+        let fragments = chunk(&original, DEFAULT_MAX_CLIPBOARD_LEN).unwrap();
+        let mut reassembler = ClipboardReassembler::default();
+        let mut results = Vec::new();
+        for fragment in fragments.iter().cloned() {
+            results.push(reassembler.push(fragment).unwrap());
+        }
+
+        // Assert
+        assert_eq!(fragments.len(), 3);
+        assert!(fragments.iter().all(|f| f.data.len() <= FRAGMENT_LEN));
+        assert_eq!(
+            fragments
+                .iter()
+                .map(|f| f.has_more_fragments)
+                .collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+        assert_eq!(results, vec![None, None, Some(original)]);
+    }
+
+    #[test]
+    fn test_chunk_refuses_content_over_the_cap() {
+        assert_eq!(
+            chunk(&image(101), 100),
+            Err(ClipboardError::TooLarge { limit: 100 })
+        );
+    }
+
+    #[test]
+    fn test_reassembler_drops_oversized_transfer_and_recovers() {
+        // Arrange – a cap of one fragment, and a transfer of three
+        let mut reassembler = ClipboardReassembler::new(FRAGMENT_LEN);
+        let oversized = chunk(&image(3 * FRAGMENT_LEN), usize::MAX).unwrap();
+
+        // Act
+        let results: Vec<_> = oversized.into_iter().map(|f| reassembler.push(f)).collect();
+        let next =
+            reassembler.push(chunk(&ClipboardContent::text("ok"), usize::MAX).unwrap()[0].clone());
+
+        // Assert – one error when the cap is crossed, the tail is ignored,
+        // and the following transfer is delivered.
+        assert_eq!(
+            results,
+            vec![
+                Ok(None),
+                Err(ClipboardError::TooLarge {
+                    limit: FRAGMENT_LEN
+                }),
+                Ok(None),
+            ]
+        );
+        assert_eq!(next, Ok(Some(ClipboardContent::text("ok"))));
+    }
+
+    #[test]
+    fn test_format_change_mid_transfer_starts_a_new_transfer() {
+        // Arrange
+        let mut reassembler = ClipboardReassembler::default();
+        let abandoned = chunk(&image(FRAGMENT_LEN + 1), usize::MAX).unwrap();
+
+        // Act
+        reassembler.push(abandoned[0].clone()).unwrap();
+        let result = reassembler.push(ClipboardDataMessage {
+            format: ClipboardFormat::Utf8Text,
+            data: b"new".to_vec(),
+            has_more_fragments: false,
+        });
+
+        // Assert – the partial image is not glued onto the text
+        assert_eq!(result, Ok(Some(ClipboardContent::text("new"))));
+    }
+}
@@ -0,0 +1,272 @@
+//! The canonical encoding of [`ClipboardFormat::Html`] content.
+//!
+//! # Why HTML carries its own text (for beginners)
+//!
+//! When you copy part of a web page, the OS clipboard holds the same
+//! selection twice: as HTML (for editors that keep formatting) and as plain
+//! text (for terminals and text fields).  Sending only the HTML would force
+//! every receiver to guess the text version, and a receiver that only
+//! understands text would get raw markup.  So an `Html` clipboard entry
+//! carries both:
+//!
+//! ```text
+//! +-------------+-------------------------+--------------------------+
+//! | html_len u32|  html (UTF-8, html_len) |  text (UTF-8, the rest)  |
+//! |  big-endian |                         |                          |
+//! +-------------+-------------------------+--------------------------+
+//! ```
+//!
+//! The HTML is the fragment the source application put on the clipboard
+//! (no Windows `CF_HTML` "Version:/StartHTML:" header; providers strip or add
+//! that themselves).  When the source offered no text version, the sender
+//! derives one with [`html_to_text`].
+
+use crate::protocol::messages::ClipboardFormat;
+
+use super::{ClipboardContent, ClipboardError};
+
+/// MIME type of [`ClipboardFormat::Html`] content, as used by X11 and the
+/// browser clipboard API.
+pub const MIME_HTML: &str = "text/html";
+
+/// Length of the `html_len` prefix.
+const LEN_PREFIX: usize = 4;
+
+/// The two representations held by an `Html` clipboard entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlClipboard {
+    /// The HTML markup.
+    pub html: String,
+    /// What to paste where formatting is not supported.
+    pub text: String,
+}
+
+impl HtmlClipboard {
+    /// Pairs `html` with a text version derived from it by [`html_to_text`].
+    pub fn from_html(html: impl Into<String>) -> Self {
+        let html = html.into();
+        let text = html_to_text(&html);
+        Self { html, text }
+    }
+
+    /// Packs both representations into an `Html` clipboard entry.
+    pub fn to_content(&self) -> ClipboardContent {
+        let mut data = Vec::with_capacity(LEN_PREFIX + self.html.len() + self.text.len());
+        data.extend_from_slice(&(self.html.len() as u32).to_be_bytes());
+        data.extend_from_slice(self.html.as_bytes());
+        data.extend_from_slice(self.text.as_bytes());
+        ClipboardContent {
+            format: ClipboardFormat::Html,
+            data,
+        }
+    }
+
+    /// Unpacks an `Html` clipboard entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::Malformed`] if the entry is not `Html`, the
+    /// length prefix points past the end, or either part is not UTF-8.
+    pub fn from_content(content: &ClipboardContent) -> Result<Self, ClipboardError> {
+        let malformed = |reason: &str| ClipboardError::Malformed {
+            format: content.format,
+            reason: reason.to_string(),
+        };
+        if content.format != ClipboardFormat::Html {
+            return Err(malformed("not an HTML clipboard entry"));
+        }
+        if content.data.len() < LEN_PREFIX {
+            return Err(malformed("missing HTML length prefix"));
+        }
+        let (prefix, rest) = content.data.split_at(LEN_PREFIX);
+        let html_len = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        if html_len > rest.len() {
+            return Err(malformed("HTML length exceeds the entry"));
+        }
+        let (html, text) = rest.split_at(html_len);
+        Ok(Self {
+            html: String::from_utf8(html.to_vec()).map_err(|_| malformed("HTML is not UTF-8"))?,
+            text: String::from_utf8(text.to_vec()).map_err(|_| malformed("text is not UTF-8"))?,
+        })
+    }
+}
+
+/// Derives a plain-text version of an HTML fragment.
+///
+/// This is a fallback, not a renderer: tags are dropped, `<script>` and
+/// `<style>` bodies are skipped, block-level elements and `<br>` become line
+/// breaks, runs of whitespace collapse to one space, and the common
+/// character references (`&amp;`, `&#169;`, `&#xA9;`, …) are decoded.
+pub fn html_to_text(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    // Whether whitespace was seen since the last character written.
+    let mut pending_space = false;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            let tag = tag_name(&rest[1..end]);
+            rest = &rest[end..];
+            if tag == "script" || tag == "style" {
+                let closing = format!("</{tag}");
+                rest = find_ignore_case(rest, &closing)
+                    .and_then(|i| rest[i..].find('>').map(|j| &rest[i + j + 1..]))
+                    .unwrap_or("");
+            }
+            if is_line_break(&tag) {
+                while out.ends_with(' ') {
+                    out.pop();
+                }
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                pending_space = false;
+            }
+            continue;
+        }
+
+        let (decoded, len) = if c == '&' {
+            decode_entity(rest).unwrap_or(('&', 1))
+        } else {
+            (c, c.len_utf8())
+        };
+        rest = &rest[len..];
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && !out.is_empty() && !out.ends_with('\n') {
+            out.push(' ');
+        }
+        pending_space = false;
+        out.push(decoded);
+    }
+
+    out.trim_end().to_string()
+}
+
+/// Returns the lower-cased element name of a tag body such as `/P class=x`.
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn is_line_break(tag: &str) -> bool {
+    matches!(
+        tag,
+        "br" | "p"
+            | "div"
+            | "li"
+            | "tr"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "pre"
+            | "blockquote"
+            | "table"
+            | "ul"
+            | "ol"
+    )
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Decodes the character reference at the start of `s` (which begins with
+/// `&`), returning the character and how many bytes it spans.
+fn decode_entity(s: &str) -> Option<(char, usize)> {
+    let end = s.bytes().take(12).position(|b| b == b';')?;
+    let name = &s[1..end];
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+    Some((c, end + 1))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_entry_round_trips() {
+        // Arrange
+        let original = HtmlClipboard {
+            html: "<b>café</b>".to_string(),
+            text: "café".to_string(),
+        };
+
+        // Act
+        let content = original.to_content();
+        let decoded = HtmlClipboard::from_content(&content).unwrap();
+
+        // Assert
+        assert_eq!(content.format, ClipboardFormat::Html);
+        assert_eq!(
+            &content.data[..4],
+            &(original.html.len() as u32).to_be_bytes()
+        );
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn test_length_prefix_past_the_end_is_rejected() {
+        let content = ClipboardContent {
+            format: ClipboardFormat::Html,
+            data: vec![0, 0, 0, 9, b'<', b'b', b'>'],
+        };
+
+        assert!(matches!(
+            HtmlClipboard::from_content(&content),
+            Err(ClipboardError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_text_entry_is_not_html() {
+        assert!(HtmlClipboard::from_content(&ClipboardContent::text("plain")).is_err());
+    }
+
+    #[test]
+    fn test_html_to_text_keeps_words_and_line_structure() {
+        // Arrange
+        let html = "<style>p { color: red }</style><h1>Title</h1>\n<p>Fish &amp; \
+                    <i>chips</i>,&nbsp;&#163;5</p><ul><li>one</li><li>two</li></ul>";
+
+        // Act
+        let text = html_to_text(html);
+
+        // Assert
+        assert_eq!(text, "Title\nFish & chips,\u{a0}£5\none\ntwo");
+    }
+
+    #[test]
+    fn test_unknown_entities_and_stray_ampersands_are_kept() {
+        assert_eq!(html_to_text("a && b &bogus; c"), "a && b &bogus; c");
+    }
+}
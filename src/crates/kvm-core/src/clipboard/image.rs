@@ -0,0 +1,460 @@
+//! The canonical encoding of [`ClipboardFormat::Image`] content: PNG.
+//!
+//! # Why PNG (for beginners)
+//!
+//! Every desktop offers copied images in some native form: Windows uses a
+//! device-independent bitmap (`CF_DIB`), X11 and macOS applications usually
+//! offer `image/png`.  Raw bitmaps of a 4K screenshot are 30+ MB, while PNG
+//! is lossless, compresses screenshots well, and every platform can read and
+//! write it.  So the wire always carries PNG, and each provider converts
+//! to and from its native form with the helpers here:
+//!
+//! ```text
+//! Windows CF_DIB ──RgbaImage::from_dib──▶ RgbaImage ──to_png──▶ wire (PNG)
+//! wire (PNG) ──RgbaImage::from_png──▶ RgbaImage ──to_dib──▶ Windows CF_DIB
+//! X11 image/png ──────────────── ClipboardContent::png ──────▶ wire (PNG)
+//! ```
+//!
+//! # Limits
+//!
+//! A PNG is a few kilobytes of compressed data that can expand to gigabytes
+//! of pixels.  Decoding therefore checks the header against
+//! [`MAX_IMAGE_DIMENSION`] and [`MAX_IMAGE_PIXELS`] before allocating
+//! anything, on top of the byte cap every clipboard transfer has.
+//!
+//! # The PNG codec
+//!
+//! Encoding and decoding use the [`png`] crate (whose zlib layer is
+//! `miniz_oxide`), behind the `clipboard-image` feature.  It reads every
+//! PNG, interlaced or not, at any bit depth and colour type, including
+//! `tRNS` transparency; ancillary chunks (colour profiles, text, …) are
+//! ignored.  Without the feature the size limits and [`inspect_png`] are
+//! still available, so PNGs from the OS clipboard can be checked and
+//! forwarded as they are.
+//!
+//! [`png`]: https://docs.rs/png
+
+use crate::protocol::messages::ClipboardFormat;
+
+use super::{ClipboardContent, ClipboardError};
+
+/// MIME type of [`ClipboardFormat::Image`] content.
+pub const MIME_PNG: &str = "image/png";
+
+/// Largest width or height accepted for a clipboard image.
+pub const MAX_IMAGE_DIMENSION: u32 = 16_384;
+
+/// Largest pixel count accepted for a clipboard image; enough for an 8K
+/// (7680 × 4320) screenshot.
+pub const MAX_IMAGE_PIXELS: u64 = 1 << 25;
+
+/// The eight bytes every PNG file starts with.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Most memory the PNG decoder may allocate: the largest image at 16 bits
+/// per channel.
+#[cfg(feature = "clipboard-image")]
+const MAX_DECODE_BYTES: u64 = MAX_IMAGE_PIXELS * 8;
+
+/// Size of a Windows `BITMAPINFOHEADER`.
+const BITMAPINFOHEADER_LEN: usize = 40;
+/// `biCompression` values.
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+/// An uncompressed image with 8-bit red, green, blue and alpha channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    /// Rows top to bottom, four bytes (R, G, B, A) per pixel.
+    pixels: Vec<u8>,
+}
+
+/// Width and height read from a PNG header without decoding the pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngInfo {
+    pub width: u32,
+    pub height: u32,
+}
+
+fn malformed(reason: impl Into<String>) -> ClipboardError {
+    ClipboardError::Malformed {
+        format: ClipboardFormat::Image,
+        reason: reason.into(),
+    }
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<(), ClipboardError> {
+    if width == 0 || height == 0 {
+        return Err(malformed("image has no pixels"));
+    }
+    if width > MAX_IMAGE_DIMENSION
+        || height > MAX_IMAGE_DIMENSION
+        || u64::from(width) * u64::from(height) > MAX_IMAGE_PIXELS
+    {
+        return Err(ClipboardError::ImageTooLarge { width, height });
+    }
+    Ok(())
+}
+
+impl RgbaImage {
+    /// Wraps `pixels` (top-down rows, RGBA order).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::Malformed`] if `pixels` is not exactly
+    /// `width * height * 4` bytes, or [`ClipboardError::ImageTooLarge`] if
+    /// the size is over the limits.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, ClipboardError> {
+        check_dimensions(width, height)?;
+        if pixels.len() as u64 != u64::from(width) * u64::from(height) * 4 {
+            return Err(malformed("pixel buffer does not match the dimensions"));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The pixel bytes: top-down rows, RGBA order.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Encodes the image as PNG.
+    ///
+    /// Fully opaque images are stored as RGB, which saves a quarter of the
+    /// data for typical screenshots.
+    #[cfg(feature = "clipboard-image")]
+    pub fn to_png(&self) -> Vec<u8> {
+        let opaque = self.pixels.chunks_exact(4).all(|px| px[3] == 0xFF);
+        let data: Vec<u8> = if opaque {
+            self.pixels
+                .chunks_exact(4)
+                .flat_map(|px| [px[0], px[1], px[2]])
+                .collect()
+        } else {
+            self.pixels.clone()
+        };
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+        encoder.set_color(if opaque {
+            png::ColorType::Rgb
+        } else {
+            png::ColorType::Rgba
+        });
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+        // The only failures are I/O errors and a buffer that does not match
+        // the header, and neither can happen writing our own pixels to memory.
+        let mut writer = encoder
+            .write_header()
+            .expect("PNG header for a valid image");
+        writer
+            .write_image_data(&data)
+            .expect("pixel buffer matches the header");
+        writer.finish().expect("PNG written to memory");
+        png
+    }
+
+    /// Decodes a PNG file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::ImageTooLarge`] if the header announces an
+    /// image over the limits, and [`ClipboardError::Malformed`] if the file
+    /// is corrupt.
+    #[cfg(feature = "clipboard-image")]
+    pub fn from_png(png: &[u8]) -> Result<Self, ClipboardError> {
+        inspect_png(png)?;
+        let limits = png::Limits {
+            bytes: usize::try_from(MAX_DECODE_BYTES).unwrap_or(usize::MAX),
+        };
+        let mut decoder = png::Decoder::new_with_limits(png, limits);
+        // Palettes, `tRNS` and low bit depths are expanded and 16-bit samples
+        // reduced, so every image arrives as 8-bit grey or RGB, with or
+        // without alpha.
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|e| malformed(e.to_string()))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let frame = reader
+            .next_frame(&mut buf)
+            .map_err(|e| malformed(e.to_string()))?;
+        let data = &buf[..frame.buffer_size()];
+
+        let pixels: Vec<u8> = match frame.color_type {
+            png::ColorType::Rgba => data.to_vec(),
+            png::ColorType::Rgb => data
+                .chunks_exact(3)
+                .flat_map(|px| [px[0], px[1], px[2], 0xFF])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks_exact(2)
+                .flat_map(|px| [px[0], px[0], px[0], px[1]])
+                .collect(),
+            png::ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 0xFF]).collect(),
+            png::ColorType::Indexed => return Err(malformed("palette was not expanded")),
+        };
+        Self::new(frame.width, frame.height, pixels)
+    }
+
+    /// Reads a Windows device-independent bitmap (`CF_DIB` / `CF_DIBV5`
+    /// clipboard data: a `BITMAPINFOHEADER` or later header followed by the
+    /// pixels).
+    ///
+    /// 24- and 32-bit uncompressed bitmaps are supported, bottom-up or
+    /// top-down.  A 32-bit bitmap whose alpha bytes are all zero is treated
+    /// as opaque, because most applications leave that byte unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::Malformed`] for truncated data or palette,
+    /// RLE and non-standard bit-field bitmaps.
+    pub fn from_dib(dib: &[u8]) -> Result<Self, ClipboardError> {
+        let u16_at = |i: usize| u16::from_le_bytes([dib[i], dib[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([dib[i], dib[i + 1], dib[i + 2], dib[i + 3]]);
+        if dib.len() < BITMAPINFOHEADER_LEN {
+            return Err(malformed("DIB header is truncated"));
+        }
+        let header_len = u32_at(0) as usize;
+        if header_len < BITMAPINFOHEADER_LEN || header_len > dib.len() {
+            return Err(malformed("unsupported DIB header"));
+        }
+        let width = u32_at(4) as i32;
+        let raw_height = u32_at(8) as i32;
+        let bit_count = u16_at(14);
+        let compression = u32_at(16);
+        if width <= 0 || raw_height == 0 || raw_height == i32::MIN {
+            return Err(malformed("invalid DIB dimensions"));
+        }
+        let (width, height) = (width as u32, raw_height.unsigned_abs());
+        check_dimensions(width, height)?;
+
+        let mut pixel_offset = header_len;
+        match (bit_count, compression) {
+            (24, BI_RGB) | (32, BI_RGB) => {}
+            (32, BI_BITFIELDS) => {
+                // V1 headers are followed by three masks; later versions
+                // carry them inside the header.
+                if header_len == BITMAPINFOHEADER_LEN {
+                    pixel_offset += 12;
+                }
+                let at = BITMAPINFOHEADER_LEN;
+                if dib.len() < at + 12 {
+                    return Err(malformed("DIB masks are truncated"));
+                }
+                let masks = [u32_at(at), u32_at(at + 4), u32_at(at + 8)];
+                if masks != [0x00FF_0000, 0x0000_FF00, 0x0000_00FF] {
+                    return Err(malformed("unsupported DIB bit-field layout"));
+                }
+            }
+            _ => {
+                return Err(malformed(
+                    "only 24- and 32-bit uncompressed DIBs are supported",
+                ))
+            }
+        }
+
+        let bytes_per_pixel = usize::from(bit_count / 8);
+        let stride = (width as usize * bytes_per_pixel).next_multiple_of(4);
+        let data = dib
+            .get(pixel_offset..)
+            .filter(|d| d.len() >= stride * height as usize)
+            .ok_or_else(|| malformed("DIB pixel data is truncated"))?;
+
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height as usize {
+            // Positive heights mean the last row is stored first.
+            let source_row = if raw_height > 0 {
+                height as usize - 1 - y
+            } else {
+                y
+            };
+            let row = &data[source_row * stride..][..width as usize * bytes_per_pixel];
+            for px in row.chunks_exact(bytes_per_pixel) {
+                let alpha = if bytes_per_pixel == 4 { px[3] } else { 0xFF };
+                pixels.extend_from_slice(&[px[2], px[1], px[0], alpha]);
+            }
+        }
+        if bytes_per_pixel == 4 && pixels.chunks_exact(4).all(|px| px[3] == 0) {
+            pixels.chunks_exact_mut(4).for_each(|px| px[3] = 0xFF);
+        }
+        Self::new(width, height, pixels)
+    }
+
+    /// Writes the image as a Windows device-independent bitmap suitable for
+    /// `CF_DIB`: a `BITMAPINFOHEADER`, then 32-bit BGRA rows bottom-up.
+    pub fn to_dib(&self) -> Vec<u8> {
+        let image_len = self.pixels.len() as u32;
+        let mut dib = Vec::with_capacity(BITMAPINFOHEADER_LEN + self.pixels.len());
+        dib.extend_from_slice(&(BITMAPINFOHEADER_LEN as u32).to_le_bytes());
+        dib.extend_from_slice(&(self.width as i32).to_le_bytes());
+        dib.extend_from_slice(&(self.height as i32).to_le_bytes());
+        dib.extend_from_slice(&1u16.to_le_bytes()); // planes
+        dib.extend_from_slice(&32u16.to_le_bytes()); // bits per pixel
+        dib.extend_from_slice(&BI_RGB.to_le_bytes());
+        dib.extend_from_slice(&image_len.to_le_bytes());
+        // Resolution (unset) and palette sizes (none).
+        dib.extend_from_slice(&[0u8; 16]);
+        for row in self.pixels.chunks_exact(self.width as usize * 4).rev() {
+            for px in row.chunks_exact(4) {
+                dib.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+            }
+        }
+        dib
+    }
+}
+
+impl ClipboardContent {
+    /// Creates an image clipboard entry by encoding `image` as PNG.
+    #[cfg(feature = "clipboard-image")]
+    pub fn image(image: &RgbaImage) -> Self {
+        Self {
+            format: ClipboardFormat::Image,
+            data: image.to_png(),
+        }
+    }
+
+    /// Creates an image clipboard entry from PNG bytes another application
+    /// produced, after checking that they are a PNG within the size limits.
+    ///
+    /// # Errors
+    ///
+    /// See [`inspect_png`].
+    pub fn png(png: Vec<u8>) -> Result<Self, ClipboardError> {
+        inspect_png(&png)?;
+        Ok(Self {
+            format: ClipboardFormat::Image,
+            data: png,
+        })
+    }
+}
+
+/// Reads a PNG's dimensions and checks them against the limits without
+/// decompressing the pixels, so it is cheap enough to run on every entry.
+///
+/// # Errors
+///
+/// Returns [`ClipboardError::Malformed`] if `png` does not start with a valid
+/// PNG signature and `IHDR` chunk, or [`ClipboardError::ImageTooLarge`].
+pub fn inspect_png(png: &[u8]) -> Result<PngInfo, ClipboardError> {
+    // Signature (8) + IHDR length and type (8) + IHDR data (13) + CRC (4).
+    let ihdr = png
+        .get(..33)
+        .filter(|head| head[..8] == PNG_SIGNATURE && &head[12..16] == b"IHDR")
+        .ok_or_else(|| malformed("not a PNG file"))?;
+    let width = u32::from_be_bytes([ihdr[16], ihdr[17], ihdr[18], ihdr[19]]);
+    let height = u32::from_be_bytes([ihdr[20], ihdr[21], ihdr[22], ihdr[23]]);
+    check_dimensions(width, height)?;
+    Ok(PngInfo { width, height })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3×2 image with transparency and every channel varying.
+    fn sample_image() -> RgbaImage {
+        RgbaImage::new(
+            3,
+            2,
+            vec![
+                255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, //
+                10, 20, 30, 0, 40, 50, 60, 128, 255, 255, 255, 255,
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard-image")]
+    fn test_png_round_trip_preserves_alpha() {
+        let image = sample_image();
+
+        let decoded = RgbaImage::from_png(&image.to_png()).unwrap();
+
+        assert_eq!(decoded, image);
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard-image")]
+    fn test_opaque_image_is_stored_as_rgb() {
+        // Arrange
+        let image = RgbaImage::new(2, 1, vec![1, 2, 3, 255, 4, 5, 6, 255]).unwrap();
+
+        // Act
+        let png = image.to_png();
+
+        // Assert – IHDR colour type byte is 2 (RGB)
+        assert_eq!(png[25], 2);
+        assert_eq!(RgbaImage::from_png(&png).unwrap(), image);
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard-image")]
+    fn test_oversized_header_is_refused_before_decoding() {
+        // Arrange – a valid PNG whose IHDR claims 20000×20000
+        let mut png = sample_image().to_png();
+        png[16..20].copy_from_slice(&20_000u32.to_be_bytes());
+        png[20..24].copy_from_slice(&20_000u32.to_be_bytes());
+
+        // Act / Assert
+        assert_eq!(
+            inspect_png(&png),
+            Err(ClipboardError::ImageTooLarge {
+                width: 20_000,
+                height: 20_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_dib_round_trip() {
+        let image = sample_image();
+
+        let dib = image.to_dib();
+
+        assert_eq!(dib.len(), 40 + 3 * 2 * 4);
+        assert_eq!(RgbaImage::from_dib(&dib).unwrap(), image);
+    }
+
+    #[test]
+    fn test_24_bit_bottom_up_dib_with_row_padding() {
+        // Arrange – 1×2 image: rows are 3 bytes padded to 4, bottom row first
+        let mut dib = vec![0u8; 40];
+        dib[0] = 40;
+        dib[4] = 1; // width
+        dib[8] = 2; // height (positive: bottom-up)
+        dib[12] = 1; // planes
+        dib[14] = 24; // bits per pixel
+        dib.extend_from_slice(&[0x00, 0x00, 0xFF, 0]); // bottom row: red (BGR)
+        dib.extend_from_slice(&[0xFF, 0x00, 0x00, 0]); // top row: blue
+
+        // Act
+        let image = RgbaImage::from_dib(&dib).unwrap();
+
+        // Assert
+        assert_eq!(image.pixels(), &[0, 0, 255, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_png_content_constructor_rejects_non_png_bytes() {
+        assert!(matches!(
+            ClipboardContent::png(b"GIF89a".to_vec()),
+            Err(ClipboardError::Malformed { .. })
+        ));
+    }
+}
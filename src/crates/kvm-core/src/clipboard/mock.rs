@@ -0,0 +1,58 @@
+//! In-memory [`ClipboardProvider`] for tests.
+//!
+//! The mock holds a single clipboard entry and records every write, so tests
+//! can both seed what the "OS clipboard" contains and assert on what was
+//! pasted into it.  Set `should_fail` to simulate an unavailable clipboard.
+
+use std::sync::Mutex;
+
+use super::{ClipboardContent, ClipboardError, ClipboardProvider};
+
+/// A clipboard that lives in memory.
+#[derive(Debug, Default)]
+pub struct MockClipboardProvider {
+    /// The current clipboard content returned by `read`.
+    pub content: Mutex<Option<ClipboardContent>>,
+    /// Every entry passed to `write`, in order.
+    pub writes: Mutex<Vec<ClipboardContent>>,
+    /// When `true`, every call fails with [`ClipboardError::Unavailable`].
+    pub should_fail: bool,
+}
+
+impl MockClipboardProvider {
+    /// Creates an empty mock clipboard.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mock clipboard that already holds `content`.
+    pub fn with_content(content: ClipboardContent) -> Self {
+        Self {
+            content: Mutex::new(Some(content)),
+            ..Self::default()
+        }
+    }
+
+    /// Replaces the content as if the user had copied something locally.
+    pub fn set(&self, content: ClipboardContent) {
+        *self.content.lock().unwrap() = Some(content);
+    }
+}
+
+impl ClipboardProvider for MockClipboardProvider {
+    fn read(&self) -> Result<Option<ClipboardContent>, ClipboardError> {
+        if self.should_fail {
+            return Err(ClipboardError::Unavailable("injected failure".to_string()));
+        }
+        Ok(self.content.lock().unwrap().clone())
+    }
+
+    fn write(&self, content: &ClipboardContent) -> Result<(), ClipboardError> {
+        if self.should_fail {
+            return Err(ClipboardError::Unavailable("injected failure".to_string()));
+        }
+        *self.content.lock().unwrap() = Some(content.clone());
+        self.writes.lock().unwrap().push(content.clone());
+        Ok(())
+    }
+}
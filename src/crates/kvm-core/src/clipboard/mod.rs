@@ -0,0 +1,253 @@
+//! Clipboard sharing: the platform abstraction and the wire fragmentation.
+//!
+//! # How clipboard sharing works (for beginners)
+//!
+//! Each machine has its own OS clipboard.  To make "copy on one machine,
+//! paste on another" work, the master acts as a hub:
+//!
+//! - When the cursor leaves the master (or one client) for a client, the
+//!   master reads its clipboard through a [`ClipboardProvider`] and sends the
+//!   content to the client that just received focus.
+//! - Clients watch their own clipboard and send every change to the master,
+//!   which writes it into its clipboard.  The next focus change carries it on
+//!   to the next client.
+//!
+//! Clipboard content can be far larger than a single protocol frame should
+//! be (think of a copied image), so it is split into `ClipboardData`
+//! fragments with [`chunk`] and put back together with a
+//! [`ClipboardReassembler`].  Both sides enforce a size cap so a peer cannot
+//! make us buffer an unbounded amount of memory.
+//!
+//! # Formats
+//!
+//! Every platform names clipboard formats differently, so the wire uses one
+//! canonical encoding per [`ClipboardFormat`] and providers convert at the
+//! edge:
+//!
+//! | Format     | MIME type                  | Bytes                                  |
+//! |------------|----------------------------|----------------------------------------|
+//! | `Utf8Text` | `text/plain;charset=utf-8` | UTF-8 text                             |
+//! | `Html`     | `text/html`                | HTML plus plain-text fallback ([`html`]) |
+//! | `Image`    | `image/png`                | a PNG file ([`image`])                 |
+//!
+//! Rich formats need their own capability bits on top of
+//! `CLIPBOARD_SHARING`.  Before sending, [`ClipboardContent::for_peer`]
+//! downgrades an entry to what the peer negotiated: HTML becomes its text
+//! fallback, and an image is not sent to a text-only peer at all.
+//!
+//! This module contains no OS code.  The real providers (X11, …) live in the
+//! applications' infrastructure layers; [`mock::MockClipboardProvider`] is an
+//! in-memory provider for tests.
+
+pub mod fragment;
+pub mod html;
+pub mod image;
+pub mod mock;
+
+pub use fragment::{chunk, ClipboardReassembler, DEFAULT_MAX_CLIPBOARD_LEN, FRAGMENT_LEN};
+pub use html::{html_to_text, HtmlClipboard, MIME_HTML};
+pub use image::{inspect_png, RgbaImage, MAX_IMAGE_DIMENSION, MAX_IMAGE_PIXELS, MIME_PNG};
+
+use thiserror::Error;
+
+use crate::protocol::messages::{capabilities, ClipboardFormat};
+
+/// MIME type of [`ClipboardFormat::Utf8Text`] content.
+pub const MIME_TEXT: &str = "text/plain;charset=utf-8";
+
+/// Returns the MIME type of the canonical encoding of `format`.
+pub fn mime_type(format: ClipboardFormat) -> &'static str {
+    match format {
+        ClipboardFormat::Utf8Text => MIME_TEXT,
+        ClipboardFormat::Html => MIME_HTML,
+        ClipboardFormat::Image => MIME_PNG,
+    }
+}
+
+/// Returns the format whose canonical encoding has MIME type `mime`.
+///
+/// Parameters other than `charset=utf-8` and letter case are ignored, so
+/// `text/plain`, `TEXT/HTML; charset=UTF-8` and `image/png` all match.
+pub fn format_for_mime(mime: &str) -> Option<ClipboardFormat> {
+    let essence = mime.split(';').next().unwrap_or("").trim();
+    if essence.eq_ignore_ascii_case("text/plain") {
+        Some(ClipboardFormat::Utf8Text)
+    } else if essence.eq_ignore_ascii_case(MIME_HTML) {
+        Some(ClipboardFormat::Html)
+    } else if essence.eq_ignore_ascii_case(MIME_PNG) {
+        Some(ClipboardFormat::Image)
+    } else {
+        None
+    }
+}
+
+/// Returns the capability bits a peer must have negotiated to receive
+/// clipboard content in `format`.
+pub fn required_capabilities(format: ClipboardFormat) -> u32 {
+    match format {
+        ClipboardFormat::Utf8Text => capabilities::CLIPBOARD_SHARING,
+        ClipboardFormat::Html => capabilities::CLIPBOARD_SHARING | capabilities::CLIPBOARD_HTML,
+        ClipboardFormat::Image => capabilities::CLIPBOARD_SHARING | capabilities::CLIPBOARD_IMAGE,
+    }
+}
+
+/// One complete clipboard entry: its format and its bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardContent {
+    /// How [`data`](Self::data) should be interpreted.
+    pub format: ClipboardFormat,
+    /// The bytes, in the canonical encoding of `format` (see the module
+    /// docs).
+    pub data: Vec<u8>,
+}
+
+impl ClipboardContent {
+    /// Creates a plain-text clipboard entry.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            format: ClipboardFormat::Utf8Text,
+            data: text.into().into_bytes(),
+        }
+    }
+
+    /// Returns the richest form of this entry that a peer with the
+    /// negotiated `peer_capabilities` can receive, or `None` if it can
+    /// receive none.
+    ///
+    /// - Content the peer supports is returned unchanged.
+    /// - HTML falls back to its plain-text part.
+    /// - Images have no text form and are not sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::Malformed`] if HTML content has to be
+    /// downgraded but cannot be unpacked.
+    pub fn for_peer(&self, peer_capabilities: u32) -> Result<Option<Self>, ClipboardError> {
+        let supports = |format| {
+            let required = required_capabilities(format);
+            peer_capabilities & required == required
+        };
+        if supports(self.format) {
+            return Ok(Some(self.clone()));
+        }
+        match self.format {
+            ClipboardFormat::Html if supports(ClipboardFormat::Utf8Text) => {
+                Ok(Some(Self::text(HtmlClipboard::from_content(self)?.text)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Errors produced by clipboard providers and by fragment reassembly.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ClipboardError {
+    /// The OS clipboard could not be accessed (no display server, the owner
+    /// did not answer in time, …).
+    #[error("clipboard unavailable: {0}")]
+    Unavailable(String),
+
+    /// The provider cannot store or produce content in this format.
+    #[error("clipboard format {0:?} is not supported by this provider")]
+    UnsupportedFormat(ClipboardFormat),
+
+    /// The content is larger than the configured cap.
+    #[error("clipboard content exceeds the {limit}-byte limit")]
+    TooLarge { limit: usize },
+
+    /// The bytes do not follow the canonical encoding of their format.
+    #[error("malformed {format:?} clipboard content: {reason}")]
+    Malformed {
+        format: ClipboardFormat,
+        reason: String,
+    },
+
+    /// An image is wider, taller or has more pixels than the limits allow.
+    #[error("clipboard image of {width}x{height} pixels exceeds the size limits")]
+    ImageTooLarge { width: u32, height: u32 },
+}
+
+/// Reads and writes the local OS clipboard.
+///
+/// Implementations must be cheap to call repeatedly: clients poll
+/// [`read`](Self::read) to notice local changes.
+pub trait ClipboardProvider: Send + Sync {
+    /// Returns the current clipboard content, or `None` if the clipboard is
+    /// empty or holds nothing this provider understands.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::Unavailable`] if the clipboard cannot be read.
+    fn read(&self) -> Result<Option<ClipboardContent>, ClipboardError>;
+
+    /// Replaces the clipboard content.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClipboardError::UnsupportedFormat`] for formats the provider
+    /// cannot store, or [`ClipboardError::Unavailable`] if the clipboard
+    /// cannot be written.
+    fn write(&self, content: &ClipboardContent) -> Result<(), ClipboardError>;
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT_ONLY: u32 = capabilities::CLIPBOARD_SHARING;
+    const ALL: u32 = capabilities::CLIPBOARD_SHARING
+        | capabilities::CLIPBOARD_HTML
+        | capabilities::CLIPBOARD_IMAGE;
+
+    fn html() -> ClipboardContent {
+        HtmlClipboard {
+            html: "<b>bold</b> move".to_string(),
+            text: "bold move".to_string(),
+        }
+        .to_content()
+    }
+
+    #[test]
+    fn test_rich_content_is_kept_for_a_capable_peer() {
+        assert_eq!(html().for_peer(ALL), Ok(Some(html())));
+    }
+
+    #[test]
+    fn test_html_falls_back_to_its_text_for_a_text_only_peer() {
+        assert_eq!(
+            html().for_peer(TEXT_ONLY),
+            Ok(Some(ClipboardContent::text("bold move")))
+        );
+    }
+
+    #[test]
+    fn test_image_is_not_sent_to_a_text_only_peer() {
+        // Arrange
+        let image = ClipboardContent {
+            format: ClipboardFormat::Image,
+            data: Vec::new(),
+        };
+
+        // Act / Assert
+        assert_eq!(image.for_peer(TEXT_ONLY), Ok(None));
+        assert_eq!(ClipboardContent::text("x").for_peer(0), Ok(None));
+    }
+
+    #[test]
+    fn test_mime_types_map_to_formats_and_back() {
+        for format in [
+            ClipboardFormat::Utf8Text,
+            ClipboardFormat::Html,
+            ClipboardFormat::Image,
+        ] {
+            assert_eq!(format_for_mime(mime_type(format)), Some(format));
+        }
+        assert_eq!(
+            format_for_mime("TEXT/HTML; charset=UTF-8"),
+            Some(ClipboardFormat::Html)
+        );
+        assert_eq!(format_for_mime("image/jpeg"), None);
+    }
+}
@@ -0,0 +1,437 @@
+//! Sealing and opening datagrams on the UDP input channel.
+//!
+//! # Why a second channel? (for beginners)
+//!
+//! The control channel is a single TCP stream.  TCP delivers bytes strictly in
+//! order, so one lost packet holds back everything queued behind it until it
+//! has been retransmitted ("head-of-line blocking").  That is fine for a
+//! clipboard transfer but makes the remote cursor stutter.  High-frequency
+//! input therefore may travel over UDP instead, where a lost mouse movement is
+//! simply superseded by the next one.
+//!
+//! UDP has no TLS, so this module provides the protection itself:
+//!
+//! - **Encryption and authentication** with ChaCha20-Poly1305.  The keys are
+//!   derived (HKDF-SHA-256) from the 32-byte session token the master issued in
+//!   the `HelloAck`, which only travelled inside the TLS control channel.  Each
+//!   direction has its own key.
+//! - **Anti-replay**: every datagram carries the sequence number from the KVM
+//!   header.  It doubles as the AEAD nonce and is checked against a
+//!   [`ReplayWindow`], so a captured datagram cannot be injected again.
+//!
+//! # Datagram layout
+//!
+//! ```text
+//! ┌───────────────┬──────────────────┬──────────────────────────────┬─────────┐
+//! │ client_id: 16 │ sequence: 8 (BE) │ encrypted KVM frame (header  │ tag: 16 │
+//! │               │                  │ + payload, as on TCP)        │         │
+//! └───────────────┴──────────────────┴──────────────────────────────┴─────────┘
+//!  └─────── additional authenticated data ──────┘
+//! ```
+//!
+//! The client ID is sent in the clear so the master can find the right key.
+//! After decryption the sequence number inside the KVM header must equal the
+//! one in front of it.
+
+use hkdf::Hkdf;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use sha2::Sha256;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::protocol::codec::{decode_message, encode_message_with_version, ProtocolError};
+use crate::protocol::messages::{KvmMessage, HEADER_SIZE, PROTOCOL_VERSION};
+
+/// Bytes in front of the ciphertext: client ID and sequence number.
+pub const DATAGRAM_PREFIX_LEN: usize = 16 + 8;
+
+/// Size of the Poly1305 authentication tag.
+pub const TAG_LEN: usize = 16;
+
+/// Bytes a sealed datagram adds to the encoded KVM frame.
+pub const DATAGRAM_OVERHEAD: usize = DATAGRAM_PREFIX_LEN + TAG_LEN;
+
+/// Largest datagram either side sends or accepts.  Keeps input datagrams
+/// below a typical 1500-byte Ethernet MTU so they are never fragmented.
+pub const MAX_DATAGRAM_LEN: usize = 1200;
+
+/// HKDF salt; changing it makes old and new implementations incompatible.
+const KEY_SALT: &[u8] = b"kvm-over-ip/input-channel/v1";
+
+/// Errors from opening or sealing a datagram.
+#[derive(Debug, Error, PartialEq)]
+pub enum DatagramError {
+    /// The datagram is too short to contain a prefix, a header and a tag.
+    #[error("datagram too short ({0} bytes)")]
+    Truncated(usize),
+
+    /// The sealed datagram would exceed [`MAX_DATAGRAM_LEN`].
+    #[error("datagram of {0} bytes exceeds the {MAX_DATAGRAM_LEN}-byte limit")]
+    TooLarge(usize),
+
+    /// The datagram names a different client than this channel's.
+    #[error("datagram is for client {0}, not this channel")]
+    WrongClient(Uuid),
+
+    /// Decryption failed: the datagram was forged, corrupted, or sealed with
+    /// another session's key.
+    #[error("datagram failed authentication")]
+    Authentication,
+
+    /// The authenticated KVM header carries a different sequence number than
+    /// the datagram prefix.
+    #[error("sequence number in the KVM header does not match the datagram")]
+    SequenceMismatch,
+
+    /// The decrypted frame is not a valid KVM message.
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+}
+
+/// Which end of the channel we are.  Determines which key seals and which
+/// opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Master,
+    Client,
+}
+
+/// Returns the client ID in front of a datagram without authenticating it.
+///
+/// The master uses it to look up the session whose key can open the datagram.
+pub fn peek_client_id(datagram: &[u8]) -> Option<Uuid> {
+    let bytes: [u8; 16] = datagram.get(..16)?.try_into().ok()?;
+    Some(Uuid::from_bytes(bytes))
+}
+
+/// The keys of one input channel, bound to one control-channel session.
+pub struct InputChannelCipher {
+    client_id: Uuid,
+    /// Protocol version written into sealed frames.
+    version: u8,
+    seal_key: LessSafeKey,
+    open_key: LessSafeKey,
+}
+
+impl InputChannelCipher {
+    /// Derives the channel keys from the session token issued in `HelloAck`.
+    ///
+    /// Master and client call this with the same token and client ID but
+    /// opposite `endpoint`s; what one seals, the other opens.
+    pub fn new(session_token: &[u8; 32], client_id: Uuid, endpoint: Endpoint) -> Self {
+        let hk = Hkdf::<Sha256>::new(Some(KEY_SALT), session_token);
+        let key = |label: &[u8]| {
+            let mut okm = [0u8; 32];
+            let mut info = label.to_vec();
+            info.extend_from_slice(client_id.as_bytes());
+            hk.expand(&info, &mut okm)
+                .expect("32 bytes is a valid HKDF-SHA-256 output length");
+            let unbound = UnboundKey::new(&CHACHA20_POLY1305, &okm)
+                .expect("ChaCha20-Poly1305 takes a 32-byte key");
+            LessSafeKey::new(unbound)
+        };
+        let to_client = key(b"master to client");
+        let to_master = key(b"client to master");
+        let (seal_key, open_key) = match endpoint {
+            Endpoint::Master => (to_client, to_master),
+            Endpoint::Client => (to_master, to_client),
+        };
+        Self {
+            client_id,
+            version: PROTOCOL_VERSION,
+            seal_key,
+            open_key,
+        }
+    }
+
+    /// Seals frames with the protocol version the session negotiated instead
+    /// of [`PROTOCOL_VERSION`].
+    pub fn with_version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Encodes `msg` with `sequence` and seals it into a datagram.
+    ///
+    /// `sequence` must never repeat for this cipher: it is the nonce.  Draw it
+    /// from a [`SequenceCounter`](crate::protocol::SequenceCounter) owned by
+    /// the channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatagramError::Protocol`] if the message cannot be encoded
+    /// and [`DatagramError::TooLarge`] if it does not fit in one datagram.
+    pub fn seal(
+        &self,
+        msg: &KvmMessage,
+        sequence: u64,
+        timestamp_us: u64,
+    ) -> Result<Vec<u8>, DatagramError> {
+        let frame = encode_message_with_version(msg, self.version, sequence, timestamp_us)?;
+        let total = DATAGRAM_OVERHEAD + frame.len();
+        if total > MAX_DATAGRAM_LEN {
+            return Err(DatagramError::TooLarge(total));
+        }
+
+        let mut datagram = Vec::with_capacity(total);
+        datagram.extend_from_slice(self.client_id.as_bytes());
+        datagram.extend_from_slice(&sequence.to_be_bytes());
+        let mut body = frame;
+        self.seal_key
+            .seal_in_place_append_tag(
+                nonce(sequence),
+                Aad::from(&datagram[..DATAGRAM_PREFIX_LEN]),
+                &mut body,
+            )
+            .expect("ChaCha20-Poly1305 sealing cannot fail for in-limit lengths");
+        datagram.extend_from_slice(&body);
+        Ok(datagram)
+    }
+
+    /// Authenticates and decrypts a datagram sealed by the other endpoint.
+    ///
+    /// Returns the sequence number and the message.  The caller must still
+    /// check the sequence number against its [`ReplayWindow`]; only do so
+    /// after this succeeds, so forged datagrams cannot move the window.
+    ///
+    /// # Errors
+    ///
+    /// See [`DatagramError`].
+    pub fn open(&self, datagram: &[u8]) -> Result<(u64, KvmMessage), DatagramError> {
+        if datagram.len() < DATAGRAM_OVERHEAD + HEADER_SIZE {
+            return Err(DatagramError::Truncated(datagram.len()));
+        }
+        if datagram.len() > MAX_DATAGRAM_LEN {
+            return Err(DatagramError::TooLarge(datagram.len()));
+        }
+        let client_id = peek_client_id(datagram).expect("length checked above");
+        if client_id != self.client_id {
+            return Err(DatagramError::WrongClient(client_id));
+        }
+
+        let (prefix, sealed) = datagram.split_at(DATAGRAM_PREFIX_LEN);
+        let sequence = u64::from_be_bytes(prefix[16..].try_into().expect("8 bytes"));
+        let mut body = sealed.to_vec();
+        let frame = self
+            .open_key
+            .open_in_place(nonce(sequence), Aad::from(prefix), &mut body)
+            .map_err(|_| DatagramError::Authentication)?;
+
+        let header_sequence = u64::from_be_bytes(frame[8..16].try_into().expect("8 bytes"));
+        if header_sequence != sequence {
+            return Err(DatagramError::SequenceMismatch);
+        }
+        let (msg, _) = decode_message(frame)?;
+        Ok((sequence, msg))
+    }
+}
+
+impl std::fmt::Debug for InputChannelCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keys are deliberately omitted.
+        f.debug_struct("InputChannelCipher")
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The 96-bit nonce for `sequence`: four zero bytes, then the sequence number.
+fn nonce(sequence: u64) -> Nonce {
+    let mut bytes = [0u8; NONCE_LEN];
+    bytes[4..].copy_from_slice(&sequence.to_be_bytes());
+    Nonce::assume_unique_for_key(bytes)
+}
+
+/// Sliding-window replay filter over sequence numbers.
+///
+/// Remembers the highest sequence number accepted so far and which of the
+/// [`ReplayWindow::SIZE`] numbers below it have been seen.  A datagram is
+/// accepted once; duplicates and anything older than the window are
+/// rejected.  Reordering within the window is allowed, which UDP needs.
+#[derive(Debug, Default, Clone)]
+pub struct ReplayWindow {
+    /// Highest accepted sequence number plus one; `0` before the first.
+    next: u64,
+    /// Bit `i` set ⇔ sequence `next - 1 - i` has been accepted.
+    seen: u64,
+}
+
+impl ReplayWindow {
+    /// How far behind the newest datagram an older one may still arrive.
+    pub const SIZE: u64 = 64;
+
+    /// Creates an empty window.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `sequence` and returns `true` if it has not been seen before
+    /// and is not too old; returns `false` (and changes nothing) otherwise.
+    pub fn accept(&mut self, sequence: u64) -> bool {
+        let Some(candidate) = sequence.checked_add(1) else {
+            return false;
+        };
+        if candidate > self.next {
+            let shift = candidate - self.next;
+            self.seen = if shift >= Self::SIZE {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.next = candidate;
+            return true;
+        }
+        let age = self.next - candidate;
+        if age >= Self::SIZE {
+            return false;
+        }
+        let bit = 1u64 << age;
+        if self.seen & bit != 0 {
+            return false;
+        }
+        self.seen |= bit;
+        true
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::messages::MouseMoveMessage;
+
+    fn pair() -> (InputChannelCipher, InputChannelCipher) {
+        let token = [42u8; 32];
+        let client_id = Uuid::new_v4();
+        (
+            InputChannelCipher::new(&token, client_id, Endpoint::Master),
+            InputChannelCipher::new(&token, client_id, Endpoint::Client),
+        )
+    }
+
+    fn mouse_move() -> KvmMessage {
+        KvmMessage::MouseMove(MouseMoveMessage {
+            x: 640,
+            y: 480,
+            delta_x: 3,
+            delta_y: -2,
+        })
+    }
+
+    #[test]
+    fn test_master_datagram_opens_on_client() {
+        // Arrange
+        let (master, client) = pair();
+
+        // Act
+        let datagram = master.seal(&mouse_move(), 7, 123).unwrap();
+        let opened = client.open(&datagram);
+
+        // Assert
+        assert_eq!(opened, Ok((7, mouse_move())));
+        assert_eq!(datagram.len(), DATAGRAM_OVERHEAD + HEADER_SIZE + 12);
+    }
+
+    #[test]
+    fn test_datagram_does_not_open_in_the_sending_direction() {
+        // Arrange: a datagram reflected back at its sender.
+        let (master, _) = pair();
+        let datagram = master.seal(&mouse_move(), 1, 0).unwrap();
+
+        // Act / Assert
+        assert_eq!(master.open(&datagram), Err(DatagramError::Authentication));
+    }
+
+    #[test]
+    fn test_datagram_from_another_session_is_rejected() {
+        // Arrange: same client, but a token from an earlier connection.
+        let (master, _) = pair();
+        let client_id = peek_client_id(&master.seal(&mouse_move(), 1, 0).unwrap()).unwrap();
+        let stale = InputChannelCipher::new(&[1u8; 32], client_id, Endpoint::Client);
+
+        // Act / Assert
+        let datagram = master.seal(&mouse_move(), 2, 0).unwrap();
+        assert_eq!(stale.open(&datagram), Err(DatagramError::Authentication));
+    }
+
+    #[test]
+    fn test_tampered_sequence_or_body_fails_authentication() {
+        let (master, client) = pair();
+        let datagram = master.seal(&mouse_move(), 9, 0).unwrap();
+
+        let mut bumped = datagram.clone();
+        bumped[DATAGRAM_PREFIX_LEN - 1] ^= 1;
+        let mut flipped = datagram.clone();
+        flipped[DATAGRAM_PREFIX_LEN + 3] ^= 0x80;
+
+        assert_eq!(client.open(&bumped), Err(DatagramError::Authentication));
+        assert_eq!(client.open(&flipped), Err(DatagramError::Authentication));
+    }
+
+    #[test]
+    fn test_datagram_for_other_client_and_truncated_datagram_are_rejected() {
+        let (master, _) = pair();
+        let other = InputChannelCipher::new(&[42u8; 32], Uuid::new_v4(), Endpoint::Client);
+        let datagram = master.seal(&mouse_move(), 1, 0).unwrap();
+
+        assert!(matches!(
+            other.open(&datagram),
+            Err(DatagramError::WrongClient(_))
+        ));
+        assert_eq!(
+            other.open(&datagram[..20]),
+            Err(DatagramError::Truncated(20))
+        );
+    }
+
+    #[test]
+    fn test_oversized_message_is_refused_when_sealing() {
+        use crate::protocol::messages::{ClipboardDataMessage, ClipboardFormat};
+        let (master, _) = pair();
+        let big = KvmMessage::ClipboardData(ClipboardDataMessage {
+            format: ClipboardFormat::Utf8Text,
+            data: vec![b'x'; MAX_DATAGRAM_LEN],
+            has_more_fragments: false,
+        });
+
+        assert!(matches!(
+            master.seal(&big, 1, 0),
+            Err(DatagramError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay_window_accepts_each_sequence_once() {
+        // Arrange
+        let mut window = ReplayWindow::new();
+
+        // Act / Assert: in order, duplicate, reordered within the window.
+        assert!(window.accept(0));
+        assert!(window.accept(5));
+        assert!(!window.accept(5), "duplicate must be rejected");
+        assert!(window.accept(3), "late but unseen must be accepted");
+        assert!(!window.accept(3));
+        assert!(window.accept(1));
+    }
+
+    #[test]
+    fn test_replay_window_rejects_sequences_older_than_the_window() {
+        let mut window = ReplayWindow::new();
+        assert!(window.accept(1_000));
+
+        assert!(!window.accept(1_000 - ReplayWindow::SIZE));
+        assert!(window.accept(1_000 - ReplayWindow::SIZE + 1));
+    }
+
+    #[test]
+    fn test_replay_window_large_jump_clears_history() {
+        let mut window = ReplayWindow::new();
+        assert!(window.accept(10));
+        assert!(window.accept(10 + 10 * ReplayWindow::SIZE));
+
+        assert!(!window.accept(10), "now far outside the window");
+        assert!(!window.accept(u64::MAX), "u64::MAX cannot be a valid nonce");
+    }
+}
@@ -0,0 +1,29 @@
+//! Cryptographic building blocks shared by the master, client and web bridge.
+//!
+//! # Why a separate module? (for beginners)
+//!
+//! Security code is easiest to review when it lives in one place with a small
+//! surface.  The applications never touch certificates, keys or cipher suites
+//! directly; they ask this module for a ready-made TLS configuration and
+//! compare [`tls::CertFingerprint`]s.
+//!
+//! # Sub-modules
+//!
+//! - **`tls`** – Self-signed TLS identities, SHA-256 certificate fingerprints,
+//!   and the certificate verifiers used for fingerprint pinning.  Requires the
+//!   `tls` cargo feature.
+//! - **`pairing`** – OS-random pairing PINs and the SPAKE2 key exchange that
+//!   turns a PIN into mutual key confirmation.  Requires the `pairing` cargo
+//!   feature.
+//! - **`input_channel`** – ChaCha20-Poly1305 sealing and replay protection for
+//!   datagrams on the UDP input channel.  Requires the `input-channel` cargo
+//!   feature.
+
+#[cfg(feature = "tls")]
+pub mod tls;
+
+#[cfg(feature = "pairing")]
+pub mod pairing;
+
+#[cfg(feature = "input-channel")]
+pub mod input_channel;
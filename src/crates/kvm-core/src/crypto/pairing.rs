@@ -0,0 +1,507 @@
+//! PIN pairing: OS-random PINs and a SPAKE2 password-authenticated key exchange.
+//!
+//! # Why not just hash the PIN? (for beginners)
+//!
+//! A 6-digit PIN has only one million possible values.  If the client sent
+//! `hash(PIN + session_id)`, anyone who recorded that hash could try all one
+//! million PINs offline in well under a second and find the one that matches.
+//!
+//! A **password-authenticated key exchange** (PAKE) avoids this.  Both sides
+//! mix the PIN into a Diffie–Hellman exchange, so each message looks like a
+//! random group element.  A recording is useless for guessing: to test one PIN
+//! an attacker has to run a live exchange with the master, and the master
+//! locks the client out after a few failures.
+//!
+//! # SPAKE2 in one paragraph
+//!
+//! `G` is the group generator; `M` and `N` are two fixed points nobody knows
+//! the discrete logarithm of; `w` is a scalar derived from the PIN.  The client
+//! picks a random `x` and sends `X = x·G + w·M`; the master picks a random `y`
+//! and sends `Y = y·G + w·N`.  The client computes `K = x·(Y − w·N)`, the master
+//! `K = y·(X − w·M)`.  Both equal `xy·G` only if both used the same `w`.  Each
+//! side then proves it knows `K` with a MAC over the transcript (*key
+//! confirmation*); a wrong PIN shows up as a confirmation mismatch.
+//!
+//! The exchange itself is the RustCrypto [`spake2`] crate (Ed25519 group, the
+//! client as side A and the master as side B).  This module only adds what the
+//! crate leaves to its caller: the session ID and channel binding in the
+//! identities, the split of the crate's key into confirmation keys and a
+//! shared key (HKDF), and the confirmation MACs.
+//!
+//! On the wire a SPAKE2 message is the 32-byte group element alone.  The
+//! crate prefixes it with a side byte (`A` or `B`), which each role knows for
+//! its peer, so [`Spake2::message`] drops it and [`Spake2::finish`] puts it back.
+//!
+//! # Binding the exchange to the TLS channel
+//!
+//! Pairing runs inside the mutual-TLS control channel (see [`super::tls`]).
+//! Both sides feed the two certificate fingerprints they see into the
+//! transcript via [`channel_binding`].  A man-in-the-middle terminates TLS
+//! separately with each side, so the two sides see different fingerprints,
+//! compute different transcripts, and key confirmation fails even if the
+//! attacker relays every message.  A successful pairing therefore proves that
+//! the certificates each side sees are the ones the *other* side is using,
+//! which is what makes it safe to pin them afterwards.
+//!
+//! ```text
+//! master                                             client
+//!   pin = generate_pin()  (shown on screen)
+//!   Spake2::start(Master, pin, session, binding)
+//!   ── PairingRequest { session, Y } ────────────────►
+//!                                    user types pin
+//!                          Spake2::start(Client, pin, session, binding)
+//!                          keys = finish(Y)
+//!   ◄─────── PairingResponse { session, X, client_confirmation } ──
+//!   keys = finish(X)
+//!   verify client_confirmation  (mismatch = wrong PIN)
+//!   ── PairingConfirm { session, master_confirmation } ──►
+//!                          verify master_confirmation
+//! ```
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use spake2::{Ed25519Group, Identity, Password};
+use thiserror::Error;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// Number of decimal digits in a pairing PIN.
+pub const PIN_LENGTH: usize = 6;
+
+/// Size of a SPAKE2 message on the wire (a compressed Edwards point, without
+/// the `spake2` crate's side byte).
+pub const SPAKE_MESSAGE_LEN: usize = 32;
+
+/// Size of a key-confirmation MAC (HMAC-SHA-256).
+pub const CONFIRMATION_LEN: usize = 32;
+
+/// Domain-separation prefix for every hash in this module.  Changing it makes
+/// old and new implementations incompatible, which is the point.
+const DOMAIN: &[u8] = b"kvm-over-ip/pairing/v2";
+
+/// Errors from the pairing exchange.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum PakeError {
+    /// The peer's SPAKE2 message is not a valid group element.
+    #[error("peer's SPAKE2 message is not a valid group element")]
+    InvalidMessage,
+
+    /// The peer's key confirmation did not verify: the PINs differ, or the two
+    /// sides see different TLS certificates.
+    #[error("key confirmation failed (wrong PIN or intercepted connection)")]
+    ConfirmationMismatch,
+}
+
+/// Which side of the exchange we are.
+///
+/// SPAKE2 is asymmetric: the client blinds its message with `M`, the master
+/// with `N`.  Both sides must agree on who is who.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Master,
+    Client,
+}
+
+impl Role {
+    fn peer(self) -> Self {
+        match self {
+            Role::Master => Role::Client,
+            Role::Client => Role::Master,
+        }
+    }
+
+    /// The byte the `spake2` crate puts in front of this role's messages.
+    fn side_byte(self) -> u8 {
+        match self {
+            Role::Client => b'A',
+            Role::Master => b'B',
+        }
+    }
+
+    fn confirmation_label(self) -> &'static [u8] {
+        match self {
+            Role::Master => b"confirm master",
+            Role::Client => b"confirm client",
+        }
+    }
+}
+
+/// Generates a uniformly random 6-digit PIN from the OS random number generator.
+///
+/// Rejection sampling keeps every PIN equally likely: values from the top of
+/// the `u32` range that would make low PINs slightly more common are thrown
+/// away and redrawn.
+///
+/// # Panics
+///
+/// Panics if the OS random number generator is unavailable.
+pub fn generate_pin() -> String {
+    const RANGE: u32 = 1_000_000;
+    // Largest multiple of RANGE that fits: accept only values below it.
+    const LIMIT: u32 = u32::MAX - (u32::MAX % RANGE);
+    loop {
+        let mut bytes = [0u8; 4];
+        getrandom::fill(&mut bytes).expect("OS random number generator unavailable");
+        let n = u32::from_be_bytes(bytes);
+        if n < LIMIT {
+            return format!("{:0width$}", n % RANGE, width = PIN_LENGTH);
+        }
+    }
+}
+
+/// Builds the channel-binding bytes from the certificate fingerprints each side
+/// observed on the control connection.
+///
+/// `client_cert` and `master_cert` are the SHA-256 fingerprints (see
+/// `tls::CertFingerprint::as_bytes`); pass `None` on a plaintext connection.
+/// Both sides must pass the fingerprints in the same order: client first.
+pub fn channel_binding(client_cert: Option<&[u8; 32]>, master_cert: Option<&[u8; 32]>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(2 + 2 * 32);
+    for cert in [client_cert, master_cert] {
+        match cert {
+            Some(fp) => {
+                out.push(1);
+                out.extend_from_slice(fp);
+            }
+            None => out.push(0),
+        }
+    }
+    out
+}
+
+/// One side of a SPAKE2 exchange.
+///
+/// Create it with [`Spake2::start`], send [`Spake2::message`] to the peer, and
+/// call [`Spake2::finish`] with the peer's message.  `finish` consumes the
+/// exchange: every PIN attempt needs a fresh one, so each guess costs an
+/// attacker a new round trip with the master.
+pub struct Spake2 {
+    role: Role,
+    state: spake2::Spake2<Ed25519Group>,
+    /// Our message for the peer, without the crate's side byte.
+    message: [u8; SPAKE_MESSAGE_LEN],
+    /// Session ID and channel binding, mixed into the confirmation transcript.
+    context: Vec<u8>,
+}
+
+impl Spake2 {
+    /// Starts an exchange for `role` using `pin`.
+    ///
+    /// `session_id` is the pairing session's UUID and `binding` the output of
+    /// [`channel_binding`]; both go into the SPAKE2 identities and are
+    /// authenticated by key confirmation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the OS random number generator is unavailable.
+    pub fn start(role: Role, pin: &str, session_id: Uuid, binding: &[u8]) -> Self {
+        let mut context = Vec::with_capacity(16 + binding.len());
+        context.extend_from_slice(session_id.as_bytes());
+        context.extend_from_slice(binding);
+
+        let identity = |label: &[u8]| {
+            let mut id = Vec::with_capacity(DOMAIN.len() + label.len() + context.len());
+            id.extend_from_slice(DOMAIN);
+            id.extend_from_slice(label);
+            id.extend_from_slice(&context);
+            Identity::new(&id)
+        };
+        let (client_id, master_id) = (identity(b"/client"), identity(b"/master"));
+        let password = Password::new(pin.as_bytes());
+        let (state, outbound) = match role {
+            Role::Client => spake2::Spake2::start_a(&password, &client_id, &master_id),
+            Role::Master => spake2::Spake2::start_b(&password, &client_id, &master_id),
+        };
+        let message = outbound[1..]
+            .try_into()
+            .expect("an Ed25519 SPAKE2 message is a side byte and a 32-byte point");
+
+        Self {
+            role,
+            state,
+            message,
+            context,
+        }
+    }
+
+    /// Our message for the peer.
+    pub fn message(&self) -> [u8; SPAKE_MESSAGE_LEN] {
+        self.message
+    }
+
+    /// Combines our state with the peer's message and derives the session keys.
+    ///
+    /// Succeeding here does **not** mean the PINs matched; that is only known
+    /// once the peer's confirmation passes [`PairingKeys::verify_confirmation`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PakeError::InvalidMessage`] if `peer_message` is not a valid
+    /// group element.
+    pub fn finish(self, peer_message: &[u8; SPAKE_MESSAGE_LEN]) -> Result<PairingKeys, PakeError> {
+        let mut inbound = Vec::with_capacity(1 + SPAKE_MESSAGE_LEN);
+        inbound.push(self.role.peer().side_byte());
+        inbound.extend_from_slice(peer_message);
+        let mut key = self
+            .state
+            .finish(&inbound)
+            .map_err(|_| PakeError::InvalidMessage)?;
+
+        let (client_msg, master_msg) = match self.role {
+            Role::Client => (&self.message, peer_message),
+            Role::Master => (peer_message, &self.message),
+        };
+        let mut transcript = Sha256::new();
+        for part in [DOMAIN, &self.context[..], &client_msg[..], &master_msg[..]] {
+            transcript.update((part.len() as u32).to_be_bytes());
+            transcript.update(part);
+        }
+        let keys = PairingKeys::derive(&key, transcript.finalize().into());
+        key.zeroize();
+        Ok(keys)
+    }
+}
+
+impl std::fmt::Debug for Spake2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Secrets (and the PIN-derived scalar) are deliberately omitted.
+        f.debug_struct("Spake2")
+            .field("role", &self.role)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Keys derived from a completed exchange.
+///
+/// The two confirmation keys are only used for the key-confirmation MACs.  The
+/// shared key is independent of them and is the output of the pairing for
+/// anything that needs a secret both sides agree on.
+pub struct PairingKeys {
+    /// SHA-256 of the public transcript; the message the confirmations
+    /// authenticate.
+    transcript_hash: [u8; 32],
+    client_confirm_key: [u8; 32],
+    master_confirm_key: [u8; 32],
+    shared_key: [u8; 32],
+}
+
+impl PairingKeys {
+    /// Splits the SPAKE2 key into the confirmation keys and the shared key.
+    fn derive(spake_key: &[u8], transcript_hash: [u8; 32]) -> Self {
+        let hk = Hkdf::<Sha256>::new(Some(&transcript_hash), spake_key);
+        let expand = |label: &[u8]| {
+            let mut okm = [0u8; 32];
+            hk.expand(label, &mut okm)
+                .expect("32 bytes is a valid HKDF-SHA-256 output length");
+            okm
+        };
+        Self {
+            transcript_hash,
+            client_confirm_key: expand(Role::Client.confirmation_label()),
+            master_confirm_key: expand(Role::Master.confirmation_label()),
+            shared_key: expand(b"shared key"),
+        }
+    }
+
+    fn confirmation_mac(&self, from: Role) -> Hmac<Sha256> {
+        let key = match from {
+            Role::Client => &self.client_confirm_key,
+            Role::Master => &self.master_confirm_key,
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(&self.transcript_hash);
+        mac
+    }
+
+    /// The key-confirmation MAC that `from` sends to its peer.
+    pub fn confirmation(&self, from: Role) -> [u8; CONFIRMATION_LEN] {
+        self.confirmation_mac(from).finalize().into_bytes().into()
+    }
+
+    /// Checks a key-confirmation MAC sent by `from`, in constant time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PakeError::ConfirmationMismatch`] if the MAC does not verify.
+    pub fn verify_confirmation(
+        &self,
+        from: Role,
+        confirmation: &[u8; CONFIRMATION_LEN],
+    ) -> Result<(), PakeError> {
+        self.confirmation_mac(from)
+            .verify_slice(confirmation)
+            .map_err(|_| PakeError::ConfirmationMismatch)
+    }
+
+    /// The secret both sides agree on after a successful exchange.
+    pub fn shared_key(&self) -> &[u8; 32] {
+        &self.shared_key
+    }
+}
+
+impl Drop for PairingKeys {
+    fn drop(&mut self) {
+        self.client_confirm_key.zeroize();
+        self.master_confirm_key.zeroize();
+        self.shared_key.zeroize();
+    }
+}
+
+impl std::fmt::Debug for PairingKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PairingKeys").finish_non_exhaustive()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINDING_CLIENT: [u8; 32] = [0x11; 32];
+    const BINDING_MASTER: [u8; 32] = [0x22; 32];
+
+    fn binding() -> Vec<u8> {
+        channel_binding(Some(&BINDING_CLIENT), Some(&BINDING_MASTER))
+    }
+
+    /// Runs both halves of the exchange and returns (master keys, client keys).
+    fn exchange(
+        master_pin: &str,
+        client_pin: &str,
+        master_binding: &[u8],
+        client_binding: &[u8],
+    ) -> (PairingKeys, PairingKeys) {
+        let session = Uuid::new_v4();
+        let master = Spake2::start(Role::Master, master_pin, session, master_binding);
+        let client = Spake2::start(Role::Client, client_pin, session, client_binding);
+        let (master_msg, client_msg) = (master.message(), client.message());
+        let client_keys = client.finish(&master_msg).unwrap();
+        let master_keys = master.finish(&client_msg).unwrap();
+        (master_keys, client_keys)
+    }
+
+    #[test]
+    fn test_generate_pin_is_six_ascii_digits() {
+        for _ in 0..100 {
+            let pin = generate_pin();
+            assert_eq!(pin.len(), PIN_LENGTH);
+            assert!(pin.bytes().all(|b| b.is_ascii_digit()), "got {pin}");
+        }
+    }
+
+    #[test]
+    fn test_generate_pin_varies() {
+        // Arrange / Act – 20 draws from a million values colliding every time
+        // would mean the generator is broken.
+        let pins: std::collections::HashSet<String> = (0..20).map(|_| generate_pin()).collect();
+
+        // Assert
+        assert!(pins.len() > 1);
+    }
+
+    #[test]
+    fn test_matching_pins_confirm_in_both_directions() {
+        // Arrange / Act
+        let (master, client) = exchange("123456", "123456", &binding(), &binding());
+
+        // Assert
+        assert_eq!(
+            master.verify_confirmation(Role::Client, &client.confirmation(Role::Client)),
+            Ok(())
+        );
+        assert_eq!(
+            client.verify_confirmation(Role::Master, &master.confirmation(Role::Master)),
+            Ok(())
+        );
+        assert_eq!(master.shared_key(), client.shared_key());
+    }
+
+    #[test]
+    fn test_wrong_pin_fails_confirmation() {
+        // Arrange / Act
+        let (master, client) = exchange("123456", "123457", &binding(), &binding());
+
+        // Assert
+        assert_eq!(
+            master.verify_confirmation(Role::Client, &client.confirmation(Role::Client)),
+            Err(PakeError::ConfirmationMismatch)
+        );
+        assert_ne!(master.shared_key(), client.shared_key());
+    }
+
+    #[test]
+    fn test_different_channel_bindings_fail_confirmation() {
+        // Arrange: a man-in-the-middle means the client sees the attacker's
+        // certificate instead of the master's.
+        let client_view = channel_binding(Some(&BINDING_CLIENT), Some(&[0x33; 32]));
+
+        // Act
+        let (master, client) = exchange("123456", "123456", &binding(), &client_view);
+
+        // Assert
+        assert_eq!(
+            master.verify_confirmation(Role::Client, &client.confirmation(Role::Client)),
+            Err(PakeError::ConfirmationMismatch)
+        );
+    }
+
+    #[test]
+    fn test_confirmation_is_not_reflectable() {
+        // Arrange
+        let (master, _client) = exchange("123456", "123456", &binding(), &binding());
+
+        // Act – echo the master's own confirmation back as if from the client.
+        let reflected = master.confirmation(Role::Master);
+
+        // Assert
+        assert_eq!(
+            master.verify_confirmation(Role::Client, &reflected),
+            Err(PakeError::ConfirmationMismatch)
+        );
+    }
+
+    #[test]
+    fn test_messages_do_not_reveal_pin() {
+        // Two exchanges with the same PIN and session produce unrelated messages
+        // because the Diffie–Hellman secret is fresh each time.
+        let session = Uuid::new_v4();
+        let a = Spake2::start(Role::Client, "000000", session, &binding());
+        let b = Spake2::start(Role::Client, "000000", session, &binding());
+        assert_ne!(a.message(), b.message());
+    }
+
+    #[test]
+    fn test_finish_rejects_invalid_point_encoding() {
+        // Arrange: y = 2 is not the y-coordinate of any point on the curve.
+        let master = Spake2::start(Role::Master, "123456", Uuid::new_v4(), &binding());
+        let mut not_a_point = [0u8; SPAKE_MESSAGE_LEN];
+        not_a_point[0] = 2;
+
+        // Act / Assert
+        assert_eq!(
+            master.finish(&not_a_point).err(),
+            Some(PakeError::InvalidMessage)
+        );
+    }
+
+    #[test]
+    fn test_channel_binding_distinguishes_missing_certificates() {
+        let fp = [0xAB; 32];
+        assert_ne!(
+            channel_binding(Some(&fp), None),
+            channel_binding(None, Some(&fp))
+        );
+        assert_eq!(channel_binding(None, None), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_debug_output_hides_secrets() {
+        let master = Spake2::start(Role::Master, "123456", Uuid::new_v4(), &binding());
+        let text = format!("{master:?}");
+        assert!(!text.contains("123456"));
+        assert!(text.contains("Master"));
+    }
+}
@@ -0,0 +1,613 @@
+//! TLS identities and certificate pinning for the control channel.
+//!
+//! # Trust model (for beginners)
+//!
+//! A web browser trusts a server because a certificate authority (CA) vouched
+//! for it.  A KVM switch on a home or office LAN has no CA, so each machine
+//! instead generates its own **self-signed** certificate once and keeps it.
+//!
+//! Trust is then established by **pinning**: the first time a client is paired
+//! (the user has confirmed the PIN on both screens), the master records the
+//! SHA-256 fingerprint of the client's certificate.  Every later connection
+//! claiming to be that client must present a certificate with the same
+//! fingerprint, otherwise it is rejected.  An attacker who copies the client's
+//! UUID cannot forge the fingerprint without also stealing its private key.
+//!
+//! ```text
+//! client cert ──SHA-256──► "sha256:3f2a…"  ──stored in──► config.toml
+//!                                                           [[clients]]
+//!                                                           pairing_hash = "sha256:3f2a…"
+//! ```
+//!
+//! # Mutual TLS
+//!
+//! Both sides present a certificate.  The verifiers in this module accept any
+//! well-formed certificate during the handshake (there is no CA to check it
+//! against) but still verify the handshake signature, which proves the peer
+//! holds the matching private key.  The *application* then compares the
+//! fingerprint with its pin, because only it knows which client ID the peer
+//! claims to be.  A client may additionally pin the master's fingerprint, in
+//! which case the handshake itself fails on a mismatch.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rcgen::{CertificateParams, DnType, KeyPair};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring as ring_provider, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::ParsedCertificate;
+use rustls::{
+    ClientConfig, CommonState, DigitallySignedStruct, DistinguishedName, ServerConfig,
+    SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Re-exported so applications can name `rustls` types without depending on
+/// a second copy of the crate.
+pub use rustls;
+
+/// DNS name placed in every identity certificate and used as the TLS server
+/// name (SNI) by clients.  Certificates are pinned, not matched by name, so
+/// this only needs to be a syntactically valid DNS name.
+pub const TLS_SERVER_NAME: &str = "kvm-over-ip";
+
+/// File name of the PEM certificate written by [`TlsIdentity::load_or_generate`].
+pub const IDENTITY_CERT_FILE: &str = "identity.crt";
+/// File name of the PEM private key written by [`TlsIdentity::load_or_generate`].
+pub const IDENTITY_KEY_FILE: &str = "identity.key";
+
+/// Errors from identity generation, loading, and TLS configuration.
+#[derive(Debug, Error)]
+pub enum TlsError {
+    /// Certificate or key generation failed.
+    #[error("failed to generate TLS identity: {0}")]
+    Generate(#[from] rcgen::Error),
+
+    /// rustls rejected the certificate, key, or configuration.
+    #[error("TLS configuration error: {0}")]
+    Rustls(#[from] rustls::Error),
+
+    /// An identity file could not be read or written.
+    #[error("I/O error accessing {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A PEM file did not contain the expected certificate or key.
+    #[error("invalid PEM data: {0}")]
+    InvalidPem(String),
+
+    /// A fingerprint string was not of the form `sha256:<64 hex digits>`.
+    #[error("invalid certificate fingerprint: {0:?}")]
+    InvalidFingerprint(String),
+}
+
+// ── Fingerprints ──────────────────────────────────────────────────────────────
+
+/// SHA-256 digest of a DER-encoded certificate.
+///
+/// Displayed and parsed as `sha256:` followed by 64 lowercase hex digits,
+/// which is the form stored in the master's config file.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CertFingerprint([u8; 32]);
+
+impl CertFingerprint {
+    const PREFIX: &'static str = "sha256:";
+
+    /// Computes the fingerprint of a DER-encoded certificate.
+    pub fn of(cert_der: &[u8]) -> Self {
+        Self(Sha256::digest(cert_der).into())
+    }
+
+    /// Returns the fingerprint of the end-entity certificate the peer
+    /// presented on a TLS connection, if any.
+    pub fn of_peer(conn: &CommonState) -> Option<Self> {
+        conn.peer_certificates()
+            .and_then(|certs| certs.first())
+            .map(|cert| Self::of(cert))
+    }
+
+    /// Returns the raw 32-byte digest.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for CertFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Self::PREFIX)?;
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CertFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CertFingerprint({self})")
+    }
+}
+
+impl FromStr for CertFingerprint {
+    type Err = TlsError;
+
+    fn from_str(s: &str) -> Result<Self, TlsError> {
+        let invalid = || TlsError::InvalidFingerprint(s.to_string());
+        let hex = s.strip_prefix(Self::PREFIX).ok_or_else(invalid)?;
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let mut digest = [0u8; 32];
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(digest))
+    }
+}
+
+// ── Identities ────────────────────────────────────────────────────────────────
+
+/// A self-signed certificate together with its private key.
+///
+/// The private key is never printed: the `Debug` output only shows the
+/// fingerprint.
+pub struct TlsIdentity {
+    cert: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+    cert_pem: String,
+    key_pem: String,
+    fingerprint: CertFingerprint,
+}
+
+impl TlsIdentity {
+    /// Generates a fresh ECDSA P-256 identity.
+    ///
+    /// `common_name` is a human-readable label (e.g. the machine name) placed
+    /// in the certificate subject; it plays no part in verification.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Generate`] if key or certificate generation fails.
+    pub fn generate(common_name: &str) -> Result<Self, TlsError> {
+        let key_pair = KeyPair::generate()?;
+        let mut params = CertificateParams::new(vec![TLS_SERVER_NAME.to_string()])?;
+        params
+            .distinguished_name
+            .push(DnType::CommonName, common_name);
+        let cert = params.self_signed(&key_pair)?;
+        Self::from_pem(&cert.pem(), &key_pair.serialize_pem())
+    }
+
+    /// Loads an identity from PEM-encoded certificate and private key text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::InvalidPem`] if either input lacks the expected
+    /// PEM block.
+    pub fn from_pem(cert_pem: &str, key_pem: &str) -> Result<Self, TlsError> {
+        let cert = CertificateDer::from_pem_slice(cert_pem.as_bytes())
+            .map_err(|e| TlsError::InvalidPem(format!("certificate: {e}")))?;
+        let key = PrivateKeyDer::from_pem_slice(key_pem.as_bytes())
+            .map_err(|e| TlsError::InvalidPem(format!("private key: {e}")))?;
+        let fingerprint = CertFingerprint::of(&cert);
+        Ok(Self {
+            cert,
+            key,
+            cert_pem: cert_pem.to_string(),
+            key_pem: key_pem.to_string(),
+            fingerprint,
+        })
+    }
+
+    /// Loads the identity stored in `dir`, generating and saving a new one if
+    /// none exists yet.
+    ///
+    /// The certificate is written to [`IDENTITY_CERT_FILE`] and the key to
+    /// [`IDENTITY_KEY_FILE`].  On Unix the key file is created with mode
+    /// `0600` so other users cannot read it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Io`] if the files cannot be read or written, or any
+    /// error from [`generate`](Self::generate) / [`from_pem`](Self::from_pem).
+    pub fn load_or_generate(dir: &Path, common_name: &str) -> Result<Self, TlsError> {
+        let cert_path = dir.join(IDENTITY_CERT_FILE);
+        let key_path = dir.join(IDENTITY_KEY_FILE);
+
+        if cert_path.exists() && key_path.exists() {
+            let cert_pem = read_to_string(&cert_path)?;
+            let key_pem = read_to_string(&key_path)?;
+            return Self::from_pem(&cert_pem, &key_pem);
+        }
+
+        let identity = Self::generate(common_name)?;
+        std::fs::create_dir_all(dir).map_err(|source| TlsError::Io {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        write_private(&key_path, identity.key_pem.as_bytes())?;
+        std::fs::write(&cert_path, identity.cert_pem.as_bytes()).map_err(|source| {
+            TlsError::Io {
+                path: cert_path.clone(),
+                source,
+            }
+        })?;
+        Ok(identity)
+    }
+
+    /// Returns the DER-encoded certificate.
+    pub fn certificate(&self) -> &CertificateDer<'static> {
+        &self.cert
+    }
+
+    /// Returns the PEM-encoded certificate.
+    pub fn cert_pem(&self) -> &str {
+        &self.cert_pem
+    }
+
+    /// Returns the certificate's fingerprint.
+    pub fn fingerprint(&self) -> CertFingerprint {
+        self.fingerprint
+    }
+
+    /// Builds a rustls server configuration that presents this identity and
+    /// requires (but does not pin) a client certificate.
+    ///
+    /// The caller pins the client's certificate after the handshake using
+    /// [`CertFingerprint::of_peer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Rustls`] if the key does not match the certificate.
+    pub fn server_config(&self) -> Result<Arc<ServerConfig>, TlsError> {
+        let provider = Arc::new(ring_provider::default_provider());
+        let config = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()?
+            .with_client_cert_verifier(Arc::new(AnyClientCert { provider }))
+            .with_single_cert(vec![self.cert.clone()], self.key.clone_key())?;
+        Ok(Arc::new(config))
+    }
+
+    /// Builds a rustls client configuration that presents this identity.
+    ///
+    /// If `pinned_server` is `Some`, the handshake fails unless the server's
+    /// certificate has exactly that fingerprint.  If it is `None`, any
+    /// well-formed server certificate is accepted (trust on first use).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Rustls`] if the key does not match the certificate.
+    pub fn client_config(
+        &self,
+        pinned_server: Option<CertFingerprint>,
+    ) -> Result<Arc<ClientConfig>, TlsError> {
+        let provider = Arc::new(ring_provider::default_provider());
+        let config = ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedServerCert {
+                provider,
+                pinned: pinned_server,
+            }))
+            .with_client_auth_cert(vec![self.cert.clone()], self.key.clone_key())?;
+        Ok(Arc::new(config))
+    }
+}
+
+impl fmt::Debug for TlsIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsIdentity")
+            .field("fingerprint", &self.fingerprint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the [`ServerName`] clients should connect with.
+pub fn server_name() -> ServerName<'static> {
+    ServerName::try_from(TLS_SERVER_NAME).expect("TLS_SERVER_NAME is a valid DNS name")
+}
+
+fn read_to_string(path: &Path) -> Result<String, TlsError> {
+    std::fs::read_to_string(path).map_err(|source| TlsError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Writes `contents` to a new file readable only by the current user.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), TlsError> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|source| TlsError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+// ── Verifiers ─────────────────────────────────────────────────────────────────
+
+/// Checks that `cert` is a parseable X.509 certificate.
+fn check_well_formed(cert: &CertificateDer<'_>) -> Result<(), rustls::Error> {
+    ParsedCertificate::try_from(cert).map(|_| ())
+}
+
+/// Server-side verifier: any well-formed client certificate is accepted; the
+/// master pins it against the claimed client ID after the handshake.
+#[derive(Debug)]
+struct AnyClientCert {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ClientCertVerifier for AnyClientCert {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        check_well_formed(end_entity)?;
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Client-side verifier: accepts the server certificate if it matches the
+/// pinned fingerprint, or any well-formed certificate when nothing is pinned.
+#[derive(Debug)]
+struct PinnedServerCert {
+    provider: Arc<CryptoProvider>,
+    pinned: Option<CertFingerprint>,
+}
+
+impl ServerCertVerifier for PinnedServerCert {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        check_well_formed(end_entity)?;
+        match self.pinned {
+            Some(pinned) if CertFingerprint::of(end_entity) != pinned => {
+                Err(rustls::Error::InvalidCertificate(
+                    rustls::CertificateError::ApplicationVerificationFailure,
+                ))
+            }
+            _ => Ok(ServerCertVerified::assertion()),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pinned_verifier(pinned: Option<CertFingerprint>) -> PinnedServerCert {
+        PinnedServerCert {
+            provider: Arc::new(ring_provider::default_provider()),
+            pinned,
+        }
+    }
+
+    fn verify_with(verifier: &PinnedServerCert, cert: &CertificateDer<'_>) -> bool {
+        verifier
+            .verify_server_cert(cert, &[], &server_name(), &[], UnixTime::now())
+            .is_ok()
+    }
+
+    #[test]
+    fn test_fingerprint_display_and_parse_round_trip() {
+        // Arrange
+        let fp = CertFingerprint::of(b"not really a certificate");
+
+        // Act
+        let text = fp.to_string();
+        let parsed: CertFingerprint = text.parse().unwrap();
+
+        // Assert
+        assert!(text.starts_with("sha256:"));
+        assert_eq!(text.len(), "sha256:".len() + 64);
+        assert_eq!(parsed, fp);
+    }
+
+    #[test]
+    fn test_fingerprint_parse_rejects_malformed_strings() {
+        for bad in [
+            "",
+            "sha256:",
+            "md5:00",
+            "sha256:abc123",
+            &format!("sha256:{}", "zz".repeat(32)),
+            &format!("SHA256:{}", "00".repeat(32)),
+        ] {
+            assert!(
+                bad.parse::<CertFingerprint>().is_err(),
+                "{bad:?} must not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_identities_are_distinct() {
+        let a = TlsIdentity::generate("a").unwrap();
+        let b = TlsIdentity::generate("b").unwrap();
+        assert_ne!(a.fingerprint(), b.fingerprint());
+    }
+
+    #[test]
+    fn test_identity_pem_round_trip_preserves_fingerprint() {
+        // Arrange
+        let original = TlsIdentity::generate("round-trip").unwrap();
+
+        // Act
+        let restored = TlsIdentity::from_pem(original.cert_pem(), &original.key_pem).unwrap();
+
+        // Assert
+        assert_eq!(restored.fingerprint(), original.fingerprint());
+        assert_eq!(restored.certificate(), original.certificate());
+    }
+
+    #[test]
+    fn test_from_pem_rejects_garbage() {
+        let result = TlsIdentity::from_pem("hello", "world");
+        assert!(matches!(result, Err(TlsError::InvalidPem(_))));
+    }
+
+    #[test]
+    fn test_load_or_generate_reuses_saved_identity() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("kvm-tls-test-{}", uuid::Uuid::new_v4()));
+
+        // Act
+        let first = TlsIdentity::load_or_generate(&dir, "persisted").unwrap();
+        let second = TlsIdentity::load_or_generate(&dir, "persisted").unwrap();
+
+        // Assert
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join(IDENTITY_KEY_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o077, 0, "key file must not be group/world readable");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_debug_output_does_not_contain_private_key() {
+        let identity = TlsIdentity::generate("secret").unwrap();
+        let debug = format!("{identity:?}");
+        assert!(!debug.contains("PRIVATE KEY"));
+        assert!(debug.contains("sha256:"));
+    }
+
+    #[test]
+    fn test_server_and_client_configs_build_from_identity() {
+        let identity = TlsIdentity::generate("configs").unwrap();
+        assert!(identity.server_config().is_ok());
+        assert!(identity.client_config(None).is_ok());
+    }
+
+    #[test]
+    fn test_pinned_server_verifier_accepts_matching_fingerprint() {
+        let server = TlsIdentity::generate("server").unwrap();
+        let verifier = pinned_verifier(Some(server.fingerprint()));
+        assert!(verify_with(&verifier, server.certificate()));
+    }
+
+    #[test]
+    fn test_pinned_server_verifier_rejects_other_certificate() {
+        let server = TlsIdentity::generate("server").unwrap();
+        let impostor = TlsIdentity::generate("impostor").unwrap();
+        let verifier = pinned_verifier(Some(server.fingerprint()));
+        assert!(!verify_with(&verifier, impostor.certificate()));
+    }
+
+    #[test]
+    fn test_unpinned_server_verifier_accepts_any_well_formed_certificate() {
+        let server = TlsIdentity::generate("server").unwrap();
+        let verifier = pinned_verifier(None);
+        assert!(verify_with(&verifier, server.certificate()));
+        assert!(!verify_with(
+            &verifier,
+            &CertificateDer::from(b"garbage".to_vec())
+        ));
+    }
+}
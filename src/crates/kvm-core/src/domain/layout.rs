@@ -1,7 +1,8 @@
 //! Virtual screen layout domain entity.
 //!
 //! The layout engine maintains a unified 2D coordinate space ("virtual screen space")
-//! where all screens are positioned. The master screen is anchored at (0, 0).
+//! where all screens are positioned. The master's desktop coordinates are the
+//! virtual coordinates: its primary monitor is anchored at (0, 0).
 //! Clients are positioned relative to the master using their virtual_x / virtual_y offsets.
 //!
 //! # What is "virtual screen space"? (for beginners)
@@ -11,8 +12,9 @@
 //!
 //! In this coordinate system:
 //!
-//! - The **master screen** always occupies the rectangle `(0, 0)` to
-//!   `(master_width, master_height)`.
+//! - The **master screen**'s primary monitor always occupies the rectangle
+//!   `(0, 0)` to `(width, height)`; further master monitors sit where the
+//!   master's OS places them, possibly at negative coordinates.
 //! - Client screens can be placed anywhere around the master — to the right
 //!   (positive X), to the left (negative X), above (negative Y), or below
 //!   (positive Y).
@@ -30,36 +32,84 @@
 //! engine sees that an [`Adjacency`] connects that edge to the left edge of the
 //! client, and seamlessly switches control to the client.
 //!
+//! # Screens with several monitors
+//!
+//! Every machine's monitors (see [`Monitor`]) are given in its own desktop
+//! coordinates, where `(0, 0)` is the top-left corner of its primary monitor.
+//! The layout places a client's monitors as one block: `region` of a
+//! [`ClientScreen`] is their bounding box in virtual space, and the
+//! monitors keep their arrangement inside it.  The master's monitors are
+//! placed at their desktop coordinates, and [`VirtualLayout::master`] is
+//! their bounding box.
+//!
+//! Two monitors of different heights leave dead space in the bounding box:
+//!
+//! ```text
+//! +----------+-------+
+//! | Monitor 0| Mon. 1|
+//! |          +-------+
+//! |          |  dead |
+//! +----------+-------+
+//! ```
+//!
+//! Monitors of the same screen are connected wherever they touch, so the
+//! cursor moves freely between them; it is held at monitor edges that face
+//! dead space ([`VirtualLayout::clamp_to_screen`]).  "Local" coordinates of
+//! a client are its desktop coordinates, which is what its input emulation
+//! expects; [`VirtualLayout::to_local`] and [`VirtualLayout::to_virtual`]
+//! convert between the two spaces.
+//!
 //! # Edge transitions
 //!
 //! An [`Adjacency`] defines which edge of one screen connects to which edge of
 //! another.  Only opposite edges are valid (Left↔Right, Top↔Bottom).
+//! An adjacency may cover only an [`EdgeSegment`] of its edge, so that a
+//! laptop beside the lower half of a tall monitor is reached only from that
+//! half, and one edge can lead to several neighbours.
 //!
 //! The [`VirtualLayout::check_edge_transition`] method is called on every mouse
-//! move event while the cursor is on the master.  When the cursor gets within
-//! [`EDGE_THRESHOLD`] pixels of a configured edge, it returns an
-//! [`EdgeTransition`] that tells the caller:
+//! move event while the cursor is on the master.  Edges are detected per
+//! monitor: a screen's right edge, say, is made of every stretch of a monitor's
+//! right edge with no other monitor of the same screen beyond it.  When the
+//! cursor gets within the edge threshold ([`EDGE_THRESHOLD`] pixels unless
+//! configured otherwise) of such an edge and the screen's edge has an
+//! adjacency, it returns an [`EdgeTransition`] that tells the caller:
 //!
 //! 1. Which screen to switch to.
-//! 2. Where on that screen the cursor should appear (proportionally mapped).
+//! 2. Where on that screen the cursor should appear: proportionally mapped
+//!    for an adjacency that covers the whole edge, or at the same virtual
+//!    coordinate along the edge for one limited to an [`EdgeSegment`].
 //! 3. Where to teleport the physical master cursor so that further movement
-//!    continues flowing towards the client.
-
+//!    continues flowing towards the client (or, when returning to the master,
+//!    where it enters the master's monitor).
+//!
+//! # Physical units
+//!
+//! Pixels are not the same size everywhere: a 4K laptop at 200 % scaling
+//! has pixels half as wide as a 1080p monitor at 100 %.  With
+//! [`VirtualLayout::set_physical_units`] crossings keep physical distances
+//! along the edge (see [`Monitor::pixel_size`]) and
+//! [`VirtualLayout::pointer_speed`] tells the router how to scale pointer
+//! motion so it feels the same on every monitor.  The layout itself stays
+//! in pixels.
+
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 use uuid::Uuid;
 
-/// Pixel threshold within which a cursor is considered to be "at the edge".
+/// Default pixel threshold within which a cursor is considered to be "at the edge".
 ///
 /// When the cursor is within this many pixels of a screen edge that has an
 /// adjacency configured, an edge transition is triggered.  A value of 2 means
 /// the last two pixel columns/rows of the screen are the "hot zone".
+/// [`VirtualLayout::set_edge_threshold`] changes it.
 ///
 /// Why 2 and not 1?  A threshold of 1 sometimes causes the cursor to "bounce"
 /// back before the OS delivers the transition event because the hook fires when
 /// the cursor is at the very last pixel and the next update has already moved
 /// it back slightly.  Two pixels gives the hook enough margin.
-const EDGE_THRESHOLD: i32 = 2;
+pub const EDGE_THRESHOLD: u32 = 2;
 
 /// Unique identifier for a client, derived from UUID v4.
 ///
@@ -77,7 +127,8 @@ pub enum ScreenId {
 }
 
 /// The four edges of a rectangular screen region.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Edge {
     Top,
     Bottom,
@@ -103,12 +154,16 @@ pub enum LayoutError {
     /// The two edges in an adjacency are not compatible (e.g., both Left edges).
     #[error("invalid adjacency: edges must be on opposite sides (Left↔Right or Top↔Bottom)")]
     IncompatibleEdges,
+
+    /// An adjacency's edge segment is empty (`start >= end`).
+    #[error("invalid adjacency: edge segment {start}..{end} is empty")]
+    EmptySegment { start: i32, end: i32 },
 }
 
 /// A rectangular region in the virtual screen coordinate system.
 ///
 /// `virtual_x` and `virtual_y` are the top-left corner coordinates in virtual space.
-/// The master screen is always at (0, 0).
+/// The master's primary monitor is always at (0, 0).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ScreenRegion {
     /// X coordinate of the top-left corner in virtual space (may be negative).
@@ -139,6 +194,84 @@ impl ScreenRegion {
             && self.virtual_y < other.bottom()
             && self.bottom() > other.virtual_y
     }
+
+    /// Returns `true` if the point `(x, y)` lies inside this region.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.virtual_x && x < self.right() && y >= self.virtual_y && y < self.bottom()
+    }
+
+    /// Returns the point of this region closest to `(x, y)`.
+    pub fn clamp(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            x.min(self.right() - 1).max(self.virtual_x),
+            y.min(self.bottom() - 1).max(self.virtual_y),
+        )
+    }
+
+    /// Returns the centre point of this region.
+    pub fn centre(&self) -> (i32, i32) {
+        (
+            self.virtual_x + self.width as i32 / 2,
+            self.virtual_y + self.height as i32 / 2,
+        )
+    }
+}
+
+/// One monitor of a machine, in that machine's own desktop coordinates.
+///
+/// The desktop origin `(0, 0)` is the top-left corner of the primary monitor;
+/// other monitors may sit at negative offsets.  This mirrors the protocol's
+/// `MonitorInfo`, which is how clients report their monitors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    /// Index reported by the machine.
+    pub monitor_id: u8,
+    /// X coordinate of the top-left corner in desktop coordinates.
+    pub x: i32,
+    /// Y coordinate of the top-left corner in desktop coordinates.
+    pub y: i32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Whether this is the machine's primary monitor.
+    pub is_primary: bool,
+    /// DPI scale factor times 100 (e.g., 150 = 150 %), as set in the OS.
+    pub scale_factor: u16,
+    /// Physical width and height in millimetres, when known.
+    pub size_mm: Option<(u32, u32)>,
+}
+
+impl Monitor {
+    /// A primary monitor of the given size at the desktop origin.
+    pub fn primary(width: u32, height: u32) -> Self {
+        Self {
+            monitor_id: 0,
+            x: 0,
+            y: 0,
+            width,
+            height,
+            is_primary: true,
+            scale_factor: 100,
+            size_mm: None,
+        }
+    }
+
+    /// The size of one of this monitor's pixels, in logical pixels.
+    ///
+    /// A logical pixel is 1/96 inch, the size of a pixel at 100 % scaling.
+    /// The physical size gives the exact value; otherwise the OS scale factor
+    /// is trusted: at 200 % a pixel is half a logical pixel.
+    pub fn pixel_size(&self) -> f64 {
+        const LOGICAL_PIXELS_PER_MM: f64 = 96.0 / 25.4;
+        match self.size_mm {
+            Some((width_mm, _)) if width_mm > 0 && self.width > 0 => {
+                width_mm as f64 / self.width as f64 * LOGICAL_PIXELS_PER_MM
+            }
+            _ if self.scale_factor > 0 => 100.0 / self.scale_factor as f64,
+            _ => 1.0,
+        }
+    }
 }
 
 /// A client screen positioned in virtual space.
@@ -146,16 +279,111 @@ impl ScreenRegion {
 pub struct ClientScreen {
     /// Unique identifier of the client.
     pub client_id: ClientId,
-    /// Position and size in virtual space.
+    /// Bounding box of the client's monitors in virtual space.
     pub region: ScreenRegion,
     /// Human-readable name for the client (e.g., hostname).
     pub name: String,
+    /// The client's monitors in its desktop coordinates.  If empty, `region`
+    /// is treated as a single monitor.
+    pub monitors: Vec<Monitor>,
+}
+
+impl ClientScreen {
+    /// Creates a client with a single monitor covering `region`.
+    pub fn new(client_id: ClientId, name: impl Into<String>, region: ScreenRegion) -> Self {
+        Self {
+            client_id,
+            monitors: vec![Monitor::primary(region.width, region.height)],
+            region,
+            name: name.into(),
+        }
+    }
+
+    /// Creates a client from its reported monitors, with the top-left corner
+    /// of their bounding box at `(virtual_x, virtual_y)`.
+    ///
+    /// Returns `None` if `monitors` is empty.
+    pub fn with_monitors(
+        client_id: ClientId,
+        name: impl Into<String>,
+        virtual_x: i32,
+        virtual_y: i32,
+        monitors: Vec<Monitor>,
+    ) -> Option<Self> {
+        let bounds = bounding_box(&monitors)?;
+        Some(Self {
+            client_id,
+            region: ScreenRegion {
+                virtual_x,
+                virtual_y,
+                ..bounds
+            },
+            name: name.into(),
+            monitors,
+        })
+    }
+
+    /// Virtual position of the client's desktop origin.
+    fn origin(&self) -> (i32, i32) {
+        match bounding_box(&self.monitors) {
+            Some(b) => (
+                self.region.virtual_x - b.virtual_x,
+                self.region.virtual_y - b.virtual_y,
+            ),
+            None => (self.region.virtual_x, self.region.virtual_y),
+        }
+    }
+
+    /// Returns the regions of the client's monitors in virtual space.
+    pub fn monitor_regions(&self) -> Vec<ScreenRegion> {
+        if self.monitors.is_empty() {
+            return vec![self.region.clone()];
+        }
+        place_monitors(self.origin(), &self.monitors)
+    }
+
+    /// Virtual region of the primary monitor.
+    fn primary_region(&self) -> ScreenRegion {
+        primary_region(&self.monitor_regions(), &self.monitors)
+            .unwrap_or_else(|| self.region.clone())
+    }
+}
+
+/// A stretch of a screen edge, in virtual coordinates along that edge.
+///
+/// For a Left or Right edge the coordinates are Y values; for a Top or Bottom
+/// edge they are X values.  `start` is inclusive and `end` exclusive, like
+/// [`ScreenRegion::right`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeSegment {
+    /// First coordinate on the segment.
+    pub start: i32,
+    /// One past the last coordinate on the segment.
+    pub end: i32,
+}
+
+impl EdgeSegment {
+    /// Returns `true` if `pos` lies on this segment.
+    pub fn contains(&self, pos: i32) -> bool {
+        pos >= self.start && pos < self.end
+    }
+
+    /// Returns `true` if this segment and `other` share at least one coordinate.
+    pub fn overlaps(&self, other: &EdgeSegment) -> bool {
+        self.start < other.end && other.start < self.end
+    }
 }
 
 /// An adjacency relationship between two screen edges.
 ///
 /// Defines that when the cursor crosses `from_edge` of `from_screen`, it should
-/// appear on `to_edge` of `to_screen` at the proportionally equivalent position.
+/// appear on `to_edge` of `to_screen`.
+///
+/// Without a `segment` the adjacency covers the whole edge and the cursor
+/// position is mapped proportionally between the two screens.  With a
+/// `segment` it covers only that stretch of `from_edge`, several adjacencies
+/// can share one edge, and the cursor keeps its virtual coordinate along the
+/// edge: it crosses where the two screens physically overlap.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Adjacency {
     /// The screen the cursor is leaving.
@@ -166,6 +394,9 @@ pub struct Adjacency {
     pub to_screen: ScreenId,
     /// The edge of `to_screen` where the cursor enters.
     pub to_edge: Edge,
+    /// The part of `from_edge` this adjacency covers, or `None` for the
+    /// whole edge.
+    pub segment: Option<EdgeSegment>,
 }
 
 impl Adjacency {
@@ -204,6 +435,8 @@ pub enum CursorLocation {
 /// Result of a cursor edge transition.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EdgeTransition {
+    /// The edge of the current screen being crossed.
+    pub from_edge: Edge,
     /// The screen the cursor is transitioning to.
     pub to_screen: ScreenId,
     /// Entry X position in the destination screen's local coordinate space.
@@ -222,35 +455,84 @@ pub struct EdgeTransition {
 /// Maintains the master screen, all client screens, and their adjacency relationships.
 /// All cursor position calculations are performed in virtual screen space.
 ///
-/// The master screen is always anchored at virtual position (0, 0).
+/// The master's primary monitor is always anchored at virtual position (0, 0).
 pub struct VirtualLayout {
-    /// The master screen region (always at virtual_x=0, virtual_y=0).
+    /// Bounding box of the master's monitors.
     pub master: ScreenRegion,
+    /// The master's monitors; their desktop coordinates are virtual coordinates.
+    master_monitors: Vec<Monitor>,
     /// All client screens indexed by client ID.
     clients: HashMap<ClientId, ClientScreen>,
     /// Adjacency relationships between screen edges.
     adjacencies: Vec<Adjacency>,
+    /// Whether edge crossings and pointer speed use physical units.
+    physical_units: bool,
+    /// Distance from an edge, in pixels, within which the cursor is at it.
+    edge_threshold: i32,
 }
 
 impl VirtualLayout {
-    /// Creates a new layout with the master screen at (0, 0).
+    /// Creates a new layout with a single master monitor at (0, 0).
     pub fn new(master_width: u32, master_height: u32) -> Self {
-        Self {
-            master: ScreenRegion {
-                virtual_x: 0,
-                virtual_y: 0,
-                width: master_width,
-                height: master_height,
-            },
+        Self::with_master_monitors(vec![Monitor::primary(master_width, master_height)])
+            .expect("one monitor is never empty")
+    }
+
+    /// Creates a new layout whose master has the given monitors.
+    ///
+    /// Returns `None` if `monitors` is empty.
+    pub fn with_master_monitors(monitors: Vec<Monitor>) -> Option<Self> {
+        Some(Self {
+            master: bounding_box(&monitors)?,
+            master_monitors: monitors,
             clients: HashMap::new(),
             adjacencies: Vec::new(),
-        }
+            physical_units: false,
+            edge_threshold: EDGE_THRESHOLD as i32,
+        })
+    }
+
+    /// Sets how close to an edge, in pixels, the cursor must come to cross
+    /// it.  Values below 1 are treated as 1.
+    pub fn set_edge_threshold(&mut self, pixels: u32) {
+        self.edge_threshold = pixels.clamp(1, i32::MAX as u32) as i32;
     }
 
-    /// Updates the master screen dimensions.
+    /// Returns the edge threshold in pixels.
+    pub fn edge_threshold(&self) -> u32 {
+        self.edge_threshold as u32
+    }
+
+    /// Makes edge crossings and pointer speed work in physical units instead
+    /// of pixels (see [`Monitor::pixel_size`]).
+    ///
+    /// Off by default.  When on, the cursor crosses an edge at the same
+    /// physical distance from where the screens meet, and
+    /// [`pointer_speed`](Self::pointer_speed) tells the router how far to
+    /// move it on each monitor.
+    pub fn set_physical_units(&mut self, enabled: bool) {
+        self.physical_units = enabled;
+    }
+
+    /// Returns whether the layout works in physical units.
+    pub fn physical_units(&self) -> bool {
+        self.physical_units
+    }
+
+    /// Updates the master screen dimensions, making it a single monitor.
     pub fn set_master_dimensions(&mut self, width: u32, height: u32) {
-        self.master.width = width;
-        self.master.height = height;
+        self.master_monitors = vec![Monitor::primary(width, height)];
+        self.master = ScreenRegion {
+            virtual_x: 0,
+            virtual_y: 0,
+            width,
+            height,
+        };
+    }
+
+    /// Returns the master's monitors.
+    pub fn master_monitors(&self) -> &[Monitor] {
+        &self.master_monitors
     }
 
     /// Adds a client screen to the layout.
@@ -260,14 +542,7 @@ impl VirtualLayout {
     /// Returns [`LayoutError::Overlap`] if the new client region overlaps with any
     /// existing screen (master or other clients).
     pub fn add_client(&mut self, client: ClientScreen) -> Result<(), LayoutError> {
-        if client.region.overlaps(&self.master) {
-            return Err(LayoutError::Overlap);
-        }
-        for existing in self.clients.values() {
-            if client.region.overlaps(&existing.region) {
-                return Err(LayoutError::Overlap);
-            }
-        }
+        self.check_free(&client)?;
         self.clients.insert(client.client_id, client);
         Ok(())
     }
@@ -283,6 +558,9 @@ impl VirtualLayout {
 
     /// Updates the virtual position and size of an existing client screen.
     ///
+    /// A client with several monitors keeps their arrangement and is only
+    /// moved; the size in `region` applies to single-monitor clients.
+    ///
     /// # Errors
     ///
     /// Returns [`LayoutError::ClientNotFound`] if no client with that ID exists.
@@ -292,23 +570,26 @@ impl VirtualLayout {
         client_id: ClientId,
         region: ScreenRegion,
     ) -> Result<(), LayoutError> {
-        if !self.clients.contains_key(&client_id) {
+        let Some(current) = self.clients.get(&client_id) else {
             return Err(LayoutError::ClientNotFound(client_id));
-        }
+        };
 
-        // Check for overlaps with master and other clients (excluding the client being updated)
-        if region.overlaps(&self.master) {
-            return Err(LayoutError::Overlap);
-        }
-        for (id, existing) in &self.clients {
-            if *id != client_id && region.overlaps(&existing.region) {
-                return Err(LayoutError::Overlap);
+        let moved = if current.monitors.len() > 1 {
+            ClientScreen {
+                region: ScreenRegion {
+                    width: current.region.width,
+                    height: current.region.height,
+                    ..region
+                },
+                ..current.clone()
             }
-        }
+        } else {
+            ClientScreen::new(client_id, current.name.clone(), region)
+        };
 
-        if let Some(client) = self.clients.get_mut(&client_id) {
-            client.region = region;
-        }
+        // Check for overlaps with master and other clients (excluding the client being updated)
+        self.check_free(&moved)?;
+        self.clients.insert(client_id, moved);
         Ok(())
     }
 
@@ -318,16 +599,29 @@ impl VirtualLayout {
     ///
     /// Returns [`LayoutError::InvalidAdjacency`] if either referenced screen does not exist.
     /// Returns [`LayoutError::IncompatibleEdges`] if the edge combination is invalid.
+    /// Returns [`LayoutError::EmptySegment`] if the segment covers nothing.
     pub fn set_adjacency(&mut self, adj: Adjacency) -> Result<(), LayoutError> {
         if !adj.is_valid() {
             return Err(LayoutError::IncompatibleEdges);
         }
+        if let Some(seg) = adj.segment {
+            if seg.start >= seg.end {
+                return Err(LayoutError::EmptySegment {
+                    start: seg.start,
+                    end: seg.end,
+                });
+            }
+        }
         self.validate_screen_id(&adj.from_screen)?;
         self.validate_screen_id(&adj.to_screen)?;
 
-        // Replace any existing adjacency for the same from_screen + from_edge pair
-        self.adjacencies
-            .retain(|a| !(a.from_screen == adj.from_screen && a.from_edge == adj.from_edge));
+        // Replace any existing adjacency on the same from_screen + from_edge
+        // whose segment overlaps the new one (a whole edge overlaps everything).
+        self.adjacencies.retain(|a| {
+            !(a.from_screen == adj.from_screen
+                && a.from_edge == adj.from_edge
+                && segments_overlap(a.segment, adj.segment))
+        });
         self.adjacencies.push(adj);
         Ok(())
     }
@@ -342,40 +636,65 @@ impl VirtualLayout {
         self.clients.values()
     }
 
+    /// Returns the client screens in layout order: in reading order by their
+    /// top-left corner, top to bottom and then left to right.
+    ///
+    /// Unlike [`clients`](Self::clients) the order is stable, so "the second
+    /// client" means the same screen every time.
+    pub fn clients_in_order(&self) -> Vec<&ClientScreen> {
+        let mut clients: Vec<_> = self.clients.values().collect();
+        clients.sort_by_key(|c| (c.region.virtual_y, c.region.virtual_x));
+        clients
+    }
+
     /// Resolves a cursor position in virtual screen coordinates to a [`CursorLocation`].
     ///
     /// The `virtual_x` and `virtual_y` parameters are in the unified virtual coordinate space
-    /// (where master occupies 0..master_width × 0..master_height).
+    /// (where the master's desktop coordinates are used unchanged).
     ///
     /// Returns [`CursorLocation::OnMaster`] when the cursor is within the master region,
     /// [`CursorLocation::OnClient`] when within a client region, or falls back to
     /// [`CursorLocation::OnMaster`] when the position is outside all known regions.
+    /// A position in the dead space of a screen's bounding box, where no other
+    /// screen is, resolves to the nearest point on one of its monitors.
     pub fn resolve_cursor(&self, virtual_x: i32, virtual_y: i32) -> CursorLocation {
-        // Check master first
-        if virtual_x >= self.master.virtual_x
-            && virtual_x < self.master.right()
-            && virtual_y >= self.master.virtual_y
-            && virtual_y < self.master.bottom()
+        let master_monitors = self.monitor_regions(&ScreenId::Master);
+        let on_master = |x, y| {
+            let (local_x, local_y) = nearest_point(&master_monitors, x, y).unwrap_or((x, y));
+            CursorLocation::OnMaster { local_x, local_y }
+        };
+
+        // Check the master's monitors first
+        if master_monitors
+            .iter()
+            .any(|m| m.contains(virtual_x, virtual_y))
         {
-            return CursorLocation::OnMaster {
-                local_x: virtual_x - self.master.virtual_x,
-                local_y: virtual_y - self.master.virtual_y,
-            };
+            return on_master(virtual_x, virtual_y);
         }
 
-        // Check each client
-        for client in self.clients.values() {
-            if virtual_x >= client.region.virtual_x
-                && virtual_x < client.region.right()
-                && virtual_y >= client.region.virtual_y
-                && virtual_y < client.region.bottom()
-            {
-                return CursorLocation::OnClient {
-                    client_id: client.client_id,
-                    local_x: virtual_x - client.region.virtual_x,
-                    local_y: virtual_y - client.region.virtual_y,
-                };
-            }
+        // Check each client's monitors, then the dead space between monitors
+        let on_monitor = self.clients.values().find(|c| {
+            c.monitor_regions()
+                .iter()
+                .any(|m| m.contains(virtual_x, virtual_y))
+        });
+        if on_monitor.is_none() && self.master.contains(virtual_x, virtual_y) {
+            return on_master(virtual_x, virtual_y);
+        }
+        let client = on_monitor.or_else(|| {
+            self.clients
+                .values()
+                .find(|c| c.region.contains(virtual_x, virtual_y))
+        });
+        if let Some(client) = client {
+            let (x, y) = nearest_point(&client.monitor_regions(), virtual_x, virtual_y)
+                .unwrap_or((virtual_x, virtual_y));
+            let (origin_x, origin_y) = client.origin();
+            return CursorLocation::OnClient {
+                client_id: client.client_id,
+                local_x: x - origin_x,
+                local_y: y - origin_y,
+            };
         }
 
         // Default to master when outside all regions
@@ -385,11 +704,14 @@ impl VirtualLayout {
         }
     }
 
-    /// Checks whether the cursor is within [`EDGE_THRESHOLD`] pixels of any configured
+    /// Checks whether the cursor is within the edge threshold of any configured
     /// transition edge on the given screen.
     ///
     /// `current_screen` identifies the screen the cursor is currently on.
     /// `local_x` and `local_y` are the cursor's position in that screen's local coordinate space.
+    /// Edges are those of the monitor under the cursor, where no other
+    /// monitor of the same screen lies beyond them; a position in dead space
+    /// is first moved onto the nearest monitor.
     ///
     /// Returns `Some(EdgeTransition)` when a transition should occur, `None` otherwise.
     pub fn check_edge_transition(
@@ -398,7 +720,11 @@ impl VirtualLayout {
         local_x: i32,
         local_y: i32,
     ) -> Option<EdgeTransition> {
-        let from_region = self.get_region(current_screen)?;
+        let bounds = self.get_region(current_screen)?;
+        let (origin_x, origin_y) = self.origin(current_screen)?;
+        let monitors = self.monitor_regions(current_screen);
+        let (x, y) = nearest_point(&monitors, origin_x + local_x, origin_y + local_y)?;
+        let monitor = monitors.iter().find(|m| m.contains(x, y))?;
 
         for adj in &self.adjacencies {
             if &adj.from_screen != current_screen {
@@ -406,48 +732,105 @@ impl VirtualLayout {
             }
 
             let at_edge = match adj.from_edge {
-                Edge::Right => local_x >= from_region.width as i32 - EDGE_THRESHOLD,
-                Edge::Left => local_x < EDGE_THRESHOLD,
-                Edge::Bottom => local_y >= from_region.height as i32 - EDGE_THRESHOLD,
-                Edge::Top => local_y < EDGE_THRESHOLD,
+                Edge::Right => x >= monitor.right() - self.edge_threshold,
+                Edge::Left => x < monitor.virtual_x + self.edge_threshold,
+                Edge::Bottom => y >= monitor.bottom() - self.edge_threshold,
+                Edge::Top => y < monitor.virtual_y + self.edge_threshold,
+            } && is_exterior(&monitors, monitor, adj.from_edge, x, y);
+            let along = match adj.from_edge {
+                Edge::Left | Edge::Right => y,
+                Edge::Top | Edge::Bottom => x,
             };
+            let on_segment = adj.segment.map_or(true, |seg| seg.contains(along));
 
-            if !at_edge {
+            if !at_edge || !on_segment {
                 continue;
             }
 
-            let to_region = self.get_region(&adj.to_screen)?;
+            let to_bounds = self.get_region(&adj.to_screen)?;
 
-            // Map the perpendicular cursor coordinate proportionally to the target edge.
+            // Map the perpendicular cursor coordinate to the target edge.
+            //
+            // A whole-edge adjacency maps proportionally: if the cursor is 30%
+            // of the way down the source screen's height when it crosses the
+            // right edge, it appears 30% of the way down the destination
+            // screen's height.  Both positions are taken along the screens'
+            // bounding boxes.
             //
-            // "Proportional mapping" means: if the cursor is 30% of the way down
-            // the source screen's height when it crosses the right edge, it should
-            // appear 30% of the way down the destination screen's height.
-            // This feels natural even when the two screens have different resolutions.
-            let (entry_x, entry_y) = match (&adj.from_edge, &adj.to_edge) {
+            // A segment adjacency describes screens that physically share
+            // only part of an edge, so the cursor keeps its virtual coordinate
+            // and crosses straight over, as it does between two monitors of
+            // the same machine.
+            let (mapped_x, mapped_y) = match (&adj.from_edge, &adj.to_edge) {
                 (Edge::Right, Edge::Left) | (Edge::Left, Edge::Right) => {
-                    let t = local_y as f64 / from_region.height as f64;
-                    let mapped_y = (t * to_region.height as f64) as i32;
+                    let mapped_y = if adj.segment.is_some() {
+                        y
+                    } else {
+                        let t = (y - bounds.virtual_y) as f64 / bounds.height as f64;
+                        to_bounds.virtual_y + (t * to_bounds.height as f64) as i32
+                    };
                     let entry_x = match adj.to_edge {
-                        Edge::Left => 0,
-                        Edge::Right => to_region.width as i32 - 1,
-                        _ => 0,
+                        Edge::Left => to_bounds.virtual_x,
+                        _ => to_bounds.right() - 1,
                     };
-                    (entry_x, mapped_y.clamp(0, to_region.height as i32 - 1))
+                    (entry_x, mapped_y)
                 }
                 (Edge::Bottom, Edge::Top) | (Edge::Top, Edge::Bottom) => {
-                    let t = local_x as f64 / from_region.width as f64;
-                    let mapped_x = (t * to_region.width as f64) as i32;
+                    let mapped_x = if adj.segment.is_some() {
+                        x
+                    } else {
+                        let t = (x - bounds.virtual_x) as f64 / bounds.width as f64;
+                        to_bounds.virtual_x + (t * to_bounds.width as f64) as i32
+                    };
                     let entry_y = match adj.to_edge {
-                        Edge::Top => 0,
-                        Edge::Bottom => to_region.height as i32 - 1,
-                        _ => 0,
+                        Edge::Top => to_bounds.virtual_y,
+                        _ => to_bounds.bottom() - 1,
                     };
-                    (mapped_x.clamp(0, to_region.width as i32 - 1), entry_y)
+                    (mapped_x, entry_y)
                 }
                 _ => continue, // already validated as incompatible, skip
             };
 
+            // In physical units the distance along the edge is kept in
+            // physical length, measured from a point both edges share: the
+            // start of the segment, or the middle of each edge.
+            let (mapped_x, mapped_y) = if self.physical_units {
+                let from_size = self.pixel_size_at(current_screen, x, y)?;
+                let to_size = self.pixel_size_at(&adj.to_screen, mapped_x, mapped_y)?;
+                let ratio = from_size / to_size;
+                let shift = |along: i32, from_anchor: i32, to_anchor: i32| {
+                    to_anchor + ((along - from_anchor) as f64 * ratio).round() as i32
+                };
+                match adj.from_edge {
+                    Edge::Left | Edge::Right => {
+                        let (from_anchor, to_anchor) = match adj.segment {
+                            Some(seg) => (seg.start, seg.start),
+                            None => (bounds.centre().1, to_bounds.centre().1),
+                        };
+                        (mapped_x, shift(y, from_anchor, to_anchor))
+                    }
+                    Edge::Top | Edge::Bottom => {
+                        let (from_anchor, to_anchor) = match adj.segment {
+                            Some(seg) => (seg.start, seg.start),
+                            None => (bounds.centre().0, to_bounds.centre().0),
+                        };
+                        (shift(x, from_anchor, to_anchor), mapped_y)
+                    }
+                }
+            } else {
+                (mapped_x, mapped_y)
+            };
+
+            // Enter on a monitor that forms the destination edge, at the
+            // point closest to the mapped position.
+            let to_monitors = self.monitor_regions(&adj.to_screen);
+            let (entry_vx, entry_vy) = to_monitors
+                .iter()
+                .map(|m| edge_point(m, adj.to_edge, mapped_x, mapped_y))
+                .filter(|&(ex, ey)| is_exterior_point(&to_monitors, adj.to_edge, ex, ey))
+                .min_by_key(|&(ex, ey)| distance_sq((ex, ey), (mapped_x, mapped_y)))?;
+            let (to_origin_x, to_origin_y) = self.origin(&adj.to_screen)?;
+
             // Determine where to teleport the master physical cursor.
             //
             // After the transition the master cursor is still physically on the
@@ -458,18 +841,26 @@ impl VirtualLayout {
             //
             // The exact values below place the cursor at x=1 (or y=1) from the
             // edge closest to the transition side, so any subsequent movement
-            // must travel across the whole screen before it could trigger again.
-            let (master_teleport_x, master_teleport_y) = match adj.from_edge {
-                Edge::Right => (1, local_y), // teleport to left side
-                Edge::Left => (from_region.width as i32 - 2, local_y), // teleport to right side
-                Edge::Bottom => (local_x, 1), // teleport to top side
-                Edge::Top => (local_x, from_region.height as i32 - 2), // teleport to bottom side
+            // must travel across the whole monitor before it could trigger again.
+            let (teleport_x, teleport_y) = match adj.from_edge {
+                Edge::Right => (monitor.virtual_x + 1, y), // teleport to left side
+                Edge::Left => (monitor.right() - 2, y),    // teleport to right side
+                Edge::Bottom => (x, monitor.virtual_y + 1), // teleport to top side
+                Edge::Top => (x, monitor.bottom() - 2),    // teleport to bottom side
+            };
+            let entry = (entry_vx - to_origin_x, entry_vy - to_origin_y);
+            // Back on the master, the physical cursor goes where it enters.
+            let (master_teleport_x, master_teleport_y) = if adj.to_screen == ScreenId::Master {
+                entry
+            } else {
+                (teleport_x - origin_x, teleport_y - origin_y)
             };
 
             return Some(EdgeTransition {
+                from_edge: adj.from_edge,
                 to_screen: adj.to_screen.clone(),
-                entry_x,
-                entry_y,
+                entry_x: entry.0,
+                entry_y: entry.1,
                 master_teleport_x,
                 master_teleport_y,
             });
@@ -478,6 +869,96 @@ impl VirtualLayout {
         None
     }
 
+    /// Returns the monitor regions of a screen in virtual space, or an empty
+    /// list for an unknown client.
+    pub fn monitor_regions(&self, id: &ScreenId) -> Vec<ScreenRegion> {
+        match id {
+            ScreenId::Master => place_monitors((0, 0), &self.master_monitors),
+            ScreenId::Client(cid) => self
+                .clients
+                .get(cid)
+                .map(ClientScreen::monitor_regions)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// How many pixels the cursor should move on `id` at virtual position
+    /// `(virtual_x, virtual_y)` for each pixel the master's pointer moves.
+    ///
+    /// In physical units this makes the pointer cover the same physical
+    /// distance on every monitor as on the master's primary monitor: on a
+    /// 200 % client next to a 100 % master it is 2.0.  Otherwise, and for an
+    /// unknown client, it is 1.0.
+    pub fn pointer_speed(&self, id: &ScreenId, virtual_x: i32, virtual_y: i32) -> f64 {
+        if !self.physical_units {
+            return 1.0;
+        }
+        let master_regions = self.monitor_regions(&ScreenId::Master);
+        let reference = primary_region(&master_regions, &self.master_monitors)
+            .and_then(|r| self.pixel_size_at(&ScreenId::Master, r.virtual_x, r.virtual_y));
+        match (reference, self.pixel_size_at(id, virtual_x, virtual_y)) {
+            (Some(reference), Some(size)) => reference / size,
+            _ => 1.0,
+        }
+    }
+
+    /// The pixel size (see [`Monitor::pixel_size`]) of the monitor of `id`
+    /// nearest to `(virtual_x, virtual_y)`.
+    fn pixel_size_at(&self, id: &ScreenId, virtual_x: i32, virtual_y: i32) -> Option<f64> {
+        let monitors: &[Monitor] = match id {
+            ScreenId::Master => &self.master_monitors,
+            ScreenId::Client(cid) => &self.clients.get(cid)?.monitors,
+        };
+        let regions = self.monitor_regions(id);
+        let nearest = regions
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, r)| distance_sq(r.clamp(virtual_x, virtual_y), (virtual_x, virtual_y)))
+            .map(|(i, _)| i)?;
+        Some(monitors.get(nearest).map_or(1.0, Monitor::pixel_size))
+    }
+
+    /// Converts a virtual position to the local coordinates of `id`.
+    ///
+    /// Returns `None` for an unknown client.
+    pub fn to_local(&self, id: &ScreenId, virtual_x: i32, virtual_y: i32) -> Option<(i32, i32)> {
+        let (origin_x, origin_y) = self.origin(id)?;
+        Some((virtual_x - origin_x, virtual_y - origin_y))
+    }
+
+    /// Converts local coordinates of `id` to a virtual position.
+    ///
+    /// Returns `None` for an unknown client.
+    pub fn to_virtual(&self, id: &ScreenId, local_x: i32, local_y: i32) -> Option<(i32, i32)> {
+        let (origin_x, origin_y) = self.origin(id)?;
+        Some((origin_x + local_x, origin_y + local_y))
+    }
+
+    /// Moves a virtual position onto the nearest monitor of `id`; positions
+    /// already on a monitor are returned unchanged.
+    ///
+    /// Returns `None` for an unknown client.
+    pub fn clamp_to_screen(
+        &self,
+        id: &ScreenId,
+        virtual_x: i32,
+        virtual_y: i32,
+    ) -> Option<(i32, i32)> {
+        nearest_point(&self.monitor_regions(id), virtual_x, virtual_y)
+    }
+
+    /// Returns the centre of the primary monitor of `id` in virtual space,
+    /// or `None` for an unknown client.
+    pub fn screen_centre(&self, id: &ScreenId) -> Option<(i32, i32)> {
+        match id {
+            ScreenId::Master => {
+                let regions = self.monitor_regions(&ScreenId::Master);
+                primary_region(&regions, &self.master_monitors).map(|r| r.centre())
+            }
+            ScreenId::Client(cid) => self.clients.get(cid).map(|c| c.primary_region().centre()),
+        }
+    }
+
     /// Maps a position along one edge proportionally to the corresponding position
     /// on another edge.
     ///
@@ -500,6 +981,32 @@ impl VirtualLayout {
 
     // ── Private helpers ───────────────────────────────────────────────────────
 
+    /// Fails with [`LayoutError::Overlap`] if any monitor of `client` overlaps
+    /// the master or a monitor of another client.
+    fn check_free(&self, client: &ClientScreen) -> Result<(), LayoutError> {
+        let monitors = client.monitor_regions();
+        let taken = self
+            .clients
+            .values()
+            .filter(|c| c.client_id != client.client_id)
+            .flat_map(ClientScreen::monitor_regions)
+            .chain(self.monitor_regions(&ScreenId::Master));
+        for other in taken {
+            if monitors.iter().any(|m| m.overlaps(&other)) {
+                return Err(LayoutError::Overlap);
+            }
+        }
+        Ok(())
+    }
+
+    /// Virtual position of the local coordinate origin of `id`.
+    fn origin(&self, id: &ScreenId) -> Option<(i32, i32)> {
+        match id {
+            ScreenId::Master => Some((0, 0)),
+            ScreenId::Client(cid) => self.clients.get(cid).map(ClientScreen::origin),
+        }
+    }
+
     fn validate_screen_id(&self, id: &ScreenId) -> Result<(), LayoutError> {
         match id {
             ScreenId::Master => Ok(()),
@@ -513,7 +1020,9 @@ impl VirtualLayout {
         }
     }
 
-    fn get_region(&self, id: &ScreenId) -> Option<&ScreenRegion> {
+    /// Returns the virtual-space region of a screen, or `None` for an
+    /// unknown client.
+    pub fn get_region(&self, id: &ScreenId) -> Option<&ScreenRegion> {
         match id {
             ScreenId::Master => Some(&self.master),
             ScreenId::Client(cid) => self.clients.get(cid).map(|c| &c.region),
@@ -521,6 +1030,100 @@ impl VirtualLayout {
     }
 }
 
+/// `true` if two adjacency segments share a coordinate; `None` stands for the
+/// whole edge.
+fn segments_overlap(a: Option<EdgeSegment>, b: Option<EdgeSegment>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.overlaps(&b),
+        _ => true,
+    }
+}
+
+/// Returns the point on any of `regions` closest to `(x, y)`, or `None` if
+/// there are no regions.
+fn nearest_point(regions: &[ScreenRegion], x: i32, y: i32) -> Option<(i32, i32)> {
+    regions
+        .iter()
+        .map(|r| r.clamp(x, y))
+        .min_by_key(|&p| distance_sq(p, (x, y)))
+}
+
+fn distance_sq(a: (i32, i32), b: (i32, i32)) -> i64 {
+    let (dx, dy) = ((a.0 - b.0) as i64, (a.1 - b.1) as i64);
+    dx * dx + dy * dy
+}
+
+/// Bounding box of `monitors` in their desktop coordinates, or `None` if
+/// there are none.
+fn bounding_box(monitors: &[Monitor]) -> Option<ScreenRegion> {
+    let min_x = monitors.iter().map(|m| m.x).min()?;
+    let min_y = monitors.iter().map(|m| m.y).min()?;
+    let max_x = monitors.iter().map(|m| m.x + m.width as i32).max()?;
+    let max_y = monitors.iter().map(|m| m.y + m.height as i32).max()?;
+    Some(ScreenRegion {
+        virtual_x: min_x,
+        virtual_y: min_y,
+        width: (max_x - min_x) as u32,
+        height: (max_y - min_y) as u32,
+    })
+}
+
+/// Places `monitors` in virtual space with their desktop origin at `origin`.
+fn place_monitors(origin: (i32, i32), monitors: &[Monitor]) -> Vec<ScreenRegion> {
+    monitors
+        .iter()
+        .map(|m| ScreenRegion {
+            virtual_x: origin.0 + m.x,
+            virtual_y: origin.1 + m.y,
+            width: m.width,
+            height: m.height,
+        })
+        .collect()
+}
+
+/// The region (from `regions`, placed from `monitors`) of the primary
+/// monitor, or of the first one if none is marked primary.
+fn primary_region(regions: &[ScreenRegion], monitors: &[Monitor]) -> Option<ScreenRegion> {
+    let index = monitors.iter().position(|m| m.is_primary).unwrap_or(0);
+    regions.get(index).cloned()
+}
+
+/// The point on `edge` of `region` closest to `(x, y)`.
+fn edge_point(region: &ScreenRegion, edge: Edge, x: i32, y: i32) -> (i32, i32) {
+    let (cx, cy) = region.clamp(x, y);
+    match edge {
+        Edge::Left => (region.virtual_x, cy),
+        Edge::Right => (region.right() - 1, cy),
+        Edge::Top => (cx, region.virtual_y),
+        Edge::Bottom => (cx, region.bottom() - 1),
+    }
+}
+
+/// `true` if the pixel just beyond `edge` from `(x, y)` is on none of
+/// `regions`, i.e. `(x, y)` lies on an outer edge of the screen.
+fn is_exterior_point(regions: &[ScreenRegion], edge: Edge, x: i32, y: i32) -> bool {
+    let (bx, by) = match edge {
+        Edge::Left => (x - 1, y),
+        Edge::Right => (x + 1, y),
+        Edge::Top => (x, y - 1),
+        Edge::Bottom => (x, y + 1),
+    };
+    !regions.iter().any(|r| r.contains(bx, by))
+}
+
+/// `true` if the cursor at `(x, y)` on `monitor` faces the outside of the
+/// screen across `edge`, rather than another of its monitors.
+fn is_exterior(
+    regions: &[ScreenRegion],
+    monitor: &ScreenRegion,
+    edge: Edge,
+    x: i32,
+    y: i32,
+) -> bool {
+    let (ex, ey) = edge_point(monitor, edge, x, y);
+    is_exterior_point(regions, edge, ex, ey)
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -533,16 +1136,16 @@ mod tests {
     }
 
     fn make_client(x: i32, y: i32, w: u32, h: u32) -> ClientScreen {
-        ClientScreen {
-            client_id: Uuid::new_v4(),
-            region: ScreenRegion {
+        ClientScreen::new(
+            Uuid::new_v4(),
+            "test-client".to_string(),
+            ScreenRegion {
                 virtual_x: x,
                 virtual_y: y,
                 width: w,
                 height: h,
             },
-            name: "test-client".to_string(),
-        }
+        )
     }
 
     // ── ScreenRegion helpers ──────────────────────────────────────────────────
@@ -653,6 +1256,29 @@ mod tests {
         assert_eq!(layout.clients().count(), 2);
     }
 
+    #[test]
+    fn test_clients_in_order_sorts_top_to_bottom_then_left_to_right() {
+        // Arrange
+        let mut layout = make_layout(1920, 1080);
+        let below = make_client(0, 1080, 1920, 1080);
+        let right = make_client(1920, 0, 1920, 1080);
+        let left = make_client(-1280, 0, 1280, 1024);
+        let expected = [left.client_id, right.client_id, below.client_id];
+        for client in [below, right, left] {
+            layout.add_client(client).unwrap();
+        }
+
+        // Act
+        let order: Vec<_> = layout
+            .clients_in_order()
+            .iter()
+            .map(|c| c.client_id)
+            .collect();
+
+        // Assert
+        assert_eq!(order, expected);
+    }
+
     // ── remove_client ─────────────────────────────────────────────────────────
 
     #[test]
@@ -667,6 +1293,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -757,6 +1384,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -779,6 +1407,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -803,6 +1432,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -832,6 +1462,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -857,6 +1488,7 @@ mod tests {
                 from_edge: Edge::Top,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Bottom,
+                segment: None,
             })
             .unwrap();
 
@@ -883,6 +1515,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -905,6 +1538,7 @@ mod tests {
             from_edge: Edge::Right,
             to_screen: ScreenId::Client(cid),
             to_edge: Edge::Right, // same side – invalid
+            segment: None,
         });
         assert_eq!(result, Err(LayoutError::IncompatibleEdges));
     }
@@ -919,6 +1553,7 @@ mod tests {
             from_edge: Edge::Right,
             to_screen: ScreenId::Client(unknown_id),
             to_edge: Edge::Left,
+            segment: None,
         });
         assert_eq!(result, Err(LayoutError::InvalidAdjacency));
     }
@@ -939,6 +1574,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid1),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
         layout
@@ -947,6 +1583,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid2),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -999,6 +1636,532 @@ mod tests {
         assert_eq!(result, 0);
     }
 
+    // ── Clients with several monitors ─────────────────────────────────────────
+
+    fn monitor(id: u8, x: i32, y: i32, w: u32, h: u32) -> Monitor {
+        Monitor {
+            monitor_id: id,
+            x,
+            y,
+            width: w,
+            height: h,
+            is_primary: id == 0,
+            scale_factor: 100,
+            size_mm: None,
+        }
+    }
+
+    /// A client right of the master with a 1920×1080 primary monitor and a
+    /// shorter 1280×720 monitor to its right.
+    fn make_dual_monitor_client() -> ClientScreen {
+        ClientScreen::with_monitors(
+            Uuid::new_v4(),
+            "dual",
+            1920,
+            0,
+            vec![monitor(0, 0, 0, 1920, 1080), monitor(1, 1920, 0, 1280, 720)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_with_monitors_keeps_arrangement_inside_bounding_box() {
+        // Arrange – secondary monitor to the left of the primary
+        let client = ClientScreen::with_monitors(
+            Uuid::new_v4(),
+            "left-secondary",
+            1920,
+            0,
+            vec![
+                monitor(0, 0, 0, 1920, 1080),
+                monitor(1, -1280, 0, 1280, 1024),
+            ],
+        )
+        .unwrap();
+        let cid = client.client_id;
+        let mut layout = make_layout(1920, 1080);
+        layout.add_client(client).unwrap();
+        let screen = ScreenId::Client(cid);
+
+        // Act
+        let regions = layout.monitor_regions(&screen);
+
+        // Assert
+        assert_eq!(layout.get_region(&screen).unwrap().width, 3200);
+        assert_eq!((regions[0].virtual_x, regions[1].virtual_x), (3200, 1920));
+        assert_eq!(layout.to_local(&screen, 1920, 10), Some((-1280, 10)));
+        assert_eq!(layout.to_virtual(&screen, 0, 0), Some((3200, 0)));
+        assert_eq!(layout.screen_centre(&screen), Some((3200 + 960, 540)));
+    }
+
+    #[test]
+    fn test_with_monitors_returns_none_without_monitors() {
+        assert!(ClientScreen::with_monitors(Uuid::new_v4(), "none", 0, 0, vec![]).is_none());
+    }
+
+    #[test]
+    fn test_resolve_cursor_in_dead_space_moves_to_nearest_monitor() {
+        // Arrange
+        let mut layout = make_layout(1920, 1080);
+        let client = make_dual_monitor_client();
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+
+        // Act – below the short monitor
+        let loc = layout.resolve_cursor(1920 + 2500, 900);
+
+        // Assert
+        assert_eq!(
+            loc,
+            CursorLocation::OnClient {
+                client_id: cid,
+                local_x: 2500,
+                local_y: 719,
+            }
+        );
+    }
+
+    #[test]
+    fn test_clients_may_use_each_others_dead_space() {
+        // Arrange
+        let mut layout = make_layout(1920, 1080);
+        layout.add_client(make_dual_monitor_client()).unwrap();
+
+        // Act – fill the dead corner below the short monitor
+        let result = layout.add_client(make_client(1920 + 1920, 720, 1280, 360));
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_edge_transition_only_from_monitors_on_that_side() {
+        // Arrange – a second client to the right of the dual-monitor client
+        let mut layout = make_layout(1920, 1080);
+        let dual = make_dual_monitor_client();
+        let dual_id = dual.client_id;
+        let right = make_client(1920 + 3200, 0, 1920, 1080);
+        let right_id = right.client_id;
+        layout.add_client(dual).unwrap();
+        layout.add_client(right).unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Client(dual_id),
+                from_edge: Edge::Right,
+                to_screen: ScreenId::Client(right_id),
+                to_edge: Edge::Left,
+                segment: None,
+            })
+            .unwrap();
+        let dual_screen = ScreenId::Client(dual_id);
+
+        // Act
+        let between_monitors = layout.check_edge_transition(&dual_screen, 1919, 300);
+        let outer_edge = layout.check_edge_transition(&dual_screen, 3199, 360);
+
+        // Assert
+        assert!(between_monitors.is_none(), "monitors of a client connect");
+        let transition = outer_edge.expect("right edge of the second monitor");
+        assert_eq!(transition.to_screen, ScreenId::Client(right_id));
+        // 360 of 1080 (bounding box) → 360 of 1080
+        assert_eq!((transition.entry_x, transition.entry_y), (0, 360));
+        assert_eq!(transition.master_teleport_x, 1920 + 1);
+    }
+
+    #[test]
+    fn test_edge_transition_enters_on_a_monitor_forming_the_edge() {
+        // Arrange – the client's left monitor is shorter than the master
+        let mut layout = make_layout(1920, 1080);
+        let client = ClientScreen::with_monitors(
+            Uuid::new_v4(),
+            "short-left",
+            1920,
+            0,
+            vec![monitor(0, 0, 0, 1280, 720), monitor(1, 1280, 0, 1920, 1080)],
+        )
+        .unwrap();
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Master,
+                from_edge: Edge::Right,
+                to_screen: ScreenId::Client(cid),
+                to_edge: Edge::Left,
+                segment: None,
+            })
+            .unwrap();
+
+        // Act – near the bottom of the master's right edge
+        let transition = layout
+            .check_edge_transition(&ScreenId::Master, 1919, 1000)
+            .expect("should transition");
+
+        // Assert – lands on the bottom row of the short monitor, not in dead space
+        assert_eq!((transition.entry_x, transition.entry_y), (0, 719));
+    }
+
+    #[test]
+    fn test_update_client_region_moves_multi_monitor_client() {
+        // Arrange
+        let mut layout = make_layout(1920, 1080);
+        let client = make_dual_monitor_client();
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+
+        // Act
+        let moved = ScreenRegion {
+            virtual_x: 0,
+            virtual_y: 1080,
+            width: 1,
+            height: 1,
+        };
+        layout.update_client_region(cid, moved).unwrap();
+
+        // Assert
+        let regions = layout.monitor_regions(&ScreenId::Client(cid));
+        assert_eq!(regions.len(), 2);
+        assert_eq!((regions[1].virtual_x, regions[1].virtual_y), (1920, 1080));
+        assert_eq!(regions[1].width, 1280);
+    }
+
+    // ── Master with several monitors ──────────────────────────────────────────
+
+    /// A 1920×1080 primary with a portrait 1080×1920 monitor to its right,
+    /// reaching 420 px higher than the primary.
+    fn make_dual_monitor_master() -> VirtualLayout {
+        VirtualLayout::with_master_monitors(vec![
+            monitor(0, 0, 0, 1920, 1080),
+            monitor(1, 1920, -420, 1080, 1920),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_master_bounding_box_covers_all_monitors() {
+        let layout = make_dual_monitor_master();
+        assert_eq!(
+            layout.master,
+            ScreenRegion {
+                virtual_x: 0,
+                virtual_y: -420,
+                width: 3000,
+                height: 1920,
+            }
+        );
+        assert_eq!(layout.screen_centre(&ScreenId::Master), Some((960, 540)));
+    }
+
+    #[test]
+    fn test_resolve_cursor_in_master_dead_corner_stays_on_a_monitor() {
+        let layout = make_dual_monitor_master();
+        assert_eq!(
+            layout.resolve_cursor(100, -200),
+            CursorLocation::OnMaster {
+                local_x: 100,
+                local_y: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_client_may_use_master_dead_corner() {
+        let mut layout = make_dual_monitor_master();
+        assert!(layout.add_client(make_client(0, -420, 1920, 420)).is_ok());
+        assert_eq!(
+            layout.add_client(make_client(1920, 0, 100, 100)),
+            Err(LayoutError::Overlap),
+            "the portrait monitor is taken"
+        );
+    }
+
+    #[test]
+    fn test_master_edges_are_detected_per_monitor() {
+        // Arrange – a client to the right of the portrait monitor
+        let mut layout = make_dual_monitor_master();
+        let client = make_client(3000, -420, 1920, 1080);
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Master,
+                from_edge: Edge::Right,
+                to_screen: ScreenId::Client(cid),
+                to_edge: Edge::Left,
+                segment: None,
+            })
+            .unwrap();
+
+        // Act
+        let inner_edge = layout.check_edge_transition(&ScreenId::Master, 1919, 500);
+        let outer_edge = layout.check_edge_transition(&ScreenId::Master, 2999, -100);
+
+        // Assert
+        assert!(
+            inner_edge.is_none(),
+            "the primary's right edge leads to the portrait monitor"
+        );
+        let transition = outer_edge.expect("right edge of the portrait monitor");
+        // Parked on the left side of the portrait monitor, not of the primary
+        assert_eq!(
+            (transition.master_teleport_x, transition.master_teleport_y),
+            (1921, -100)
+        );
+    }
+
+    #[test]
+    fn test_transition_to_master_teleports_to_entry_on_the_right_monitor() {
+        // Arrange
+        let mut layout = make_dual_monitor_master();
+        let client = make_client(3000, -420, 1920, 1920);
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Client(cid),
+                from_edge: Edge::Left,
+                to_screen: ScreenId::Master,
+                to_edge: Edge::Right,
+                segment: None,
+            })
+            .unwrap();
+
+        // Act – leave the client's left edge a quarter of the way down
+        let transition = layout
+            .check_edge_transition(&ScreenId::Client(cid), 0, 480)
+            .expect("should transition");
+
+        // Assert – enters the portrait monitor's right edge at the same height
+        assert_eq!((transition.entry_x, transition.entry_y), (2999, 60));
+        assert_eq!(
+            (transition.master_teleport_x, transition.master_teleport_y),
+            (2999, 60)
+        );
+    }
+
+    // ── Edge segments ─────────────────────────────────────────────────────────
+
+    fn segment_adjacency(to: ClientId, start: i32, end: i32) -> Adjacency {
+        Adjacency {
+            from_screen: ScreenId::Master,
+            from_edge: Edge::Right,
+            to_screen: ScreenId::Client(to),
+            to_edge: Edge::Left,
+            segment: Some(EdgeSegment { start, end }),
+        }
+    }
+
+    #[test]
+    fn test_segment_adjacency_only_covers_its_part_of_the_edge() {
+        // Arrange – a 4K master with a laptop beside its lower half
+        let mut layout = make_layout(3840, 2160);
+        let laptop = make_client(3840, 1080, 1920, 1080);
+        let cid = laptop.client_id;
+        layout.add_client(laptop).unwrap();
+        layout
+            .set_adjacency(segment_adjacency(cid, 1080, 2160))
+            .unwrap();
+
+        // Act
+        let upper = layout.check_edge_transition(&ScreenId::Master, 3839, 500);
+        let lower = layout.check_edge_transition(&ScreenId::Master, 3839, 1500);
+
+        // Assert
+        assert!(upper.is_none(), "nothing beside the upper half");
+        let transition = lower.expect("laptop beside the lower half");
+        assert_eq!(transition.to_screen, ScreenId::Client(cid));
+    }
+
+    #[test]
+    fn test_segment_adjacency_maps_by_physical_overlap() {
+        // Arrange
+        let mut layout = make_layout(3840, 2160);
+        let laptop = make_client(3840, 1080, 1920, 1080);
+        let cid = laptop.client_id;
+        layout.add_client(laptop).unwrap();
+        layout
+            .set_adjacency(segment_adjacency(cid, 1080, 2160))
+            .unwrap();
+
+        // Act
+        let transition = layout
+            .check_edge_transition(&ScreenId::Master, 3839, 1500)
+            .expect("should transition");
+
+        // Assert – same virtual height, not stretched over the master's height
+        assert_eq!((transition.entry_x, transition.entry_y), (0, 420));
+    }
+
+    #[test]
+    fn test_one_edge_may_lead_to_several_neighbours() {
+        // Arrange – two laptops stacked beside a 4K master
+        let mut layout = make_layout(3840, 2160);
+        let upper = make_client(3840, 0, 1920, 1080);
+        let lower = make_client(3840, 1080, 1920, 1080);
+        let (upper_id, lower_id) = (upper.client_id, lower.client_id);
+        layout.add_client(upper).unwrap();
+        layout.add_client(lower).unwrap();
+        layout
+            .set_adjacency(segment_adjacency(upper_id, 0, 1080))
+            .unwrap();
+        layout
+            .set_adjacency(segment_adjacency(lower_id, 1080, 2160))
+            .unwrap();
+
+        // Act
+        let to_upper = layout.check_edge_transition(&ScreenId::Master, 3839, 1079);
+        let to_lower = layout.check_edge_transition(&ScreenId::Master, 3839, 1080);
+
+        // Assert
+        assert_eq!(layout.adjacencies.len(), 2);
+        assert_eq!(
+            to_upper.map(|t| t.to_screen),
+            Some(ScreenId::Client(upper_id))
+        );
+        assert_eq!(
+            to_lower.map(|t| t.to_screen),
+            Some(ScreenId::Client(lower_id))
+        );
+    }
+
+    #[test]
+    fn test_set_adjacency_replaces_only_overlapping_segments() {
+        // Arrange
+        let mut layout = make_layout(3840, 2160);
+        let client = make_client(3840, 0, 1920, 2160);
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+        layout
+            .set_adjacency(segment_adjacency(cid, 0, 1080))
+            .unwrap();
+        layout
+            .set_adjacency(segment_adjacency(cid, 1080, 2160))
+            .unwrap();
+
+        // Act
+        layout
+            .set_adjacency(segment_adjacency(cid, 1000, 1200))
+            .unwrap();
+
+        // Assert – both earlier segments overlap the new one
+        assert_eq!(layout.adjacencies, vec![segment_adjacency(cid, 1000, 1200)]);
+    }
+
+    #[test]
+    fn test_set_adjacency_rejects_empty_segment() {
+        let mut layout = make_layout(1920, 1080);
+        let client = make_client(1920, 0, 1920, 1080);
+        let cid = client.client_id;
+        layout.add_client(client).unwrap();
+
+        let result = layout.set_adjacency(segment_adjacency(cid, 500, 500));
+
+        assert_eq!(
+            result,
+            Err(LayoutError::EmptySegment {
+                start: 500,
+                end: 500
+            })
+        );
+    }
+
+    // ── Physical units ────────────────────────────────────────────────────────
+
+    /// A 1920×1080 master at 100 % with a 2560×1600 laptop at 200 % (1280×800
+    /// logical pixels) whose top is at `laptop_y`, to the master's right.
+    fn make_hidpi_layout(laptop_y: i32, segment: Option<EdgeSegment>) -> (VirtualLayout, ClientId) {
+        let mut layout = make_layout(1920, 1080);
+        let laptop = ClientScreen::with_monitors(
+            Uuid::new_v4(),
+            "laptop",
+            1920,
+            laptop_y,
+            vec![Monitor {
+                scale_factor: 200,
+                ..Monitor::primary(2560, 1600)
+            }],
+        )
+        .unwrap();
+        let cid = laptop.client_id;
+        layout.add_client(laptop).unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Master,
+                from_edge: Edge::Right,
+                to_screen: ScreenId::Client(cid),
+                to_edge: Edge::Left,
+                segment,
+            })
+            .unwrap();
+        layout.set_physical_units(true);
+        (layout, cid)
+    }
+
+    #[test]
+    fn test_pixel_size_prefers_physical_size_over_scale_factor() {
+        let scaled = Monitor {
+            scale_factor: 150,
+            ..Monitor::primary(3840, 2160)
+        };
+        // 27-inch 4K: 597 mm wide, about 163 DPI
+        let measured = Monitor {
+            size_mm: Some((597, 336)),
+            ..scaled.clone()
+        };
+
+        assert!((scaled.pixel_size() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((measured.pixel_size() - 0.5876).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_physical_units_keep_distance_from_edge_middle() {
+        // Arrange
+        let (layout, _) = make_hidpi_layout(0, None);
+
+        // Act – 200 master pixels above the middle of the master's edge
+        let transition = layout
+            .check_edge_transition(&ScreenId::Master, 1919, 340)
+            .expect("should transition");
+
+        // Assert – 400 laptop pixels above the middle of the laptop's edge
+        assert_eq!((transition.entry_x, transition.entry_y), (0, 400));
+    }
+
+    #[test]
+    fn test_physical_units_keep_distance_from_segment_start() {
+        // Arrange – the laptop meets the master from y = 500 down
+        let segment = EdgeSegment {
+            start: 500,
+            end: 1080,
+        };
+        let (mut layout, _) = make_hidpi_layout(500, Some(segment));
+
+        // Act
+        let physical = layout.check_edge_transition(&ScreenId::Master, 1919, 700);
+        layout.set_physical_units(false);
+        let pixels = layout.check_edge_transition(&ScreenId::Master, 1919, 700);
+
+        // Assert – 200 master pixels below the corner are 400 laptop pixels
+        assert_eq!(physical.map(|t| t.entry_y), Some(400));
+        assert_eq!(pixels.map(|t| t.entry_y), Some(200));
+    }
+
+    #[test]
+    fn test_pointer_speed_follows_pixel_size() {
+        // Arrange
+        let (mut layout, cid) = make_hidpi_layout(0, None);
+        let laptop = ScreenId::Client(cid);
+
+        // Act
+        let physical = layout.pointer_speed(&laptop, 2000, 100);
+        let on_master = layout.pointer_speed(&ScreenId::Master, 100, 100);
+        layout.set_physical_units(false);
+        let pixels = layout.pointer_speed(&laptop, 2000, 100);
+
+        // Assert
+        assert_eq!((physical, on_master, pixels), (2.0, 1.0, 1.0));
+    }
+
     // ── update_client_region ──────────────────────────────────────────────────
 
     #[test]
@@ -30,7 +30,9 @@
 //! # The `Unknown` sentinel
 //!
 //! Not every key has a universally assigned HID code (e.g., some multimedia
-//! keys are vendor-specific).  [`HidKeyCode::Unknown`] (value 0x0000) is used
+//! keys are vendor-specific).  Media transport keys such as Play/Pause live
+//! on the Consumer page (0x0C) rather than the keyboard page, so only the
+//! volume keys (0x7F–0x81) are represented here.  [`HidKeyCode::Unknown`] (value 0x0000) is used
 //! as a placeholder for any key that has no standard mapping.  The encoder
 //! will still transmit the event with code 0x0000 so the receiver can decide
 //! what to do with it.
@@ -153,8 +155,42 @@ pub enum HidKeyCode {
     Numpad0 = 0x62,
     NumpadDecimal = 0x63,
 
+    // Non-US \ and | next to Left Shift on ISO keyboards (HID 0x64)
+    IntlBackslash = 0x64,
+
     // Application key (HID 0x65)
     ContextMenu = 0x65,
+    Power = 0x66,
+    NumpadEqual = 0x67,
+
+    // Extended function keys (HID 0x68–0x73)
+    F13 = 0x68,
+    F14 = 0x69,
+    F15 = 0x6A,
+    F16 = 0x6B,
+    F17 = 0x6C,
+    F18 = 0x6D,
+    F19 = 0x6E,
+    F20 = 0x6F,
+    F21 = 0x70,
+    F22 = 0x71,
+    F23 = 0x72,
+    F24 = 0x73,
+
+    // Media keys on the keyboard page (HID 0x7F–0x81)
+    AudioVolumeMute = 0x7F,
+    AudioVolumeUp = 0x80,
+    AudioVolumeDown = 0x81,
+
+    // International and language keys (HID 0x85–0x91)
+    NumpadComma = 0x85,
+    IntlRo = 0x87,
+    KanaMode = 0x88,
+    IntlYen = 0x89,
+    Convert = 0x8A,
+    NonConvert = 0x8B,
+    Lang1 = 0x90,
+    Lang2 = 0x91,
 
     // Modifier keys (HID 0xE0–0xE7)
     ControlLeft = 0xE0,
@@ -175,7 +211,7 @@ impl HidKeyCode {
     ///
     /// Returns [`HidKeyCode::Unknown`] if the value does not correspond to a
     /// known key code variant.
-    pub fn from_u16(value: u16) -> Self {
+    pub const fn from_u16(value: u16) -> Self {
         match value {
             0x04 => HidKeyCode::KeyA,
             0x05 => HidKeyCode::KeyB,
@@ -272,7 +308,33 @@ impl HidKeyCode {
             0x61 => HidKeyCode::Numpad9,
             0x62 => HidKeyCode::Numpad0,
             0x63 => HidKeyCode::NumpadDecimal,
+            0x64 => HidKeyCode::IntlBackslash,
             0x65 => HidKeyCode::ContextMenu,
+            0x66 => HidKeyCode::Power,
+            0x67 => HidKeyCode::NumpadEqual,
+            0x68 => HidKeyCode::F13,
+            0x69 => HidKeyCode::F14,
+            0x6A => HidKeyCode::F15,
+            0x6B => HidKeyCode::F16,
+            0x6C => HidKeyCode::F17,
+            0x6D => HidKeyCode::F18,
+            0x6E => HidKeyCode::F19,
+            0x6F => HidKeyCode::F20,
+            0x70 => HidKeyCode::F21,
+            0x71 => HidKeyCode::F22,
+            0x72 => HidKeyCode::F23,
+            0x73 => HidKeyCode::F24,
+            0x7F => HidKeyCode::AudioVolumeMute,
+            0x80 => HidKeyCode::AudioVolumeUp,
+            0x81 => HidKeyCode::AudioVolumeDown,
+            0x85 => HidKeyCode::NumpadComma,
+            0x87 => HidKeyCode::IntlRo,
+            0x88 => HidKeyCode::KanaMode,
+            0x89 => HidKeyCode::IntlYen,
+            0x8A => HidKeyCode::Convert,
+            0x8B => HidKeyCode::NonConvert,
+            0x90 => HidKeyCode::Lang1,
+            0x91 => HidKeyCode::Lang2,
             0xE0 => HidKeyCode::ControlLeft,
             0xE1 => HidKeyCode::ShiftLeft,
             0xE2 => HidKeyCode::AltLeft,
@@ -340,7 +402,16 @@ mod tests {
         (0x53, HidKeyCode::NumLock),
         (0x58, HidKeyCode::NumpadEnter),
         (0x62, HidKeyCode::Numpad0),
+        (0x64, HidKeyCode::IntlBackslash),
         (0x65, HidKeyCode::ContextMenu),
+        (0x67, HidKeyCode::NumpadEqual),
+        (0x68, HidKeyCode::F13),
+        (0x73, HidKeyCode::F24),
+        (0x7F, HidKeyCode::AudioVolumeMute),
+        (0x81, HidKeyCode::AudioVolumeDown),
+        (0x87, HidKeyCode::IntlRo),
+        (0x89, HidKeyCode::IntlYen),
+        (0x91, HidKeyCode::Lang2),
         (0xE0, HidKeyCode::ControlLeft),
         (0xE1, HidKeyCode::ShiftLeft),
         (0xE2, HidKeyCode::AltLeft),
@@ -394,7 +465,7 @@ mod tests {
     #[test]
     fn test_unknown_u16_values_return_unknown() {
         // Values that are not assigned in HID keyboard/keypad page
-        for unassigned in [0x00, 0x01, 0x02, 0x03, 0x32, 0x64, 0xA0, 0xFF] {
+        for unassigned in [0x00, 0x01, 0x02, 0x03, 0x32, 0x74, 0x86, 0xA0, 0xFF] {
             let result = HidKeyCode::from_u16(unassigned);
             assert_eq!(
                 result,
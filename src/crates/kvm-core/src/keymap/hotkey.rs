@@ -0,0 +1,636 @@
+//! Hotkey grammar and chord matching over HID usages.
+//!
+//! Hotkeys are written as text in the configuration (`disable_hotkey` and
+//! friends) and parsed into a [`Hotkey`]: a sequence of one or more
+//! [`Chord`]s, each a set of modifiers plus one key.
+//!
+//! # Grammar
+//!
+//! ```text
+//! hotkey   = chord { whitespace chord }      e.g. "ScrollLock ScrollLock"
+//! chord    = { modifier "+" } key            e.g. "Ctrl+Alt+Right"
+//! modifier = "Ctrl" | "Control" | "Shift" | "Alt" | "Option"
+//!          | "Meta" | "Win" | "Super" | "Cmd" | "Command"
+//! key      = a DOM `KeyboardEvent.code` name ("KeyA", "ArrowRight", "F13")
+//!          | an alias ("A", "1", "Right", "Esc", "PgUp", ...)
+//! ```
+//!
+//! Names are case-insensitive.  Modifiers match either side of the keyboard,
+//! so `Ctrl+Alt+Right` fires with Left or Right Ctrl.  To bind a modifier key
+//! itself, name its side: `"ShiftLeft ShiftLeft"` is a double-tap of Left
+//! Shift.
+//!
+//! Chords separated by whitespace must be pressed one after the other, each
+//! within [`SEQUENCE_TIMEOUT_MS`] of the previous one.  Two chords on the same
+//! key make a double-tap.
+//!
+//! The older spelling `"ScrollLock+ScrollLock"`, where the same key is joined
+//! to itself with `+`, is accepted as a double-tap too, since a chord cannot
+//! contain one key twice.
+//!
+//! # Matching (for beginners)
+//!
+//! A [`HotkeyMatcher`] watches the key-down events as they arrive and
+//! remembers how far into the sequence the user has got.  A chord matches
+//! when its key goes down while *exactly* its modifiers are held: extra
+//! modifiers mean a different shortcut (`Ctrl+Shift+Right` is not
+//! `Ctrl+Right`).  Autorepeat of a held key never advances a sequence, so
+//! holding ScrollLock does not count as a double-tap.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::hid::HidKeyCode;
+use super::KeyMapper;
+use crate::protocol::messages::ModifierFlags;
+
+/// Longest gap allowed between the chords of a sequence, in milliseconds.
+pub const SEQUENCE_TIMEOUT_MS: u32 = 500;
+
+/// Most chords a single hotkey may chain.
+pub const MAX_SEQUENCE_LEN: usize = 4;
+
+/// Error returned when a hotkey string does not follow the grammar.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HotkeyParseError {
+    /// The string contains no chords.
+    #[error("hotkey is empty")]
+    Empty,
+
+    /// A name is neither a modifier nor a known key.
+    #[error("unknown key name {0:?}")]
+    UnknownKey(String),
+
+    /// A chord has only modifiers, e.g. `"Ctrl+Alt"`.
+    #[error("chord {0:?} has no key after its modifiers")]
+    MissingKey(String),
+
+    /// A chord names two different keys, e.g. `"A+B"`.
+    #[error("chord {0:?} has more than one key")]
+    MultipleKeys(String),
+
+    /// The hotkey chains more than [`MAX_SEQUENCE_LEN`] chords.
+    #[error("hotkey has {0} chords; at most {MAX_SEQUENCE_LEN} are allowed")]
+    TooLong(usize),
+}
+
+/// One step of a hotkey: modifiers held while a key goes down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    /// Either Ctrl must be held.
+    pub ctrl: bool,
+    /// Either Shift must be held.
+    pub shift: bool,
+    /// Either Alt must be held.
+    pub alt: bool,
+    /// Either Meta (Win/Cmd/Super) must be held.
+    pub meta: bool,
+    /// The key whose key-down completes the chord.
+    pub key: HidKeyCode,
+}
+
+impl Chord {
+    /// A chord of `key` alone, with no modifiers.
+    pub fn plain(key: HidKeyCode) -> Self {
+        Self {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+            key,
+        }
+    }
+
+    /// `true` if `key` going down while `modifiers` are held completes this
+    /// chord.
+    ///
+    /// The modifier bit of `key` itself is ignored, because it is already set
+    /// when a modifier key's own key-down is reported.
+    pub fn matches(&self, key: HidKeyCode, modifiers: ModifierFlags) -> bool {
+        if key != self.key {
+            return false;
+        }
+        let held = ModifierFlags(modifiers.0 & !modifier_bit(key));
+        held.ctrl() == self.ctrl
+            && held.shift() == self.shift
+            && held.alt() == self.alt
+            && held.meta() == self.meta
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.shift, "Shift"),
+            (self.alt, "Alt"),
+            (self.meta, "Meta"),
+        ] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+        match KeyMapper::hid_to_dom_code(self.key) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:?}", self.key),
+        }
+    }
+}
+
+/// A parsed hotkey: one or more chords pressed in sequence.
+///
+/// Parse one with [`str::parse`]; [`Display`](fmt::Display) writes the
+/// canonical spelling, which parses back to the same hotkey.
+///
+/// ```
+/// use kvm_core::keymap::hotkey::Hotkey;
+/// use kvm_core::keymap::HidKeyCode;
+///
+/// let hotkey: Hotkey = "ctrl+alt+right".parse().unwrap();
+/// assert_eq!(hotkey.chords()[0].key, HidKeyCode::ArrowRight);
+/// assert_eq!(hotkey.to_string(), "Ctrl+Alt+ArrowRight");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    chords: Vec<Chord>,
+}
+
+impl Hotkey {
+    /// A hotkey of a single chord.
+    pub fn single(chord: Chord) -> Self {
+        Self {
+            chords: vec![chord],
+        }
+    }
+
+    /// The chords in the order they must be pressed.
+    pub fn chords(&self) -> &[Chord] {
+        &self.chords
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = HotkeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chords = Vec::new();
+        for word in s.split_whitespace() {
+            chords.extend(parse_word(word)?);
+        }
+        if chords.is_empty() {
+            return Err(HotkeyParseError::Empty);
+        }
+        if chords.len() > MAX_SEQUENCE_LEN {
+            return Err(HotkeyParseError::TooLong(chords.len()));
+        }
+        Ok(Self { chords })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chord) in self.chords.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{chord}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses one whitespace-free word: a chord, or the legacy `Key+Key`
+/// double-tap spelling (which yields one chord per repetition).
+fn parse_word(word: &str) -> Result<Vec<Chord>, HotkeyParseError> {
+    let parts: Vec<&str> = word.split('+').collect();
+    let (last, modifiers) = parts.split_last().expect("split yields at least one part");
+
+    let mut chord = Chord::plain(HidKeyCode::Unknown);
+    for (i, part) in modifiers.iter().enumerate() {
+        match parse_modifier(part) {
+            Some(Modifier::Ctrl) => chord.ctrl = true,
+            Some(Modifier::Shift) => chord.shift = true,
+            Some(Modifier::Alt) => chord.alt = true,
+            Some(Modifier::Meta) => chord.meta = true,
+            None if i == 0 => return parse_repeated_key(word, &parts),
+            None => return Err(HotkeyParseError::MultipleKeys(word.to_string())),
+        }
+    }
+
+    if parse_modifier(last).is_some() {
+        return Err(HotkeyParseError::MissingKey(word.to_string()));
+    }
+    chord.key = parse_key(last)?;
+    Ok(vec![chord])
+}
+
+/// The legacy `Key+Key` form: every part must name the same key.
+fn parse_repeated_key(word: &str, parts: &[&str]) -> Result<Vec<Chord>, HotkeyParseError> {
+    let key = parse_key(parts[0])?;
+    for part in &parts[1..] {
+        if parse_key(part)? != key {
+            return Err(HotkeyParseError::MultipleKeys(word.to_string()));
+        }
+    }
+    Ok(vec![Chord::plain(key); parts.len()])
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Meta,
+}
+
+fn parse_modifier(name: &str) -> Option<Modifier> {
+    match name.to_ascii_lowercase().as_str() {
+        "ctrl" | "control" => Some(Modifier::Ctrl),
+        "shift" => Some(Modifier::Shift),
+        "alt" | "option" => Some(Modifier::Alt),
+        "meta" | "win" | "super" | "cmd" | "command" => Some(Modifier::Meta),
+        _ => None,
+    }
+}
+
+fn parse_key(name: &str) -> Result<HidKeyCode, HotkeyParseError> {
+    let lower = name.to_ascii_lowercase();
+    let alias = match lower.as_str() {
+        "left" => Some(HidKeyCode::ArrowLeft),
+        "right" => Some(HidKeyCode::ArrowRight),
+        "up" => Some(HidKeyCode::ArrowUp),
+        "down" => Some(HidKeyCode::ArrowDown),
+        "esc" => Some(HidKeyCode::Escape),
+        "return" => Some(HidKeyCode::Enter),
+        "del" => Some(HidKeyCode::Delete),
+        "ins" => Some(HidKeyCode::Insert),
+        "pgup" => Some(HidKeyCode::PageUp),
+        "pgdn" => Some(HidKeyCode::PageDown),
+        "scrlk" => Some(HidKeyCode::ScrollLock),
+        "break" => Some(HidKeyCode::Pause),
+        "menu" => Some(HidKeyCode::ContextMenu),
+        _ => None,
+    };
+    if let Some(key) = alias {
+        return Ok(key);
+    }
+
+    // Single letters and digits: "A" is KeyA, "1" is Digit1.
+    let mut chars = lower.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let dom = match c {
+            'a'..='z' => Some(format!("Key{}", c.to_ascii_uppercase())),
+            '0'..='9' => Some(format!("Digit{c}")),
+            _ => None,
+        };
+        if let Some(dom) = dom {
+            return parse_key(&dom);
+        }
+    }
+
+    (0u16..=0xFF)
+        .map(HidKeyCode::from_u16)
+        .find(|key| {
+            KeyMapper::hid_to_dom_code(*key).is_some_and(|dom| dom.eq_ignore_ascii_case(name))
+        })
+        .ok_or_else(|| HotkeyParseError::UnknownKey(name.to_string()))
+}
+
+/// The [`ModifierFlags`] bit of a modifier key, or 0 for other keys.
+fn modifier_bit(key: HidKeyCode) -> u8 {
+    match key {
+        HidKeyCode::ControlLeft => ModifierFlags::LEFT_CTRL,
+        HidKeyCode::ControlRight => ModifierFlags::RIGHT_CTRL,
+        HidKeyCode::ShiftLeft => ModifierFlags::LEFT_SHIFT,
+        HidKeyCode::ShiftRight => ModifierFlags::RIGHT_SHIFT,
+        HidKeyCode::AltLeft => ModifierFlags::LEFT_ALT,
+        HidKeyCode::AltRight => ModifierFlags::RIGHT_ALT,
+        HidKeyCode::MetaLeft => ModifierFlags::LEFT_META,
+        HidKeyCode::MetaRight => ModifierFlags::RIGHT_META,
+        _ => 0,
+    }
+}
+
+/// Tracks progress through one [`Hotkey`] as key events arrive.
+///
+/// Feed it every key-down and key-up; [`key_down`](Self::key_down) returns
+/// `true` on the event that completes the hotkey.
+#[derive(Debug, Clone)]
+pub struct HotkeyMatcher {
+    hotkey: Hotkey,
+    /// Number of chords matched so far.
+    progress: usize,
+    /// Time of the key-down that matched the last chord.
+    last_step_ms: u32,
+    /// The most recently pressed key, while it is held; its autorepeat is
+    /// ignored.
+    held: Option<HidKeyCode>,
+}
+
+impl HotkeyMatcher {
+    /// Creates a matcher waiting for the first chord of `hotkey`.
+    pub fn new(hotkey: Hotkey) -> Self {
+        Self {
+            hotkey,
+            progress: 0,
+            last_step_ms: 0,
+            held: None,
+        }
+    }
+
+    /// The hotkey this matcher recognises.
+    pub fn hotkey(&self) -> &Hotkey {
+        &self.hotkey
+    }
+
+    /// Handles a key-down; returns `true` if it completes the hotkey.
+    ///
+    /// `modifiers` is the modifier state including this key; `time_ms` is
+    /// the event timestamp (it may wrap around).
+    pub fn key_down(&mut self, key: HidKeyCode, modifiers: ModifierFlags, time_ms: u32) -> bool {
+        if self.held == Some(key) {
+            return false; // autorepeat
+        }
+        self.held = Some(key);
+
+        if self.progress > 0 && time_ms.wrapping_sub(self.last_step_ms) > SEQUENCE_TIMEOUT_MS {
+            self.progress = 0;
+        }
+        if self.advance(key, modifiers, time_ms) {
+            return self.complete();
+        }
+        // Modifiers pressed on the way to the next chord keep the progress.
+        if key.is_modifier() || self.progress == 0 {
+            return false;
+        }
+        // A wrong key restarts the sequence, possibly as its first chord.
+        self.progress = 0;
+        self.advance(key, modifiers, time_ms) && self.complete()
+    }
+
+    /// Handles a key-up.
+    pub fn key_up(&mut self, key: HidKeyCode) {
+        if self.held == Some(key) {
+            self.held = None;
+        }
+    }
+
+    /// Forgets any partial sequence.
+    pub fn reset(&mut self) {
+        self.progress = 0;
+    }
+
+    /// Moves past the next chord if `key` matches it.
+    fn advance(&mut self, key: HidKeyCode, modifiers: ModifierFlags, time_ms: u32) -> bool {
+        if self.hotkey.chords[self.progress].matches(key, modifiers) {
+            self.progress += 1;
+            self.last_step_ms = time_ms;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` (and starts over) once every chord has matched.
+    fn complete(&mut self) -> bool {
+        if self.progress == self.hotkey.chords.len() {
+            self.progress = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL_ALT: ModifierFlags =
+        ModifierFlags(ModifierFlags::LEFT_CTRL | ModifierFlags::RIGHT_ALT);
+    const NONE: ModifierFlags = ModifierFlags(0);
+
+    fn parse(s: &str) -> Hotkey {
+        s.parse()
+            .unwrap_or_else(|e| panic!("{s:?} should parse: {e}"))
+    }
+
+    // ── Parsing ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_chord_with_modifiers_and_alias() {
+        // Act
+        let hotkey = parse("Ctrl+Alt+Right");
+
+        // Assert
+        assert_eq!(
+            hotkey.chords(),
+            [Chord {
+                ctrl: true,
+                alt: true,
+                ..Chord::plain(HidKeyCode::ArrowRight)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_is_case_insensitive_and_accepts_dom_codes() {
+        assert_eq!(parse("CMD+shift+keyk"), parse("Meta+Shift+K"));
+        assert_eq!(parse("f13").chords()[0].key, HidKeyCode::F13);
+        assert_eq!(parse("Ctrl+3").chords()[0].key, HidKeyCode::Digit3);
+    }
+
+    #[test]
+    fn test_parse_whitespace_separates_sequence() {
+        // Act
+        let hotkey = parse("  ScrollLock   ScrollLock ");
+
+        // Assert
+        assert_eq!(hotkey.chords(), [Chord::plain(HidKeyCode::ScrollLock); 2]);
+    }
+
+    #[test]
+    fn test_parse_legacy_plus_double_tap() {
+        assert_eq!(
+            parse("ScrollLock+ScrollLock"),
+            parse("ScrollLock ScrollLock")
+        );
+    }
+
+    #[test]
+    fn test_parse_side_specific_modifier_key() {
+        assert_eq!(
+            parse("ShiftLeft ShiftLeft").chords()[0],
+            Chord::plain(HidKeyCode::ShiftLeft)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_hotkeys() {
+        let cases = [
+            ("", HotkeyParseError::Empty),
+            ("   ", HotkeyParseError::Empty),
+            ("Ctrl+Hyper", HotkeyParseError::UnknownKey("Hyper".into())),
+            ("Ctrl+Alt", HotkeyParseError::MissingKey("Ctrl+Alt".into())),
+            ("Ctrl+", HotkeyParseError::UnknownKey("".into())),
+            ("A+B", HotkeyParseError::MultipleKeys("A+B".into())),
+            (
+                "Ctrl+A+B",
+                HotkeyParseError::MultipleKeys("Ctrl+A+B".into()),
+            ),
+            ("A A A A A", HotkeyParseError::TooLong(5)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<Hotkey>(), Err(expected), "input {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for input in ["Ctrl+Alt+Right", "scrolllock+scrolllock", "Win+Shift+F13 A"] {
+            // Arrange
+            let hotkey = parse(input);
+
+            // Act
+            let text = hotkey.to_string();
+
+            // Assert
+            assert_eq!(parse(&text), hotkey, "{input:?} printed as {text:?}");
+        }
+        assert_eq!(parse("scrlk scrlk").to_string(), "ScrollLock ScrollLock");
+    }
+
+    // ── Matching ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_chord_fires_with_either_side_modifiers() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("Ctrl+Alt+Right"));
+
+        // Act / Assert
+        assert!(matcher.key_down(HidKeyCode::ArrowRight, CTRL_ALT, 0));
+    }
+
+    #[test]
+    fn test_chord_requires_exact_modifiers() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("Ctrl+Right"));
+        let ctrl_shift = ModifierFlags(ModifierFlags::LEFT_CTRL | ModifierFlags::LEFT_SHIFT);
+
+        // Act / Assert
+        assert!(!matcher.key_down(HidKeyCode::ArrowRight, NONE, 0));
+        matcher.key_up(HidKeyCode::ArrowRight);
+        assert!(!matcher.key_down(HidKeyCode::ArrowRight, ctrl_shift, 10));
+    }
+
+    #[test]
+    fn test_double_tap_fires_on_second_press() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("ScrollLock ScrollLock"));
+
+        // Act
+        let first = matcher.key_down(HidKeyCode::ScrollLock, NONE, 1000);
+        matcher.key_up(HidKeyCode::ScrollLock);
+        let second = matcher.key_down(HidKeyCode::ScrollLock, NONE, 1200);
+
+        // Assert
+        assert!(!first);
+        assert!(second);
+    }
+
+    #[test]
+    fn test_double_tap_ignores_autorepeat() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("ScrollLock ScrollLock"));
+
+        // Act – held down: the key-down repeats without a key-up.
+        let fired = (0..5).any(|i| matcher.key_down(HidKeyCode::ScrollLock, NONE, i * 30));
+
+        // Assert
+        assert!(!fired);
+    }
+
+    #[test]
+    fn test_held_key_fires_once() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("F13"));
+
+        // Act
+        let first = matcher.key_down(HidKeyCode::F13, NONE, 0);
+        matcher.reset();
+        let repeat = matcher.key_down(HidKeyCode::F13, NONE, 30);
+
+        // Assert
+        assert!(first);
+        assert!(!repeat);
+    }
+
+    #[test]
+    fn test_sequence_times_out() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("ScrollLock ScrollLock"));
+        matcher.key_down(HidKeyCode::ScrollLock, NONE, 1000);
+        matcher.key_up(HidKeyCode::ScrollLock);
+
+        // Act – too slow, so this press starts a new attempt.
+        let late = matcher.key_down(HidKeyCode::ScrollLock, NONE, 1000 + SEQUENCE_TIMEOUT_MS + 1);
+        matcher.key_up(HidKeyCode::ScrollLock);
+        let then = matcher.key_down(
+            HidKeyCode::ScrollLock,
+            NONE,
+            1000 + SEQUENCE_TIMEOUT_MS + 100,
+        );
+
+        // Assert
+        assert!(!late);
+        assert!(then);
+    }
+
+    #[test]
+    fn test_sequence_timeout_survives_clock_wrap() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("A A"));
+        matcher.key_down(HidKeyCode::KeyA, NONE, u32::MAX - 10);
+        matcher.key_up(HidKeyCode::KeyA);
+
+        // Act / Assert
+        assert!(matcher.key_down(HidKeyCode::KeyA, NONE, 50));
+    }
+
+    #[test]
+    fn test_wrong_key_restarts_sequence() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("Ctrl+K Ctrl+B"));
+        let ctrl = ModifierFlags(ModifierFlags::LEFT_CTRL);
+
+        // Act – Ctrl+K, Ctrl+X (wrong), Ctrl+B: must not fire.
+        matcher.key_down(HidKeyCode::KeyK, ctrl, 0);
+        matcher.key_down(HidKeyCode::KeyX, ctrl, 10);
+        let after_wrong = matcher.key_down(HidKeyCode::KeyB, ctrl, 20);
+        // Ctrl+K, then Ctrl released and pressed again, Ctrl+B: fires.
+        matcher.key_down(HidKeyCode::KeyK, ctrl, 100);
+        matcher.key_down(HidKeyCode::ControlLeft, ctrl, 110);
+        let correct = matcher.key_down(HidKeyCode::KeyB, ctrl, 120);
+
+        // Assert
+        assert!(!after_wrong);
+        assert!(correct);
+    }
+
+    #[test]
+    fn test_modifier_key_double_tap_ignores_own_flag() {
+        // Arrange
+        let mut matcher = HotkeyMatcher::new(parse("ShiftLeft ShiftLeft"));
+        let shift = ModifierFlags(ModifierFlags::LEFT_SHIFT);
+
+        // Act – the router reports Left Shift as held on its own key-down.
+        matcher.key_down(HidKeyCode::ShiftLeft, shift, 0);
+        matcher.key_up(HidKeyCode::ShiftLeft);
+        let fired = matcher.key_down(HidKeyCode::ShiftLeft, shift, 100);
+
+        // Assert
+        assert!(fired);
+    }
+}
@@ -0,0 +1,335 @@
+//! HID Usage ID ↔ Linux evdev key code translation tables.
+//!
+//! Evdev key codes are defined in `linux/input-event-codes.h`.
+//! Reference: https://github.com/torvalds/linux/blob/master/include/uapi/linux/input-event-codes.h
+//!
+//! # What is an evdev key code? (for beginners)
+//!
+//! Below X11 and Wayland, the Linux kernel reports every keyboard through the
+//! *evdev* interface (`/dev/input/event*`).  Each key is a `KEY_*` constant
+//! that names a **physical key position**, just like a HID Usage ID:
+//!
+//! | Key          | HID Usage ID | evdev code        |
+//! |--------------|--------------|-------------------|
+//! | Letter A     | 0x04         | `KEY_A` (30)      |
+//! | Enter        | 0x28         | `KEY_ENTER` (28)  |
+//! | Left Ctrl    | 0xE0         | `KEY_LEFTCTRL` (29) |
+//!
+//! The numbering follows the old PC/AT scan codes rather than HID, so a table
+//! is still needed, but unlike X11 KeySyms no keyboard layout is involved:
+//! the mapping is one-to-one.  The kernel's own `hid-input` driver uses the
+//! same correspondence.
+//!
+//! Evdev codes are what a `/dev/uinput` virtual keyboard emits and what
+//! `/dev/input/event*` devices report, so they work under Wayland as well as
+//! X11, in both directions.
+//!
+//! # How these tables work
+//!
+//! [`hid_to_evdev`] is the single source of truth, written as a `match`.
+//! [`evdev_to_hid`] reads `EVDEV_TO_HID_TABLE`, a 256-entry array indexed by
+//! evdev code that is built from `hid_to_evdev` at compile time, so the two
+//! directions cannot drift apart.  Every mapped evdev code is below 256.
+
+use super::hid::HidKeyCode;
+
+/// Translates a Linux evdev `KEY_*` code to a [`HidKeyCode`].
+///
+/// Returns [`HidKeyCode::Unknown`] for codes with no keyboard HID equivalent
+/// (e.g., `BTN_*` mouse buttons or vendor keys).
+///
+/// # Panics
+///
+/// This function never panics; all u16 inputs are handled.
+pub fn evdev_to_hid(code: u16) -> HidKeyCode {
+    match EVDEV_TO_HID_TABLE.get(code as usize) {
+        Some(&hid) => hid,
+        None => HidKeyCode::Unknown,
+    }
+}
+
+/// Translates a [`HidKeyCode`] to a Linux evdev `KEY_*` code.
+///
+/// Returns `None` if the HID code has no evdev equivalent.
+///
+/// # Panics
+///
+/// This function never panics.
+pub const fn hid_to_evdev(hid: HidKeyCode) -> Option<u16> {
+    match hid {
+        // Letters
+        HidKeyCode::KeyA => Some(30), // KEY_A
+        HidKeyCode::KeyB => Some(48), // KEY_B
+        HidKeyCode::KeyC => Some(46), // KEY_C
+        HidKeyCode::KeyD => Some(32), // KEY_D
+        HidKeyCode::KeyE => Some(18), // KEY_E
+        HidKeyCode::KeyF => Some(33), // KEY_F
+        HidKeyCode::KeyG => Some(34), // KEY_G
+        HidKeyCode::KeyH => Some(35), // KEY_H
+        HidKeyCode::KeyI => Some(23), // KEY_I
+        HidKeyCode::KeyJ => Some(36), // KEY_J
+        HidKeyCode::KeyK => Some(37), // KEY_K
+        HidKeyCode::KeyL => Some(38), // KEY_L
+        HidKeyCode::KeyM => Some(50), // KEY_M
+        HidKeyCode::KeyN => Some(49), // KEY_N
+        HidKeyCode::KeyO => Some(24), // KEY_O
+        HidKeyCode::KeyP => Some(25), // KEY_P
+        HidKeyCode::KeyQ => Some(16), // KEY_Q
+        HidKeyCode::KeyR => Some(19), // KEY_R
+        HidKeyCode::KeyS => Some(31), // KEY_S
+        HidKeyCode::KeyT => Some(20), // KEY_T
+        HidKeyCode::KeyU => Some(22), // KEY_U
+        HidKeyCode::KeyV => Some(47), // KEY_V
+        HidKeyCode::KeyW => Some(17), // KEY_W
+        HidKeyCode::KeyX => Some(45), // KEY_X
+        HidKeyCode::KeyY => Some(21), // KEY_Y
+        HidKeyCode::KeyZ => Some(44), // KEY_Z
+
+        // Digits (KEY_1..KEY_9 are 2..10, KEY_0 follows them)
+        HidKeyCode::Digit1 => Some(2),  // KEY_1
+        HidKeyCode::Digit2 => Some(3),  // KEY_2
+        HidKeyCode::Digit3 => Some(4),  // KEY_3
+        HidKeyCode::Digit4 => Some(5),  // KEY_4
+        HidKeyCode::Digit5 => Some(6),  // KEY_5
+        HidKeyCode::Digit6 => Some(7),  // KEY_6
+        HidKeyCode::Digit7 => Some(8),  // KEY_7
+        HidKeyCode::Digit8 => Some(9),  // KEY_8
+        HidKeyCode::Digit9 => Some(10), // KEY_9
+        HidKeyCode::Digit0 => Some(11), // KEY_0
+
+        // Control keys
+        HidKeyCode::Enter => Some(28),        // KEY_ENTER
+        HidKeyCode::Escape => Some(1),        // KEY_ESC
+        HidKeyCode::Backspace => Some(14),    // KEY_BACKSPACE
+        HidKeyCode::Tab => Some(15),          // KEY_TAB
+        HidKeyCode::Space => Some(57),        // KEY_SPACE
+        HidKeyCode::CapsLock => Some(58),     // KEY_CAPSLOCK
+        HidKeyCode::ScrollLock => Some(70),   // KEY_SCROLLLOCK
+        HidKeyCode::Pause => Some(119),       // KEY_PAUSE
+        HidKeyCode::Insert => Some(110),      // KEY_INSERT
+        HidKeyCode::Home => Some(102),        // KEY_HOME
+        HidKeyCode::PageUp => Some(104),      // KEY_PAGEUP
+        HidKeyCode::Delete => Some(111),      // KEY_DELETE
+        HidKeyCode::End => Some(107),         // KEY_END
+        HidKeyCode::PageDown => Some(109),    // KEY_PAGEDOWN
+        HidKeyCode::PrintScreen => Some(99),  // KEY_SYSRQ
+        HidKeyCode::ContextMenu => Some(127), // KEY_COMPOSE
+
+        // Arrow keys
+        HidKeyCode::ArrowLeft => Some(105),  // KEY_LEFT
+        HidKeyCode::ArrowUp => Some(103),    // KEY_UP
+        HidKeyCode::ArrowRight => Some(106), // KEY_RIGHT
+        HidKeyCode::ArrowDown => Some(108),  // KEY_DOWN
+
+        // Function keys (F11/F12 are not contiguous with F1-F10)
+        HidKeyCode::F1 => Some(59),  // KEY_F1
+        HidKeyCode::F2 => Some(60),  // KEY_F2
+        HidKeyCode::F3 => Some(61),  // KEY_F3
+        HidKeyCode::F4 => Some(62),  // KEY_F4
+        HidKeyCode::F5 => Some(63),  // KEY_F5
+        HidKeyCode::F6 => Some(64),  // KEY_F6
+        HidKeyCode::F7 => Some(65),  // KEY_F7
+        HidKeyCode::F8 => Some(66),  // KEY_F8
+        HidKeyCode::F9 => Some(67),  // KEY_F9
+        HidKeyCode::F10 => Some(68), // KEY_F10
+        HidKeyCode::F11 => Some(87), // KEY_F11
+        HidKeyCode::F12 => Some(88), // KEY_F12
+
+        // Numpad
+        HidKeyCode::NumLock => Some(69),        // KEY_NUMLOCK
+        HidKeyCode::NumpadDivide => Some(98),   // KEY_KPSLASH
+        HidKeyCode::NumpadMultiply => Some(55), // KEY_KPASTERISK
+        HidKeyCode::NumpadSubtract => Some(74), // KEY_KPMINUS
+        HidKeyCode::NumpadAdd => Some(78),      // KEY_KPPLUS
+        HidKeyCode::NumpadEnter => Some(96),    // KEY_KPENTER
+        HidKeyCode::Numpad0 => Some(82),        // KEY_KP0
+        HidKeyCode::Numpad1 => Some(79),        // KEY_KP1
+        HidKeyCode::Numpad2 => Some(80),        // KEY_KP2
+        HidKeyCode::Numpad3 => Some(81),        // KEY_KP3
+        HidKeyCode::Numpad4 => Some(75),        // KEY_KP4
+        HidKeyCode::Numpad5 => Some(76),        // KEY_KP5
+        HidKeyCode::Numpad6 => Some(77),        // KEY_KP6
+        HidKeyCode::Numpad7 => Some(71),        // KEY_KP7
+        HidKeyCode::Numpad8 => Some(72),        // KEY_KP8
+        HidKeyCode::Numpad9 => Some(73),        // KEY_KP9
+        HidKeyCode::NumpadDecimal => Some(83),  // KEY_KPDOT
+
+        // Punctuation / symbols
+        HidKeyCode::Minus => Some(12),        // KEY_MINUS
+        HidKeyCode::Equal => Some(13),        // KEY_EQUAL
+        HidKeyCode::BracketLeft => Some(26),  // KEY_LEFTBRACE
+        HidKeyCode::BracketRight => Some(27), // KEY_RIGHTBRACE
+        HidKeyCode::Backslash => Some(43),    // KEY_BACKSLASH
+        HidKeyCode::Semicolon => Some(39),    // KEY_SEMICOLON
+        HidKeyCode::Quote => Some(40),        // KEY_APOSTROPHE
+        HidKeyCode::Backquote => Some(41),    // KEY_GRAVE
+        HidKeyCode::Comma => Some(51),        // KEY_COMMA
+        HidKeyCode::Period => Some(52),       // KEY_DOT
+        HidKeyCode::Slash => Some(53),        // KEY_SLASH
+
+        // Modifier keys
+        HidKeyCode::ControlLeft => Some(29),  // KEY_LEFTCTRL
+        HidKeyCode::ControlRight => Some(97), // KEY_RIGHTCTRL
+        HidKeyCode::ShiftLeft => Some(42),    // KEY_LEFTSHIFT
+        HidKeyCode::ShiftRight => Some(54),   // KEY_RIGHTSHIFT
+        HidKeyCode::AltLeft => Some(56),      // KEY_LEFTALT
+        HidKeyCode::AltRight => Some(100),    // KEY_RIGHTALT
+        HidKeyCode::MetaLeft => Some(125),    // KEY_LEFTMETA
+        HidKeyCode::MetaRight => Some(126),   // KEY_RIGHTMETA
+
+        // Extended function keys (KEY_F13..KEY_F24 are contiguous)
+        HidKeyCode::F13 => Some(183), // KEY_F13
+        HidKeyCode::F14 => Some(184), // KEY_F14
+        HidKeyCode::F15 => Some(185), // KEY_F15
+        HidKeyCode::F16 => Some(186), // KEY_F16
+        HidKeyCode::F17 => Some(187), // KEY_F17
+        HidKeyCode::F18 => Some(188), // KEY_F18
+        HidKeyCode::F19 => Some(189), // KEY_F19
+        HidKeyCode::F20 => Some(190), // KEY_F20
+        HidKeyCode::F21 => Some(191), // KEY_F21
+        HidKeyCode::F22 => Some(192), // KEY_F22
+        HidKeyCode::F23 => Some(193), // KEY_F23
+        HidKeyCode::F24 => Some(194), // KEY_F24
+
+        // Keypad extras
+        HidKeyCode::NumpadEqual => Some(117), // KEY_KPEQUAL
+        HidKeyCode::NumpadComma => Some(121), // KEY_KPCOMMA
+
+        // Media and power
+        HidKeyCode::AudioVolumeMute => Some(113), // KEY_MUTE
+        HidKeyCode::AudioVolumeDown => Some(114), // KEY_VOLUMEDOWN
+        HidKeyCode::AudioVolumeUp => Some(115),   // KEY_VOLUMEUP
+        HidKeyCode::Power => Some(116),           // KEY_POWER
+
+        // International keys
+        HidKeyCode::IntlBackslash => Some(86), // KEY_102ND
+        HidKeyCode::IntlRo => Some(89),        // KEY_RO
+        HidKeyCode::Convert => Some(92),       // KEY_HENKAN
+        HidKeyCode::KanaMode => Some(93),      // KEY_KATAKANAHIRAGANA
+        HidKeyCode::NonConvert => Some(94),    // KEY_MUHENKAN
+        HidKeyCode::Lang1 => Some(122),        // KEY_HANGEUL
+        HidKeyCode::Lang2 => Some(123),        // KEY_HANJA
+        HidKeyCode::IntlYen => Some(124),      // KEY_YEN
+
+        HidKeyCode::Unknown => None,
+    }
+}
+
+/// Complete evdev → HID mapping table indexed by evdev code (0–255).
+///
+/// Built by inverting [`hid_to_evdev`] over every HID Usage ID on the
+/// keyboard page; entries no HID code maps to stay `HidKeyCode::Unknown`.
+const EVDEV_TO_HID_TABLE: [HidKeyCode; 256] = {
+    let mut t = [HidKeyCode::Unknown; 256];
+    let mut usage = 0u16;
+    while usage <= 0xFF {
+        if let Some(code) = hid_to_evdev(HidKeyCode::from_u16(usage)) {
+            t[code as usize] = HidKeyCode::from_u16(usage);
+        }
+        usage += 1;
+    }
+    t
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every key code except `Unknown`, by HID Usage ID.
+    fn all_known_keys() -> impl Iterator<Item = HidKeyCode> {
+        (0u16..=0xFF)
+            .map(HidKeyCode::from_u16)
+            .filter(|k| *k != HidKeyCode::Unknown)
+    }
+
+    #[test]
+    fn test_every_known_hid_key_has_an_evdev_code() {
+        for key in all_known_keys() {
+            assert!(hid_to_evdev(key).is_some(), "{key:?} has no evdev code");
+        }
+    }
+
+    #[test]
+    fn test_evdev_codes_are_unique() {
+        let mut seen = std::collections::HashMap::new();
+        for key in all_known_keys() {
+            let code = hid_to_evdev(key).unwrap();
+            if let Some(other) = seen.insert(code, key) {
+                panic!("{key:?} and {other:?} both map to evdev {code}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_known_hid_key_round_trips_through_evdev() {
+        for key in all_known_keys() {
+            // Arrange / Act
+            let code = hid_to_evdev(key).unwrap();
+            let back = evdev_to_hid(code);
+
+            // Assert
+            assert_eq!(back, key, "{key:?} -> evdev {code} -> {back:?}");
+        }
+    }
+
+    #[test]
+    fn test_every_mapped_evdev_code_round_trips_through_hid() {
+        // KEY_MAX is 0x2FF; codes above the table must be Unknown too.
+        for code in 0u16..=0x2FF {
+            let hid = evdev_to_hid(code);
+            if hid != HidKeyCode::Unknown {
+                assert_eq!(
+                    hid_to_evdev(hid),
+                    Some(code),
+                    "evdev {code} -> {hid:?} does not map back"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unmapped_evdev_codes_return_unknown() {
+        // KEY_RESERVED, KEY_ZENKAKUHANKAKU, KEY_PLAYPAUSE, BTN_LEFT, u16::MAX
+        for code in [0, 85, 164, 0x110, u16::MAX] {
+            assert_eq!(evdev_to_hid(code), HidKeyCode::Unknown, "evdev {code}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_hid_returns_none() {
+        assert_eq!(hid_to_evdev(HidKeyCode::Unknown), None);
+    }
+
+    #[test]
+    fn test_letters_follow_qwerty_rows() {
+        // KEY_Q..KEY_P, KEY_A..KEY_L and KEY_Z..KEY_M are numbered row by row.
+        assert_eq!(hid_to_evdev(HidKeyCode::KeyQ), Some(16));
+        assert_eq!(hid_to_evdev(HidKeyCode::KeyP), Some(25));
+        assert_eq!(hid_to_evdev(HidKeyCode::KeyA), Some(30));
+        assert_eq!(hid_to_evdev(HidKeyCode::KeyL), Some(38));
+        assert_eq!(hid_to_evdev(HidKeyCode::KeyZ), Some(44));
+        assert_eq!(hid_to_evdev(HidKeyCode::KeyM), Some(50));
+    }
+
+    #[test]
+    fn test_navigation_and_modifier_keys_have_correct_codes() {
+        assert_eq!(hid_to_evdev(HidKeyCode::Enter), Some(28));
+        assert_eq!(hid_to_evdev(HidKeyCode::Escape), Some(1));
+        assert_eq!(hid_to_evdev(HidKeyCode::ArrowUp), Some(103));
+        assert_eq!(hid_to_evdev(HidKeyCode::ArrowDown), Some(108));
+        assert_eq!(hid_to_evdev(HidKeyCode::ControlLeft), Some(29));
+        assert_eq!(hid_to_evdev(HidKeyCode::MetaRight), Some(126));
+    }
+
+    #[test]
+    fn test_keypad_media_and_international_keys_have_correct_codes() {
+        assert_eq!(hid_to_evdev(HidKeyCode::NumpadEnter), Some(96));
+        assert_eq!(hid_to_evdev(HidKeyCode::NumpadEqual), Some(117));
+        assert_eq!(hid_to_evdev(HidKeyCode::F24), Some(194));
+        assert_eq!(hid_to_evdev(HidKeyCode::AudioVolumeMute), Some(113));
+        assert_eq!(hid_to_evdev(HidKeyCode::AudioVolumeUp), Some(115));
+        assert_eq!(hid_to_evdev(HidKeyCode::IntlBackslash), Some(86));
+        assert_eq!(hid_to_evdev(HidKeyCode::IntlYen), Some(124));
+        assert_eq!(hid_to_evdev(HidKeyCode::Lang1), Some(122));
+    }
+}
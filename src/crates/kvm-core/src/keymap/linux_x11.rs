@@ -160,6 +160,40 @@ pub fn hid_to_keysym(hid: HidKeyCode) -> Option<u32> {
         HidKeyCode::MetaLeft => Some(0xFFEB),    // XK_Super_L
         HidKeyCode::MetaRight => Some(0xFFEC),   // XK_Super_R
 
+        // Extended function keys (XK_F13..XK_F24 are contiguous)
+        HidKeyCode::F13 => Some(0xFFCA), // XK_F13
+        HidKeyCode::F14 => Some(0xFFCB), // XK_F14
+        HidKeyCode::F15 => Some(0xFFCC), // XK_F15
+        HidKeyCode::F16 => Some(0xFFCD), // XK_F16
+        HidKeyCode::F17 => Some(0xFFCE), // XK_F17
+        HidKeyCode::F18 => Some(0xFFCF), // XK_F18
+        HidKeyCode::F19 => Some(0xFFD0), // XK_F19
+        HidKeyCode::F20 => Some(0xFFD1), // XK_F20
+        HidKeyCode::F21 => Some(0xFFD2), // XK_F21
+        HidKeyCode::F22 => Some(0xFFD3), // XK_F22
+        HidKeyCode::F23 => Some(0xFFD4), // XK_F23
+        HidKeyCode::F24 => Some(0xFFD5), // XK_F24
+
+        // Keypad extras
+        HidKeyCode::NumpadEqual => Some(0xFFBD), // XK_KP_Equal
+        HidKeyCode::NumpadComma => Some(0xFFAC), // XK_KP_Separator
+
+        // Media and power (XF86 vendor keysyms)
+        HidKeyCode::AudioVolumeMute => Some(0x1008_FF12), // XF86XK_AudioMute
+        HidKeyCode::AudioVolumeUp => Some(0x1008_FF13),   // XF86XK_AudioRaiseVolume
+        HidKeyCode::AudioVolumeDown => Some(0x1008_FF11), // XF86XK_AudioLowerVolume
+        HidKeyCode::Power => Some(0x1008_FF2A),           // XF86XK_PowerOff
+
+        // International keys (ISO, JIS and Korean layouts)
+        HidKeyCode::IntlBackslash => Some(0x003C), // XK_less
+        HidKeyCode::IntlRo => Some(0x04DB),        // XK_kana_RO
+        HidKeyCode::IntlYen => Some(0x00A5),       // XK_yen
+        HidKeyCode::KanaMode => Some(0xFF27),      // XK_Hiragana_Katakana
+        HidKeyCode::Convert => Some(0xFF23),       // XK_Henkan
+        HidKeyCode::NonConvert => Some(0xFF22),    // XK_Muhenkan
+        HidKeyCode::Lang1 => Some(0xFF31),         // XK_Hangul
+        HidKeyCode::Lang2 => Some(0xFF34),         // XK_Hangul_Hanja
+
         HidKeyCode::Unknown => None,
     }
 }
@@ -154,6 +154,35 @@ pub fn hid_to_cgkeycode(hid: HidKeyCode) -> Option<u16> {
         HidKeyCode::MetaLeft => Some(0x37),     // kVK_Command
         HidKeyCode::MetaRight => Some(0x36),    // kVK_RightCommand
 
+        // Extended function keys (macOS has no F21-F24)
+        HidKeyCode::F13 => Some(0x69), // kVK_F13
+        HidKeyCode::F14 => Some(0x6B), // kVK_F14
+        HidKeyCode::F15 => Some(0x71), // kVK_F15
+        HidKeyCode::F16 => Some(0x6A), // kVK_F16
+        HidKeyCode::F17 => Some(0x40), // kVK_F17
+        HidKeyCode::F18 => Some(0x4F), // kVK_F18
+        HidKeyCode::F19 => Some(0x50), // kVK_F19
+        HidKeyCode::F20 => Some(0x5A), // kVK_F20
+        HidKeyCode::F21 | HidKeyCode::F22 | HidKeyCode::F23 | HidKeyCode::F24 => None,
+
+        // Keypad extras
+        HidKeyCode::NumpadEqual => Some(0x51), // kVK_ANSI_KeypadEquals
+        HidKeyCode::NumpadComma => Some(0x5F), // kVK_JIS_KeypadComma
+
+        // Media keys (Power is a system-defined event, not a key code)
+        HidKeyCode::AudioVolumeMute => Some(0x4A), // kVK_Mute
+        HidKeyCode::AudioVolumeUp => Some(0x48),   // kVK_VolumeUp
+        HidKeyCode::AudioVolumeDown => Some(0x49), // kVK_VolumeDown
+        HidKeyCode::Power => None,
+
+        // International keys (Korean and the remaining JIS keys have no code)
+        HidKeyCode::IntlBackslash => Some(0x0A), // kVK_ISO_Section
+        HidKeyCode::IntlRo => Some(0x5E),        // kVK_JIS_Underscore
+        HidKeyCode::IntlYen => Some(0x5D),       // kVK_JIS_Yen
+        HidKeyCode::Lang1 => Some(0x68),         // kVK_JIS_Kana
+        HidKeyCode::Lang2 => Some(0x66),         // kVK_JIS_Eisu
+        HidKeyCode::KanaMode | HidKeyCode::Convert | HidKeyCode::NonConvert => None,
+
         HidKeyCode::Unknown => None,
     }
 }
@@ -274,6 +303,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(non_snake_case)] // named after Apple's kVK_Return constant
     fn test_enter_maps_to_kVK_Return() {
         assert_eq!(hid_to_cgkeycode(HidKeyCode::Enter), Some(0x24));
     }
@@ -12,6 +12,7 @@
 //! |---------|-------------------|-------------------|
 //! | Windows | Virtual Key (VK)  | 0x41              |
 //! | Linux   | X11 KeySym        | 0x0061            |
+//! | Linux   | evdev `KEY_*`     | 30                |
 //! | macOS   | CGKeyCode         | 0x00              |
 //! | Web     | DOM key code      | `"KeyA"`          |
 //!
@@ -41,9 +42,13 @@
 //! - **`hid`**         – The [`HidKeyCode`] enum with all USB HID Usage IDs.
 //! - **`windows_vk`**  – VK ↔ HID table for Windows.
 //! - **`linux_x11`**   – HID → X11 KeySym table.
+//! - **`linux_evdev`** – evdev ↔ HID tables for Linux (uinput and `/dev/input`).
 //! - **`macos_cg`**    – HID → macOS CGKeyCode table.
+//! - **`hotkey`**      – Hotkey grammar (`"Ctrl+Alt+Right"`) and chord matching.
 
 pub mod hid;
+pub mod hotkey;
+pub mod linux_evdev;
 pub mod linux_x11;
 pub mod macos_cg;
 pub mod windows_vk;
@@ -78,6 +83,22 @@ impl KeyMapper {
         linux_x11::hid_to_keysym(hid)
     }
 
+    /// Translates a Linux evdev `KEY_*` code, as read from `/dev/input/event*`,
+    /// to a [`HidKeyCode`].
+    ///
+    /// Returns [`HidKeyCode::Unknown`] if no mapping exists for `code`.
+    pub fn linux_evdev_to_hid(code: u16) -> HidKeyCode {
+        linux_evdev::evdev_to_hid(code)
+    }
+
+    /// Translates a [`HidKeyCode`] to a Linux evdev `KEY_*` code, as used by
+    /// `/dev/uinput` virtual keyboards.
+    ///
+    /// Returns `None` if the HID code has no evdev equivalent.
+    pub fn hid_to_linux_evdev(hid: HidKeyCode) -> Option<u16> {
+        linux_evdev::hid_to_evdev(hid)
+    }
+
     /// Translates a [`HidKeyCode`] to a macOS `CGKeyCode` value.
     ///
     /// Returns `None` if the HID code has no macOS equivalent.
@@ -200,6 +221,32 @@ fn hid_to_dom_code_str(hid: HidKeyCode) -> Option<&'static str> {
         HidKeyCode::ShiftRight => Some("ShiftRight"),
         HidKeyCode::AltRight => Some("AltRight"),
         HidKeyCode::MetaRight => Some("MetaRight"),
+        HidKeyCode::IntlBackslash => Some("IntlBackslash"),
+        HidKeyCode::Power => Some("Power"),
+        HidKeyCode::NumpadEqual => Some("NumpadEqual"),
+        HidKeyCode::F13 => Some("F13"),
+        HidKeyCode::F14 => Some("F14"),
+        HidKeyCode::F15 => Some("F15"),
+        HidKeyCode::F16 => Some("F16"),
+        HidKeyCode::F17 => Some("F17"),
+        HidKeyCode::F18 => Some("F18"),
+        HidKeyCode::F19 => Some("F19"),
+        HidKeyCode::F20 => Some("F20"),
+        HidKeyCode::F21 => Some("F21"),
+        HidKeyCode::F22 => Some("F22"),
+        HidKeyCode::F23 => Some("F23"),
+        HidKeyCode::F24 => Some("F24"),
+        HidKeyCode::AudioVolumeMute => Some("AudioVolumeMute"),
+        HidKeyCode::AudioVolumeUp => Some("AudioVolumeUp"),
+        HidKeyCode::AudioVolumeDown => Some("AudioVolumeDown"),
+        HidKeyCode::NumpadComma => Some("NumpadComma"),
+        HidKeyCode::IntlRo => Some("IntlRo"),
+        HidKeyCode::KanaMode => Some("KanaMode"),
+        HidKeyCode::IntlYen => Some("IntlYen"),
+        HidKeyCode::Convert => Some("Convert"),
+        HidKeyCode::NonConvert => Some("NonConvert"),
+        HidKeyCode::Lang1 => Some("Lang1"),
+        HidKeyCode::Lang2 => Some("Lang2"),
         HidKeyCode::Unknown => None,
     }
 }
@@ -177,6 +177,39 @@ const VK_TO_HID_TABLE: [HidKeyCode; 256] = {
     t[0x5B] = MetaLeft; // VK_LWIN
     t[0x5C] = MetaRight; // VK_RWIN
 
+    // ── Extended function keys (VK_F13=0x7C … VK_F24=0x87) ──────────────────
+    t[0x7C] = F13;
+    t[0x7D] = F14;
+    t[0x7E] = F15;
+    t[0x7F] = F16;
+    t[0x80] = F17;
+    t[0x81] = F18;
+    t[0x82] = F19;
+    t[0x83] = F20;
+    t[0x84] = F21;
+    t[0x85] = F22;
+    t[0x86] = F23;
+    t[0x87] = F24;
+
+    // ── Keypad extras ─────────────────────────────────────────────────────────
+    t[0x6C] = NumpadComma; // VK_SEPARATOR
+    t[0x92] = NumpadEqual; // VK_OEM_NEC_EQUAL
+
+    // ── Media keys ────────────────────────────────────────────────────────────
+    t[0xAD] = AudioVolumeMute; // VK_VOLUME_MUTE
+    t[0xAE] = AudioVolumeDown; // VK_VOLUME_DOWN
+    t[0xAF] = AudioVolumeUp; // VK_VOLUME_UP
+
+    // ── International keys ────────────────────────────────────────────────────
+    // VK_KANA and VK_HANGUL share 0x15; it is mapped as Lang1 (Hangul/English),
+    // so the Japanese KanaMode key has no VK of its own.  Power has no VK.
+    t[0xE2] = IntlBackslash; // VK_OEM_102
+    t[0xC1] = IntlRo; // VK_ABNT_C1
+    t[0x1C] = Convert; // VK_CONVERT
+    t[0x1D] = NonConvert; // VK_NONCONVERT
+    t[0x15] = Lang1; // VK_HANGUL
+    t[0x19] = Lang2; // VK_HANJA
+
     // ── Numpad Enter (extended) ───────────────────────────────────────────────
     // Note: WH_KEYBOARD_LL delivers VK_RETURN with extended flag for numpad Enter.
     // The infrastructure layer must detect this and use NumpadEnter.
@@ -15,6 +15,11 @@
 //!
 //! This crate (`kvm-core`) is the shared foundation.  It defines:
 //!
+//! - **`clipboard`** – The `ClipboardProvider` abstraction over the OS
+//!   clipboard, the canonical HTML and PNG encodings of rich content, and
+//!   the fragmentation of large clipboard content into `ClipboardData`
+//!   frames.
+//!
 //! - **`protocol`** – How bytes travel over the network.  Messages are encoded
 //!   into a compact binary format (24-byte header + payload) and decoded back
 //!   into typed Rust structs on the other end.
@@ -23,12 +28,18 @@
 //!   important piece is the `VirtualLayout`: a 2-D map of where each screen
 //!   lives relative to the master.
 //!
+//! - **`crypto`** – TLS identities and certificate fingerprint pinning for the
+//!   control channel (behind the `tls` cargo feature), and SPAKE2 PIN pairing
+//!   (behind the `pairing` cargo feature).
+//!
 //! - **`keymap`** – Translation tables that convert keyboard codes between
 //!   platforms (Windows VK codes, Linux X11 KeySyms, macOS CGKeyCodes) and the
 //!   canonical representation used on the wire: USB HID Usage IDs.
 
-// Declare the three top-level modules.  Rust will look for each in a
+// Declare the top-level modules.  Rust will look for each in a
 // subdirectory with the same name (e.g., src/protocol/mod.rs).
+pub mod clipboard;
+pub mod crypto;
 pub mod domain;
 pub mod keymap;
 pub mod protocol;
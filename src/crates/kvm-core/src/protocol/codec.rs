@@ -41,13 +41,14 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::keymap::hid::HidKeyCode;
 use crate::protocol::messages::{
-    AnnounceMessage, AnnounceResponseMessage, ButtonEventType, ClipboardDataMessage,
+    capabilities, AnnounceMessage, AnnounceResponseMessage, ButtonEventType, ClipboardDataMessage,
     ClipboardFormat, ConfigUpdateMessage, DisconnectReason, ErrorMessage, HelloAckMessage,
-    HelloMessage, InputEvent, KeyEventMessage, KeyEventType, KvmMessage, MessageType,
-    ModifierFlags, MonitorInfo, MouseButton, MouseButtonMessage, MouseMoveMessage,
-    MouseScrollMessage, PairingRequestMessage, PairingResponseMessage, PlatformId,
-    ProtocolErrorCode, ScreenInfoMessage, HEADER_SIZE, PROTOCOL_VERSION,
+    HelloMessage, InputEvent, KeyEventMessage, KeyEventType, KvmMessage, MessageHeader,
+    MessageType, ModifierFlags, MonitorInfo, MouseButton, MouseButtonMessage, MouseMoveMessage,
+    MouseScrollMessage, PairingConfirmMessage, PairingRequestMessage, PairingResponseMessage,
+    PlatformId, ProtocolErrorCode, ScreenInfoMessage, HEADER_SIZE, PROTOCOL_VERSION,
 };
+use crate::protocol::negotiation::is_supported_version;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -76,7 +77,8 @@ pub enum ProtocolError {
 
     /// The protocol version in the header is not supported.
     ///
-    /// Returned when byte 0 of the header is not equal to `PROTOCOL_VERSION`.
+    /// Returned when byte 0 of the header is outside
+    /// `MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION`.
     #[error("unsupported protocol version: {0}")]
     UnsupportedVersion(u8),
 
@@ -133,6 +135,28 @@ pub fn encode_message(
     sequence_number: u64,
     timestamp_us: u64,
 ) -> Result<Vec<u8>, ProtocolError> {
+    encode_message_with_version(msg, PROTOCOL_VERSION, sequence_number, timestamp_us)
+}
+
+/// Like [`encode_message`], but writes `version` into the header instead of
+/// [`PROTOCOL_VERSION`].
+///
+/// Use this after the handshake, with the version the session negotiated
+/// (see [`crate::protocol::negotiation`]), so an older peer can read the frame.
+///
+/// # Errors
+///
+/// Returns [`ProtocolError::UnsupportedVersion`] if this build does not speak
+/// `version`, or any error `encode_message` returns.
+pub fn encode_message_with_version(
+    msg: &KvmMessage,
+    version: u8,
+    sequence_number: u64,
+    timestamp_us: u64,
+) -> Result<Vec<u8>, ProtocolError> {
+    if !is_supported_version(version) {
+        return Err(ProtocolError::UnsupportedVersion(version));
+    }
     let payload = encode_payload(msg)?;
     let payload_len = payload.len() as u32;
 
@@ -140,7 +164,7 @@ pub fn encode_message(
 
     // Header: version (1) + msg_type (1) + reserved (2) + payload_len (4) +
     //         seq (8) + timestamp_us (8) = 24 bytes
-    buf.push(PROTOCOL_VERSION);
+    buf.push(version);
     buf.push(msg.message_type() as u8);
     buf.push(0x00); // reserved
     buf.push(0x00); // reserved
@@ -194,6 +218,35 @@ pub fn encode_message_now(
 /// assert_eq!(n, bytes.len());
 /// ```
 pub fn decode_message(bytes: &[u8]) -> Result<(KvmMessage, usize), ProtocolError> {
+    let header = decode_header(bytes)?;
+    let payload_len = header.payload_length as usize;
+
+    let total_needed = HEADER_SIZE + payload_len;
+    if bytes.len() < total_needed {
+        return Err(ProtocolError::PayloadLengthMismatch {
+            declared: payload_len,
+            available: bytes.len() - HEADER_SIZE,
+        });
+    }
+
+    let payload = &bytes[HEADER_SIZE..HEADER_SIZE + payload_len];
+    let msg = decode_payload(header.message_type, payload)?;
+    Ok((msg, total_needed))
+}
+
+/// Parses and validates the 24-byte header at the beginning of `bytes`.
+///
+/// Only the header is examined; the payload does not need to be present.
+/// Streaming readers use this to learn how many more bytes a frame needs (and
+/// to reject an absurd `payload_length`) before buffering the payload.
+///
+/// # Errors
+///
+/// - [`ProtocolError::InsufficientData`] if fewer than 24 bytes are available.
+/// - [`ProtocolError::UnsupportedVersion`] if byte 0 is not a version this
+///   build speaks.
+/// - [`ProtocolError::UnknownMessageType`] if byte 1 is not a known type.
+pub fn decode_header(bytes: &[u8]) -> Result<MessageHeader, ProtocolError> {
     if bytes.len() < HEADER_SIZE {
         return Err(ProtocolError::InsufficientData {
             needed: HEADER_SIZE,
@@ -202,29 +255,23 @@ pub fn decode_message(bytes: &[u8]) -> Result<(KvmMessage, usize), ProtocolError
     }
 
     let version = bytes[0];
-    if version != PROTOCOL_VERSION {
+    if !is_supported_version(version) {
         return Err(ProtocolError::UnsupportedVersion(version));
     }
 
     let msg_type_byte = bytes[1];
-    let msg_type = MessageType::try_from(msg_type_byte)
+    let message_type = MessageType::try_from(msg_type_byte)
         .map_err(|_| ProtocolError::UnknownMessageType(msg_type_byte))?;
 
     // bytes[2..4] are reserved – ignored on decode
 
-    let payload_len = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
-
-    let total_needed = HEADER_SIZE + payload_len;
-    if bytes.len() < total_needed {
-        return Err(ProtocolError::PayloadLengthMismatch {
-            declared: payload_len,
-            available: bytes.len() - HEADER_SIZE,
-        });
-    }
-
-    let payload = &bytes[HEADER_SIZE..HEADER_SIZE + payload_len];
-    let msg = decode_payload(msg_type, payload)?;
-    Ok((msg, total_needed))
+    Ok(MessageHeader {
+        version,
+        message_type,
+        payload_length: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        sequence_number: u64::from_be_bytes(bytes[8..16].try_into().expect("8-byte slice")),
+        timestamp_us: u64::from_be_bytes(bytes[16..24].try_into().expect("8-byte slice")),
+    })
 }
 
 // ── Payload encoding ──────────────────────────────────────────────────────────
@@ -238,6 +285,7 @@ fn encode_payload(msg: &KvmMessage) -> Result<Vec<u8>, ProtocolError> {
         KvmMessage::HelloAck(m) => encode_hello_ack(&mut buf, m),
         KvmMessage::PairingRequest(m) => encode_pairing_request(&mut buf, m),
         KvmMessage::PairingResponse(m) => encode_pairing_response(&mut buf, m),
+        KvmMessage::PairingConfirm(m) => encode_pairing_confirm(&mut buf, m),
         KvmMessage::ScreenInfo(m) => encode_screen_info(&mut buf, m),
         KvmMessage::ScreenInfoAck => {} // empty payload
         KvmMessage::Ping(token) => buf.extend_from_slice(&token.to_be_bytes()),
@@ -271,6 +319,9 @@ fn decode_payload(msg_type: MessageType, payload: &[u8]) -> Result<KvmMessage, P
         MessageType::PairingResponse => {
             decode_pairing_response(payload).map(KvmMessage::PairingResponse)
         }
+        MessageType::PairingConfirm => {
+            decode_pairing_confirm(payload).map(KvmMessage::PairingConfirm)
+        }
         MessageType::ScreenInfo => decode_screen_info(payload).map(KvmMessage::ScreenInfo),
         MessageType::ScreenInfoAck => Ok(KvmMessage::ScreenInfoAck),
         MessageType::Ping => {
@@ -325,6 +376,9 @@ fn encode_hello(buf: &mut Vec<u8>, m: &HelloMessage) {
     buf.push(m.platform_id as u8);
     write_length_prefixed_string(buf, &m.client_name);
     buf.extend_from_slice(&m.capabilities.to_be_bytes());
+    if let Some(token) = &m.resume_token {
+        buf.extend_from_slice(token);
+    }
 }
 
 fn encode_hello_ack(buf: &mut Vec<u8>, m: &HelloAckMessage) {
@@ -332,19 +386,28 @@ fn encode_hello_ack(buf: &mut Vec<u8>, m: &HelloAckMessage) {
     buf.push(m.server_version);
     buf.push(if m.accepted { 0x01 } else { 0x00 });
     buf.push(m.reject_reason);
+    buf.extend_from_slice(&m.capabilities.to_be_bytes());
+    buf.push(u8::from(m.resumed));
 }
 
 fn encode_pairing_request(buf: &mut Vec<u8>, m: &PairingRequestMessage) {
     buf.extend_from_slice(m.pairing_session_id.as_bytes());
     buf.extend_from_slice(&m.expires_at_secs.to_be_bytes());
+    buf.extend_from_slice(&m.spake_message);
 }
 
 fn encode_pairing_response(buf: &mut Vec<u8>, m: &PairingResponseMessage) {
     buf.extend_from_slice(m.pairing_session_id.as_bytes());
-    write_length_prefixed_string(buf, &m.pin_hash);
+    buf.extend_from_slice(&m.spake_message);
+    buf.extend_from_slice(&m.confirmation);
     buf.push(if m.accepted { 0x01 } else { 0x00 });
 }
 
+fn encode_pairing_confirm(buf: &mut Vec<u8>, m: &PairingConfirmMessage) {
+    buf.extend_from_slice(m.pairing_session_id.as_bytes());
+    buf.extend_from_slice(&m.confirmation);
+}
+
 fn encode_screen_info(buf: &mut Vec<u8>, m: &ScreenInfoMessage) {
     buf.push(m.monitors.len() as u8);
     for monitor in &m.monitors {
@@ -468,7 +531,8 @@ fn encode_announce_response(buf: &mut Vec<u8>, m: &AnnounceResponseMessage) {
 // out of range.  They never panic on invalid input.
 
 fn decode_hello(p: &[u8]) -> Result<HelloMessage, ProtocolError> {
-    // Minimum layout: 16 (uuid) + 1 (proto ver) + 1 (platform) + 2 (name_len) + name + 4 (caps)
+    // Minimum layout: 16 (uuid) + 1 (proto ver) + 1 (platform) + 2 (name_len) + name + 4 (caps),
+    // then an optional 32-byte resume token.
     require_len(p, 24, "Hello")?;
     let client_id = read_uuid(p, 0)?;
     let protocol_version = p[16];
@@ -483,55 +547,83 @@ fn decode_hello(p: &[u8]) -> Result<HelloMessage, ProtocolError> {
         p[caps_off + 2],
         p[caps_off + 3],
     ]);
+    let resume_token = p
+        .get(caps_off + 4..caps_off + 36)
+        .map(|token| token.try_into().expect("32-byte slice"));
     Ok(HelloMessage {
         client_id,
         protocol_version,
         platform_id,
         client_name,
         capabilities,
+        resume_token,
     })
 }
 
 fn decode_hello_ack(p: &[u8]) -> Result<HelloAckMessage, ProtocolError> {
-    // 32 (token) + 1 (ver) + 1 (accepted) + 1 (reject) = 35
+    // 32 (token) + 1 (ver) + 1 (accepted) + 1 (reject) = 35, then an optional
+    // 4-byte capability mask and an optional flags byte that older masters do
+    // not send.
     require_len(p, 35, "HelloAck")?;
     let mut session_token = [0u8; 32];
     session_token.copy_from_slice(&p[0..32]);
     let server_version = p[32];
     let accepted = p[33] != 0;
     let reject_reason = p[34];
+    let capabilities = match p.get(35..39) {
+        Some(caps) => u32::from_be_bytes(caps.try_into().expect("4-byte slice")),
+        None => capabilities::BASELINE,
+    };
+    let resumed = p.get(39).is_some_and(|flags| flags & 0x01 != 0);
     Ok(HelloAckMessage {
         session_token,
         server_version,
         accepted,
         reject_reason,
+        capabilities,
+        resumed,
     })
 }
 
 fn decode_pairing_request(p: &[u8]) -> Result<PairingRequestMessage, ProtocolError> {
-    // 16 (uuid) + 8 (expires)
-    require_len(p, 24, "PairingRequest")?;
+    // 16 (uuid) + 8 (expires) + 32 (spake message)
+    require_len(p, 56, "PairingRequest")?;
     let pairing_session_id = read_uuid(p, 0)?;
     let expires_at_secs = read_u64(p, 16)?;
+    let spake_message = read_array32(p, 24)?;
     Ok(PairingRequestMessage {
         pairing_session_id,
         expires_at_secs,
+        spake_message,
     })
 }
 
 fn decode_pairing_response(p: &[u8]) -> Result<PairingResponseMessage, ProtocolError> {
-    require_len(p, 19, "PairingResponse")?;
+    // 16 (uuid) + 32 (spake message) + 32 (confirmation) + 1 (accepted)
+    require_len(p, 81, "PairingResponse")?;
     let pairing_session_id = read_uuid(p, 0)?;
-    let (pin_hash, hash_end) = read_length_prefixed_string(p, 16)?;
-    require_len(p, hash_end + 1, "PairingResponse.accepted")?;
-    let accepted = p[hash_end] != 0;
+    let spake_message = read_array32(p, 16)?;
+    let confirmation = read_array32(p, 48)?;
+    let accepted = p[80] != 0;
     Ok(PairingResponseMessage {
         pairing_session_id,
-        pin_hash,
+        spake_message,
+        confirmation,
         accepted,
     })
 }
 
+fn decode_pairing_confirm(p: &[u8]) -> Result<PairingConfirmMessage, ProtocolError> {
+    // 16 (uuid) + 32 (confirmation)
+    require_len(p, 48, "PairingConfirm")?;
+    let pairing_session_id = read_uuid(p, 0)?;
+    let confirmation = read_array32(p, 16)?;
+    Ok(PairingConfirmMessage {
+        pairing_session_id,
+        confirmation,
+    })
+}
+
 fn decode_screen_info(p: &[u8]) -> Result<ScreenInfoMessage, ProtocolError> {
     require_len(p, 1, "ScreenInfo")?;
     let monitor_count = p[0] as usize;
@@ -831,6 +923,19 @@ fn read_uuid(buf: &[u8], offset: usize) -> Result<Uuid, ProtocolError> {
     ))
 }
 
+/// Reads a fixed 32-byte field (key-exchange messages and MACs) from `buf` at
+/// `offset`.
+fn read_array32(buf: &[u8], offset: usize) -> Result<[u8; 32], ProtocolError> {
+    buf.get(offset..offset + 32)
+        .map(|b| b.try_into().unwrap())
+        .ok_or_else(|| {
+            ProtocolError::MalformedPayload(format!(
+                "need 32 bytes at offset {offset}, got {}",
+                buf.len().saturating_sub(offset)
+            ))
+        })
+}
+
 /// Writes a 2-byte length prefix followed by the UTF-8 string bytes.
 ///
 /// This is the "Pascal string" encoding: [len_hi][len_lo][bytes...].
@@ -912,6 +1017,7 @@ mod tests {
             platform_id: PlatformId::Linux,
             client_name: "dev-linux".to_string(),
             capabilities: capabilities::KEYBOARD_EMULATION | capabilities::MOUSE_EMULATION,
+            resume_token: None,
         });
         assert_eq!(round_trip(&msg), msg);
     }
@@ -924,6 +1030,7 @@ mod tests {
             platform_id: PlatformId::Web,
             client_name: String::new(),
             capabilities: 0,
+            resume_token: None,
         });
         assert_eq!(round_trip(&msg), msg);
     }
@@ -937,6 +1044,7 @@ mod tests {
             platform_id: PlatformId::Windows,
             client_name: long_name,
             capabilities: 0xFF,
+            resume_token: None,
         });
         assert_eq!(round_trip(&msg), msg);
     }
@@ -950,6 +1058,8 @@ mod tests {
             server_version: 1,
             accepted: true,
             reject_reason: 0,
+            capabilities: capabilities::KEYBOARD_EMULATION | capabilities::CLIPBOARD_SHARING,
+            resumed: false,
         });
         assert_eq!(round_trip(&msg), msg);
     }
@@ -961,17 +1071,73 @@ mod tests {
             server_version: 1,
             accepted: false,
             reject_reason: 0x03,
+            capabilities: 0,
+            resumed: false,
         });
         assert_eq!(round_trip(&msg), msg);
     }
 
-    // ── PairingRequest / PairingResponse ──────────────────────────────────────
+    #[test]
+    fn test_hello_ack_without_capabilities_decodes_as_baseline() {
+        // Arrange – a HelloAck from a master that predates negotiation: the
+        // payload stops after reject_reason.
+        let mut bytes = encode_message(
+            &KvmMessage::HelloAck(HelloAckMessage {
+                session_token: [1; 32],
+                server_version: 1,
+                accepted: true,
+                reject_reason: 0,
+                capabilities: u32::MAX,
+                resumed: false,
+            }),
+            0,
+            0,
+        )
+        .unwrap();
+        bytes.truncate(bytes.len() - 5);
+        bytes[4..8].copy_from_slice(&35u32.to_be_bytes());
+
+        // Act
+        let (msg, _) = decode_message(&bytes).unwrap();
+
+        // Assert
+        match msg {
+            KvmMessage::HelloAck(ack) => assert_eq!(ack.capabilities, capabilities::BASELINE),
+            other => panic!("expected HelloAck, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resuming_hello_and_ack_round_trip() {
+        let hello = KvmMessage::Hello(HelloMessage {
+            client_id: Uuid::new_v4(),
+            protocol_version: 1,
+            platform_id: PlatformId::MacOs,
+            client_name: "laptop".to_string(),
+            capabilities: capabilities::BASELINE,
+            resume_token: Some([0x42; 32]),
+        });
+        let ack = KvmMessage::HelloAck(HelloAckMessage {
+            session_token: [7; 32],
+            server_version: 1,
+            accepted: true,
+            reject_reason: 0,
+            capabilities: capabilities::BASELINE,
+            resumed: true,
+        });
+
+        assert_eq!(round_trip(&hello), hello);
+        assert_eq!(round_trip(&ack), ack);
+    }
+
+    // ── PairingRequest / PairingResponse / PairingConfirm ─────────────────────
 
     #[test]
     fn test_pairing_request_round_trip() {
         let msg = KvmMessage::PairingRequest(PairingRequestMessage {
             pairing_session_id: Uuid::new_v4(),
             expires_at_secs: 1_700_000_000,
+            spake_message: [0x5A; 32],
         });
         assert_eq!(round_trip(&msg), msg);
     }
@@ -980,12 +1146,45 @@ mod tests {
     fn test_pairing_response_accepted_round_trip() {
         let msg = KvmMessage::PairingResponse(PairingResponseMessage {
             pairing_session_id: Uuid::new_v4(),
-            pin_hash: "sha256:abcdef1234567890".to_string(),
+            spake_message: [0x11; 32],
+            confirmation: [0x22; 32],
             accepted: true,
         });
         assert_eq!(round_trip(&msg), msg);
     }
 
+    #[test]
+    fn test_pairing_confirm_round_trip() {
+        let msg = KvmMessage::PairingConfirm(PairingConfirmMessage {
+            pairing_session_id: Uuid::new_v4(),
+            confirmation: [0xC3; 32],
+        });
+        let bytes = encode_message(&msg, 1, 0).unwrap();
+        assert_eq!(bytes[1], 0x0D);
+        assert_eq!(round_trip(&msg), msg);
+    }
+
+    #[test]
+    fn test_pairing_response_truncated_confirmation_is_rejected() {
+        // Arrange: drop the last MAC byte and the accepted flag.
+        let msg = KvmMessage::PairingResponse(PairingResponseMessage {
+            pairing_session_id: Uuid::new_v4(),
+            spake_message: [0x11; 32],
+            confirmation: [0x22; 32],
+            accepted: true,
+        });
+        let mut bytes = encode_message(&msg, 1, 0).unwrap();
+        bytes.truncate(bytes.len() - 2);
+        let payload_len = (bytes.len() - HEADER_SIZE) as u32;
+        bytes[4..8].copy_from_slice(&payload_len.to_be_bytes());
+
+        // Act
+        let result = decode_message(&bytes);
+
+        // Assert
+        assert!(matches!(result, Err(ProtocolError::MalformedPayload(_))));
+    }
+
     // ── ScreenInfo ────────────────────────────────────────────────────────────
 
     #[test]
@@ -1462,6 +1661,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_encode_with_version_writes_it_into_the_header() {
+        let bytes =
+            encode_message_with_version(&KvmMessage::Ping(1), MIN_PROTOCOL_VERSION, 0, 0).unwrap();
+
+        assert_eq!(bytes[0], MIN_PROTOCOL_VERSION);
+        assert_eq!(
+            encode_message_with_version(&KvmMessage::Ping(1), PROTOCOL_VERSION + 1, 0, 0),
+            Err(ProtocolError::UnsupportedVersion(PROTOCOL_VERSION + 1))
+        );
+    }
+
     #[test]
     fn test_decode_payload_length_exceeds_available_returns_error() {
         let mut bytes = vec![0u8; 24];
@@ -1499,6 +1710,22 @@ mod tests {
         assert_eq!(decoded_ts, ts);
     }
 
+    #[test]
+    fn test_decode_header_reads_fields_without_payload() {
+        // Arrange – keep only the header of an encoded Ping
+        let bytes = encode_message(&KvmMessage::Ping(7), 42, 1_000).unwrap();
+
+        // Act
+        let header = decode_header(&bytes[..HEADER_SIZE]).unwrap();
+
+        // Assert
+        assert_eq!(header.version, PROTOCOL_VERSION);
+        assert_eq!(header.message_type, MessageType::Ping);
+        assert_eq!(header.payload_length as usize, bytes.len() - HEADER_SIZE);
+        assert_eq!(header.sequence_number, 42);
+        assert_eq!(header.timestamp_us, 1_000);
+    }
+
     #[test]
     fn test_header_size_is_24_bytes() {
         let msg = KvmMessage::ScreenInfoAck;
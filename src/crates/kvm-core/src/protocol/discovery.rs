@@ -0,0 +1,158 @@
+//! What both sides agree on for finding a master: the discovery backends and
+//! the DNS-SD service description.
+//!
+//! # Two ways to find a master (for beginners)
+//!
+//! - **Broadcast** – the client sends an `Announce` datagram to
+//!   `255.255.255.255` on the discovery port and the master answers with an
+//!   `AnnounceResponse`.  Simple, but routers never forward broadcasts, and
+//!   some networks filter them altogether.
+//! - **mDNS / DNS-SD** – the master advertises a `_kvm-over-ip._tcp` service
+//!   with multicast DNS (the mechanism behind Bonjour and Avahi), and the
+//!   client browses for it.  Networks that block broadcasts usually still
+//!   carry mDNS, and it can be forwarded between VLANs by an mDNS reflector.
+//!
+//! # The advertised service
+//!
+//! ```text
+//! instance:  <master name>._kvm-over-ip._tcp.local.
+//! SRV:       <host>.local.:<control port>
+//! TXT:       protover=<PROTOCOL_VERSION>  minver=<MIN_PROTOCOL_VERSION>
+//!            control_port=<control port>
+//! ```
+//!
+//! The control port is in both the SRV record and the TXT record; clients use
+//! the TXT value, which travels together with the protocol versions.
+
+use serde::{Deserialize, Serialize};
+
+use super::messages::{MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+/// DNS-SD service type advertised by masters.
+pub const SERVICE_TYPE: &str = "_kvm-over-ip._tcp.local.";
+
+/// TXT key holding the newest protocol version the master speaks.
+pub const TXT_PROTOCOL_VERSION: &str = "protover";
+/// TXT key holding the oldest protocol version the master accepts.
+pub const TXT_MIN_PROTOCOL_VERSION: &str = "minver";
+/// TXT key holding the master's TCP control port.
+pub const TXT_CONTROL_PORT: &str = "control_port";
+
+/// How a client looks for masters, and how a master makes itself findable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryBackend {
+    /// `Announce` / `AnnounceResponse` over UDP broadcast.
+    #[default]
+    Broadcast,
+    /// DNS-SD over multicast DNS.
+    Mdns,
+}
+
+/// The TXT record of an advertised master.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MasterTxt {
+    /// Newest protocol version the master speaks.
+    pub protocol_version: u8,
+    /// Oldest protocol version the master accepts.
+    pub min_protocol_version: u8,
+    /// The master's TCP control port.
+    pub control_port: u16,
+}
+
+impl MasterTxt {
+    /// The TXT record for a master of this build listening on `control_port`.
+    pub fn new(control_port: u16) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            control_port,
+        }
+    }
+
+    /// Renders the record as TXT key/value pairs.
+    pub fn to_properties(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (TXT_PROTOCOL_VERSION, self.protocol_version.to_string()),
+            (
+                TXT_MIN_PROTOCOL_VERSION,
+                self.min_protocol_version.to_string(),
+            ),
+            (TXT_CONTROL_PORT, self.control_port.to_string()),
+        ]
+    }
+
+    /// Reads the record back, looking each key up with `get`.
+    ///
+    /// Returns `None` if a key is missing or not a number.  A missing
+    /// `minver` is read as equal to `protover`.
+    pub fn from_properties<'a>(get: impl Fn(&str) -> Option<&'a str>) -> Option<Self> {
+        let protocol_version = get(TXT_PROTOCOL_VERSION)?.parse().ok()?;
+        let min_protocol_version = match get(TXT_MIN_PROTOCOL_VERSION) {
+            Some(value) => value.parse().ok()?,
+            None => protocol_version,
+        };
+        Some(Self {
+            protocol_version,
+            min_protocol_version,
+            control_port: get(TXT_CONTROL_PORT)?.parse().ok()?,
+        })
+    }
+
+    /// Whether this build can talk to the advertised master: the two
+    /// supported version ranges must overlap.
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version >= MIN_PROTOCOL_VERSION
+            && self.min_protocol_version <= PROTOCOL_VERSION
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(props: &[(&'static str, String)]) -> HashMap<String, String> {
+        props
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_txt_record_round_trips() {
+        // Arrange
+        let txt = MasterTxt::new(24800);
+
+        // Act
+        let map = lookup(&txt.to_properties());
+        let parsed = MasterTxt::from_properties(|k| map.get(k).map(String::as_str));
+
+        // Assert
+        assert_eq!(parsed, Some(txt));
+        assert!(txt.is_compatible());
+    }
+
+    #[test]
+    fn test_txt_record_without_control_port_is_rejected() {
+        let map = lookup(&[(TXT_PROTOCOL_VERSION, "1".to_string())]);
+
+        assert_eq!(
+            MasterTxt::from_properties(|k| map.get(k).map(String::as_str)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_master_newer_than_we_accept_is_incompatible() {
+        let txt = MasterTxt {
+            protocol_version: PROTOCOL_VERSION + 2,
+            min_protocol_version: PROTOCOL_VERSION + 1,
+            control_port: 24800,
+        };
+
+        assert!(!txt.is_compatible());
+    }
+}
@@ -0,0 +1,717 @@
+//! Streaming frame decoder: turns an arbitrary byte stream into messages.
+//!
+//! TCP delivers a *stream* of bytes, not a sequence of messages.  A single
+//! `read()` can return half a header, three whole frames, or one frame plus the
+//! first byte of the next.  [`FrameDecoder`] owns the buffering needed to cope
+//! with that: feed it whatever chunks arrive with [`FrameDecoder::push`] and
+//! pull complete frames out with [`FrameDecoder::next_frame`].
+//!
+//! # Why a maximum payload length? (for beginners)
+//!
+//! The header's `payload_len` field is a `u32`, so a peer (or a corrupted
+//! byte) can claim a payload of up to 4 GiB.  A naive reader that allocates
+//! `payload_len` bytes before reading would let anyone on the network exhaust
+//! the process's memory with a single 24-byte header.  The decoder validates
+//! the header *before* buffering the payload and rejects frames whose declared
+//! length exceeds [`FrameDecoder::max_payload_len`]
+//! (default: [`DEFAULT_MAX_PAYLOAD_LEN`]).
+//!
+//! # What happens on a bad header?
+//!
+//! That depends on the [`BadFramePolicy`]:
+//!
+//! - [`BadFramePolicy::Fail`] (default) – return an error and drop everything
+//!   buffered.  Right for TCP, where a bad header means the peer is broken or
+//!   hostile and the connection should be closed.
+//! - [`BadFramePolicy::Resync`] – skip forward one byte at a time until a
+//!   plausible header (valid version, acceptable length) appears,
+//!   counting the bytes thrown away in [`FrameDecoder::discarded_bytes`].
+//!   Useful for diagnostics tools reading captured traffic.
+//!
+//! A frame with an unknown message type or a payload that fails to parse does
+//! not break framing (the length is still trustworthy), so that frame is
+//! consumed and either reported as a non-fatal error (`Fail`) or skipped
+//! (`Resync`).
+//!
+//! # tokio-util integration
+//!
+//! With the `tokio-codec` cargo feature enabled, `KvmCodec` implements
+//! `tokio_util::codec::{Decoder, Encoder}` so a socket can be wrapped in a
+//! `FramedRead` / `FramedWrite`.  The master, client and web bridge all read
+//! through this codec, so there is exactly one framing implementation.
+
+use thiserror::Error;
+use tracing::warn;
+
+use crate::protocol::codec::{decode_header, decode_message, ProtocolError};
+use crate::protocol::messages::{KvmMessage, MessageHeader, HEADER_SIZE};
+use crate::protocol::negotiation::is_supported_version;
+
+/// Default cap on a single frame's payload: 1 MiB.
+///
+/// Input events are a few dozen bytes; the largest legitimate frames are
+/// clipboard transfers, which are chunked well below this limit.
+pub const DEFAULT_MAX_PAYLOAD_LEN: usize = 1024 * 1024;
+
+/// Once this many consumed bytes sit at the front of the buffer, they are
+/// compacted away on the next `push`.
+const COMPACT_THRESHOLD: usize = 64 * 1024;
+
+/// Errors returned by [`FrameDecoder::next_frame`] and `KvmCodec`.
+#[derive(Debug, Error)]
+pub enum FrameError {
+    /// The header declared a payload larger than the decoder accepts.
+    ///
+    /// The payload is never buffered, so this cannot be used to exhaust
+    /// memory.  The connection should be closed.
+    #[error("frame payload of {declared} bytes exceeds the {max}-byte limit")]
+    PayloadTooLarge { declared: usize, max: usize },
+
+    /// The header or payload could not be decoded.
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+
+    /// The underlying transport failed (only produced by `KvmCodec`).
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl FrameError {
+    /// Returns `true` if the stream can no longer be read after this error.
+    ///
+    /// Non-fatal errors (an unknown message type or a malformed payload) affect
+    /// a single frame whose boundaries were still known, so the decoder has
+    /// already moved past it and the next frame can be read normally.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            FrameError::Protocol(
+                ProtocolError::UnknownMessageType(_) | ProtocolError::MalformedPayload(_)
+            )
+        )
+    }
+}
+
+/// What a [`FrameDecoder`] does when it meets a header it cannot accept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BadFramePolicy {
+    /// Return the error and discard all buffered bytes.
+    #[default]
+    Fail,
+    /// Skip bytes until the next plausible header and keep going.
+    Resync,
+}
+
+/// One decoded frame: the parsed header plus the typed message.
+///
+/// The header is kept because the sequence number and timestamp are needed
+/// for latency measurement and replay protection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// The frame's 24-byte header.
+    pub header: MessageHeader,
+    /// The decoded payload.
+    pub message: KvmMessage,
+}
+
+/// Incremental decoder for the KVM-Over-IP wire format.
+///
+/// # Example
+///
+/// ```rust
+/// use kvm_core::protocol::{encode_message, FrameDecoder, KvmMessage};
+///
+/// let bytes = encode_message(&KvmMessage::Ping(7), 3, 0).unwrap();
+/// let mut decoder = FrameDecoder::new();
+///
+/// // Feed the frame in two pieces; nothing comes out until it is complete.
+/// decoder.push(&bytes[..10]);
+/// assert!(decoder.next_frame().unwrap().is_none());
+/// decoder.push(&bytes[10..]);
+///
+/// let frame = decoder.next_frame().unwrap().expect("frame is complete");
+/// assert_eq!(frame.message, KvmMessage::Ping(7));
+/// assert_eq!(frame.header.sequence_number, 3);
+/// ```
+#[derive(Debug)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+    /// Index of the first unconsumed byte in `buf`.
+    pos: usize,
+    max_payload_len: usize,
+    policy: BadFramePolicy,
+    discarded: u64,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    /// Creates a decoder with [`DEFAULT_MAX_PAYLOAD_LEN`] and
+    /// [`BadFramePolicy::Fail`].
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            pos: 0,
+            max_payload_len: DEFAULT_MAX_PAYLOAD_LEN,
+            policy: BadFramePolicy::Fail,
+            discarded: 0,
+        }
+    }
+
+    /// Sets the largest payload (in bytes, excluding the header) to accept.
+    pub fn with_max_payload_len(mut self, max_payload_len: usize) -> Self {
+        self.max_payload_len = max_payload_len;
+        self
+    }
+
+    /// Sets how bad headers are handled.
+    pub fn with_policy(mut self, policy: BadFramePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the configured payload limit.
+    pub fn max_payload_len(&self) -> usize {
+        self.max_payload_len
+    }
+
+    /// Returns the number of bytes buffered but not yet returned as frames.
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Returns how many bytes have been skipped while resynchronising or
+    /// dropping undecodable frames.
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Appends a chunk of received bytes.  Chunks may split frames anywhere.
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+        } else if self.pos >= COMPACT_THRESHOLD {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete frame, or `Ok(None)` if more bytes are needed.
+    ///
+    /// Call this in a loop after each [`push`](Self::push) until it returns
+    /// `Ok(None)`: one chunk can contain several frames.
+    ///
+    /// # Errors
+    ///
+    /// Under [`BadFramePolicy::Fail`]:
+    ///
+    /// - A bad header ([`FrameError::PayloadTooLarge`] or an unsupported
+    ///   version) clears the buffer; the stream should be abandoned.
+    /// - A frame with an unknown message type or an undecodable payload is
+    ///   consumed and reported; the stream remains usable (see
+    ///   [`FrameError::is_fatal`]).
+    ///
+    /// Under [`BadFramePolicy::Resync`] no errors are returned.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, FrameError> {
+        loop {
+            let available = &self.buf[self.pos..];
+            if available.len() < HEADER_SIZE {
+                return Ok(None);
+            }
+
+            let payload_len = match self.check_framing(available) {
+                Ok(len) => len,
+                Err(e) => match self.policy {
+                    BadFramePolicy::Fail => {
+                        self.buf.clear();
+                        self.pos = 0;
+                        return Err(e);
+                    }
+                    BadFramePolicy::Resync => {
+                        self.skip_to_next_candidate(&e);
+                        continue;
+                    }
+                },
+            };
+
+            let frame_len = HEADER_SIZE + payload_len;
+            if available.len() < frame_len {
+                return Ok(None);
+            }
+
+            let frame = &available[..frame_len];
+            let result = decode_header(frame).and_then(|header| {
+                decode_message(frame).map(|(message, _)| Frame { header, message })
+            });
+            self.pos += frame_len;
+            match result {
+                Ok(frame) => return Ok(Some(frame)),
+                Err(e) => match self.policy {
+                    BadFramePolicy::Fail => return Err(e.into()),
+                    BadFramePolicy::Resync => {
+                        warn!("dropping undecodable {frame_len}-byte frame: {e}");
+                        self.discarded += frame_len as u64;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Checks the parts of the header that framing depends on (version and
+    /// length) and returns the payload length.
+    ///
+    /// The message type is deliberately not checked here: a frame of an
+    /// unknown type still has a trustworthy length, so it can be skipped
+    /// without losing track of frame boundaries.
+    fn check_framing(&self, bytes: &[u8]) -> Result<usize, FrameError> {
+        if !is_supported_version(bytes[0]) {
+            return Err(ProtocolError::UnsupportedVersion(bytes[0]).into());
+        }
+        let declared = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        if declared > self.max_payload_len {
+            return Err(FrameError::PayloadTooLarge {
+                declared,
+                max: self.max_payload_len,
+            });
+        }
+        Ok(declared)
+    }
+
+    /// Drops the byte at the cursor, then everything up to the next byte that
+    /// could start a header (i.e. is a supported protocol version).
+    fn skip_to_next_candidate(&mut self, cause: &FrameError) {
+        let rest = &self.buf[self.pos + 1..];
+        let skip = 1 + rest
+            .iter()
+            .position(|&b| is_supported_version(b))
+            .unwrap_or(rest.len());
+        warn!("bad frame header ({cause}); skipping {skip} bytes to resync");
+        self.pos += skip;
+        self.discarded += skip as u64;
+    }
+}
+
+#[cfg(feature = "tokio-codec")]
+pub use tokio_codec::KvmCodec;
+
+#[cfg(feature = "tokio-codec")]
+mod tokio_codec {
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+    use tracing::warn;
+
+    use super::{Frame, FrameDecoder, FrameError};
+    use crate::protocol::codec::encode_message_now;
+    use crate::protocol::messages::KvmMessage;
+    use crate::protocol::sequence::SequenceCounter;
+
+    /// `tokio_util` codec wrapping a [`FrameDecoder`] (reading) and a
+    /// [`SequenceCounter`] (writing).
+    ///
+    /// Decoding yields [`Frame`]s, skipping frames that fail with a non-fatal
+    /// [`FrameError`]; encoding accepts a [`KvmMessage`], stamps it
+    /// with the next sequence number and the current time, and appends it to
+    /// the output buffer.
+    #[derive(Debug, Default)]
+    pub struct KvmCodec {
+        decoder: FrameDecoder,
+        sequence: SequenceCounter,
+    }
+
+    impl KvmCodec {
+        /// Creates a codec with a default [`FrameDecoder`].
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Creates a codec around a pre-configured decoder.
+        pub fn with_decoder(decoder: FrameDecoder) -> Self {
+            Self {
+                decoder,
+                sequence: SequenceCounter::default(),
+            }
+        }
+    }
+
+    impl Decoder for KvmCodec {
+        type Item = Frame;
+        type Error = FrameError;
+
+        /// Skips (and logs) frames that fail with a non-fatal error, because
+        /// `FramedRead` ends the stream after the first error it sees.
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, FrameError> {
+            if !src.is_empty() {
+                self.decoder.push(src);
+                src.clear();
+            }
+            loop {
+                match self.decoder.next_frame() {
+                    Err(e) if !e.is_fatal() => warn!("skipping inbound frame: {e}"),
+                    other => return other,
+                }
+            }
+        }
+
+        fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, FrameError> {
+            match self.decode(src)? {
+                Some(frame) => Ok(Some(frame)),
+                None if self.decoder.buffered_len() == 0 => Ok(None),
+                None => Err(FrameError::Io(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "stream closed with {} bytes of an incomplete frame",
+                        self.decoder.buffered_len()
+                    ),
+                ))),
+            }
+        }
+    }
+
+    impl Encoder<KvmMessage> for KvmCodec {
+        type Error = FrameError;
+
+        fn encode(&mut self, item: KvmMessage, dst: &mut BytesMut) -> Result<(), FrameError> {
+            let bytes = encode_message_now(&item, self.sequence.next())?;
+            dst.extend_from_slice(&bytes);
+            Ok(())
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::codec::encode_message;
+    use crate::protocol::messages::{MessageType, ScreenInfoMessage};
+
+    fn frame_bytes(msg: &KvmMessage, seq: u64) -> Vec<u8> {
+        encode_message(msg, seq, 0).expect("encode failed")
+    }
+
+    /// Drains every frame the decoder can currently produce.
+    fn drain(decoder: &mut FrameDecoder) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.next_frame().expect("decode failed") {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[test]
+    fn test_frame_fed_one_byte_at_a_time_is_decoded_once_complete() {
+        // Arrange
+        let msg = KvmMessage::ScreenInfo(ScreenInfoMessage { monitors: vec![] });
+        let bytes = frame_bytes(&msg, 9);
+        let mut decoder = FrameDecoder::new();
+
+        // Act / Assert
+        for (i, byte) in bytes.iter().enumerate() {
+            decoder.push(std::slice::from_ref(byte));
+            let frame = decoder.next_frame().unwrap();
+            if i + 1 < bytes.len() {
+                assert!(frame.is_none(), "frame returned early at byte {i}");
+            } else {
+                let frame = frame.expect("frame must be complete");
+                assert_eq!(frame.message, msg);
+                assert_eq!(frame.header.sequence_number, 9);
+            }
+        }
+        assert_eq!(decoder.buffered_len(), 0);
+    }
+
+    #[test]
+    fn test_several_frames_in_one_chunk_are_all_returned_in_order() {
+        // Arrange
+        let mut chunk = frame_bytes(&KvmMessage::Ping(1), 1);
+        chunk.extend(frame_bytes(&KvmMessage::ScreenInfoAck, 2));
+        chunk.extend(frame_bytes(&KvmMessage::Pong(3), 3));
+        let mut decoder = FrameDecoder::new();
+
+        // Act
+        decoder.push(&chunk);
+        let frames = drain(&mut decoder);
+
+        // Assert
+        let messages: Vec<_> = frames.into_iter().map(|f| f.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                KvmMessage::Ping(1),
+                KvmMessage::ScreenInfoAck,
+                KvmMessage::Pong(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunk_boundary_inside_second_frame_keeps_remainder_buffered() {
+        // Arrange
+        let first = frame_bytes(&KvmMessage::Ping(1), 0);
+        let second = frame_bytes(&KvmMessage::Ping(2), 1);
+        let mut stream = first.clone();
+        stream.extend(&second);
+        let split = first.len() + 5;
+        let mut decoder = FrameDecoder::new();
+
+        // Act
+        decoder.push(&stream[..split]);
+        let before = drain(&mut decoder);
+        decoder.push(&stream[split..]);
+        let after = drain(&mut decoder);
+
+        // Assert
+        assert_eq!(before.len(), 1);
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].message, KvmMessage::Ping(2));
+    }
+
+    #[test]
+    fn test_oversized_payload_is_rejected_from_header_alone() {
+        // Arrange – only the 24-byte header is supplied; it claims 4 GiB.
+        let mut header = frame_bytes(&KvmMessage::Ping(0), 0);
+        header.truncate(HEADER_SIZE);
+        header[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut decoder = FrameDecoder::new().with_max_payload_len(1024);
+
+        // Act
+        decoder.push(&header);
+        let result = decoder.next_frame();
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(FrameError::PayloadTooLarge {
+                declared,
+                max: 1024
+            }) if declared == u32::MAX as usize
+        ));
+        assert_eq!(decoder.buffered_len(), 0, "fail policy drops the buffer");
+    }
+
+    #[test]
+    fn test_payload_exactly_at_limit_is_accepted() {
+        // Arrange – a Ping payload is 8 bytes.
+        let bytes = frame_bytes(&KvmMessage::Ping(5), 0);
+        let mut decoder = FrameDecoder::new().with_max_payload_len(8);
+
+        // Act
+        decoder.push(&bytes);
+
+        // Assert
+        assert_eq!(drain(&mut decoder).len(), 1);
+    }
+
+    #[test]
+    fn test_bad_version_under_fail_policy_returns_protocol_error() {
+        // Arrange
+        let mut bytes = frame_bytes(&KvmMessage::Ping(0), 0);
+        bytes[0] = 0x7F;
+        let mut decoder = FrameDecoder::new();
+
+        // Act
+        decoder.push(&bytes);
+        let result = decoder.next_frame();
+
+        // Assert
+        let err = result.expect_err("bad version must be an error");
+        assert!(matches!(
+            err,
+            FrameError::Protocol(ProtocolError::UnsupportedVersion(0x7F))
+        ));
+        assert!(err.is_fatal());
+    }
+
+    #[test]
+    fn test_resync_policy_skips_garbage_before_a_valid_frame() {
+        // Arrange
+        let mut stream = vec![0xDE, 0xAD, 0xBE, 0xEF];
+        let garbage_len = stream.len() as u64;
+        stream.extend(frame_bytes(&KvmMessage::Ping(42), 0));
+        let mut decoder = FrameDecoder::new().with_policy(BadFramePolicy::Resync);
+
+        // Act
+        decoder.push(&stream);
+        let frames = drain(&mut decoder);
+
+        // Assert
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].message, KvmMessage::Ping(42));
+        assert_eq!(decoder.discarded_bytes(), garbage_len);
+    }
+
+    #[test]
+    fn test_resync_policy_skips_oversized_header() {
+        // Arrange
+        let mut bad = frame_bytes(&KvmMessage::Ping(0), 0);
+        bad.truncate(HEADER_SIZE);
+        bad[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut stream = bad;
+        stream.extend(frame_bytes(&KvmMessage::Pong(1), 1));
+        let mut decoder = FrameDecoder::new().with_policy(BadFramePolicy::Resync);
+
+        // Act
+        decoder.push(&stream);
+        let frames = drain(&mut decoder);
+
+        // Assert
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].message, KvmMessage::Pong(1));
+    }
+
+    #[test]
+    fn test_malformed_payload_under_fail_policy_is_consumed_and_stream_continues() {
+        // Arrange – a Disconnect frame with an invalid reason byte.
+        let mut bad = frame_bytes(
+            &KvmMessage::Disconnect {
+                reason: crate::protocol::messages::DisconnectReason::UserInitiated,
+            },
+            0,
+        );
+        assert_eq!(bad[1], MessageType::Disconnect as u8);
+        *bad.last_mut().unwrap() = 0xEE;
+        let mut stream = bad;
+        stream.extend(frame_bytes(&KvmMessage::Ping(1), 1));
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&stream);
+
+        // Act
+        let first = decoder.next_frame();
+        let second = decoder.next_frame().unwrap();
+
+        // Assert
+        let err = first.expect_err("bad payload must be reported");
+        assert!(matches!(
+            err,
+            FrameError::Protocol(ProtocolError::MalformedPayload(_))
+        ));
+        assert!(!err.is_fatal());
+        assert_eq!(second.map(|f| f.message), Some(KvmMessage::Ping(1)));
+    }
+
+    #[test]
+    fn test_unknown_message_type_is_skipped_without_losing_frame_boundaries() {
+        // Arrange – a future message type carrying a 3-byte payload.
+        let mut unknown = frame_bytes(&KvmMessage::ScreenInfoAck, 0);
+        unknown[1] = 0xF0;
+        unknown[4..8].copy_from_slice(&3u32.to_be_bytes());
+        unknown.extend([1, 2, 3]);
+        let mut stream = unknown;
+        stream.extend(frame_bytes(&KvmMessage::Pong(8), 1));
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&stream);
+
+        // Act
+        let first = decoder.next_frame();
+        let second = decoder.next_frame().unwrap();
+
+        // Assert
+        assert!(matches!(
+            first,
+            Err(FrameError::Protocol(ProtocolError::UnknownMessageType(
+                0xF0
+            )))
+        ));
+        assert_eq!(second.map(|f| f.message), Some(KvmMessage::Pong(8)));
+    }
+
+    #[test]
+    fn test_consumed_bytes_are_compacted_while_a_partial_frame_is_pending() {
+        // Arrange – every push completes one frame and starts the next, so the
+        // buffer is never empty when the next chunk arrives.
+        let bytes = frame_bytes(&KvmMessage::Ping(0), 0);
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&bytes[..1]);
+        let mut chunk = bytes[1..].to_vec();
+        chunk.push(bytes[0]);
+
+        // Act
+        for _ in 0..(2 * COMPACT_THRESHOLD / bytes.len()) {
+            decoder.push(&chunk);
+            assert!(decoder.next_frame().unwrap().is_some());
+        }
+
+        // Assert
+        assert_eq!(decoder.buffered_len(), 1);
+        assert!(
+            decoder.buf.len() <= COMPACT_THRESHOLD + bytes.len(),
+            "buffer grew to {} bytes",
+            decoder.buf.len()
+        );
+    }
+
+    #[cfg(feature = "tokio-codec")]
+    mod codec {
+        use bytes::BytesMut;
+        use tokio_util::codec::{Decoder, Encoder};
+
+        use super::super::*;
+        use crate::protocol::codec::encode_message;
+        use crate::protocol::messages::PROTOCOL_VERSION;
+
+        #[test]
+        fn test_codec_round_trips_and_numbers_frames() {
+            // Arrange
+            let mut codec = KvmCodec::new();
+            let mut buf = BytesMut::new();
+
+            // Act
+            codec.encode(KvmMessage::Ping(1), &mut buf).unwrap();
+            codec.encode(KvmMessage::Pong(2), &mut buf).unwrap();
+            let first = codec.decode(&mut buf).unwrap().unwrap();
+            let second = codec.decode(&mut buf).unwrap().unwrap();
+
+            // Assert
+            assert_eq!(first.message, KvmMessage::Ping(1));
+            assert_eq!(second.message, KvmMessage::Pong(2));
+            assert_eq!(
+                (first.header.sequence_number, second.header.sequence_number),
+                (0, 1)
+            );
+            assert!(codec.decode(&mut buf).unwrap().is_none());
+        }
+
+        #[test]
+        fn test_codec_skips_malformed_frame_and_returns_the_next_one() {
+            // Arrange – Disconnect with an out-of-range reason byte, then a Ping.
+            let mut bytes = encode_message(
+                &KvmMessage::Disconnect {
+                    reason: crate::protocol::messages::DisconnectReason::Timeout,
+                },
+                0,
+                0,
+            )
+            .unwrap();
+            *bytes.last_mut().unwrap() = 0xEE;
+            bytes.extend(encode_message(&KvmMessage::Ping(4), 1, 0).unwrap());
+            let mut buf = BytesMut::from(&bytes[..]);
+            let mut codec = KvmCodec::new();
+
+            // Act
+            let frame = codec.decode(&mut buf).unwrap();
+
+            // Assert
+            assert_eq!(frame.map(|f| f.message), Some(KvmMessage::Ping(4)));
+        }
+
+        #[test]
+        fn test_codec_decode_eof_with_partial_frame_is_an_error() {
+            // Arrange
+            let mut codec = KvmCodec::new();
+            let mut buf = BytesMut::from(&[PROTOCOL_VERSION, 0x01, 0x00][..]);
+
+            // Act
+            let result = codec.decode_eof(&mut buf);
+
+            // Assert
+            assert!(matches!(result, Err(FrameError::Io(_))));
+        }
+    }
+}
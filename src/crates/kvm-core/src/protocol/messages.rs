@@ -22,13 +22,21 @@ use uuid::Uuid;
 
 // ── Protocol constants ────────────────────────────────────────────────────────
 
-/// Current protocol version byte.
+/// Newest protocol version this build speaks.
 ///
-/// This value is written into byte 0 of every message header.  If a receiver
-/// sees a different value it will reject the message with
+/// This value is written into byte 0 of every message header unless a session
+/// negotiated an older version (see [`crate::protocol::negotiation`]).  If a
+/// receiver sees a version outside [`MIN_PROTOCOL_VERSION`]`..=PROTOCOL_VERSION`
+/// it rejects the message with
 /// [`crate::protocol::codec::ProtocolError::UnsupportedVersion`].
 pub const PROTOCOL_VERSION: u8 = 0x01;
 
+/// Oldest protocol version this build still speaks.
+///
+/// Raising it drops support for peers that have not been upgraded, so it only
+/// moves once every deployed master and client is newer.
+pub const MIN_PROTOCOL_VERSION: u8 = 0x01;
+
 /// Total size of the common message header in bytes.
 ///
 /// Every message sent over the wire starts with exactly this many bytes before
@@ -36,7 +44,7 @@ pub const PROTOCOL_VERSION: u8 = 0x01;
 ///
 /// ```text
 /// Offset  Size  Field
-/// 0       1     version          (always PROTOCOL_VERSION = 0x01)
+/// 0       1     version          (the session's protocol version)
 /// 1       1     message_type     (discriminates the payload)
 /// 2       2     reserved         (must be 0x00 0x00)
 /// 4       4     payload_length   (big-endian u32)
@@ -107,7 +115,7 @@ pub enum MessageType {
     HelloAck = 0x02,
     /// Master offers a PIN-based pairing session.
     PairingRequest = 0x03,
-    /// Client submits its PIN hash to complete pairing.
+    /// Client answers the pairing request with its SPAKE2 message and key confirmation.
     PairingResponse = 0x04,
     /// Client reports its monitor configuration.
     ScreenInfo = 0x05,
@@ -125,6 +133,8 @@ pub enum MessageType {
     ClipboardData = 0x0B,
     /// Master pushes live configuration changes to the client.
     ConfigUpdate = 0x0C,
+    /// Master proves it derived the same pairing key as the client.
+    PairingConfirm = 0x0D,
     // ── Input channel (0x40–0x7F) ─────────────────────────────────────────────
     /// A single keyboard key press or release.
     KeyEvent = 0x40,
@@ -163,6 +173,7 @@ impl TryFrom<u8> for MessageType {
             0x0A => Ok(MessageType::Error),
             0x0B => Ok(MessageType::ClipboardData),
             0x0C => Ok(MessageType::ConfigUpdate),
+            0x0D => Ok(MessageType::PairingConfirm),
             0x40 => Ok(MessageType::KeyEvent),
             0x41 => Ok(MessageType::MouseMove),
             0x42 => Ok(MessageType::MouseButton),
@@ -183,7 +194,8 @@ impl TryFrom<u8> for MessageType {
 /// builds it for you from the message content and the parameters you supply.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MessageHeader {
-    /// Protocol version; always [`PROTOCOL_VERSION`].
+    /// Protocol version the frame was encoded with; within
+    /// [`MIN_PROTOCOL_VERSION`]`..=`[`PROTOCOL_VERSION`].
     pub version: u8,
     /// Identifies the payload type.
     pub message_type: MessageType,
@@ -215,7 +227,10 @@ pub struct HelloMessage {
     /// Generated once when the client application is first installed and stored
     /// persistently.  The master uses this to recognise returning clients.
     pub client_id: Uuid,
-    /// Protocol version the client supports.
+    /// Highest protocol version the client supports.
+    ///
+    /// The master answers with the version both sides will use; see
+    /// [`crate::protocol::negotiation`].
     pub protocol_version: u8,
     /// Operating system of the client.
     pub platform_id: PlatformId,
@@ -228,6 +243,12 @@ pub struct HelloMessage {
     /// Each bit signals that the client supports a particular feature.
     /// See the [`capabilities`] module for the individual flag constants.
     pub capabilities: u32,
+    /// The `session_token` of this client's previous session, if it is
+    /// reconnecting.  A master that still remembers that session resumes it
+    /// (see [`HelloAckMessage::resumed`]) instead of starting from scratch.
+    ///
+    /// Encoded after `capabilities`, and only when present.
+    pub resume_token: Option<[u8; 32]>,
 }
 
 /// Capability bitmask flags used in [`HelloMessage::capabilities`].
@@ -255,6 +276,17 @@ pub mod capabilities {
     pub const CLIPBOARD_SHARING: u32 = 1 << 2;
     /// Bit 3: client has more than one monitor.
     pub const MULTI_MONITOR: u32 = 1 << 3;
+    /// Bit 4: peer understands [`ClipboardFormat::Html`](super::ClipboardFormat::Html)
+    /// clipboard content.  Only meaningful together with `CLIPBOARD_SHARING`.
+    pub const CLIPBOARD_HTML: u32 = 1 << 4;
+    /// Bit 5: peer understands [`ClipboardFormat::Image`](super::ClipboardFormat::Image)
+    /// (PNG) clipboard content.  Only meaningful together with
+    /// `CLIPBOARD_SHARING`.
+    pub const CLIPBOARD_IMAGE: u32 = 1 << 5;
+
+    /// Capabilities assumed for a master whose `HelloAck` predates capability
+    /// negotiation and therefore does not list any.
+    pub const BASELINE: u32 = KEYBOARD_EMULATION | MOUSE_EMULATION;
 }
 
 /// HELLO_ACK (0x02): master response to a HELLO.
@@ -264,12 +296,14 @@ pub mod capabilities {
 /// understand why and display an appropriate error to the user.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HelloAckMessage {
-    /// 32-byte random session token used to bind the DTLS input channel.
+    /// 32-byte random session token that keys the UDP input channel.
     ///
-    /// The client must present this token when opening the secondary (input)
-    /// channel so the master knows which TCP session it belongs to.
+    /// Both sides derive the input channel's encryption keys from it (see
+    /// `crypto::input_channel`), so only the peer of this TLS session can
+    /// send or read input datagrams.
     pub session_token: [u8; 32],
-    /// Protocol version the master is using.
+    /// Protocol version agreed for this session (the highest version both
+    /// sides speak).  On rejection it is the master's newest version.
     pub server_version: u8,
     /// Whether the connection was accepted.
     pub accepted: bool,
@@ -278,6 +312,19 @@ pub struct HelloAckMessage {
     /// Non-zero values correspond to specific rejection reasons (e.g., too many
     /// clients connected, or pairing required).
     pub reject_reason: u8,
+    /// Capabilities enabled for this session: the client's `Hello`
+    /// capabilities that the master supports as well.
+    ///
+    /// Encoded after `reject_reason`; older masters leave it out, in which case
+    /// it decodes as [`capabilities::BASELINE`].
+    pub capabilities: u32,
+    /// Whether the master resumed the session named by the `Hello`'s
+    /// `resume_token`.  A resumed session keeps the client's last
+    /// `ScreenInfo`, so the client need not send it again.
+    ///
+    /// Encoded as bit 0 of a flags byte after `capabilities`; decodes as
+    /// `false` when the byte is absent.
+    pub resumed: bool,
 }
 
 /// PAIRING_REQUEST (0x03): master initiates PIN pairing.
@@ -289,47 +336,87 @@ pub struct HelloAckMessage {
 /// # How PIN pairing works
 ///
 /// 1. Master generates a random 6-digit PIN and displays it on screen.
-/// 2. Master sends `PairingRequest` to the client (with a session UUID and expiry).
+/// 2. Master sends `PairingRequest` to the client (session UUID, expiry, and
+///    the master's SPAKE2 message derived from the PIN).
 /// 3. Client user types the PIN into the client UI.
-/// 4. Client sends `PairingResponse` with a hash of (PIN + session UUID).
-/// 5. Master verifies the hash; if it matches, pairing is complete.
+/// 4. Client sends `PairingResponse` with its own SPAKE2 message and a key
+///    confirmation MAC.
+/// 5. Master checks the MAC; if it verifies, the PINs matched.  The master
+///    replies with `PairingConfirm` carrying its own MAC.
+///
+/// Neither the PIN nor anything an eavesdropper could brute-force offline
+/// crosses the wire; see `kvm_core::crypto::pairing` for the details.
+///
+/// # Wire layout (big-endian)
 ///
-/// Hashing rather than sending the plain PIN prevents a network eavesdropper
-/// from learning the PIN.
+/// ```text
+/// [pairing_session_id : 16 bytes][expires_at_secs : 8 bytes]
+/// [spake_message      : 32 bytes]
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PairingRequestMessage {
     /// Unique identifier for this pairing session.
     ///
-    /// Included in the hash input to prevent replay attacks: an attacker who
-    /// intercepts a valid `PairingResponse` cannot reuse it for a different
-    /// session because the session UUID would not match.
+    /// Mixed into the PIN-derived secret and the key-exchange transcript, so a
+    /// response recorded in one session is worthless in any other.
     pub pairing_session_id: Uuid,
     /// Expiry timestamp in seconds since Unix epoch.
     ///
     /// The client must reject this message and not display the PIN prompt if
     /// the current time is past this value.
     pub expires_at_secs: u64,
+    /// The master's SPAKE2 message (a compressed Edwards point).
+    pub spake_message: [u8; 32],
 }
 
 /// PAIRING_RESPONSE (0x04): client accepts/rejects a pairing request.
 ///
 /// Sent by the client after the user types the PIN shown on the master.
+///
+/// # Wire layout (big-endian)
+///
+/// ```text
+/// [pairing_session_id : 16 bytes][spake_message : 32 bytes]
+/// [confirmation       : 32 bytes][accepted      :  1 byte ]
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PairingResponseMessage {
     /// Pairing session being responded to.
     pub pairing_session_id: Uuid,
-    /// SHA-256 hash of (PIN + pairing_session_id) as hex string.
+    /// The client's SPAKE2 message (a compressed Edwards point).
+    pub spake_message: [u8; 32],
+    /// The client's key-confirmation MAC.
     ///
-    /// The master hashes (PIN + pairing_session_id) on its side and
-    /// compares — if the digests match, the PIN was correct.
-    pub pin_hash: String,
+    /// The master derives the same key only if both sides used the same PIN,
+    /// so a MAC that fails to verify means the PIN was wrong.
+    pub confirmation: [u8; 32],
     /// Whether the client accepted the pairing request.
     ///
     /// A client sets this to `false` if the user dismissed the PIN dialog
-    /// without entering a PIN.
+    /// without entering a PIN; the other fields are then all zeroes.
     pub accepted: bool,
 }
 
+/// PAIRING_CONFIRM (0x0D): master proves it derived the same pairing key.
+///
+/// Sent after the master has verified the client's confirmation.  Without it
+/// the client would only know that *someone* answered; with it the client
+/// knows the peer that showed the PIN is the one on the other end of this
+/// connection.
+///
+/// # Wire layout (big-endian)
+///
+/// ```text
+/// [pairing_session_id : 16 bytes][confirmation : 32 bytes]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingConfirmMessage {
+    /// Pairing session being confirmed.
+    pub pairing_session_id: Uuid,
+    /// The master's key-confirmation MAC.
+    pub confirmation: [u8; 32],
+}
+
 /// Single monitor information within [`ScreenInfoMessage`].
 ///
 /// Describes one physical monitor attached to the client machine.
@@ -646,9 +733,10 @@ pub struct AnnounceResponseMessage {
 pub enum ClipboardFormat {
     /// Plain UTF-8 text (most common).
     Utf8Text = 0x01,
-    /// HTML markup.
+    /// HTML markup with a plain-text fallback, packed as described in
+    /// `kvm_core::clipboard::html`.
     Html = 0x02,
-    /// Image data (format-specific; typically PNG).
+    /// A PNG image (see `kvm_core::clipboard::image`).
     Image = 0x03,
 }
 
@@ -720,7 +808,7 @@ impl TryFrom<u8> for DisconnectReason {
 pub enum ProtocolErrorCode {
     /// The client's protocol version is incompatible with the master's.
     ProtocolVersionMismatch = 0x01,
-    /// The pairing PIN hash did not match.
+    /// The pairing PIN did not match.
     AuthenticationFailed = 0x02,
     /// This client has not yet completed pairing.
     PairingRequired = 0x03,
@@ -794,9 +882,10 @@ pub struct ConfigUpdateMessage {
     /// Valid values: `"error"`, `"warn"`, `"info"`, `"debug"`, `"trace"`.
     pub log_level: String,
 
-    /// Human-readable description of the hotkey that switches input focus away
-    /// from the client back to the master (e.g. `"ScrollLock+ScrollLock"`).
-    /// The client can display this in its UI so the user knows how to escape.
+    /// The hotkey that switches input focus away from the client back to the
+    /// master (e.g. `"ScrollLock ScrollLock"`), in the grammar parsed by
+    /// [`crate::keymap::hotkey::Hotkey`].  The client can display this in its
+    /// UI so the user knows how to escape.
     pub disable_hotkey: String,
 
     /// Packed boolean settings — see [`config_flags`] for the individual bits.
@@ -835,6 +924,7 @@ pub enum KvmMessage {
     HelloAck(HelloAckMessage),
     PairingRequest(PairingRequestMessage),
     PairingResponse(PairingResponseMessage),
+    PairingConfirm(PairingConfirmMessage),
     ScreenInfo(ScreenInfoMessage),
     /// Acknowledgement that screen info was received; carries no payload.
     ScreenInfoAck,
@@ -873,6 +963,7 @@ impl KvmMessage {
             KvmMessage::HelloAck(_) => MessageType::HelloAck,
             KvmMessage::PairingRequest(_) => MessageType::PairingRequest,
             KvmMessage::PairingResponse(_) => MessageType::PairingResponse,
+            KvmMessage::PairingConfirm(_) => MessageType::PairingConfirm,
             KvmMessage::ScreenInfo(_) => MessageType::ScreenInfo,
             KvmMessage::ScreenInfoAck => MessageType::ScreenInfoAck,
             KvmMessage::Ping(_) => MessageType::Ping,
@@ -22,16 +22,29 @@
 //!
 //! - **`messages`** – All message type definitions (enums, structs).
 //! - **`codec`**    – Binary encoding and decoding logic.
+//! - **`discovery`** – Discovery backends and the DNS-SD service description
+//!   masters advertise.
+//! - **`negotiation`** – Protocol version range and capability checks agreed
+//!   in the `Hello` handshake.
+//! - **`framing`**  – Streaming decoder that splits a byte stream into frames
+//!   and enforces a maximum payload size.
 //! - **`sequence`** – Thread-safe incrementing counter for sequence numbers.
 
 // Declare the sub-modules.  Rust compiles these from separate source files.
 pub mod codec;
+pub mod discovery;
+pub mod framing;
 pub mod messages;
+pub mod negotiation;
 pub mod sequence;
 
 // Re-export the most commonly needed items at the protocol module level,
 // so callers can write `kvm_core::protocol::encode_message` instead of
 // `kvm_core::protocol::codec::encode_message`.
 pub use codec::{decode_message, encode_message, ProtocolError};
+#[cfg(feature = "tokio-codec")]
+pub use framing::KvmCodec;
+pub use framing::{BadFramePolicy, Frame, FrameDecoder, FrameError, DEFAULT_MAX_PAYLOAD_LEN};
 pub use messages::*;
+pub use negotiation::{Negotiated, NegotiationError};
 pub use sequence::SequenceCounter;
@@ -0,0 +1,289 @@
+//! Protocol version and capability negotiation for the `Hello` handshake.
+//!
+//! # How negotiation works (for beginners)
+//!
+//! Master and clients are upgraded one machine at a time, so for a while a
+//! new master talks to old clients and vice versa.  Each build therefore
+//! speaks a *range* of protocol versions, from [`MIN_PROTOCOL_VERSION`] up to
+//! [`PROTOCOL_VERSION`]:
+//!
+//! 1. The client sends `Hello` with the highest version it speaks in
+//!    `protocol_version` and the features it supports in `capabilities`.
+//!    The `Hello` frame itself carries [`MIN_PROTOCOL_VERSION`] in its header
+//!    so that even the oldest supported master can read it.
+//! 2. The master picks the highest version both sides speak
+//!    ([`negotiate_version`]) and answers with it in `HelloAck.server_version`,
+//!    together with the capabilities *both* sides support (the bitwise AND
+//!    of the two masks).
+//! 3. Both sides remember the outcome as a [`Negotiated`] value for the rest
+//!    of the session.  Before sending a message they ask
+//!    [`Negotiated::check`] whether the peer agreed to receive it; for example
+//!    `ClipboardData` needs the `CLIPBOARD_SHARING` bit, plus
+//!    `CLIPBOARD_HTML` or `CLIPBOARD_IMAGE` for the rich formats.
+//!
+//! Refusing locally with a [`NegotiationError`] is better than sending: an
+//! older peer would at best ignore the message and at worst drop the
+//! connection because it does not understand it.
+
+use thiserror::Error;
+
+use crate::protocol::messages::{
+    capabilities, InputEvent, KvmMessage, MessageType, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+
+/// Errors produced while negotiating or enforcing a session's protocol terms.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum NegotiationError {
+    /// The peer's versions do not overlap with ours.
+    #[error(
+        "peer speaks protocol version {peer}, but this build supports \
+         {MIN_PROTOCOL_VERSION}..={PROTOCOL_VERSION}"
+    )]
+    UnsupportedVersion { peer: u8 },
+
+    /// The message needs a capability that was not agreed for this session.
+    #[error(
+        "cannot send {message_type:?}: peer did not advertise capability bits 0x{missing:08X}"
+    )]
+    CapabilityNotNegotiated {
+        message_type: MessageType,
+        /// The required capability bits that are missing.
+        missing: u32,
+    },
+}
+
+/// Returns `true` if this build can read and write frames of `version`.
+pub fn is_supported_version(version: u8) -> bool {
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version)
+}
+
+/// Picks the protocol version for a session, given the highest version the
+/// peer speaks.
+///
+/// The result is the highest version both sides support.
+///
+/// # Errors
+///
+/// Returns [`NegotiationError::UnsupportedVersion`] if the peer is older than
+/// [`MIN_PROTOCOL_VERSION`].
+pub fn negotiate_version(peer_max: u8) -> Result<u8, NegotiationError> {
+    let agreed = peer_max.min(PROTOCOL_VERSION);
+    if agreed < MIN_PROTOCOL_VERSION {
+        return Err(NegotiationError::UnsupportedVersion { peer: peer_max });
+    }
+    Ok(agreed)
+}
+
+/// Capability bits the receiver of `msg` must have advertised.
+///
+/// Messages that every peer understands (handshake, pairing, keepalive, …)
+/// need none and return 0.
+pub fn required_capabilities(msg: &KvmMessage) -> u32 {
+    match msg {
+        KvmMessage::KeyEvent(_) => capabilities::KEYBOARD_EMULATION,
+        KvmMessage::MouseMove(_) | KvmMessage::MouseButton(_) | KvmMessage::MouseScroll(_) => {
+            capabilities::MOUSE_EMULATION
+        }
+        KvmMessage::InputBatch(events) => events.iter().fold(0, |acc, event| {
+            acc | match event {
+                InputEvent::Key(_) => capabilities::KEYBOARD_EMULATION,
+                _ => capabilities::MOUSE_EMULATION,
+            }
+        }),
+        KvmMessage::ClipboardData(m) => crate::clipboard::required_capabilities(m.format),
+        _ => 0,
+    }
+}
+
+/// The protocol terms agreed for one session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated {
+    version: u8,
+    capabilities: u32,
+}
+
+impl Negotiated {
+    /// Records the agreed `version` and the capabilities supported by both
+    /// the local side (`local`) and the peer (`peer`).
+    pub fn new(version: u8, local: u32, peer: u32) -> Self {
+        Self {
+            version,
+            capabilities: local & peer,
+        }
+    }
+
+    /// Protocol version used for every frame after the handshake.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Capability bits both sides support.
+    pub fn capabilities(&self) -> u32 {
+        self.capabilities
+    }
+
+    /// Returns `true` if every bit in `capability` was agreed.
+    pub fn supports(&self, capability: u32) -> bool {
+        self.capabilities & capability == capability
+    }
+
+    /// Checks that `msg` may be sent to the peer in this session.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NegotiationError::CapabilityNotNegotiated`] if the peer did
+    /// not advertise a capability the message needs.
+    pub fn check(&self, msg: &KvmMessage) -> Result<(), NegotiationError> {
+        let missing = required_capabilities(msg) & !self.capabilities;
+        if missing != 0 {
+            return Err(NegotiationError::CapabilityNotNegotiated {
+                message_type: msg.message_type(),
+                missing,
+            });
+        }
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::messages::{
+        ClipboardDataMessage, ClipboardFormat, MouseMoveMessage, MouseScrollMessage,
+    };
+
+    fn clipboard() -> KvmMessage {
+        KvmMessage::ClipboardData(ClipboardDataMessage {
+            format: ClipboardFormat::Utf8Text,
+            data: b"hi".to_vec(),
+            has_more_fragments: false,
+        })
+    }
+
+    #[test]
+    fn test_negotiate_version_picks_highest_common_version() {
+        assert_eq!(negotiate_version(PROTOCOL_VERSION), Ok(PROTOCOL_VERSION));
+        // A newer peer is talked down to our version.
+        assert_eq!(
+            negotiate_version(PROTOCOL_VERSION + 1),
+            Ok(PROTOCOL_VERSION)
+        );
+        assert_eq!(negotiate_version(u8::MAX), Ok(PROTOCOL_VERSION));
+    }
+
+    #[test]
+    fn test_negotiate_version_rejects_peer_older_than_minimum() {
+        let too_old = MIN_PROTOCOL_VERSION - 1;
+
+        assert_eq!(
+            negotiate_version(too_old),
+            Err(NegotiationError::UnsupportedVersion { peer: too_old })
+        );
+        assert!(!is_supported_version(too_old));
+        assert!(!is_supported_version(PROTOCOL_VERSION + 1));
+        assert!(is_supported_version(PROTOCOL_VERSION));
+    }
+
+    #[test]
+    fn test_negotiated_capabilities_are_the_intersection() {
+        // Arrange
+        let local = capabilities::KEYBOARD_EMULATION
+            | capabilities::MOUSE_EMULATION
+            | capabilities::CLIPBOARD_SHARING;
+        let peer = capabilities::KEYBOARD_EMULATION
+            | capabilities::MOUSE_EMULATION
+            | capabilities::MULTI_MONITOR;
+
+        // Act
+        let terms = Negotiated::new(PROTOCOL_VERSION, local, peer);
+
+        // Assert
+        assert_eq!(
+            terms.capabilities(),
+            capabilities::KEYBOARD_EMULATION | capabilities::MOUSE_EMULATION
+        );
+        assert!(!terms.supports(capabilities::CLIPBOARD_SHARING));
+        assert!(!terms.supports(capabilities::MULTI_MONITOR));
+    }
+
+    #[test]
+    fn test_check_refuses_clipboard_without_clipboard_sharing() {
+        // Arrange
+        let terms = Negotiated::new(PROTOCOL_VERSION, u32::MAX, capabilities::BASELINE);
+
+        // Act
+        let result = terms.check(&clipboard());
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(NegotiationError::CapabilityNotNegotiated {
+                message_type: MessageType::ClipboardData,
+                missing: capabilities::CLIPBOARD_SHARING,
+            })
+        );
+        assert!(terms.check(&KvmMessage::Ping(1)).is_ok());
+    }
+
+    #[test]
+    fn test_check_refuses_image_clipboard_without_clipboard_image() {
+        // Arrange – clipboard shared, but text only
+        let terms = Negotiated::new(PROTOCOL_VERSION, u32::MAX, capabilities::CLIPBOARD_SHARING);
+        let image = KvmMessage::ClipboardData(ClipboardDataMessage {
+            format: ClipboardFormat::Image,
+            data: vec![0x89],
+            has_more_fragments: true,
+        });
+
+        // Act / Assert
+        assert_eq!(
+            terms.check(&image),
+            Err(NegotiationError::CapabilityNotNegotiated {
+                message_type: MessageType::ClipboardData,
+                missing: capabilities::CLIPBOARD_IMAGE,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_allows_clipboard_once_negotiated() {
+        let terms = Negotiated::new(PROTOCOL_VERSION, u32::MAX, capabilities::CLIPBOARD_SHARING);
+
+        assert!(terms.check(&clipboard()).is_ok());
+        assert!(terms
+            .check(&KvmMessage::MouseMove(MouseMoveMessage {
+                x: 0,
+                y: 0,
+                delta_x: 0,
+                delta_y: 0,
+            }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_input_batch_needs_every_capability_its_events_need() {
+        // Arrange
+        let keyboard_only =
+            Negotiated::new(PROTOCOL_VERSION, u32::MAX, capabilities::KEYBOARD_EMULATION);
+        let batch = KvmMessage::InputBatch(vec![InputEvent::MouseScroll(MouseScrollMessage {
+            delta_x: 0,
+            delta_y: -120,
+            x: 10,
+            y: 10,
+        })]);
+
+        // Act
+        let result = keyboard_only.check(&batch);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(NegotiationError::CapabilityNotNegotiated {
+                missing: capabilities::MOUSE_EMULATION,
+                ..
+            })
+        ));
+    }
+}
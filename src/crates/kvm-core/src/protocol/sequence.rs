@@ -40,6 +40,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 /// assert_eq!(counter.next(), 0);
 /// assert_eq!(counter.next(), 1);
 /// ```
+#[derive(Debug)]
 pub struct SequenceCounter {
     /// The underlying atomic integer.
     ///
@@ -0,0 +1,147 @@
+//! Integration tests for the canonical clipboard encodings.
+//!
+//! # Purpose
+//!
+//! A screenshot copied on one machine must paste as the same image on
+//! another, whatever produced the PNG.  These tests decode fixture PNGs
+//! written by a different encoder than the `png` crate behind
+//! `RgbaImage` (Python's `zlib`, which uses dynamic Huffman blocks, every
+//! filter type and split `IDAT` chunks) and compare
+//! them with the expected pixels stored next to them as raw `.rgba` files.
+//! They then push images and HTML through the whole path a transfer takes:
+//!
+//! ```text
+//! native form ─▶ canonical bytes ─▶ chunk ─▶ ClipboardReassembler ─▶ canonical bytes ─▶ native form
+//! ```
+//!
+//! # Fixtures
+//!
+//! | File             | Kind                                            |
+//! |------------------|-------------------------------------------------|
+//! | `screenshot_rgb` | 160×100 RGB, two `IDAT` chunks, a `tEXt` chunk  |
+//! | `logo_rgba`      | 32×32 RGBA with varying alpha                   |
+//! | `palette_trns`   | 16×16 4-bit palette with `tRNS` transparency    |
+//! | `grey16`         | 8×8 16-bit greyscale                            |
+//! | `mono1`          | 10×3 1-bit greyscale (rows not byte-aligned)    |
+
+use kvm_core::clipboard::{
+    chunk, ClipboardContent, ClipboardReassembler, HtmlClipboard, RgbaImage,
+    DEFAULT_MAX_CLIPBOARD_LEN, FRAGMENT_LEN,
+};
+use kvm_core::protocol::messages::{capabilities, ClipboardFormat};
+
+/// Loads `tests/fixtures/clipboard/<name>.png` and its expected pixels.
+fn fixture(name: &str) -> (Vec<u8>, Vec<u8>) {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/clipboard/");
+    let png = std::fs::read(format!("{dir}{name}.png")).expect("fixture PNG");
+    let rgba = std::fs::read(format!("{dir}{name}.rgba")).expect("fixture pixels");
+    (png, rgba)
+}
+
+/// Sends `content` through fragmentation and reassembly, like the wire.
+fn transfer(content: &ClipboardContent) -> ClipboardContent {
+    let mut reassembler = ClipboardReassembler::default();
+    let mut result = None;
+    for fragment in chunk(content, DEFAULT_MAX_CLIPBOARD_LEN).unwrap() {
+        result = reassembler.push(fragment).unwrap();
+    }
+    result.expect("last fragment completes the transfer")
+}
+
+#[test]
+fn test_fixture_pngs_decode_to_the_expected_pixels() {
+    for (name, width, height) in [
+        ("screenshot_rgb", 160, 100),
+        ("logo_rgba", 32, 32),
+        ("palette_trns", 16, 16),
+        ("grey16", 8, 8),
+        ("mono1", 10, 3),
+    ] {
+        // Arrange
+        let (png, expected) = fixture(name);
+
+        // Act
+        let image = RgbaImage::from_png(&png).unwrap_or_else(|e| panic!("{name}: {e}"));
+
+        // Assert
+        assert_eq!((image.width(), image.height()), (width, height), "{name}");
+        assert!(image.pixels() == expected, "{name}: pixels differ");
+    }
+}
+
+#[test]
+fn test_foreign_png_is_forwarded_byte_for_byte() {
+    // Arrange – an X11 application offered this PNG
+    let (png, expected) = fixture("screenshot_rgb");
+
+    // Act
+    let received = transfer(&ClipboardContent::png(png.clone()).unwrap());
+
+    // Assert – no re-encoding on the way, and it still decodes
+    assert_eq!(received.data, png);
+    assert_eq!(
+        RgbaImage::from_png(&received.data).unwrap().pixels(),
+        expected
+    );
+}
+
+#[test]
+fn test_windows_bitmap_round_trips_through_png_across_fragments() {
+    // Arrange – a noisy 300×300 bitmap, too big to fit in one fragment as PNG
+    let mut seed = 7u32;
+    let pixels: Vec<u8> = (0..300 * 300 * 4)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 24) as u8
+        })
+        .collect();
+    let source = RgbaImage::new(300, 300, pixels).unwrap();
+    let dib = source.to_dib();
+
+    // Act – Windows copy → wire → Windows paste
+    let sent = ClipboardContent::image(&RgbaImage::from_dib(&dib).unwrap());
+    let received = transfer(&sent);
+    let pasted = RgbaImage::from_png(&received.data).unwrap();
+
+    // Assert
+    assert!(sent.data.len() > FRAGMENT_LEN);
+    assert_eq!(received.format, ClipboardFormat::Image);
+    assert_eq!(pasted, source);
+    assert!(pasted.to_dib() == dib);
+}
+
+#[test]
+fn test_decoded_fixture_reencodes_losslessly() {
+    for name in ["logo_rgba", "palette_trns", "mono1"] {
+        let (png, _) = fixture(name);
+        let image = RgbaImage::from_png(&png).unwrap();
+
+        let reencoded = RgbaImage::from_png(&image.to_png()).unwrap();
+
+        assert_eq!(reencoded, image, "{name}");
+    }
+}
+
+#[test]
+fn test_html_reaches_rich_and_text_only_peers() {
+    // Arrange
+    let copied = HtmlClipboard::from_html("<p>Meeting at <b>10:00</b></p><p>Room&nbsp;4</p>");
+    let content = copied.to_content();
+    let rich = capabilities::CLIPBOARD_SHARING | capabilities::CLIPBOARD_HTML;
+
+    // Act
+    let to_rich = transfer(&content.for_peer(rich).unwrap().unwrap());
+    let to_plain = transfer(
+        &content
+            .for_peer(capabilities::CLIPBOARD_SHARING)
+            .unwrap()
+            .unwrap(),
+    );
+
+    // Assert
+    assert_eq!(HtmlClipboard::from_content(&to_rich).unwrap(), copied);
+    assert_eq!(
+        to_plain,
+        ClipboardContent::text("Meeting at 10:00\nRoom\u{a0}4")
+    );
+}
@@ -56,8 +56,9 @@ use kvm_core::{
             AnnounceMessage, AnnounceResponseMessage, ButtonEventType, ClipboardDataMessage,
             ClipboardFormat, DisconnectReason, ErrorMessage, HelloAckMessage, HelloMessage,
             InputEvent, KeyEventMessage, KeyEventType, ModifierFlags, MonitorInfo, MouseButton,
-            MouseButtonMessage, MouseMoveMessage, MouseScrollMessage, PairingRequestMessage,
-            PairingResponseMessage, PlatformId, ProtocolErrorCode, ScreenInfoMessage,
+            MouseButtonMessage, MouseMoveMessage, MouseScrollMessage, PairingConfirmMessage,
+            PairingRequestMessage, PairingResponseMessage, PlatformId, ProtocolErrorCode,
+            ScreenInfoMessage,
         },
         sequence::SequenceCounter,
     },
@@ -125,6 +126,7 @@ fn test_roundtrip_hello_message() {
         platform_id: PlatformId::Linux,
         client_name: "integration-test".to_string(),
         capabilities: 0b0011,
+        resume_token: None,
     });
 
     let decoded = roundtrip(original.clone());
@@ -137,11 +139,14 @@ fn test_roundtrip_hello_ack_message() {
     // HelloAck is the master's response to Hello.
     // `session_token` is 32 bytes; [0xAB; 32] fills every byte with 0xAB.
     // `reject_reason: 0` means "no rejection" (accepted = true).
+    // `capabilities` is the set both sides agreed on (here keyboard + mouse).
     let original = KvmMessage::HelloAck(HelloAckMessage {
         session_token: [0xAB; 32],
         server_version: 0x01,
         accepted: true,
         reject_reason: 0x00,
+        capabilities: 0x03,
+        resumed: false,
     });
 
     assert_eq!(original, roundtrip(original.clone()));
@@ -154,6 +159,7 @@ fn test_roundtrip_pairing_request_message() {
     let original = KvmMessage::PairingRequest(PairingRequestMessage {
         pairing_session_id: Uuid::new_v4(),
         expires_at_secs: 9999,
+        spake_message: [7; 32],
     });
 
     assert_eq!(original, roundtrip(original.clone()));
@@ -161,17 +167,29 @@ fn test_roundtrip_pairing_request_message() {
 
 #[test]
 fn test_roundtrip_pairing_response_message() {
-    // PairingResponse is the client's PIN submission.
-    // `pin_hash` is a hex-encoded hash of the PIN and session ID.
+    // PairingResponse is the client's answer to a PIN prompt: its SPAKE2
+    // message plus a MAC proving it derived the same key as the master.
     let original = KvmMessage::PairingResponse(PairingResponseMessage {
         pairing_session_id: Uuid::new_v4(),
-        pin_hash: "sha256:aabbccdd".to_string(),
+        spake_message: [0xAA; 32],
+        confirmation: [0xBB; 32],
         accepted: true,
     });
 
     assert_eq!(original, roundtrip(original.clone()));
 }
 
+#[test]
+fn test_roundtrip_pairing_confirm_message() {
+    // PairingConfirm is the master's half of key confirmation.
+    let original = KvmMessage::PairingConfirm(PairingConfirmMessage {
+        pairing_session_id: Uuid::new_v4(),
+        confirmation: [0xCC; 32],
+    });
+
+    assert_eq!(original, roundtrip(original.clone()));
+}
+
 #[test]
 fn test_roundtrip_screen_info_message() {
     // ScreenInfo is sent by the client after Hello to tell the master about
@@ -0,0 +1,386 @@
+//! Edge-crossing guards: when the cursor at a screen edge may cross it.
+//!
+//! Without guards the cursor crosses as soon as it reaches an edge with an
+//! adjacency.  That gets in the way of anyone who works near the edges —
+//! scrollbars, taskbars, window close buttons — so a [`CrossingPolicy`] can
+//! ask for more before the crossing happens:
+//!
+//! - **Dwell**: the cursor must stay at the edge for a while.
+//! - **Double bump**: the cursor must hit the edge, leave it, and hit it
+//!   again within a short window.
+//! - **Modifier**: a modifier key must be held.
+//! - **No dragging**: no crossing while a mouse button is held.
+//!
+//! All configured conditions must hold.  Time comes from the input events
+//! themselves (`time_ms`), so a dwell completes on the first movement after
+//! the dwell time, not on a timer.
+//!
+//! # Configuration
+//!
+//! The policy in `[master.edge_crossing]` applies to every edge; entries in
+//! `[[master.edge_crossing.edges]]` replace it for one edge of one screen
+//! (the master unless `client` is given):
+//!
+//! ```toml
+//! [master.edge_crossing]
+//! edge_threshold = 2
+//! block_while_dragging = true
+//!
+//! [[master.edge_crossing.edges]]
+//! edge = "right"
+//! dwell_ms = 300
+//! modifier = "ctrl"
+//! ```
+
+use kvm_core::domain::layout::{Edge, ScreenId, EDGE_THRESHOLD};
+use kvm_core::protocol::messages::ModifierFlags;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A modifier key, either side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequiredModifier {
+    Ctrl,
+    Shift,
+    Alt,
+    /// Windows / Command / Super.
+    Meta,
+}
+
+impl RequiredModifier {
+    /// Returns `true` if this modifier is held in `flags`.
+    pub fn is_held(self, flags: ModifierFlags) -> bool {
+        match self {
+            Self::Ctrl => flags.ctrl(),
+            Self::Shift => flags.shift(),
+            Self::Alt => flags.alt(),
+            Self::Meta => flags.meta(),
+        }
+    }
+}
+
+/// What it takes to cross an edge.  The default crosses immediately.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrossingPolicy {
+    /// How long, in milliseconds, the cursor must stay at the edge.
+    /// 0 disables the dwell.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub dwell_ms: u32,
+    /// The window, in milliseconds, in which the cursor must hit the edge
+    /// twice.  0 disables the double bump.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub double_bump_ms: u32,
+    /// A modifier that must be held while crossing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modifier: Option<RequiredModifier>,
+    /// Whether crossing is blocked while a mouse button is held.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub block_while_dragging: bool,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// A policy for one edge of one screen, replacing the global policy there.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgePolicyEntry {
+    /// The client whose edge this is; the master's when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<Uuid>,
+    /// The edge the cursor leaves through.
+    pub edge: Edge,
+    /// What it takes to cross that edge.
+    #[serde(flatten)]
+    pub policy: CrossingPolicy,
+}
+
+impl EdgePolicyEntry {
+    fn screen(&self) -> ScreenId {
+        self.client.map_or(ScreenId::Master, ScreenId::Client)
+    }
+}
+
+/// `[master.edge_crossing]`: the edge threshold and crossing policies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeCrossingConfig {
+    /// How close to an edge, in pixels, the cursor must come to cross it.
+    pub edge_threshold: u32,
+    /// The policy for every edge without its own entry.
+    #[serde(flatten)]
+    pub policy: CrossingPolicy,
+    /// Policies for individual edges.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<EdgePolicyEntry>,
+}
+
+impl Default for EdgeCrossingConfig {
+    fn default() -> Self {
+        Self {
+            edge_threshold: EDGE_THRESHOLD,
+            policy: CrossingPolicy::default(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl EdgeCrossingConfig {
+    /// The policy for leaving `screen` through `edge`.
+    pub fn policy_for(&self, screen: &ScreenId, edge: Edge) -> &CrossingPolicy {
+        self.edges
+            .iter()
+            .find(|e| e.edge == edge && e.screen() == *screen)
+            .map_or(&self.policy, |e| &e.policy)
+    }
+}
+
+/// The input state a crossing is judged by.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrossingInput {
+    /// Event time in milliseconds since an arbitrary epoch.
+    pub time_ms: u32,
+    /// Modifiers currently held.
+    pub modifiers: ModifierFlags,
+    /// Whether any mouse button is held.
+    pub button_held: bool,
+}
+
+/// The edge the cursor is currently at.
+#[derive(Debug, Clone)]
+struct EdgeVisit {
+    screen: ScreenId,
+    edge: Edge,
+    since_ms: u32,
+    /// Whether this visit is the second bump within the window.
+    second_bump: bool,
+}
+
+/// Applies an [`EdgeCrossingConfig`] to the cursor's visits to edges.
+#[derive(Debug, Clone, Default)]
+pub struct CrossingGuards {
+    config: EdgeCrossingConfig,
+    visit: Option<EdgeVisit>,
+    /// Edge and time of the last arrival at an edge.
+    last_bump: Option<(ScreenId, Edge, u32)>,
+}
+
+impl CrossingGuards {
+    /// Creates guards for `config`.
+    pub fn new(config: EdgeCrossingConfig) -> Self {
+        Self {
+            config,
+            visit: None,
+            last_bump: None,
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &EdgeCrossingConfig {
+        &self.config
+    }
+
+    /// Called when the cursor is at `edge` of `screen`, which has an
+    /// adjacency; returns `true` if it may cross now.
+    pub fn at_edge(&mut self, screen: &ScreenId, edge: Edge, input: CrossingInput) -> bool {
+        let now = input.time_ms;
+        let arrived = !matches!(
+            &self.visit,
+            Some(v) if v.screen == *screen && v.edge == edge
+        );
+        if arrived {
+            let policy = self.config.policy_for(screen, edge);
+            let second_bump = matches!(
+                &self.last_bump,
+                Some((s, e, t)) if s == screen && *e == edge
+                    && now.wrapping_sub(*t) <= policy.double_bump_ms
+            );
+            self.last_bump = Some((screen.clone(), edge, now));
+            self.visit = Some(EdgeVisit {
+                screen: screen.clone(),
+                edge,
+                since_ms: now,
+                second_bump,
+            });
+        }
+        let Some(visit) = &self.visit else {
+            return false;
+        };
+
+        let policy = self.config.policy_for(screen, edge);
+        let allowed = now.wrapping_sub(visit.since_ms) >= policy.dwell_ms
+            && (policy.double_bump_ms == 0 || visit.second_bump)
+            && policy.modifier.map_or(true, |m| m.is_held(input.modifiers))
+            && !(policy.block_while_dragging && input.button_held);
+        if allowed {
+            self.visit = None;
+            self.last_bump = None;
+        }
+        allowed
+    }
+
+    /// Called when the cursor is at no edge with an adjacency.
+    pub fn away_from_edges(&mut self) {
+        self.visit = None;
+    }
+
+    /// Forgets all visits, e.g. after focus moved without crossing an edge.
+    pub fn reset(&mut self) {
+        self.visit = None;
+        self.last_bump = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIGHT: Edge = Edge::Right;
+
+    fn input(time_ms: u32) -> CrossingInput {
+        CrossingInput {
+            time_ms,
+            ..CrossingInput::default()
+        }
+    }
+
+    fn guards(policy: CrossingPolicy) -> CrossingGuards {
+        CrossingGuards::new(EdgeCrossingConfig {
+            policy,
+            ..EdgeCrossingConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_default_policy_crosses_immediately() {
+        let mut g = guards(CrossingPolicy::default());
+        assert!(g.at_edge(&ScreenId::Master, RIGHT, input(0)));
+    }
+
+    #[test]
+    fn test_dwell_requires_staying_at_the_edge() {
+        // Arrange
+        let mut g = guards(CrossingPolicy {
+            dwell_ms: 300,
+            ..CrossingPolicy::default()
+        });
+
+        // Act
+        let arrive = g.at_edge(&ScreenId::Master, RIGHT, input(1000));
+        let early = g.at_edge(&ScreenId::Master, RIGHT, input(1200));
+        g.away_from_edges();
+        let back = g.at_edge(&ScreenId::Master, RIGHT, input(1400));
+        let waited = g.at_edge(&ScreenId::Master, RIGHT, input(1700));
+
+        // Assert – leaving the edge restarts the dwell
+        assert_eq!((arrive, early, back, waited), (false, false, false, true));
+    }
+
+    #[test]
+    fn test_double_bump_requires_two_arrivals_within_the_window() {
+        // Arrange
+        let mut g = guards(CrossingPolicy {
+            double_bump_ms: 500,
+            ..CrossingPolicy::default()
+        });
+        let bump = |g: &mut CrossingGuards, t: u32| {
+            let crossed = g.at_edge(&ScreenId::Master, RIGHT, input(t));
+            g.away_from_edges();
+            crossed
+        };
+
+        // Act
+        let first = bump(&mut g, 0);
+        let too_late = bump(&mut g, 800);
+        let in_time = bump(&mut g, 1100);
+
+        // Assert
+        assert_eq!((first, too_late, in_time), (false, false, true));
+    }
+
+    #[test]
+    fn test_modifier_and_drag_guards() {
+        // Arrange
+        let mut g = guards(CrossingPolicy {
+            modifier: Some(RequiredModifier::Ctrl),
+            block_while_dragging: true,
+            ..CrossingPolicy::default()
+        });
+        let ctrl = ModifierFlags(ModifierFlags::RIGHT_CTRL);
+
+        // Act
+        let plain = g.at_edge(&ScreenId::Master, RIGHT, input(0));
+        let dragging = g.at_edge(
+            &ScreenId::Master,
+            RIGHT,
+            CrossingInput {
+                modifiers: ctrl,
+                button_held: true,
+                ..input(10)
+            },
+        );
+        let with_ctrl = g.at_edge(
+            &ScreenId::Master,
+            RIGHT,
+            CrossingInput {
+                modifiers: ctrl,
+                ..input(20)
+            },
+        );
+
+        // Assert
+        assert_eq!((plain, dragging, with_ctrl), (false, false, true));
+    }
+
+    #[test]
+    fn test_edge_entry_replaces_global_policy() {
+        // Arrange
+        let client = Uuid::new_v4();
+        let mut g = CrossingGuards::new(EdgeCrossingConfig {
+            policy: CrossingPolicy {
+                dwell_ms: 1000,
+                ..CrossingPolicy::default()
+            },
+            edges: vec![EdgePolicyEntry {
+                client: Some(client),
+                edge: Edge::Left,
+                policy: CrossingPolicy::default(),
+            }],
+            ..EdgeCrossingConfig::default()
+        });
+
+        // Act / Assert
+        assert!(g.at_edge(&ScreenId::Client(client), Edge::Left, input(0)));
+        assert!(!g.at_edge(&ScreenId::Master, Edge::Left, input(0)));
+    }
+
+    #[test]
+    fn test_config_deserializes_from_toml() {
+        // Arrange
+        let toml_str = r#"
+edge_threshold = 4
+block_while_dragging = true
+
+[[edges]]
+edge = "right"
+dwell_ms = 300
+modifier = "ctrl"
+"#;
+
+        // Act
+        let cfg: EdgeCrossingConfig = toml::from_str(toml_str).unwrap();
+
+        // Assert
+        assert_eq!(cfg.edge_threshold, 4);
+        assert!(cfg.policy.block_while_dragging);
+        let right = cfg.policy_for(&ScreenId::Master, Edge::Right);
+        assert_eq!(right.dwell_ms, 300);
+        assert_eq!(right.modifier, Some(RequiredModifier::Ctrl));
+        assert!(
+            !right.block_while_dragging,
+            "entries replace the global policy"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Hotkey bindings: which key sequences trigger which routing actions.
+//!
+//! The hotkey text itself (`"Ctrl+Alt+Right"`, `"ScrollLock ScrollLock"`) is
+//! parsed by [`kvm_core::keymap::hotkey`]; this module pairs each parsed
+//! [`Hotkey`] with a [`HotkeyAction`] and feeds key events to all of them.
+//!
+//! # Configuration
+//!
+//! `[master] disable_hotkey` is always bound to
+//! [`HotkeyAction::ToggleSharing`].  Further bindings come from
+//! `[[master.hotkeys]]` tables:
+//!
+//! ```toml
+//! [[master.hotkeys]]
+//! keys = "Ctrl+Alt+1"
+//! action = { jump_to_client = 1 }
+//!
+//! [[master.hotkeys]]
+//! keys = "Ctrl+Alt+Home"
+//! action = "return_to_master"
+//! ```
+//!
+//! # Swallowed keys
+//!
+//! The key-down that completes a hotkey is consumed by the router and never
+//! reaches a client, and neither do that key's autorepeats and key-up.  Earlier chords of
+//! a sequence (the first tap of a double-tap) cannot be known to be part of a
+//! hotkey when they happen, so they are forwarded as usual.
+
+use std::num::NonZeroUsize;
+
+use kvm_core::keymap::hotkey::{Hotkey, HotkeyMatcher};
+use kvm_core::keymap::HidKeyCode;
+use kvm_core::protocol::messages::ModifierFlags;
+use serde::{Deserialize, Serialize};
+
+/// What a hotkey does when it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Turns input sharing off (focus returns to the master) or back on.
+    ToggleSharing,
+    /// Moves focus to the N-th client in layout order, counting from 1.
+    JumpToClient(NonZeroUsize),
+    /// Moves focus to the next screen: the master, then the clients in
+    /// layout order, wrapping around.
+    CycleNext,
+    /// Moves focus to the previous screen in the same order.
+    CyclePrevious,
+    /// Moves focus back to the master screen.
+    ReturnToMaster,
+    /// Stops (or resumes) edge transitions, keeping the cursor on the
+    /// current screen.
+    LockCursor,
+}
+
+/// A set of hotkeys and the actions they trigger.
+#[derive(Debug, Clone, Default)]
+pub struct HotkeyBindings {
+    bindings: Vec<(HotkeyMatcher, HotkeyAction)>,
+    /// The key that completed the last hotkey, until its key-up is seen.
+    swallowed: Option<HidKeyCode>,
+}
+
+impl HotkeyBindings {
+    /// Creates an empty set; no key does anything special.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `hotkey` to `action`.
+    ///
+    /// When two hotkeys complete on the same key-down, the one bound first
+    /// wins.
+    pub fn bind(&mut self, hotkey: Hotkey, action: HotkeyAction) {
+        self.bindings.push((HotkeyMatcher::new(hotkey), action));
+    }
+
+    /// Iterates over the bound hotkeys and their actions.
+    pub fn iter(&self) -> impl Iterator<Item = (&Hotkey, HotkeyAction)> {
+        self.bindings
+            .iter()
+            .map(|(m, action)| (m.hotkey(), *action))
+    }
+
+    /// Handles a key-down and returns the action of the hotkey it completes.
+    ///
+    /// `modifiers` is the modifier state including this key.  When an action
+    /// is returned the key-down must not be forwarded.
+    pub fn key_down(
+        &mut self,
+        key: HidKeyCode,
+        modifiers: ModifierFlags,
+        time_ms: u32,
+    ) -> Option<HotkeyAction> {
+        let mut fired = None;
+        // Every matcher sees the event so its sequence state stays correct.
+        for (matcher, action) in &mut self.bindings {
+            if matcher.key_down(key, modifiers, time_ms) && fired.is_none() {
+                fired = Some(*action);
+            }
+        }
+        if fired.is_some() {
+            for (matcher, _) in &mut self.bindings {
+                matcher.reset();
+            }
+            self.swallowed = Some(key);
+        }
+        fired
+    }
+
+    /// `true` while `key` is the held key that fired the last hotkey; its
+    /// autorepeat key-downs must not be forwarded.
+    pub fn is_trigger_held(&self, key: HidKeyCode) -> bool {
+        self.swallowed == Some(key)
+    }
+
+    /// Handles a key-up; returns `true` if it belongs to the key-down that
+    /// fired a hotkey and must not be forwarded either.
+    pub fn key_up(&mut self, key: HidKeyCode) -> bool {
+        for (matcher, _) in &mut self.bindings {
+            matcher.key_up(key);
+        }
+        if self.swallowed == Some(key) {
+            self.swallowed = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: ModifierFlags = ModifierFlags(0);
+
+    fn bindings(entries: &[(&str, HotkeyAction)]) -> HotkeyBindings {
+        let mut b = HotkeyBindings::new();
+        for (keys, action) in entries {
+            b.bind(keys.parse().unwrap(), *action);
+        }
+        b
+    }
+
+    #[test]
+    fn test_completed_hotkey_returns_its_action_and_swallows_key_up() {
+        // Arrange
+        let mut b = bindings(&[("F13", HotkeyAction::LockCursor)]);
+
+        // Act
+        let action = b.key_down(HidKeyCode::F13, NONE, 0);
+
+        // Assert
+        assert_eq!(action, Some(HotkeyAction::LockCursor));
+        assert!(b.is_trigger_held(HidKeyCode::F13));
+        assert_eq!(b.key_down(HidKeyCode::F13, NONE, 30), None, "autorepeat");
+        assert!(b.key_up(HidKeyCode::F13), "trigger key-up is swallowed");
+        assert!(!b.is_trigger_held(HidKeyCode::F13));
+        assert!(!b.key_up(HidKeyCode::F13), "only once");
+    }
+
+    #[test]
+    fn test_unbound_keys_pass_through() {
+        // Arrange
+        let mut b = bindings(&[("F13", HotkeyAction::LockCursor)]);
+
+        // Act / Assert
+        assert_eq!(b.key_down(HidKeyCode::KeyA, NONE, 0), None);
+        assert!(!b.key_up(HidKeyCode::KeyA));
+    }
+
+    #[test]
+    fn test_each_binding_tracks_its_own_sequence() {
+        // Arrange
+        let ctrl_alt = ModifierFlags(ModifierFlags::LEFT_CTRL | ModifierFlags::LEFT_ALT);
+        let second = NonZeroUsize::new(2).unwrap();
+        let mut b = bindings(&[
+            ("ScrollLock ScrollLock", HotkeyAction::ToggleSharing),
+            ("Ctrl+Alt+2", HotkeyAction::JumpToClient(second)),
+        ]);
+
+        // Act
+        let tap = b.key_down(HidKeyCode::ScrollLock, NONE, 0);
+        b.key_up(HidKeyCode::ScrollLock);
+        let jump = b.key_down(HidKeyCode::Digit2, ctrl_alt, 100);
+
+        // Assert
+        assert_eq!(tap, None);
+        assert_eq!(jump, Some(HotkeyAction::JumpToClient(second)));
+    }
+
+    #[test]
+    fn test_action_deserializes_from_toml() {
+        // Arrange
+        #[derive(Deserialize)]
+        struct Entry {
+            action: HotkeyAction,
+        }
+
+        // Act
+        let jump: Entry = toml::from_str("action = { jump_to_client = 3 }").unwrap();
+        let back: Entry = toml::from_str("action = \"return_to_master\"").unwrap();
+        let zero = toml::from_str::<Entry>("action = { jump_to_client = 0 }");
+
+        // Assert
+        assert_eq!(
+            jump.action,
+            HotkeyAction::JumpToClient(NonZeroUsize::new(3).unwrap())
+        );
+        assert_eq!(back.action, HotkeyAction::ReturnToMaster);
+        assert!(zero.is_err(), "clients are counted from 1");
+    }
+}
@@ -111,6 +111,13 @@ impl ClientRegistry {
             client.latency_ms = latency_ms;
         }
     }
+
+    /// Updates the input throughput shown for a client.
+    pub fn update_events_per_second(&mut self, id: ClientId, events_per_second: u32) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.events_per_second = events_per_second;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -196,4 +203,14 @@ mod tests {
         registry.update_latency(id, 3.7);
         assert!((registry.get(id).unwrap().latency_ms - 3.7).abs() < f32::EPSILON);
     }
+
+    #[test]
+    fn test_update_events_per_second_changes_throughput() {
+        let mut registry = ClientRegistry::new();
+        let client = make_client("test");
+        let id = client.id;
+        registry.upsert(client);
+        registry.update_events_per_second(id, 120);
+        assert_eq!(registry.get(id).unwrap().events_per_second, 120);
+    }
 }
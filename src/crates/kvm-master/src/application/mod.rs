@@ -19,12 +19,20 @@
 //!   process them locally or forward them to a client.  This is the most
 //!   critical use case — it runs on every keystroke and mouse movement.
 //!
+//! - **`edge_crossing`** – Decides when the cursor at a screen edge may
+//!   cross it (dwell time, double bump, held modifier, no dragging).
+//!
+//! - **`hotkeys`**       – Binds parsed hotkeys to routing actions (toggle
+//!   sharing, jump to a client, return to the master, lock the cursor).
+//!
 //! - **`manage_clients`** – Maintains the in-memory registry of all known
 //!   clients and their connection states.
 //!
 //! - **`update_layout`** – Validates and applies layout changes (screen
 //!   positions and adjacencies) coming from the drag-and-drop UI editor.
 
+pub mod edge_crossing;
+pub mod hotkeys;
 pub mod manage_clients;
 pub mod route_input;
 pub mod update_layout;
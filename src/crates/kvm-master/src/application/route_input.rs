@@ -18,26 +18,85 @@
 //! # Event flow
 //!
 //! ```text
-//! Windows hook (WH_KEYBOARD_LL / WH_MOUSE_LL)
-//!   └─ RawInputEvent sent over mpsc channel
+//! InputSource (Windows hooks, Linux evdev, ...)
+//!   └─ RawInputEvent (keys as HID usages) sent over mpsc channel
 //!        └─ RouteInputUseCase::handle_event()
 //!             ├─ Update modifier key state
-//!             ├─ Check for hotkey (ScrollLock: toggle sharing on/off)
+//!             ├─ Check hotkeys (toggle sharing, jump, lock cursor)
 //!             ├─ Check for edge transition (cursor near screen boundary)
 //!             │    └─ apply_transition():
 //!             │         ├─ Update active_target
 //!             │         ├─ Teleport physical cursor (CursorController)
-//!             │         └─ Send entry position to new client (InputTransmitter)
+//!             │         ├─ Send entry position to new client (InputTransmitter)
+//!             │         └─ Sync the clipboard to new client (ClipboardProvider)
 //!             └─ Forward event to active client (InputTransmitter)
 //! ```
-
+//!
+//! # Switching without the mouse
+//!
+//! Besides edge transitions, focus can move directly:
+//! [`switch_to`](RouteInputUseCase::switch_to) a given screen,
+//! [`cycle_next`](RouteInputUseCase::cycle_next) /
+//! [`cycle_previous`](RouteInputUseCase::cycle_previous) through the master
+//! followed by the clients in layout order, or
+//! [`return_to_master`](RouteInputUseCase::return_to_master).  Keys still
+//! held on the client being left are released there first, so nothing stays
+//! stuck down.  The cursor lands where it was when that screen was last left,
+//! or at its centre the first time.
+//!
+//! These run from hotkeys and from [`FocusRequest`]s sent by the UI bridge.
+//!
+//! # Hotkeys
+//!
+//! Key-downs are offered to the [`HotkeyBindings`] before anything else.
+//! The toggle-sharing hotkey works at any time; the other actions are ignored
+//! while sharing is off.  A locked cursor never crosses an edge.
+//!
+//! # Edge-crossing guards
+//!
+//! An edge transition only happens once the [`CrossingGuards`] allow it:
+//! depending on the configured [`EdgeCrossingConfig`] the cursor may have
+//! to rest at the edge, bump it twice, come with a modifier held, or arrive
+//! with no mouse button down.  The short debounce after each transition
+//! applies on top of these.
+//!
+//! # Pointer speed
+//!
+//! When the layout works in physical units
+//! ([`VirtualLayout::set_physical_units`]), motion on a client is not taken
+//! from the captured position directly: each movement is scaled by
+//! [`VirtualLayout::pointer_speed`] and added to the cursor's position, so
+//! the pointer covers the same physical distance on a high-DPI laptop as on
+//! the master.
+//!
+//! # Exclusive capture
+//!
+//! With an [`InputSource`] attached (see
+//! [`RouteInputUseCase::with_input_source`]), the source is told through
+//! [`InputSource::set_exclusive`] whenever focus moves between the master and
+//! a client, so input meant for a client does not also reach the local
+//! desktop.  Moving from one client to another changes nothing.
+//!
+//! # Clipboard sync
+//!
+//! With a [`ClipboardProvider`] attached (see
+//! [`RouteInputUseCase::with_clipboard`]), every transition onto a client
+//! sends it the master's clipboard, so whatever was copied before the cursor
+//! crossed can be pasted on the other side.  Content a client already
+//! received is not sent to it again.  Clipboard failures are logged and never
+//! interrupt input routing.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use kvm_core::{
+    clipboard::{ClipboardContent, ClipboardProvider},
     domain::layout::{EdgeTransition, ScreenId, VirtualLayout},
-    keymap::{hid::HidKeyCode, KeyMapper},
+    keymap::hid::HidKeyCode,
     protocol::messages::{
         ButtonEventType, KeyEventMessage, KeyEventType, ModifierFlags,
         MouseButton as ProtoMouseButton, MouseButtonMessage, MouseMoveMessage, MouseScrollMessage,
@@ -45,8 +104,13 @@ use kvm_core::{
     ClientId,
 };
 use thiserror::Error;
+use tracing::{debug, warn};
 
-use crate::infrastructure::input_capture::{MouseButton as RawMouseButton, RawInputEvent};
+use crate::application::edge_crossing::{CrossingGuards, CrossingInput, EdgeCrossingConfig};
+use crate::application::hotkeys::{HotkeyAction, HotkeyBindings};
+use crate::infrastructure::input_capture::{
+    InputSource, MouseButton as RawMouseButton, RawInputEvent,
+};
 
 /// Debounce duration for edge transitions to prevent oscillation.
 ///
@@ -75,13 +139,34 @@ pub enum RouteError {
     /// A routing decision was attempted but no layout has been configured yet.
     #[error("no layout configured")]
     NoLayout,
+    /// A switch named a screen that is not in the layout.
+    #[error("screen {0:?} is not in the layout")]
+    UnknownScreen(ScreenId),
+    /// A switch to a client was requested while input sharing is off.
+    #[error("input sharing is disabled")]
+    SharingDisabled,
+}
+
+/// A request to move input focus, sent by the UI bridge to the task that
+/// owns the [`RouteInputUseCase`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusRequest {
+    /// See [`RouteInputUseCase::switch_to`].
+    SwitchTo(ScreenId),
+    /// See [`RouteInputUseCase::cycle_next`].
+    CycleNext,
+    /// See [`RouteInputUseCase::cycle_previous`].
+    CyclePrevious,
+    /// See [`RouteInputUseCase::return_to_master`].
+    ReturnToMaster,
 }
 
 /// Trait for sending translated input events to a remote client.
 ///
-/// Infrastructure implementations use DTLS (secure datagram transport) to
-/// deliver events over the network; test implementations record all calls so
-/// tests can assert on which events were sent.
+/// Infrastructure implementations deliver events over the network (the
+/// encrypted UDP input channel or the TLS control channel); test
+/// implementations record all calls so tests can assert on which events were
+/// sent.
 ///
 /// # Design note
 ///
@@ -118,6 +203,15 @@ pub trait InputTransmitter: Send + Sync {
         client_id: ClientId,
         event: MouseScrollMessage,
     ) -> Result<(), String>;
+
+    /// Sends a complete clipboard entry to the specified client.
+    ///
+    /// Implementations split it into `ClipboardData` fragments as needed.
+    async fn send_clipboard(
+        &self,
+        client_id: ClientId,
+        content: ClipboardContent,
+    ) -> Result<(), String>;
 }
 
 /// Trait for controlling the physical master cursor position.
@@ -148,8 +242,8 @@ pub enum ActiveTarget {
 
 /// The current modifier key state maintained across key-down/up events.
 ///
-/// Windows low-level hooks receive individual key-down and key-up events for
-/// modifier keys (Shift, Ctrl, Alt, Meta) just like regular keys.  We track
+/// Input sources report individual key-down and key-up events for modifier
+/// keys (Shift, Ctrl, Alt, Meta) just like regular keys.  We track
 /// each modifier's state here so we can include an accurate `ModifierFlags`
 /// bitmask with every forwarded key event.
 ///
@@ -200,27 +294,25 @@ impl ModifierState {
         ModifierFlags(flags)
     }
 
-    /// Updates the state of a single modifier key based on a Windows VK code.
+    /// Updates the state of a single modifier key.
     ///
-    /// `vk` is the Windows Virtual Key code from the low-level hook struct.
-    /// `is_down` is `true` for key-down events, `false` for key-up.
-    ///
-    /// The Windows VK codes for modifier keys:
-    /// - 0xA2 = VK_LCONTROL, 0xA3 = VK_RCONTROL
-    /// - 0xA0 = VK_LSHIFT,   0xA1 = VK_RSHIFT
-    /// - 0xA4 = VK_LMENU (Alt), 0xA5 = VK_RMENU
-    /// - 0x5B = VK_LWIN,     0x5C = VK_RWIN
-    fn update(&mut self, vk: u8, is_down: bool) {
-        match vk {
-            0xA2 => self.left_ctrl = is_down,
-            0xA3 => self.right_ctrl = is_down,
-            0xA0 => self.left_shift = is_down,
-            0xA1 => self.right_shift = is_down,
-            0xA4 => self.left_alt = is_down,
-            0xA5 => self.right_alt = is_down,
-            0x5B => self.left_meta = is_down,
-            0x5C => self.right_meta = is_down,
-            _ => {} // Not a modifier key; ignore.
+    /// `key` is the HID usage reported by the input source; `is_down` is
+    /// `true` for key-down events, `false` for key-up.  Keys that are not
+    /// modifiers are ignored.
+    fn update(&mut self, key: HidKeyCode, is_down: bool) {
+        if !key.is_modifier() {
+            return;
+        }
+        match key {
+            HidKeyCode::ControlLeft => self.left_ctrl = is_down,
+            HidKeyCode::ControlRight => self.right_ctrl = is_down,
+            HidKeyCode::ShiftLeft => self.left_shift = is_down,
+            HidKeyCode::ShiftRight => self.right_shift = is_down,
+            HidKeyCode::AltLeft => self.left_alt = is_down,
+            HidKeyCode::AltRight => self.right_alt = is_down,
+            HidKeyCode::MetaLeft => self.left_meta = is_down,
+            HidKeyCode::MetaRight => self.right_meta = is_down,
+            _ => {}
         }
     }
 }
@@ -234,11 +326,30 @@ pub struct RouteInputUseCase {
     active_target: ActiveTarget,
     cursor_pos: (i32, i32),
     sharing_enabled: bool,
-    hotkey_vk: u8,
+    hotkeys: HotkeyBindings,
+    /// While `true`, edge transitions are disabled.
+    cursor_locked: bool,
     modifiers: ModifierState,
+    /// Keys forwarded as pressed to the active client, with their scan codes.
+    held_keys: HashMap<HidKeyCode, u16>,
+    /// Local cursor position on each screen when focus last left it.
+    last_positions: HashMap<ScreenId, (i32, i32)>,
+    /// The position last reported by the capture (or set by a teleport).
+    raw_pos: Option<(i32, i32)>,
+    /// Fractions of a pixel of scaled motion not yet applied to `cursor_pos`.
+    motion_carry: (f64, f64),
+    /// Decides when the cursor at an edge may cross it.
+    crossing: CrossingGuards,
+    /// Mouse buttons currently held down.
+    buttons_held: Vec<RawMouseButton>,
     last_transition: Option<Instant>,
     transmitter: Arc<dyn InputTransmitter>,
     cursor_controller: Arc<dyn CursorController>,
+    /// Told to keep input from the local desktop while a client is active.
+    input_source: Option<Arc<dyn InputSource + Sync>>,
+    clipboard: Option<Arc<dyn ClipboardProvider>>,
+    /// Hash of the clipboard content last sent to each client.
+    clipboard_sent: HashMap<ClientId, u64>,
     /// Sequence counter for outbound messages (reserved for future use).
     _sequence: u64,
 }
@@ -250,33 +361,70 @@ impl RouteInputUseCase {
         master_height: u32,
         transmitter: Arc<dyn InputTransmitter>,
         cursor_controller: Arc<dyn CursorController>,
-        hotkey_vk: u8,
+        hotkeys: HotkeyBindings,
     ) -> Self {
         Self {
             layout: VirtualLayout::new(master_width, master_height),
             active_target: ActiveTarget::Master,
             cursor_pos: (0, 0),
             sharing_enabled: true,
-            hotkey_vk,
+            hotkeys,
+            cursor_locked: false,
             modifiers: ModifierState::default(),
+            held_keys: HashMap::new(),
+            last_positions: HashMap::new(),
+            raw_pos: None,
+            motion_carry: (0.0, 0.0),
+            crossing: CrossingGuards::default(),
+            buttons_held: Vec::new(),
             last_transition: None,
             transmitter,
             cursor_controller,
+            input_source: None,
+            clipboard: None,
+            clipboard_sent: HashMap::new(),
             _sequence: 0,
         }
     }
 
+    /// Sends the master's clipboard to each client as it receives focus.
+    pub fn with_clipboard(mut self, clipboard: Arc<dyn ClipboardProvider>) -> Self {
+        self.clipboard = Some(clipboard);
+        self
+    }
+
+    /// Calls [`InputSource::set_exclusive`] on `source` whenever focus moves
+    /// between the master and a client: `true` when a client becomes active,
+    /// `false` when focus returns to the master.
+    pub fn with_input_source(mut self, source: Arc<dyn InputSource + Sync>) -> Self {
+        self.input_source = Some(source);
+        self
+    }
+
+    /// Guards edge crossings and sets the edge threshold as configured.
+    pub fn with_edge_crossing(mut self, config: EdgeCrossingConfig) -> Self {
+        self.layout.set_edge_threshold(config.edge_threshold);
+        self.crossing = CrossingGuards::new(config);
+        self
+    }
+
     /// Replaces the layout with an updated configuration.
     ///
     /// If the active client is no longer in the new layout, routing falls back to master.
-    pub fn update_layout(&mut self, layout: VirtualLayout) {
+    /// The configured edge threshold is applied to the new layout.
+    pub fn update_layout(&mut self, mut layout: VirtualLayout) {
+        layout.set_edge_threshold(self.crossing.config().edge_threshold);
         // If the active client was removed, fall back to master
         if let ActiveTarget::Client(cid) = &self.active_target {
             let still_exists = layout.clients().any(|c| c.client_id == *cid);
             if !still_exists {
-                self.active_target = ActiveTarget::Master;
+                self.set_active_target(ActiveTarget::Master);
             }
         }
+        self.clipboard_sent
+            .retain(|cid, _| layout.clients().any(|c| c.client_id == *cid));
+        self.last_positions
+            .retain(|screen, _| layout.get_region(screen).is_some());
         self.layout = layout;
     }
 
@@ -290,11 +438,29 @@ impl RouteInputUseCase {
         self.sharing_enabled
     }
 
+    /// Returns whether the cursor is locked to the current screen.
+    pub fn is_cursor_locked(&self) -> bool {
+        self.cursor_locked
+    }
+
     /// Enables or disables input sharing.
     pub fn set_sharing_enabled(&mut self, enabled: bool) {
         self.sharing_enabled = enabled;
         if !enabled {
-            self.active_target = ActiveTarget::Master;
+            self.set_active_target(ActiveTarget::Master);
+        }
+    }
+
+    /// Makes `target` the active target, telling the input source when focus
+    /// moves between the master and a client.
+    fn set_active_target(&mut self, target: ActiveTarget) {
+        let was_client = self.active_target != ActiveTarget::Master;
+        let is_client = target != ActiveTarget::Master;
+        self.active_target = target;
+        if was_client != is_client {
+            if let Some(source) = &self.input_source {
+                source.set_exclusive(is_client);
+            }
         }
     }
 
@@ -308,19 +474,19 @@ impl RouteInputUseCase {
     pub async fn handle_event(&mut self, event: RawInputEvent) -> Result<(), RouteError> {
         match event {
             RawInputEvent::KeyDown {
-                vk_code, scan_code, ..
+                key,
+                scan_code,
+                time_ms,
             } => {
-                self.modifiers.update(vk_code, true);
-                self.handle_key_down(vk_code, scan_code).await?;
+                self.modifiers.update(key, true);
+                self.handle_key_down(key, scan_code, time_ms).await?;
             }
-            RawInputEvent::KeyUp {
-                vk_code, scan_code, ..
-            } => {
-                self.modifiers.update(vk_code, false);
-                self.handle_key_up(vk_code, scan_code).await?;
+            RawInputEvent::KeyUp { key, scan_code, .. } => {
+                self.modifiers.update(key, false);
+                self.handle_key_up(key, scan_code).await?;
             }
-            RawInputEvent::MouseMove { x, y, .. } => {
-                self.handle_mouse_move(x, y).await?;
+            RawInputEvent::MouseMove { x, y, time_ms } => {
+                self.handle_mouse_move(x, y, time_ms).await?;
             }
             RawInputEvent::MouseButtonDown { button, x, y, .. } => {
                 self.handle_mouse_button(button, true, x, y).await?;
@@ -340,14 +506,18 @@ impl RouteInputUseCase {
 
     // ── Private event handlers ────────────────────────────────────────────────
 
-    async fn handle_key_down(&mut self, vk_code: u8, scan_code: u16) -> Result<(), RouteError> {
-        // Check for hotkey (disable/enable sharing)
-        if vk_code == self.hotkey_vk {
-            self.sharing_enabled = !self.sharing_enabled;
-            if !self.sharing_enabled {
-                self.active_target = ActiveTarget::Master;
-            }
-            return Ok(());
+    async fn handle_key_down(
+        &mut self,
+        key: HidKeyCode,
+        scan_code: Option<u16>,
+        time_ms: u32,
+    ) -> Result<(), RouteError> {
+        if self.hotkeys.is_trigger_held(key) {
+            return Ok(()); // autorepeat of a hotkey's last key
+        }
+        let modifiers = self.modifiers.to_flags();
+        if let Some(action) = self.hotkeys.key_down(key, modifiers, time_ms) {
+            return self.run_hotkey_action(action).await;
         }
 
         if !self.sharing_enabled {
@@ -355,12 +525,13 @@ impl RouteInputUseCase {
         }
 
         if let ActiveTarget::Client(cid) = self.active_target.clone() {
-            let hid = KeyMapper::windows_vk_to_hid(vk_code);
-            if hid == HidKeyCode::Unknown {
+            if key == HidKeyCode::Unknown {
                 return Ok(());
             }
+            let scan_code = scan_code.unwrap_or(0);
+            self.held_keys.insert(key, scan_code);
             let event = KeyEventMessage {
-                key_code: hid,
+                key_code: key,
                 scan_code,
                 event_type: KeyEventType::KeyDown,
                 modifiers: self.modifiers.to_flags(),
@@ -373,18 +544,22 @@ impl RouteInputUseCase {
         Ok(())
     }
 
-    async fn handle_key_up(&mut self, vk_code: u8, scan_code: u16) -> Result<(), RouteError> {
-        if !self.sharing_enabled {
+    async fn handle_key_up(
+        &mut self,
+        key: HidKeyCode,
+        scan_code: Option<u16>,
+    ) -> Result<(), RouteError> {
+        if self.hotkeys.key_up(key) || !self.sharing_enabled {
             return Ok(());
         }
         if let ActiveTarget::Client(cid) = self.active_target.clone() {
-            let hid = KeyMapper::windows_vk_to_hid(vk_code);
-            if hid == HidKeyCode::Unknown {
+            if key == HidKeyCode::Unknown {
                 return Ok(());
             }
+            self.held_keys.remove(&key);
             let event = KeyEventMessage {
-                key_code: hid,
-                scan_code,
+                key_code: key,
+                scan_code: scan_code.unwrap_or(0),
                 event_type: KeyEventType::KeyUp,
                 modifiers: self.modifiers.to_flags(),
             };
@@ -396,30 +571,29 @@ impl RouteInputUseCase {
         Ok(())
     }
 
-    async fn handle_mouse_move(&mut self, x: i32, y: i32) -> Result<(), RouteError> {
-        self.cursor_pos = (x, y);
+    async fn handle_mouse_move(&mut self, x: i32, y: i32, time_ms: u32) -> Result<(), RouteError> {
+        self.cursor_pos = self.scale_motion(x, y);
+        let (x, y) = self.cursor_pos;
 
         if !self.sharing_enabled {
             return Ok(());
         }
 
-        let current_screen = match &self.active_target {
-            ActiveTarget::Master => ScreenId::Master,
-            ActiveTarget::Client(cid) => ScreenId::Client(*cid),
-        };
-
-        // Compute local position for edge detection
-        let (local_x, local_y) = match &current_screen {
-            ScreenId::Master => (x, y),
-            ScreenId::Client(cid) => {
-                if let Some(client) = self.layout.clients().find(|c| c.client_id == *cid) {
-                    (x - client.region.virtual_x, y - client.region.virtual_y)
-                } else {
-                    // Client disappeared from layout; fall back to master
-                    self.active_target = ActiveTarget::Master;
-                    return Ok(());
-                }
-            }
+        let current_screen = self.current_screen();
+
+        // Compute local position for edge detection, keeping the cursor on
+        // the screen's monitors, out of the dead space between them.
+        let local = self
+            .layout
+            .clamp_to_screen(&current_screen, x, y)
+            .and_then(|(cx, cy)| {
+                self.cursor_pos = (cx, cy);
+                self.layout.to_local(&current_screen, cx, cy)
+            });
+        let Some((local_x, local_y)) = local else {
+            // Client disappeared from layout; fall back to master
+            self.set_active_target(ActiveTarget::Master);
+            return Ok(());
         };
 
         // Check for edge transition (with debounce)
@@ -428,12 +602,25 @@ impl RouteInputUseCase {
             .map(|t| t.elapsed() >= TRANSITION_DEBOUNCE)
             .unwrap_or(true);
 
-        if can_transition {
-            if let Some(transition) =
-                self.layout
-                    .check_edge_transition(&current_screen, local_x, local_y)
+        if can_transition && !self.cursor_locked {
+            match self
+                .layout
+                .check_edge_transition(&current_screen, local_x, local_y)
             {
-                return self.apply_transition(transition).await;
+                Some(transition) => {
+                    let input = CrossingInput {
+                        time_ms,
+                        modifiers: self.modifiers.to_flags(),
+                        button_held: !self.buttons_held.is_empty(),
+                    };
+                    if self
+                        .crossing
+                        .at_edge(&current_screen, transition.from_edge, input)
+                    {
+                        return self.apply_transition(transition).await;
+                    }
+                }
+                None => self.crossing.away_from_edges(),
             }
         }
 
@@ -453,14 +640,50 @@ impl RouteInputUseCase {
         Ok(())
     }
 
+    /// Turns a position reported by the capture into the cursor's new
+    /// virtual position.
+    ///
+    /// On a client in a layout with physical units, the pointer's motion is
+    /// scaled by [`VirtualLayout::pointer_speed`] and added to the current
+    /// position, so the cursor covers the same physical distance as on the
+    /// master.  Otherwise the reported position is used as it is.
+    fn scale_motion(&mut self, x: i32, y: i32) -> (i32, i32) {
+        let previous = self.raw_pos.replace((x, y));
+        let screen = self.current_screen();
+        let Some((prev_x, prev_y)) = previous else {
+            return (x, y);
+        };
+        if !self.layout.physical_units() || screen == ScreenId::Master {
+            self.motion_carry = (0.0, 0.0);
+            return (x, y);
+        }
+        let (cx, cy) = self.cursor_pos;
+        let speed = self.layout.pointer_speed(&screen, cx, cy);
+        let dx = (x - prev_x) as f64 * speed + self.motion_carry.0;
+        let dy = (y - prev_y) as f64 * speed + self.motion_carry.1;
+        self.motion_carry = (dx.fract(), dy.fract());
+        (cx + dx.trunc() as i32, cy + dy.trunc() as i32)
+    }
+
     async fn apply_transition(&mut self, transition: EdgeTransition) -> Result<(), RouteError> {
         self.last_transition = Some(Instant::now());
+        self.release_held_keys().await?;
+        self.remember_position();
+        if let Some(entry) = self.layout.to_virtual(
+            &transition.to_screen,
+            transition.entry_x,
+            transition.entry_y,
+        ) {
+            self.cursor_pos = entry;
+        }
+        self.raw_pos = Some((transition.master_teleport_x, transition.master_teleport_y));
+        self.motion_carry = (0.0, 0.0);
 
         // Update the active target
-        self.active_target = match &transition.to_screen {
+        self.set_active_target(match &transition.to_screen {
             ScreenId::Master => ActiveTarget::Master,
             ScreenId::Client(cid) => ActiveTarget::Client(*cid),
-        };
+        });
 
         // Teleport the physical cursor to prevent it from straying off the master screen
         self.cursor_controller
@@ -478,10 +701,270 @@ impl RouteInputUseCase {
                 .send_mouse_move(cid, event)
                 .await
                 .map_err(RouteError::Transmit)?;
+            self.sync_clipboard(cid).await;
+        }
+        Ok(())
+    }
+
+    async fn run_hotkey_action(&mut self, action: HotkeyAction) -> Result<(), RouteError> {
+        debug!("hotkey action {action:?}");
+        if action == HotkeyAction::ToggleSharing {
+            let enabled = !self.sharing_enabled;
+            if !enabled {
+                self.release_held_keys().await?;
+            }
+            self.set_sharing_enabled(enabled);
+            return Ok(());
+        }
+        if !self.sharing_enabled {
+            return Ok(());
+        }
+        match action {
+            HotkeyAction::ToggleSharing => unreachable!("handled above"),
+            HotkeyAction::JumpToClient(n) => {
+                let target = self
+                    .layout
+                    .clients_in_order()
+                    .get(n.get() - 1)
+                    .map(|c| ScreenId::Client(c.client_id));
+                match target {
+                    Some(screen) => self.switch_to(screen).await?,
+                    None => debug!("no client {n} in the layout"),
+                }
+            }
+            HotkeyAction::CycleNext => self.cycle_next().await?,
+            HotkeyAction::CyclePrevious => self.cycle_previous().await?,
+            HotkeyAction::ReturnToMaster => self.return_to_master().await?,
+            HotkeyAction::LockCursor => self.cursor_locked = !self.cursor_locked,
+        }
+        Ok(())
+    }
+
+    fn current_screen(&self) -> ScreenId {
+        match &self.active_target {
+            ActiveTarget::Master => ScreenId::Master,
+            ActiveTarget::Client(cid) => ScreenId::Client(*cid),
+        }
+    }
+
+    /// Records the cursor's local position on the screen being left.
+    fn remember_position(&mut self) {
+        let screen = self.current_screen();
+        let (x, y) = self.cursor_pos;
+        let local = self
+            .layout
+            .clamp_to_screen(&screen, x, y)
+            .and_then(|(cx, cy)| self.layout.to_local(&screen, cx, cy));
+        if let Some(local) = local {
+            self.last_positions.insert(screen, local);
+        }
+    }
+
+    /// Sends a key-up to the active client for every key still held there.
+    async fn release_held_keys(&mut self) -> Result<(), RouteError> {
+        let ActiveTarget::Client(cid) = self.active_target.clone() else {
+            self.held_keys.clear();
+            return Ok(());
+        };
+        // Ordinary keys first, then the modifiers they were pressed with.
+        let mut held: Vec<_> = self.held_keys.drain().collect();
+        held.sort_by_key(|(key, _)| (key.is_modifier(), *key as u16));
+        for (key, scan_code) in held {
+            let event = KeyEventMessage {
+                key_code: key,
+                scan_code,
+                event_type: KeyEventType::KeyUp,
+                modifiers: ModifierFlags::default(),
+            };
+            self.transmitter
+                .send_key_event(cid, event)
+                .await
+                .map_err(RouteError::Transmit)?;
+        }
+        Ok(())
+    }
+
+    // ── Direct switching ──────────────────────────────────────────────────────
+
+    /// Moves input focus straight to `screen`, without an edge crossing.
+    ///
+    /// Keys held on the client being left are released there.  The cursor is
+    /// placed where it was when `screen` last lost focus, or at the centre of
+    /// its primary monitor.  While a client has focus the physical cursor is
+    /// parked at the centre of the master, like after an edge crossing.
+    /// Switching to the screen that already has focus does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RouteError::UnknownScreen`] if `screen` is not in the layout,
+    /// [`RouteError::SharingDisabled`] for a client while sharing is off, and
+    /// [`RouteError::Transmit`] if a message cannot be delivered.
+    pub async fn switch_to(&mut self, screen: ScreenId) -> Result<(), RouteError> {
+        let Some(centre) = self.layout.screen_centre(&screen) else {
+            return Err(RouteError::UnknownScreen(screen));
+        };
+        if screen != ScreenId::Master && !self.sharing_enabled {
+            return Err(RouteError::SharingDisabled);
+        }
+        if screen == self.current_screen() {
+            return Ok(());
+        }
+
+        self.release_held_keys().await?;
+        self.remember_position();
+        self.crossing.reset();
+
+        let (x, y) = self
+            .last_positions
+            .get(&screen)
+            .and_then(|&(lx, ly)| self.layout.to_virtual(&screen, lx, ly))
+            .unwrap_or(centre);
+        // The remembered spot may have been removed by a layout change.
+        let virtual_pos = self.layout.clamp_to_screen(&screen, x, y).unwrap_or(centre);
+        let (local_x, local_y) = self
+            .layout
+            .to_local(&screen, virtual_pos.0, virtual_pos.1)
+            .unwrap_or_default();
+
+        // The physical cursor stays on the master: at the new position when
+        // switching to it, otherwise parked at its centre, clear of any edge.
+        let park = match &screen {
+            ScreenId::Master => (local_x, local_y),
+            ScreenId::Client(_) => self.master_centre_local(),
+        };
+
+        self.set_active_target(match &screen {
+            ScreenId::Master => ActiveTarget::Master,
+            ScreenId::Client(cid) => ActiveTarget::Client(*cid),
+        });
+        self.last_transition = Some(Instant::now());
+        self.cursor_pos = virtual_pos;
+        self.raw_pos = Some(park);
+        self.motion_carry = (0.0, 0.0);
+        self.cursor_controller.teleport_cursor(park.0, park.1);
+
+        if let ActiveTarget::Client(cid) = self.active_target.clone() {
+            let event = MouseMoveMessage {
+                x: local_x,
+                y: local_y,
+                delta_x: 0,
+                delta_y: 0,
+            };
+            self.transmitter
+                .send_mouse_move(cid, event)
+                .await
+                .map_err(RouteError::Transmit)?;
+            self.sync_clipboard(cid).await;
         }
         Ok(())
     }
 
+    /// Returns the centre of the master's primary monitor in master-local
+    /// coordinates.
+    fn master_centre_local(&self) -> (i32, i32) {
+        self.layout
+            .screen_centre(&ScreenId::Master)
+            .and_then(|(x, y)| self.layout.to_local(&ScreenId::Master, x, y))
+            .unwrap_or_default()
+    }
+
+    /// Moves focus to the next screen: the master, then each client in
+    /// layout order, wrapping around.
+    ///
+    /// # Errors
+    ///
+    /// As for [`switch_to`](Self::switch_to).
+    pub async fn cycle_next(&mut self) -> Result<(), RouteError> {
+        self.cycle(true).await
+    }
+
+    /// Moves focus to the previous screen in the order of
+    /// [`cycle_next`](Self::cycle_next).
+    ///
+    /// # Errors
+    ///
+    /// As for [`switch_to`](Self::switch_to).
+    pub async fn cycle_previous(&mut self) -> Result<(), RouteError> {
+        self.cycle(false).await
+    }
+
+    /// Moves focus back to the master screen.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RouteError::Transmit`] if releasing held keys on the client
+    /// fails.
+    pub async fn return_to_master(&mut self) -> Result<(), RouteError> {
+        self.switch_to(ScreenId::Master).await
+    }
+
+    /// Carries out a [`FocusRequest`] from the UI bridge.
+    ///
+    /// # Errors
+    ///
+    /// As for [`switch_to`](Self::switch_to).
+    pub async fn handle_focus_request(&mut self, request: FocusRequest) -> Result<(), RouteError> {
+        match request {
+            FocusRequest::SwitchTo(screen) => self.switch_to(screen).await,
+            FocusRequest::CycleNext => self.cycle_next().await,
+            FocusRequest::CyclePrevious => self.cycle_previous().await,
+            FocusRequest::ReturnToMaster => self.return_to_master().await,
+        }
+    }
+
+    async fn cycle(&mut self, forward: bool) -> Result<(), RouteError> {
+        if !self.sharing_enabled {
+            return Err(RouteError::SharingDisabled);
+        }
+        let order: Vec<ScreenId> = std::iter::once(ScreenId::Master)
+            .chain(
+                self.layout
+                    .clients_in_order()
+                    .into_iter()
+                    .map(|c| ScreenId::Client(c.client_id)),
+            )
+            .collect();
+        let current = self.current_screen();
+        let index = order.iter().position(|s| *s == current).unwrap_or(0);
+        let next = if forward {
+            (index + 1) % order.len()
+        } else {
+            (index + order.len() - 1) % order.len()
+        };
+        self.switch_to(order[next].clone()).await
+    }
+
+    /// Sends the master's clipboard to `cid` unless it already has it.
+    async fn sync_clipboard(&mut self, cid: ClientId) {
+        let Some(clipboard) = &self.clipboard else {
+            return;
+        };
+        let content = match clipboard.read() {
+            Ok(Some(content)) => content,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("clipboard not synced to {cid}: {e}");
+                return;
+            }
+        };
+
+        let mut hasher = DefaultHasher::new();
+        (content.format as u8).hash(&mut hasher);
+        content.data.hash(&mut hasher);
+        let digest = hasher.finish();
+        if self.clipboard_sent.get(&cid) == Some(&digest) {
+            debug!("client {cid} already has the current clipboard");
+            return;
+        }
+
+        match self.transmitter.send_clipboard(cid, content).await {
+            Ok(()) => {
+                self.clipboard_sent.insert(cid, digest);
+            }
+            Err(e) => warn!("clipboard not synced to {cid}: {e}"),
+        }
+    }
+
     async fn handle_mouse_button(
         &mut self,
         button: RawMouseButton,
@@ -489,6 +972,10 @@ impl RouteInputUseCase {
         x: i32,
         y: i32,
     ) -> Result<(), RouteError> {
+        self.buttons_held.retain(|&b| b != button);
+        if pressed {
+            self.buttons_held.push(button);
+        }
         if !self.sharing_enabled {
             return Ok(());
         }
@@ -558,7 +1045,12 @@ impl RouteInputUseCase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use kvm_core::domain::layout::{Adjacency, ClientScreen, Edge, ScreenId, ScreenRegion};
+    use crate::application::edge_crossing::{CrossingPolicy, RequiredModifier};
+    use crate::infrastructure::input_capture::mock::MockInputSource;
+    use kvm_core::clipboard::mock::MockClipboardProvider;
+    use kvm_core::domain::layout::{
+        Adjacency, ClientScreen, Edge, Monitor, ScreenId, ScreenRegion,
+    };
     use std::sync::Mutex;
     use uuid::Uuid;
 
@@ -570,6 +1062,7 @@ mod tests {
         mouse_moves: Mutex<Vec<(ClientId, MouseMoveMessage)>>,
         mouse_buttons: Mutex<Vec<(ClientId, MouseButtonMessage)>>,
         mouse_scrolls: Mutex<Vec<(ClientId, MouseScrollMessage)>>,
+        clipboards: Mutex<Vec<(ClientId, ClipboardContent)>>,
         should_fail: bool,
     }
 
@@ -622,6 +1115,18 @@ mod tests {
             self.mouse_scrolls.lock().unwrap().push((client_id, event));
             Ok(())
         }
+
+        async fn send_clipboard(
+            &self,
+            client_id: ClientId,
+            content: ClipboardContent,
+        ) -> Result<(), String> {
+            if self.should_fail {
+                return Err("injected failure".to_string());
+            }
+            self.clipboards.lock().unwrap().push((client_id, content));
+            Ok(())
+        }
     }
 
     #[derive(Default)]
@@ -641,6 +1146,51 @@ mod tests {
         }
     }
 
+    /// ScrollLock toggles sharing; Ctrl+Alt+1 jumps to the first client,
+    /// Ctrl+Alt+Home returns to the master and Ctrl+Alt+L locks the cursor.
+    fn test_hotkeys() -> HotkeyBindings {
+        let mut hotkeys = HotkeyBindings::new();
+        for (keys, action) in [
+            ("ScrollLock", HotkeyAction::ToggleSharing),
+            (
+                "Ctrl+Alt+1",
+                HotkeyAction::JumpToClient(std::num::NonZeroUsize::MIN),
+            ),
+            ("Ctrl+Alt+Home", HotkeyAction::ReturnToMaster),
+            ("Ctrl+Alt+L", HotkeyAction::LockCursor),
+        ] {
+            hotkeys.bind(keys.parse().unwrap(), action);
+        }
+        hotkeys
+    }
+
+    /// Presses and releases `key` with Left Ctrl and Left Alt held.
+    async fn press_ctrl_alt(uc: &mut RouteInputUseCase, key: HidKeyCode) {
+        for (key, down) in [
+            (HidKeyCode::ControlLeft, true),
+            (HidKeyCode::AltLeft, true),
+            (key, true),
+            (key, false),
+            (HidKeyCode::AltLeft, false),
+            (HidKeyCode::ControlLeft, false),
+        ] {
+            let event = if down {
+                RawInputEvent::KeyDown {
+                    key,
+                    scan_code: None,
+                    time_ms: 0,
+                }
+            } else {
+                RawInputEvent::KeyUp {
+                    key,
+                    scan_code: None,
+                    time_ms: 0,
+                }
+            };
+            uc.handle_event(event).await.unwrap();
+        }
+    }
+
     fn make_use_case_with_client(
         cid: Uuid,
     ) -> (
@@ -655,21 +1205,21 @@ mod tests {
             1080,
             Arc::clone(&transmitter) as Arc<dyn InputTransmitter>,
             Arc::clone(&cursor) as Arc<dyn CursorController>,
-            0x91, // ScrollLock as hotkey VK
+            test_hotkeys(),
         );
 
         // Add a client to the right
         uc.layout
-            .add_client(ClientScreen {
-                client_id: cid,
-                region: ScreenRegion {
+            .add_client(ClientScreen::new(
+                cid,
+                "test-client".to_string(),
+                ScreenRegion {
                     virtual_x: 1920,
                     virtual_y: 0,
                     width: 1920,
                     height: 1080,
                 },
-                name: "test-client".to_string(),
-            })
+            ))
             .unwrap();
         uc.layout
             .set_adjacency(Adjacency {
@@ -677,6 +1227,7 @@ mod tests {
                 from_edge: Edge::Right,
                 to_screen: ScreenId::Client(cid),
                 to_edge: Edge::Left,
+                segment: None,
             })
             .unwrap();
 
@@ -694,10 +1245,9 @@ mod tests {
 
         // Act
         uc.handle_event(RawInputEvent::KeyDown {
-            vk_code: 0x41, // VK_A
-            scan_code: 0x1E,
+            key: HidKeyCode::KeyA,
+            scan_code: Some(0x1E),
             time_ms: 0,
-            is_extended: false,
         })
         .await
         .unwrap();
@@ -719,10 +1269,9 @@ mod tests {
 
         // Act
         uc.handle_event(RawInputEvent::KeyDown {
-            vk_code: 0x41,
-            scan_code: 0x1E,
+            key: HidKeyCode::KeyA,
+            scan_code: Some(0x1E),
             time_ms: 0,
-            is_extended: false,
         })
         .await
         .unwrap();
@@ -739,12 +1288,11 @@ mod tests {
         uc.active_target = ActiveTarget::Client(cid);
         assert!(uc.is_sharing_enabled());
 
-        // Act – press ScrollLock (0x91)
+        // Act – press ScrollLock
         uc.handle_event(RawInputEvent::KeyDown {
-            vk_code: 0x91,
-            scan_code: 0,
+            key: HidKeyCode::ScrollLock,
+            scan_code: Some(0x46),
             time_ms: 0,
-            is_extended: false,
         })
         .await
         .unwrap();
@@ -763,10 +1311,9 @@ mod tests {
 
         // Act
         uc.handle_event(RawInputEvent::KeyDown {
-            vk_code: 0x91,
-            scan_code: 0,
+            key: HidKeyCode::ScrollLock,
+            scan_code: Some(0x46),
             time_ms: 0,
-            is_extended: false,
         })
         .await
         .unwrap();
@@ -775,6 +1322,95 @@ mod tests {
         assert!(uc.is_sharing_enabled());
     }
 
+    #[tokio::test]
+    async fn test_jump_hotkey_moves_focus_to_client_centre() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, tx, cursor) = make_use_case_with_client(cid);
+
+        // Act
+        press_ctrl_alt(&mut uc, HidKeyCode::Digit1).await;
+
+        // Assert
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+        // The physical cursor is parked at the master's centre, not the client's.
+        assert_eq!(*cursor.teleport_calls.lock().unwrap(), [(960, 540)]);
+        let moves = tx.mouse_moves.lock().unwrap();
+        assert_eq!((moves[0].1.x, moves[0].1.y), (960, 540));
+        // Only the modifier releases reach the client; the chord key never does.
+        let keys: Vec<_> = tx
+            .key_events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, e)| e.key_code)
+            .collect();
+        assert_eq!(keys, [HidKeyCode::AltLeft, HidKeyCode::ControlLeft]);
+    }
+
+    #[tokio::test]
+    async fn test_return_hotkey_moves_focus_to_master() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, _, cursor) = make_use_case_with_client(cid);
+        uc.active_target = ActiveTarget::Client(cid);
+
+        // Act
+        press_ctrl_alt(&mut uc, HidKeyCode::Home).await;
+
+        // Assert
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+        assert_eq!(*cursor.teleport_calls.lock().unwrap(), [(960, 540)]);
+    }
+
+    #[tokio::test]
+    async fn test_jump_hotkeys_ignored_while_sharing_disabled() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, _, _) = make_use_case_with_client(cid);
+        uc.set_sharing_enabled(false);
+
+        // Act
+        press_ctrl_alt(&mut uc, HidKeyCode::Digit1).await;
+
+        // Assert
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+    }
+
+    #[tokio::test]
+    async fn test_locked_cursor_does_not_cross_edges() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, _, _) = make_use_case_with_client(cid);
+        press_ctrl_alt(&mut uc, HidKeyCode::KeyL).await;
+        assert!(uc.is_cursor_locked());
+
+        // Act – push against the master's right edge
+        uc.handle_event(RawInputEvent::MouseMove {
+            x: 1919,
+            y: 540,
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+
+        // Act – unlock and push again
+        press_ctrl_alt(&mut uc, HidKeyCode::KeyL).await;
+        uc.handle_event(RawInputEvent::MouseMove {
+            x: 1919,
+            y: 540,
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
     #[tokio::test]
     async fn test_key_event_not_routed_when_sharing_disabled() {
         // Arrange
@@ -785,10 +1421,9 @@ mod tests {
 
         // Act
         uc.handle_event(RawInputEvent::KeyDown {
-            vk_code: 0x41,
-            scan_code: 0x1E,
+            key: HidKeyCode::KeyA,
+            scan_code: Some(0x1E),
             time_ms: 0,
-            is_extended: false,
         })
         .await
         .unwrap();
@@ -797,6 +1432,37 @@ mod tests {
         assert!(tx.key_events.lock().unwrap().is_empty());
     }
 
+    #[tokio::test]
+    async fn test_held_modifier_is_reported_with_key_event() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, tx, _) = make_use_case_with_client(cid);
+        uc.active_target = ActiveTarget::Client(cid);
+
+        // Act – Right Shift, then a key without a native scan code
+        uc.handle_event(RawInputEvent::KeyDown {
+            key: HidKeyCode::ShiftRight,
+            scan_code: Some(0x36),
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+        uc.handle_event(RawInputEvent::KeyDown {
+            key: HidKeyCode::KanaMode,
+            scan_code: None,
+            time_ms: 1,
+        })
+        .await
+        .unwrap();
+
+        // Assert
+        let events = tx.key_events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].1.key_code, HidKeyCode::KanaMode);
+        assert_eq!(events[1].1.scan_code, 0);
+        assert_eq!(events[1].1.modifiers.0, ModifierFlags::RIGHT_SHIFT);
+    }
+
     // ── Mouse routing ─────────────────────────────────────────────────────────
 
     #[tokio::test]
@@ -868,6 +1534,521 @@ mod tests {
         assert_eq!(teleports.len(), 1);
     }
 
+    /// Moves the cursor onto the master's right edge, crossing to the client.
+    async fn cross_to_client(uc: &mut RouteInputUseCase) {
+        uc.active_target = ActiveTarget::Master;
+        uc.last_transition = None;
+        uc.handle_event(RawInputEvent::MouseMove {
+            x: 1919,
+            y: 540,
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+    }
+
+    // ── Edge-crossing guards ──────────────────────────────────────────────────
+
+    fn make_guarded_use_case(
+        policy: CrossingPolicy,
+    ) -> (RouteInputUseCase, Uuid, Arc<RecordingCursorController>) {
+        let cid = Uuid::new_v4();
+        let (uc, _tx, cursor) = make_use_case_with_client(cid);
+        let config = EdgeCrossingConfig {
+            policy,
+            ..EdgeCrossingConfig::default()
+        };
+        (uc.with_edge_crossing(config), cid, cursor)
+    }
+
+    async fn move_to(uc: &mut RouteInputUseCase, x: i32, time_ms: u32) {
+        uc.handle_event(RawInputEvent::MouseMove { x, y: 540, time_ms })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dwell_guard_crosses_after_resting_at_edge() {
+        // Arrange
+        let (mut uc, cid, _) = make_guarded_use_case(CrossingPolicy {
+            dwell_ms: 300,
+            ..CrossingPolicy::default()
+        });
+
+        // Act / Assert
+        move_to(&mut uc, 1919, 1000).await;
+        move_to(&mut uc, 1919, 1200).await;
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+        move_to(&mut uc, 1919, 1300).await;
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
+    #[tokio::test]
+    async fn test_double_bump_guard_needs_a_second_push() {
+        // Arrange
+        let (mut uc, cid, cursor) = make_guarded_use_case(CrossingPolicy {
+            double_bump_ms: 500,
+            ..CrossingPolicy::default()
+        });
+
+        // Act
+        move_to(&mut uc, 1919, 0).await;
+        let after_first = uc.get_active_target().clone();
+        move_to(&mut uc, 1900, 100).await;
+        move_to(&mut uc, 1919, 200).await;
+
+        // Assert
+        assert_eq!(after_first, ActiveTarget::Master);
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+        assert_eq!(cursor.teleport_calls.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_modifier_guard_crosses_only_with_modifier_held() {
+        // Arrange
+        let (mut uc, cid, _) = make_guarded_use_case(CrossingPolicy {
+            modifier: Some(RequiredModifier::Shift),
+            ..CrossingPolicy::default()
+        });
+
+        // Act / Assert
+        move_to(&mut uc, 1919, 0).await;
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+        key(&mut uc, HidKeyCode::ShiftLeft, true).await;
+        move_to(&mut uc, 1919, 10).await;
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
+    #[tokio::test]
+    async fn test_drag_guard_blocks_crossing_while_button_held() {
+        // Arrange
+        let (mut uc, cid, _) = make_guarded_use_case(CrossingPolicy {
+            block_while_dragging: true,
+            ..CrossingPolicy::default()
+        });
+        let button = |down: bool| {
+            let (button, x, y, time_ms) = (RawMouseButton::Left, 1000, 540, 0);
+            if down {
+                RawInputEvent::MouseButtonDown {
+                    button,
+                    x,
+                    y,
+                    time_ms,
+                }
+            } else {
+                RawInputEvent::MouseButtonUp {
+                    button,
+                    x,
+                    y,
+                    time_ms,
+                }
+            }
+        };
+
+        // Act / Assert
+        uc.handle_event(button(true)).await.unwrap();
+        move_to(&mut uc, 1919, 0).await;
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+        uc.handle_event(button(false)).await.unwrap();
+        move_to(&mut uc, 1919, 10).await;
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
+    #[tokio::test]
+    async fn test_configured_edge_threshold_survives_layout_updates() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (uc, _tx, _) = make_use_case_with_client(cid);
+        let mut uc = uc.with_edge_crossing(EdgeCrossingConfig {
+            edge_threshold: 10,
+            ..EdgeCrossingConfig::default()
+        });
+        let layout = std::mem::replace(&mut uc.layout, VirtualLayout::new(1, 1));
+
+        // Act
+        uc.update_layout(layout);
+        move_to(&mut uc, 1912, 0).await;
+
+        // Assert
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
+    #[tokio::test]
+    async fn test_transition_to_client_sends_master_clipboard() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (uc, tx, _) = make_use_case_with_client(cid);
+        let clipboard = Arc::new(MockClipboardProvider::with_content(ClipboardContent::text(
+            "copied on master",
+        )));
+        let mut uc = uc.with_clipboard(clipboard);
+
+        // Act
+        cross_to_client(&mut uc).await;
+
+        // Assert
+        let sent = tx.clipboards.lock().unwrap();
+        assert_eq!(
+            *sent,
+            vec![(cid, ClipboardContent::text("copied on master"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_only_resent_after_it_changes() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (uc, tx, _) = make_use_case_with_client(cid);
+        let clipboard = Arc::new(MockClipboardProvider::with_content(ClipboardContent::text(
+            "first",
+        )));
+        let mut uc = uc.with_clipboard(Arc::clone(&clipboard) as Arc<dyn ClipboardProvider>);
+
+        // Act – cross twice with the same content, then once after a copy
+        cross_to_client(&mut uc).await;
+        cross_to_client(&mut uc).await;
+        clipboard.set(ClipboardContent::text("second"));
+        cross_to_client(&mut uc).await;
+
+        // Assert
+        let sent: Vec<_> = tx
+            .clipboards
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, c)| c.clone())
+            .collect();
+        assert_eq!(
+            sent,
+            vec![
+                ClipboardContent::text("first"),
+                ClipboardContent::text("second"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_clipboard_does_not_block_transition() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (uc, tx, _) = make_use_case_with_client(cid);
+        let clipboard = MockClipboardProvider {
+            should_fail: true,
+            ..MockClipboardProvider::default()
+        };
+        let mut uc = uc.with_clipboard(Arc::new(clipboard));
+
+        // Act
+        cross_to_client(&mut uc).await;
+
+        // Assert – focus moved and the entry position was still sent
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+        assert_eq!(tx.mouse_moves.lock().unwrap().len(), 1);
+        assert!(tx.clipboards.lock().unwrap().is_empty());
+    }
+
+    // ── Direct switching ──────────────────────────────────────────────────────
+
+    /// Adds a second client to the right of the first one.
+    fn add_second_client(uc: &mut RouteInputUseCase) -> Uuid {
+        let cid = Uuid::new_v4();
+        uc.layout
+            .add_client(ClientScreen::new(
+                cid,
+                "second-client".to_string(),
+                ScreenRegion {
+                    virtual_x: 3840,
+                    virtual_y: 0,
+                    width: 1280,
+                    height: 1024,
+                },
+            ))
+            .unwrap();
+        cid
+    }
+
+    async fn key(uc: &mut RouteInputUseCase, key: HidKeyCode, down: bool) {
+        let event = if down {
+            RawInputEvent::KeyDown {
+                key,
+                scan_code: Some(7),
+                time_ms: 0,
+            }
+        } else {
+            RawInputEvent::KeyUp {
+                key,
+                scan_code: Some(7),
+                time_ms: 0,
+            }
+        };
+        uc.handle_event(event).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_releases_keys_held_on_old_client() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, tx, _) = make_use_case_with_client(cid);
+        uc.active_target = ActiveTarget::Client(cid);
+        key(&mut uc, HidKeyCode::ShiftLeft, true).await;
+        key(&mut uc, HidKeyCode::KeyA, true).await;
+        key(&mut uc, HidKeyCode::KeyB, true).await;
+        key(&mut uc, HidKeyCode::KeyB, false).await;
+        tx.key_events.lock().unwrap().clear();
+
+        // Act
+        uc.switch_to(ScreenId::Master).await.unwrap();
+
+        // Assert – ordinary keys first, then the modifier
+        let released: Vec<_> = tx
+            .key_events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, e)| (*id, e.key_code, e.event_type, e.scan_code))
+            .collect();
+        assert_eq!(
+            released,
+            [
+                (cid, HidKeyCode::KeyA, KeyEventType::KeyUp, 7),
+                (cid, HidKeyCode::ShiftLeft, KeyEventType::KeyUp, 7),
+            ]
+        );
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_restores_last_known_position() {
+        // Arrange – the cursor was at local (100, 200) when the client was left
+        let cid = Uuid::new_v4();
+        let (mut uc, tx, cursor) = make_use_case_with_client(cid);
+        uc.switch_to(ScreenId::Client(cid)).await.unwrap();
+        uc.handle_event(RawInputEvent::MouseMove {
+            x: 1920 + 100,
+            y: 200,
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+        uc.switch_to(ScreenId::Master).await.unwrap();
+        tx.mouse_moves.lock().unwrap().clear();
+
+        // Act
+        uc.switch_to(ScreenId::Client(cid)).await.unwrap();
+
+        // Assert
+        let moves = tx.mouse_moves.lock().unwrap();
+        assert_eq!((moves[0].1.x, moves[0].1.y), (100, 200));
+        assert_eq!(
+            cursor.teleport_calls.lock().unwrap().last(),
+            Some(&(960, 540))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_motion_after_switch_to_client_continues_from_parked_cursor() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, tx, _cursor) = make_use_case_with_client(cid);
+        uc.layout.set_physical_units(true);
+        uc.switch_to(ScreenId::Client(cid)).await.unwrap();
+        tx.mouse_moves.lock().unwrap().clear();
+
+        // Act – the capture's next position is relative to the parked cursor.
+        uc.handle_event(RawInputEvent::MouseMove {
+            x: 970,
+            y: 545,
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+
+        // Assert
+        let moves = tx.mouse_moves.lock().unwrap();
+        assert_eq!((moves[0].1.x, moves[0].1.y), (970, 545));
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
+    #[tokio::test]
+    async fn test_input_source_made_exclusive_only_while_a_client_is_active() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (uc, _, _) = make_use_case_with_client(cid);
+        let source = Arc::new(MockInputSource::new());
+        let mut uc = uc.with_input_source(Arc::clone(&source) as Arc<dyn InputSource + Sync>);
+        let second = add_second_client(&mut uc);
+
+        // Act
+        cross_to_client(&mut uc).await;
+        uc.switch_to(ScreenId::Client(second)).await.unwrap();
+        uc.return_to_master().await.unwrap();
+        uc.switch_to(ScreenId::Client(cid)).await.unwrap();
+        uc.set_sharing_enabled(false);
+
+        // Assert – client-to-client switches leave the grab alone.
+        assert_eq!(source.exclusive_calls(), [true, false, true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_rejects_unknown_screen_and_disabled_sharing() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, _, _) = make_use_case_with_client(cid);
+
+        // Act
+        let unknown = uc.switch_to(ScreenId::Client(Uuid::new_v4())).await;
+        uc.set_sharing_enabled(false);
+        let disabled = uc.switch_to(ScreenId::Client(cid)).await;
+
+        // Assert
+        assert!(matches!(unknown, Err(RouteError::UnknownScreen(_))));
+        assert!(matches!(disabled, Err(RouteError::SharingDisabled)));
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+    }
+
+    #[tokio::test]
+    async fn test_cycle_walks_layout_order_and_wraps() {
+        // Arrange
+        let first = Uuid::new_v4();
+        let (mut uc, _, _) = make_use_case_with_client(first);
+        let second = add_second_client(&mut uc);
+
+        // Act / Assert – forwards
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            uc.cycle_next().await.unwrap();
+            seen.push(uc.get_active_target().clone());
+        }
+        assert_eq!(
+            seen,
+            [
+                ActiveTarget::Client(first),
+                ActiveTarget::Client(second),
+                ActiveTarget::Master,
+            ]
+        );
+
+        // Act / Assert – backwards from the master wraps to the last client
+        uc.cycle_previous().await.unwrap();
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Client(second));
+    }
+
+    #[tokio::test]
+    async fn test_focus_request_is_dispatched() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (mut uc, _, _) = make_use_case_with_client(cid);
+
+        // Act
+        uc.handle_focus_request(FocusRequest::SwitchTo(ScreenId::Client(cid)))
+            .await
+            .unwrap();
+        let on_client = uc.get_active_target().clone();
+        uc.handle_focus_request(FocusRequest::ReturnToMaster)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(on_client, ActiveTarget::Client(cid));
+        assert_eq!(uc.get_active_target(), &ActiveTarget::Master);
+    }
+
+    #[tokio::test]
+    async fn test_mouse_move_on_client_is_kept_off_dead_space() {
+        // Arrange – client with a short second monitor, to the right of the master
+        let transmitter = Arc::new(RecordingTransmitter::default());
+        let mut uc = RouteInputUseCase::new(
+            1920,
+            1080,
+            Arc::clone(&transmitter) as Arc<dyn InputTransmitter>,
+            Arc::new(RecordingCursorController::default()),
+            HotkeyBindings::new(),
+        );
+        let monitor = |id: u8, x: i32, w: u32, h: u32| Monitor {
+            monitor_id: id,
+            x,
+            y: 0,
+            width: w,
+            height: h,
+            is_primary: id == 0,
+            scale_factor: 100,
+            size_mm: None,
+        };
+        let client = ClientScreen::with_monitors(
+            Uuid::new_v4(),
+            "dual",
+            1920,
+            0,
+            vec![monitor(0, 0, 1920, 1080), monitor(1, 1920, 1280, 720)],
+        )
+        .unwrap();
+        let cid = client.client_id;
+        uc.layout.add_client(client).unwrap();
+        uc.active_target = ActiveTarget::Client(cid);
+
+        // Act – below the second monitor
+        uc.handle_event(RawInputEvent::MouseMove {
+            x: 1920 + 2500,
+            y: 1000,
+            time_ms: 0,
+        })
+        .await
+        .unwrap();
+
+        // Assert
+        let moves = transmitter.mouse_moves.lock().unwrap();
+        assert_eq!((moves[0].1.x, moves[0].1.y), (2500, 719));
+    }
+
+    #[tokio::test]
+    async fn test_motion_on_hidpi_client_is_scaled_in_physical_units() {
+        // Arrange – a 200 % laptop to the right of a 100 % master
+        let transmitter = Arc::new(RecordingTransmitter::default());
+        let mut uc = RouteInputUseCase::new(
+            1920,
+            1080,
+            Arc::clone(&transmitter) as Arc<dyn InputTransmitter>,
+            Arc::new(RecordingCursorController::default()),
+            HotkeyBindings::new(),
+        );
+        let laptop = ClientScreen::with_monitors(
+            Uuid::new_v4(),
+            "laptop",
+            1920,
+            0,
+            vec![Monitor {
+                scale_factor: 200,
+                ..Monitor::primary(2560, 1600)
+            }],
+        )
+        .unwrap();
+        let cid = laptop.client_id;
+        uc.layout.add_client(laptop).unwrap();
+        uc.layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Master,
+                from_edge: Edge::Right,
+                to_screen: ScreenId::Client(cid),
+                to_edge: Edge::Left,
+                segment: None,
+            })
+            .unwrap();
+        uc.layout.set_physical_units(true);
+
+        // Act – cross at the middle of the edge, then move 10 right and 5
+        // down from where the master cursor was parked
+        for (x, y) in [(1919, 540), (11, 545)] {
+            uc.handle_event(RawInputEvent::MouseMove { x, y, time_ms: 0 })
+                .await
+                .unwrap();
+        }
+
+        // Assert – entered at the laptop's middle, then moved twice as far
+        let moves = transmitter.mouse_moves.lock().unwrap();
+        let positions: Vec<_> = moves.iter().map(|(_, m)| (m.x, m.y)).collect();
+        assert_eq!(positions, [(0, 800), (20, 810)]);
+    }
+
     #[tokio::test]
     async fn test_update_layout_removes_client_falls_back_to_master() {
         // Arrange
@@ -10,12 +10,30 @@
 //! When the user drags a client screen directly to the right of the master in
 //! the layout editor, the two screens share an edge (master's right == client's
 //! left).  The [`detect_and_add_adjacencies`] function scans all screen pairs
-//! and calls `layout.set_adjacency()` for each touching edge pair.
+//! and calls `layout.set_adjacency()` for each touching edge pair, in both
+//! directions.  Each adjacency covers only the stretch of edge the two
+//! screens share, so a laptop placed beside the lower half of a tall monitor
+//! is reached only from that half.
 //!
 //! This saves the user from having to manually configure adjacencies: just
 //! position screens correctly and the connections are inferred automatically.
+//!
+//! # Clients with several monitors
+//!
+//! A client that has reported its monitors (`ScreenInfo`) is laid out with
+//! one region per monitor, keeping their arrangement, with the top-left of
+//! their bounding box at the configured offset.  Without a report the
+//! configured width and height describe a single monitor.
+//!
+//! The master may have several monitors too
+//! ([`build_layout_with_master_monitors`]); adjacencies are then detected
+//! between the individual monitors that touch, one segment per touching pair.
 
-use kvm_core::domain::layout::{Adjacency, ClientId, ClientScreen, ScreenRegion, VirtualLayout};
+use kvm_core::domain::layout::{
+    Adjacency, ClientId, ClientScreen, Edge, EdgeSegment, Monitor, ScreenId, ScreenRegion,
+    VirtualLayout,
+};
+use kvm_core::protocol::messages::MonitorInfo;
 use thiserror::Error;
 
 /// Error type for layout update operations.
@@ -39,18 +57,43 @@ pub fn build_layout(
     master_height: u32,
     clients: Vec<ClientLayoutConfig>,
 ) -> Result<VirtualLayout, UpdateLayoutError> {
-    let mut layout = VirtualLayout::new(master_width, master_height);
+    build_layout_with_master_monitors(vec![Monitor::primary(master_width, master_height)], clients)
+}
+
+/// Builds a new [`VirtualLayout`] for a master with the given monitors.
+///
+/// # Errors
+///
+/// Returns [`UpdateLayoutError::ValidationFailed`] if `master_monitors` is
+/// empty or any screen regions overlap.
+pub fn build_layout_with_master_monitors(
+    master_monitors: Vec<Monitor>,
+    clients: Vec<ClientLayoutConfig>,
+) -> Result<VirtualLayout, UpdateLayoutError> {
+    let mut layout = VirtualLayout::with_master_monitors(master_monitors).ok_or_else(|| {
+        UpdateLayoutError::ValidationFailed("the master has no monitors".to_string())
+    })?;
     for client_cfg in clients {
-        let screen = ClientScreen {
-            client_id: client_cfg.client_id,
-            region: ScreenRegion {
-                virtual_x: client_cfg.x_offset,
-                virtual_y: client_cfg.y_offset,
-                width: client_cfg.width,
-                height: client_cfg.height,
-            },
-            name: client_cfg.name,
-        };
+        let monitors = client_cfg.monitors.iter().map(monitor_from_info).collect();
+        let screen = ClientScreen::with_monitors(
+            client_cfg.client_id,
+            client_cfg.name.clone(),
+            client_cfg.x_offset,
+            client_cfg.y_offset,
+            monitors,
+        )
+        .unwrap_or_else(|| {
+            ClientScreen::new(
+                client_cfg.client_id,
+                client_cfg.name,
+                ScreenRegion {
+                    virtual_x: client_cfg.x_offset,
+                    virtual_y: client_cfg.y_offset,
+                    width: client_cfg.width,
+                    height: client_cfg.height,
+                },
+            )
+        });
         layout
             .add_client(screen)
             .map_err(|e| UpdateLayoutError::ValidationFailed(e.to_string()))?;
@@ -71,90 +114,89 @@ pub struct ClientLayoutConfig {
     pub y_offset: i32,
     pub width: u32,
     pub height: u32,
+    /// The monitors the client reported.  When empty, `width` × `height` is
+    /// used as a single monitor.
+    pub monitors: Vec<MonitorInfo>,
+}
+
+fn monitor_from_info(info: &MonitorInfo) -> Monitor {
+    Monitor {
+        monitor_id: info.monitor_id,
+        x: info.x_offset,
+        y: info.y_offset,
+        width: info.width,
+        height: info.height,
+        is_primary: info.is_primary,
+        scale_factor: info.scale_factor,
+        size_mm: None,
+    }
 }
 
 /// Automatically detects touching edges between all screen pairs and adds adjacencies.
+///
+/// Every monitor of one screen is compared with every monitor of another.
+/// Where two monitors touch, the stretch they share becomes an adjacency
+/// limited to that [`EdgeSegment`], in both directions, so the cursor crosses
+/// only where the screens physically meet.
 fn detect_and_add_adjacencies(layout: &mut VirtualLayout) {
-    use kvm_core::domain::layout::{Edge, ScreenId};
-
-    let master = layout.master.clone();
-    let clients: Vec<_> = layout.clients().cloned().collect();
-
-    // Check master right edge against each client left edge
-    for client in &clients {
-        if master.right() == client.region.virtual_x
-            && ranges_overlap(
-                master.virtual_y,
-                master.virtual_y + master.height as i32,
-                client.region.virtual_y,
-                client.region.virtual_y + client.region.height as i32,
-            )
-        {
-            let _ = layout.set_adjacency(Adjacency {
-                from_screen: ScreenId::Master,
-                from_edge: Edge::Right,
-                to_screen: ScreenId::Client(client.client_id),
-                to_edge: Edge::Left,
-            });
-            let _ = layout.set_adjacency(Adjacency {
-                from_screen: ScreenId::Client(client.client_id),
-                from_edge: Edge::Left,
-                to_screen: ScreenId::Master,
-                to_edge: Edge::Right,
-            });
-        }
+    let screens: Vec<_> = std::iter::once(ScreenId::Master)
+        .chain(layout.clients().map(|c| ScreenId::Client(c.client_id)))
+        .collect();
 
-        // Check master left edge
-        if master.virtual_x == client.region.right()
-            && ranges_overlap(
-                master.virtual_y,
-                master.virtual_y + master.height as i32,
-                client.region.virtual_y,
-                client.region.virtual_y + client.region.height as i32,
-            )
-        {
-            let _ = layout.set_adjacency(Adjacency {
-                from_screen: ScreenId::Master,
-                from_edge: Edge::Left,
-                to_screen: ScreenId::Client(client.client_id),
-                to_edge: Edge::Right,
-            });
+    for from in &screens {
+        let from_monitors = layout.monitor_regions(from);
+        for to in screens.iter().filter(|&to| to != from) {
+            let to_monitors = layout.monitor_regions(to);
+            for a in &from_monitors {
+                for b in &to_monitors {
+                    for (from_edge, to_edge, segment) in touching_edges(a, b) {
+                        let _ = layout.set_adjacency(Adjacency {
+                            from_screen: from.clone(),
+                            from_edge,
+                            to_screen: to.clone(),
+                            to_edge,
+                            segment: Some(segment),
+                        });
+                    }
+                }
+            }
         }
+    }
+}
 
-        // Check master bottom edge
-        if master.bottom() == client.region.virtual_y
-            && ranges_overlap(
-                master.virtual_x,
-                master.right(),
-                client.region.virtual_x,
-                client.region.right(),
-            )
-        {
-            let _ = layout.set_adjacency(Adjacency {
-                from_screen: ScreenId::Master,
-                from_edge: Edge::Bottom,
-                to_screen: ScreenId::Client(client.client_id),
-                to_edge: Edge::Top,
-            });
-        }
+/// Returns the edges along which monitor `a` touches monitor `b`: the edge of
+/// `a`, the edge of `b`, and the stretch of `a`'s edge they share.
+fn touching_edges(a: &ScreenRegion, b: &ScreenRegion) -> Vec<(Edge, Edge, EdgeSegment)> {
+    let vertical = shared_range(a.virtual_y, a.bottom(), b.virtual_y, b.bottom());
+    let horizontal = shared_range(a.virtual_x, a.right(), b.virtual_x, b.right());
 
-        // Check master top edge
-        if master.virtual_y == client.region.bottom()
-            && ranges_overlap(
-                master.virtual_x,
-                master.right(),
-                client.region.virtual_x,
-                client.region.right(),
-            )
-        {
-            let _ = layout.set_adjacency(Adjacency {
-                from_screen: ScreenId::Master,
-                from_edge: Edge::Top,
-                to_screen: ScreenId::Client(client.client_id),
-                to_edge: Edge::Bottom,
-            });
+    let mut edges = Vec::new();
+    if let Some(segment) = vertical {
+        if a.right() == b.virtual_x {
+            edges.push((Edge::Right, Edge::Left, segment));
+        }
+        if a.virtual_x == b.right() {
+            edges.push((Edge::Left, Edge::Right, segment));
         }
     }
+    if let Some(segment) = horizontal {
+        if a.bottom() == b.virtual_y {
+            edges.push((Edge::Bottom, Edge::Top, segment));
+        }
+        if a.virtual_y == b.bottom() {
+            edges.push((Edge::Top, Edge::Bottom, segment));
+        }
+    }
+    edges
+}
+
+/// The part shared by the intervals `[a_start, a_end)` and `[b_start, b_end)`,
+/// or `None` if they do not overlap.
+fn shared_range(a_start: i32, a_end: i32, b_start: i32, b_end: i32) -> Option<EdgeSegment> {
+    ranges_overlap(a_start, a_end, b_start, b_end).then(|| EdgeSegment {
+        start: a_start.max(b_start),
+        end: a_end.min(b_end),
+    })
 }
 
 /// Returns `true` if the two 1-D intervals `[a_start, a_end)` and `[b_start, b_end)` overlap.
@@ -190,6 +232,7 @@ mod tests {
             y_offset: y,
             width: w,
             height: h,
+            monitors: Vec::new(),
         }
     }
 
@@ -210,6 +253,7 @@ mod tests {
             y_offset: 0,
             width: 1920,
             height: 1080,
+            monitors: Vec::new(),
         };
         let result = build_layout(1920, 1080, vec![c1, c2]);
         assert!(matches!(
@@ -245,6 +289,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_layout_uses_reported_monitors() {
+        // Arrange – a 1920×1080 primary with a 1280×1024 monitor to its left
+        let info = |id: u8, x: i32, w: u32, h: u32| MonitorInfo {
+            monitor_id: id,
+            x_offset: x,
+            y_offset: 0,
+            width: w,
+            height: h,
+            scale_factor: 100,
+            is_primary: id == 0,
+        };
+        let mut client_cfg = make_client_cfg(1920, 0, 1, 1);
+        client_cfg.monitors = vec![info(0, 0, 1920, 1080), info(1, -1280, 1280, 1024)];
+        let cid = client_cfg.client_id;
+
+        // Act
+        let layout = build_layout(1920, 1080, vec![client_cfg]).unwrap();
+
+        // Assert
+        let screen = kvm_core::domain::layout::ScreenId::Client(cid);
+        assert_eq!(layout.monitor_regions(&screen).len(), 2);
+        assert_eq!(layout.get_region(&screen).unwrap().width, 3200);
+        assert_eq!(layout.to_local(&screen, 1920, 0), Some((-1280, 0)));
+    }
+
+    #[test]
+    fn test_detect_adjacency_uses_individual_master_monitors() {
+        // Arrange – landscape primary and a portrait monitor to its right that
+        // reaches lower; the client sits below the portrait monitor only
+        let master = vec![
+            Monitor::primary(1920, 1080),
+            Monitor {
+                monitor_id: 1,
+                x: 1920,
+                y: -420,
+                width: 1080,
+                height: 1920,
+                is_primary: false,
+                scale_factor: 100,
+                size_mm: None,
+            },
+        ];
+        let client_cfg = make_client_cfg(1920, 1500, 1080, 1080);
+        let cid = client_cfg.client_id;
+
+        // Act
+        let layout = build_layout_with_master_monitors(master, vec![client_cfg]).unwrap();
+
+        // Assert – crossing at the bottom of the portrait monitor reaches the client
+        let master_id = kvm_core::domain::layout::ScreenId::Master;
+        let transition = layout.check_edge_transition(&master_id, 2400, 1499);
+        assert_eq!(
+            transition.map(|t| t.to_screen),
+            Some(kvm_core::domain::layout::ScreenId::Client(cid))
+        );
+    }
+
+    #[test]
+    fn test_detect_adjacency_limits_crossing_to_the_shared_segment() {
+        // Arrange – a laptop beside the lower half of a 4K master
+        let client_cfg = make_client_cfg(3840, 1080, 1920, 1080);
+        let cid = client_cfg.client_id;
+        let master_id = kvm_core::domain::layout::ScreenId::Master;
+        let client_id = kvm_core::domain::layout::ScreenId::Client(cid);
+
+        // Act
+        let layout = build_layout(3840, 2160, vec![client_cfg]).unwrap();
+
+        // Assert – only the lower half leads to the laptop, at the same height
+        assert!(layout
+            .check_edge_transition(&master_id, 3839, 500)
+            .is_none());
+        let transition = layout
+            .check_edge_transition(&master_id, 3839, 1500)
+            .expect("lower half touches the laptop");
+        assert_eq!(transition.to_screen, client_id);
+        assert_eq!((transition.entry_x, transition.entry_y), (0, 420));
+    }
+
+    #[test]
+    fn test_detect_adjacency_adds_both_directions() {
+        // Arrange – a client below the master
+        let client_cfg = make_client_cfg(0, 1080, 1920, 1080);
+        let cid = client_cfg.client_id;
+        let client_id = kvm_core::domain::layout::ScreenId::Client(cid);
+
+        // Act
+        let layout = build_layout(1920, 1080, vec![client_cfg]).unwrap();
+
+        // Assert
+        let back = layout.check_edge_transition(&client_id, 700, 0);
+        assert_eq!(
+            back.map(|t| (t.to_screen, t.entry_x, t.entry_y)),
+            Some((kvm_core::domain::layout::ScreenId::Master, 700, 1079))
+        );
+    }
+
+    #[test]
+    fn test_touching_edges_returns_the_shared_segment() {
+        let region = |x: i32, y: i32, w: u32, h: u32| ScreenRegion {
+            virtual_x: x,
+            virtual_y: y,
+            width: w,
+            height: h,
+        };
+
+        let edges = touching_edges(&region(0, 0, 3840, 2160), &region(3840, 1080, 1920, 1440));
+
+        assert_eq!(
+            edges,
+            vec![(
+                Edge::Right,
+                Edge::Left,
+                EdgeSegment {
+                    start: 1080,
+                    end: 2160
+                }
+            )]
+        );
+        assert!(touching_edges(&region(0, 0, 100, 100), &region(100, 100, 100, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_build_layout_rejects_master_without_monitors() {
+        let result = build_layout_with_master_monitors(vec![], vec![]);
+        assert!(matches!(
+            result,
+            Err(UpdateLayoutError::ValidationFailed(_))
+        ));
+    }
+
     #[test]
     fn test_ranges_overlap_returns_true_for_overlapping_ranges() {
         assert!(ranges_overlap(0, 100, 50, 150));
@@ -0,0 +1,907 @@
+//! Linux input capture from evdev devices (`/dev/input/event*`).
+//!
+//! This module opens every keyboard and mouse under `/dev/input`, reads their
+//! `struct input_event` streams on a dedicated thread and turns them into
+//! [`RawInputEvent`]s.  While a client is active the devices are grabbed with
+//! `EVIOCGRAB`, so keystrokes and pointer motion go only to the master
+//! process and not to the local desktop.
+//!
+//! # What is evdev? (for beginners)
+//!
+//! Every input device on Linux is exposed by the kernel as a character device
+//! such as `/dev/input/event3`.  Reading from it yields fixed-size
+//! `struct input_event` records:
+//!
+//! ```text
+//! struct input_event {
+//!     struct timeval time;   // two C longs: seconds, microseconds
+//!     __u16 type;            // EV_KEY, EV_REL, EV_SYN, ...
+//!     __u16 code;            // KEY_A, REL_X, BTN_LEFT, ...
+//!     __s32 value;           // 1 = press, 0 = release, 2 = autorepeat; or a delta
+//! };
+//! ```
+//!
+//! Events arrive in *frames*: everything up to an `EV_SYN`/`SYN_REPORT`
+//! happened at the same instant.  A mouse moving diagonally while a button is
+//! pressed sends `REL_X`, `REL_Y` and `BTN_LEFT` followed by one
+//! `SYN_REPORT`.  [`EvdevParser`] buffers each device's frame and emits the
+//! resulting events when the frame completes.
+//!
+//! Because evdev sits below X11 and Wayland, this works under any display
+//! server (and on a text console).
+//!
+//! # Key codes
+//!
+//! Each evdev code is translated straight to a [`HidKeyCode`] with
+//! [`KeyMapper::linux_evdev_to_hid`]; keys with no HID usage are dropped.
+//! `scan_code` carries the original evdev code.
+//!
+//! # Pointer position
+//!
+//! Mice report relative motion, and the display server applies its own
+//! acceleration before moving the visible cursor, which evdev readers never
+//! see.  The parser therefore keeps its *own* pointer position from the raw
+//! deltas.  While the devices are not grabbed that position is clamped to the
+//! master screen, so pushing against an edge always reaches it; while a
+//! client is active it moves freely through the virtual layout.  The position
+//! is also the [`CursorController`] for the router: a teleport simply moves
+//! the tracked position.
+//!
+//! # Grabbing
+//!
+//! `EVIOCGRAB` makes this process the only reader of a device.  Grabbing a
+//! keyboard while a key is held would leave that key stuck down on the local
+//! desktop (it never sees the release), so a grab requested with
+//! [`InputSource::set_exclusive`] is applied to each device only once none of
+//! its keys or buttons is pressed.  Releasing the grab is immediate.
+//!
+//! # Permissions and limitations
+//!
+//! The user running the master must be able to read `/dev/input/event*`,
+//! usually by being in the `input` group.  Devices are enumerated once in
+//! `start()`; hot-plugged devices are not picked up until restart.
+//! Touchpads and tablets report absolute axes and are not captured.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::mem::size_of;
+use std::os::fd::AsRawFd;
+use std::os::raw::{c_int, c_long};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use kvm_core::keymap::{HidKeyCode, KeyMapper};
+use tracing::{debug, info, warn};
+
+use super::{CaptureError, InputSource, MouseButton, RawInputEvent};
+use crate::application::route_input::CursorController;
+
+// ── evdev constants (linux/input-event-codes.h) ───────────────────────────────
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const SYN_REPORT: u16 = 0x00;
+const SYN_DROPPED: u16 = 0x03;
+const KEY_A: u16 = 30;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+const REL_WHEEL_HI_RES: u16 = 0x0B;
+const REL_HWHEEL_HI_RES: u16 = 0x0C;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BTN_SIDE: u16 = 0x113;
+const BTN_EXTRA: u16 = 0x114;
+const KEY_MAX: usize = 0x2FF;
+
+/// One wheel notch in `RawInputEvent` units (Windows `WHEEL_DELTA`), which is
+/// also one notch in `REL_*_HI_RES` units.
+const WHEEL_DELTA: i32 = 120;
+
+/// Size of `struct input_event`: a `struct timeval` (two C longs), then
+/// type, code and value.
+pub const INPUT_EVENT_SIZE: usize = 2 * size_of::<c_long>() + 8;
+
+/// How often the reader thread wakes up to check for `stop()`.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// ── evdev ioctls (linux/input.h) ──────────────────────────────────────────────
+
+/// `_IOC(dir, 'E', nr, size)`.
+const fn ioc(dir: u32, nr: u32, size: usize) -> u32 {
+    dir << 30 | (size as u32) << 16 | (b'E' as u32) << 8 | nr
+}
+
+/// `EVIOCGBIT(ev, len)`: which codes of event type `ev` the device supports
+/// (`ev = 0` asks for the supported event types).
+const fn eviocgbit(ev: u16, len: usize) -> u32 {
+    ioc(2, 0x20 + ev as u32, len)
+}
+
+/// `EVIOCGRAB`: grab (non-zero argument) or release (zero) the device.
+const EVIOCGRAB: u32 = ioc(1, 0x90, size_of::<c_int>());
+
+// ── Event decoding ────────────────────────────────────────────────────────────
+
+/// One decoded `struct input_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    /// Event time in milliseconds (wraps like the Windows hook `time` field).
+    pub time_ms: u32,
+    /// Event type (`EV_*`).
+    pub kind: u16,
+    /// Event code (`KEY_*`, `REL_*`, `SYN_*`, ...).
+    pub code: u16,
+    /// Event value.
+    pub value: i32,
+}
+
+/// Decodes a buffer of native-layout `input_event` records.
+///
+/// A trailing partial record is ignored; the kernel never returns one.
+pub fn decode_events(bytes: &[u8]) -> impl Iterator<Item = InputEvent> + '_ {
+    const LONG: usize = size_of::<c_long>();
+    bytes.chunks_exact(INPUT_EVENT_SIZE).map(|e| {
+        let sec = c_long::from_ne_bytes(e[..LONG].try_into().expect("LONG bytes"));
+        let usec = c_long::from_ne_bytes(e[LONG..2 * LONG].try_into().expect("LONG bytes"));
+        let rest = &e[2 * LONG..];
+        // c_long is only i64 on 64-bit targets.
+        #[allow(clippy::useless_conversion)]
+        let (sec, usec) = (i64::from(sec), i64::from(usec));
+        InputEvent {
+            time_ms: (sec * 1000 + usec / 1000) as u32,
+            kind: u16::from_ne_bytes([rest[0], rest[1]]),
+            code: u16::from_ne_bytes([rest[2], rest[3]]),
+            value: i32::from_ne_bytes([rest[4], rest[5], rest[6], rest[7]]),
+        }
+    })
+}
+
+// ── Parser ────────────────────────────────────────────────────────────────────
+
+/// The events of one device since its last `SYN_REPORT`.
+#[derive(Debug, Default)]
+struct Frame {
+    dx: i32,
+    dy: i32,
+    /// `EV_KEY` (code, value) pairs in arrival order.
+    keys: Vec<(u16, i32)>,
+    wheel: i32,
+    wheel_hi_res: Option<i32>,
+    hwheel: i32,
+    hwheel_hi_res: Option<i32>,
+    /// Set by `SYN_DROPPED`: discard everything up to the next `SYN_REPORT`.
+    dropping: bool,
+}
+
+/// Per-device state.
+#[derive(Debug, Default)]
+struct DeviceState {
+    frame: Frame,
+    /// Keys and buttons currently held on this device.
+    pressed: HashSet<u16>,
+}
+
+/// Turns evdev event streams into [`RawInputEvent`]s.
+///
+/// Pure state machine with no I/O, so recorded streams can be replayed in
+/// tests.  Devices are identified by a caller-chosen index.
+#[derive(Debug)]
+pub struct EvdevParser {
+    devices: Vec<DeviceState>,
+    position: (i32, i32),
+    master_width: u32,
+    master_height: u32,
+    clamp_to_master: bool,
+}
+
+impl EvdevParser {
+    /// Creates a parser for a `master_width` × `master_height` screen, with
+    /// the pointer at its centre and clamped to it.
+    pub fn new(master_width: u32, master_height: u32) -> Self {
+        Self {
+            devices: Vec::new(),
+            position: ((master_width / 2) as i32, (master_height / 2) as i32),
+            master_width,
+            master_height,
+            clamp_to_master: true,
+        }
+    }
+
+    /// The tracked pointer position.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// Moves the tracked pointer, e.g. after a teleport.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.position = (x, y);
+        if self.clamp_to_master {
+            self.clamp();
+        }
+    }
+
+    /// Whether pointer motion is limited to the master screen.  Off while a
+    /// client is active, so the position can enter the client's region.
+    pub fn set_clamp_to_master(&mut self, clamp: bool) {
+        self.clamp_to_master = clamp;
+        if clamp {
+            self.clamp();
+        }
+    }
+
+    /// `true` if any key or button of `device` is held down.
+    pub fn has_pressed(&self, device: usize) -> bool {
+        self.devices
+            .get(device)
+            .is_some_and(|d| !d.pressed.is_empty())
+    }
+
+    /// Feeds one event from `device`, appending any completed events to `out`.
+    pub fn push(&mut self, device: usize, event: InputEvent, out: &mut Vec<RawInputEvent>) {
+        if self.devices.len() <= device {
+            self.devices.resize_with(device + 1, DeviceState::default);
+        }
+        let state = &mut self.devices[device];
+        let frame = &mut state.frame;
+
+        match (event.kind, event.code) {
+            (EV_SYN, SYN_DROPPED) => {
+                debug!("evdev device {device} overflowed; dropping a frame");
+                state.frame = Frame {
+                    dropping: true,
+                    ..Frame::default()
+                };
+            }
+            (EV_SYN, SYN_REPORT) => {
+                let frame = std::mem::take(&mut state.frame);
+                if !frame.dropping {
+                    for &(code, value) in &frame.keys {
+                        match value {
+                            0 => {
+                                state.pressed.remove(&code);
+                            }
+                            1 => {
+                                state.pressed.insert(code);
+                            }
+                            _ => {}
+                        }
+                    }
+                    self.flush(frame, event.time_ms, out);
+                }
+            }
+            _ if frame.dropping => {}
+            (EV_KEY, code) => frame.keys.push((code, event.value)),
+            (EV_REL, REL_X) => frame.dx += event.value,
+            (EV_REL, REL_Y) => frame.dy += event.value,
+            (EV_REL, REL_WHEEL) => frame.wheel += event.value,
+            (EV_REL, REL_HWHEEL) => frame.hwheel += event.value,
+            (EV_REL, REL_WHEEL_HI_RES) => {
+                *frame.wheel_hi_res.get_or_insert(0) += event.value;
+            }
+            (EV_REL, REL_HWHEEL_HI_RES) => {
+                *frame.hwheel_hi_res.get_or_insert(0) += event.value;
+            }
+            // EV_MSC scan codes, LEDs, absolute axes, ...
+            _ => {}
+        }
+    }
+
+    /// Emits a completed frame: motion first, then keys and buttons at the
+    /// new position, then scrolling.
+    fn flush(&mut self, frame: Frame, time_ms: u32, out: &mut Vec<RawInputEvent>) {
+        if frame.dx != 0 || frame.dy != 0 {
+            self.position.0 = self.position.0.saturating_add(frame.dx);
+            self.position.1 = self.position.1.saturating_add(frame.dy);
+            if self.clamp_to_master {
+                self.clamp();
+            }
+            let (x, y) = self.position;
+            out.push(RawInputEvent::MouseMove { x, y, time_ms });
+        }
+
+        let (x, y) = self.position;
+        for (code, value) in frame.keys {
+            if let Some(button) = mouse_button(code) {
+                out.push(match value {
+                    0 => RawInputEvent::MouseButtonUp {
+                        button,
+                        x,
+                        y,
+                        time_ms,
+                    },
+                    1 => RawInputEvent::MouseButtonDown {
+                        button,
+                        x,
+                        y,
+                        time_ms,
+                    },
+                    _ => continue,
+                });
+            } else {
+                let key = KeyMapper::linux_evdev_to_hid(code);
+                if key == HidKeyCode::Unknown {
+                    continue;
+                }
+                let scan_code = Some(code);
+                // Autorepeat (value 2) is a further key-down, as on Windows.
+                out.push(if value == 0 {
+                    RawInputEvent::KeyUp {
+                        key,
+                        scan_code,
+                        time_ms,
+                    }
+                } else {
+                    RawInputEvent::KeyDown {
+                        key,
+                        scan_code,
+                        time_ms,
+                    }
+                });
+            }
+        }
+
+        // High-resolution devices send both axes; the hi-res one wins.
+        let wheel = frame.wheel_hi_res.unwrap_or(frame.wheel * WHEEL_DELTA);
+        if wheel != 0 {
+            out.push(RawInputEvent::MouseWheel {
+                delta: saturate_i16(wheel),
+                x,
+                y,
+                time_ms,
+            });
+        }
+        let hwheel = frame.hwheel_hi_res.unwrap_or(frame.hwheel * WHEEL_DELTA);
+        if hwheel != 0 {
+            out.push(RawInputEvent::MouseWheelH {
+                delta: saturate_i16(hwheel),
+                x,
+                y,
+                time_ms,
+            });
+        }
+    }
+
+    fn clamp(&mut self) {
+        let max_x = self.master_width.saturating_sub(1) as i32;
+        let max_y = self.master_height.saturating_sub(1) as i32;
+        self.position = (
+            self.position.0.clamp(0, max_x),
+            self.position.1.clamp(0, max_y),
+        );
+    }
+}
+
+fn saturate_i16(value: i32) -> i16 {
+    value.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16
+}
+
+fn mouse_button(code: u16) -> Option<MouseButton> {
+    match code {
+        BTN_LEFT => Some(MouseButton::Left),
+        BTN_RIGHT => Some(MouseButton::Right),
+        BTN_MIDDLE => Some(MouseButton::Middle),
+        BTN_SIDE => Some(MouseButton::X1),
+        BTN_EXTRA => Some(MouseButton::X2),
+        _ => None,
+    }
+}
+
+// ── Device discovery ──────────────────────────────────────────────────────────
+
+/// `true` if a bit is set in an `EVIOCGBIT` bitmap.
+fn test_bit(bits: &[u8], bit: u16) -> bool {
+    bits.get(usize::from(bit / 8))
+        .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+/// Whether a device with these capability bitmaps is a keyboard (has letter
+/// keys) or a mouse (relative X axis and a left button).
+fn is_keyboard_or_mouse(ev_bits: &[u8], key_bits: &[u8], rel_bits: &[u8]) -> bool {
+    let has_keys = test_bit(ev_bits, EV_KEY);
+    let keyboard = has_keys && test_bit(key_bits, KEY_A);
+    let mouse = has_keys
+        && test_bit(key_bits, BTN_LEFT)
+        && test_bit(ev_bits, EV_REL)
+        && test_bit(rel_bits, REL_X);
+    keyboard || mouse
+}
+
+fn ioctl(file: &File, request: u32, arg: libc::c_ulong) -> io::Result<()> {
+    // SAFETY: the fd is open for the lifetime of `file`, and every request
+    // used here takes either an integer or a pointer to a live buffer of the
+    // size encoded in the request number.
+    let rc = unsafe { libc::ioctl(file.as_raw_fd(), request as libc::Ioctl, arg) };
+    if rc < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Reads the `EVIOCGBIT` bitmap for event type `ev`.
+fn capability_bits(file: &File, ev: u16, len: usize) -> io::Result<Vec<u8>> {
+    let mut bits = vec![0u8; len];
+    ioctl(file, eviocgbit(ev, len), bits.as_mut_ptr() as libc::c_ulong)?;
+    Ok(bits)
+}
+
+/// Opens every readable keyboard and mouse under `/dev/input`.
+fn open_devices() -> io::Result<Vec<(String, File)>> {
+    let mut devices = Vec::new();
+    for entry in fs::read_dir("/dev/input")? {
+        let path = entry?.path();
+        let is_event_node = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("event"));
+        if !is_event_node {
+            continue;
+        }
+        let file = match OpenOptions::new().read(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                debug!("skipping {}: {e}", path.display());
+                continue;
+            }
+        };
+        let ev_bits = capability_bits(&file, 0, 4)?;
+        let key_bits = capability_bits(&file, EV_KEY, KEY_MAX / 8 + 1)?;
+        let rel_bits = capability_bits(&file, EV_REL, 2)?;
+        if is_keyboard_or_mouse(&ev_bits, &key_bits, &rel_bits) {
+            devices.push((path.display().to_string(), file));
+        }
+    }
+    Ok(devices)
+}
+
+// ── Input source ──────────────────────────────────────────────────────────────
+
+/// Linux input capture service reading evdev devices.
+///
+/// Also implements [`CursorController`] over its tracked pointer position.
+pub struct LinuxEvdevInputSource {
+    parser: Arc<Mutex<EvdevParser>>,
+    exclusive: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl LinuxEvdevInputSource {
+    /// Creates a new (unstarted) source for a master screen of the given size.
+    pub fn new(master_width: u32, master_height: u32) -> Self {
+        Self {
+            parser: Arc::new(Mutex::new(EvdevParser::new(master_width, master_height))),
+            exclusive: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn parser(&self) -> MutexGuard<'_, EvdevParser> {
+        lock(&self.parser)
+    }
+}
+
+fn lock(parser: &Mutex<EvdevParser>) -> MutexGuard<'_, EvdevParser> {
+    // The parser holds no invariant a panicking reader could break.
+    parser.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl InputSource for LinuxEvdevInputSource {
+    fn start(&self) -> Result<mpsc::Receiver<RawInputEvent>, CaptureError> {
+        if self.stopped.load(Ordering::SeqCst) {
+            return Err(CaptureError::AlreadyStopped);
+        }
+        let devices = open_devices()
+            .map_err(|e| CaptureError::DeviceUnavailable(format!("/dev/input: {e}")))?;
+        if devices.is_empty() {
+            return Err(CaptureError::DeviceUnavailable(
+                "no readable keyboard or mouse in /dev/input (is the user in the `input` group?)"
+                    .to_string(),
+            ));
+        }
+        for (path, _) in &devices {
+            info!("capturing input from {path}");
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let reader = Reader {
+            devices: devices.into_iter().map(|(_, file)| file).collect(),
+            parser: Arc::clone(&self.parser),
+            exclusive: Arc::clone(&self.exclusive),
+            stopped: Arc::clone(&self.stopped),
+            tx,
+        };
+        thread::Builder::new()
+            .name("kvm-evdev-reader".to_string())
+            .spawn(move || reader.run())
+            .map_err(|e| CaptureError::DeviceUnavailable(e.to_string()))?;
+        Ok(rx)
+    }
+
+    fn stop(&self) {
+        // The reader exits within one poll interval; closing the devices
+        // releases any grab.
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    fn suppress_current_event(&self) {
+        // evdev cannot withhold a single event; see `set_exclusive`.
+    }
+
+    fn set_exclusive(&self, exclusive: bool) {
+        self.exclusive.store(exclusive, Ordering::SeqCst);
+        self.parser().set_clamp_to_master(!exclusive);
+    }
+}
+
+impl CursorController for LinuxEvdevInputSource {
+    fn teleport_cursor(&self, x: i32, y: i32) {
+        self.parser().set_position(x, y);
+    }
+
+    fn get_cursor_pos(&self) -> (i32, i32) {
+        self.parser().position()
+    }
+}
+
+/// The reader thread's state.
+struct Reader {
+    devices: Vec<File>,
+    parser: Arc<Mutex<EvdevParser>>,
+    exclusive: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    tx: Sender<RawInputEvent>,
+}
+
+impl Reader {
+    fn run(self) {
+        let mut pollfds: Vec<libc::pollfd> = self
+            .devices
+            .iter()
+            .map(|f| libc::pollfd {
+                fd: f.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+        let mut grabbed = vec![false; self.devices.len()];
+        let mut buf = vec![0u8; 64 * INPUT_EVENT_SIZE];
+        let mut out = Vec::new();
+
+        while !self.stopped.load(Ordering::SeqCst) {
+            // SAFETY: `pollfds` is a valid array of `pollfds.len()` entries.
+            let ready = unsafe {
+                libc::poll(
+                    pollfds.as_mut_ptr(),
+                    pollfds.len() as libc::nfds_t,
+                    POLL_INTERVAL.as_millis() as c_int,
+                )
+            };
+            if ready < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                warn!("evdev poll failed, input capture stopped: {err}");
+                return;
+            }
+
+            for (index, pfd) in pollfds.iter_mut().enumerate() {
+                if pfd.revents == 0 {
+                    continue;
+                }
+                match (&self.devices[index]).read(&mut buf) {
+                    Ok(n) => {
+                        let mut parser = lock(&self.parser);
+                        for event in decode_events(&buf[..n]) {
+                            parser.push(index, event, &mut out);
+                        }
+                    }
+                    Err(e) => {
+                        // Usually ENODEV: the device was unplugged.  A
+                        // negative fd makes poll() skip the entry.
+                        info!("stopped reading an input device: {e}");
+                        pfd.fd = -1;
+                    }
+                }
+            }
+            for event in out.drain(..) {
+                if self.tx.send(event).is_err() {
+                    return; // receiver dropped
+                }
+            }
+            self.update_grabs(&mut grabbed, &pollfds);
+        }
+    }
+
+    /// Applies the requested grab state to each device; a grab waits until
+    /// the device has nothing held down.
+    fn update_grabs(&self, grabbed: &mut [bool], pollfds: &[libc::pollfd]) {
+        let want = self.exclusive.load(Ordering::SeqCst);
+        let parser = lock(&self.parser);
+        for (index, is_grabbed) in grabbed.iter_mut().enumerate() {
+            if *is_grabbed == want || pollfds[index].fd < 0 {
+                continue;
+            }
+            if want && parser.has_pressed(index) {
+                continue;
+            }
+            if let Err(e) = ioctl(&self.devices[index], EVIOCGRAB, libc::c_ulong::from(want)) {
+                warn!("EVIOCGRAB({want}) failed: {e}");
+            }
+            // Not retried on failure, so a refusal is logged only once.
+            *is_grabbed = want;
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+/// Replays recordings made with `cat /dev/input/eventN > file` on x86_64
+/// (24-byte events), stored in `tests/fixtures/evdev/`:
+///
+/// | File                          | Contents                                                      |
+/// |-------------------------------|---------------------------------------------------------------|
+/// | `keyboard_typing`             | Shift+A, B with autorepeat, Right Ctrl, keypad Enter, Kana    |
+/// | `mouse_move_click_scroll`     | moves, click with motion, hi-res and partial scroll, tilt, side button |
+/// | `syn_dropped`                 | a frame cut short by `SYN_DROPPED`                            |
+#[cfg(all(test, target_pointer_width = "64"))]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = format!(
+            "{}/tests/fixtures/evdev/{name}.bin",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        std::fs::read(path).expect("fixture recording")
+    }
+
+    /// Replays a recording as device 0 of a 1920x1080 master.
+    fn replay(parser: &mut EvdevParser, name: &str) -> Vec<RawInputEvent> {
+        let mut out = Vec::new();
+        for event in decode_events(&fixture(name)) {
+            parser.push(0, event, &mut out);
+        }
+        out
+    }
+
+    /// (key, scan_code, down) for each key event.
+    fn keys(events: &[RawInputEvent]) -> Vec<(HidKeyCode, Option<u16>, bool)> {
+        events
+            .iter()
+            .map(|e| match *e {
+                RawInputEvent::KeyDown { key, scan_code, .. } => (key, scan_code, true),
+                RawInputEvent::KeyUp { key, scan_code, .. } => (key, scan_code, false),
+                ref other => panic!("expected a key event, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_events_reads_kernel_layout() {
+        // Arrange
+        let bytes = fixture("keyboard_typing");
+
+        // Act
+        let events: Vec<InputEvent> = decode_events(&bytes).collect();
+
+        // Assert – the first frame is MSC_SCAN, KEY_LEFTSHIFT down, SYN_REPORT.
+        assert_eq!(INPUT_EVENT_SIZE, 24);
+        assert_eq!(events.len(), bytes.len() / INPUT_EVENT_SIZE);
+        assert_eq!(
+            &events[..3],
+            &[
+                InputEvent {
+                    time_ms: (1_760_000_000_000u64 + 100) as u32,
+                    kind: 4,
+                    code: 4,
+                    value: 0x700E1,
+                },
+                InputEvent {
+                    time_ms: (1_760_000_000_000u64 + 100) as u32,
+                    kind: EV_KEY,
+                    code: 42,
+                    value: 1,
+                },
+                InputEvent {
+                    time_ms: (1_760_000_000_000u64 + 100) as u32,
+                    kind: EV_SYN,
+                    code: SYN_REPORT,
+                    value: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyboard_recording_translates_to_hid_usages() {
+        // Arrange
+        let mut parser = EvdevParser::new(1920, 1080);
+
+        // Act
+        let events = replay(&mut parser, "keyboard_typing");
+
+        // Assert – keypad Enter and Kana keep their own usages.
+        assert_eq!(
+            keys(&events),
+            [
+                (HidKeyCode::ShiftLeft, Some(42), true),
+                (HidKeyCode::KeyA, Some(30), true),
+                (HidKeyCode::KeyA, Some(30), false),
+                (HidKeyCode::ShiftLeft, Some(42), false),
+                (HidKeyCode::KeyB, Some(48), true),
+                (HidKeyCode::KeyB, Some(48), true), // autorepeat
+                (HidKeyCode::KeyB, Some(48), false),
+                (HidKeyCode::ControlRight, Some(97), true),
+                (HidKeyCode::ControlRight, Some(97), false),
+                (HidKeyCode::NumpadEnter, Some(96), true),
+                (HidKeyCode::NumpadEnter, Some(96), false),
+                (HidKeyCode::KanaMode, Some(93), true),
+            ]
+        );
+        assert!(parser.has_pressed(0), "Kana is still held at the end");
+    }
+
+    #[test]
+    fn test_mouse_recording_tracks_position_buttons_and_wheels() {
+        // Arrange – the pointer starts at the centre (960, 540).
+        let mut parser = EvdevParser::new(1920, 1080);
+
+        // Act
+        let events = replay(&mut parser, "mouse_move_click_scroll");
+
+        // Assert
+        let summary: Vec<String> = events
+            .iter()
+            .map(|e| match e {
+                RawInputEvent::MouseMove { x, y, .. } => format!("move {x},{y}"),
+                RawInputEvent::MouseButtonDown { button, x, y, .. } => {
+                    format!("down {button:?} {x},{y}")
+                }
+                RawInputEvent::MouseButtonUp { button, .. } => format!("up {button:?}"),
+                RawInputEvent::MouseWheel { delta, .. } => format!("wheel {delta}"),
+                RawInputEvent::MouseWheelH { delta, .. } => format!("hwheel {delta}"),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "move 965,537",
+                "move 977,537",
+                "move 977,539",
+                "down Left 977,539",
+                "up Left",
+                "wheel -120",
+                "wheel 30",
+                "hwheel 120",
+                "down X1 977,539",
+                "up X1",
+            ]
+        );
+        assert!(!parser.has_pressed(0));
+    }
+
+    #[test]
+    fn test_syn_dropped_discards_the_rest_of_the_frame() {
+        // Arrange
+        let mut parser = EvdevParser::new(1920, 1080);
+
+        // Act
+        let events = replay(&mut parser, "syn_dropped");
+
+        // Assert – only the first and last frames survive.
+        let moves: Vec<(i32, i32)> = events
+            .iter()
+            .map(|e| match *e {
+                RawInputEvent::MouseMove { x, y, .. } => (x, y),
+                ref other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(moves, [(964, 540), (962, 540)]);
+        assert!(!parser.has_pressed(0));
+    }
+
+    #[test]
+    fn test_position_is_clamped_to_master_only_when_not_exclusive() {
+        // Arrange
+        let mut parser = EvdevParser::new(1920, 1080);
+        let mut out = Vec::new();
+        let right = |value| InputEvent {
+            time_ms: 0,
+            kind: EV_REL,
+            code: REL_X,
+            value,
+        };
+        let syn = InputEvent {
+            time_ms: 0,
+            kind: EV_SYN,
+            code: SYN_REPORT,
+            value: 0,
+        };
+
+        // Act / Assert – pushing past the right edge stops at it.
+        parser.push(0, right(5000), &mut out);
+        parser.push(0, syn, &mut out);
+        assert_eq!(parser.position(), (1919, 540));
+
+        // While a client is active the position runs into the virtual layout.
+        parser.set_clamp_to_master(false);
+        parser.push(0, right(100), &mut out);
+        parser.push(0, syn, &mut out);
+        assert_eq!(parser.position(), (2019, 540));
+
+        // Returning to the master pulls it back onto the screen.
+        parser.set_clamp_to_master(true);
+        assert_eq!(parser.position(), (1919, 540));
+    }
+
+    #[test]
+    fn test_frames_are_kept_per_device() {
+        // Arrange – a keyboard (device 1) reports while the mouse (device 0)
+        // is in the middle of a frame.
+        let mut parser = EvdevParser::new(1920, 1080);
+        let mut out = Vec::new();
+        let event = |kind, code, value| InputEvent {
+            time_ms: 0,
+            kind,
+            code,
+            value,
+        };
+
+        // Act
+        parser.push(0, event(EV_REL, REL_X, 10), &mut out);
+        parser.push(1, event(EV_KEY, KEY_A, 1), &mut out);
+        parser.push(1, event(EV_SYN, SYN_REPORT, 0), &mut out);
+        let after_keyboard = out.len();
+        parser.push(0, event(EV_SYN, SYN_REPORT, 0), &mut out);
+
+        // Assert
+        assert_eq!(after_keyboard, 1, "only the key event is complete");
+        assert!(matches!(
+            out[0],
+            RawInputEvent::KeyDown {
+                key: HidKeyCode::KeyA,
+                ..
+            }
+        ));
+        assert!(matches!(out[1], RawInputEvent::MouseMove { x: 970, .. }));
+        assert!(parser.has_pressed(1));
+        assert!(!parser.has_pressed(0));
+    }
+
+    #[test]
+    fn test_device_classification_from_capability_bits() {
+        // Arrange – bitmaps as EVIOCGBIT returns them.
+        let mut ev_key_rel = vec![0u8; 4];
+        ev_key_rel[0] = 1 << EV_KEY | 1 << EV_REL;
+        let mut keyboard_keys = vec![0u8; KEY_MAX / 8 + 1];
+        keyboard_keys[usize::from(KEY_A / 8)] |= 1 << (KEY_A % 8);
+        let mut mouse_keys = vec![0u8; KEY_MAX / 8 + 1];
+        mouse_keys[usize::from(BTN_LEFT / 8)] |= 1 << (BTN_LEFT % 8);
+        let rel_xy = vec![0b11, 0];
+        let no_rel = vec![0, 0];
+
+        // Act / Assert
+        assert!(is_keyboard_or_mouse(&ev_key_rel, &keyboard_keys, &no_rel));
+        assert!(is_keyboard_or_mouse(&ev_key_rel, &mouse_keys, &rel_xy));
+        // A power button or a touchpad (no REL_X) is not captured.
+        assert!(!is_keyboard_or_mouse(&ev_key_rel, &mouse_keys, &no_rel));
+        assert!(!is_keyboard_or_mouse(&[0; 4], &keyboard_keys, &rel_xy));
+    }
+
+    #[test]
+    fn test_ioctl_numbers_match_linux_headers() {
+        assert_eq!(EVIOCGRAB, 0x4004_4590);
+        assert_eq!(eviocgbit(0, 4), 0x8004_4520);
+        assert_eq!(eviocgbit(EV_KEY, 96), 0x8060_4521);
+    }
+}
@@ -29,6 +29,7 @@ use super::{CaptureError, InputSource, RawInputEvent};
 pub struct MockInputSource {
     sender: Arc<Mutex<Option<Sender<RawInputEvent>>>>,
     suppress_count: Arc<Mutex<u32>>,
+    exclusive_calls: Arc<Mutex<Vec<bool>>>,
 }
 
 impl MockInputSource {
@@ -37,6 +38,7 @@ impl MockInputSource {
         Self {
             sender: Arc::new(Mutex::new(None)),
             suppress_count: Arc::new(Mutex::new(0)),
+            exclusive_calls: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -58,6 +60,13 @@ impl MockInputSource {
     pub fn suppress_count(&self) -> u32 {
         *self.suppress_count.lock().expect("lock poisoned")
     }
+
+    /// Returns the arguments of every [`set_exclusive`] call, in order.
+    ///
+    /// [`set_exclusive`]: InputSource::set_exclusive
+    pub fn exclusive_calls(&self) -> Vec<bool> {
+        self.exclusive_calls.lock().expect("lock poisoned").clone()
+    }
 }
 
 impl Default for MockInputSource {
@@ -82,12 +91,20 @@ impl InputSource for MockInputSource {
         let mut count = self.suppress_count.lock().expect("lock poisoned");
         *count += 1;
     }
+
+    fn set_exclusive(&self, exclusive: bool) {
+        self.exclusive_calls
+            .lock()
+            .expect("lock poisoned")
+            .push(exclusive);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::infrastructure::input_capture::MouseButton;
+    use kvm_core::keymap::HidKeyCode;
 
     #[test]
     fn test_mock_input_source_starts_and_receives_events() {
@@ -97,17 +114,19 @@ mod tests {
 
         // Act
         source.inject_event(RawInputEvent::KeyDown {
-            vk_code: 0x41,
-            scan_code: 0x1E,
+            key: HidKeyCode::KeyA,
+            scan_code: Some(0x1E),
             time_ms: 0,
-            is_extended: false,
         });
 
         // Assert
         let event = rx.recv().expect("should receive event");
         assert!(matches!(
             event,
-            RawInputEvent::KeyDown { vk_code: 0x41, .. }
+            RawInputEvent::KeyDown {
+                key: HidKeyCode::KeyA,
+                ..
+            }
         ));
     }
 
@@ -140,6 +159,19 @@ mod tests {
         assert_eq!(source.suppress_count(), 3);
     }
 
+    #[test]
+    fn test_mock_input_source_records_exclusive_calls() {
+        // Arrange
+        let source = MockInputSource::new();
+
+        // Act
+        source.set_exclusive(true);
+        source.set_exclusive(false);
+
+        // Assert
+        assert_eq!(source.exclusive_calls(), [true, false]);
+    }
+
     #[test]
     fn test_mock_input_source_inject_multiple_event_types() {
         // Arrange
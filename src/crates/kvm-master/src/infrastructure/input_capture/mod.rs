@@ -4,6 +4,9 @@
 //! WH_MOUSE_LL) on a dedicated Win32 message loop thread. Raw events are placed
 //! into a lock-free channel and consumed by the Tokio async runtime.
 //!
+//! On Linux, [`linux::LinuxEvdevInputSource`] reads the kernel's evdev devices
+//! (`/dev/input/event*`) on a dedicated thread instead; see that module.
+//!
 //! # Windows-Specific Implementation
 //!
 //! The hook callbacks must complete within ~300ms or Windows will remove the hook.
@@ -38,34 +41,44 @@
 //! `CallNextHookEx`.  This prevents the event from reaching the local desktop
 //! (e.g., so that mouse movements to a client screen do not also move the local
 //! cursor to some unintended position).
+//!
+//! evdev cannot withhold single events; the Linux source grabs its devices
+//! for as long as [`InputSource::set_exclusive`] is on instead.
 
 use std::sync::mpsc;
 
+use kvm_core::keymap::HidKeyCode;
+
 pub mod mock;
 
 #[cfg(target_os = "windows")]
 pub mod windows;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 /// A raw input event produced by the input capture infrastructure.
+///
+/// Key events are platform-neutral: each [`InputSource`] translates its
+/// native key codes to a [`HidKeyCode`] before sending, so the application
+/// layer never sees Windows VK codes or evdev codes.
 #[derive(Debug, Clone)]
 pub enum RawInputEvent {
-    /// A key was pressed down.
+    /// A key was pressed down (repeated while the key auto-repeats).
     KeyDown {
-        /// Windows Virtual Key code.
-        vk_code: u8,
-        /// Hardware scan code.
-        scan_code: u16,
-        /// Milliseconds since system start (from the hook struct).
+        /// The key's HID Usage ID.  Keys without one are not reported.
+        key: HidKeyCode,
+        /// The native scan code, if the platform reports one (the hardware
+        /// scan code on Windows, the evdev key code on Linux).
+        scan_code: Option<u16>,
+        /// Milliseconds since an arbitrary platform epoch.
         time_ms: u32,
-        /// `true` if this is an extended key (e.g., right-side modifiers, numpad Enter).
-        is_extended: bool,
     },
     /// A key was released.
     KeyUp {
-        vk_code: u8,
-        scan_code: u16,
+        key: HidKeyCode,
+        scan_code: Option<u16>,
         time_ms: u32,
-        is_extended: bool,
     },
     /// The mouse cursor moved to an absolute screen position.
     MouseMove {
@@ -128,11 +141,14 @@ pub enum CaptureError {
     AlreadyStopped,
     #[error("platform not supported: {0}")]
     UnsupportedPlatform(String),
+    #[error("input devices unavailable: {0}")]
+    DeviceUnavailable(String),
 }
 
 /// Trait abstracting input event production.
 ///
-/// The production implementation uses Windows hooks; tests use [`mock::MockInputSource`].
+/// The production implementations use Windows hooks or Linux evdev devices;
+/// tests use [`mock::MockInputSource`].
 pub trait InputSource: Send {
     /// Starts the input source and returns a receiver for captured events.
     fn start(&self) -> Result<mpsc::Receiver<RawInputEvent>, CaptureError>;
@@ -140,4 +156,10 @@ pub trait InputSource: Send {
     fn stop(&self);
     /// Instructs the source to suppress the current event (if applicable).
     fn suppress_current_event(&self);
+    /// Keeps all captured input from the local desktop while `exclusive` is
+    /// `true`.  Call it with `true` when a client becomes active and `false`
+    /// when focus returns to the master.
+    ///
+    /// The default does nothing, for sources that suppress per event instead.
+    fn set_exclusive(&self, _exclusive: bool) {}
 }
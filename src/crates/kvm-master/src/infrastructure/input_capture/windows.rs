@@ -35,6 +35,7 @@ use std::sync::mpsc::{self, Sender};
 use std::sync::OnceLock;
 use std::thread;
 
+use kvm_core::keymap::{HidKeyCode, KeyMapper};
 use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
 // NOTE: We deliberately do NOT import MOUSEEVENTF_WHEEL / MOUSEEVENTF_HWHEEL here.
 // Those constants are for SendInput() output, not for WH_MOUSE_LL input matching.
@@ -176,6 +177,18 @@ fn run_hook_message_loop() {
     }
 }
 
+/// Translates a hook's virtual key to a HID usage.
+///
+/// Windows reports keypad Enter as `VK_RETURN` with the extended flag set;
+/// every other key is identified by its VK alone.
+fn hid_for_vk(vk_code: u8, is_extended: bool) -> HidKeyCode {
+    const VK_RETURN: u8 = 0x0D;
+    if vk_code == VK_RETURN && is_extended {
+        return HidKeyCode::NumpadEnter;
+    }
+    KeyMapper::windows_vk_to_hid(vk_code)
+}
+
 /// Low-level keyboard hook callback.
 ///
 /// # Safety
@@ -195,23 +208,24 @@ unsafe extern "system" fn keyboard_hook_proc(
     // SAFETY: l_param points to a KBDLLHOOKSTRUCT when n_code == HC_ACTION.
     let kbs = &*(l_param.0 as *const KBDLLHOOKSTRUCT);
 
-    let vk_code = kbs.vkCode as u8;
-    let scan_code = kbs.scanCode as u16;
-    let time_ms = kbs.time;
     let is_extended = (kbs.flags & LLKHF_EXTENDED) != KBDLLHOOKSTRUCT_FLAGS(0);
+    let key = hid_for_vk(kbs.vkCode as u8, is_extended);
+    if key == HidKeyCode::Unknown {
+        return CallNextHookEx(None, n_code, w_param, l_param);
+    }
+    let scan_code = Some(kbs.scanCode as u16);
+    let time_ms = kbs.time;
 
     let event = match w_param.0 as u32 {
         WM_KEYDOWN | WM_SYSKEYDOWN => RawInputEvent::KeyDown {
-            vk_code,
+            key,
             scan_code,
             time_ms,
-            is_extended,
         },
         WM_KEYUP | WM_SYSKEYUP => RawInputEvent::KeyUp {
-            vk_code,
+            key,
             scan_code,
             time_ms,
-            is_extended,
         },
         _ => {
             return CallNextHookEx(None, n_code, w_param, l_param);
@@ -0,0 +1,313 @@
+//! The task that owns the [`RouteInputUseCase`].
+//!
+//! Three things drive the router: events from the [`InputSource`],
+//! [`FocusRequest`]s from the UI bridge, and new [`VirtualLayout`]s built when
+//! the user saves a layout or a client reports its monitors.  [`run_router`]
+//! waits on all of them and hands each to the use case in the order it
+//! arrives, so neither a focus change nor a layout change ever races with a
+//! mouse movement that is being routed.
+//!
+//! # Bridging the capture thread (for beginners)
+//!
+//! Input sources deliver events on a `std::sync::mpsc` channel, because they
+//! run on plain OS threads (the Windows hook thread, the evdev reader).
+//! Waiting on such a channel blocks the thread, which must never happen inside
+//! a Tokio task.  [`forward_captured`] therefore moves the blocking `recv()`
+//! onto a thread of its own that passes each event into a Tokio channel,
+//! which the router can `.await` alongside the focus requests.
+//!
+//! # Errors
+//!
+//! A failure to route one event (typically a client that has just
+//! disconnected) is logged and the router carries on with the next one.
+//!
+//! [`InputSource`]: crate::infrastructure::input_capture::InputSource
+
+use std::sync::mpsc as std_mpsc;
+use std::thread;
+
+use kvm_core::domain::layout::VirtualLayout;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::application::route_input::{FocusRequest, RouteInputUseCase};
+use crate::infrastructure::input_capture::RawInputEvent;
+
+/// Capacity of the channel between the capture thread and the router.
+const CAPTURED_EVENT_BUFFER: usize = 1024;
+
+/// Passes the events of a capture channel into a Tokio channel.
+///
+/// The forwarding thread ends when the capture channel closes (the source was
+/// stopped) or the returned receiver is dropped.
+pub fn forward_captured(
+    captured: std_mpsc::Receiver<RawInputEvent>,
+) -> mpsc::Receiver<RawInputEvent> {
+    let (tx, rx) = mpsc::channel(CAPTURED_EVENT_BUFFER);
+    let spawned = thread::Builder::new()
+        .name("kvm-capture-forward".to_string())
+        .spawn(move || {
+            while let Ok(event) = captured.recv() {
+                if tx.blocking_send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("failed to start the capture forwarding thread: {e}");
+    }
+    rx
+}
+
+/// Routes captured events, UI focus requests and layout updates until all
+/// three channels close.
+///
+/// Returns the use case, so its final state can be inspected.
+pub async fn run_router(
+    mut use_case: RouteInputUseCase,
+    mut events: mpsc::Receiver<RawInputEvent>,
+    mut focus_requests: mpsc::Receiver<FocusRequest>,
+    mut layout_updates: mpsc::Receiver<VirtualLayout>,
+) -> RouteInputUseCase {
+    let mut events_open = true;
+    let mut requests_open = true;
+    let mut layouts_open = true;
+    while events_open || requests_open || layouts_open {
+        tokio::select! {
+            event = events.recv(), if events_open => match event {
+                Some(event) => {
+                    if let Err(e) = use_case.handle_event(event).await {
+                        warn!("input not routed: {e}");
+                    }
+                }
+                None => events_open = false,
+            },
+            request = focus_requests.recv(), if requests_open => match request {
+                Some(request) => {
+                    debug!("focus request {request:?}");
+                    if let Err(e) = use_case.handle_focus_request(request).await {
+                        warn!("focus request not carried out: {e}");
+                    }
+                }
+                None => requests_open = false,
+            },
+            layout = layout_updates.recv(), if layouts_open => match layout {
+                Some(layout) => {
+                    debug!("layout updated ({} clients)", layout.clients().count());
+                    use_case.update_layout(layout);
+                }
+                None => layouts_open = false,
+            },
+        }
+    }
+    use_case
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use kvm_core::clipboard::ClipboardContent;
+    use kvm_core::domain::layout::{
+        Adjacency, ClientScreen, Edge, ScreenId, ScreenRegion, VirtualLayout,
+    };
+    use kvm_core::protocol::messages::{
+        KeyEventMessage, MouseButtonMessage, MouseMoveMessage, MouseScrollMessage,
+    };
+    use kvm_core::ClientId;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::application::hotkeys::HotkeyBindings;
+    use crate::application::route_input::{ActiveTarget, CursorController, InputTransmitter};
+
+    /// Records the mouse movements sent to clients.
+    #[derive(Default)]
+    struct MoveRecorder {
+        moves: Mutex<Vec<(ClientId, MouseMoveMessage)>>,
+    }
+
+    #[async_trait]
+    impl InputTransmitter for MoveRecorder {
+        async fn send_key_event(&self, _: ClientId, _: KeyEventMessage) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn send_mouse_move(
+            &self,
+            client_id: ClientId,
+            event: MouseMoveMessage,
+        ) -> Result<(), String> {
+            self.moves.lock().unwrap().push((client_id, event));
+            Ok(())
+        }
+
+        async fn send_mouse_button(
+            &self,
+            _: ClientId,
+            _: MouseButtonMessage,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn send_mouse_scroll(
+            &self,
+            _: ClientId,
+            _: MouseScrollMessage,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn send_clipboard(&self, _: ClientId, _: ClipboardContent) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    struct FixedCursor;
+
+    impl CursorController for FixedCursor {
+        fn teleport_cursor(&self, _x: i32, _y: i32) {}
+
+        fn get_cursor_pos(&self) -> (i32, i32) {
+            (0, 0)
+        }
+    }
+
+    /// A 1920×1080 master with one client of the same size to its right.
+    fn router_with_client(cid: ClientId) -> (RouteInputUseCase, Arc<MoveRecorder>) {
+        let transmitter = Arc::new(MoveRecorder::default());
+        let mut use_case = RouteInputUseCase::new(
+            1920,
+            1080,
+            Arc::clone(&transmitter) as Arc<dyn InputTransmitter>,
+            Arc::new(FixedCursor),
+            HotkeyBindings::default(),
+        );
+        let mut layout = VirtualLayout::new(1920, 1080);
+        layout
+            .add_client(ClientScreen::new(
+                cid,
+                "right".to_string(),
+                ScreenRegion {
+                    virtual_x: 1920,
+                    virtual_y: 0,
+                    width: 1920,
+                    height: 1080,
+                },
+            ))
+            .unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Master,
+                from_edge: Edge::Right,
+                to_screen: ScreenId::Client(cid),
+                to_edge: Edge::Left,
+                segment: None,
+            })
+            .unwrap();
+        use_case.update_layout(layout);
+        (use_case, transmitter)
+    }
+
+    #[tokio::test]
+    async fn test_focus_requests_reach_the_use_case() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (use_case, transmitter) = router_with_client(cid);
+        let (events_tx, events_rx) = mpsc::channel(1);
+        let (focus_tx, focus_rx) = mpsc::channel(4);
+        let (_, layout_rx) = mpsc::channel(1);
+        focus_tx
+            .send(FocusRequest::SwitchTo(ScreenId::Client(cid)))
+            .await
+            .unwrap();
+        drop(focus_tx);
+        drop(events_tx);
+
+        // Act
+        let use_case = run_router(use_case, events_rx, focus_rx, layout_rx).await;
+
+        // Assert
+        assert_eq!(use_case.get_active_target(), &ActiveTarget::Client(cid));
+        assert_eq!(transmitter.moves.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_captured_events_are_forwarded_and_routed() {
+        // Arrange
+        let cid = Uuid::new_v4();
+        let (use_case, _) = router_with_client(cid);
+        let (captured_tx, captured_rx) = std_mpsc::channel();
+        let (_, focus_rx) = mpsc::channel(1);
+        let (_, layout_rx) = mpsc::channel(1);
+        captured_tx
+            .send(RawInputEvent::MouseMove {
+                x: 1919,
+                y: 540,
+                time_ms: 0,
+            })
+            .unwrap();
+        drop(captured_tx);
+
+        // Act
+        let use_case =
+            run_router(use_case, forward_captured(captured_rx), focus_rx, layout_rx).await;
+
+        // Assert – the move onto the right edge crossed to the client.
+        assert_eq!(use_case.get_active_target(), &ActiveTarget::Client(cid));
+    }
+
+    #[tokio::test]
+    async fn test_layout_update_replaces_the_router_layout() {
+        // Arrange – the client moves from the right of the master to its left.
+        let cid = Uuid::new_v4();
+        let (use_case, _) = router_with_client(cid);
+        let (events_tx, events_rx) = mpsc::channel(4);
+        let (_, focus_rx) = mpsc::channel(1);
+        let (layout_tx, layout_rx) = mpsc::channel(1);
+        let mut layout = VirtualLayout::new(1920, 1080);
+        layout
+            .add_client(ClientScreen::new(
+                cid,
+                "left".to_string(),
+                ScreenRegion {
+                    virtual_x: -1920,
+                    virtual_y: 0,
+                    width: 1920,
+                    height: 1080,
+                },
+            ))
+            .unwrap();
+        layout
+            .set_adjacency(Adjacency {
+                from_screen: ScreenId::Master,
+                from_edge: Edge::Left,
+                to_screen: ScreenId::Client(cid),
+                to_edge: Edge::Right,
+                segment: None,
+            })
+            .unwrap();
+        let router = tokio::spawn(run_router(use_case, events_rx, focus_rx, layout_rx));
+        layout_tx.send(layout).await.unwrap();
+        drop(layout_tx);
+        tokio::task::yield_now().await;
+
+        // Act – the cursor reaches the left edge.
+        events_tx
+            .send(RawInputEvent::MouseMove {
+                x: 0,
+                y: 540,
+                time_ms: 0,
+            })
+            .await
+            .unwrap();
+        drop(events_tx);
+        let use_case = router.await.unwrap();
+
+        // Assert
+        assert_eq!(use_case.get_active_target(), &ActiveTarget::Client(cid));
+    }
+}
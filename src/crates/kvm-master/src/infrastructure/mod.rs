@@ -24,6 +24,8 @@
 //!
 //! - **`input_capture`** – Windows low-level hooks that intercept keyboard and
 //!   mouse events before they reach the local desktop.
+//! - **`input_routing`** – the task that feeds captured events and UI focus
+//!   requests to the `RouteInputUseCase`.
 //! - **`network`**       – TCP control channel, pairing state machine, and UDP
 //!   discovery responder.
 //! - **`storage`**       – TOML configuration file read/write.
@@ -31,6 +33,7 @@
 //!   to the React UI.
 
 pub mod input_capture;
+pub mod input_routing;
 pub mod network;
 pub mod storage;
 pub mod ui_bridge;
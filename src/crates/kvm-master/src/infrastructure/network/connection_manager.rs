@@ -6,33 +6,138 @@
 //!
 //! - **Active pairing sessions** – A pairing session is created when a new client
 //!   connects and needs to prove it is authorised.  The master shows a 6-digit PIN
-//!   and waits for the client to submit a hash of (PIN + session UUID).
+//!   and runs a SPAKE2 key exchange with the client, keyed by that PIN.
 //!
 //! - **Lockout tracking** – If a client submits the wrong PIN more than a configured
 //!   number of times, it is locked out for a period.  This prevents brute-force
 //!   PIN guessing.
 //!
-//! - **Session tokens** – After pairing succeeds, a 32-byte random token is issued.
-//!   The client must present this token when opening the secondary (input) channel.
+//! - **Session tokens** – When a client's `Hello` is accepted, a 32-byte random token
+//!   from the OS RNG is issued in the `HelloAck`.
+//!   Both sides derive the keys of the UDP input channel from it (see
+//!   [`ConnectionManager::start_input_channel`]).
 //!
-//! # Security note on PIN hashing
+//! # PIN pairing
 //!
-//! The PIN is never sent over the network in plaintext.  Instead, the client
-//! sends `SHA-256(PIN || session_id)` where `||` is concatenation.  The
-//! `session_id` is a random UUID that changes for every pairing attempt, so
-//! an attacker cannot reuse a captured response for a different session.
-//! This is a simplified challenge-response scheme (not full PAKE, but
-//! sufficient for a trusted local network).
+//! The PIN is drawn from the OS random number generator and never crosses the
+//! network, not even hashed.  Instead both sides run SPAKE2 (see
+//! `kvm_core::crypto::pairing`):
+//!
+//! ```text
+//! initiate_pairing()          ── PairingRequest { session, master share } ──►
+//!                             ◄── PairingResponse { client share, client MAC } ──
+//! verify_pairing_response()
+//!   ├─ MAC wrong → Error(PairingFailed) and a fresh PairingRequest; counts
+//!   │              towards the lockout
+//!   └─ MAC right → pin certificate, PairingConfirm { master MAC } ──►
+//! ```
+//!
+//! A recorded exchange gives an attacker nothing to brute-force offline; each
+//! guess needs a live attempt, and `MAX_PIN_ATTEMPTS` failures lock the
+//! client's IP out for `LOCKOUT_DURATION`.  The exchange also mixes in the
+//! certificate fingerprints both sides see, so a successful pairing proves
+//! there is no TLS man-in-the-middle and the pinned certificate really is the
+//! client's.
+//!
+//! # Control channel listener
+//!
+//! [`ConnectionManager::start_control_listener`] binds the TCP control port and
+//! runs one Tokio task per accepted socket:
+//!
+//! ```text
+//! accept()
+//!   └─ read first frame (must be Hello, within HELLO_TIMEOUT)
+//!        ├─ invalid → HelloAck { accepted: false, reject_reason } → close
+//!        └─ valid   → HelloAck { accepted: true, session_token }
+//!                       ├─ register ClientSession, emit ClientConnected
+//!                       ├─ start the heartbeat task
+//!                       ├─ serve Ping / Pong / ScreenInfo / ClipboardData /
+//!                       │  Disconnect until EOF or the session is closed
+//!                       └─ unregister, emit ClientDisconnected
+//! ```
+//!
+//! Each accepted client gets a [`ClientSession`] holding a queue to a
+//! dedicated writer task, so any number of callers can send to the client
+//! concurrently without sharing the socket.
+//!
+//! # Session resumption
+//!
+//! A client that lost its connection presents the `session_token` of its last
+//! `HelloAck` as `Hello.resume_token`.  The master accepts it as proof that
+//! this is the same client picking up where it left off if it matches
+//!
+//! - the client's still-registered session (the master has not noticed the
+//!   old socket die yet), or
+//! - a session that ended less than `RESUME_WINDOW` ago, connected with the
+//!   same certificate.
+//!
+//! A resumed session keeps the client's last `ScreenInfo`, so the client does
+//! not need to send its layout again, and `HelloAck.resumed` tells it so.  The
+//! new session still gets a fresh `session_token`: the UDP input keys are
+//! derived from it, and reusing a token would reuse nonces under the same key.
+//!
+//! # Heartbeat
+//!
+//! Every session also gets a heartbeat task (see [`super::heartbeat`]).  It
+//! sends `Ping` every `NetworkConfig::heartbeat.interval`, measures the
+//! round trip from the matching `Pong`, and emits `LinkStatsUpdated` with the
+//! smoothed latency and the events forwarded per second.  After
+//! `max_missed` unanswered `Ping`s it sends `Disconnect { reason: Timeout }`
+//! and closes the session, which ends in the usual `ClientDisconnected`.
+//!
+//! # TLS and certificate pinning
+//!
+//! Once [`ConnectionManager::enable_tls`] has been called, every accepted
+//! socket must complete a mutual-TLS handshake before the `Hello` is read.
+//! Both sides use self-signed certificates (see `kvm_core::crypto::tls`), so
+//! the handshake alone proves only that the client owns *some* key pair.
+//! Identity is tied to a key pair at pairing time:
+//!
+//! 1. A successful `verify_pairing_response` records the fingerprint of the
+//!    certificate the client was connected with when pairing started, and emits
+//!    `PairingCompleted { fingerprint }` so the application can persist it to
+//!    `ClientEntry.pairing_hash`.
+//! 2. On startup the application hands the persisted fingerprints back via
+//!    [`ConnectionManager::restore_pairings`].
+//! 3. A later `Hello` for a paired client ID is rejected with
+//!    `AuthenticationFailed` unless the connection's certificate has the
+//!    recorded fingerprint.
+//!
+//! Without `enable_tls` the listener speaks plaintext; this exists for tests
+//! and for debugging with packet captures, never for production use.
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
-use kvm_core::ClientId;
+use futures_util::StreamExt;
+use kvm_core::clipboard::{self, ClipboardContent, ClipboardReassembler};
+use kvm_core::crypto::pairing::{self, Role, Spake2};
+use kvm_core::crypto::tls::{CertFingerprint, TlsError, TlsIdentity};
+use kvm_core::protocol::codec::encode_message_with_version;
+use kvm_core::protocol::messages::{
+    capabilities, DisconnectReason, ErrorMessage, HelloAckMessage, HelloMessage, KvmMessage,
+    PairingConfirmMessage, PairingRequestMessage, PairingResponseMessage, PlatformId,
+    ProtocolErrorCode, ScreenInfoMessage, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+use kvm_core::protocol::negotiation::{negotiate_version, Negotiated, NegotiationError};
+use kvm_core::protocol::{Frame, FrameError, KvmCodec, SequenceCounter};
+use kvm_core::{ClientId, ProtocolError};
 use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_util::codec::FramedRead;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use super::heartbeat::{HeartbeatConfig, LinkMonitor, Tick};
+use super::input_channel::InputChannel;
+use crate::infrastructure::storage::config::ClientEntry;
+
 /// Error type for connection management operations.
 #[derive(Debug, Error)]
 pub enum NetworkError {
@@ -46,6 +151,16 @@ pub enum NetworkError {
     Pairing(String),
     #[error("client not found: {0}")]
     ClientNotFound(ClientId),
+    #[error("control channel to client {0} is closed")]
+    ConnectionClosed(ClientId),
+    #[error("protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+    #[error("control channel I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TLS setup failed: {0}")]
+    Tls(#[from] TlsError),
+    #[error("not negotiated with the client: {0}")]
+    NotNegotiated(#[from] NegotiationError),
 }
 
 /// Error type specific to pairing.
@@ -59,6 +174,10 @@ pub enum PairingError {
     LockedOut { seconds_remaining: u64 },
     #[error("pairing session expired")]
     Expired,
+    #[error("the client declined to pair")]
+    Declined,
+    #[error("client {0} is not connected")]
+    NotConnected(ClientId),
 }
 
 /// Configuration for the network service.
@@ -68,6 +187,8 @@ pub struct NetworkConfig {
     pub input_port: u16,
     pub discovery_port: u16,
     pub bind_address: std::net::IpAddr,
+    /// Keepalive timing for control-channel sessions.
+    pub heartbeat: HeartbeatConfig,
 }
 
 impl Default for NetworkConfig {
@@ -77,6 +198,7 @@ impl Default for NetworkConfig {
             input_port: 24801,
             discovery_port: 24802,
             bind_address: "0.0.0.0".parse().unwrap(),
+            heartbeat: HeartbeatConfig::default(),
         }
     }
 }
@@ -102,17 +224,31 @@ pub enum ConnectionEvent {
     },
     ClientConnected {
         client_id: ClientId,
+        /// The client resumed its previous session (see the module docs);
+        /// its screen info and pairing state carry over.
+        resumed: bool,
     },
     ClientDisconnected {
         client_id: ClientId,
     },
+    /// A pairing session started; show `pin` to the user at the master.
     PairingRequested {
         client_id: ClientId,
         session_id: Uuid,
         pin: String,
     },
+    /// A client answered a pairing request.  Pass it to
+    /// [`ConnectionManager::verify_pairing_response`].
+    PairingResponseReceived {
+        client_id: ClientId,
+        address: SocketAddr,
+        response: PairingResponseMessage,
+    },
     PairingCompleted {
         client_id: ClientId,
+        /// Certificate fingerprint pinned for the client; `None` when TLS is
+        /// disabled.  Persist this as `ClientEntry.pairing_hash`.
+        fingerprint: Option<CertFingerprint>,
     },
     PairingFailed {
         client_id: ClientId,
@@ -122,38 +258,432 @@ pub enum ConnectionEvent {
         client_id: ClientId,
         monitor_count: u8,
     },
+    /// Fresh link measurements, emitted once per heartbeat interval.
+    LinkStatsUpdated {
+        client_id: ClientId,
+        /// Smoothed `Ping`/`Pong` round-trip time; `None` until the client
+        /// has answered a `Ping`.
+        latency_ms: Option<f32>,
+        /// Input events forwarded to the client per second.
+        events_per_second: u32,
+    },
+    /// A paired client sent a complete clipboard entry (the user copied
+    /// something there).  Write it into the master's clipboard.
+    ClipboardReceived {
+        client_id: ClientId,
+        content: ClipboardContent,
+    },
 }
 
 /// Active pairing session.
-#[derive(Debug)]
+///
+/// Not `Debug`: it holds the PIN.
 struct PairingSession {
     client_id: ClientId,
-    pin_hash: String,
+    /// Certificate the client was connected with when pairing started; this
+    /// is what gets pinned if the exchange succeeds.
+    client_fingerprint: Option<CertFingerprint>,
+    /// The PIN shown at the master.  Each attempt runs a fresh exchange with
+    /// it, because a SPAKE2 exchange can only be finished once.
+    pin: String,
+    binding: Vec<u8>,
+    /// The master's half of the exchange for the client's next attempt.
+    spake: Spake2,
     created_at: Instant,
+    expires_at_secs: u64,
     attempts: u8,
 }
 
-/// Per-IP lockout state.
-#[derive(Debug)]
-struct LockoutEntry {
-    locked_until: Instant,
-    _failed_attempts: u8,
+impl PairingSession {
+    /// The request that tells the client which exchange to answer.
+    fn request(&self, session_id: Uuid) -> PairingRequestMessage {
+        PairingRequestMessage {
+            pairing_session_id: session_id,
+            expires_at_secs: self.expires_at_secs,
+            spake_message: self.spake.message(),
+        }
+    }
+
+    /// Takes the exchange for the current attempt and starts a fresh one, with
+    /// the same PIN, for the attempt after it.
+    fn take_attempt(&mut self, session_id: Uuid) -> Spake2 {
+        let next = Spake2::start(Role::Master, &self.pin, session_id, &self.binding);
+        std::mem::replace(&mut self.spake, next)
+    }
+}
+
+/// A freshly started pairing session, returned by
+/// [`ConnectionManager::initiate_pairing`].
+#[derive(Debug, Clone)]
+pub struct PairingChallenge {
+    /// Identifies the session in the client's `PairingResponse`.
+    pub session_id: Uuid,
+    /// The PIN to show the user at the master.  Never send it to the client.
+    pub pin: String,
+    /// The request for the client.  It has already been queued on the
+    /// client's control channel if the client is connected.
+    pub request: PairingRequestMessage,
 }
 
 const MAX_PIN_ATTEMPTS: u8 = 3;
 const LOCKOUT_DURATION: Duration = Duration::from_secs(60);
 const PAIRING_EXPIRY: Duration = Duration::from_secs(60);
 
+/// Capabilities the master offers.  Each session enables the subset the
+/// client advertises as well.
+pub const MASTER_CAPABILITIES: u32 = capabilities::KEYBOARD_EMULATION
+    | capabilities::MOUSE_EMULATION
+    | capabilities::CLIPBOARD_SHARING
+    | capabilities::CLIPBOARD_HTML
+    | capabilities::CLIPBOARD_IMAGE
+    | capabilities::MULTI_MONITOR;
+
+/// How long a freshly accepted socket may take to send its `Hello`.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the accept loop wakes up to check the shutdown flag.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Number of encoded frames that may be queued for one client's writer task.
+const OUTBOUND_QUEUE_DEPTH: usize = 256;
+/// How long after a session ends its client may still resume it.
+const RESUME_WINDOW: Duration = Duration::from_secs(60);
+
+/// An item queued for a client's writer task.
+#[derive(Debug)]
+enum OutboundFrame {
+    /// A fully encoded message (header + payload).
+    Data(Vec<u8>),
+    /// Flush everything queued so far, then shut down the write half.
+    Close,
+}
+
+/// A live control-channel connection to one client.
+///
+/// Created by the control listener once the client's `Hello` has been
+/// accepted, and removed again when the socket closes.  Sessions are handed out
+/// as `Arc<ClientSession>` so other components (for example the input
+/// transmitter) can send to a client without holding the `ConnectionManager`
+/// lock.
+pub struct ClientSession {
+    client_id: ClientId,
+    name: String,
+    address: SocketAddr,
+    platform_id: PlatformId,
+    capabilities: u32,
+    /// Protocol version and capabilities agreed in the handshake.
+    negotiated: Negotiated,
+    session_token: [u8; 32],
+    /// Fingerprint of the certificate presented in the TLS handshake.
+    peer_fingerprint: Option<CertFingerprint>,
+    /// Sequence numbers for frames sent on this connection.
+    sequence: SequenceCounter,
+    /// Queue drained by the writer task that owns the socket's write half.
+    outbound: mpsc::Sender<OutboundFrame>,
+    /// Round-trip time and throughput, kept up to date by the heartbeat.
+    link: LinkMonitor,
+    /// The client's last reported screen layout.
+    screen_info: Mutex<Option<ScreenInfoMessage>>,
+}
+
+impl ClientSession {
+    /// The client's UUID, as announced in its `Hello`.
+    pub fn client_id(&self) -> ClientId {
+        self.client_id
+    }
+
+    /// Human-readable client name from the `Hello`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Remote socket address of the control connection.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Operating system reported by the client.
+    pub fn platform_id(&self) -> PlatformId {
+        self.platform_id
+    }
+
+    /// Capability bitmask reported by the client (see `capabilities`).
+    pub fn capabilities(&self) -> u32 {
+        self.capabilities
+    }
+
+    /// Protocol version and capabilities agreed for this session.
+    pub fn negotiated(&self) -> Negotiated {
+        self.negotiated
+    }
+
+    /// The random token issued to this client in its `HelloAck`.
+    pub fn session_token(&self) -> &[u8; 32] {
+        &self.session_token
+    }
+
+    /// Fingerprint of the client's TLS certificate, or `None` on a plaintext
+    /// connection.
+    pub fn peer_fingerprint(&self) -> Option<CertFingerprint> {
+        self.peer_fingerprint
+    }
+
+    /// The screen layout the client last reported, carried over from the
+    /// previous session when the client resumed.
+    pub fn screen_info(&self) -> Option<ScreenInfoMessage> {
+        self.screen_info
+            .lock()
+            .expect("screen info lock poisoned")
+            .clone()
+    }
+
+    /// Returns `false` once the writer task has exited (socket closed).
+    pub fn is_open(&self) -> bool {
+        !self.outbound.is_closed()
+    }
+
+    /// Latency and throughput measurements for this connection.
+    ///
+    /// Senders of input events call `link().record_event()` for each event
+    /// delivered, so the heartbeat can report events per second.
+    pub fn link(&self) -> &LinkMonitor {
+        &self.link
+    }
+
+    /// Encodes `msg` with this connection's next sequence number and queues it
+    /// for transmission.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkError::NotNegotiated`] if the client did not advertise
+    /// a capability the message needs, [`NetworkError::Protocol`] if the
+    /// message cannot be encoded, or [`NetworkError::ConnectionClosed`] if the
+    /// connection has gone away.
+    pub async fn send(&self, msg: &KvmMessage) -> Result<(), NetworkError> {
+        let bytes = self.encode(msg)?;
+        self.outbound
+            .send(OutboundFrame::Data(bytes))
+            .await
+            .map_err(|_| NetworkError::ConnectionClosed(self.client_id))
+    }
+
+    /// Like [`send`](Self::send), but fails instead of waiting when the
+    /// outbound queue is full.  For use from synchronous code.
+    ///
+    /// # Errors
+    ///
+    /// As for `send`; a full queue is reported as
+    /// [`NetworkError::ConnectionClosed`].
+    pub fn try_send(&self, msg: &KvmMessage) -> Result<(), NetworkError> {
+        let bytes = self.encode(msg)?;
+        self.outbound
+            .try_send(OutboundFrame::Data(bytes))
+            .map_err(|_| NetworkError::ConnectionClosed(self.client_id))
+    }
+
+    /// Checks `msg` against the negotiated terms and encodes it with the
+    /// session's protocol version and next sequence number.
+    fn encode(&self, msg: &KvmMessage) -> Result<Vec<u8>, NetworkError> {
+        self.negotiated.check(msg)?;
+        Ok(encode_message_with_version(
+            msg,
+            self.negotiated.version(),
+            self.sequence.next(),
+            now_us(),
+        )?)
+    }
+
+    /// Asks the writer task to flush and close the connection.
+    async fn close(&self) {
+        let _ = self.outbound.send(OutboundFrame::Close).await;
+    }
+
+    /// Resolves once the writer task has exited.
+    async fn closed(&self) {
+        self.outbound.closed().await;
+    }
+}
+
+impl std::fmt::Debug for ClientSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The session token is deliberately left out so it never ends up in logs.
+        f.debug_struct("ClientSession")
+            .field("client_id", &self.client_id)
+            .field("name", &self.name)
+            .field("address", &self.address)
+            .field("platform_id", &self.platform_id)
+            .field("capabilities", &self.capabilities)
+            .field("negotiated", &self.negotiated)
+            .field("peer_fingerprint", &self.peer_fingerprint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Cheaply cloneable handle to the set of live client sessions.
+///
+/// The listener tasks add and remove entries; everything else (the manager,
+/// the input transmitter) only looks sessions up.  Cloning the handle shares
+/// the same underlying map.
+#[derive(Clone, Default)]
+pub struct SessionRegistry {
+    inner: Arc<RwLock<HashMap<ClientId, Arc<ClientSession>>>>,
+}
+
+impl SessionRegistry {
+    /// Returns the live session for `client_id`, if the client is connected.
+    pub fn get(&self, client_id: ClientId) -> Option<Arc<ClientSession>> {
+        self.inner
+            .read()
+            .expect("session registry lock poisoned")
+            .get(&client_id)
+            .cloned()
+    }
+
+    /// Returns every live session.
+    pub fn all(&self) -> Vec<Arc<ClientSession>> {
+        self.inner
+            .read()
+            .expect("session registry lock poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Registers `session`, returning the session it replaced (if any).
+    fn insert(&self, session: Arc<ClientSession>) -> Option<Arc<ClientSession>> {
+        self.inner
+            .write()
+            .expect("session registry lock poisoned")
+            .insert(session.client_id, session)
+    }
+
+    /// Removes `session` if it is still the registered session for its client.
+    ///
+    /// Returns `false` when a newer connection from the same client has
+    /// already replaced it.
+    fn remove_if_current(&self, session: &Arc<ClientSession>) -> bool {
+        let mut map = self.inner.write().expect("session registry lock poisoned");
+        if map
+            .get(&session.client_id)
+            .is_some_and(|current| Arc::ptr_eq(current, session))
+        {
+            map.remove(&session.client_id);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// What the master remembers about a session after it ended, so its client
+/// can resume it.
+struct EndedSession {
+    session_token: [u8; 32],
+    peer_fingerprint: Option<CertFingerprint>,
+    screen_info: Option<ScreenInfoMessage>,
+    expires_at: Instant,
+}
+
+/// Cheaply cloneable handle to the recently ended sessions, one per client.
+#[derive(Clone, Default)]
+struct ResumableSessions {
+    inner: Arc<Mutex<HashMap<ClientId, EndedSession>>>,
+}
+
+impl ResumableSessions {
+    /// Remembers `session` for `RESUME_WINDOW`, dropping entries that have
+    /// expired in the meantime.
+    fn remember(&self, session: &ClientSession, now: Instant) {
+        let mut map = self.inner.lock().expect("resumable sessions lock poisoned");
+        map.retain(|_, ended| ended.expires_at > now);
+        map.insert(
+            session.client_id,
+            EndedSession {
+                session_token: session.session_token,
+                peer_fingerprint: session.peer_fingerprint,
+                screen_info: session.screen_info(),
+                expires_at: now + RESUME_WINDOW,
+            },
+        );
+    }
+
+    /// Takes the ended session of `client_id` if `token` and `fingerprint`
+    /// match it and it has not expired.  Returns its screen info.
+    ///
+    /// The entry is consumed either way, so a token is good for one attempt.
+    fn take(
+        &self,
+        client_id: ClientId,
+        token: &[u8; 32],
+        fingerprint: Option<CertFingerprint>,
+        now: Instant,
+    ) -> Option<Option<ScreenInfoMessage>> {
+        let ended = self
+            .inner
+            .lock()
+            .expect("resumable sessions lock poisoned")
+            .remove(&client_id)?;
+        let valid = ended.expires_at > now
+            && ended.peer_fingerprint == fingerprint
+            && tokens_match(&ended.session_token, token);
+        valid.then_some(ended.screen_info)
+    }
+}
+
+/// Cheaply cloneable handle to the paired clients and their pinned
+/// certificate fingerprints.
+///
+/// Shared with the listener tasks, which check pins during the handshake.  A
+/// `None` value means the client was paired over a plaintext connection, so
+/// there is no certificate to pin.
+#[derive(Clone, Default)]
+struct PairedClients {
+    inner: Arc<RwLock<HashMap<ClientId, Option<CertFingerprint>>>>,
+}
+
+impl PairedClients {
+    fn contains(&self, client_id: ClientId) -> bool {
+        self.inner
+            .read()
+            .expect("paired clients lock poisoned")
+            .contains_key(&client_id)
+    }
+
+    /// Returns the pinned fingerprint, if the client is paired and pinned.
+    fn pin(&self, client_id: ClientId) -> Option<CertFingerprint> {
+        self.inner
+            .read()
+            .expect("paired clients lock poisoned")
+            .get(&client_id)
+            .copied()
+            .flatten()
+    }
+
+    fn insert(&self, client_id: ClientId, fingerprint: Option<CertFingerprint>) {
+        self.inner
+            .write()
+            .expect("paired clients lock poisoned")
+            .insert(client_id, fingerprint);
+    }
+}
+
 /// The connection manager.
 ///
-/// In the full implementation this would manage TLS listeners. For now
-/// it provides the state machine and pairing logic, which are fully testable.
+/// Owns the pairing state machine and, once [`start_control_listener`] has
+/// been called, the set of live control-channel sessions.
+///
+/// [`start_control_listener`]: ConnectionManager::start_control_listener
 pub struct ConnectionManager {
-    _config: NetworkConfig,
+    config: NetworkConfig,
     pairing_sessions: HashMap<Uuid, PairingSession>,
-    lockouts: HashMap<std::net::IpAddr, LockoutEntry>,
-    paired_clients: HashMap<ClientId, String>, // client_id -> cert fingerprint
-    _event_tx: mpsc::Sender<ConnectionEvent>,
+    /// When each locked-out IP may try pairing again.
+    lockouts: HashMap<std::net::IpAddr, Instant>,
+    paired_clients: PairedClients,
+    sessions: SessionRegistry,
+    resumable: ResumableSessions,
+    /// Present once `enable_tls` has been called.
+    tls: Option<TlsAcceptor>,
+    /// Fingerprint of our own certificate, mixed into pairing exchanges.
+    own_fingerprint: Option<CertFingerprint>,
+    event_tx: mpsc::Sender<ConnectionEvent>,
 }
 
 impl ConnectionManager {
@@ -161,23 +691,188 @@ impl ConnectionManager {
     pub fn new(config: NetworkConfig) -> (Self, mpsc::Receiver<ConnectionEvent>) {
         let (tx, rx) = mpsc::channel(64);
         let mgr = Self {
-            _config: config,
+            config,
             pairing_sessions: HashMap::new(),
             lockouts: HashMap::new(),
-            paired_clients: HashMap::new(),
-            _event_tx: tx,
+            paired_clients: PairedClients::default(),
+            sessions: SessionRegistry::default(),
+            resumable: ResumableSessions::default(),
+            tls: None,
+            own_fingerprint: None,
+            event_tx: tx,
         };
         (mgr, rx)
     }
 
+    /// Requires TLS, presenting `identity`, on every control connection
+    /// accepted from now on.
+    ///
+    /// Call this before [`start_control_listener`](Self::start_control_listener).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkError::Tls`] if the identity cannot be used.
+    pub fn enable_tls(&mut self, identity: &TlsIdentity) -> Result<(), NetworkError> {
+        self.tls = Some(TlsAcceptor::from(identity.server_config()?));
+        self.own_fingerprint = Some(identity.fingerprint());
+        Ok(())
+    }
+
+    /// Marks `client_id` as paired with the certificate `fingerprint`, as
+    /// loaded from the persisted configuration.
+    pub fn restore_pairing(&mut self, client_id: ClientId, fingerprint: CertFingerprint) {
+        self.paired_clients.insert(client_id, Some(fingerprint));
+    }
+
+    /// Restores the pairings persisted in the configuration's client list.
+    ///
+    /// An entry whose `pairing_hash` does not parse is logged and skipped, so
+    /// that client has to pair again.
+    pub fn restore_pairings(&mut self, clients: &[ClientEntry]) {
+        for entry in clients {
+            let Some(hash) = entry.pairing_hash.as_deref() else {
+                continue;
+            };
+            match hash.parse::<CertFingerprint>() {
+                Ok(fp) => self.restore_pairing(entry.client_id, fp),
+                Err(e) => warn!(
+                    "ignoring pairing for client {} ({}): {e}; it must pair again",
+                    entry.client_id, entry.name
+                ),
+            }
+        }
+    }
+
+    /// Returns a check for whether a client is paired, usable from other
+    /// threads (such as the discovery responder) without the manager lock.
+    pub fn pairing_check(&self) -> impl Fn(ClientId) -> bool + Send + Sync + 'static {
+        let paired = self.paired_clients.clone();
+        move |client_id| paired.contains(client_id)
+    }
+
+    /// Returns the certificate fingerprint pinned for `client_id`, if any.
+    pub fn paired_fingerprint(&self, client_id: ClientId) -> Option<CertFingerprint> {
+        self.paired_clients.pin(client_id)
+    }
+
+    /// Binds the TCP control port and starts accepting client connections.
+    ///
+    /// Returns the address actually bound, which differs from the configured
+    /// one when `control_port` is `0` (useful in tests).  The accept loop runs
+    /// on a background Tokio task until `running` is set to `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkError::BindFailed`] if the port cannot be bound.
+    pub async fn start_control_listener(
+        &self,
+        running: Arc<AtomicBool>,
+    ) -> Result<SocketAddr, NetworkError> {
+        let addr = SocketAddr::new(self.config.bind_address, self.config.control_port);
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|source| NetworkError::BindFailed { addr, source })?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|source| NetworkError::BindFailed { addr, source })?;
+
+        let ctx = ListenerContext {
+            tls: self.tls.clone(),
+            heartbeat: self.config.heartbeat,
+            sessions: self.sessions.clone(),
+            resumable: self.resumable.clone(),
+            paired_clients: self.paired_clients.clone(),
+            event_tx: self.event_tx.clone(),
+        };
+        tokio::spawn(accept_loop(listener, ctx, running));
+
+        Ok(local_addr)
+    }
+
+    /// Binds the UDP input channel on `input_port` and starts serving it.
+    ///
+    /// Hand the returned handle to `NetworkInputTransmitter::with_input_channel`
+    /// so high-frequency input can bypass the control channel.  Clients
+    /// register their UDP address themselves; until one has, input for it keeps
+    /// going over TCP.  The receive loop runs until `running` is set to `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkError::BindFailed`] if the port cannot be bound.
+    pub async fn start_input_channel(
+        &self,
+        running: Arc<AtomicBool>,
+    ) -> Result<InputChannel, NetworkError> {
+        let addr = SocketAddr::new(self.config.bind_address, self.config.input_port);
+        InputChannel::start(addr, self.sessions.clone(), running).await
+    }
+
+    /// Returns the live session for `client_id`, if the client is connected.
+    pub fn session(&self, client_id: ClientId) -> Option<Arc<ClientSession>> {
+        self.sessions.get(client_id)
+    }
+
+    /// Returns a shared handle to the live sessions.
+    ///
+    /// Components that send to clients from outside the manager (such as
+    /// `NetworkInputTransmitter`) hold this handle so they never need the
+    /// `ConnectionManager` lock on the hot path.
+    pub fn session_registry(&self) -> SessionRegistry {
+        self.sessions.clone()
+    }
+
+    /// Returns a summary of every client with an open control channel.
+    pub fn get_connected_clients(&self) -> Vec<ConnectedClientInfo> {
+        self.sessions
+            .all()
+            .iter()
+            .map(|s| ConnectedClientInfo {
+                client_id: s.client_id,
+                name: s.name.clone(),
+                address: s.address,
+                latency_ms: s
+                    .link
+                    .latency()
+                    .map_or(0.0, |rtt| rtt.as_secs_f32() * 1000.0),
+                events_per_second: s.link.events_per_second(),
+                is_paired: self.is_paired(s.client_id),
+            })
+            .collect()
+    }
+
+    /// Sends `Disconnect { reason }` to a connected client and closes its
+    /// control channel.
+    ///
+    /// The client's connection task emits `ClientDisconnected` once the socket
+    /// has actually closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkError::ClientNotFound`] if the client is not connected.
+    pub async fn disconnect_client(
+        &self,
+        client_id: ClientId,
+        reason: DisconnectReason,
+    ) -> Result<(), NetworkError> {
+        let session = self
+            .session(client_id)
+            .ok_or(NetworkError::ClientNotFound(client_id))?;
+        session.send(&KvmMessage::Disconnect { reason }).await?;
+        session.close().await;
+        Ok(())
+    }
+
     /// Checks whether a client has been paired.
     pub fn is_paired(&self, client_id: ClientId) -> bool {
-        self.paired_clients.contains_key(&client_id)
+        self.paired_clients.contains(client_id)
     }
 
     /// Initiates a new pairing session for a discovered client.
     ///
-    /// Generates a 6-digit PIN, stores a hash, and emits a `PairingRequested` event.
+    /// Draws a random 6-digit PIN, starts the master's half of the SPAKE2
+    /// exchange, and emits a `PairingRequested` event carrying the PIN for
+    /// display.  If the client is connected the `PairingRequest` is queued on
+    /// its control channel straight away.
     ///
     /// # Errors
     ///
@@ -186,42 +881,96 @@ impl ConnectionManager {
         &mut self,
         client_id: ClientId,
         client_addr: std::net::IpAddr,
-    ) -> Result<(Uuid, String), PairingError> {
+    ) -> Result<PairingChallenge, PairingError> {
         self.check_lockout(client_addr)?;
 
-        let pin = generate_pin();
+        let pin = pairing::generate_pin();
         let session_id = Uuid::new_v4();
-        let pin_hash = hash_pin(&pin, &session_id);
+        let live = self.sessions.get(client_id);
+        let client_fingerprint = live.as_ref().and_then(|s| s.peer_fingerprint());
+        let binding = pairing::channel_binding(
+            client_fingerprint.as_ref().map(CertFingerprint::as_bytes),
+            self.own_fingerprint.as_ref().map(CertFingerprint::as_bytes),
+        );
+        let spake = Spake2::start(Role::Master, &pin, session_id, &binding);
+
+        let session = PairingSession {
+            client_id,
+            client_fingerprint,
+            pin: pin.clone(),
+            binding,
+            spake,
+            created_at: Instant::now(),
+            expires_at_secs: unix_now_secs() + PAIRING_EXPIRY.as_secs(),
+            attempts: 0,
+        };
+        let request = session.request(session_id);
+        self.pairing_sessions.insert(session_id, session);
 
-        self.pairing_sessions.insert(
+        if let Some(session) = live {
+            if let Err(e) = session.try_send(&KvmMessage::PairingRequest(request.clone())) {
+                warn!("could not send PairingRequest to client {client_id}: {e}");
+            }
+        }
+        let _ = self.event_tx.try_send(ConnectionEvent::PairingRequested {
+            client_id,
             session_id,
-            PairingSession {
-                client_id,
-                pin_hash,
-                created_at: Instant::now(),
-                attempts: 0,
-            },
-        );
+            pin: pin.clone(),
+        });
 
-        Ok((session_id, pin))
+        Ok(PairingChallenge {
+            session_id,
+            pin,
+            request,
+        })
     }
 
-    /// Verifies a PIN response and completes pairing on success.
+    /// Starts pairing with a connected client, from the address it connected
+    /// from.  This is what the UI's "Pair" button and the headless master's
+    /// automatic pairing use; see [`initiate_pairing`](Self::initiate_pairing).
     ///
     /// # Errors
     ///
-    /// Returns [`PairingError`] variants for wrong PIN, lockout, or expiry.
-    pub fn verify_pairing_pin(
+    /// Returns [`PairingError::NotConnected`] if the client has no live
+    /// session, or [`PairingError::LockedOut`] as for `initiate_pairing`.
+    pub fn pair_connected_client(
         &mut self,
-        session_id: Uuid,
-        submitted_pin_hash: &str,
+        client_id: ClientId,
+    ) -> Result<PairingChallenge, PairingError> {
+        let address = self
+            .session(client_id)
+            .ok_or(PairingError::NotConnected(client_id))?
+            .address();
+        self.initiate_pairing(client_id, address.ip())
+    }
+
+    /// Checks a client's `PairingResponse` and completes pairing on success.
+    ///
+    /// `client_id` is the client the response arrived from; a response for a
+    /// session started for a different client is treated as unknown.  On
+    /// success the client's certificate fingerprint is pinned,
+    /// `PairingCompleted` is emitted, and the master's `PairingConfirm` is
+    /// queued for the client.  A wrong PIN is reported to the client as
+    /// `Error(PairingFailed)`, followed by a new `PairingRequest` for the next
+    /// attempt while any remain.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PairingError`] variants for wrong PIN, lockout, expiry, or a
+    /// declined request.
+    pub fn verify_pairing_response(
+        &mut self,
+        client_id: ClientId,
+        response: &PairingResponseMessage,
         client_addr: std::net::IpAddr,
     ) -> Result<ClientId, PairingError> {
         self.check_lockout(client_addr)?;
 
+        let session_id = response.pairing_session_id;
         let session = self
             .pairing_sessions
             .get_mut(&session_id)
+            .filter(|s| s.client_id == client_id)
             .ok_or(PairingError::SessionNotFound)?;
 
         if session.created_at.elapsed() > PAIRING_EXPIRY {
@@ -229,37 +978,95 @@ impl ConnectionManager {
             return Err(PairingError::Expired);
         }
 
-        if session.pin_hash != submitted_pin_hash {
-            session.attempts += 1;
-            let remaining = MAX_PIN_ATTEMPTS.saturating_sub(session.attempts);
-            if remaining == 0 {
-                self.lockouts.insert(
-                    client_addr,
-                    LockoutEntry {
-                        locked_until: Instant::now() + LOCKOUT_DURATION,
-                        _failed_attempts: MAX_PIN_ATTEMPTS,
-                    },
+        if !response.accepted {
+            self.pairing_sessions.remove(&session_id);
+            self.emit_pairing_failed(client_id, "declined by the client");
+            return Err(PairingError::Declined);
+        }
+
+        // An unparseable share is treated like a wrong PIN: either way the
+        // attempt counts towards the lockout.
+        let keys = session
+            .take_attempt(session_id)
+            .finish(&response.spake_message)
+            .and_then(|keys| {
+                keys.verify_confirmation(Role::Client, &response.confirmation)?;
+                Ok(keys)
+            });
+        let keys = match keys {
+            Ok(keys) => keys,
+            Err(e) => {
+                session.attempts += 1;
+                let remaining = MAX_PIN_ATTEMPTS.saturating_sub(session.attempts);
+                let retry = (remaining > 0).then(|| session.request(session_id));
+                if remaining == 0 {
+                    self.lockouts
+                        .insert(client_addr, Instant::now() + LOCKOUT_DURATION);
+                    self.pairing_sessions.remove(&session_id);
+                }
+                self.send_to_client(
+                    client_id,
+                    KvmMessage::Error(ErrorMessage {
+                        error_code: ProtocolErrorCode::PairingFailed,
+                        description: format!("{e}; {remaining} attempt(s) remaining"),
+                    }),
                 );
-                self.pairing_sessions.remove(&session_id);
+                if let Some(request) = retry {
+                    self.send_to_client(client_id, KvmMessage::PairingRequest(request));
+                }
+                self.emit_pairing_failed(client_id, &e.to_string());
+                return Err(PairingError::WrongPin {
+                    attempts_remaining: remaining,
+                });
             }
-            return Err(PairingError::WrongPin {
-                attempts_remaining: remaining,
-            });
-        }
+        };
 
-        let client_id = session.client_id;
+        let fingerprint = session.client_fingerprint;
         self.pairing_sessions.remove(&session_id);
-        // Store a placeholder cert fingerprint (real implementation uses TLS cert hash)
-        self.paired_clients
-            .insert(client_id, format!("pinned:{client_id}"));
+
+        if fingerprint.is_none() && self.tls.is_some() {
+            warn!("client {client_id} paired without a live TLS session; nothing to pin");
+        }
+        self.paired_clients.insert(client_id, fingerprint);
+        self.send_to_client(
+            client_id,
+            KvmMessage::PairingConfirm(PairingConfirmMessage {
+                pairing_session_id: session_id,
+                confirmation: keys.confirmation(Role::Master),
+            }),
+        );
+        let _ = self.event_tx.try_send(ConnectionEvent::PairingCompleted {
+            client_id,
+            fingerprint,
+        });
         Ok(client_id)
     }
 
+    /// Queues `msg` for `client_id` if it is connected; pairing replies are
+    /// best-effort because the client may already have gone away.
+    fn send_to_client(&self, client_id: ClientId, msg: KvmMessage) {
+        if let Some(session) = self.sessions.get(client_id) {
+            if let Err(e) = session.try_send(&msg) {
+                warn!(
+                    "could not send {:?} to client {client_id}: {e}",
+                    msg.message_type()
+                );
+            }
+        }
+    }
+
+    fn emit_pairing_failed(&self, client_id: ClientId, reason: &str) {
+        let _ = self.event_tx.try_send(ConnectionEvent::PairingFailed {
+            client_id,
+            reason: reason.to_string(),
+        });
+    }
+
     fn check_lockout(&self, addr: std::net::IpAddr) -> Result<(), PairingError> {
-        if let Some(entry) = self.lockouts.get(&addr) {
+        if let Some(&locked_until) = self.lockouts.get(&addr) {
             let now = Instant::now();
-            if now < entry.locked_until {
-                let remaining = (entry.locked_until - now).as_secs();
+            if now < locked_until {
+                let remaining = (locked_until - now).as_secs();
                 return Err(PairingError::LockedOut {
                     seconds_remaining: remaining,
                 });
@@ -269,90 +1076,633 @@ impl ConnectionManager {
     }
 }
 
-/// Generates a cryptographically random 6-digit numeric PIN.
-fn generate_pin() -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    use std::time::SystemTime;
+// ── Control channel tasks ─────────────────────────────────────────────────────
 
-    // Simple determinism-free generation using system time + thread ID.
-    // Production code should use `rand` crate with OsRng.
-    let mut hasher = DefaultHasher::new();
-    SystemTime::now().hash(&mut hasher);
-    std::thread::current().id().hash(&mut hasher);
-    let n = hasher.finish() % 1_000_000;
-    format!("{n:06}")
+/// Accepts control connections until `running` is cleared.
+///
+/// `accept()` is wrapped in a short timeout so the loop notices the shutdown
+/// flag even when no client is connecting.
+async fn accept_loop(listener: TcpListener, ctx: ListenerContext, running: Arc<AtomicBool>) {
+    while running.load(Ordering::Relaxed) {
+        match tokio::time::timeout(ACCEPT_POLL_INTERVAL, listener.accept()).await {
+            Ok(Ok((stream, peer))) => {
+                debug!("control connection from {peer}");
+                tokio::spawn(serve_connection(stream, peer, ctx.clone()));
+            }
+            Ok(Err(e)) => warn!("control listener accept error: {e}"),
+            // Poll interval elapsed – loop round and re-check `running`.
+            Err(_) => {}
+        }
+    }
+    info!("control channel listener stopped");
 }
 
-/// Hashes a PIN with the session ID using a simple scheme.
-///
-/// Production code should use PBKDF2 or Argon2.
-fn hash_pin(pin: &str, session_id: &Uuid) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+/// Everything a connection task needs from the manager.
+#[derive(Clone)]
+struct ListenerContext {
+    tls: Option<TlsAcceptor>,
+    heartbeat: HeartbeatConfig,
+    sessions: SessionRegistry,
+    resumable: ResumableSessions,
+    paired_clients: PairedClients,
+    event_tx: mpsc::Sender<ConnectionEvent>,
+}
 
-    let mut hasher = DefaultHasher::new();
-    pin.hash(&mut hasher);
-    session_id.as_bytes().hash(&mut hasher);
-    format!("{:016x}", hasher.finish())
+/// Performs the TLS handshake (if enabled) for an accepted socket, then
+/// serves it.
+async fn serve_connection(stream: TcpStream, peer: SocketAddr, ctx: ListenerContext) {
+    // Control messages are small and latency-sensitive; don't let Nagle batch them.
+    let _ = stream.set_nodelay(true);
+
+    let Some(acceptor) = ctx.tls.clone() else {
+        serve_stream(stream, peer, None, ctx).await;
+        return;
+    };
+    match tokio::time::timeout(HELLO_TIMEOUT, acceptor.accept(stream)).await {
+        Ok(Ok(tls)) => {
+            let fingerprint = CertFingerprint::of_peer(tls.get_ref().1);
+            serve_stream(tls, peer, fingerprint, ctx).await;
+        }
+        Ok(Err(e)) => warn!("{peer}: TLS handshake failed: {e}"),
+        Err(_) => warn!("{peer}: TLS handshake did not complete within {HELLO_TIMEOUT:?}"),
+    }
 }
 
-// ── Tests ─────────────────────────────────────────────────────────────────────
+/// Runs the `Hello` handshake and message loop on an established stream.
+async fn serve_stream<S>(
+    stream: S,
+    peer: SocketAddr,
+    peer_fingerprint: Option<CertFingerprint>,
+    ctx: ListenerContext,
+) where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let ListenerContext {
+        heartbeat,
+        sessions,
+        resumable,
+        paired_clients,
+        event_tx,
+        ..
+    } = ctx;
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut frames = FramedRead::new(reader, KvmCodec::new());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Step 1: the first frame must be a Hello, and it must arrive promptly.
+    let hello = match tokio::time::timeout(HELLO_TIMEOUT, frames.next()).await {
+        Ok(Some(Ok(Frame {
+            message: KvmMessage::Hello(hello),
+            ..
+        }))) => hello,
+        Ok(Some(Ok(other))) => {
+            warn!(
+                "{peer}: expected Hello, got {:?}",
+                other.header.message_type
+            );
+            reject(&mut writer, ProtocolErrorCode::InvalidMessage).await;
+            return;
+        }
+        // Closed before sending anything (e.g. a port scanner).
+        Ok(None) => return,
+        Ok(Some(Err(e))) => {
+            warn!("{peer}: could not read Hello: {e}");
+            let reason = match e {
+                FrameError::Protocol(ProtocolError::UnsupportedVersion(_)) => {
+                    ProtocolErrorCode::ProtocolVersionMismatch
+                }
+                _ => ProtocolErrorCode::InvalidMessage,
+            };
+            reject(&mut writer, reason).await;
+            return;
+        }
+        Err(_) => {
+            warn!("{peer}: no Hello received within {HELLO_TIMEOUT:?}");
+            return;
+        }
+    };
 
-    fn make_manager() -> (ConnectionManager, mpsc::Receiver<ConnectionEvent>) {
-        ConnectionManager::new(NetworkConfig::default())
+    let verdict = validate_hello(&hello).and_then(|version| {
+        check_pin(paired_clients.pin(hello.client_id), peer_fingerprint).map(|()| version)
+    });
+    let version = match verdict {
+        Ok(version) => version,
+        Err(reason) => {
+            warn!(
+                "{peer}: rejecting Hello from {} ({reason:?})",
+                hello.client_id
+            );
+            reject(&mut writer, reason).await;
+            return;
+        }
+    };
+
+    // Step 2: register the session and acknowledge.  The ack goes through the
+    // session queue so it is guaranteed to be the first frame the client sees.
+    let client_id = hello.client_id;
+    let resumed = hello
+        .resume_token
+        .and_then(|token| resume(&sessions, &resumable, client_id, &token, peer_fingerprint));
+    let (out_tx, out_rx) = mpsc::channel(OUTBOUND_QUEUE_DEPTH);
+    tokio::spawn(write_loop(writer, out_rx));
+
+    let session = Arc::new(ClientSession {
+        client_id,
+        name: hello.client_name,
+        address: peer,
+        platform_id: hello.platform_id,
+        capabilities: hello.capabilities,
+        negotiated: Negotiated::new(version, MASTER_CAPABILITIES, hello.capabilities),
+        session_token: generate_session_token(),
+        peer_fingerprint,
+        sequence: SequenceCounter::new(),
+        outbound: out_tx,
+        link: LinkMonitor::new(),
+        screen_info: Mutex::new(resumed.clone().flatten()),
+    });
+
+    let previous = sessions.insert(Arc::clone(&session));
+    if let Some(previous) = previous {
+        // The client reconnected before we noticed its old socket die.
+        info!("client {client_id} reconnected from {peer}; closing stale session");
+        previous.close().await;
     }
 
-    #[test]
-    fn test_initiate_pairing_returns_six_digit_pin() {
-        let (mut mgr, _rx) = make_manager();
-        let client_id = Uuid::new_v4();
-        let addr: std::net::IpAddr = "192.168.1.1".parse().unwrap();
-        let (_session_id, pin) = mgr.initiate_pairing(client_id, addr).unwrap();
-        assert_eq!(pin.len(), 6, "PIN must be exactly 6 digits");
-        assert!(
-            pin.chars().all(|c| c.is_ascii_digit()),
-            "PIN must contain only digits"
-        );
+    let ack = KvmMessage::HelloAck(HelloAckMessage {
+        session_token: session.session_token,
+        server_version: version,
+        accepted: true,
+        reject_reason: 0,
+        capabilities: session.negotiated.capabilities(),
+        resumed: resumed.is_some(),
+    });
+    if let Err(e) = session.send(&ack).await {
+        warn!("{peer}: failed to queue HelloAck: {e}");
     }
 
-    #[test]
-    fn test_verify_pairing_pin_succeeds_with_correct_hash() {
-        let (mut mgr, _rx) = make_manager();
-        let client_id = Uuid::new_v4();
-        let addr: std::net::IpAddr = "192.168.1.2".parse().unwrap();
-        let (session_id, pin) = mgr.initiate_pairing(client_id, addr).unwrap();
-        let pin_hash = hash_pin(&pin, &session_id);
+    info!(
+        "client {client_id} ({}) {} from {peer}",
+        session.name,
+        if resumed.is_some() {
+            "resumed"
+        } else {
+            "connected"
+        }
+    );
+    let _ = event_tx
+        .send(ConnectionEvent::ClientConnected {
+            client_id,
+            resumed: resumed.is_some(),
+        })
+        .await;
+    tokio::spawn(heartbeat_loop(
+        Arc::clone(&session),
+        heartbeat,
+        event_tx.clone(),
+    ));
 
-        let result = mgr.verify_pairing_pin(session_id, &pin_hash, addr);
-        assert_eq!(result, Ok(client_id));
+    // Step 3: serve the connection until either side closes it.  Closing the
+    // session (a timeout, `disconnect_client`, a reconnect) ends the loop even
+    // if the peer never closes its end.
+    let mut clipboard = ClipboardReassembler::default();
+    loop {
+        let result = tokio::select! {
+            next = frames.next() => match next {
+                Some(result) => result,
+                None => break,
+            },
+            () = session.closed() => break,
+        };
+        match result {
+            Ok(frame) => {
+                let keep_open = handle_session_message(
+                    &session,
+                    frame.message,
+                    &paired_clients,
+                    &mut clipboard,
+                    &event_tx,
+                )
+                .await;
+                if !keep_open {
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!("client {client_id}: control channel error: {e}");
+                break;
+            }
+        }
     }
 
-    #[test]
-    fn test_verify_pairing_pin_fails_with_wrong_hash() {
-        let (mut mgr, _rx) = make_manager();
-        let client_id = Uuid::new_v4();
-        let addr: std::net::IpAddr = "192.168.1.3".parse().unwrap();
-        let (session_id, _pin) = mgr.initiate_pairing(client_id, addr).unwrap();
+    // Step 4: unregister – unless a newer connection from the same client has
+    // already replaced this one, in which case the client is still connected.
+    let was_current = sessions.remove_if_current(&session);
+    session.close().await;
 
-        let result = mgr.verify_pairing_pin(session_id, "wrong_hash", addr);
-        assert!(matches!(result, Err(PairingError::WrongPin { .. })));
+    if was_current {
+        info!("client {client_id} disconnected");
+        resumable.remember(&session, Instant::now());
+        let _ = event_tx
+            .send(ConnectionEvent::ClientDisconnected { client_id })
+            .await;
+    }
+}
+
+/// Handles one message received after the handshake.
+///
+/// Clipboard data is accepted only from paired clients.
+///
+/// Returns `false` when the connection should be closed.
+async fn handle_session_message(
+    session: &ClientSession,
+    msg: KvmMessage,
+    paired_clients: &PairedClients,
+    clipboard: &mut ClipboardReassembler,
+    event_tx: &mpsc::Sender<ConnectionEvent>,
+) -> bool {
+    let client_id = session.client_id;
+    match msg {
+        KvmMessage::Ping(token) => {
+            if session.send(&KvmMessage::Pong(token)).await.is_err() {
+                return false;
+            }
+        }
+        KvmMessage::Pong(token) => match session.link.pong(token, Instant::now()) {
+            Some(rtt) => debug!("client {client_id}: round trip {rtt:?}"),
+            None => debug!("client {client_id}: ignoring unexpected Pong({token})"),
+        },
+        KvmMessage::ScreenInfo(info) => {
+            let monitor_count = info.monitors.len().min(u8::MAX as usize) as u8;
+            *session
+                .screen_info
+                .lock()
+                .expect("screen info lock poisoned") = Some(info);
+            if session.send(&KvmMessage::ScreenInfoAck).await.is_err() {
+                return false;
+            }
+            let _ = event_tx
+                .send(ConnectionEvent::ScreenInfoUpdated {
+                    client_id,
+                    monitor_count,
+                })
+                .await;
+        }
+        KvmMessage::ClipboardData(_) if !paired_clients.contains(client_id) => {
+            debug!("client {client_id}: ignoring clipboard data from an unpaired client");
+        }
+        KvmMessage::ClipboardData(fragment)
+            if session
+                .negotiated
+                .supports(clipboard::required_capabilities(fragment.format)) =>
+        {
+            match clipboard.push(fragment) {
+                Ok(Some(content)) => {
+                    let _ = event_tx
+                        .send(ConnectionEvent::ClipboardReceived { client_id, content })
+                        .await;
+                }
+                Ok(None) => {}
+                Err(e) => warn!("client {client_id}: dropping clipboard transfer: {e}"),
+            }
+        }
+        KvmMessage::Disconnect { reason } => {
+            info!("client {client_id} sent Disconnect ({reason:?})");
+            return false;
+        }
+        KvmMessage::PairingResponse(response) => {
+            // Verification needs the manager's pairing state, which the
+            // connection task does not own; the application forwards it.
+            let _ = event_tx
+                .send(ConnectionEvent::PairingResponseReceived {
+                    client_id,
+                    address: session.address,
+                    response,
+                })
+                .await;
+        }
+        other => {
+            debug!(
+                "client {client_id}: ignoring {:?} on control channel",
+                other.message_type()
+            );
+        }
+    }
+    true
+}
+
+/// Checks a `Hello.resume_token` against the client's previous session.
+///
+/// Returns the screen info to carry over (`Some(None)` if the old session
+/// never received one), or `None` if the token resumes nothing.
+fn resume(
+    sessions: &SessionRegistry,
+    resumable: &ResumableSessions,
+    client_id: ClientId,
+    token: &[u8; 32],
+    fingerprint: Option<CertFingerprint>,
+) -> Option<Option<ScreenInfoMessage>> {
+    if let Some(live) = sessions.get(client_id) {
+        return (live.peer_fingerprint == fingerprint && tokens_match(&live.session_token, token))
+            .then(|| live.screen_info());
+    }
+    resumable.take(client_id, token, fingerprint, Instant::now())
+}
+
+/// Compares two session tokens in constant time, so the time a comparison
+/// takes does not reveal how many leading bytes of a guess were right.
+fn tokens_match(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Checks a client's `Hello` and returns the protocol version to use with it,
+/// or the rejection reason if it is unacceptable.
+fn validate_hello(hello: &HelloMessage) -> Result<u8, ProtocolErrorCode> {
+    let version = negotiate_version(hello.protocol_version)
+        .map_err(|_| ProtocolErrorCode::ProtocolVersionMismatch)?;
+    // A nil UUID means the client never generated an identity; we could not
+    // tell it apart from any other unconfigured client.
+    if hello.client_id.is_nil() {
+        return Err(ProtocolErrorCode::InvalidMessage);
+    }
+    Ok(version)
+}
+
+/// Checks the certificate a client connected with against the fingerprint
+/// pinned when it was paired.
+///
+/// Unpaired clients (and clients paired without TLS) have no pin and pass; they
+/// still have to complete pairing before the application trusts them.
+fn check_pin(
+    pinned: Option<CertFingerprint>,
+    presented: Option<CertFingerprint>,
+) -> Result<(), ProtocolErrorCode> {
+    match pinned {
+        Some(expected) if presented != Some(expected) => {
+            Err(ProtocolErrorCode::AuthenticationFailed)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sends a negative `HelloAck` and closes the write half.
+///
+/// The frame uses the oldest version we speak, so every supported client can
+/// read why it was turned away.
+async fn reject<W: AsyncWrite + Unpin>(writer: &mut W, reason: ProtocolErrorCode) {
+    let ack = KvmMessage::HelloAck(HelloAckMessage {
+        session_token: [0u8; 32],
+        server_version: PROTOCOL_VERSION,
+        accepted: false,
+        reject_reason: reason as u8,
+        capabilities: 0,
+        resumed: false,
+    });
+    match encode_message_with_version(&ack, MIN_PROTOCOL_VERSION, 0, now_us()) {
+        Ok(bytes) => {
+            let _ = writer.write_all(&bytes).await;
+            let _ = writer.shutdown().await;
+        }
+        Err(e) => warn!("failed to encode HelloAck rejection: {e}"),
+    }
+}
+
+/// Pings the client every `config.interval` and reports link statistics
+/// until the session closes.
+///
+/// The first `Ping` goes out one interval after the handshake, so the
+/// `HelloAck` and any immediate replies are never interleaved with it.
+async fn heartbeat_loop(
+    session: Arc<ClientSession>,
+    config: HeartbeatConfig,
+    event_tx: mpsc::Sender<ConnectionEvent>,
+) {
+    let client_id = session.client_id;
+    let mut ticker = tokio::time::interval_at(
+        tokio::time::Instant::now() + config.interval,
+        config.interval,
+    );
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_sample = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            () = session.closed() => return,
+        }
+        let now = Instant::now();
+        let events_per_second = session
+            .link
+            .sample_throughput(now.duration_since(last_sample));
+        last_sample = now;
+
+        match session.link.tick(now, config.max_missed) {
+            Tick::Ping(token) => {
+                if session.send(&KvmMessage::Ping(token)).await.is_err() {
+                    return;
+                }
+            }
+            Tick::TimedOut => {
+                warn!(
+                    "client {client_id}: no Pong for {} Pings; disconnecting",
+                    config.max_missed
+                );
+                let _ = session
+                    .send(&KvmMessage::Disconnect {
+                        reason: DisconnectReason::Timeout,
+                    })
+                    .await;
+                session.close().await;
+                return;
+            }
+        }
+
+        let _ = event_tx
+            .send(ConnectionEvent::LinkStatsUpdated {
+                client_id,
+                latency_ms: session.link.latency().map(|rtt| rtt.as_secs_f32() * 1000.0),
+                events_per_second,
+            })
+            .await;
+    }
+}
+
+/// Drains a session's outbound queue onto the socket.
+///
+/// Exits (dropping the receiver, which marks the session closed) when the
+/// queue is closed, a `Close` frame arrives, or a write fails.
+async fn write_loop<W: AsyncWrite + Unpin>(mut writer: W, mut rx: mpsc::Receiver<OutboundFrame>) {
+    while let Some(frame) = rx.recv().await {
+        match frame {
+            OutboundFrame::Data(bytes) => {
+                // Flushing matters for TLS, which buffers records until asked.
+                let written = match writer.write_all(&bytes).await {
+                    Ok(()) => writer.flush().await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = written {
+                    debug!("control channel write failed: {e}");
+                    break;
+                }
+            }
+            OutboundFrame::Close => break,
+        }
+    }
+    let _ = writer.shutdown().await;
+}
+
+/// Generates a 32-byte session token from the OS random number generator.
+fn generate_session_token() -> [u8; 32] {
+    let mut token = [0u8; 32];
+    getrandom::fill(&mut token).expect("OS random number generator unavailable");
+    token
+}
+
+/// Current wall-clock time in seconds since the Unix epoch.
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Current wall-clock time in microseconds, for frame timestamps.
+fn now_us() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvm_core::protocol::codec::encode_message_now;
+    use kvm_core::protocol::messages::{ClipboardDataMessage, ClipboardFormat, MessageType};
+
+    fn make_manager() -> (ConnectionManager, mpsc::Receiver<ConnectionEvent>) {
+        ConnectionManager::new(NetworkConfig::default())
+    }
+
+    /// Answers `request` the way a client on a plaintext connection would.
+    fn client_response(request: &PairingRequestMessage, pin: &str) -> PairingResponseMessage {
+        let binding = pairing::channel_binding(None, None);
+        let client = Spake2::start(Role::Client, pin, request.pairing_session_id, &binding);
+        let spake_message = client.message();
+        let keys = client.finish(&request.spake_message).unwrap();
+        PairingResponseMessage {
+            pairing_session_id: request.pairing_session_id,
+            spake_message,
+            confirmation: keys.confirmation(Role::Client),
+            accepted: true,
+        }
+    }
+
+    /// A PIN guaranteed to differ from `pin`.
+    fn wrong_pin(pin: &str) -> String {
+        if pin == "000000" { "000001" } else { "000000" }.to_string()
+    }
+
+    #[test]
+    fn test_initiate_pairing_returns_six_digit_pin() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.1".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        assert_eq!(challenge.pin.len(), 6, "PIN must be exactly 6 digits");
+        assert!(
+            challenge.pin.chars().all(|c| c.is_ascii_digit()),
+            "PIN must contain only digits"
+        );
+        assert_eq!(challenge.request.pairing_session_id, challenge.session_id);
+    }
+
+    #[test]
+    fn test_initiate_pairing_emits_pin_for_display() {
+        let (mut mgr, mut rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.8".parse().unwrap();
+
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ConnectionEvent::PairingRequested { client_id: id, session_id, pin })
+                if id == client_id && session_id == challenge.session_id && pin == challenge.pin
+        ));
     }
 
     #[test]
-    fn test_verify_pairing_pin_locks_out_after_three_failures() {
+    fn test_pair_connected_client_requires_a_live_session() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+
+        let result = mgr.pair_connected_client(client_id);
+
+        assert!(matches!(result, Err(PairingError::NotConnected(id)) if id == client_id));
+    }
+
+    #[test]
+    fn test_verify_pairing_response_succeeds_with_correct_pin() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.2".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = client_response(&challenge.request, &challenge.pin);
+
+        let result = mgr.verify_pairing_response(client_id, &response, addr);
+        assert_eq!(result, Ok(client_id));
+    }
+
+    #[test]
+    fn test_verify_pairing_response_fails_with_wrong_pin() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.3".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = client_response(&challenge.request, &wrong_pin(&challenge.pin));
+
+        let result = mgr.verify_pairing_response(client_id, &response, addr);
+        assert_eq!(
+            result,
+            Err(PairingError::WrongPin {
+                attempts_remaining: 2
+            })
+        );
+        assert!(!mgr.is_paired(client_id));
+    }
+
+    #[test]
+    fn test_verify_pairing_response_accepts_correct_pin_after_a_wrong_guess() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.9".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+
+        let wrong = client_response(&challenge.request, &wrong_pin(&challenge.pin));
+        let _ = mgr.verify_pairing_response(client_id, &wrong, addr);
+
+        // The retry answers the fresh exchange the master sends after a failure.
+        let retry = mgr.pairing_sessions[&challenge.session_id].request(challenge.session_id);
+        assert_ne!(retry.spake_message, challenge.request.spake_message);
+        let right = client_response(&retry, &challenge.pin);
+        assert_eq!(
+            mgr.verify_pairing_response(client_id, &right, addr),
+            Ok(client_id)
+        );
+    }
+
+    #[test]
+    fn test_verify_pairing_response_locks_out_after_three_failures() {
         let (mut mgr, _rx) = make_manager();
         let client_id = Uuid::new_v4();
         let addr: std::net::IpAddr = "192.168.1.4".parse().unwrap();
-        let (session_id, _pin) = mgr.initiate_pairing(client_id, addr).unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = client_response(&challenge.request, &wrong_pin(&challenge.pin));
 
         // Three wrong attempts
         for _ in 0..3 {
-            let _ = mgr.verify_pairing_pin(session_id, "bad", addr);
+            let _ = mgr.verify_pairing_response(client_id, &response, addr);
         }
 
         // Next attempt should be locked out
@@ -364,11 +1714,64 @@ mod tests {
     }
 
     #[test]
-    fn test_verify_pairing_pin_returns_session_not_found_for_unknown_session() {
+    fn test_verify_pairing_response_counts_invalid_share_as_wrong_pin() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.10".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let mut response = client_response(&challenge.request, &challenge.pin);
+        response.spake_message = [0xFF; 32];
+
+        let result = mgr.verify_pairing_response(client_id, &response, addr);
+        assert!(matches!(result, Err(PairingError::WrongPin { .. })));
+    }
+
+    #[test]
+    fn test_verify_pairing_response_returns_session_not_found_for_unknown_session() {
         let (mut mgr, _rx) = make_manager();
         let addr: std::net::IpAddr = "192.168.1.5".parse().unwrap();
-        let result = mgr.verify_pairing_pin(Uuid::new_v4(), "any", addr);
+        let response = PairingResponseMessage {
+            pairing_session_id: Uuid::new_v4(),
+            spake_message: [0; 32],
+            confirmation: [0; 32],
+            accepted: true,
+        };
+        let result = mgr.verify_pairing_response(Uuid::new_v4(), &response, addr);
+        assert_eq!(result, Err(PairingError::SessionNotFound));
+    }
+
+    #[test]
+    fn test_verify_pairing_response_ignores_answers_from_another_client() {
+        // Arrange: a session started for one client, answered by another.
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.11".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = client_response(&challenge.request, &challenge.pin);
+
+        // Act
+        let result = mgr.verify_pairing_response(Uuid::new_v4(), &response, addr);
+
+        // Assert
         assert_eq!(result, Err(PairingError::SessionNotFound));
+        assert!(!mgr.is_paired(client_id));
+    }
+
+    #[test]
+    fn test_verify_pairing_response_reports_declined_request() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.12".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = PairingResponseMessage {
+            pairing_session_id: challenge.session_id,
+            spake_message: [0; 32],
+            confirmation: [0; 32],
+            accepted: false,
+        };
+
+        let result = mgr.verify_pairing_response(client_id, &response, addr);
+        assert_eq!(result, Err(PairingError::Declined));
     }
 
     #[test]
@@ -382,34 +1785,743 @@ mod tests {
         let (mut mgr, _rx) = make_manager();
         let client_id = Uuid::new_v4();
         let addr: std::net::IpAddr = "192.168.1.6".parse().unwrap();
-        let (session_id, pin) = mgr.initiate_pairing(client_id, addr).unwrap();
-        let pin_hash = hash_pin(&pin, &session_id);
-        mgr.verify_pairing_pin(session_id, &pin_hash, addr).unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = client_response(&challenge.request, &challenge.pin);
+        mgr.verify_pairing_response(client_id, &response, addr)
+            .unwrap();
         assert!(mgr.is_paired(client_id));
     }
 
+    // ── Control channel ───────────────────────────────────────────────────────
+
+    fn make_hello(protocol_version: u8, client_id: Uuid) -> HelloMessage {
+        HelloMessage {
+            client_id,
+            protocol_version,
+            platform_id: PlatformId::Linux,
+            client_name: "test-client".to_string(),
+            capabilities: 0,
+            resume_token: None,
+        }
+    }
+
+    type ClientStream = FramedRead<TcpStream, KvmCodec>;
+
+    /// Connects a raw test client; write through `get_mut()`.
+    async fn connect_framed(addr: SocketAddr) -> ClientStream {
+        FramedRead::new(TcpStream::connect(addr).await.unwrap(), KvmCodec::new())
+    }
+
+    /// Reads the next message, or `None` once the master closes the socket.
+    async fn next_message(stream: &mut ClientStream) -> Option<KvmMessage> {
+        stream
+            .next()
+            .await
+            .transpose()
+            .expect("decode failed")
+            .map(|frame| frame.message)
+    }
+
+    /// Starts a listener on an ephemeral loopback port.
+    async fn start_loopback_listener() -> (
+        ConnectionManager,
+        mpsc::Receiver<ConnectionEvent>,
+        SocketAddr,
+    ) {
+        start_loopback_listener_with(HeartbeatConfig::default()).await
+    }
+
+    /// Like `start_loopback_listener`, with custom heartbeat timing.
+    async fn start_loopback_listener_with(
+        heartbeat: HeartbeatConfig,
+    ) -> (
+        ConnectionManager,
+        mpsc::Receiver<ConnectionEvent>,
+        SocketAddr,
+    ) {
+        let (mgr, rx) = ConnectionManager::new(NetworkConfig {
+            control_port: 0,
+            bind_address: "127.0.0.1".parse().unwrap(),
+            heartbeat,
+            ..NetworkConfig::default()
+        });
+        let addr = mgr
+            .start_control_listener(Arc::new(AtomicBool::new(true)))
+            .await
+            .unwrap();
+        (mgr, rx, addr)
+    }
+
     #[test]
-    fn test_hash_pin_is_deterministic_for_same_inputs() {
-        let session_id = Uuid::new_v4();
-        let h1 = hash_pin("123456", &session_id);
-        let h2 = hash_pin("123456", &session_id);
-        assert_eq!(h1, h2);
+    fn test_validate_hello_accepts_current_version() {
+        let hello = make_hello(PROTOCOL_VERSION, Uuid::new_v4());
+        assert_eq!(validate_hello(&hello), Ok(PROTOCOL_VERSION));
     }
 
     #[test]
-    fn test_hash_pin_differs_for_different_pins() {
-        let session_id = Uuid::new_v4();
-        let h1 = hash_pin("123456", &session_id);
-        let h2 = hash_pin("654321", &session_id);
-        assert_ne!(h1, h2);
+    fn test_validate_hello_rejects_version_below_minimum() {
+        let hello = make_hello(MIN_PROTOCOL_VERSION - 1, Uuid::new_v4());
+        assert_eq!(
+            validate_hello(&hello),
+            Err(ProtocolErrorCode::ProtocolVersionMismatch)
+        );
+    }
+
+    #[test]
+    fn test_validate_hello_rejects_nil_client_id() {
+        let hello = make_hello(PROTOCOL_VERSION, Uuid::nil());
+        assert_eq!(
+            validate_hello(&hello),
+            Err(ProtocolErrorCode::InvalidMessage)
+        );
+    }
+
+    #[test]
+    fn test_check_pin_passes_unpinned_clients_with_or_without_certificate() {
+        let fp = CertFingerprint::of(b"some certificate");
+        assert_eq!(check_pin(None, None), Ok(()));
+        assert_eq!(check_pin(None, Some(fp)), Ok(()));
+    }
+
+    #[test]
+    fn test_check_pin_requires_the_pinned_certificate() {
+        let pinned = CertFingerprint::of(b"paired certificate");
+        let other = CertFingerprint::of(b"impostor certificate");
+        assert_eq!(check_pin(Some(pinned), Some(pinned)), Ok(()));
+        assert_eq!(
+            check_pin(Some(pinned), Some(other)),
+            Err(ProtocolErrorCode::AuthenticationFailed)
+        );
+        assert_eq!(
+            check_pin(Some(pinned), None),
+            Err(ProtocolErrorCode::AuthenticationFailed)
+        );
     }
 
     #[test]
-    fn test_hash_pin_differs_for_different_sessions() {
-        let s1 = Uuid::new_v4();
-        let s2 = Uuid::new_v4();
-        let h1 = hash_pin("123456", &s1);
-        let h2 = hash_pin("123456", &s2);
-        assert_ne!(h1, h2);
+    fn test_pairing_without_live_session_emits_event_without_fingerprint() {
+        // Arrange
+        let (mut mgr, mut rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let addr: std::net::IpAddr = "192.168.1.7".parse().unwrap();
+        let challenge = mgr.initiate_pairing(client_id, addr).unwrap();
+        let response = client_response(&challenge.request, &challenge.pin);
+        let _ = rx.try_recv(); // PairingRequested
+
+        // Act
+        mgr.verify_pairing_response(client_id, &response, addr)
+            .unwrap();
+
+        // Assert
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ConnectionEvent::PairingCompleted { client_id: id, fingerprint: None }) if id == client_id
+        ));
+        assert_eq!(mgr.paired_fingerprint(client_id), None);
+    }
+
+    #[test]
+    fn test_restore_pairing_marks_client_paired_with_fingerprint() {
+        let (mut mgr, _rx) = make_manager();
+        let client_id = Uuid::new_v4();
+        let fp = CertFingerprint::of(b"persisted certificate");
+
+        mgr.restore_pairing(client_id, fp);
+
+        assert!(mgr.is_paired(client_id));
+        assert_eq!(mgr.paired_fingerprint(client_id), Some(fp));
+    }
+
+    #[test]
+    fn test_restore_pairings_skips_unpaired_and_malformed_entries() {
+        // Arrange
+        let (mut mgr, _rx) = make_manager();
+        let fp = CertFingerprint::of(b"persisted certificate");
+        let entry = |pairing_hash: Option<String>| ClientEntry {
+            client_id: Uuid::new_v4(),
+            name: "client".to_string(),
+            host: None,
+            pairing_hash,
+        };
+        let clients = [
+            entry(Some(fp.to_string())),
+            entry(None),
+            entry(Some("sha256:not-hex".to_string())),
+        ];
+
+        // Act
+        mgr.restore_pairings(&clients);
+
+        // Assert
+        assert_eq!(mgr.paired_fingerprint(clients[0].client_id), Some(fp));
+        assert!(!mgr.is_paired(clients[1].client_id));
+        assert!(!mgr.is_paired(clients[2].client_id));
+    }
+
+    #[test]
+    fn test_generate_session_token_is_random() {
+        let a = generate_session_token();
+        let b = generate_session_token();
+        assert_ne!(a, [0u8; 32]);
+        assert_ne!(a, b, "two tokens must not collide");
+    }
+
+    #[tokio::test]
+    async fn test_listener_accepts_hello_split_across_writes() {
+        // Arrange
+        let (mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_framed(addr).await;
+        let hello = encode_message_now(
+            &KvmMessage::Hello(make_hello(PROTOCOL_VERSION, client_id)),
+            0,
+        )
+        .unwrap();
+
+        // Act: header plus a few payload bytes first, the rest later.
+        stream.get_mut().write_all(&hello[..30]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stream.get_mut().write_all(&hello[30..]).await.unwrap();
+
+        // Assert
+        assert!(
+            matches!(next_message(&mut stream).await, Some(KvmMessage::HelloAck(a)) if a.accepted)
+        );
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { .. })
+        ));
+        assert!(mgr.session(client_id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_closes_connection_without_reading_payload() {
+        // Arrange: a connected client.
+        let (_mgr, mut rx, addr) = start_loopback_listener().await;
+        let mut stream = connect_framed(addr).await;
+        let hello = KvmMessage::Hello(make_hello(PROTOCOL_VERSION, Uuid::new_v4()));
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&hello, 0).unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_message(&mut stream).await,
+            Some(KvmMessage::HelloAck(_))
+        ));
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { .. })
+        ));
+
+        // Act: a header announcing a 4 GiB payload.
+        let mut header = encode_message_now(&KvmMessage::Ping(0), 1).unwrap();
+        header.truncate(kvm_core::protocol::messages::HEADER_SIZE);
+        header[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        stream.get_mut().write_all(&header).await.unwrap();
+
+        // Assert: the master hangs up instead of waiting for 4 GiB.
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for disconnect");
+        assert!(matches!(
+            event,
+            Some(ConnectionEvent::ClientDisconnected { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_listener_rejects_hello_older_than_min_version() {
+        // Arrange
+        let (mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_framed(addr).await;
+        let hello = KvmMessage::Hello(make_hello(MIN_PROTOCOL_VERSION - 1, client_id));
+
+        // Act
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&hello, 0).unwrap())
+            .await
+            .unwrap();
+        let reply = next_message(&mut stream).await;
+
+        // Assert
+        match reply {
+            Some(KvmMessage::HelloAck(ack)) => {
+                assert!(!ack.accepted);
+                assert_eq!(
+                    ack.reject_reason,
+                    ProtocolErrorCode::ProtocolVersionMismatch as u8
+                );
+                assert_eq!(ack.server_version, PROTOCOL_VERSION);
+                assert_eq!(ack.session_token, [0u8; 32]);
+            }
+            other => panic!("expected HelloAck, got {other:?}"),
+        }
+        // The master closes the connection after rejecting.
+        assert!(next_message(&mut stream).await.is_none());
+        assert!(mgr.session(client_id).is_none());
+        assert!(rx.try_recv().is_err(), "no event for a rejected client");
+    }
+
+    #[tokio::test]
+    async fn test_listener_negotiates_down_to_own_version_for_newer_client() {
+        // Arrange
+        let (mgr, _rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_framed(addr).await;
+        let hello = KvmMessage::Hello(HelloMessage {
+            capabilities: capabilities::KEYBOARD_EMULATION | 1 << 31,
+            ..make_hello(PROTOCOL_VERSION + 1, client_id)
+        });
+
+        // Act
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&hello, 0).unwrap())
+            .await
+            .unwrap();
+        let reply = next_message(&mut stream).await;
+
+        // Assert – unknown capability bits are dropped from the agreed set.
+        match reply {
+            Some(KvmMessage::HelloAck(ack)) => {
+                assert!(ack.accepted);
+                assert_eq!(ack.server_version, PROTOCOL_VERSION);
+                assert_eq!(ack.capabilities, capabilities::KEYBOARD_EMULATION);
+            }
+            other => panic!("expected HelloAck, got {other:?}"),
+        }
+        let negotiated = mgr.session(client_id).unwrap().negotiated();
+        assert_eq!(negotiated.version(), PROTOCOL_VERSION);
+        assert_eq!(negotiated.capabilities(), capabilities::KEYBOARD_EMULATION);
+    }
+
+    #[tokio::test]
+    async fn test_session_refuses_message_type_client_did_not_advertise() {
+        // Arrange – the test client advertises keyboard and mouse only.
+        let (mgr, _rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_framed(addr).await;
+        let hello = KvmMessage::Hello(HelloMessage {
+            capabilities: capabilities::KEYBOARD_EMULATION | capabilities::MOUSE_EMULATION,
+            ..make_hello(PROTOCOL_VERSION, client_id)
+        });
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&hello, 0).unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_message(&mut stream).await,
+            Some(KvmMessage::HelloAck(_))
+        ));
+        let session = mgr.session(client_id).unwrap();
+        let clipboard = KvmMessage::ClipboardData(ClipboardDataMessage {
+            format: ClipboardFormat::Utf8Text,
+            data: b"secret".to_vec(),
+            has_more_fragments: false,
+        });
+
+        // Act
+        let result = session.send(&clipboard).await;
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(NetworkError::NotNegotiated(
+                NegotiationError::CapabilityNotNegotiated {
+                    message_type: MessageType::ClipboardData,
+                    missing: capabilities::CLIPBOARD_SHARING,
+                }
+            ))
+        ));
+        assert!(session.try_send(&clipboard).is_err());
+        // Nothing reached the wire; the next frame is the Pong.
+        session.send(&KvmMessage::Pong(5)).await.unwrap();
+        assert_eq!(next_message(&mut stream).await, Some(KvmMessage::Pong(5)));
+    }
+
+    /// Encodes a clipboard-sharing `Hello` from `client_id` followed by
+    /// `copied` split into `ClipboardData` fragments.
+    fn hello_and_clipboard_frames(client_id: ClientId, copied: &ClipboardContent) -> Vec<u8> {
+        let hello = KvmMessage::Hello(HelloMessage {
+            capabilities: capabilities::CLIPBOARD_SHARING | capabilities::CLIPBOARD_IMAGE,
+            ..make_hello(PROTOCOL_VERSION, client_id)
+        });
+        let mut frames = encode_message_now(&hello, 0).unwrap();
+        for (seq, fragment) in kvm_core::clipboard::chunk(copied, usize::MAX)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+        {
+            frames.extend(
+                encode_message_now(&KvmMessage::ClipboardData(fragment), seq as u64 + 1).unwrap(),
+            );
+        }
+        frames
+    }
+
+    #[tokio::test]
+    async fn test_fragmented_clipboard_from_client_is_reassembled() {
+        // Arrange
+        let (mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        mgr.paired_clients.insert(client_id, None);
+        let mut stream = connect_framed(addr).await;
+        let copied = ClipboardContent {
+            format: ClipboardFormat::Image,
+            data: vec![0xAB; kvm_core::clipboard::FRAGMENT_LEN + 10],
+        };
+        let frames = hello_and_clipboard_frames(client_id, &copied);
+
+        // Act
+        stream.get_mut().write_all(&frames).await.unwrap();
+
+        // Assert
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match rx.recv().await {
+                    Some(ConnectionEvent::ClipboardReceived { client_id, content }) => {
+                        break (client_id, content)
+                    }
+                    Some(_) => continue,
+                    None => panic!("event channel closed"),
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for ClipboardReceived");
+        assert_eq!(received, (client_id, copied));
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_from_unpaired_client_is_dropped() {
+        // Arrange
+        let (_mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_framed(addr).await;
+        let mut frames = hello_and_clipboard_frames(client_id, &ClipboardContent::text("secret"));
+        frames.extend(encode_message_now(&KvmMessage::Ping(9), 2).unwrap());
+
+        // Act
+        stream.get_mut().write_all(&frames).await.unwrap();
+
+        // Assert – the Pong shows the clipboard frame before it was handled.
+        assert!(matches!(
+            next_message(&mut stream).await,
+            Some(KvmMessage::HelloAck(ref a)) if a.accepted
+        ));
+        assert_eq!(next_message(&mut stream).await, Some(KvmMessage::Pong(9)));
+        while let Ok(event) = rx.try_recv() {
+            assert!(
+                !matches!(event, ConnectionEvent::ClipboardReceived { .. }),
+                "unexpected {event:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_listener_rejects_non_hello_first_message() {
+        // Arrange
+        let (_mgr, _rx, addr) = start_loopback_listener().await;
+        let mut stream = connect_framed(addr).await;
+
+        // Act
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&KvmMessage::Ping(1), 0).unwrap())
+            .await
+            .unwrap();
+        let reply = next_message(&mut stream).await;
+
+        // Assert
+        match reply {
+            Some(KvmMessage::HelloAck(ack)) => {
+                assert!(!ack.accepted);
+                assert_eq!(ack.reject_reason, ProtocolErrorCode::InvalidMessage as u8);
+            }
+            other => panic!("expected HelloAck, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_reconnect_replaces_session_without_disconnect_event() {
+        // Arrange: the same client connects twice.
+        let (mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let hello = encode_message_now(
+            &KvmMessage::Hello(make_hello(PROTOCOL_VERSION, client_id)),
+            0,
+        )
+        .unwrap();
+
+        let mut first = connect_framed(addr).await;
+        first.get_mut().write_all(&hello).await.unwrap();
+        let first_ack = next_message(&mut first).await;
+        assert!(matches!(first_ack, Some(KvmMessage::HelloAck(ref a)) if a.accepted));
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { .. })
+        ));
+
+        // Act
+        let mut second = connect_framed(addr).await;
+        second.get_mut().write_all(&hello).await.unwrap();
+        let second_ack = match next_message(&mut second).await {
+            Some(KvmMessage::HelloAck(a)) => a,
+            other => panic!("expected HelloAck, got {other:?}"),
+        };
+
+        // Assert: the stale socket is closed, the new session is current and
+        // the application only ever sees the client as connected.
+        assert!(next_message(&mut first).await.is_none());
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { .. })
+        ));
+        let session = mgr.session(client_id).expect("client still connected");
+        assert_eq!(session.session_token(), &second_ack.session_token);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            rx.try_recv().is_err(),
+            "no ClientDisconnected for a replaced session"
+        );
+    }
+
+    /// Sends `hello` on a new connection and returns the stream with the ack.
+    async fn hello_ack(addr: SocketAddr, hello: HelloMessage) -> (ClientStream, HelloAckMessage) {
+        let mut stream = connect_framed(addr).await;
+        let bytes = encode_message_now(&KvmMessage::Hello(hello), 0).unwrap();
+        stream.get_mut().write_all(&bytes).await.unwrap();
+        match next_message(&mut stream).await {
+            Some(KvmMessage::HelloAck(ack)) => (stream, ack),
+            other => panic!("expected HelloAck, got {other:?}"),
+        }
+    }
+
+    fn one_monitor() -> ScreenInfoMessage {
+        ScreenInfoMessage {
+            monitors: vec![kvm_core::protocol::messages::MonitorInfo {
+                monitor_id: 0,
+                x_offset: 0,
+                y_offset: 0,
+                width: 1920,
+                height: 1080,
+                scale_factor: 100,
+                is_primary: true,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_with_previous_token_resumes_session() {
+        // Arrange: a session that reported its screen, then dropped.
+        let (mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let (mut first, first_ack) = hello_ack(addr, make_hello(PROTOCOL_VERSION, client_id)).await;
+        let screen = encode_message_now(&KvmMessage::ScreenInfo(one_monitor()), 1).unwrap();
+        first.get_mut().write_all(&screen).await.unwrap();
+        assert!(matches!(
+            next_message(&mut first).await,
+            Some(KvmMessage::ScreenInfoAck)
+        ));
+        drop(first);
+        loop {
+            match rx.recv().await {
+                Some(ConnectionEvent::ClientDisconnected { .. }) => break,
+                Some(_) => {}
+                None => panic!("event channel closed"),
+            }
+        }
+
+        // Act
+        let hello = HelloMessage {
+            resume_token: Some(first_ack.session_token),
+            ..make_hello(PROTOCOL_VERSION, client_id)
+        };
+        let (_second, second_ack) = hello_ack(addr, hello).await;
+
+        // Assert: resumed with the old layout, under a fresh token.
+        assert!(second_ack.accepted && second_ack.resumed);
+        assert_ne!(second_ack.session_token, first_ack.session_token);
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { resumed: true, .. })
+        ));
+        let session = mgr.session(client_id).expect("client connected");
+        assert_eq!(session.screen_info(), Some(one_monitor()));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_with_unknown_token_starts_a_fresh_session() {
+        // Arrange
+        let (mgr, mut rx, addr) = start_loopback_listener().await;
+        let client_id = Uuid::new_v4();
+        let hello = HelloMessage {
+            resume_token: Some([7u8; 32]),
+            ..make_hello(PROTOCOL_VERSION, client_id)
+        };
+
+        // Act
+        let (_stream, ack) = hello_ack(addr, hello).await;
+
+        // Assert: still accepted, just not resumed.
+        assert!(ack.accepted);
+        assert!(!ack.resumed);
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { resumed: false, .. })
+        ));
+        assert_eq!(mgr.session(client_id).unwrap().screen_info(), None);
+    }
+
+    #[test]
+    fn test_ended_session_cannot_be_resumed_after_the_window() {
+        // Arrange
+        let resumable = ResumableSessions::default();
+        let client_id = Uuid::new_v4();
+        let token = [3u8; 32];
+        let remember = |now| {
+            resumable.inner.lock().unwrap().insert(
+                client_id,
+                EndedSession {
+                    session_token: token,
+                    peer_fingerprint: None,
+                    screen_info: None,
+                    expires_at: now + RESUME_WINDOW,
+                },
+            );
+        };
+        let start = Instant::now();
+
+        // Act / Assert
+        remember(start);
+        assert!(resumable.take(client_id, &token, None, start).is_some());
+        assert!(
+            resumable.take(client_id, &token, None, start).is_none(),
+            "a token resumes at most once"
+        );
+        remember(start);
+        assert!(resumable
+            .take(client_id, &token, None, start + RESUME_WINDOW)
+            .is_none());
+    }
+
+    /// Heartbeat timing short enough for tests.
+    const FAST_HEARTBEAT: HeartbeatConfig = HeartbeatConfig {
+        interval: Duration::from_millis(50),
+        max_missed: 3,
+    };
+
+    /// Connects a raw client with `Hello` and consumes the `HelloAck`.
+    async fn connect_hello(addr: SocketAddr, client_id: Uuid) -> ClientStream {
+        let mut stream = connect_framed(addr).await;
+        let hello = KvmMessage::Hello(make_hello(PROTOCOL_VERSION, client_id));
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&hello, 0).unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_message(&mut stream).await,
+            Some(KvmMessage::HelloAck(ack)) if ack.accepted
+        ));
+        stream
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_reports_latency_once_pings_are_answered() {
+        // Arrange
+        let (mgr, mut rx, addr) = start_loopback_listener_with(FAST_HEARTBEAT).await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_hello(addr, client_id).await;
+
+        // Act – answer the first Ping
+        let token = match next_message(&mut stream).await {
+            Some(KvmMessage::Ping(token)) => token,
+            other => panic!("expected Ping, got {other:?}"),
+        };
+        stream
+            .get_mut()
+            .write_all(&encode_message_now(&KvmMessage::Pong(token), 1).unwrap())
+            .await
+            .unwrap();
+
+        // Assert – a later LinkStatsUpdated carries the measured latency
+        let latency = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match rx.recv().await {
+                    Some(ConnectionEvent::LinkStatsUpdated {
+                        client_id: id,
+                        latency_ms: Some(latency),
+                        ..
+                    }) if id == client_id => break latency,
+                    Some(_) => continue,
+                    None => panic!("event channel closed"),
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for a latency figure");
+        assert!(latency >= 0.0);
+        let info = mgr.get_connected_clients();
+        assert_eq!(info.len(), 1);
+        assert!((info[0].latency_ms - latency).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_silent_client_is_disconnected_with_timeout() {
+        // Arrange
+        let (mgr, mut rx, addr) = start_loopback_listener_with(FAST_HEARTBEAT).await;
+        let client_id = Uuid::new_v4();
+        let mut stream = connect_hello(addr, client_id).await;
+
+        // Act – never answer anything
+        let mut received = Vec::new();
+        while let Some(msg) =
+            tokio::time::timeout(Duration::from_secs(5), next_message(&mut stream))
+                .await
+                .expect("master never closed the connection")
+        {
+            received.push(msg);
+        }
+
+        // Assert – three Pings, then the timeout notice, then EOF
+        assert_eq!(
+            received,
+            vec![
+                KvmMessage::Ping(0),
+                KvmMessage::Ping(1),
+                KvmMessage::Ping(2),
+                KvmMessage::Disconnect {
+                    reason: DisconnectReason::Timeout
+                },
+            ]
+        );
+        let disconnected = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match rx.recv().await {
+                    Some(ConnectionEvent::ClientDisconnected { client_id }) => break client_id,
+                    Some(_) => continue,
+                    None => panic!("event channel closed"),
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for ClientDisconnected");
+        assert_eq!(disconnected, client_id);
+        assert!(mgr.session(client_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_client_returns_not_found_for_unknown_client() {
+        let (mgr, _rx) = make_manager();
+        let id = Uuid::new_v4();
+        let result = mgr
+            .disconnect_client(id, DisconnectReason::UserInitiated)
+            .await;
+        assert!(matches!(result, Err(NetworkError::ClientNotFound(x)) if x == id));
     }
 }
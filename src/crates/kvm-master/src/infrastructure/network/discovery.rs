@@ -5,7 +5,9 @@
 //! `Announce`, it:
 //!
 //! 1. Parses the client identity from the payload.
-//! 2. Sends an `AnnounceResponse` back to the client's source address.
+//! 2. Sends an `AnnounceResponse` back to the client's source address,
+//!    carrying the master's control port and whether that client is already
+//!    paired (see [`AnnounceReply`]).
 //! 3. Emits a [`DiscoveryEvent`] on the internal channel so the application
 //!    layer can initiate pairing / connection.
 //!
@@ -84,19 +86,49 @@ pub struct DiscoveryEvent {
     pub control_port: u16,
 }
 
+/// What the responder tells announcing clients about this master.
+#[derive(Clone)]
+pub struct AnnounceReply {
+    /// TCP control port advertised in every `AnnounceResponse`.
+    pub control_port: u16,
+    /// Decides `already_paired` for the announcing client; typically
+    /// `ConnectionManager::pairing_check()`.
+    pub is_paired: Arc<dyn Fn(ClientId) -> bool + Send + Sync>,
+}
+
+impl AnnounceReply {
+    /// Advertises `control_port` and reports every client as unpaired.
+    pub fn unpaired(control_port: u16) -> Self {
+        Self {
+            control_port,
+            is_paired: Arc::new(|_| false),
+        }
+    }
+}
+
+impl std::fmt::Debug for AnnounceReply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnnounceReply")
+            .field("control_port", &self.control_port)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Sequence counter used when constructing `AnnounceResponse` messages.
 static RESP_SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
 /// Binds a UDP socket on `discovery_port` and spawns a background thread that
 /// processes incoming `Announce` datagrams.
 ///
-/// Returns a receiver from which the application layer reads [`DiscoveryEvent`]s.
+/// Every `Announce` is answered according to `reply`.  Returns a receiver
+/// from which the application layer reads [`DiscoveryEvent`]s.
 ///
 /// # Errors
 ///
 /// Returns [`DiscoveryError::BindFailed`] if the socket cannot be bound.
 pub fn start_discovery_responder(
     discovery_port: u16,
+    reply: AnnounceReply,
     running: Arc<AtomicBool>,
 ) -> Result<mpsc::Receiver<DiscoveryEvent>, DiscoveryError> {
     let addr: SocketAddr = format!("0.0.0.0:{discovery_port}").parse().unwrap();
@@ -111,7 +143,7 @@ pub fn start_discovery_responder(
     std::thread::Builder::new()
         .name("kvm-discovery".to_string())
         .spawn(move || {
-            discovery_loop(socket, tx, running);
+            discovery_loop(socket, reply, tx, running);
         })
         .expect("failed to spawn discovery thread");
 
@@ -120,7 +152,12 @@ pub fn start_discovery_responder(
 }
 
 /// The main receive loop executed on the discovery thread.
-fn discovery_loop(socket: UdpSocket, tx: mpsc::Sender<DiscoveryEvent>, running: Arc<AtomicBool>) {
+fn discovery_loop(
+    socket: UdpSocket,
+    reply: AnnounceReply,
+    tx: mpsc::Sender<DiscoveryEvent>,
+    running: Arc<AtomicBool>,
+) {
     let mut buf = vec![0u8; 4096];
 
     while running.load(Ordering::Relaxed) {
@@ -142,9 +179,13 @@ fn discovery_loop(socket: UdpSocket, tx: mpsc::Sender<DiscoveryEvent>, running:
                 );
 
                 // Respond so the client knows the master is present.
-                send_announce_response(&socket, src);
-
                 let client_id: ClientId = msg.client_id;
+                let response = AnnounceResponseMessage {
+                    master_control_port: reply.control_port,
+                    already_paired: (reply.is_paired)(client_id),
+                };
+                send_announce_response(&socket, response, src);
+
                 let event = DiscoveryEvent {
                     client_id,
                     name: msg.client_name.clone(),
@@ -173,13 +214,10 @@ fn discovery_loop(socket: UdpSocket, tx: mpsc::Sender<DiscoveryEvent>, running:
     info!("discovery responder stopped");
 }
 
-/// Sends an `AnnounceResponse` back to `dest`.
-fn send_announce_response(socket: &UdpSocket, dest: SocketAddr) {
+/// Sends `response` back to `dest`.
+fn send_announce_response(socket: &UdpSocket, response: AnnounceResponseMessage, dest: SocketAddr) {
     let seq = RESP_SEQ.fetch_add(1, Ordering::Relaxed);
-    let msg = KvmMessage::AnnounceResponse(AnnounceResponseMessage {
-        master_control_port: 24800,
-        already_paired: false,
-    });
+    let msg = KvmMessage::AnnounceResponse(response);
     let ts = current_timestamp_us();
     match encode_message(&msg, seq, ts) {
         Ok(bytes) => {
@@ -278,7 +316,7 @@ mod tests {
         let running = Arc::new(AtomicBool::new(false)); // stopped immediately
 
         // Act
-        let result = start_discovery_responder(port, running);
+        let result = start_discovery_responder(port, AnnounceReply::unpaired(24800), running);
 
         // Assert
         assert!(result.is_ok(), "responder must bind successfully");
@@ -292,7 +330,7 @@ mod tests {
             return;
         }
         let running = Arc::new(AtomicBool::new(false));
-        let result = start_discovery_responder(1, running);
+        let result = start_discovery_responder(1, AnnounceReply::unpaired(24800), running);
 
         // On most systems this will be Err; if it succeeds (e.g. running as root) that is OK.
         // We only assert that the function does not panic.
@@ -0,0 +1,286 @@
+//! Control-channel heartbeat: round-trip time, throughput and dead-peer
+//! detection for one client session.
+//!
+//! # How the heartbeat works (for beginners)
+//!
+//! TCP only notices that the other end has vanished (laptop lid closed, cable
+//! pulled, Wi-Fi gone) when a write finally times out, which can take many
+//! minutes.  So the master probes each client itself:
+//!
+//! ```text
+//! every interval:
+//!   previous Ping still unanswered? ── yes ─► missed += 1
+//!   missed == max_missed?           ── yes ─► Disconnect { Timeout }, close
+//!   otherwise                               ─► Ping(token)
+//!
+//! Pong(token) for the outstanding Ping ─► RTT sample, missed = 0
+//! ```
+//!
+//! With the defaults (a `Ping` every 5 seconds, three misses allowed) a dead
+//! client is dropped after 15 seconds, as the protocol specification requires.
+//!
+//! # Smoothed RTT
+//!
+//! A single RTT sample is noisy: one delayed packet would make the UI's
+//! latency figure jump around.  The reported value is an exponentially
+//! weighted moving average, the same one TCP uses for its retransmission
+//! timer (RFC 6298):
+//!
+//! ```text
+//! srtt = srtt + (sample − srtt) / 8
+//! ```
+//!
+//! # Throughput
+//!
+//! The input transmitter calls [`LinkMonitor::record_event`] for every event
+//! it delivers; once per interval the heartbeat turns the count into an
+//! events-per-second figure.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Weight of a new RTT sample in the moving average (1/8, as in RFC 6298).
+const RTT_SAMPLE_WEIGHT: f64 = 1.0 / 8.0;
+
+/// Heartbeat timing for control-channel sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// Time between `Ping`s.  Each `Ping` has this long to be answered.
+    pub interval: Duration,
+    /// Consecutive unanswered `Ping`s after which the client is considered
+    /// dead and disconnected with `DisconnectReason::Timeout`.
+    pub max_missed: u32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            max_missed: 3,
+        }
+    }
+}
+
+/// What the heartbeat should do on a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tick {
+    /// Send `Ping` with this token.
+    Ping(u64),
+    /// Too many `Ping`s went unanswered; disconnect the client.
+    TimedOut,
+}
+
+/// Link measurements for one session.
+///
+/// Shared by the heartbeat task (which sends the `Ping`s), the connection task
+/// (which receives the `Pong`s) and the input transmitter (which counts
+/// events), so every method takes `&self`.
+#[derive(Debug, Default)]
+pub struct LinkMonitor {
+    pings: Mutex<PingState>,
+    /// Events delivered since the last throughput sample.
+    events: AtomicU64,
+    /// Result of the last throughput sample.
+    events_per_second: AtomicU32,
+}
+
+#[derive(Debug, Default)]
+struct PingState {
+    next_token: u64,
+    /// The `Ping` awaiting its `Pong`, and when it was sent.
+    outstanding: Option<(u64, Instant)>,
+    /// Consecutive `Ping`s that went unanswered.
+    missed: u32,
+    /// Smoothed round-trip time; `None` until the first `Pong`.
+    srtt: Option<Duration>,
+}
+
+impl LinkMonitor {
+    /// Creates a monitor with no measurements yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the heartbeat at `now`.
+    ///
+    /// A `Ping` still outstanding from the previous tick counts as missed.
+    /// Returns [`Tick::TimedOut`] once `max_missed` `Ping`s in a row have gone
+    /// unanswered, and otherwise the token of the next `Ping` to send.
+    pub fn tick(&self, now: Instant, max_missed: u32) -> Tick {
+        let mut pings = self.pings.lock().expect("link monitor lock poisoned");
+        if pings.outstanding.take().is_some() {
+            pings.missed += 1;
+        }
+        if pings.missed >= max_missed {
+            return Tick::TimedOut;
+        }
+        let token = pings.next_token;
+        pings.next_token = token.wrapping_add(1);
+        pings.outstanding = Some((token, now));
+        Tick::Ping(token)
+    }
+
+    /// Records a `Pong` received at `now`.
+    ///
+    /// Returns the round-trip time if `token` answers the outstanding `Ping`.
+    /// Stale or unsolicited `Pong`s are ignored and return `None`.
+    pub fn pong(&self, token: u64, now: Instant) -> Option<Duration> {
+        let mut pings = self.pings.lock().expect("link monitor lock poisoned");
+        let (expected, sent_at) = pings.outstanding?;
+        if token != expected {
+            return None;
+        }
+        let sample = now.saturating_duration_since(sent_at);
+        pings.outstanding = None;
+        pings.missed = 0;
+        pings.srtt = Some(match pings.srtt {
+            None => sample,
+            Some(srtt) => {
+                let srtt = srtt.as_secs_f64();
+                Duration::from_secs_f64(srtt + (sample.as_secs_f64() - srtt) * RTT_SAMPLE_WEIGHT)
+            }
+        });
+        Some(sample)
+    }
+
+    /// Smoothed round-trip time, or `None` before the first `Pong`.
+    pub fn latency(&self) -> Option<Duration> {
+        self.pings.lock().expect("link monitor lock poisoned").srtt
+    }
+
+    /// Counts one event delivered to the client.
+    pub fn record_event(&self) {
+        self.events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Converts the events counted over the last `elapsed` into a rate,
+    /// stores it for [`events_per_second`](Self::events_per_second), and
+    /// starts a new count.
+    pub fn sample_throughput(&self, elapsed: Duration) -> u32 {
+        let count = self.events.swap(0, Ordering::Relaxed);
+        let secs = elapsed.as_secs_f64();
+        let rate = if secs > 0.0 {
+            (count as f64 / secs).round().min(f64::from(u32::MAX)) as u32
+        } else {
+            0
+        };
+        self.events_per_second.store(rate, Ordering::Relaxed);
+        rate
+    }
+
+    /// Events per second at the last throughput sample.
+    pub fn events_per_second(&self) -> u32 {
+        self.events_per_second.load(Ordering::Relaxed)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_answered_pings_never_time_out() {
+        // Arrange
+        let link = LinkMonitor::new();
+        let start = Instant::now();
+
+        // Act / Assert – every Ping is answered before the next tick
+        for i in 0..10u64 {
+            let now = start + 1000 * MS * i as u32;
+            let Tick::Ping(token) = link.tick(now, 3) else {
+                panic!("timed out on tick {i}");
+            };
+            assert_eq!(link.pong(token, now + 20 * MS), Some(20 * MS));
+        }
+    }
+
+    #[test]
+    fn test_times_out_after_max_missed_pings() {
+        // Arrange
+        let link = LinkMonitor::new();
+        let now = Instant::now();
+
+        // Act – nothing is ever answered
+        let ticks: Vec<Tick> = (0..4).map(|_| link.tick(now, 3)).collect();
+
+        // Assert – three Pings go out, the fourth tick gives up
+        assert_eq!(
+            ticks,
+            vec![Tick::Ping(0), Tick::Ping(1), Tick::Ping(2), Tick::TimedOut]
+        );
+    }
+
+    #[test]
+    fn test_a_pong_resets_the_missed_count() {
+        let link = LinkMonitor::new();
+        let now = Instant::now();
+        link.tick(now, 3);
+        link.tick(now, 3);
+        let Tick::Ping(token) = link.tick(now, 3) else {
+            panic!("timed out too early");
+        };
+
+        link.pong(token, now);
+
+        assert!(matches!(link.tick(now, 3), Tick::Ping(_)));
+        assert!(matches!(link.tick(now, 3), Tick::Ping(_)));
+    }
+
+    #[test]
+    fn test_stale_pong_is_ignored() {
+        // Arrange – Ping 0 goes unanswered and is superseded by Ping 1
+        let link = LinkMonitor::new();
+        let now = Instant::now();
+        link.tick(now, 3);
+        link.tick(now, 3);
+
+        // Act
+        let late = link.pong(0, now + 100 * MS);
+
+        // Assert
+        assert_eq!(late, None);
+        assert_eq!(link.latency(), None);
+    }
+
+    #[test]
+    fn test_latency_is_smoothed() {
+        // Arrange
+        let link = LinkMonitor::new();
+        let now = Instant::now();
+
+        // Act – a steady 10 ms, then one 90 ms outlier
+        for rtt in [10, 10, 90] {
+            let Tick::Ping(token) = link.tick(now, 3) else {
+                unreachable!()
+            };
+            link.pong(token, now + rtt * MS);
+        }
+
+        // Assert – the outlier moves the average by an eighth of the difference
+        let latency = link.latency().unwrap().as_secs_f64() * 1000.0;
+        assert!((latency - 20.0).abs() < 0.01, "latency = {latency}");
+    }
+
+    #[test]
+    fn test_throughput_is_events_per_second_since_last_sample() {
+        // Arrange
+        let link = LinkMonitor::new();
+        for _ in 0..250 {
+            link.record_event();
+        }
+
+        // Act
+        let first = link.sample_throughput(Duration::from_millis(500));
+        let second = link.sample_throughput(Duration::from_secs(1));
+
+        // Assert
+        assert_eq!(first, 500);
+        assert_eq!(second, 0);
+        assert_eq!(link.events_per_second(), 0);
+    }
+}
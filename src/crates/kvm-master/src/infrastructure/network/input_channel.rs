@@ -0,0 +1,542 @@
+//! UDP input channel: low-latency delivery of high-frequency input.
+//!
+//! # Why (for beginners)
+//!
+//! Everything the master sends to a client normally shares one TCP stream.  A
+//! single lost packet on that stream stalls every message behind it until TCP
+//! retransmits it, so a clipboard transfer or a flaky Wi-Fi link makes the
+//! remote cursor stutter.  This module offers a second path on
+//! `NetworkConfig::input_port`: each `MouseMove` travels in its own encrypted
+//! datagram (see `kvm_core::crypto::input_channel`), keyed from the client's
+//! control-channel session token.  Key and button events stay on TCP, where
+//! they cannot be lost or reordered.
+//!
+//! # Registration and path verification
+//!
+//! The master never sends datagrams to an address it has not heard from, and
+//! only uses a path it has seen working in *both* directions:
+//!
+//! ```text
+//! client                                             master
+//!   ── Ping(t) ─────────────────────────────────────►  record client address
+//!   ◄──────────────────────────────────────── Ping(t) ── probe
+//!   ── Pong(t) ─────────────────────────────────────►  path verified (now)
+//! ```
+//!
+//! The client repeats its `Ping` every second or so, which also keeps NAT
+//! mappings open.  A path counts as live for [`UDP_PATH_LIVENESS`] after the
+//! last `Pong`.
+//!
+//! # Fallback to TCP
+//!
+//! [`InputChannel::try_send`] returns `false` whenever the path is not live:
+//! the client never registered (UDP blocked on the way in, or the client does
+//! not support it), the master's probes are not answered (blocked on the way
+//! out), or the socket refused the datagram.  The caller then sends the
+//! message over the control channel, so input is never silently dropped
+//! because UDP is unavailable.
+//!
+//! # Anti-replay
+//!
+//! Each path keeps a `ReplayWindow` over the sequence numbers of the client's
+//! datagrams.  A datagram is only acted on if it authenticates *and* its
+//! sequence number has not been seen before.  Paths are tied to one
+//! control-channel session; when the client reconnects it gets a new token and
+//! therefore new keys, and the old path is discarded.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use kvm_core::crypto::input_channel::{
+    peek_client_id, Endpoint, InputChannelCipher, ReplayWindow, MAX_DATAGRAM_LEN,
+};
+use kvm_core::protocol::messages::KvmMessage;
+use kvm_core::protocol::SequenceCounter;
+use kvm_core::ClientId;
+use tokio::net::UdpSocket;
+use tracing::debug;
+
+use super::connection_manager::{ClientSession, NetworkError, SessionRegistry};
+
+/// How long a path stays usable after the client last answered a probe.
+///
+/// Clients ping every second, so this tolerates two lost round trips before
+/// input falls back to TCP.
+pub const UDP_PATH_LIVENESS: Duration = Duration::from_secs(3);
+
+/// How often the receive loop wakes up to check the shutdown flag.
+const RECV_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Cheaply cloneable handle to the master's UDP input socket.
+#[derive(Clone)]
+pub struct InputChannel {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    socket: UdpSocket,
+    sessions: SessionRegistry,
+    paths: RwLock<HashMap<ClientId, Arc<UdpPath>>>,
+}
+
+/// UDP state for one control-channel session.
+struct UdpPath {
+    /// The session whose token keyed `cipher`.
+    session: Weak<ClientSession>,
+    cipher: InputChannelCipher,
+    /// Sequence numbers (and so AEAD nonces) for datagrams we send.
+    sequence: SequenceCounter,
+    state: Mutex<PathState>,
+}
+
+#[derive(Default)]
+struct PathState {
+    /// Where the client's last authenticated `Ping` came from.
+    peer: Option<SocketAddr>,
+    /// When the client last answered a probe from `peer`.
+    verified_at: Option<Instant>,
+    replay: ReplayWindow,
+}
+
+impl InputChannel {
+    /// Binds `addr` and starts the receive loop on a background task.
+    ///
+    /// The loop runs until `running` is set to `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkError::BindFailed`] if the UDP port cannot be bound.
+    pub async fn start(
+        addr: SocketAddr,
+        sessions: SessionRegistry,
+        running: Arc<AtomicBool>,
+    ) -> Result<Self, NetworkError> {
+        let socket = UdpSocket::bind(addr)
+            .await
+            .map_err(|source| NetworkError::BindFailed { addr, source })?;
+        let channel = Self {
+            inner: Arc::new(Inner {
+                socket,
+                sessions,
+                paths: RwLock::new(HashMap::new()),
+            }),
+        };
+        tokio::spawn(recv_loop(Arc::clone(&channel.inner), running));
+        Ok(channel)
+    }
+
+    /// The address the socket is bound to (useful when binding port `0`).
+    ///
+    /// # Errors
+    ///
+    /// Propagates the OS error from `getsockname`.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.inner.socket.local_addr()
+    }
+
+    /// Returns `true` if `client_id` has a verified, live UDP path.
+    pub fn is_active(&self, client_id: ClientId) -> bool {
+        let Some(session) = self.inner.sessions.get(client_id) else {
+            return false;
+        };
+        self.inner.live_path(&session).is_some()
+    }
+
+    /// Sends `msg` to `session`'s client over UDP if its path is live.
+    ///
+    /// Returns `false` without sending when the caller should use the
+    /// control channel instead (see the module docs).
+    pub fn try_send(&self, session: &Arc<ClientSession>, msg: &KvmMessage) -> bool {
+        // Leave refusing unnegotiated messages to the control channel, which
+        // reports it as an error.
+        if session.negotiated().check(msg).is_err() {
+            return false;
+        }
+        let Some((path, peer)) = self.inner.live_path(session) else {
+            return false;
+        };
+        let datagram = match path.cipher.seal(msg, path.sequence.next(), now_us()) {
+            Ok(d) => d,
+            Err(e) => {
+                debug!("not sending {:?} over UDP: {e}", msg.message_type());
+                return false;
+            }
+        };
+        self.inner.socket.try_send_to(&datagram, peer).is_ok()
+    }
+}
+
+impl Inner {
+    /// Returns the session's path and peer address if it is verified and
+    /// recent.
+    fn live_path(&self, session: &Arc<ClientSession>) -> Option<(Arc<UdpPath>, SocketAddr)> {
+        let path = self
+            .paths
+            .read()
+            .expect("input paths lock poisoned")
+            .get(&session.client_id())
+            .cloned()?;
+        if !path.belongs_to(session) {
+            return None;
+        }
+        let state = path.state.lock().expect("input path lock poisoned");
+        let fresh = state
+            .verified_at
+            .is_some_and(|t| t.elapsed() < UDP_PATH_LIVENESS);
+        let peer = state.peer.filter(|_| fresh)?;
+        drop(state);
+        Some((path, peer))
+    }
+
+    /// Returns the path for `session`, creating it (and discarding a path left
+    /// over from an earlier session of the same client) if needed.
+    fn path_for(&self, session: &Arc<ClientSession>) -> Arc<UdpPath> {
+        let client_id = session.client_id();
+        if let Some(path) = self
+            .paths
+            .read()
+            .expect("input paths lock poisoned")
+            .get(&client_id)
+            .filter(|p| p.belongs_to(session))
+        {
+            return Arc::clone(path);
+        }
+
+        let mut paths = self.paths.write().expect("input paths lock poisoned");
+        paths.retain(|_, p| p.session.strong_count() > 0);
+        let path = paths
+            .entry(client_id)
+            .and_modify(|p| {
+                if !p.belongs_to(session) {
+                    *p = Arc::new(UdpPath::new(session));
+                }
+            })
+            .or_insert_with(|| Arc::new(UdpPath::new(session)));
+        Arc::clone(path)
+    }
+
+    /// Authenticates one datagram and updates the path; returns the reply to
+    /// send, if any.
+    fn handle_datagram(&self, datagram: &[u8], from: SocketAddr) -> Option<Vec<u8>> {
+        let client_id = peek_client_id(datagram)?;
+        let session = self.sessions.get(client_id)?;
+        let path = self.path_for(&session);
+
+        let (sequence, msg) = match path.cipher.open(datagram) {
+            Ok(opened) => opened,
+            Err(e) => {
+                debug!("dropping datagram from {from} for client {client_id}: {e}");
+                return None;
+            }
+        };
+
+        let mut state = path.state.lock().expect("input path lock poisoned");
+        if !state.replay.accept(sequence) {
+            debug!("dropping replayed datagram {sequence} from {from}");
+            return None;
+        }
+        match msg {
+            KvmMessage::Ping(token) => {
+                if state.peer != Some(from) {
+                    // A new address (first contact or NAT rebinding) has to be
+                    // verified before input is sent there.
+                    state.peer = Some(from);
+                    state.verified_at = None;
+                }
+                drop(state);
+                path.cipher
+                    .seal(&KvmMessage::Ping(token), path.sequence.next(), now_us())
+                    .ok()
+            }
+            KvmMessage::Pong(_) if state.peer == Some(from) => {
+                state.verified_at = Some(Instant::now());
+                None
+            }
+            other => {
+                debug!(
+                    "ignoring {:?} on the input channel from {from}",
+                    other.message_type()
+                );
+                None
+            }
+        }
+    }
+}
+
+impl UdpPath {
+    fn new(session: &Arc<ClientSession>) -> Self {
+        Self {
+            session: Arc::downgrade(session),
+            cipher: InputChannelCipher::new(
+                session.session_token(),
+                session.client_id(),
+                Endpoint::Master,
+            )
+            .with_version(session.negotiated().version()),
+            sequence: SequenceCounter::new(),
+            state: Mutex::new(PathState::default()),
+        }
+    }
+
+    fn belongs_to(&self, session: &Arc<ClientSession>) -> bool {
+        std::ptr::eq(self.session.as_ptr(), Arc::as_ptr(session))
+    }
+}
+
+/// Receives datagrams until `running` is cleared.
+async fn recv_loop(inner: Arc<Inner>, running: Arc<AtomicBool>) {
+    // One spare byte so oversized datagrams are seen as such, not truncated.
+    let mut buf = vec![0u8; MAX_DATAGRAM_LEN + 1];
+    while running.load(Ordering::Relaxed) {
+        let (len, from) = match tokio::time::timeout(
+            RECV_POLL_INTERVAL,
+            inner.socket.recv_from(&mut buf),
+        )
+        .await
+        {
+            Err(_) => continue,
+            Ok(Ok(received)) => received,
+            Ok(Err(e)) => {
+                // Includes ICMP "port unreachable" reports on some
+                // platforms; the client will simply register again.
+                debug!("input channel receive error: {e}");
+                continue;
+            }
+        };
+        if let Some(reply) = inner.handle_datagram(&buf[..len], from) {
+            if let Err(e) = inner.socket.send_to(&reply, from).await {
+                debug!("input channel send to {from} failed: {e}");
+            }
+        }
+    }
+}
+
+fn now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::StreamExt;
+    use kvm_core::protocol::codec::encode_message_now;
+    use kvm_core::protocol::messages::{
+        capabilities, HelloAckMessage, HelloMessage, MouseMoveMessage, PlatformId, PROTOCOL_VERSION,
+    };
+    use kvm_core::protocol::{Frame, KvmCodec};
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+    use tokio::sync::mpsc;
+    use tokio_util::codec::FramedRead;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::infrastructure::network::connection_manager::{
+        ConnectionEvent, ConnectionManager, NetworkConfig,
+    };
+
+    const STEP: Duration = Duration::from_secs(5);
+
+    /// A master with one connected control-channel client and a running
+    /// input channel, plus a UDP socket playing the client's side.
+    struct Fixture {
+        _mgr: ConnectionManager,
+        _rx: mpsc::Receiver<ConnectionEvent>,
+        _tcp: FramedRead<TcpStream, KvmCodec>,
+        channel: InputChannel,
+        session: Arc<ClientSession>,
+        cipher: InputChannelCipher,
+        udp: UdpSocket,
+        sequence: u64,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let (mgr, mut rx) = ConnectionManager::new(NetworkConfig {
+                control_port: 0,
+                input_port: 0,
+                bind_address: "127.0.0.1".parse().unwrap(),
+                ..NetworkConfig::default()
+            });
+            let running = Arc::new(AtomicBool::new(true));
+            let addr = mgr
+                .start_control_listener(Arc::clone(&running))
+                .await
+                .unwrap();
+            let channel = mgr.start_input_channel(running).await.unwrap();
+
+            let client_id = Uuid::new_v4();
+            let mut tcp = TcpStream::connect(addr).await.unwrap();
+            let hello = KvmMessage::Hello(HelloMessage {
+                client_id,
+                protocol_version: PROTOCOL_VERSION,
+                platform_id: PlatformId::Linux,
+                client_name: "udp-test".to_string(),
+                capabilities: capabilities::KEYBOARD_EMULATION | capabilities::MOUSE_EMULATION,
+                resume_token: None,
+            });
+            tcp.write_all(&encode_message_now(&hello, 0).unwrap())
+                .await
+                .unwrap();
+            let mut tcp = FramedRead::new(tcp, KvmCodec::new());
+            let ack: HelloAckMessage = match tcp.next().await {
+                Some(Ok(Frame {
+                    message: KvmMessage::HelloAck(ack),
+                    ..
+                })) => ack,
+                other => panic!("expected HelloAck, got {other:?}"),
+            };
+            assert!(matches!(
+                tokio::time::timeout(STEP, rx.recv()).await,
+                Ok(Some(ConnectionEvent::ClientConnected { .. }))
+            ));
+            let session = mgr.session(client_id).unwrap();
+
+            let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            udp.connect(channel.local_addr().unwrap()).await.unwrap();
+            Self {
+                cipher: InputChannelCipher::new(&ack.session_token, client_id, Endpoint::Client),
+                _mgr: mgr,
+                _rx: rx,
+                _tcp: tcp,
+                channel,
+                session,
+                udp,
+                sequence: 0,
+            }
+        }
+
+        async fn send(&mut self, msg: &KvmMessage) -> Vec<u8> {
+            self.sequence += 1;
+            let datagram = self.cipher.seal(msg, self.sequence, 0).unwrap();
+            self.udp.send(&datagram).await.unwrap();
+            datagram
+        }
+
+        async fn recv(&self) -> KvmMessage {
+            let mut buf = vec![0u8; MAX_DATAGRAM_LEN];
+            let len = tokio::time::timeout(STEP, self.udp.recv(&mut buf))
+                .await
+                .expect("timed out waiting for datagram")
+                .unwrap();
+            self.cipher.open(&buf[..len]).unwrap().1
+        }
+
+        /// Runs the registration handshake from the module docs.
+        async fn register(&mut self) {
+            self.send(&KvmMessage::Ping(11)).await;
+            assert_eq!(self.recv().await, KvmMessage::Ping(11));
+            self.send(&KvmMessage::Pong(11)).await;
+        }
+
+        async fn wait_until_active(&self) {
+            tokio::time::timeout(STEP, async {
+                while !self.channel.is_active(self.session.client_id()) {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("path never became active");
+        }
+    }
+
+    fn mouse_move() -> KvmMessage {
+        KvmMessage::MouseMove(MouseMoveMessage {
+            x: 10,
+            y: 20,
+            delta_x: 1,
+            delta_y: 1,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_client_falls_back_to_tcp() {
+        // Arrange
+        let fx = Fixture::new().await;
+
+        // Act
+        let sent = fx.channel.try_send(&fx.session, &mouse_move());
+
+        // Assert
+        assert!(!sent, "no UDP path yet: caller must use TCP");
+        assert!(!fx.channel.is_active(fx.session.client_id()));
+    }
+
+    #[tokio::test]
+    async fn test_verified_path_carries_input() {
+        // Arrange
+        let mut fx = Fixture::new().await;
+        fx.register().await;
+        fx.wait_until_active().await;
+
+        // Act
+        let sent = fx.channel.try_send(&fx.session, &mouse_move());
+
+        // Assert
+        assert!(sent);
+        assert_eq!(fx.recv().await, mouse_move());
+    }
+
+    #[tokio::test]
+    async fn test_ping_alone_does_not_activate_path() {
+        // Arrange: the client registers but never answers the probe, as when
+        // datagrams from the master are blocked.
+        let mut fx = Fixture::new().await;
+        fx.send(&KvmMessage::Ping(1)).await;
+        assert_eq!(fx.recv().await, KvmMessage::Ping(1));
+
+        // Act
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Assert
+        assert!(!fx.channel.try_send(&fx.session, &mouse_move()));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_ping_is_not_answered() {
+        // Arrange
+        let mut fx = Fixture::new().await;
+        let ping = fx.send(&KvmMessage::Ping(5)).await;
+        assert_eq!(fx.recv().await, KvmMessage::Ping(5));
+
+        // Act: an attacker re-sends the captured datagram.
+        fx.udp.send(&ping).await.unwrap();
+
+        // Assert
+        let mut buf = [0u8; 64];
+        assert!(
+            tokio::time::timeout(Duration::from_millis(300), fx.udp.recv(&mut buf))
+                .await
+                .is_err(),
+            "a replayed datagram must be ignored"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_datagram_with_wrong_key_is_ignored() {
+        // Arrange: right client ID, but keys from a guessed token.
+        let fx = Fixture::new().await;
+        let forger = InputChannelCipher::new(&[0u8; 32], fx.session.client_id(), Endpoint::Client);
+
+        // Act
+        let forged = forger.seal(&KvmMessage::Ping(1), 1, 0).unwrap();
+        fx.udp.send(&forged).await.unwrap();
+
+        // Assert
+        let mut buf = [0u8; 64];
+        assert!(
+            tokio::time::timeout(Duration::from_millis(300), fx.udp.recv(&mut buf))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,491 @@
+//! Network-backed [`InputTransmitter`]: delivers routed input to live clients.
+//!
+//! `RouteInputUseCase` decides *which* client should receive an event; this
+//! module does the actual delivery.  For every call it:
+//!
+//! 1. Looks up the client's live [`ClientSession`] in the shared
+//!    [`SessionRegistry`] (populated by the control-channel listener).
+//! 2. Wraps the event in the matching [`KvmMessage`] variant.
+//! 3. Encodes it with the session's own sequence counter, so sequence numbers
+//!    are contiguous per connection rather than global.
+//! 4. Queues the frame on the session's writer task.
+//! 5. Counts the event on the session's link monitor, from which the
+//!    heartbeat reports events per second.
+//!
+//! # UDP for high-frequency input
+//!
+//! With an [`InputChannel`] attached (see
+//! [`NetworkInputTransmitter::with_input_channel`]), mouse movements go out
+//! as encrypted datagrams whenever the client's UDP path is live, so they are
+//! not held up behind other traffic on the TCP stream.  A lost or late
+//! movement is harmless: the next one carries the absolute position.
+//!
+//! Everything else stays on TCP.  A lost `KeyUp` would leave a key stuck down
+//! on the client, and a key that overtook a button click (or the other way
+//! round) would turn Ctrl+click into a plain click, so key, button and scroll
+//! events keep the control channel's delivery and ordering guarantees.  If
+//! the UDP path is not live, movements fall back to the control channel too,
+//! exactly as without an input channel.
+//!
+//! # Clipboard
+//!
+//! Clipboard entries are split with [`kvm_core::clipboard::chunk`] and always
+//! sent over the control channel, whose ordering the receiver relies on to
+//! reassemble them.  Each entry is first downgraded to the formats the client
+//! negotiated (`ClipboardContent::for_peer`): a text-only client receives the
+//! text of copied HTML.  A client that can receive none of it (no clipboard
+//! sharing, or an image for a text-only client) is skipped silently: not
+//! having the clipboard is a normal configuration, not a delivery failure.
+//!
+//! # Why not lock the `ConnectionManager`? (for beginners)
+//!
+//! The `ConnectionManager` lives behind a `tokio::sync::Mutex` in `AppState`
+//! and is also used by UI commands and the pairing flow.  Input routing runs on
+//! every mouse movement, so taking that lock per event would make input latency
+//! depend on whatever the UI happens to be doing.  Instead the transmitter keeps
+//! its own clone of the [`SessionRegistry`] handle, whose read lock is held only
+//! for a `HashMap` lookup.
+//!
+//! [`ClientSession`]: super::connection_manager::ClientSession
+
+use async_trait::async_trait;
+use kvm_core::{
+    clipboard::{chunk, ClipboardContent, DEFAULT_MAX_CLIPBOARD_LEN},
+    protocol::messages::{
+        KeyEventMessage, KvmMessage, MouseButtonMessage, MouseMoveMessage, MouseScrollMessage,
+    },
+    ClientId,
+};
+
+use super::connection_manager::{NetworkError, SessionRegistry};
+use super::input_channel::InputChannel;
+use crate::application::route_input::InputTransmitter;
+
+/// [`InputTransmitter`] that sends events over each client's control channel.
+///
+/// Errors are reported as strings (as the trait requires) and end up in
+/// `RouteError::Transmit`:
+///
+/// - `client not found: <id>` – the client has no live connection.
+/// - `control channel to client <id> is closed` – the connection dropped
+///   between the lookup and the send.
+pub struct NetworkInputTransmitter {
+    sessions: SessionRegistry,
+    input_channel: Option<InputChannel>,
+}
+
+impl NetworkInputTransmitter {
+    /// Creates a transmitter that sends to the sessions in `sessions`.
+    ///
+    /// Obtain the registry with `ConnectionManager::session_registry()`.
+    pub fn new(sessions: SessionRegistry) -> Self {
+        Self {
+            sessions,
+            input_channel: None,
+        }
+    }
+
+    /// Sends mouse movements over `channel` when the client's UDP path is
+    /// live.
+    ///
+    /// Obtain the channel with `ConnectionManager::start_input_channel()`.
+    pub fn with_input_channel(mut self, channel: InputChannel) -> Self {
+        self.input_channel = Some(channel);
+        self
+    }
+
+    /// Sends `msg` to `client_id`'s live session, over the input channel when
+    /// [`prefers_udp`] allows it.
+    async fn send(&self, client_id: ClientId, msg: KvmMessage) -> Result<(), NetworkError> {
+        let session = self
+            .sessions
+            .get(client_id)
+            .ok_or(NetworkError::ClientNotFound(client_id))?;
+        let sent_over_udp = prefers_udp(&msg)
+            && self
+                .input_channel
+                .as_ref()
+                .is_some_and(|channel| channel.try_send(&session, &msg));
+        if !sent_over_udp {
+            session.send(&msg).await?;
+        }
+        session.link().record_event();
+        Ok(())
+    }
+}
+
+/// Whether `msg` should use the input channel when the client's UDP path is
+/// live.  Only mouse movements may be lost or reordered (see the module docs).
+fn prefers_udp(msg: &KvmMessage) -> bool {
+    matches!(msg, KvmMessage::MouseMove(_))
+}
+
+#[async_trait]
+impl InputTransmitter for NetworkInputTransmitter {
+    async fn send_key_event(
+        &self,
+        client_id: ClientId,
+        event: KeyEventMessage,
+    ) -> Result<(), String> {
+        self.send(client_id, KvmMessage::KeyEvent(event))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn send_mouse_move(
+        &self,
+        client_id: ClientId,
+        event: MouseMoveMessage,
+    ) -> Result<(), String> {
+        self.send(client_id, KvmMessage::MouseMove(event))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn send_mouse_button(
+        &self,
+        client_id: ClientId,
+        event: MouseButtonMessage,
+    ) -> Result<(), String> {
+        self.send(client_id, KvmMessage::MouseButton(event))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn send_mouse_scroll(
+        &self,
+        client_id: ClientId,
+        event: MouseScrollMessage,
+    ) -> Result<(), String> {
+        self.send(client_id, KvmMessage::MouseScroll(event))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn send_clipboard(
+        &self,
+        client_id: ClientId,
+        content: ClipboardContent,
+    ) -> Result<(), String> {
+        let session = self
+            .sessions
+            .get(client_id)
+            .ok_or_else(|| NetworkError::ClientNotFound(client_id).to_string())?;
+        let Some(content) = content
+            .for_peer(session.negotiated().capabilities())
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(());
+        };
+        let fragments = chunk(&content, DEFAULT_MAX_CLIPBOARD_LEN).map_err(|e| e.to_string())?;
+        for fragment in fragments {
+            session
+                .send(&KvmMessage::ClipboardData(fragment))
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use kvm_core::clipboard::HtmlClipboard;
+    use kvm_core::decode_message;
+    use kvm_core::keymap::hid::HidKeyCode;
+    use kvm_core::protocol::codec::encode_message_now;
+    use kvm_core::protocol::messages::{
+        capabilities, ClipboardFormat, HelloMessage, KeyEventType, ModifierFlags, PlatformId,
+        HEADER_SIZE, PROTOCOL_VERSION,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::infrastructure::network::connection_manager::{
+        ConnectionEvent, ConnectionManager, NetworkConfig,
+    };
+
+    /// Reads one raw frame and returns `(sequence_number, message)`.
+    async fn read_raw_frame(stream: &mut TcpStream) -> (u64, KvmMessage) {
+        let mut header = [0u8; HEADER_SIZE];
+        stream.read_exact(&mut header).await.expect("read header");
+        let payload_len = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        let mut frame = header.to_vec();
+        frame.resize(HEADER_SIZE + payload_len, 0);
+        stream
+            .read_exact(&mut frame[HEADER_SIZE..])
+            .await
+            .expect("read payload");
+        let sequence = u64::from_be_bytes(header[8..16].try_into().unwrap());
+        let (msg, _) = decode_message(&frame).expect("decode");
+        (sequence, msg)
+    }
+
+    /// Starts a loopback listener, connects one raw keyboard-and-mouse client
+    /// and completes the handshake.  Returns the manager, its event receiver,
+    /// the client socket and the client's ID.
+    async fn connected_client() -> (
+        ConnectionManager,
+        mpsc::Receiver<ConnectionEvent>,
+        TcpStream,
+        ClientId,
+    ) {
+        connected_client_with(capabilities::KEYBOARD_EMULATION | capabilities::MOUSE_EMULATION)
+            .await
+    }
+
+    /// Like [`connected_client`], with the client advertising `caps`.
+    async fn connected_client_with(
+        caps: u32,
+    ) -> (
+        ConnectionManager,
+        mpsc::Receiver<ConnectionEvent>,
+        TcpStream,
+        ClientId,
+    ) {
+        let (mgr, mut rx) = ConnectionManager::new(NetworkConfig {
+            control_port: 0,
+            bind_address: "127.0.0.1".parse().unwrap(),
+            ..NetworkConfig::default()
+        });
+        let addr = mgr
+            .start_control_listener(Arc::new(AtomicBool::new(true)))
+            .await
+            .unwrap();
+
+        let client_id = Uuid::new_v4();
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let hello = KvmMessage::Hello(HelloMessage {
+            client_id,
+            protocol_version: PROTOCOL_VERSION,
+            platform_id: PlatformId::Linux,
+            client_name: "transmitter-test".to_string(),
+            capabilities: caps,
+            resume_token: None,
+        });
+        stream
+            .write_all(&encode_message_now(&hello, 0).unwrap())
+            .await
+            .unwrap();
+        let (_, ack) = read_raw_frame(&mut stream).await;
+        assert!(matches!(ack, KvmMessage::HelloAck(ref a) if a.accepted));
+        assert!(matches!(
+            rx.recv().await,
+            Some(ConnectionEvent::ClientConnected { .. })
+        ));
+
+        (mgr, rx, stream, client_id)
+    }
+
+    fn key_a_down() -> KeyEventMessage {
+        KeyEventMessage {
+            key_code: HidKeyCode::KeyA,
+            scan_code: 0x1E,
+            event_type: KeyEventType::KeyDown,
+            modifiers: ModifierFlags::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_key_event_reaches_connected_client() {
+        // Arrange
+        let (mgr, _rx, mut stream, client_id) = connected_client().await;
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+
+        // Act
+        transmitter
+            .send_key_event(client_id, key_a_down())
+            .await
+            .expect("send must succeed");
+
+        // Assert
+        let (_, msg) = read_raw_frame(&mut stream).await;
+        assert_eq!(msg, KvmMessage::KeyEvent(key_a_down()));
+    }
+
+    #[tokio::test]
+    async fn test_all_event_kinds_are_sent_with_contiguous_sequence_numbers() {
+        // Arrange
+        let (mgr, _rx, mut stream, client_id) = connected_client().await;
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+        let mv = MouseMoveMessage {
+            x: 10,
+            y: 20,
+            delta_x: 1,
+            delta_y: 2,
+        };
+        let btn = MouseButtonMessage {
+            button: kvm_core::protocol::messages::MouseButton::Left,
+            event_type: kvm_core::protocol::messages::ButtonEventType::Press,
+            x: 10,
+            y: 20,
+        };
+        let scroll = MouseScrollMessage {
+            delta_x: 0,
+            delta_y: -120,
+            x: 10,
+            y: 20,
+        };
+
+        // Act
+        transmitter
+            .send_mouse_move(client_id, mv.clone())
+            .await
+            .unwrap();
+        transmitter
+            .send_mouse_button(client_id, btn.clone())
+            .await
+            .unwrap();
+        transmitter
+            .send_mouse_scroll(client_id, scroll.clone())
+            .await
+            .unwrap();
+
+        // Assert – HelloAck used sequence 0 on this connection, so input
+        // frames continue at 1, 2, 3.
+        let (s1, m1) = read_raw_frame(&mut stream).await;
+        let (s2, m2) = read_raw_frame(&mut stream).await;
+        let (s3, m3) = read_raw_frame(&mut stream).await;
+        assert_eq!((s1, s2, s3), (1, 2, 3));
+        assert_eq!(m1, KvmMessage::MouseMove(mv));
+        assert_eq!(m2, KvmMessage::MouseButton(btn));
+        assert_eq!(m3, KvmMessage::MouseScroll(scroll));
+    }
+
+    #[test]
+    fn test_only_mouse_moves_prefer_udp() {
+        // Arrange
+        let mv = KvmMessage::MouseMove(MouseMoveMessage {
+            x: 10,
+            y: 20,
+            delta_x: 1,
+            delta_y: 2,
+        });
+        let key = KvmMessage::KeyEvent(key_a_down());
+        let btn = KvmMessage::MouseButton(MouseButtonMessage {
+            button: kvm_core::protocol::messages::MouseButton::Left,
+            event_type: kvm_core::protocol::messages::ButtonEventType::Release,
+            x: 10,
+            y: 20,
+        });
+
+        // Act / Assert – a lost KeyUp or button release must not be possible.
+        assert!(prefers_udp(&mv));
+        assert!(!prefers_udp(&key));
+        assert!(!prefers_udp(&btn));
+    }
+
+    #[tokio::test]
+    async fn test_delivered_events_are_counted_for_throughput() {
+        // Arrange
+        let (mgr, _rx, _stream, client_id) = connected_client().await;
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+
+        // Act
+        for _ in 0..3 {
+            transmitter
+                .send_key_event(client_id, key_a_down())
+                .await
+                .unwrap();
+        }
+
+        // Assert – three events over one second
+        let session = mgr.session(client_id).unwrap();
+        assert_eq!(session.link().sample_throughput(Duration::from_secs(1)), 3);
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_is_skipped_for_client_without_clipboard_sharing() {
+        // Arrange – the fixture client only advertises keyboard and mouse
+        let (mgr, _rx, mut stream, client_id) = connected_client().await;
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+
+        // Act
+        let result = transmitter
+            .send_clipboard(client_id, ClipboardContent::text("secret"))
+            .await;
+        transmitter
+            .send_key_event(client_id, key_a_down())
+            .await
+            .unwrap();
+
+        // Assert – no ClipboardData frame was queued ahead of the key event
+        assert_eq!(result, Ok(()));
+        let (sequence, msg) = read_raw_frame(&mut stream).await;
+        assert_eq!(sequence, 1);
+        assert_eq!(msg, KvmMessage::KeyEvent(key_a_down()));
+    }
+
+    #[tokio::test]
+    async fn test_html_clipboard_reaches_text_only_client_as_text() {
+        // Arrange
+        let (mgr, _rx, mut stream, client_id) =
+            connected_client_with(capabilities::CLIPBOARD_SHARING).await;
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+        let html = HtmlClipboard {
+            html: "<i>draft</i>".to_string(),
+            text: "draft".to_string(),
+        };
+
+        // Act
+        transmitter
+            .send_clipboard(client_id, html.to_content())
+            .await
+            .expect("send must succeed");
+
+        // Assert
+        let (_, msg) = read_raw_frame(&mut stream).await;
+        match msg {
+            KvmMessage::ClipboardData(fragment) => {
+                assert_eq!(fragment.format, ClipboardFormat::Utf8Text);
+                assert_eq!(fragment.data, b"draft");
+            }
+            other => panic!("expected ClipboardData, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_to_unknown_client_returns_error() {
+        // Arrange
+        let (mgr, _rx) = ConnectionManager::new(NetworkConfig::default());
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+        let unknown = Uuid::new_v4();
+
+        // Act
+        let result = transmitter.send_key_event(unknown, key_a_down()).await;
+
+        // Assert
+        let err = result.expect_err("unknown client must be an error");
+        assert!(err.contains("client not found"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn test_send_after_client_disconnects_returns_error() {
+        // Arrange
+        let (mgr, mut rx, stream, client_id) = connected_client().await;
+        let transmitter = NetworkInputTransmitter::new(mgr.session_registry());
+
+        // Act – the client hangs up and the master notices.
+        drop(stream);
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for disconnect");
+        assert!(matches!(
+            event,
+            Some(ConnectionEvent::ClientDisconnected { .. })
+        ));
+        let result = transmitter.send_key_event(client_id, key_a_down()).await;
+
+        // Assert
+        assert!(result.is_err(), "disconnected client must be an error");
+    }
+}
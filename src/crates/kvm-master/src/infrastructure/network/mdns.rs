@@ -0,0 +1,154 @@
+//! mDNS / DNS-SD advertisement of the master, the alternative to the
+//! broadcast responder in `discovery`.
+//!
+//! # What gets published (for beginners)
+//!
+//! Multicast DNS lets machines answer DNS questions for the `.local.` domain
+//! among themselves, without a DNS server.  DNS-SD builds service discovery on
+//! top: a client asks "who offers `_kvm-over-ip._tcp`?" and every master
+//! answers with three records:
+//!
+//! ```text
+//! PTR  _kvm-over-ip._tcp.local.            → office-pc._kvm-over-ip._tcp.local.
+//! SRV  office-pc._kvm-over-ip._tcp.local.  → office-pc.local.:24800
+//! TXT  office-pc._kvm-over-ip._tcp.local.  → protover=1 minver=1 control_port=24800
+//! A    office-pc.local.                    → 192.168.1.10
+//! ```
+//!
+//! The record layout is defined in `kvm_core::protocol::discovery`, which the
+//! client's browser shares.  The responder itself is the pure-Rust `mdns-sd`
+//! daemon, which runs on its own thread and answers queries until
+//! [`MdnsAdvertiser`] is dropped.
+//!
+//! # Compared with the broadcast responder
+//!
+//! mDNS cannot say whether the asking client is already paired (the answer is
+//! the same for everyone), so clients that find a master this way see
+//! `already_paired = false`.  Pairing itself is unaffected.
+
+use kvm_core::protocol::discovery::{MasterTxt, SERVICE_TYPE};
+use mdns_sd::{IfKind, ServiceDaemon, ServiceInfo};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Errors from the mDNS advertiser.
+#[derive(Debug, Error)]
+pub enum MdnsError {
+    /// The mDNS daemon could not start or rejected the service.
+    #[error("mDNS error: {0}")]
+    Daemon(#[from] mdns_sd::Error),
+}
+
+/// What to advertise.
+#[derive(Debug, Clone)]
+pub struct AdvertiseConfig {
+    /// Instance name shown to clients, usually the host name.
+    pub instance_name: String,
+    /// TCP control port clients should connect to.
+    pub control_port: u16,
+    /// Also answer on loopback interfaces (off by default, as in `mdns-sd`).
+    /// Needed when master and client run on the same machine, e.g. in tests.
+    pub include_loopback: bool,
+}
+
+/// A running advertisement.  Dropping it withdraws the service (a "goodbye"
+/// packet tells browsers it is gone) and stops the daemon.
+pub struct MdnsAdvertiser {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl MdnsAdvertiser {
+    /// Starts an mDNS responder advertising this master.
+    ///
+    /// The advertised addresses follow the machine's interfaces as they come
+    /// and go.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MdnsError::Daemon`] if the multicast sockets cannot be
+    /// opened or the instance name is not a valid DNS label.
+    pub fn start(config: &AdvertiseConfig) -> Result<Self, MdnsError> {
+        let daemon = ServiceDaemon::new()?;
+        if config.include_loopback {
+            daemon.enable_interface(IfKind::LoopbackV4)?;
+        }
+
+        let host_name = format!("{}.local.", dns_label(&config.instance_name));
+        let txt = MasterTxt::new(config.control_port).to_properties();
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            &config.instance_name,
+            &host_name,
+            "",
+            config.control_port,
+            &txt[..],
+        )?
+        .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+        daemon.register(service)?;
+
+        info!("advertising {fullname} via mDNS");
+        Ok(Self { daemon, fullname })
+    }
+
+    /// Full DNS-SD name of the advertised instance.
+    pub fn fullname(&self) -> &str {
+        &self.fullname
+    }
+}
+
+impl Drop for MdnsAdvertiser {
+    fn drop(&mut self) {
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            warn!("failed to withdraw mDNS service {}: {e}", self.fullname);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Turns a display name into a host label: letters, digits and hyphens only.
+fn dns_label(name: &str) -> String {
+    let label: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        "kvm-master".to_string()
+    } else {
+        label.to_string()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_label_replaces_characters_dns_does_not_allow() {
+        assert_eq!(dns_label("Office PC (2)"), "Office-PC--2");
+        assert_eq!(dns_label("研究室"), "kvm-master");
+    }
+
+    #[test]
+    fn test_advertised_instance_has_the_service_type() {
+        // Arrange
+        let config = AdvertiseConfig {
+            instance_name: "unit-test-master".to_string(),
+            control_port: 24800,
+            include_loopback: false,
+        };
+
+        // Act
+        let advertiser = MdnsAdvertiser::start(&config).unwrap();
+
+        // Assert
+        assert_eq!(
+            advertiser.fullname(),
+            "unit-test-master._kvm-over-ip._tcp.local."
+        );
+    }
+}
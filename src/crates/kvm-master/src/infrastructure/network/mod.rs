@@ -3,12 +3,32 @@
 //! # Sub-modules
 //!
 //! - **`connection_manager`** – Manages the pairing state machine and the per-client
-//!   TCP control channel lifecycle.  Handles PIN generation, hash verification,
-//!   lockout on repeated failures, and session token issuance.
+//!   TCP control channel lifecycle.  Handles PIN pairing, lockout on repeated
+//!   failures, and session token issuance.
+//!
+//! - **`heartbeat`** – Per-session link measurements: the `Ping`/`Pong`
+//!   round-trip time, events forwarded per second, and the missed-`Pong`
+//!   count that decides when a silent client is dropped.
 //!
 //! - **`discovery`** – Listens for UDP `AnnounceMessage` broadcasts from clients
 //!   on the local network and notifies the application layer via an async channel.
 //!   This is how clients are found without manual IP configuration.
+//!
+//! - **`mdns`** – The alternative to `discovery`: advertises the master as a
+//!   `_kvm-over-ip._tcp` DNS-SD service over multicast DNS, for networks that
+//!   filter broadcasts.  `network.discovery_backend` picks one of the two.
+//!
+//! - **`input_channel`** – The encrypted UDP socket on `input_port`.  Verifies
+//!   each client's UDP path and carries input datagrams while it is live.
+//!
+//! - **`input_transmitter`** – The production `InputTransmitter`: sends the
+//!   key and mouse events chosen by `RouteInputUseCase` to the target client's
+//!   live session, over the UDP input channel when possible and the control
+//!   channel otherwise.
 
 pub mod connection_manager;
 pub mod discovery;
+pub mod heartbeat;
+pub mod input_channel;
+pub mod input_transmitter;
+pub mod mdns;
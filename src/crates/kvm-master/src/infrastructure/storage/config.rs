@@ -19,6 +19,10 @@
 //! [master]
 //! disable_hotkey = "ScrollLock+ScrollLock"
 //! autostart = true
+//!
+//! [[master.hotkeys]]
+//! keys = "Ctrl+Alt+Right"
+//! action = { jump_to_client = 1 }
 //! ```
 //!
 //! The `serde` library provides automatic serialisation/deserialisation between
@@ -34,10 +38,20 @@
 
 use std::path::PathBuf;
 
+use kvm_core::domain::layout::{Monitor, VirtualLayout};
+use kvm_core::keymap::hotkey::{Hotkey, HotkeyParseError};
+use kvm_core::protocol::discovery::DiscoveryBackend;
+use kvm_core::protocol::messages::ScreenInfoMessage;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::application::edge_crossing::EdgeCrossingConfig;
+use crate::application::hotkeys::{HotkeyAction, HotkeyBindings};
+use crate::application::update_layout::{
+    build_layout_with_master_monitors, ClientLayoutConfig, UpdateLayoutError,
+};
+
 /// Error type for configuration file operations.
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -60,6 +74,14 @@ pub enum ConfigError {
     /// The config could not be serialized to TOML.
     #[error("failed to serialize config: {0}")]
     Serialize(#[from] toml::ser::Error),
+
+    /// A hotkey string does not follow the hotkey grammar.
+    #[error("invalid hotkey {hotkey:?}: {source}")]
+    InvalidHotkey {
+        hotkey: String,
+        #[source]
+        source: HotkeyParseError,
+    },
 }
 
 // ── Config schema types ───────────────────────────────────────────────────────
@@ -80,15 +102,57 @@ pub struct MasterConfig {
     /// Schema version string – bump when breaking changes are introduced.
     #[serde(default = "default_version")]
     pub version: String,
-    /// Human-readable hotkey description (e.g. `"ScrollLock+ScrollLock"`).
+    /// Hotkey that toggles input sharing, in the grammar of
+    /// [`kvm_core::keymap::hotkey`] (e.g. `"ScrollLock ScrollLock"`).
     #[serde(default = "default_hotkey")]
     pub disable_hotkey: String,
+    /// Further hotkeys bound to routing actions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotkeys: Vec<HotkeyEntry>,
     /// Whether the master starts minimised to tray on OS login.
     #[serde(default = "default_true")]
     pub autostart: bool,
     /// `tracing` log level: `"error"`, `"warn"`, `"info"`, `"debug"`, `"trace"`.
     #[serde(default = "default_log_level")]
     pub log_level: String,
+    /// The edge threshold and the conditions for crossing screen edges.
+    #[serde(default)]
+    pub edge_crossing: EdgeCrossingConfig,
+}
+
+/// One `[[master.hotkeys]]` binding.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HotkeyEntry {
+    /// The key sequence, e.g. `"Ctrl+Alt+1"`.
+    pub keys: String,
+    /// What the hotkey does.
+    pub action: HotkeyAction,
+}
+
+impl MasterConfig {
+    /// Parses `disable_hotkey` and `hotkeys` into the router's bindings.
+    ///
+    /// `disable_hotkey` is bound first, to [`HotkeyAction::ToggleSharing`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::InvalidHotkey`] for the first hotkey string
+    /// that does not parse.
+    pub fn hotkey_bindings(&self) -> Result<HotkeyBindings, ConfigError> {
+        let parse = |keys: &str| {
+            keys.parse::<Hotkey>()
+                .map_err(|source| ConfigError::InvalidHotkey {
+                    hotkey: keys.to_string(),
+                    source,
+                })
+        };
+        let mut bindings = HotkeyBindings::new();
+        bindings.bind(parse(&self.disable_hotkey)?, HotkeyAction::ToggleSharing);
+        for entry in &self.hotkeys {
+            bindings.bind(parse(&entry.keys)?, entry.action);
+        }
+        Ok(bindings)
+    }
 }
 
 /// Network port and bind-address settings.
@@ -97,12 +161,21 @@ pub struct NetworkConfig {
     /// TCP port for the TLS control channel.
     #[serde(default = "default_control_port")]
     pub control_port: u16,
-    /// UDP port for the DTLS input channel.
+    /// UDP port for the encrypted input channel.
     #[serde(default = "default_input_port")]
     pub input_port: u16,
+    /// Whether mouse movement may be sent over UDP.  When
+    /// `false` (or when a client's UDP path is not working) all input goes
+    /// over the control channel.
+    #[serde(default = "default_true")]
+    pub udp_input: bool,
     /// UDP port for LAN device discovery broadcasts.
     #[serde(default = "default_discovery_port")]
     pub discovery_port: u16,
+    /// How clients find this master: `"broadcast"` answers `Announce`
+    /// datagrams on `discovery_port`, `"mdns"` advertises a DNS-SD service.
+    #[serde(default)]
+    pub discovery_backend: DiscoveryBackend,
     /// IP address to bind all sockets to.  `"0.0.0.0"` binds all interfaces.
     #[serde(default = "default_bind_address")]
     pub bind_address: String,
@@ -117,11 +190,103 @@ pub struct LayoutConfig {
     /// Physical height of the master monitor in pixels.
     #[serde(default = "default_screen_height")]
     pub master_screen_height: u32,
+    /// The master's monitors, for masters with more than one.  When empty,
+    /// `master_screen_width` × `master_screen_height` is the only monitor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub master_monitors: Vec<MasterMonitorEntry>,
+    /// Map edge crossings and pointer speed by physical size rather than
+    /// pixels (see `VirtualLayout::set_physical_units`).
+    #[serde(default)]
+    pub physical_units: bool,
     /// Positioned client screens.
     #[serde(default)]
     pub clients: Vec<ClientLayoutEntry>,
 }
 
+impl LayoutConfig {
+    /// The master's monitors as the layout expects them.
+    pub fn master_monitor_list(&self) -> Vec<Monitor> {
+        if self.master_monitors.is_empty() {
+            return vec![Monitor::primary(
+                self.master_screen_width,
+                self.master_screen_height,
+            )];
+        }
+        self.master_monitors
+            .iter()
+            .enumerate()
+            .map(|(i, m)| Monitor {
+                monitor_id: i.min(u8::MAX as usize) as u8,
+                x: m.x_offset,
+                y: m.y_offset,
+                width: m.width,
+                height: m.height,
+                is_primary: m.x_offset == 0 && m.y_offset == 0,
+                scale_factor: m.scale_factor,
+                size_mm: m.width_mm.zip(m.height_mm),
+            })
+            .collect()
+    }
+
+    /// Builds the layout the input router works with.
+    ///
+    /// `screen_info` returns what a connected client reported about its
+    /// monitors; such a client is laid out with those monitors (and their
+    /// scale factors) instead of the configured width and height.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UpdateLayoutError::ValidationFailed`] if screens overlap.
+    pub fn virtual_layout(
+        &self,
+        screen_info: impl Fn(Uuid) -> Option<ScreenInfoMessage>,
+    ) -> Result<VirtualLayout, UpdateLayoutError> {
+        let clients = self
+            .clients
+            .iter()
+            .map(|c| ClientLayoutConfig {
+                client_id: c.client_id,
+                name: c.name.clone(),
+                x_offset: c.x_offset,
+                y_offset: c.y_offset,
+                width: c.width,
+                height: c.height,
+                monitors: screen_info(c.client_id)
+                    .map(|info| info.monitors)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        let mut layout = build_layout_with_master_monitors(self.master_monitor_list(), clients)?;
+        layout.set_physical_units(self.physical_units);
+        Ok(layout)
+    }
+}
+
+/// One monitor of the master, in the master's desktop coordinates.
+///
+/// The primary monitor is the one at offset (0, 0).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MasterMonitorEntry {
+    /// Horizontal offset in pixels from the primary monitor's top-left corner.
+    pub x_offset: i32,
+    /// Vertical offset in pixels from the primary monitor's top-left corner.
+    pub y_offset: i32,
+    /// Monitor width in pixels.
+    pub width: u32,
+    /// Monitor height in pixels.
+    pub height: u32,
+    /// DPI scale factor times 100, as set in the OS (150 = 150 %).
+    #[serde(default = "default_scale_factor")]
+    pub scale_factor: u16,
+    /// Physical width in millimetres.  Used together with `height_mm`
+    /// instead of `scale_factor` when both are given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width_mm: Option<u32>,
+    /// Physical height in millimetres.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height_mm: Option<u32>,
+}
+
 /// Positioned layout entry for a single client screen.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ClientLayoutEntry {
@@ -149,11 +314,39 @@ pub struct ClientEntry {
     /// Optional static IP – if absent, discovery resolves the address.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub host: Option<String>,
-    /// SHA-256 derived pairing hash stored after successful PIN exchange.
+    /// Fingerprint of the client's TLS certificate, pinned after a successful
+    /// PIN exchange, in the form `sha256:<64 hex digits>`.
+    ///
+    /// While this is set, the master only accepts connections for this client
+    /// ID that present the same certificate.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pairing_hash: Option<String>,
 }
 
+impl AppConfig {
+    /// Records a completed pairing, adding a [`ClientEntry`] for `client_id`
+    /// or updating the existing one.
+    ///
+    /// The entry's `host` is left untouched; `name` is only updated when
+    /// non-empty so a reconnect without a name does not erase it.
+    pub fn record_pairing(&mut self, client_id: Uuid, name: &str, pairing_hash: Option<String>) {
+        match self.clients.iter_mut().find(|c| c.client_id == client_id) {
+            Some(entry) => {
+                if !name.is_empty() {
+                    entry.name = name.to_string();
+                }
+                entry.pairing_hash = pairing_hash;
+            }
+            None => self.clients.push(ClientEntry {
+                client_id,
+                name: name.to_string(),
+                host: None,
+                pairing_hash,
+            }),
+        }
+    }
+}
+
 // ── Default helpers ───────────────────────────────────────────────────────────
 
 fn default_version() -> String {
@@ -186,6 +379,9 @@ fn default_screen_width() -> u32 {
 fn default_screen_height() -> u32 {
     1080
 }
+fn default_scale_factor() -> u16 {
+    100
+}
 
 // AppConfig derives Default because all its fields implement Default.
 
@@ -194,8 +390,10 @@ impl Default for MasterConfig {
         Self {
             version: default_version(),
             disable_hotkey: default_hotkey(),
+            hotkeys: Vec::new(),
             autostart: default_true(),
             log_level: default_log_level(),
+            edge_crossing: EdgeCrossingConfig::default(),
         }
     }
 }
@@ -205,7 +403,9 @@ impl Default for NetworkConfig {
         Self {
             control_port: default_control_port(),
             input_port: default_input_port(),
+            udp_input: default_true(),
             discovery_port: default_discovery_port(),
+            discovery_backend: DiscoveryBackend::default(),
             bind_address: default_bind_address(),
         }
     }
@@ -216,6 +416,8 @@ impl Default for LayoutConfig {
         Self {
             master_screen_width: default_screen_width(),
             master_screen_height: default_screen_height(),
+            master_monitors: Vec::new(),
+            physical_units: false,
             clients: Vec::new(),
         }
     }
@@ -249,13 +451,15 @@ pub fn config_file_path() -> Result<PathBuf, ConfigError> {
 /// # Errors
 ///
 /// Returns [`ConfigError::Io`] for file-system errors other than "not found",
-/// and [`ConfigError::Parse`] if the TOML is malformed.
+/// [`ConfigError::Parse`] if the TOML is malformed, and
+/// [`ConfigError::InvalidHotkey`] if a hotkey does not parse.
 pub fn load_config() -> Result<AppConfig, ConfigError> {
     let path = config_file_path()?;
 
     match std::fs::read_to_string(&path) {
         Ok(content) => {
             let cfg: AppConfig = toml::from_str(&content)?;
+            cfg.master.hotkey_bindings()?;
             Ok(cfg)
         }
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AppConfig::default()),
@@ -330,6 +534,8 @@ fn platform_config_dir() -> Option<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use kvm_core::domain::layout::ScreenId;
+    use kvm_core::protocol::messages::MonitorInfo;
 
     // ── AppConfig defaults ────────────────────────────────────────────────────
 
@@ -342,6 +548,8 @@ mod tests {
         assert_eq!(cfg.network.control_port, 24800);
         assert_eq!(cfg.network.input_port, 24801);
         assert_eq!(cfg.network.discovery_port, 24802);
+        assert!(cfg.network.udp_input);
+        assert_eq!(cfg.network.discovery_backend, DiscoveryBackend::Broadcast);
     }
 
     #[test]
@@ -444,6 +652,24 @@ mod tests {
         assert_eq!(restored.clients[0].pairing_hash, None);
     }
 
+    #[test]
+    fn test_record_pairing_adds_then_updates_client_entry() {
+        // Arrange
+        let client_id = Uuid::new_v4();
+        let mut cfg = AppConfig::default();
+
+        // Act
+        cfg.record_pairing(client_id, "laptop", Some("sha256:aa".to_string()));
+        cfg.clients[0].host = Some("10.0.0.5".to_string());
+        cfg.record_pairing(client_id, "", Some("sha256:bb".to_string()));
+
+        // Assert – one entry, new pin, name and host preserved
+        assert_eq!(cfg.clients.len(), 1);
+        assert_eq!(cfg.clients[0].name, "laptop");
+        assert_eq!(cfg.clients[0].host.as_deref(), Some("10.0.0.5"));
+        assert_eq!(cfg.clients[0].pairing_hash.as_deref(), Some("sha256:bb"));
+    }
+
     #[test]
     fn test_deserialize_minimal_toml_uses_defaults() {
         // Arrange: minimal TOML with only required sections
@@ -481,6 +707,23 @@ control_port = 9999
         assert_eq!(cfg.network.input_port, 24801);
     }
 
+    #[test]
+    fn test_deserialize_mdns_discovery_backend() {
+        // Arrange
+        let toml_str = r#"
+[master]
+[network]
+discovery_backend = "mdns"
+[layout]
+"#;
+
+        // Act
+        let cfg: AppConfig = toml::from_str(toml_str).expect("deserialize");
+
+        // Assert
+        assert_eq!(cfg.network.discovery_backend, DiscoveryBackend::Mdns);
+    }
+
     #[test]
     fn test_deserialize_invalid_toml_returns_parse_error() {
         // Arrange
@@ -493,6 +736,218 @@ control_port = 9999
         assert!(result.is_err());
     }
 
+    // ── Hotkeys ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_default_disable_hotkey_binds_toggle_sharing() {
+        // Arrange
+        let cfg = MasterConfig::default();
+
+        // Act
+        let bindings = cfg.hotkey_bindings().expect("default hotkey parses");
+
+        // Assert
+        let bound: Vec<_> = bindings
+            .iter()
+            .map(|(hotkey, action)| (hotkey.to_string(), action))
+            .collect();
+        assert_eq!(
+            bound,
+            [(
+                "ScrollLock ScrollLock".to_string(),
+                HotkeyAction::ToggleSharing
+            )]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_hotkey_bindings() {
+        // Arrange
+        let toml_str = r#"
+[master]
+disable_hotkey = "Ctrl+Alt+Pause"
+
+[[master.hotkeys]]
+keys = "Ctrl+Alt+1"
+action = { jump_to_client = 1 }
+
+[[master.hotkeys]]
+keys = "Ctrl+Alt+L"
+action = "lock_cursor"
+
+[network]
+[layout]
+"#;
+
+        // Act
+        let cfg: AppConfig = toml::from_str(toml_str).expect("deserialize");
+        let bindings = cfg.master.hotkey_bindings().expect("hotkeys parse");
+
+        // Assert
+        let actions: Vec<_> = bindings.iter().map(|(_, action)| action).collect();
+        assert_eq!(
+            actions,
+            [
+                HotkeyAction::ToggleSharing,
+                HotkeyAction::JumpToClient(std::num::NonZeroUsize::MIN),
+                HotkeyAction::LockCursor,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_master_monitors() {
+        // Arrange – a portrait monitor to the right of the primary
+        let toml_str = r#"
+[master]
+[network]
+[layout]
+
+[[layout.master_monitors]]
+x_offset = 0
+y_offset = 0
+width = 1920
+height = 1080
+
+[[layout.master_monitors]]
+x_offset = 1920
+y_offset = -420
+width = 1080
+height = 1920
+"#;
+
+        // Act
+        let cfg: AppConfig = toml::from_str(toml_str).expect("deserialize");
+        let monitors = cfg.layout.master_monitor_list();
+
+        // Assert
+        assert_eq!(monitors.len(), 2);
+        assert!(monitors[0].is_primary);
+        assert_eq!((monitors[1].x, monitors[1].y), (1920, -420));
+        assert!(!monitors[1].is_primary);
+        assert_eq!(monitors[1].scale_factor, 100);
+    }
+
+    #[test]
+    fn test_master_monitors_carry_scale_and_physical_size() {
+        // Arrange
+        let toml_str = r#"
+[master]
+[network]
+[layout]
+physical_units = true
+
+[[layout.master_monitors]]
+x_offset = 0
+y_offset = 0
+width = 3840
+height = 2160
+scale_factor = 150
+width_mm = 597
+height_mm = 336
+"#;
+
+        // Act
+        let cfg: AppConfig = toml::from_str(toml_str).expect("deserialize");
+        let monitors = cfg.layout.master_monitor_list();
+
+        // Assert
+        assert!(cfg.layout.physical_units);
+        assert_eq!(monitors[0].scale_factor, 150);
+        assert_eq!(monitors[0].size_mm, Some((597, 336)));
+    }
+
+    #[test]
+    fn test_master_monitor_list_defaults_to_screen_size() {
+        let monitors = LayoutConfig::default().master_monitor_list();
+        assert_eq!(monitors, [Monitor::primary(1920, 1080)]);
+    }
+
+    /// A layout with one 1920×1080 client to the right of the master.
+    fn layout_with_client(client_id: Uuid) -> LayoutConfig {
+        LayoutConfig {
+            physical_units: true,
+            clients: vec![ClientLayoutEntry {
+                client_id,
+                name: "laptop".to_string(),
+                x_offset: 1920,
+                y_offset: 0,
+                width: 1920,
+                height: 1080,
+            }],
+            ..LayoutConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_virtual_layout_uses_reported_monitors_and_scale() {
+        // Arrange – the client reports a 4K monitor at 200 %.
+        let client_id = Uuid::new_v4();
+        let layout_cfg = layout_with_client(client_id);
+        let info = ScreenInfoMessage {
+            monitors: vec![MonitorInfo {
+                monitor_id: 0,
+                x_offset: 0,
+                y_offset: 0,
+                width: 3840,
+                height: 2160,
+                scale_factor: 200,
+                is_primary: true,
+            }],
+        };
+
+        // Act
+        let layout = layout_cfg
+            .virtual_layout(|id| (id == client_id).then(|| info.clone()))
+            .expect("layout");
+
+        // Assert
+        let id = ScreenId::Client(client_id);
+        let region = layout.get_region(&id).expect("client region");
+        assert_eq!((region.width, region.height), (3840, 2160));
+        assert!(layout.physical_units());
+        assert_eq!(layout.pointer_speed(&id, 1920 + 100, 100), 2.0);
+    }
+
+    #[test]
+    fn test_virtual_layout_without_report_uses_configured_size() {
+        // Arrange
+        let client_id = Uuid::new_v4();
+        let layout_cfg = layout_with_client(client_id);
+
+        // Act
+        let layout = layout_cfg.virtual_layout(|_| None).expect("layout");
+
+        // Assert
+        let id = ScreenId::Client(client_id);
+        let region = layout.get_region(&id).expect("client region");
+        assert_eq!((region.width, region.height), (1920, 1080));
+        assert_eq!(layout.pointer_speed(&id, 1920 + 100, 100), 1.0);
+    }
+
+    #[test]
+    fn test_invalid_hotkey_is_reported_with_its_text() {
+        // Arrange
+        let mut cfg = MasterConfig::default();
+        cfg.hotkeys.push(HotkeyEntry {
+            keys: "Ctrl+Alt".to_string(),
+            action: HotkeyAction::ReturnToMaster,
+        });
+
+        // Act
+        let err = cfg.hotkey_bindings().unwrap_err();
+
+        // Assert
+        assert!(matches!(
+            &err,
+            ConfigError::InvalidHotkey {
+                hotkey,
+                source: HotkeyParseError::MissingKey(_),
+            } if hotkey == "Ctrl+Alt"
+        ));
+        assert!(err.to_string().contains("Ctrl+Alt"));
+    }
+
     // ── load_config from temp directory ──────────────────────────────────────
 
     #[test]
@@ -43,18 +43,29 @@
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, warn};
 
 use crate::application::{
     manage_clients::{ClientRegistry, ClientRuntimeState},
-    update_layout::{build_layout, ClientLayoutConfig},
+    route_input::FocusRequest,
+    update_layout::UpdateLayoutError,
 };
 use crate::infrastructure::{
-    network::connection_manager::{ConnectionManager, NetworkConfig},
-    storage::config::{load_config, save_config, AppConfig, ClientLayoutEntry},
+    network::connection_manager::{ConnectionEvent, ConnectionManager, NetworkConfig},
+    network::heartbeat::HeartbeatConfig,
+    storage::config::{load_config, save_config, AppConfig, ClientLayoutEntry, LayoutConfig},
 };
+use kvm_core::domain::layout::{ScreenId, VirtualLayout};
 use kvm_core::ClientId;
 
+/// How many focus requests may queue up before `switch_focus` and friends
+/// report the router as busy.
+const FOCUS_REQUEST_CAPACITY: usize = 16;
+
+/// How many rebuilt layouts may wait for the input router.
+const LAYOUT_UPDATE_CAPACITY: usize = 4;
+
 // ── Shared application state ──────────────────────────────────────────────────
 
 /// Application state shared between Tauri commands via `tauri::State`.
@@ -78,6 +89,19 @@ pub struct AppState {
     pub client_registry: Mutex<ClientRegistry>,
     /// Manages TCP connections and the pairing state machine.
     pub connection_manager: Mutex<ConnectionManager>,
+    /// Events from the connection manager.  Taken (once) by the event pump
+    /// started in `main`, which is why it is wrapped in an `Option`.
+    pub connection_events: Mutex<Option<mpsc::Receiver<ConnectionEvent>>>,
+    /// Sends focus changes requested from the UI to the input router.
+    pub focus_requests: mpsc::Sender<FocusRequest>,
+    /// The receiving end of `focus_requests`.  Taken (once) by the task that
+    /// owns the `RouteInputUseCase`.
+    pub focus_request_rx: Mutex<Option<mpsc::Receiver<FocusRequest>>>,
+    /// Sends rebuilt layouts to the input router.
+    pub layout_updates: mpsc::Sender<VirtualLayout>,
+    /// The receiving end of `layout_updates`.  Taken (once) together with
+    /// `focus_request_rx`.
+    pub layout_update_rx: Mutex<Option<mpsc::Receiver<VirtualLayout>>>,
     /// The current application configuration (network ports, layout, etc.).
     pub config: Mutex<AppConfig>,
 }
@@ -85,7 +109,9 @@ pub struct AppState {
 impl AppState {
     /// Initialises application state from the persisted configuration.
     ///
-    /// Falls back to defaults if no config file exists yet.
+    /// Falls back to defaults if no config file exists yet.  Certificate pins
+    /// stored in `clients[].pairing_hash` are handed to the connection manager
+    /// so previously paired clients must keep presenting the same certificate.
     pub fn new() -> Arc<Self> {
         let config = load_config().unwrap_or_default();
         let net_cfg = NetworkConfig {
@@ -97,15 +123,66 @@ impl AppState {
                 .bind_address
                 .parse()
                 .unwrap_or_else(|_| "0.0.0.0".parse().unwrap()),
+            heartbeat: HeartbeatConfig::default(),
         };
-        let (conn_mgr, _event_rx) = ConnectionManager::new(net_cfg);
+        let (mut conn_mgr, event_rx) = ConnectionManager::new(net_cfg);
+        conn_mgr.restore_pairings(&config.clients);
+
+        let (focus_tx, focus_rx) = mpsc::channel(FOCUS_REQUEST_CAPACITY);
+        let (layout_tx, layout_rx) = mpsc::channel(LAYOUT_UPDATE_CAPACITY);
 
         Arc::new(Self {
             client_registry: Mutex::new(ClientRegistry::new()),
             connection_manager: Mutex::new(conn_mgr),
+            connection_events: Mutex::new(Some(event_rx)),
+            focus_requests: focus_tx,
+            focus_request_rx: Mutex::new(Some(focus_rx)),
+            layout_updates: layout_tx,
+            layout_update_rx: Mutex::new(Some(layout_rx)),
             config: Mutex::new(config),
         })
     }
+
+    /// Builds the input router's layout from the configured positions and
+    /// the monitors each connected client has reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UpdateLayoutError::ValidationFailed`] if screens overlap.
+    pub async fn router_layout(&self) -> Result<VirtualLayout, UpdateLayoutError> {
+        let layout = self.config.lock().await.layout.clone();
+        self.layout_with_reported_monitors(&layout).await
+    }
+
+    /// Rebuilds the router's layout and hands it to the input router.
+    ///
+    /// Called whenever a client reports new monitors.
+    pub async fn refresh_router_layout(&self) {
+        match self.router_layout().await {
+            Ok(layout) => self.send_layout_update(layout),
+            Err(e) => warn!("layout not updated: {e}"),
+        }
+    }
+
+    async fn layout_with_reported_monitors(
+        &self,
+        layout: &LayoutConfig,
+    ) -> Result<VirtualLayout, UpdateLayoutError> {
+        let mgr = self.connection_manager.lock().await;
+        layout.virtual_layout(|id| mgr.session(id).and_then(|s| s.screen_info()))
+    }
+
+    fn send_layout_update(&self, layout: VirtualLayout) {
+        match self.layout_updates.try_send(layout) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                warn!("input router is busy; layout update dropped")
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                debug!("input router is not running; layout update dropped")
+            }
+        }
+    }
 }
 
 // ── Data Transfer Objects (Presentation layer) ────────────────────────────────
@@ -148,10 +225,20 @@ pub struct ClientLayoutDto {
 pub struct NetworkConfigDto {
     pub control_port: u16,
     pub input_port: u16,
+    pub udp_input: bool,
     pub discovery_port: u16,
     pub bind_address: String,
 }
 
+/// DTO for a pairing the master has started: the UI shows `pin`, which the
+/// user types on the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingDto {
+    pub client_id: String,
+    pub session_id: String,
+    pub pin: String,
+}
+
 /// Unified response wrapper used by Tauri commands.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CommandResult<T: Serialize> {
@@ -191,6 +278,31 @@ pub async fn get_clients(state: Arc<AppState>) -> CommandResult<Vec<ClientDto>>
     CommandResult::ok(dtos)
 }
 
+/// Starts pairing with a connected client and returns the PIN to display.
+///
+/// The client shows a PIN prompt; the exchange completes when its answer
+/// arrives (`ConnectionEvent::PairingResponseReceived`), after which the
+/// client's certificate is pinned and its clipboard is accepted.
+pub async fn pair_client(state: Arc<AppState>, client_id: String) -> CommandResult<PairingDto> {
+    let id = match client_id.parse::<ClientId>() {
+        Ok(id) => id,
+        Err(e) => return CommandResult::err(format!("invalid client_id UUID: {e}")),
+    };
+    let challenge = state
+        .connection_manager
+        .lock()
+        .await
+        .pair_connected_client(id);
+    match challenge {
+        Ok(challenge) => CommandResult::ok(PairingDto {
+            client_id,
+            session_id: challenge.session_id.to_string(),
+            pin: challenge.pin,
+        }),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
 /// Returns the current layout configuration.
 pub async fn get_layout(state: Arc<AppState>) -> CommandResult<Vec<ClientLayoutDto>> {
     let config = state.config.lock().await;
@@ -218,12 +330,12 @@ pub async fn update_layout(
     clients: Vec<ClientLayoutDto>,
 ) -> CommandResult<()> {
     // Parse client IDs from strings
-    let configs: Vec<ClientLayoutConfig> = match clients
+    let entries: Vec<ClientLayoutEntry> = match clients
         .iter()
         .map(|dto| {
             dto.client_id
                 .parse::<ClientId>()
-                .map(|id| ClientLayoutConfig {
+                .map(|id| ClientLayoutEntry {
                     client_id: id,
                     name: dto.name.clone(),
                     x_offset: dto.x_offset,
@@ -238,43 +350,25 @@ pub async fn update_layout(
         Err(e) => return CommandResult::err(format!("invalid client_id UUID: {e}")),
     };
 
-    // Lock config to get master dimensions
-    let (master_w, master_h) = {
-        let cfg = state.config.lock().await;
-        (
-            cfg.layout.master_screen_width,
-            cfg.layout.master_screen_height,
-        )
+    // Validate layout geometry; connected clients are laid out with the
+    // monitors they reported.
+    let mut candidate = state.config.lock().await.layout.clone();
+    candidate.clients = entries;
+    let layout = match state.layout_with_reported_monitors(&candidate).await {
+        Ok(layout) => layout,
+        Err(e) => return CommandResult::err(e.to_string()),
     };
 
-    // Validate layout geometry
-    if let Err(e) = build_layout(master_w, master_h, configs) {
-        return CommandResult::err(e.to_string());
-    }
-
     // Persist to config file
     let mut cfg = state.config.lock().await;
-    cfg.layout.clients = clients
-        .iter()
-        .filter_map(|dto| {
-            dto.client_id
-                .parse::<ClientId>()
-                .ok()
-                .map(|id| ClientLayoutEntry {
-                    client_id: id,
-                    name: dto.name.clone(),
-                    x_offset: dto.x_offset,
-                    y_offset: dto.y_offset,
-                    width: dto.width,
-                    height: dto.height,
-                })
-        })
-        .collect();
+    cfg.layout.clients = candidate.clients;
 
     if let Err(e) = save_config(&cfg) {
         return CommandResult::err(format!("failed to save config: {e}"));
     }
+    drop(cfg);
 
+    state.send_layout_update(layout);
     CommandResult::ok(())
 }
 
@@ -284,6 +378,7 @@ pub async fn get_network_config(state: Arc<AppState>) -> CommandResult<NetworkCo
     CommandResult::ok(NetworkConfigDto {
         control_port: cfg.network.control_port,
         input_port: cfg.network.input_port,
+        udp_input: cfg.network.udp_input,
         discovery_port: cfg.network.discovery_port,
         bind_address: cfg.network.bind_address.clone(),
     })
@@ -297,6 +392,7 @@ pub async fn update_network_config(
     let mut cfg = state.config.lock().await;
     cfg.network.control_port = network.control_port;
     cfg.network.input_port = network.input_port;
+    cfg.network.udp_input = network.udp_input;
     cfg.network.discovery_port = network.discovery_port;
     cfg.network.bind_address = network.bind_address;
 
@@ -317,6 +413,48 @@ pub async fn get_sharing_enabled(_state: Arc<AppState>) -> CommandResult<bool> {
     CommandResult::ok(false)
 }
 
+/// Moves input focus to `target`: `"master"` or a client's UUID.
+///
+/// The switch itself happens in the input router; this only queues the
+/// request, so success means "accepted", not "done".
+pub async fn switch_focus(state: Arc<AppState>, target: String) -> CommandResult<()> {
+    let screen = if target.eq_ignore_ascii_case("master") {
+        ScreenId::Master
+    } else {
+        match target.parse::<ClientId>() {
+            Ok(id) => ScreenId::Client(id),
+            Err(e) => return CommandResult::err(format!("invalid focus target '{target}': {e}")),
+        }
+    };
+    send_focus_request(&state, FocusRequest::SwitchTo(screen))
+}
+
+/// Moves input focus to the next (`forward`) or previous screen in layout
+/// order.
+pub async fn cycle_focus(state: Arc<AppState>, forward: bool) -> CommandResult<()> {
+    let request = if forward {
+        FocusRequest::CycleNext
+    } else {
+        FocusRequest::CyclePrevious
+    };
+    send_focus_request(&state, request)
+}
+
+/// Moves input focus back to the master screen.
+pub async fn return_to_master(state: Arc<AppState>) -> CommandResult<()> {
+    send_focus_request(&state, FocusRequest::ReturnToMaster)
+}
+
+fn send_focus_request(state: &AppState, request: FocusRequest) -> CommandResult<()> {
+    match state.focus_requests.try_send(request) {
+        Ok(()) => CommandResult::ok(()),
+        Err(mpsc::error::TrySendError::Full(_)) => CommandResult::err("input router is busy"),
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            CommandResult::err("input router is not running")
+        }
+    }
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -337,11 +475,20 @@ mod tests {
                 .bind_address
                 .parse()
                 .unwrap_or_else(|_| "0.0.0.0".parse().unwrap()),
+            heartbeat: HeartbeatConfig::default(),
         };
-        let (conn_mgr, _event_rx) = ConnectionManager::new(net_cfg);
+        let (mut conn_mgr, event_rx) = ConnectionManager::new(net_cfg);
+        conn_mgr.restore_pairings(&config.clients);
+        let (focus_tx, focus_rx) = mpsc::channel(FOCUS_REQUEST_CAPACITY);
+        let (layout_tx, layout_rx) = mpsc::channel(LAYOUT_UPDATE_CAPACITY);
         Arc::new(AppState {
             client_registry: Mutex::new(ClientRegistry::new()),
             connection_manager: Mutex::new(conn_mgr),
+            connection_events: Mutex::new(Some(event_rx)),
+            focus_requests: focus_tx,
+            focus_request_rx: Mutex::new(Some(focus_rx)),
+            layout_updates: layout_tx,
+            layout_update_rx: Mutex::new(Some(layout_rx)),
             config: Mutex::new(config),
         })
     }
@@ -407,6 +554,8 @@ mod tests {
             height: 1080,
         }];
 
+        let mut layouts = state.layout_update_rx.lock().await.take().unwrap();
+
         // Act
         let result = update_layout(state, clients).await;
 
@@ -416,6 +565,8 @@ mod tests {
             "expected success, got error: {:?}",
             result.error
         );
+        let layout = layouts.try_recv().expect("the router gets the new layout");
+        assert!(layout.get_region(&ScreenId::Client(id)).is_some());
 
         // Cleanup: remove the config file written to the real platform config dir
         // to avoid contaminating subsequent test runs.
@@ -424,6 +575,23 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_pair_client_fails_for_client_that_is_not_connected() {
+        // Arrange
+        let state = make_state();
+        let id = ClientId::new_v4();
+
+        // Act
+        let result = pair_client(state, id.to_string()).await;
+
+        // Assert
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some(format!("client {id} is not connected").as_str())
+        );
+    }
+
     #[tokio::test]
     async fn test_get_network_config_returns_default_ports() {
         // Arrange
@@ -441,6 +609,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[allow(clippy::bool_assert_comparison)]
     async fn test_get_sharing_enabled_returns_false_initially() {
         // Arrange
         let state = make_state();
@@ -453,6 +622,59 @@ mod tests {
         assert_eq!(result.data.unwrap(), false);
     }
 
+    #[tokio::test]
+    async fn test_switch_focus_queues_request_for_router() {
+        // Arrange
+        let state = make_state();
+        let mut rx = state.focus_request_rx.lock().await.take().unwrap();
+        let id = ClientId::new_v4();
+
+        // Act
+        let to_client = switch_focus(Arc::clone(&state), id.to_string()).await;
+        let to_master = switch_focus(Arc::clone(&state), "Master".to_string()).await;
+        let cycle = cycle_focus(Arc::clone(&state), false).await;
+        let back = return_to_master(state).await;
+
+        // Assert
+        assert!(to_client.success && to_master.success && cycle.success && back.success);
+        assert_eq!(
+            rx.recv().await,
+            Some(FocusRequest::SwitchTo(ScreenId::Client(id)))
+        );
+        assert_eq!(
+            rx.recv().await,
+            Some(FocusRequest::SwitchTo(ScreenId::Master))
+        );
+        assert_eq!(rx.recv().await, Some(FocusRequest::CyclePrevious));
+        assert_eq!(rx.recv().await, Some(FocusRequest::ReturnToMaster));
+    }
+
+    #[tokio::test]
+    async fn test_switch_focus_rejects_invalid_target() {
+        // Arrange
+        let state = make_state();
+
+        // Act
+        let result = switch_focus(state, "not-a-uuid".to_string()).await;
+
+        // Assert
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not-a-uuid"));
+    }
+
+    #[tokio::test]
+    async fn test_focus_commands_fail_when_router_is_gone() {
+        // Arrange
+        let state = make_state();
+        drop(state.focus_request_rx.lock().await.take());
+
+        // Act
+        let result = cycle_focus(state, true).await;
+
+        // Assert
+        assert!(!result.success);
+    }
+
     #[test]
     fn test_command_result_ok_sets_success_true() {
         let r: CommandResult<i32> = CommandResult::ok(42);
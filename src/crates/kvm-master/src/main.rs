@@ -8,11 +8,14 @@
 //!
 //! ```text
 //! main()
-//!  └─ AppState::new()       -- loads config, creates registries
+//!  └─ AppState::new()       -- loads config, creates registries, restores pins
+//!  └─ TlsIdentity            -- loaded from (or created in) the config dir
 //!  └─ start services
-//!       ├─ InputCaptureService (Windows hook thread)
-//!       ├─ DiscoveryResponder  (UDP background thread)
-//!       └─ RouteInputUseCase   (Tokio task)
+//!       ├─ DiscoveryResponder  (UDP background thread) or mDNS advertiser
+//!       ├─ Control listener    (TCP accept loop, one task per client)
+//!       ├─ Input channel       (UDP, encrypted key/mouse datagrams)
+//!       ├─ InputSource         (evdev reader thread on Linux)
+//!       └─ RouteInputUseCase   (Tokio task, also serving UI focus requests)
 //! ```
 //!
 //! # What is Tokio? (for beginners)
@@ -40,11 +43,23 @@ use std::sync::{
     Arc,
 };
 
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 
+use kvm_core::clipboard::{mock::MockClipboardProvider, ClipboardProvider};
+use kvm_core::crypto::tls::TlsIdentity;
+use kvm_core::protocol::discovery::DiscoveryBackend;
 use kvm_master::application::manage_clients::{ClientRuntimeState, ConnectionState};
+use kvm_master::application::route_input::{CursorController, RouteInputUseCase};
 use kvm_master::infrastructure;
+use kvm_master::infrastructure::input_capture::{InputSource, RawInputEvent};
+use kvm_master::infrastructure::input_routing::{forward_captured, run_router};
+use kvm_master::infrastructure::network::connection_manager::ConnectionEvent;
+use kvm_master::infrastructure::network::discovery::AnnounceReply;
+use kvm_master::infrastructure::network::input_channel::InputChannel;
+use kvm_master::infrastructure::network::input_transmitter::NetworkInputTransmitter;
+use kvm_master::infrastructure::network::mdns::{AdvertiseConfig, MdnsAdvertiser};
+use kvm_master::infrastructure::storage::config::{config_dir, save_config};
 use kvm_master::infrastructure::ui_bridge::AppState;
 
 #[tokio::main]
@@ -65,22 +80,51 @@ async fn main() -> anyhow::Result<()> {
     let running = Arc::new(AtomicBool::new(true));
 
     // ── Discovery responder ────────────────────────────────────────────────────
-    let discovery_port = {
+    let (discovery_port, control_port, discovery_backend) = {
         let cfg = state.config.lock().await;
-        cfg.network.discovery_port
+        (
+            cfg.network.discovery_port,
+            cfg.network.control_port,
+            cfg.network.discovery_backend,
+        )
     };
-
-    let discovery_rx = match infrastructure::network::discovery::start_discovery_responder(
-        discovery_port,
-        Arc::clone(&running),
-    ) {
-        Ok(rx) => {
-            info!("discovery responder started on UDP {discovery_port}");
-            Some(rx)
+    // The advertiser is kept alive until `main` returns; dropping it
+    // withdraws the service.
+    let (discovery_rx, _mdns_advertiser) = match discovery_backend {
+        DiscoveryBackend::Broadcast => {
+            // Clients use `already_paired` to prefer this master when several answer.
+            let reply = AnnounceReply {
+                control_port,
+                is_paired: Arc::new(state.connection_manager.lock().await.pairing_check()),
+            };
+            match infrastructure::network::discovery::start_discovery_responder(
+                discovery_port,
+                reply,
+                Arc::clone(&running),
+            ) {
+                Ok(rx) => {
+                    info!("discovery responder started on UDP {discovery_port}");
+                    (Some(rx), None)
+                }
+                Err(e) => {
+                    error!("failed to start discovery responder: {e}");
+                    (None, None)
+                }
+            }
         }
-        Err(e) => {
-            error!("failed to start discovery responder: {e}");
-            None
+        DiscoveryBackend::Mdns => {
+            let config = AdvertiseConfig {
+                instance_name: host_name(),
+                control_port,
+                include_loopback: false,
+            };
+            match MdnsAdvertiser::start(&config) {
+                Ok(advertiser) => (None, Some(advertiser)),
+                Err(e) => {
+                    error!("failed to start mDNS advertiser: {e}");
+                    (None, None)
+                }
+            }
         }
     };
 
@@ -102,6 +146,169 @@ async fn main() -> anyhow::Result<()> {
         });
     }
 
+    // ── TLS identity ──────────────────────────────────────────────────────────
+    // The certificate is generated on first run and reused afterwards, so
+    // clients that pinned the master's fingerprint keep trusting it.
+    let identity = TlsIdentity::load_or_generate(&config_dir()?, "kvm-master")?;
+    info!("master certificate fingerprint: {}", identity.fingerprint());
+
+    // ── Control channel listener ──────────────────────────────────────────────
+    {
+        let mut mgr = state.connection_manager.lock().await;
+        mgr.enable_tls(&identity)?;
+        match mgr.start_control_listener(Arc::clone(&running)).await {
+            Ok(addr) => info!("control channel listening on TCP {addr}"),
+            Err(e) => error!("failed to start control channel listener: {e}"),
+        }
+    }
+
+    // ── UDP input channel ─────────────────────────────────────────────────────
+    // Optional: without it every input event simply travels over TCP.  The
+    // router's transmitter sends key and mouse-move events over it.
+    let udp_input = state.config.lock().await.network.udp_input;
+    let input_channel = if udp_input {
+        let mgr = state.connection_manager.lock().await;
+        match mgr.start_input_channel(Arc::clone(&running)).await {
+            Ok(channel) => {
+                if let Ok(addr) = channel.local_addr() {
+                    info!("input channel listening on UDP {addr}");
+                }
+                Some(channel)
+            }
+            Err(e) => {
+                error!("failed to start UDP input channel, using TCP only: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // ── Clipboard ─────────────────────────────────────────────────────────────
+    // There is no Windows clipboard backend yet.  The in-memory clipboard
+    // still relays copies between clients: what one client sends is what
+    // `RouteInputUseCase::with_clipboard` hands the next client on focus.
+    let clipboard: Arc<dyn ClipboardProvider> = Arc::new(MockClipboardProvider::new());
+
+    // ── Input routing ─────────────────────────────────────────────────────────
+    // Without a router the focus requests are dropped, so the UI commands
+    // report that it is not running instead of queueing requests forever.
+    // The layout is rebuilt whenever a client reports its monitors or the
+    // user saves a new layout.
+    let focus_requests = state.focus_request_rx.lock().await.take();
+    let layout_updates = state.layout_update_rx.lock().await.take();
+    let router = start_input_router(&state, Arc::clone(&clipboard), input_channel).await;
+    match (router, focus_requests, layout_updates) {
+        (Some((use_case, events)), Some(focus_requests), Some(layout_updates)) => {
+            tokio::spawn(run_router(use_case, events, focus_requests, layout_updates));
+            info!("input routing started");
+        }
+        _ => warn!("input routing is not running; input stays on this machine"),
+    }
+
+    // ── Connection event pump ─────────────────────────────────────────────────
+    if let Some(mut rx) = state.connection_events.lock().await.take() {
+        let state_clone = Arc::clone(&state);
+        let clipboard = Arc::clone(&clipboard);
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                match event {
+                    ConnectionEvent::ClientConnected { client_id, .. } => {
+                        let mut mgr = state_clone.connection_manager.lock().await;
+                        let name = mgr
+                            .session(client_id)
+                            .map(|s| s.name().to_string())
+                            .unwrap_or_default();
+                        // Headless build: there is no "Pair" button, so every
+                        // unpaired client is asked to pair as it connects.
+                        if !mgr.is_paired(client_id) {
+                            if let Err(e) = mgr.pair_connected_client(client_id) {
+                                warn!("cannot pair with client {client_id}: {e}");
+                            }
+                        }
+                        drop(mgr);
+                        let mut registry = state_clone.client_registry.lock().await;
+                        if registry.get(client_id).is_some() {
+                            registry.set_state(client_id, ConnectionState::Connected);
+                        } else {
+                            registry.upsert(ClientRuntimeState {
+                                id: client_id,
+                                name,
+                                connection_state: ConnectionState::Connected,
+                                latency_ms: 0.0,
+                                events_per_second: 0,
+                            });
+                        }
+                    }
+                    ConnectionEvent::ClientDisconnected { client_id } => {
+                        let mut registry = state_clone.client_registry.lock().await;
+                        registry.set_state(client_id, ConnectionState::Disconnected);
+                    }
+                    ConnectionEvent::LinkStatsUpdated {
+                        client_id,
+                        latency_ms,
+                        events_per_second,
+                    } => {
+                        let mut registry = state_clone.client_registry.lock().await;
+                        if let Some(latency_ms) = latency_ms {
+                            registry.update_latency(client_id, latency_ms);
+                        }
+                        registry.update_events_per_second(client_id, events_per_second);
+                    }
+                    ConnectionEvent::PairingCompleted {
+                        client_id,
+                        fingerprint,
+                    } => {
+                        let name = state_clone
+                            .client_registry
+                            .lock()
+                            .await
+                            .get(client_id)
+                            .map(|c| c.name.clone())
+                            .unwrap_or_default();
+                        let mut cfg = state_clone.config.lock().await;
+                        cfg.record_pairing(client_id, &name, fingerprint.map(|f| f.to_string()));
+                        match save_config(&cfg) {
+                            Ok(()) => info!("paired client {client_id} saved"),
+                            Err(e) => error!("failed to save pairing for {client_id}: {e}"),
+                        }
+                    }
+                    ConnectionEvent::PairingRequested { client_id, pin, .. } => {
+                        // Headless build: the log is the only place to show the PIN.
+                        info!("pairing with client {client_id}: enter PIN {pin} on the client");
+                    }
+                    ConnectionEvent::PairingResponseReceived {
+                        client_id,
+                        address,
+                        response,
+                    } => {
+                        let result = state_clone
+                            .connection_manager
+                            .lock()
+                            .await
+                            .verify_pairing_response(client_id, &response, address.ip());
+                        if let Err(e) = result {
+                            warn!("pairing with client {client_id} failed: {e}");
+                        }
+                    }
+                    ConnectionEvent::ClipboardReceived { client_id, content } => {
+                        if let Err(e) = clipboard.write(&content) {
+                            warn!("clipboard from client {client_id} not stored: {e}");
+                        }
+                    }
+                    ConnectionEvent::ScreenInfoUpdated {
+                        client_id,
+                        monitor_count,
+                    } => {
+                        info!("client {client_id} reported {monitor_count} monitor(s)");
+                        state_clone.refresh_router_layout().await;
+                    }
+                    other => info!("connection event: {other:?}"),
+                }
+            }
+        });
+    }
+
     // ── Ctrl-C / SIGTERM handler ──────────────────────────────────────────────
     let running_clone = Arc::clone(&running);
     tokio::spawn(async move {
@@ -126,3 +333,91 @@ async fn main() -> anyhow::Result<()> {
     info!("KVM-Over-IP Master stopped");
     Ok(())
 }
+
+/// Starts capturing input and builds the router for it from the saved
+/// configuration and the monitors connected clients have reported.  Mouse
+/// movements travel over `input_channel` when one is given.
+///
+/// Returns `None`, after logging why, when there is no input source for this
+/// platform or it cannot start.
+async fn start_input_router(
+    state: &AppState,
+    clipboard: Arc<dyn ClipboardProvider>,
+    input_channel: Option<InputChannel>,
+) -> Option<(
+    RouteInputUseCase,
+    tokio::sync::mpsc::Receiver<RawInputEvent>,
+)> {
+    let cfg = state.config.lock().await.clone();
+    let (width, height) = (
+        cfg.layout.master_screen_width,
+        cfg.layout.master_screen_height,
+    );
+    let (source, cursor) = platform_input(width, height)?;
+
+    let hotkeys = match cfg.master.hotkey_bindings() {
+        Ok(hotkeys) => hotkeys,
+        Err(e) => {
+            error!("hotkeys not usable: {e}");
+            return None;
+        }
+    };
+    let layout = match state.router_layout().await {
+        Ok(layout) => layout,
+        Err(e) => {
+            error!("screen layout not usable: {e}");
+            return None;
+        }
+    };
+
+    let sessions = state.connection_manager.lock().await.session_registry();
+    let mut transmitter = NetworkInputTransmitter::new(sessions);
+    if let Some(channel) = input_channel {
+        transmitter = transmitter.with_input_channel(channel);
+    }
+    let transmitter = Arc::new(transmitter);
+    let mut use_case = RouteInputUseCase::new(width, height, transmitter, cursor, hotkeys)
+        .with_clipboard(clipboard)
+        .with_edge_crossing(cfg.master.edge_crossing.clone())
+        .with_input_source(Arc::clone(&source));
+    use_case.update_layout(layout);
+
+    match source.start() {
+        Ok(captured) => Some((use_case, forward_captured(captured))),
+        Err(e) => {
+            error!("failed to start input capture: {e}");
+            None
+        }
+    }
+}
+
+/// The platform's input source, which also moves the master's cursor.
+#[cfg(target_os = "linux")]
+fn platform_input(
+    master_width: u32,
+    master_height: u32,
+) -> Option<(Arc<dyn InputSource + Sync>, Arc<dyn CursorController>)> {
+    use kvm_master::infrastructure::input_capture::linux::LinuxEvdevInputSource;
+
+    let source = Arc::new(LinuxEvdevInputSource::new(master_width, master_height));
+    Some((Arc::clone(&source) as Arc<dyn InputSource + Sync>, source))
+}
+
+/// The platform's input source, which also moves the master's cursor.
+///
+/// The Windows hooks have no cursor controller yet, so nothing is captured.
+#[cfg(not(target_os = "linux"))]
+fn platform_input(
+    _master_width: u32,
+    _master_height: u32,
+) -> Option<(Arc<dyn InputSource + Sync>, Arc<dyn CursorController>)> {
+    warn!("input capture is not supported on this platform yet");
+    None
+}
+
+/// This machine's name, used as the mDNS instance name.
+fn host_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "kvm-master".to_string())
+}
@@ -0,0 +1,204 @@
+//! End-to-end tests for clipboard sharing over the control channel.
+//!
+//! # Purpose
+//!
+//! These tests run the real `ConnectionManager`, the real
+//! `NetworkInputTransmitter`, and the real `kvm_client` `ClientConnection`
+//! with its `SyncClipboardUseCase` over loopback.  They verify:
+//!
+//! - A PNG screenshot larger than one fragment sent by the master arrives in
+//!   the client's clipboard intact and decodes to the same image.
+//! - A copy made on the paired client reaches the master as one
+//!   `ClipboardReceived` event.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use kvm_client::application::sync_clipboard::SyncClipboardUseCase;
+use kvm_client::infrastructure::network::{ClientConnection, ClientConnectionConfig, NetworkEvent};
+use kvm_core::clipboard::mock::MockClipboardProvider;
+use kvm_core::clipboard::{ClipboardContent, ClipboardProvider, RgbaImage, FRAGMENT_LEN};
+use kvm_core::crypto::pairing::{channel_binding, Role, Spake2};
+use kvm_core::protocol::messages::{KvmMessage, PairingResponseMessage};
+use kvm_master::application::route_input::InputTransmitter;
+use kvm_master::infrastructure::network::connection_manager::{
+    ConnectionEvent, ConnectionManager, NetworkConfig,
+};
+use kvm_master::infrastructure::network::input_transmitter::NetworkInputTransmitter;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Upper bound for any single wait in these tests.
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Harness {
+    mgr: ConnectionManager,
+    master_rx: mpsc::Receiver<ConnectionEvent>,
+    client: Arc<ClientConnection>,
+    client_rx: mpsc::Receiver<NetworkEvent>,
+    client_running: Arc<AtomicBool>,
+    client_id: Uuid,
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        self.client_running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Pairs `client_id` with `mgr` by running the client's half of the PIN
+/// exchange locally; the master only accepts clipboard data from paired
+/// clients.
+fn pair(mgr: &mut ConnectionManager, client_id: Uuid) {
+    let addr = "127.0.0.1".parse().unwrap();
+    let challenge = mgr.initiate_pairing(client_id, addr).expect("initiate");
+    let client = Spake2::start(
+        Role::Client,
+        &challenge.pin,
+        challenge.session_id,
+        &channel_binding(None, None),
+    );
+    let spake_message = client.message();
+    let keys = client.finish(&challenge.request.spake_message).unwrap();
+    let response = PairingResponseMessage {
+        pairing_session_id: challenge.session_id,
+        spake_message,
+        confirmation: keys.confirmation(Role::Client),
+        accepted: true,
+    };
+    mgr.verify_pairing_response(client_id, &response, addr)
+        .expect("pairing must succeed");
+}
+
+/// Starts a master and connects one paired client.  Returns once the client
+/// has its `HelloAck` and the master has emitted `ClientConnected`.
+async fn connect_client() -> Harness {
+    let (mut mgr, mut master_rx) = ConnectionManager::new(NetworkConfig {
+        control_port: 0,
+        bind_address: "127.0.0.1".parse().unwrap(),
+        ..NetworkConfig::default()
+    });
+    let addr = mgr
+        .start_control_listener(Arc::new(AtomicBool::new(true)))
+        .await
+        .expect("listener must bind");
+
+    let client_id = Uuid::new_v4();
+    pair(&mut mgr, client_id);
+    // Skip the pairing events; the next one must be the connection.
+    while master_rx.try_recv().is_ok() {}
+    let client = Arc::new(ClientConnection::new(ClientConnectionConfig {
+        master_addr: addr,
+        client_id,
+        client_name: "clipboard-client".to_string(),
+        reconnect_interval: Duration::from_secs(60),
+        max_reconnect_interval: Duration::from_secs(60),
+        tls: None,
+        input_port: None,
+    }));
+    let client_running = Arc::new(AtomicBool::new(true));
+    let mut client_rx = Arc::clone(&client).start(Arc::clone(&client_running)).await;
+
+    tokio::time::timeout(STEP_TIMEOUT, async {
+        while !matches!(
+            client_rx.recv().await,
+            Some(NetworkEvent::MessageReceived(KvmMessage::HelloAck(_)))
+        ) {}
+    })
+    .await
+    .expect("timed out waiting for HelloAck");
+    let event = tokio::time::timeout(STEP_TIMEOUT, master_rx.recv())
+        .await
+        .expect("timed out waiting for ClientConnected");
+    assert!(matches!(
+        event,
+        Some(ConnectionEvent::ClientConnected { client_id: id, .. }) if id == client_id
+    ));
+
+    Harness {
+        mgr,
+        master_rx,
+        client,
+        client_rx,
+        client_running,
+        client_id,
+    }
+}
+
+#[tokio::test]
+async fn test_large_master_clipboard_arrives_intact_on_client() {
+    // Arrange
+    let mut h = connect_client().await;
+    let transmitter = NetworkInputTransmitter::new(h.mgr.session_registry());
+    let client_clipboard = Arc::new(MockClipboardProvider::new());
+    let mut sync =
+        SyncClipboardUseCase::new(Arc::clone(&client_clipboard) as Arc<dyn ClipboardProvider>);
+    // Noise does not compress, so the PNG spans several fragments.
+    let mut seed = 1u32;
+    let pixels = (0..256 * 256 * 4)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as u8
+        })
+        .collect();
+    let image = RgbaImage::new(256, 256, pixels).unwrap();
+    let screenshot = ClipboardContent::image(&image);
+    assert!(screenshot.data.len() > 3 * FRAGMENT_LEN);
+
+    // Act
+    transmitter
+        .send_clipboard(h.client_id, screenshot.clone())
+        .await
+        .expect("send must succeed");
+    tokio::time::timeout(STEP_TIMEOUT, async {
+        while client_clipboard.writes.lock().unwrap().is_empty() {
+            match h.client_rx.recv().await {
+                Some(NetworkEvent::MessageReceived(KvmMessage::ClipboardData(fragment))) => {
+                    sync.handle_fragment(fragment).expect("fragment accepted");
+                }
+                Some(_) => {}
+                None => panic!("client event channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for the clipboard");
+
+    // Assert – the client pastes the same image
+    let writes = client_clipboard.writes.lock().unwrap();
+    assert_eq!(*writes, vec![screenshot]);
+    assert_eq!(RgbaImage::from_png(&writes[0].data).unwrap(), image);
+}
+
+#[tokio::test]
+async fn test_client_copy_reaches_master_as_one_event() {
+    // Arrange
+    let mut h = connect_client().await;
+    let client_clipboard = Arc::new(MockClipboardProvider::with_content(ClipboardContent::text(
+        "copied on the client",
+    )));
+    let mut sync =
+        SyncClipboardUseCase::new(Arc::clone(&client_clipboard) as Arc<dyn ClipboardProvider>);
+
+    // Act
+    let terms = h.client.negotiated().expect("handshake completed");
+    for fragment in sync.poll_local_change(terms.capabilities()).unwrap() {
+        h.client
+            .send_message(&KvmMessage::ClipboardData(fragment))
+            .await
+            .expect("clipboard is negotiated");
+    }
+
+    // Assert
+    let event = tokio::time::timeout(STEP_TIMEOUT, h.master_rx.recv())
+        .await
+        .expect("timed out waiting for ClipboardReceived");
+    match event {
+        Some(ConnectionEvent::ClipboardReceived { client_id, content }) => {
+            assert_eq!(client_id, h.client_id);
+            assert_eq!(content, ClipboardContent::text("copied on the client"));
+        }
+        other => panic!("expected ClipboardReceived, got {other:?}"),
+    }
+}
@@ -5,8 +5,8 @@
 //! These tests exercise the `ConnectionManager` through its *public* API in
 //! the same way that the application layer uses it.  They verify:
 //!
-//! - The happy path: initiating a pairing session and submitting the correct
-//!   PIN hash results in a successfully paired client.
+//! - The happy path: initiating a pairing session and answering it with the
+//!   correct PIN results in a successfully paired client.
 //! - The error paths: wrong PIN decrements the attempt counter, and three
 //!   failures trigger a per-IP lockout.
 //! - Edge cases: verifying against an unknown session ID, querying paired
@@ -16,45 +16,63 @@
 //! # What is the pairing flow?
 //!
 //! When a new client appears on the network, the master UI shows a 6-digit PIN
-//! to the user.  The user types the PIN on the client machine.  The client
-//! hashes the PIN together with the session UUID and sends the hash to the
-//! master.  The master checks the hash against its own computation.
+//! to the user.  The user types the PIN on the client machine.  Both sides
+//! then run a SPAKE2 key exchange keyed by the PIN; the client's key
+//! confirmation only verifies on the master if both used the same PIN.
 //!
 //! ```text
 //! Master                              Client
 //! ──────                              ──────
 //! initiate_pairing(client_id, addr)
-//!   → (session_id, pin)
+//!   → PairingChallenge { session_id, pin, request }
 //! Show PIN to user                    User types PIN
-//!                                     Compute hash(PIN + session_id)
-//!                                     Send verify_pairing_pin(session_id, hash)
-//! verify_pairing_pin(session_id, hash)
-//!   → Ok(client_id) if hash matches
-//!   → Err(WrongPin) if hash wrong
+//!                                     Spake2::start(Client, pin, …)
+//!                                     finish(request.spake_message)
+//!                                     Send PairingResponse
+//! verify_pairing_response(client_id, response)
+//!   → Ok(client_id) if the confirmation verifies
+//!   → Err(WrongPin) if it does not
 //!   → Err(LockedOut) after 3 failures
 //! ```
 //!
-//! # PIN hash computation
-//!
-//! Tests that need to supply the correct PIN hash mirror the private
-//! `hash_pin` function using `DefaultHasher`:
-//!
-//! ```rust,ignore
-//! let mut hasher = DefaultHasher::new();
-//! pin.hash(&mut hasher);
-//! session_id.as_bytes().hash(&mut hasher);
-//! let pin_hash = format!("{:016x}", hasher.finish());
-//! ```
-//!
-//! This is intentionally simple (not cryptographically secure) for a
-//! local-network application; a production deployment would use HMAC-SHA256.
+//! These tests play the client's part with [`answer`], which uses the same
+//! `kvm_core::crypto::pairing` primitives as the real client.  There is no
+//! TLS here, so the channel binding is empty on both sides.
 
+use kvm_core::crypto::pairing::{channel_binding, Role, Spake2};
+use kvm_core::protocol::messages::PairingResponseMessage;
+use kvm_master::infrastructure::network::connection_manager::PairingChallenge;
 use uuid::Uuid;
 
+/// Builds the client's `PairingResponse` to `challenge` using `pin`.
+fn answer(challenge: &PairingChallenge, pin: &str) -> PairingResponseMessage {
+    let client = Spake2::start(
+        Role::Client,
+        pin,
+        challenge.session_id,
+        &channel_binding(None, None),
+    );
+    let spake_message = client.message();
+    let keys = client
+        .finish(&challenge.request.spake_message)
+        .expect("master share must be valid");
+    PairingResponseMessage {
+        pairing_session_id: challenge.session_id,
+        spake_message,
+        confirmation: keys.confirmation(Role::Client),
+        accepted: true,
+    }
+}
+
+/// A PIN that is certainly not `pin`.
+fn other_pin(pin: &str) -> String {
+    if pin == "111111" { "222222" } else { "111111" }.to_string()
+}
+
 // ── Pairing lifecycle tests ───────────────────────────────────────────────────
 
-/// Tests the complete happy-path pairing flow: initiate, then verify with
-/// the correct PIN hash.
+/// Tests the complete happy-path pairing flow: initiate, then verify an
+/// answer built from the correct PIN.
 ///
 /// After successful verification, `is_paired(client_id)` must return `true`.
 #[test]
@@ -62,8 +80,6 @@ fn test_pairing_lifecycle_initiate_then_verify_succeeds() {
     use kvm_master::infrastructure::network::connection_manager::{
         ConnectionManager, NetworkConfig,
     };
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
 
     // Arrange: create a fresh ConnectionManager.
     // `_rx` is the event-receiver channel; we discard it because these tests
@@ -75,22 +91,18 @@ fn test_pairing_lifecycle_initiate_then_verify_succeeds() {
     let addr: std::net::IpAddr = "10.0.0.1".parse().unwrap();
 
     // Step 1: Initiate pairing.
-    // Returns (session_id, pin) where `pin` is the 6-digit code to show the user.
-    let (session_id, pin) = mgr.initiate_pairing(client_id, addr).expect("initiate");
-
-    // Step 2: Compute the PIN hash the same way the client would.
-    // The hash combines the PIN string with the session UUID bytes so that
-    // an attacker who observes the session ID cannot compute the hash without
-    // knowing the PIN.
-    let mut hasher = DefaultHasher::new();
-    pin.hash(&mut hasher);
-    session_id.as_bytes().hash(&mut hasher);
-    let pin_hash = format!("{:016x}", hasher.finish());
-
-    // Step 3: Verify the PIN hash.
+    // `challenge.pin` is the 6-digit code to show the user; `challenge.request`
+    // is what the client receives.
+    let challenge = mgr.initiate_pairing(client_id, addr).expect("initiate");
+
+    // Step 2: Answer the request the way the client would once the user has
+    // typed the PIN.
+    let response = answer(&challenge, &challenge.pin);
+
+    // Step 3: Verify the answer.
     let paired_id = mgr
-        .verify_pairing_pin(session_id, &pin_hash, addr)
-        .expect("verify must succeed with correct hash");
+        .verify_pairing_response(client_id, &response, addr)
+        .expect("verify must succeed with correct PIN");
 
     // Assert: the returned ID matches the original client, and the manager
     // now recognises the client as paired.
@@ -98,7 +110,7 @@ fn test_pairing_lifecycle_initiate_then_verify_succeeds() {
     assert!(mgr.is_paired(client_id), "client must be marked as paired");
 }
 
-/// Tests that submitting a wrong PIN hash decrements the attempts-remaining
+/// Tests that answering with a wrong PIN decrements the attempts-remaining
 /// counter and returns `WrongPin { attempts_remaining: 2 }` on the first
 /// failure.
 ///
@@ -114,10 +126,11 @@ fn test_pairing_wrong_pin_increments_failure_counter() {
     let client_id = Uuid::new_v4();
     let addr: std::net::IpAddr = "10.0.0.2".parse().unwrap();
 
-    let (session_id, _pin) = mgr.initiate_pairing(client_id, addr).expect("initiate");
+    let challenge = mgr.initiate_pairing(client_id, addr).expect("initiate");
+    let response = answer(&challenge, &other_pin(&challenge.pin));
 
     // First wrong attempt: 2 attempts remaining (3 total − 1 used = 2)
-    let result = mgr.verify_pairing_pin(session_id, "bad_hash_1", addr);
+    let result = mgr.verify_pairing_response(client_id, &response, addr);
     assert!(
         matches!(
             result,
@@ -130,7 +143,7 @@ fn test_pairing_wrong_pin_increments_failure_counter() {
     );
 }
 
-/// Tests that three consecutive wrong PIN submissions trigger a per-IP
+/// Tests that three consecutive wrong PIN answers trigger a per-IP
 /// lockout.
 ///
 /// After lockout, any further `initiate_pairing` call from the same IP
@@ -146,13 +159,17 @@ fn test_pairing_three_failures_trigger_lockout() {
     let client_id = Uuid::new_v4();
     let addr: std::net::IpAddr = "10.0.0.3".parse().unwrap();
 
-    let (session_id, _pin) = mgr.initiate_pairing(client_id, addr).expect("initiate");
-
-    // Exhaust all 3 attempts with wrong hashes.
-    // The loop variable `i` is used only to produce distinct bad hashes;
-    // any wrong hash triggers a failure count increment.
-    for i in 0..3u8 {
-        let _ = mgr.verify_pairing_pin(session_id, &format!("bad_{i}"), addr);
+    let challenge = mgr.initiate_pairing(client_id, addr).expect("initiate");
+
+    // Exhaust all 3 attempts with wrong PINs.  Each guess is a fresh key
+    // exchange, exactly as an attacker would have to run it.
+    for guess in ["000000", "111111", "222222"] {
+        let guess = if guess == challenge.pin {
+            "333333"
+        } else {
+            guess
+        };
+        let _ = mgr.verify_pairing_response(client_id, &answer(&challenge, guess), addr);
     }
 
     // After 3 failures the IP is locked out.
@@ -180,7 +197,13 @@ fn test_pairing_session_not_found_for_unknown_session_id() {
     let addr: std::net::IpAddr = "10.0.0.4".parse().unwrap();
 
     // `Uuid::new_v4()` generates a random UUID that has never been registered.
-    let result = mgr.verify_pairing_pin(Uuid::new_v4(), "irrelevant_hash", addr);
+    let response = PairingResponseMessage {
+        pairing_session_id: Uuid::new_v4(),
+        spake_message: [0; 32],
+        confirmation: [0; 32],
+        accepted: true,
+    };
+    let result = mgr.verify_pairing_response(Uuid::new_v4(), &response, addr);
     assert_eq!(
         result,
         Err(PairingError::SessionNotFound),
@@ -191,7 +214,7 @@ fn test_pairing_session_not_found_for_unknown_session_id() {
 /// Tests that multiple clients can pair independently and concurrently.
 ///
 /// Each client initiates its own pairing session with a unique ID and IP
-/// address.  Verifying each one with the correct hash should succeed without
+/// address.  Verifying each one with the correct PIN should succeed without
 /// interfering with the other sessions.
 ///
 /// This validates that session state is stored per-session (not globally) and
@@ -202,8 +225,6 @@ fn test_multiple_clients_can_pair_independently() {
     use kvm_master::infrastructure::network::connection_manager::{
         ConnectionManager, NetworkConfig,
     };
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
 
     let (mut mgr, _rx) = ConnectionManager::new(NetworkConfig::default());
 
@@ -216,17 +237,12 @@ fn test_multiple_clients_can_pair_independently() {
 
     // Pair each client one at a time.
     for (client_id, addr) in &pairs {
-        let (session_id, pin) = mgr
+        let challenge = mgr
             .initiate_pairing(*client_id, *addr)
             .expect("initiate must succeed");
 
-        // Compute the correct hash for this specific session.
-        let mut hasher = DefaultHasher::new();
-        pin.hash(&mut hasher);
-        session_id.as_bytes().hash(&mut hasher);
-        let pin_hash = format!("{:016x}", hasher.finish());
-
-        let result = mgr.verify_pairing_pin(session_id, &pin_hash, *addr);
+        let response = answer(&challenge, &challenge.pin);
+        let result = mgr.verify_pairing_response(*client_id, &response, *addr);
         assert_eq!(result, Ok(*client_id));
     }
 
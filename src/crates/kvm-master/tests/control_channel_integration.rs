@@ -0,0 +1,349 @@
+//! End-to-end tests for the master's TCP control channel.
+//!
+//! # Purpose
+//!
+//! These tests run the real `ConnectionManager` listener and the real
+//! `kvm_client` `ClientConnection` against each other over loopback.  They
+//! verify:
+//!
+//! - A client's `Hello` is answered with an accepted `HelloAck` carrying a
+//!   non-zero session token, and the master emits `ClientConnected`.
+//! - Both sides agree on the protocol version and on the capabilities they
+//!   share.
+//! - Messages sent by the client after the handshake (`Ping`, `ScreenInfo`)
+//!   are served by the master.
+//! - When the master disconnects a client, the client sees `Disconnect` followed
+//!   by the socket closing, and the master emits `ClientDisconnected`.
+//! - A client dropped for a heartbeat timeout reconnects by itself and resumes
+//!   its session.
+//!
+//! # Why port 0?
+//!
+//! Binding to port 0 asks the OS for any free port.  `start_control_listener`
+//! returns the address it actually bound, so tests can run in parallel without
+//! fighting over 24800.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use kvm_client::infrastructure::network::{
+    ClientConnection, ClientConnectionConfig, NetworkEvent, CLIENT_CAPABILITIES,
+};
+use kvm_core::protocol::messages::{
+    capabilities, ClipboardDataMessage, ClipboardFormat, DisconnectReason, KvmMessage, MonitorInfo,
+    ScreenInfoMessage, PROTOCOL_VERSION,
+};
+use kvm_master::infrastructure::network::connection_manager::{
+    ConnectionEvent, ConnectionManager, NetworkConfig, MASTER_CAPABILITIES,
+};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Upper bound for any single wait in these tests.
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Everything a test needs: the manager, both event streams, and the client.
+struct Harness {
+    mgr: ConnectionManager,
+    master_rx: mpsc::Receiver<ConnectionEvent>,
+    client: Arc<ClientConnection>,
+    client_rx: mpsc::Receiver<NetworkEvent>,
+    client_running: Arc<AtomicBool>,
+    client_id: Uuid,
+    /// Session token the client received in its `HelloAck`.
+    session_token: [u8; 32],
+}
+
+/// Starts a loopback listener and connects a `ClientConnection` to it.
+///
+/// Returns once the client has received its `HelloAck` and the master has
+/// emitted `ClientConnected`.
+async fn connect_client(name: &str) -> Harness {
+    connect_client_with_reconnect(name, Duration::from_secs(60)).await
+}
+
+/// As [`connect_client`], with the client retrying after `reconnect_interval`
+/// once its connection drops.
+async fn connect_client_with_reconnect(name: &str, reconnect_interval: Duration) -> Harness {
+    let config = NetworkConfig {
+        control_port: 0,
+        bind_address: "127.0.0.1".parse().unwrap(),
+        ..NetworkConfig::default()
+    };
+    let (mgr, mut master_rx) = ConnectionManager::new(config);
+    let addr = mgr
+        .start_control_listener(Arc::new(AtomicBool::new(true)))
+        .await
+        .expect("listener must bind");
+
+    let client_id = Uuid::new_v4();
+    let client = Arc::new(ClientConnection::new(ClientConnectionConfig {
+        master_addr: addr,
+        client_id,
+        client_name: name.to_string(),
+        reconnect_interval,
+        max_reconnect_interval: reconnect_interval,
+        tls: None,
+        input_port: None,
+    }));
+    let client_running = Arc::new(AtomicBool::new(true));
+    let mut client_rx = Arc::clone(&client).start(Arc::clone(&client_running)).await;
+
+    // Client side: Connected, then the HelloAck.
+    assert!(matches!(
+        next_client_event(&mut client_rx).await,
+        NetworkEvent::Connected { .. }
+    ));
+    let session_token = match next_client_event(&mut client_rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::HelloAck(ack)) => {
+            assert!(ack.accepted, "master must accept a valid Hello");
+            assert_eq!(ack.reject_reason, 0);
+            ack.session_token
+        }
+        other => panic!("expected HelloAck, got {other:?}"),
+    };
+
+    // Master side: ClientConnected for the same ID.
+    match next_master_event(&mut master_rx).await {
+        ConnectionEvent::ClientConnected { client_id: id, .. } => assert_eq!(id, client_id),
+        other => panic!("expected ClientConnected, got {other:?}"),
+    }
+
+    Harness {
+        mgr,
+        master_rx,
+        client,
+        client_rx,
+        client_running,
+        client_id,
+        session_token,
+    }
+}
+
+/// Next client event, skipping connection state changes.
+async fn next_client_event(rx: &mut mpsc::Receiver<NetworkEvent>) -> NetworkEvent {
+    loop {
+        let event = tokio::time::timeout(STEP_TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for client event")
+            .expect("client event channel closed");
+        if !matches!(event, NetworkEvent::StateChanged(_)) {
+            return event;
+        }
+    }
+}
+
+async fn next_master_event(rx: &mut mpsc::Receiver<ConnectionEvent>) -> ConnectionEvent {
+    tokio::time::timeout(STEP_TIMEOUT, rx.recv())
+        .await
+        .expect("timed out waiting for master event")
+        .expect("master event channel closed")
+}
+
+/// Tests the full handshake: the client is registered under its own ID and
+/// name, and the token it received matches the one the master stored.
+#[tokio::test]
+async fn test_client_hello_is_accepted_and_session_registered() {
+    // Arrange / Act
+    let mut h = connect_client("integration-client").await;
+
+    // Assert: the master knows about the session.
+    let session = h
+        .mgr
+        .session(h.client_id)
+        .expect("session must be registered after HelloAck");
+    assert_eq!(session.name(), "integration-client");
+    assert_eq!(session.session_token(), &h.session_token);
+    assert_ne!(
+        h.session_token, [0u8; 32],
+        "session token must be random, not all zeros"
+    );
+
+    let connected = h.mgr.get_connected_clients();
+    assert_eq!(connected.len(), 1);
+    assert_eq!(connected[0].client_id, h.client_id);
+    assert!(!connected[0].is_paired, "a fresh client is not paired yet");
+
+    // No further events are expected at this point.
+    assert!(h.master_rx.try_recv().is_err());
+    h.client_running.store(false, Ordering::Relaxed);
+}
+
+/// Tests that both ends record the same negotiated terms, and that messages
+/// needing a shared capability go through in both directions.
+#[tokio::test]
+async fn test_both_sides_agree_on_version_and_shared_capabilities() {
+    // Arrange
+    let h = connect_client("negotiating-client").await;
+    let session = h.mgr.session(h.client_id).unwrap();
+    let clipboard = KvmMessage::ClipboardData(ClipboardDataMessage {
+        format: ClipboardFormat::Utf8Text,
+        data: b"copied".to_vec(),
+        has_more_fragments: false,
+    });
+
+    // Act
+    let client_terms = h.client.negotiated().expect("set by the HelloAck");
+    let master_send = session.send(&clipboard).await;
+    let client_send = h.client.send_message(&clipboard).await;
+
+    // Assert
+    assert_eq!(client_terms, session.negotiated());
+    assert_eq!(client_terms.version(), PROTOCOL_VERSION);
+    assert_eq!(
+        client_terms.capabilities(),
+        CLIENT_CAPABILITIES & MASTER_CAPABILITIES
+    );
+    assert!(client_terms.supports(capabilities::CLIPBOARD_SHARING));
+    assert!(master_send.is_ok());
+    assert!(client_send.is_ok());
+    h.client_running.store(false, Ordering::Relaxed);
+}
+
+/// Tests that a `Ping` from the client is answered with a `Pong` carrying the
+/// same token.
+#[tokio::test]
+async fn test_client_ping_is_answered_with_pong() {
+    // Arrange
+    let mut h = connect_client("ping-client").await;
+
+    // Act
+    h.client.send_ping().await.expect("ping must be sent");
+
+    // Assert
+    match next_client_event(&mut h.client_rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::Pong(_)) => {}
+        other => panic!("expected Pong, got {other:?}"),
+    }
+    h.client_running.store(false, Ordering::Relaxed);
+}
+
+/// Tests that a `ScreenInfo` report is acknowledged to the client and
+/// surfaced to the application layer as `ScreenInfoUpdated`.
+#[tokio::test]
+async fn test_client_screen_info_is_acknowledged_and_reported() {
+    // Arrange
+    let mut h = connect_client("screens-client").await;
+    let monitor = |id: u8, x: i32| MonitorInfo {
+        monitor_id: id,
+        x_offset: x,
+        y_offset: 0,
+        width: 1920,
+        height: 1080,
+        scale_factor: 100,
+        is_primary: id == 0,
+    };
+
+    // Act
+    h.client
+        .send_screen_info(ScreenInfoMessage {
+            monitors: vec![monitor(0, 0), monitor(1, 1920)],
+        })
+        .await
+        .expect("screen info must be sent");
+
+    // Assert
+    match next_client_event(&mut h.client_rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::ScreenInfoAck) => {}
+        other => panic!("expected ScreenInfoAck, got {other:?}"),
+    }
+    match next_master_event(&mut h.master_rx).await {
+        ConnectionEvent::ScreenInfoUpdated {
+            client_id,
+            monitor_count,
+        } => {
+            assert_eq!(client_id, h.client_id);
+            assert_eq!(monitor_count, 2);
+        }
+        other => panic!("expected ScreenInfoUpdated, got {other:?}"),
+    }
+    h.client_running.store(false, Ordering::Relaxed);
+}
+
+/// Tests that `disconnect_client` sends `Disconnect`, closes the socket, and
+/// results in `ClientDisconnected` on the master once the client hangs up.
+#[tokio::test]
+async fn test_master_disconnect_closes_connection_on_both_sides() {
+    // Arrange
+    let mut h = connect_client("disconnect-client").await;
+    // Stop the client from reconnecting after the socket closes.
+    h.client_running.store(false, Ordering::Relaxed);
+
+    // Act
+    h.mgr
+        .disconnect_client(h.client_id, DisconnectReason::UserInitiated)
+        .await
+        .expect("client is connected");
+
+    // Assert: client sees the Disconnect message, then the socket closing.
+    match next_client_event(&mut h.client_rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::Disconnect { reason }) => {
+            assert_eq!(reason, DisconnectReason::UserInitiated);
+        }
+        other => panic!("expected Disconnect, got {other:?}"),
+    }
+    assert!(matches!(
+        next_client_event(&mut h.client_rx).await,
+        NetworkEvent::Disconnected
+    ));
+
+    // Master side: ClientDisconnected, and the session is gone.
+    match next_master_event(&mut h.master_rx).await {
+        ConnectionEvent::ClientDisconnected { client_id } => assert_eq!(client_id, h.client_id),
+        other => panic!("expected ClientDisconnected, got {other:?}"),
+    }
+    assert!(h.mgr.session(h.client_id).is_none());
+    assert!(h.mgr.get_connected_clients().is_empty());
+}
+
+/// Tests that a heartbeat timeout is not the end: the client reconnects with
+/// its session token and the master resumes the session.
+#[tokio::test]
+async fn test_timed_out_client_reconnects_and_resumes_its_session() {
+    // Arrange
+    let mut h = connect_client_with_reconnect("timeout-client", Duration::from_millis(20)).await;
+
+    // Act
+    h.mgr
+        .disconnect_client(h.client_id, DisconnectReason::Timeout)
+        .await
+        .expect("client is connected");
+
+    // Assert: the client sees the timeout, loses the socket, and comes back.
+    match next_client_event(&mut h.client_rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::Disconnect { reason }) => {
+            assert_eq!(reason, DisconnectReason::Timeout);
+        }
+        other => panic!("expected Disconnect, got {other:?}"),
+    }
+    assert!(matches!(
+        next_client_event(&mut h.client_rx).await,
+        NetworkEvent::Disconnected
+    ));
+    assert!(matches!(
+        next_client_event(&mut h.client_rx).await,
+        NetworkEvent::Connected { .. }
+    ));
+    match next_client_event(&mut h.client_rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::HelloAck(ack)) => {
+            assert!(ack.accepted && ack.resumed);
+            assert_ne!(ack.session_token, h.session_token);
+        }
+        other => panic!("expected HelloAck, got {other:?}"),
+    }
+
+    // Master side: the old session ends and the same client resumes.
+    loop {
+        match next_master_event(&mut h.master_rx).await {
+            ConnectionEvent::ClientConnected { client_id, resumed } => {
+                assert_eq!(client_id, h.client_id);
+                assert!(resumed);
+                break;
+            }
+            ConnectionEvent::ClientDisconnected { .. } => continue,
+            other => panic!("expected ClientConnected, got {other:?}"),
+        }
+    }
+    h.client_running.store(false, Ordering::Relaxed);
+}
@@ -0,0 +1,137 @@
+//! End-to-end tests for LAN discovery.
+//!
+//! # Purpose
+//!
+//! These tests run the real master discovery responder and the real
+//! `kvm_client` discovery over loopback (a unicast target stands in for the
+//! broadcast address).  They verify:
+//!
+//! - The client learns the master's control address from the response: the
+//!   IP from the datagram's source, the port from `master_control_port`.
+//! - `already_paired` reflects the `ConnectionManager`'s pairings, so a paired
+//!   client picks the master without asking.
+//! - The master reports the announcing client as a `DiscoveryEvent`.
+//! - With the mDNS backend, a browsing client finds an advertising master over
+//!   loopback multicast and reads the control port from its TXT record.
+
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use kvm_client::infrastructure::network::discovery::{
+    choose_master, discover_masters, DiscoveredMaster, DiscoveryConfig,
+};
+use kvm_core::crypto::tls::TlsIdentity;
+use kvm_core::protocol::discovery::DiscoveryBackend;
+use kvm_master::infrastructure::network::connection_manager::{ConnectionManager, NetworkConfig};
+use kvm_master::infrastructure::network::discovery::{start_discovery_responder, AnnounceReply};
+use kvm_master::infrastructure::network::mdns::{AdvertiseConfig, MdnsAdvertiser};
+use uuid::Uuid;
+
+/// Control port the test master advertises.
+const CONTROL_PORT: u16 = 24_850;
+
+/// Returns a UDP port that was free a moment ago.
+fn free_udp_port() -> u16 {
+    let probe = UdpSocket::bind("127.0.0.1:0").expect("probe bind");
+    probe.local_addr().unwrap().port()
+}
+
+fn discovery_config(client_id: Uuid, port: u16) -> DiscoveryConfig {
+    DiscoveryConfig {
+        client_id,
+        client_name: "discovery-test".to_string(),
+        targets: vec![SocketAddr::from(([127, 0, 0, 1], port))],
+        timeout: Duration::from_millis(500),
+        resend_interval: Duration::from_millis(100),
+        ..DiscoveryConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_client_finds_master_and_master_sees_client() {
+    // Arrange
+    let port = free_udp_port();
+    let running = Arc::new(AtomicBool::new(true));
+    let mut events =
+        start_discovery_responder(port, AnnounceReply::unpaired(CONTROL_PORT), running).unwrap();
+    let client_id = Uuid::new_v4();
+
+    // Act
+    let found = discover_masters(&discovery_config(client_id, port))
+        .await
+        .unwrap();
+
+    // Assert
+    let expected = DiscoveredMaster {
+        control_addr: SocketAddr::from(([127, 0, 0, 1], CONTROL_PORT)),
+        already_paired: false,
+    };
+    assert_eq!(found, vec![expected.clone()]);
+    assert_eq!(choose_master(&found), Some(&expected));
+    let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timed out waiting for DiscoveryEvent")
+        .expect("discovery channel closed");
+    assert_eq!(event.client_id, client_id);
+    assert_eq!(event.name, "discovery-test");
+}
+
+#[tokio::test]
+async fn test_paired_client_is_told_it_is_already_paired() {
+    // Arrange – the manager restored a pairing for one client only
+    let (mut mgr, _rx) = ConnectionManager::new(NetworkConfig::default());
+    let paired = Uuid::new_v4();
+    let identity = TlsIdentity::generate("paired-client").unwrap();
+    mgr.restore_pairing(paired, identity.fingerprint());
+    let reply = AnnounceReply {
+        control_port: CONTROL_PORT,
+        is_paired: Arc::new(mgr.pairing_check()),
+    };
+    let port = free_udp_port();
+    let _events = start_discovery_responder(port, reply, Arc::new(AtomicBool::new(true))).unwrap();
+
+    // Act
+    let as_paired = discover_masters(&discovery_config(paired, port))
+        .await
+        .unwrap();
+    let as_stranger = discover_masters(&discovery_config(Uuid::new_v4(), port))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(as_paired.len(), 1);
+    assert!(as_paired[0].already_paired);
+    assert_eq!(as_stranger.len(), 1);
+    assert!(!as_stranger[0].already_paired);
+}
+
+#[tokio::test]
+async fn test_mdns_browser_finds_advertised_master() {
+    // Arrange – a unique instance name, so concurrent runs never collide
+    let instance_name = format!("test-master-{}", Uuid::new_v4().simple());
+    let _advertiser = MdnsAdvertiser::start(&AdvertiseConfig {
+        instance_name,
+        control_port: CONTROL_PORT,
+        include_loopback: true,
+    })
+    .unwrap();
+    let config = DiscoveryConfig {
+        timeout: Duration::from_secs(3),
+        backend: DiscoveryBackend::Mdns,
+        include_loopback: true,
+        ..DiscoveryConfig::default()
+    };
+
+    // Act
+    let found = discover_masters(&config).await.unwrap();
+
+    // Assert – the port comes from the TXT record; mDNS cannot know pairings
+    let ours: Vec<_> = found
+        .iter()
+        .filter(|m| m.control_addr.port() == CONTROL_PORT)
+        .collect();
+    assert!(!ours.is_empty(), "advertised master not found in {found:?}");
+    assert!(ours.iter().all(|m| !m.already_paired));
+}
@@ -0,0 +1,245 @@
+//! End-to-end test for pairing a connected client.
+//!
+//! # Purpose
+//!
+//! This test runs the real `ConnectionManager` and the real `kvm_client`
+//! `ClientConnection` and `ClientPairing` over loopback.  It walks the path
+//! the running master takes (the UI's `pair_client` command, or the headless
+//! master as a client connects):
+//!
+//! 1. `pair_connected_client` sends the client a `PairingRequest`.
+//! 2. The client answers with the PIN the master displays.
+//! 3. The master checks the answer when `PairingResponseReceived` arrives, as
+//!    its event pump does, and the client accepts the `PairingConfirm`.
+//! 4. The now paired client's clipboard reaches the master.
+//!
+//! A second test types a wrong PIN first: the master answers with
+//! `Error(PairingFailed)` and a fresh `PairingRequest`, which the client then
+//! answers with the right PIN.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use kvm_client::application::pair_with_master::ClientPairing;
+use kvm_client::infrastructure::network::{ClientConnection, ClientConnectionConfig, NetworkEvent};
+use kvm_core::clipboard::{chunk, ClipboardContent, DEFAULT_MAX_CLIPBOARD_LEN};
+use kvm_core::protocol::messages::KvmMessage;
+use kvm_master::infrastructure::network::connection_manager::{
+    ConnectionEvent, ConnectionManager, NetworkConfig, PairingError,
+};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Upper bound for any single wait in these tests.
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Waits for the first client message `pick` accepts.
+async fn next_client_message<T>(
+    rx: &mut mpsc::Receiver<NetworkEvent>,
+    pick: impl Fn(KvmMessage) -> Option<T>,
+) -> T {
+    tokio::time::timeout(STEP_TIMEOUT, async {
+        loop {
+            match rx.recv().await.expect("client event channel closed") {
+                NetworkEvent::MessageReceived(msg) => {
+                    if let Some(found) = pick(msg) {
+                        return found;
+                    }
+                }
+                _ => continue,
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for a client message")
+}
+
+/// Waits for the first master event `pick` accepts.
+async fn next_master_event<T>(
+    rx: &mut mpsc::Receiver<ConnectionEvent>,
+    pick: impl Fn(ConnectionEvent) -> Option<T>,
+) -> T {
+    tokio::time::timeout(STEP_TIMEOUT, async {
+        loop {
+            let event = rx.recv().await.expect("master event channel closed");
+            if let Some(found) = pick(event) {
+                return found;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for a master event")
+}
+
+/// A master and an unpaired client connected to it.
+struct Harness {
+    mgr: ConnectionManager,
+    master_rx: mpsc::Receiver<ConnectionEvent>,
+    client_id: Uuid,
+    client: Arc<ClientConnection>,
+    client_rx: mpsc::Receiver<NetworkEvent>,
+    client_running: Arc<AtomicBool>,
+}
+
+impl Harness {
+    async fn connect() -> Self {
+        let (mgr, mut master_rx) = ConnectionManager::new(NetworkConfig {
+            control_port: 0,
+            bind_address: "127.0.0.1".parse().unwrap(),
+            ..NetworkConfig::default()
+        });
+        let addr = mgr
+            .start_control_listener(Arc::new(AtomicBool::new(true)))
+            .await
+            .expect("listener must bind");
+        let client_id = Uuid::new_v4();
+        let client = Arc::new(ClientConnection::new(ClientConnectionConfig {
+            master_addr: addr,
+            client_id,
+            client_name: "pairing-client".to_string(),
+            reconnect_interval: Duration::from_secs(60),
+            max_reconnect_interval: Duration::from_secs(60),
+            tls: None,
+            input_port: None,
+        }));
+        let client_running = Arc::new(AtomicBool::new(true));
+        let client_rx = Arc::clone(&client).start(Arc::clone(&client_running)).await;
+        next_master_event(&mut master_rx, |e| match e {
+            ConnectionEvent::ClientConnected { client_id: id, .. } if id == client_id => Some(()),
+            _ => None,
+        })
+        .await;
+        Self {
+            mgr,
+            master_rx,
+            client_id,
+            client,
+            client_rx,
+            client_running,
+        }
+    }
+
+    /// Waits for the next `PairingRequest` and starts answering it.
+    async fn next_request(&mut self) -> ClientPairing {
+        let request = next_client_message(&mut self.client_rx, |m| match m {
+            KvmMessage::PairingRequest(req) => Some(req),
+            _ => None,
+        })
+        .await;
+        ClientPairing::new(request, None, None)
+    }
+
+    /// Sends the client's answer with `pin` and has the master check it when
+    /// it arrives, as the master's event pump does.
+    async fn answer(
+        &mut self,
+        attempt: &mut ClientPairing,
+        pin: &str,
+    ) -> Result<Uuid, PairingError> {
+        let response = attempt.respond(pin).expect("valid request");
+        self.client
+            .send_message(&KvmMessage::PairingResponse(response))
+            .await
+            .expect("pairing is always allowed");
+        let client_id = self.client_id;
+        let (address, response) = next_master_event(&mut self.master_rx, |e| match e {
+            ConnectionEvent::PairingResponseReceived {
+                client_id: id,
+                address,
+                response,
+            } if id == client_id => Some((address, response)),
+            _ => None,
+        })
+        .await;
+        self.mgr
+            .verify_pairing_response(client_id, &response, address.ip())
+    }
+
+    /// Waits for the master's `PairingConfirm` and checks it.
+    async fn confirm(&mut self, attempt: &ClientPairing) -> bool {
+        let confirm = next_client_message(&mut self.client_rx, |m| match m {
+            KvmMessage::PairingConfirm(confirm) => Some(confirm),
+            _ => None,
+        })
+        .await;
+        attempt.confirm(&confirm).is_ok()
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        self.client_running.store(false, Ordering::Relaxed);
+    }
+}
+
+#[tokio::test]
+async fn test_connected_client_pairs_and_then_shares_its_clipboard() {
+    // Arrange – a master and an unpaired client.
+    let mut h = Harness::connect().await;
+    let client_id = h.client_id;
+    assert!(!h.mgr.is_paired(client_id));
+
+    // Act – the master starts pairing; the user types the PIN on the client.
+    let challenge = h
+        .mgr
+        .pair_connected_client(client_id)
+        .expect("client is connected");
+    let mut attempt = h.next_request().await;
+    h.answer(&mut attempt, &challenge.pin)
+        .await
+        .expect("the PIN is correct");
+
+    // Assert – both sides agree, and the client's clipboard is now accepted.
+    assert!(h.confirm(&attempt).await);
+    assert!(h.mgr.is_paired(client_id));
+
+    let copied = ClipboardContent::text("copied after pairing");
+    for fragment in chunk(&copied, DEFAULT_MAX_CLIPBOARD_LEN).unwrap() {
+        h.client
+            .send_message(&KvmMessage::ClipboardData(fragment))
+            .await
+            .expect("clipboard is negotiated");
+    }
+    let received = next_master_event(&mut h.master_rx, |e| match e {
+        ConnectionEvent::ClipboardReceived {
+            client_id: id,
+            content,
+        } if id == client_id => Some(content),
+        _ => None,
+    })
+    .await;
+    assert_eq!(received, copied);
+}
+
+#[tokio::test]
+async fn test_client_pairs_with_the_new_request_after_a_wrong_pin() {
+    // Arrange – the user mistypes the PIN once.
+    let mut h = Harness::connect().await;
+    let challenge = h
+        .mgr
+        .pair_connected_client(h.client_id)
+        .expect("client is connected");
+    let wrong_pin = if challenge.pin == "000000" {
+        "000001"
+    } else {
+        "000000"
+    };
+    let mut first = h.next_request().await;
+    let failed = h.answer(&mut first, wrong_pin).await;
+    assert_eq!(
+        failed,
+        Err(PairingError::WrongPin {
+            attempts_remaining: 2
+        })
+    );
+
+    // Act – the client answers the request the master sent after the failure.
+    let mut retry = h.next_request().await;
+    let paired = h.answer(&mut retry, &challenge.pin).await;
+
+    // Assert
+    assert_eq!(retry.session_id(), challenge.session_id);
+    assert_eq!(paired, Ok(h.client_id));
+    assert!(h.confirm(&retry).await);
+}
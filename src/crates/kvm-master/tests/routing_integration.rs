@@ -14,6 +14,8 @@
 //!   `ClientRuntimeState` records correctly.
 //! - **Pairing lifecycle**: re-verifies the `ConnectionManager` pairing flow
 //!   as a cross-cutting integration test.
+//! - **Key routing**: HID key events from an `InputSource` reach the active
+//!   client with the held modifiers, once the cursor has crossed onto it.
 //!
 //! # How routing works (background)
 //!
@@ -50,6 +52,7 @@ fn test_build_layout_with_client_to_the_right_of_master() {
         y_offset: 0,
         width: 1920,
         height: 1080,
+        monitors: Vec::new(),
     }];
 
     // `build_layout(master_width, master_height, client_configs)`
@@ -80,6 +83,7 @@ fn test_build_layout_rejects_overlapping_clients() {
             y_offset: 0,
             width: 1920,
             height: 1080,
+            monitors: Vec::new(),
         },
         ClientLayoutConfig {
             client_id: id2,
@@ -89,6 +93,7 @@ fn test_build_layout_rejects_overlapping_clients() {
             y_offset: 0,
             width: 1920,
             height: 1080,
+            monitors: Vec::new(),
         },
     ];
 
@@ -200,24 +205,206 @@ fn test_connection_manager_pairing_lifecycle() {
     assert!(!mgr.is_paired(client_id));
 
     // Step 2: Initiate pairing — master generates a session UUID and a PIN.
-    let (session_id, pin) = mgr.initiate_pairing(client_id, addr).expect("initiate");
-
-    // Step 3: Compute the PIN hash the same way the client code would.
-    // In production, the client receives the session_id in the PairingRequest
-    // message and combines it with the PIN the user types.
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    let mut hasher = DefaultHasher::new();
-    pin.hash(&mut hasher);
-    session_id.as_bytes().hash(&mut hasher);
-    let pin_hash = format!("{:016x}", hasher.finish());
-
-    // Step 4: Submit the correct hash — must succeed and return the client_id.
+    let challenge = mgr.initiate_pairing(client_id, addr).expect("initiate");
+
+    // Step 3: Answer the request the same way the client code would.
+    // In production, the client receives the PairingRequest message and runs
+    // its half of the key exchange with the PIN the user types.
+    use kvm_core::crypto::pairing::{channel_binding, Role, Spake2};
+    let client = Spake2::start(
+        Role::Client,
+        &challenge.pin,
+        challenge.session_id,
+        &channel_binding(None, None),
+    );
+    let spake_message = client.message();
+    let keys = client.finish(&challenge.request.spake_message).unwrap();
+    let response = kvm_core::protocol::messages::PairingResponseMessage {
+        pairing_session_id: challenge.session_id,
+        spake_message,
+        confirmation: keys.confirmation(Role::Client),
+        accepted: true,
+    };
+
+    // Step 4: Submit the answer — must succeed and return the client_id.
     let paired_id = mgr
-        .verify_pairing_pin(session_id, &pin_hash, addr)
+        .verify_pairing_response(client_id, &response, addr)
         .expect("verify must succeed");
 
     // Step 5: Confirm the client is now recognised as paired.
     assert_eq!(paired_id, client_id);
     assert!(mgr.is_paired(client_id));
 }
+
+/// Records key events sent by the router; other input is accepted and dropped.
+#[derive(Default)]
+struct KeyRecorder {
+    keys: std::sync::Mutex<Vec<kvm_core::protocol::messages::KeyEventMessage>>,
+}
+
+#[async_trait::async_trait]
+impl kvm_master::application::route_input::InputTransmitter for KeyRecorder {
+    async fn send_key_event(
+        &self,
+        _client_id: Uuid,
+        event: kvm_core::protocol::messages::KeyEventMessage,
+    ) -> Result<(), String> {
+        self.keys.lock().unwrap().push(event);
+        Ok(())
+    }
+
+    async fn send_mouse_move(
+        &self,
+        _client_id: Uuid,
+        _event: kvm_core::protocol::messages::MouseMoveMessage,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn send_mouse_button(
+        &self,
+        _client_id: Uuid,
+        _event: kvm_core::protocol::messages::MouseButtonMessage,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn send_mouse_scroll(
+        &self,
+        _client_id: Uuid,
+        _event: kvm_core::protocol::messages::MouseScrollMessage,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn send_clipboard(
+        &self,
+        _client_id: Uuid,
+        _content: kvm_core::clipboard::ClipboardContent,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A cursor that stays wherever it was last teleported.
+#[derive(Default)]
+struct StillCursor(std::sync::Mutex<(i32, i32)>);
+
+impl kvm_master::application::route_input::CursorController for StillCursor {
+    fn teleport_cursor(&self, x: i32, y: i32) {
+        *self.0.lock().unwrap() = (x, y);
+    }
+
+    fn get_cursor_pos(&self) -> (i32, i32) {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Tests that key events captured as HID usages are forwarded unchanged to
+/// the client the cursor crossed onto, with the held modifier flags.
+///
+/// The events come from `MockInputSource`, so this is exactly what a platform
+/// source (Windows hooks or Linux evdev) hands to the router.
+#[tokio::test]
+async fn test_captured_hid_keys_route_to_active_client() {
+    use std::sync::Arc;
+
+    use kvm_core::keymap::HidKeyCode;
+    use kvm_core::protocol::messages::{KeyEventType, ModifierFlags};
+    use kvm_master::application::hotkeys::HotkeyBindings;
+    use kvm_master::application::route_input::{ActiveTarget, RouteInputUseCase};
+    use kvm_master::application::update_layout::{build_layout, ClientLayoutConfig};
+    use kvm_master::infrastructure::input_capture::{
+        mock::MockInputSource, InputSource, RawInputEvent,
+    };
+
+    // Arrange: one client to the right of a 1920x1080 master.
+    let client_id = Uuid::new_v4();
+    let layout = build_layout(
+        1920,
+        1080,
+        vec![ClientLayoutConfig {
+            client_id,
+            name: "right-screen".to_string(),
+            x_offset: 1920,
+            y_offset: 0,
+            width: 1920,
+            height: 1080,
+            monitors: Vec::new(),
+        }],
+    )
+    .expect("layout must be valid");
+    let recorder = Arc::new(KeyRecorder::default());
+    let mut router = RouteInputUseCase::new(
+        1920,
+        1080,
+        Arc::clone(&recorder) as _,
+        Arc::new(StillCursor::default()),
+        HotkeyBindings::new(),
+    );
+    router.update_layout(layout);
+
+    let source = MockInputSource::new();
+    let rx = source.start().expect("mock source starts");
+    let key = |key, down| {
+        if down {
+            RawInputEvent::KeyDown {
+                key,
+                scan_code: None,
+                time_ms: 0,
+            }
+        } else {
+            RawInputEvent::KeyUp {
+                key,
+                scan_code: None,
+                time_ms: 0,
+            }
+        }
+    };
+    source.inject_event(RawInputEvent::MouseMove {
+        x: 1919,
+        y: 500,
+        time_ms: 0,
+    });
+    source.inject_event(key(HidKeyCode::ControlLeft, true));
+    source.inject_event(key(HidKeyCode::NumpadEnter, true));
+    source.inject_event(key(HidKeyCode::NumpadEnter, false));
+    source.inject_event(key(HidKeyCode::ControlLeft, false));
+    source.stop();
+
+    // Act
+    while let Ok(event) = rx.recv() {
+        router.handle_event(event).await.expect("routing succeeds");
+    }
+
+    // Assert
+    assert_eq!(router.get_active_target(), &ActiveTarget::Client(client_id));
+    let keys: Vec<_> = recorder
+        .keys
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|k| (k.key_code, k.event_type, k.modifiers.0))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            (
+                HidKeyCode::ControlLeft,
+                KeyEventType::KeyDown,
+                ModifierFlags::LEFT_CTRL
+            ),
+            (
+                HidKeyCode::NumpadEnter,
+                KeyEventType::KeyDown,
+                ModifierFlags::LEFT_CTRL
+            ),
+            (
+                HidKeyCode::NumpadEnter,
+                KeyEventType::KeyUp,
+                ModifierFlags::LEFT_CTRL
+            ),
+            (HidKeyCode::ControlLeft, KeyEventType::KeyUp, 0),
+        ]
+    );
+}
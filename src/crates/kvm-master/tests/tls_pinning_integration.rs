@@ -0,0 +1,346 @@
+//! End-to-end tests for TLS on the control channel and certificate pinning.
+//!
+//! # Purpose
+//!
+//! These tests run the real `ConnectionManager` listener with TLS enabled and
+//! the real `kvm_client` `ClientConnection` over loopback.  They verify:
+//!
+//! - The mutual-TLS handshake succeeds and the master sees the fingerprint of
+//!   the client's certificate.
+//! - PIN pairing runs end to end over the TLS connection: the client answers
+//!   the master's `PairingRequest` with the PIN, the master pins the client's
+//!   fingerprint and reports it in `PairingCompleted`, and the client accepts
+//!   the master's `PairingConfirm`.
+//! - After pairing, a connection claiming the same client ID with a different
+//!   certificate is rejected with `AuthenticationFailed`, while the original
+//!   certificate is still accepted.
+//! - A client that pins the master's fingerprint refuses an impostor master.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use kvm_client::application::pair_with_master::ClientPairing;
+use kvm_client::infrastructure::network::{
+    ClientConnection, ClientConnectionConfig, ConnectionState, NetworkEvent,
+};
+use kvm_core::crypto::tls::{CertFingerprint, TlsIdentity};
+use kvm_core::protocol::messages::{HelloAckMessage, KvmMessage, ProtocolErrorCode};
+use kvm_master::infrastructure::network::connection_manager::{
+    ConnectionEvent, ConnectionManager, NetworkConfig,
+};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Upper bound for any single wait in these tests.
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to watch for an event that must *not* happen.
+const QUIET_PERIOD: Duration = Duration::from_millis(300);
+
+/// A TLS-enabled master listening on loopback.
+struct Master {
+    mgr: ConnectionManager,
+    rx: mpsc::Receiver<ConnectionEvent>,
+    addr: std::net::SocketAddr,
+    fingerprint: CertFingerprint,
+}
+
+async fn start_master() -> Master {
+    let (mut mgr, rx) = ConnectionManager::new(NetworkConfig {
+        control_port: 0,
+        bind_address: "127.0.0.1".parse().unwrap(),
+        ..NetworkConfig::default()
+    });
+    let identity = TlsIdentity::generate("test-master").unwrap();
+    mgr.enable_tls(&identity)
+        .expect("TLS identity must be usable");
+    let addr = mgr
+        .start_control_listener(Arc::new(AtomicBool::new(true)))
+        .await
+        .expect("listener must bind");
+    Master {
+        mgr,
+        rx,
+        addr,
+        fingerprint: identity.fingerprint(),
+    }
+}
+
+/// A running client; dropping it stops the reconnect loop.
+struct Client {
+    conn: Arc<ClientConnection>,
+    rx: mpsc::Receiver<NetworkEvent>,
+    running: Arc<AtomicBool>,
+    /// Master fingerprint seen in the TLS handshake.
+    master_fingerprint: Option<CertFingerprint>,
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+async fn start_client(
+    master: &Master,
+    client_id: Uuid,
+    identity: &TlsIdentity,
+    pinned_master: CertFingerprint,
+) -> Client {
+    let conn = Arc::new(ClientConnection::new(ClientConnectionConfig {
+        master_addr: master.addr,
+        client_id,
+        client_name: "tls-client".to_string(),
+        reconnect_interval: Duration::from_secs(60),
+        max_reconnect_interval: Duration::from_secs(60),
+        tls: Some(identity.client_config(Some(pinned_master)).unwrap()),
+        input_port: None,
+    }));
+    let running = Arc::new(AtomicBool::new(true));
+    let rx = Arc::clone(&conn).start(Arc::clone(&running)).await;
+    Client {
+        conn,
+        rx,
+        running,
+        master_fingerprint: None,
+    }
+}
+
+/// Waits for `Connected` followed by the `HelloAck`.
+async fn expect_hello_ack(client: &mut Client) -> HelloAckMessage {
+    match next_client_event(&mut client.rx).await {
+        NetworkEvent::Connected {
+            master_fingerprint, ..
+        } => client.master_fingerprint = master_fingerprint,
+        other => panic!("expected Connected, got {other:?}"),
+    }
+    match next_client_event(&mut client.rx).await {
+        NetworkEvent::MessageReceived(KvmMessage::HelloAck(ack)) => ack,
+        other => panic!("expected HelloAck, got {other:?}"),
+    }
+}
+
+/// Next client event, skipping connection state changes.
+async fn next_client_event(rx: &mut mpsc::Receiver<NetworkEvent>) -> NetworkEvent {
+    loop {
+        let event = tokio::time::timeout(STEP_TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for client event")
+            .expect("client event channel closed");
+        if !matches!(event, NetworkEvent::StateChanged(_)) {
+            return event;
+        }
+    }
+}
+
+async fn next_master_event(rx: &mut mpsc::Receiver<ConnectionEvent>) -> ConnectionEvent {
+    tokio::time::timeout(STEP_TIMEOUT, rx.recv())
+        .await
+        .expect("timed out waiting for master event")
+        .expect("master event channel closed")
+}
+
+/// Waits for the next control message, skipping connection events.
+async fn next_client_message(client: &mut Client) -> KvmMessage {
+    loop {
+        if let NetworkEvent::MessageReceived(msg) = next_client_event(&mut client.rx).await {
+            return msg;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_tls_session_exposes_client_certificate_fingerprint() {
+    // Arrange
+    let mut master = start_master().await;
+    let identity = TlsIdentity::generate("tls-client").unwrap();
+    let client_id = Uuid::new_v4();
+
+    // Act
+    let mut client = start_client(&master, client_id, &identity, master.fingerprint).await;
+    let ack = expect_hello_ack(&mut client).await;
+
+    // Assert
+    assert!(ack.accepted);
+    assert!(matches!(
+        next_master_event(&mut master.rx).await,
+        ConnectionEvent::ClientConnected { client_id: id, .. } if id == client_id
+    ));
+    let session = master.mgr.session(client_id).expect("session registered");
+    assert_eq!(session.peer_fingerprint(), Some(identity.fingerprint()));
+}
+
+#[tokio::test]
+async fn test_paired_client_id_only_accepted_with_pinned_certificate() {
+    // Arrange: connect and pair the genuine client.
+    let mut master = start_master().await;
+    let genuine = TlsIdentity::generate("genuine").unwrap();
+    let client_id = Uuid::new_v4();
+    let mut first = start_client(&master, client_id, &genuine, master.fingerprint).await;
+    assert!(expect_hello_ack(&mut first).await.accepted);
+    next_master_event(&mut master.rx).await; // ClientConnected
+
+    let ip = "127.0.0.1".parse().unwrap();
+    let challenge = master.mgr.initiate_pairing(client_id, ip).unwrap();
+    assert!(matches!(
+        next_master_event(&mut master.rx).await,
+        ConnectionEvent::PairingRequested { .. }
+    ));
+
+    // The client receives the request and the user types the PIN.
+    let request = match next_client_message(&mut first).await {
+        KvmMessage::PairingRequest(req) => req,
+        other => panic!("expected PairingRequest, got {other:?}"),
+    };
+    assert_eq!(first.master_fingerprint, Some(master.fingerprint));
+    let mut pairing = ClientPairing::new(
+        request,
+        Some(genuine.fingerprint()),
+        first.master_fingerprint,
+    );
+    let response = pairing.respond(&challenge.pin).unwrap();
+    first
+        .conn
+        .send_message(&KvmMessage::PairingResponse(response))
+        .await
+        .expect("pairing response must be sent");
+
+    // The master's connection task hands the response to the application.
+    let (address, response) = match next_master_event(&mut master.rx).await {
+        ConnectionEvent::PairingResponseReceived {
+            client_id: id,
+            address,
+            response,
+        } if id == client_id => (address, response),
+        other => panic!("expected PairingResponseReceived, got {other:?}"),
+    };
+    master
+        .mgr
+        .verify_pairing_response(client_id, &response, address.ip())
+        .expect("correct PIN must pair");
+    match next_master_event(&mut master.rx).await {
+        ConnectionEvent::PairingCompleted {
+            client_id: id,
+            fingerprint,
+        } => {
+            assert_eq!(id, client_id);
+            assert_eq!(fingerprint, Some(genuine.fingerprint()));
+        }
+        other => panic!("expected PairingCompleted, got {other:?}"),
+    }
+    assert_eq!(
+        master.mgr.paired_fingerprint(client_id),
+        Some(genuine.fingerprint())
+    );
+    match next_client_message(&mut first).await {
+        KvmMessage::PairingConfirm(confirm) => {
+            assert!(
+                pairing.confirm(&confirm).is_ok(),
+                "master must prove the PIN"
+            );
+        }
+        other => panic!("expected PairingConfirm, got {other:?}"),
+    }
+
+    // Act 1: an impostor reuses the client ID with its own certificate.
+    let impostor = TlsIdentity::generate("impostor").unwrap();
+    let mut fake = start_client(&master, client_id, &impostor, master.fingerprint).await;
+    let ack = expect_hello_ack(&mut fake).await;
+
+    // Assert 1
+    assert!(!ack.accepted, "impostor must be rejected");
+    assert_eq!(
+        ack.reject_reason,
+        ProtocolErrorCode::AuthenticationFailed as u8
+    );
+    assert!(
+        tokio::time::timeout(QUIET_PERIOD, master.rx.recv())
+            .await
+            .is_err(),
+        "a rejected connection must not produce events"
+    );
+
+    // Act 2: the genuine client reconnects with its own certificate.
+    drop(first);
+    let mut second = start_client(&master, client_id, &genuine, master.fingerprint).await;
+    let ack = expect_hello_ack(&mut second).await;
+
+    // Assert 2
+    assert!(
+        ack.accepted,
+        "the pinned certificate must still be accepted"
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_pin_is_reported_to_client_and_does_not_pair() {
+    // Arrange
+    let mut master = start_master().await;
+    let identity = TlsIdentity::generate("tls-client").unwrap();
+    let client_id = Uuid::new_v4();
+    let mut client = start_client(&master, client_id, &identity, master.fingerprint).await;
+    assert!(expect_hello_ack(&mut client).await.accepted);
+    next_master_event(&mut master.rx).await; // ClientConnected
+    let ip = "127.0.0.1".parse().unwrap();
+    let challenge = master.mgr.initiate_pairing(client_id, ip).unwrap();
+    next_master_event(&mut master.rx).await; // PairingRequested
+    let request = match next_client_message(&mut client).await {
+        KvmMessage::PairingRequest(req) => req,
+        other => panic!("expected PairingRequest, got {other:?}"),
+    };
+    let wrong_pin = if challenge.pin == "000000" {
+        "000001"
+    } else {
+        "000000"
+    };
+
+    // Act
+    let mut pairing = ClientPairing::new(
+        request,
+        Some(identity.fingerprint()),
+        client.master_fingerprint,
+    );
+    let response = pairing.respond(wrong_pin).unwrap();
+    let result = master.mgr.verify_pairing_response(client_id, &response, ip);
+
+    // Assert
+    assert!(result.is_err());
+    assert!(!master.mgr.is_paired(client_id));
+    match next_client_message(&mut client).await {
+        KvmMessage::Error(err) => {
+            assert_eq!(err.error_code, ProtocolErrorCode::PairingFailed);
+        }
+        other => panic!("expected Error(PairingFailed), got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_client_refuses_master_with_unexpected_fingerprint() {
+    // Arrange: the client expects a different master certificate.
+    let mut master = start_master().await;
+    let identity = TlsIdentity::generate("tls-client").unwrap();
+    let expected_master = TlsIdentity::generate("real-master").unwrap().fingerprint();
+
+    // Act
+    let mut client = start_client(&master, Uuid::new_v4(), &identity, expected_master).await;
+
+    // Assert: the handshake fails, so neither side sees a connection.  The
+    // client only gets as far as Connecting before backing off.
+    let deadline = tokio::time::Instant::now() + QUIET_PERIOD;
+    while let Ok(Some(event)) = tokio::time::timeout_at(deadline, client.rx.recv()).await {
+        assert!(
+            matches!(
+                event,
+                NetworkEvent::StateChanged(ConnectionState::Connecting | ConnectionState::Backoff)
+            ),
+            "client must not report Connected, got {event:?}"
+        );
+    }
+    assert!(
+        tokio::time::timeout(QUIET_PERIOD, master.rx.recv())
+            .await
+            .is_err(),
+        "master must not report ClientConnected"
+    );
+}
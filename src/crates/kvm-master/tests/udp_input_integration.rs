@@ -0,0 +1,158 @@
+//! End-to-end tests for the UDP input channel and its TCP fallback.
+//!
+//! # Purpose
+//!
+//! These tests run the real `ConnectionManager` (control listener and input
+//! channel), the real `NetworkInputTransmitter`, and the real `kvm_client`
+//! `ClientConnection` over loopback.  They verify:
+//!
+//! - A client with UDP enabled registers its address, answers the master's
+//!   probe, and then receives mouse movements through the input channel.
+//! - A client without UDP still receives every event: the transmitter falls
+//!   back to the control channel.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use kvm_client::infrastructure::network::{ClientConnection, ClientConnectionConfig, NetworkEvent};
+use kvm_core::protocol::messages::{KvmMessage, MouseMoveMessage};
+use kvm_master::application::route_input::InputTransmitter;
+use kvm_master::infrastructure::network::connection_manager::{
+    ConnectionEvent, ConnectionManager, NetworkConfig,
+};
+use kvm_master::infrastructure::network::input_channel::InputChannel;
+use kvm_master::infrastructure::network::input_transmitter::NetworkInputTransmitter;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Upper bound for any single wait in these tests.
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Harness {
+    mgr: ConnectionManager,
+    channel: InputChannel,
+    client_rx: mpsc::Receiver<NetworkEvent>,
+    client_running: Arc<AtomicBool>,
+    client_id: Uuid,
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        self.client_running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Starts a master with an input channel and connects one client, with or
+/// without UDP.  Returns once the master has emitted `ClientConnected`.
+async fn connect_client(client_udp: bool) -> Harness {
+    let (mgr, mut master_rx) = ConnectionManager::new(NetworkConfig {
+        control_port: 0,
+        input_port: 0,
+        bind_address: "127.0.0.1".parse().unwrap(),
+        ..NetworkConfig::default()
+    });
+    let running = Arc::new(AtomicBool::new(true));
+    let addr = mgr
+        .start_control_listener(Arc::clone(&running))
+        .await
+        .expect("listener must bind");
+    let channel = mgr
+        .start_input_channel(running)
+        .await
+        .expect("input channel must bind");
+
+    let client_id = Uuid::new_v4();
+    let client = Arc::new(ClientConnection::new(ClientConnectionConfig {
+        master_addr: addr,
+        client_id,
+        client_name: "udp-client".to_string(),
+        reconnect_interval: Duration::from_secs(60),
+        max_reconnect_interval: Duration::from_secs(60),
+        tls: None,
+        input_port: client_udp.then(|| channel.local_addr().unwrap().port()),
+    }));
+    let client_running = Arc::new(AtomicBool::new(true));
+    let client_rx = client.start(Arc::clone(&client_running)).await;
+
+    let event = tokio::time::timeout(STEP_TIMEOUT, master_rx.recv())
+        .await
+        .expect("timed out waiting for ClientConnected");
+    assert!(matches!(
+        event,
+        Some(ConnectionEvent::ClientConnected { client_id: id, .. }) if id == client_id
+    ));
+
+    Harness {
+        mgr,
+        channel,
+        client_rx,
+        client_running,
+        client_id,
+    }
+}
+
+/// Waits for the next input message, skipping connection events and the
+/// handshake.
+async fn next_mouse_move(rx: &mut mpsc::Receiver<NetworkEvent>) -> MouseMoveMessage {
+    loop {
+        let event = tokio::time::timeout(STEP_TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for input")
+            .expect("client event channel closed");
+        if let NetworkEvent::MessageReceived(KvmMessage::MouseMove(mv)) = event {
+            return mv;
+        }
+    }
+}
+
+fn mouse_move(x: i32) -> MouseMoveMessage {
+    MouseMoveMessage {
+        x,
+        y: 100,
+        delta_x: 1,
+        delta_y: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_mouse_moves_travel_over_udp_once_path_is_verified() {
+    // Arrange
+    let mut h = connect_client(true).await;
+    let transmitter = NetworkInputTransmitter::new(h.mgr.session_registry())
+        .with_input_channel(h.channel.clone());
+    tokio::time::timeout(STEP_TIMEOUT, async {
+        while !h.channel.is_active(h.client_id) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("client never verified its UDP path");
+
+    // Act
+    transmitter
+        .send_mouse_move(h.client_id, mouse_move(321))
+        .await
+        .expect("send must succeed");
+
+    // Assert
+    assert_eq!(next_mouse_move(&mut h.client_rx).await, mouse_move(321));
+}
+
+#[tokio::test]
+async fn test_client_without_udp_receives_input_over_tcp() {
+    // Arrange
+    let mut h = connect_client(false).await;
+    let transmitter = NetworkInputTransmitter::new(h.mgr.session_registry())
+        .with_input_channel(h.channel.clone());
+
+    // Act
+    transmitter
+        .send_mouse_move(h.client_id, mouse_move(7))
+        .await
+        .expect("fallback send must succeed");
+
+    // Assert
+    assert!(!h.channel.is_active(h.client_id));
+    assert_eq!(next_mouse_move(&mut h.client_rx).await, mouse_move(7));
+}
@@ -22,9 +22,10 @@
 
 use thiserror::Error;
 
+use kvm_core::crypto::pairing::PakeError;
 use kvm_core::protocol::messages::{
     ClipboardDataMessage, ClipboardFormat, DisconnectReason, HelloMessage, InputEvent, KvmMessage,
-    MonitorInfo, PairingResponseMessage, PlatformId, ScreenInfoMessage,
+    MonitorInfo, PlatformId, ScreenInfoMessage,
 };
 
 use crate::domain::messages::{BrowserToMasterMsg, InputEventJson, MasterToBrowserMsg};
@@ -48,6 +49,23 @@ pub enum BridgeError {
     /// For example, a capability bitmask contained reserved bits.
     #[error("invalid field value: {0}")]
     InvalidField(String),
+
+    /// A `PairingResponse` was passed to the stateless translator.
+    ///
+    /// Answering a pairing request needs the session's key-exchange state;
+    /// use [`super::BridgePairing::respond`] instead.
+    #[error("PairingResponse must be answered through the session's BridgePairing")]
+    NeedsPairingState,
+
+    /// The browser answered, or the master confirmed, a pairing session that
+    /// this bridge session is not running.
+    #[error("no matching pairing session in progress")]
+    NoPairingInProgress,
+
+    /// The SPAKE2 exchange failed: the master's share was invalid or its
+    /// confirmation did not verify.
+    #[error(transparent)]
+    KeyExchange(#[from] PakeError),
 }
 
 // ── Browser → Master translation ─────────────────────────────────────────────
@@ -61,7 +79,9 @@ pub enum BridgeError {
 /// # Errors
 ///
 /// Returns [`BridgeError::InvalidUuid`] if the browser sent a malformed UUID
-/// string (in `Hello.client_id` or `PairingResponse.pairing_session_id`).
+/// string in `Hello.client_id`, and [`BridgeError::NeedsPairingState`] for a
+/// `PairingResponse`, which only the session's [`super::BridgePairing`] can
+/// answer.
 ///
 /// # Example
 ///
@@ -94,6 +114,7 @@ pub fn translate_browser_to_kvm(json_msg: &BrowserToMasterMsg) -> Result<KvmMess
                 platform_id: PlatformId::Web,
                 client_name: client_name.clone(),
                 capabilities: *caps,
+                resume_token: None,
             }))
         }
 
@@ -117,20 +138,9 @@ pub fn translate_browser_to_kvm(json_msg: &BrowserToMasterMsg) -> Result<KvmMess
             }))
         }
 
-        BrowserToMasterMsg::PairingResponse {
-            pairing_session_id,
-            pin_hash,
-            accepted,
-        } => {
-            let session_uuid = uuid::Uuid::parse_str(pairing_session_id)
-                .map_err(|_| BridgeError::InvalidUuid(pairing_session_id.clone()))?;
-
-            Ok(KvmMessage::PairingResponse(PairingResponseMessage {
-                pairing_session_id: session_uuid,
-                pin_hash: pin_hash.clone(),
-                accepted: *accepted,
-            }))
-        }
+        // The PIN has to be fed through SPAKE2 with the request's share, which
+        // this stateless function does not have.
+        BrowserToMasterMsg::PairingResponse { .. } => Err(BridgeError::NeedsPairingState),
 
         BrowserToMasterMsg::ClipboardData { text } => {
             // Browser clipboard is always plain UTF-8 text.
@@ -278,7 +288,10 @@ pub fn translate_kvm_to_browser(kvm_msg: &KvmMessage) -> Option<MasterToBrowserM
         //   (should never come from the master)
         // - Announce / AnnounceResponse: UDP discovery, not used on WebSocket
         // - Pong: handled internally by the keepalive task, not for browser
+        // - PairingConfirm: verified by the session's `BridgePairing`, which
+        //   sends `PairingComplete` to the browser on success
         KvmMessage::Hello(_)
+        | KvmMessage::PairingConfirm(_)
         | KvmMessage::PairingResponse(_)
         | KvmMessage::ScreenInfo(_)
         | KvmMessage::Announce(_)
@@ -497,62 +510,19 @@ mod tests {
     }
 
     #[test]
-    fn test_browser_pairing_response_accepted_is_translated() {
+    fn test_browser_pairing_response_needs_session_state() {
         // Arrange
-        let session_id = Uuid::new_v4();
         let msg = BrowserToMasterMsg::PairingResponse {
-            pairing_session_id: session_id.to_string(),
-            pin_hash: "sha256:deadbeef01234567".to_string(),
+            pairing_session_id: Uuid::new_v4().to_string(),
+            pin: "123456".to_string(),
             accepted: true,
         };
 
         // Act
-        let result = translate_browser_to_kvm(&msg).unwrap();
-
-        // Assert
-        match result {
-            KvmMessage::PairingResponse(p) => {
-                assert_eq!(p.pairing_session_id, session_id);
-                assert_eq!(p.pin_hash, "sha256:deadbeef01234567");
-                assert!(p.accepted);
-            }
-            other => panic!("expected PairingResponse, got {:?}", other),
-        }
-    }
-
-    #[test]
-    fn test_browser_pairing_response_rejected_is_translated() {
-        // Arrange: user dismissed the dialog
-        let session_id = Uuid::new_v4();
-        let msg = BrowserToMasterMsg::PairingResponse {
-            pairing_session_id: session_id.to_string(),
-            pin_hash: String::new(),
-            accepted: false,
-        };
-
-        // Act
-        let result = translate_browser_to_kvm(&msg).unwrap();
-
-        // Assert
-        match result {
-            KvmMessage::PairingResponse(p) => {
-                assert!(!p.accepted);
-                assert!(p.pin_hash.is_empty());
-            }
-            other => panic!("expected PairingResponse, got {:?}", other),
-        }
-    }
-
-    #[test]
-    fn test_browser_pairing_response_with_invalid_uuid_returns_error() {
-        let msg = BrowserToMasterMsg::PairingResponse {
-            pairing_session_id: "bad-uuid".to_string(),
-            pin_hash: "abc".to_string(),
-            accepted: true,
-        };
         let result = translate_browser_to_kvm(&msg);
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), BridgeError::InvalidUuid(_)));
+
+        // Assert: the PIN is never forwarded as-is.
+        assert!(matches!(result, Err(BridgeError::NeedsPairingState)));
     }
 
     #[test]
@@ -618,6 +588,8 @@ mod tests {
             server_version: 1,
             accepted: true,
             reject_reason: 0,
+            capabilities: 0,
+            resumed: false,
         });
 
         // Act
@@ -645,6 +617,8 @@ mod tests {
             server_version: 1,
             accepted: false,
             reject_reason: 0x03, // PairingRequired
+            capabilities: 0,
+            resumed: false,
         });
         let result = translate_kvm_to_browser(&kvm).unwrap();
         match result {
@@ -667,6 +641,7 @@ mod tests {
         let kvm = KvmMessage::PairingRequest(PairingRequestMessage {
             pairing_session_id: session_id,
             expires_at_secs: 1_700_000_000,
+            spake_message: [9; 32],
         });
 
         // Act
@@ -1036,6 +1011,7 @@ mod tests {
             platform_id: PlatformId::Web,
             client_name: "test".to_string(),
             capabilities: 0,
+            resume_token: None,
         });
         assert!(translate_kvm_to_browser(&kvm).is_none());
     }
@@ -7,6 +7,7 @@
 //!
 //! - Translating browser JSON messages into binary KVM protocol messages
 //! - Translating binary KVM protocol messages into browser JSON messages
+//! - Running PIN pairing on the browser's behalf (`pairing`)
 //! - Defining the `BridgeError` type for application-level failures
 //!
 //! # What does NOT belong here?
@@ -16,9 +17,11 @@
 //! - WebSocket framing (handled by tokio-tungstenite)
 
 pub mod bridge_service;
+pub mod pairing;
 
 // Re-export so callers can write `application::bridge_service::translate_browser_to_kvm`
 // or more concisely `application::translate_browser_to_kvm`.
 pub use bridge_service::{
     base64_encode, translate_browser_to_kvm, translate_kvm_to_browser, BridgeError,
 };
+pub use pairing::BridgePairing;
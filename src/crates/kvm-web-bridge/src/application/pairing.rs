@@ -0,0 +1,252 @@
+//! PIN pairing on behalf of the browser.
+//!
+//! # Why the bridge runs the key exchange (for beginners)
+//!
+//! Pairing proves two things at once: the user knows the PIN the master is
+//! showing, and nobody is sitting between the two TLS endpoints.  The second
+//! half needs the certificate fingerprints of *this* connection, and the TLS
+//! connection to the master ends at the bridge, not in the browser.  So the
+//! browser only collects the PIN; the bridge turns it into the SPAKE2 share
+//! and confirmation MAC the master expects (see `kvm_core::crypto::pairing`),
+//! and checks the master's `PairingConfirm` before telling the browser that
+//! pairing succeeded.
+//!
+//! One [`BridgePairing`] lives for the whole browser session; it remembers the
+//! most recent `PairingRequest` and the keys from the most recent answer.
+
+use kvm_core::crypto::pairing::{channel_binding, PairingKeys, Role, Spake2};
+use kvm_core::crypto::tls::CertFingerprint;
+use kvm_core::protocol::messages::{
+    KvmMessage, PairingConfirmMessage, PairingRequestMessage, PairingResponseMessage,
+};
+
+use super::BridgeError;
+use crate::domain::messages::MasterToBrowserMsg;
+
+/// Pairing state of one browser session.
+pub struct BridgePairing {
+    binding: Vec<u8>,
+    request: Option<PairingRequestMessage>,
+    /// Keys from the last PIN the browser submitted.
+    keys: Option<PairingKeys>,
+}
+
+impl BridgePairing {
+    /// Creates the pairing state for a master connection.
+    ///
+    /// `own_certificate` is the bridge's TLS fingerprint and
+    /// `master_certificate` the one the master presented; both are `None` on
+    /// a plaintext connection.
+    pub fn new(
+        own_certificate: Option<CertFingerprint>,
+        master_certificate: Option<CertFingerprint>,
+    ) -> Self {
+        Self {
+            binding: channel_binding(
+                own_certificate.as_ref().map(CertFingerprint::as_bytes),
+                master_certificate.as_ref().map(CertFingerprint::as_bytes),
+            ),
+            request: None,
+            keys: None,
+        }
+    }
+
+    /// Records a `PairingRequest` from the master, replacing any earlier one.
+    pub fn on_request(&mut self, request: &PairingRequestMessage) {
+        self.request = Some(request.clone());
+        self.keys = None;
+    }
+
+    /// Builds the `PairingResponse` for the PIN the browser submitted.
+    ///
+    /// # Errors
+    ///
+    /// - [`BridgeError::InvalidUuid`] if `pairing_session_id` is malformed.
+    /// - [`BridgeError::NoPairingInProgress`] if the master has not asked to
+    ///   pair, or asked for a different session.
+    /// - [`BridgeError::KeyExchange`] if the master's share is invalid.
+    pub fn respond(
+        &mut self,
+        pairing_session_id: &str,
+        pin: &str,
+        accepted: bool,
+    ) -> Result<KvmMessage, BridgeError> {
+        let session_id = uuid::Uuid::parse_str(pairing_session_id)
+            .map_err(|_| BridgeError::InvalidUuid(pairing_session_id.to_string()))?;
+        let request = self
+            .request
+            .as_ref()
+            .filter(|r| r.pairing_session_id == session_id)
+            .ok_or(BridgeError::NoPairingInProgress)?;
+
+        if !accepted {
+            return Ok(KvmMessage::PairingResponse(PairingResponseMessage {
+                pairing_session_id: session_id,
+                spake_message: [0; 32],
+                confirmation: [0; 32],
+                accepted: false,
+            }));
+        }
+
+        let spake = Spake2::start(Role::Client, pin.trim(), session_id, &self.binding);
+        let spake_message = spake.message();
+        let keys = spake.finish(&request.spake_message)?;
+        let confirmation = keys.confirmation(Role::Client);
+        self.keys = Some(keys);
+        Ok(KvmMessage::PairingResponse(PairingResponseMessage {
+            pairing_session_id: session_id,
+            spake_message,
+            confirmation,
+            accepted: true,
+        }))
+    }
+
+    /// Checks the master's `PairingConfirm` and, if it proves the master used
+    /// the same PIN, returns the `PairingComplete` message for the browser.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BridgeError::NoPairingInProgress`] for a confirmation we did
+    /// not ask for and [`BridgeError::KeyExchange`] if the MAC is wrong.
+    pub fn confirm(&self, msg: &PairingConfirmMessage) -> Result<MasterToBrowserMsg, BridgeError> {
+        let keys = self
+            .keys
+            .as_ref()
+            .filter(|_| {
+                self.request.as_ref().map(|r| r.pairing_session_id) == Some(msg.pairing_session_id)
+            })
+            .ok_or(BridgeError::NoPairingInProgress)?;
+        keys.verify_confirmation(Role::Master, &msg.confirmation)?;
+        Ok(MasterToBrowserMsg::PairingComplete {
+            pairing_session_id: msg.pairing_session_id.to_string(),
+        })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvm_core::crypto::pairing::PakeError;
+    use uuid::Uuid;
+
+    const PIN: &str = "864209";
+
+    /// Master-side key exchange for a request, using the same certificates.
+    fn master(
+        bridge_cert: CertFingerprint,
+        master_cert: CertFingerprint,
+    ) -> (Spake2, PairingRequestMessage) {
+        let session = Uuid::new_v4();
+        let binding = channel_binding(Some(bridge_cert.as_bytes()), Some(master_cert.as_bytes()));
+        let spake = Spake2::start(Role::Master, PIN, session, &binding);
+        let request = PairingRequestMessage {
+            pairing_session_id: session,
+            expires_at_secs: 1_700_000_000,
+            spake_message: spake.message(),
+        };
+        (spake, request)
+    }
+
+    fn response(msg: KvmMessage) -> PairingResponseMessage {
+        match msg {
+            KvmMessage::PairingResponse(r) => r,
+            other => panic!("expected PairingResponse, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_pin_from_browser_completes_pairing() {
+        // Arrange
+        let bridge_cert = CertFingerprint::of(b"bridge");
+        let master_cert = CertFingerprint::of(b"master");
+        let (master_spake, request) = master(bridge_cert, master_cert);
+        let mut pairing = BridgePairing::new(Some(bridge_cert), Some(master_cert));
+        pairing.on_request(&request);
+
+        // Act
+        let resp = response(
+            pairing
+                .respond(&request.pairing_session_id.to_string(), PIN, true)
+                .unwrap(),
+        );
+        let master_keys = master_spake.finish(&resp.spake_message).unwrap();
+        let confirm = PairingConfirmMessage {
+            pairing_session_id: request.pairing_session_id,
+            confirmation: master_keys.confirmation(Role::Master),
+        };
+
+        // Assert
+        assert!(master_keys
+            .verify_confirmation(Role::Client, &resp.confirmation)
+            .is_ok());
+        assert_eq!(
+            pairing.confirm(&confirm).unwrap(),
+            MasterToBrowserMsg::PairingComplete {
+                pairing_session_id: request.pairing_session_id.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_forged_confirmation_is_rejected() {
+        // Arrange
+        let bridge_cert = CertFingerprint::of(b"bridge");
+        let master_cert = CertFingerprint::of(b"master");
+        let (_, request) = master(bridge_cert, master_cert);
+        let mut pairing = BridgePairing::new(Some(bridge_cert), Some(master_cert));
+        pairing.on_request(&request);
+        pairing
+            .respond(&request.pairing_session_id.to_string(), PIN, true)
+            .unwrap();
+
+        // Act
+        let result = pairing.confirm(&PairingConfirmMessage {
+            pairing_session_id: request.pairing_session_id,
+            confirmation: [7; 32],
+        });
+
+        // Assert
+        assert!(matches!(
+            result,
+            Err(BridgeError::KeyExchange(PakeError::ConfirmationMismatch))
+        ));
+    }
+
+    #[test]
+    fn test_declined_response_carries_no_key_material() {
+        let (_, request) = master(CertFingerprint::of(b"b"), CertFingerprint::of(b"m"));
+        let mut pairing = BridgePairing::new(None, None);
+        pairing.on_request(&request);
+
+        let resp = response(
+            pairing
+                .respond(&request.pairing_session_id.to_string(), "", false)
+                .unwrap(),
+        );
+
+        assert!(!resp.accepted);
+        assert_eq!(resp.spake_message, [0; 32]);
+    }
+
+    #[test]
+    fn test_response_without_matching_request_is_rejected() {
+        let (_, request) = master(CertFingerprint::of(b"b"), CertFingerprint::of(b"m"));
+        let mut pairing = BridgePairing::new(None, None);
+
+        assert!(matches!(
+            pairing.respond(&request.pairing_session_id.to_string(), PIN, true),
+            Err(BridgeError::NoPairingInProgress)
+        ));
+        pairing.on_request(&request);
+        assert!(matches!(
+            pairing.respond(&Uuid::new_v4().to_string(), PIN, true),
+            Err(BridgeError::NoPairingInProgress)
+        ));
+        assert!(matches!(
+            pairing.respond("bad-uuid", PIN, true),
+            Err(BridgeError::InvalidUuid(_))
+        ));
+    }
+}
@@ -14,6 +14,8 @@
 use std::net::SocketAddr;
 use std::time::Duration;
 
+use kvm_core::crypto::tls::CertFingerprint;
+
 /// All runtime configuration for the WebSocket bridge.
 ///
 /// Build this struct once at startup (via CLI args or defaults) and then wrap
@@ -54,6 +56,19 @@ pub struct BridgeConfig {
     /// Maximum time to wait for a KVM Pong reply before the bridge considers
     /// the master connection dead and closes the session.
     pub ping_timeout: Duration,
+
+    /// Whether to wrap the master connection in TLS.
+    ///
+    /// The master requires TLS; turning this off only works against a master
+    /// started without it (tests, packet-capture debugging).
+    pub master_tls: bool,
+
+    /// Expected fingerprint of the master's certificate.
+    ///
+    /// When set, connections to a master presenting any other certificate are
+    /// refused during the TLS handshake.  When `None`, any master certificate
+    /// is accepted.
+    pub master_fingerprint: Option<CertFingerprint>,
 }
 
 impl Default for BridgeConfig {
@@ -66,6 +81,8 @@ impl Default for BridgeConfig {
     /// | master_addr     | `127.0.0.1:24800`   |
     /// | ping_interval   | 5 seconds           |
     /// | ping_timeout    | 15 seconds          |
+    /// | master_tls      | `true`              |
+    /// | master_fingerprint | `None`           |
     fn default() -> Self {
         Self {
             // The `.parse().unwrap()` calls here are safe because these are
@@ -74,6 +91,8 @@ impl Default for BridgeConfig {
             master_addr: "127.0.0.1:24800".parse().unwrap(),
             ping_interval: Duration::from_secs(5),
             ping_timeout: Duration::from_secs(15),
+            master_tls: true,
+            master_fingerprint: None,
         }
     }
 }
@@ -119,6 +138,13 @@ mod tests {
         assert_eq!(cfg.ping_timeout, Duration::from_secs(15));
     }
 
+    #[test]
+    fn test_default_uses_tls_to_master_without_pin() {
+        let cfg = BridgeConfig::default();
+        assert!(cfg.master_tls);
+        assert_eq!(cfg.master_fingerprint, None);
+    }
+
     #[test]
     fn test_config_can_be_cloned() {
         // Cloneability is required so an Arc<BridgeConfig> can be shared
@@ -137,6 +163,7 @@ mod tests {
             master_addr: "10.0.0.5:24800".parse().unwrap(),
             ping_interval: Duration::from_secs(10),
             ping_timeout: Duration::from_secs(30),
+            ..BridgeConfig::default()
         };
         assert_eq!(cfg.ws_bind_addr.port(), 9000);
         assert_eq!(cfg.master_addr.ip().to_string(), "10.0.0.5");
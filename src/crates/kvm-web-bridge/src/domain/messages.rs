@@ -100,10 +100,12 @@ pub enum BrowserToMasterMsg {
     PairingResponse {
         /// The pairing session UUID received in the `PairingRequest`.
         pairing_session_id: String,
-        /// SHA-256 hash of (PIN + pairing_session_id), as a lowercase hex string.
+        /// The 6-digit PIN exactly as the user typed it.
         ///
-        /// The master verifies this by computing the same hash on its side.
-        pin_hash: String,
+        /// The PIN never reaches the master: the bridge runs the SPAKE2 key
+        /// exchange on the browser's behalf and sends only its share and
+        /// confirmation MAC.  Leave empty when `accepted` is `false`.
+        pin: String,
         /// `true` if the user entered a PIN; `false` if they dismissed the dialog.
         accepted: bool,
     },
@@ -163,6 +165,13 @@ pub enum MasterToBrowserMsg {
         expires_at_secs: u64,
     },
 
+    /// Pairing finished: the master accepted the PIN and proved it knows it
+    /// too, so this bridge session is now trusted.
+    PairingComplete {
+        /// The pairing session that completed.
+        pairing_session_id: String,
+    },
+
     /// Master acknowledged receipt of the browser's screen information.
     ScreenInfoAck,
 
@@ -395,7 +404,7 @@ mod tests {
     fn test_browser_pairing_response_round_trips() {
         let original = BrowserToMasterMsg::PairingResponse {
             pairing_session_id: "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee".to_string(),
-            pin_hash: "sha256:deadbeef".to_string(),
+            pin: "123456".to_string(),
             accepted: true,
         };
         let json = serde_json::to_string(&original).unwrap();
@@ -6,9 +6,16 @@
 //! # Binary streaming protocol
 //!
 //! TCP is a *stream* protocol: a single `read()` call may return less than one
-//! complete KVM message, or more than one.  This module buffers incoming bytes
-//! and uses [`kvm_core::protocol::decode_message`] to extract complete messages
-//! from the buffer one at a time.
+//! complete KVM message, or more than one.  This module reads through
+//! [`kvm_core::protocol::KvmCodec`], which buffers incoming bytes and extracts
+//! complete messages one at a time.
+//!
+//! # TLS
+//!
+//! The master requires TLS on its control port.  [`MasterConnection::connect`]
+//! takes an optional `TlsConnector`; the read and write halves are boxed trait
+//! objects so the rest of the bridge does not care whether the stream is
+//! encrypted.
 //!
 //! # Portability note
 //!
@@ -19,12 +26,21 @@
 use std::net::SocketAddr;
 
 use anyhow::Context;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use futures_util::StreamExt;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_util::codec::FramedRead;
 use tracing::{debug, warn};
 
-use kvm_core::protocol::codec::ProtocolError;
+use kvm_core::crypto::tls::{server_name, CertFingerprint};
 use kvm_core::protocol::messages::KvmMessage;
+use kvm_core::protocol::KvmCodec;
+
+/// Read half of the master connection (plain TCP or TLS).
+pub type MasterReader = Box<dyn AsyncRead + Send + Unpin>;
+/// Write half of the master connection (plain TCP or TLS).
+pub type MasterWriter = Box<dyn AsyncWrite + Send + Unpin>;
 
 /// A wrapper around a TCP connection to the KVM master.
 ///
@@ -38,20 +54,25 @@ use kvm_core::protocol::messages::KvmMessage;
 /// move them out by value.  Once split, the individual halves are passed to
 /// the two forwarding tasks.
 pub struct MasterConnection {
-    /// Read half of the master TCP stream.
-    pub read_half: tokio::net::tcp::OwnedReadHalf,
-    /// Write half of the master TCP stream.
-    pub write_half: tokio::net::tcp::OwnedWriteHalf,
+    /// Read half of the master stream.
+    pub read_half: MasterReader,
+    /// Write half of the master stream.
+    pub write_half: MasterWriter,
+    /// Fingerprint of the certificate the master presented, or `None` on a
+    /// plaintext connection.  PIN pairing binds to it.
+    pub master_fingerprint: Option<CertFingerprint>,
 }
 
 impl MasterConnection {
-    /// Opens a new TCP connection to the KVM master at `master_addr`.
+    /// Opens a new TCP connection to the KVM master at `master_addr`, wrapped
+    /// in TLS when `tls` is `Some`.
     ///
     /// # Errors
     ///
     /// Returns an error if the TCP connection cannot be established (e.g.,
     /// the master is not running, the address is wrong, or a firewall blocks
-    /// the connection).
+    /// the connection), or if the TLS handshake fails (e.g., the master's
+    /// certificate does not match the pinned fingerprint).
     ///
     /// # Example
     ///
@@ -61,11 +82,14 @@ impl MasterConnection {
     ///
     /// # async fn example() -> anyhow::Result<()> {
     /// let addr: SocketAddr = "127.0.0.1:24800".parse().unwrap();
-    /// let conn = MasterConnection::connect(addr).await?;
+    /// let conn = MasterConnection::connect(addr, None).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn connect(master_addr: SocketAddr) -> anyhow::Result<Self> {
+    pub async fn connect(
+        master_addr: SocketAddr,
+        tls: Option<&TlsConnector>,
+    ) -> anyhow::Result<Self> {
         // `TcpStream::connect` performs the TCP three-way handshake asynchronously.
         // We await it here, which yields control to the Tokio runtime while
         // the network operation is in progress.
@@ -75,11 +99,25 @@ impl MasterConnection {
 
         // Split into independent read and write halves so we can pass each to
         // a separate async task without shared ownership.
-        let (read_half, write_half) = stream.into_split();
+        let Some(connector) = tls else {
+            let (read_half, write_half) = stream.into_split();
+            return Ok(Self {
+                read_half: Box::new(read_half),
+                write_half: Box::new(write_half),
+                master_fingerprint: None,
+            });
+        };
+        let stream = connector
+            .connect(server_name(), stream)
+            .await
+            .with_context(|| format!("TLS handshake with KVM master at {master_addr} failed"))?;
+        let master_fingerprint = CertFingerprint::of_peer(stream.get_ref().1);
+        let (read_half, write_half) = tokio::io::split(stream);
 
         Ok(Self {
-            read_half,
-            write_half,
+            read_half: Box::new(read_half),
+            write_half: Box::new(write_half),
+            master_fingerprint,
         })
     }
 }
@@ -88,109 +126,77 @@ impl MasterConnection {
 
 /// Reads KVM messages from the master TCP stream and forwards them as JSON.
 ///
-/// This function runs in a loop, accumulating bytes from the TCP stream until
-/// complete KVM messages can be decoded.  Each decoded message is translated
-/// to JSON and written to the `tx` channel, where the WebSocket write task
-/// picks it up and sends it to the browser.
+/// This function runs in a loop, pulling complete KVM messages off the TCP
+/// stream.  Each decoded message is written to the `tx` channel, where the
+/// WebSocket write task translates it to JSON and sends it to the browser.
 ///
-/// # Why a buffer is needed
+/// # Why a framing codec is needed
 ///
 /// TCP is a stream protocol.  There is no guarantee that a single `read()` call
 /// returns exactly one complete KVM message:
 ///
-/// - It may return fewer bytes than the full message (partial read).
+/// - It may return fewer bytes than the full message (partial read) – even
+///   part of the payload after a complete header.
 /// - It may return bytes from multiple messages at once (coalesced reads).
 ///
-/// We solve this by accumulating all received bytes in `recv_buf` and calling
-/// `decode_message` in a loop until `InsufficientData` tells us we need more.
+/// The stream is wrapped in a `FramedRead` driven by kvm-core's [`KvmCodec`],
+/// the same framing layer the master and the native client use.  It buffers
+/// partial frames, splits coalesced ones, and rejects frames whose declared
+/// payload exceeds the codec's limit.
 ///
 /// # Parameters
 ///
-/// - `read_half`  – Read half of the master TCP stream.
+/// - `read_half`  – Read half of the master stream.
 /// - `session_id` – Session identifier string for log messages.
 /// - `tx`         – Channel sender: each decoded message is sent here.
 ///
 /// The function returns when the TCP connection is closed (EOF) or an
 /// unrecoverable error occurs.
 pub async fn read_master_messages(
-    mut read_half: tokio::net::tcp::OwnedReadHalf,
+    read_half: MasterReader,
     session_id: &str,
     tx: tokio::sync::mpsc::Sender<KvmMessage>,
 ) {
-    // Streaming receive buffer — accumulates bytes across multiple read() calls.
-    let mut recv_buf: Vec<u8> = Vec::with_capacity(4096);
-    // Temporary read buffer for each individual `read()` syscall.
-    let mut read_tmp = vec![0u8; 4096];
-
-    loop {
-        // Read more bytes from the master TCP stream.
-        // `read()` returns the number of bytes actually read (may be less than
-        // `read_tmp.len()` — that is normal and expected).
-        let n = match read_half.read(&mut read_tmp).await {
-            Ok(0) => {
-                // `read()` returned 0 bytes → the master closed the connection (EOF).
-                debug!("session {session_id}: master TCP connection closed (EOF)");
-                break;
-            }
-            Ok(n) => n,
+    let mut frames = FramedRead::new(read_half, KvmCodec::new());
+
+    while let Some(result) = frames.next().await {
+        let kvm_msg = match result {
+            Ok(frame) => frame.message,
             Err(e) => {
+                // A corrupt header, an oversized frame, or an I/O failure.
+                // The connection is unsalvageable; close the session.
                 warn!("session {session_id}: read from master failed: {e}");
-                break;
+                return;
             }
         };
+        debug!(
+            "session {session_id}: decoded KVM message: {:?}",
+            kvm_msg.message_type()
+        );
 
-        // Append the newly arrived bytes to the accumulation buffer.
-        recv_buf.extend_from_slice(&read_tmp[..n]);
-
-        // Attempt to decode one or more complete messages from the buffer.
-        // We loop here because a single `read()` may have delivered multiple
-        // complete messages at once.
-        loop {
-            match kvm_core::protocol::decode_message(&recv_buf) {
-                Ok((kvm_msg, consumed)) => {
-                    debug!(
-                        "session {session_id}: decoded KVM message: {:?}",
-                        kvm_msg.message_type()
-                    );
-
-                    // Remove the consumed bytes from the front of the buffer.
-                    // `drain(..consumed)` shifts remaining bytes to the front,
-                    // which is O(n) but fine for the small message sizes here.
-                    recv_buf.drain(..consumed);
-
-                    // Send the decoded message to the WebSocket write task.
-                    // If the receiver has been dropped, the session is over.
-                    if tx.send(kvm_msg).await.is_err() {
-                        debug!("session {session_id}: message channel closed; exiting reader");
-                        return;
-                    }
-                }
-                Err(ProtocolError::InsufficientData { .. }) => {
-                    // Normal — we just don't have a full message yet.
-                    // Break out of the inner loop and wait for more bytes.
-                    break;
-                }
-                Err(e) => {
-                    // A real decode error (unknown message type, corrupt header, etc.).
-                    // The connection is likely unsalvageable; close the session.
-                    warn!("session {session_id}: KVM decode error from master: {e}");
-                    return;
-                }
-            }
+        // Send the decoded message to the WebSocket write task.
+        // If the receiver has been dropped, the session is over.
+        if tx.send(kvm_msg).await.is_err() {
+            debug!("session {session_id}: message channel closed; exiting reader");
+            return;
         }
     }
+
+    // The stream ended: the master closed the connection (EOF).
+    debug!("session {session_id}: master TCP connection closed (EOF)");
 }
 
-/// Writes an encoded KVM message to the master TCP stream.
+/// Writes an encoded KVM message to the master stream.
 ///
-/// This is a thin wrapper around `write_all` that provides a session-ID
-/// context for error logging.
+/// This is a thin wrapper around `write_all` + `flush` that provides a
+/// session-ID context for error logging.  The flush matters for TLS, which
+/// otherwise may hold the record back.
 ///
 /// # Errors
 ///
 /// Returns an error if the write fails (e.g., the master closed the connection).
 pub async fn write_kvm_message(
-    write_half: &mut tokio::net::tcp::OwnedWriteHalf,
+    write_half: &mut MasterWriter,
     bytes: &[u8],
     session_id: &str,
 ) -> anyhow::Result<()> {
@@ -199,7 +205,11 @@ pub async fn write_kvm_message(
     write_half
         .write_all(bytes)
         .await
-        .with_context(|| format!("session {session_id}: write to master failed"))
+        .with_context(|| format!("session {session_id}: write to master failed"))?;
+    write_half
+        .flush()
+        .await
+        .with_context(|| format!("session {session_id}: flush to master failed"))
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -207,7 +217,8 @@ pub async fn write_kvm_message(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use kvm_core::protocol::codec::encode_message;
+    use kvm_core::crypto::tls::TlsIdentity;
+    use kvm_core::protocol::codec::{encode_message, ProtocolError};
     use kvm_core::protocol::messages::{KvmMessage, MouseMoveMessage};
 
     /// Test that the `read_master_messages` loop correctly handles a pre-encoded
@@ -288,4 +299,128 @@ mod tests {
         assert_eq!(decoded2, msg2);
         assert_eq!(consumed1 + consumed2, buf.len());
     }
+
+    /// Connects a loopback socket pair and starts `read_master_messages` on
+    /// the accepted side.  Returns the "master" end and the message receiver.
+    async fn spawn_reader() -> (TcpStream, tokio::sync::mpsc::Receiver<KvmMessage>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let master = TcpStream::connect(addr).await.unwrap();
+        let (bridge, _) = listener.accept().await.unwrap();
+        let (read_half, write_half) = bridge.into_split();
+        let (tx, rx) = tokio::sync::mpsc::channel(8);
+        tokio::spawn(async move {
+            read_master_messages(Box::new(read_half), "test", tx).await;
+            // Keep the write half alive until the reader is done.
+            drop(write_half);
+        });
+        (master, rx)
+    }
+
+    #[tokio::test]
+    async fn test_reader_waits_for_payload_split_across_tcp_writes() {
+        // Arrange: a complete header arrives first, the payload later.  This
+        // used to be reported as a length mismatch and close the session.
+        let (mut master, mut rx) = spawn_reader().await;
+        let msg = KvmMessage::MouseMove(MouseMoveMessage {
+            x: 1,
+            y: 2,
+            delta_x: 3,
+            delta_y: 4,
+        });
+        let bytes = encode_message(&msg, 0, 0).unwrap();
+        let split = kvm_core::protocol::messages::HEADER_SIZE + 2;
+
+        // Act
+        master.write_all(&bytes[..split]).await.unwrap();
+        master.flush().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        master.write_all(&bytes[split..]).await.unwrap();
+
+        // Assert
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out");
+        assert_eq!(received, Some(msg));
+    }
+
+    #[tokio::test]
+    async fn test_reader_closes_session_on_oversized_frame_header() {
+        // Arrange: a header claiming a 4 GiB payload.
+        let (mut master, mut rx) = spawn_reader().await;
+        let mut header = encode_message(&KvmMessage::Ping(0), 0, 0).unwrap();
+        header.truncate(kvm_core::protocol::messages::HEADER_SIZE);
+        header[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        // Act
+        master.write_all(&header).await.unwrap();
+
+        // Assert: the reader gives up, which drops the sender.
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out");
+        assert_eq!(received, None);
+    }
+
+    /// Starts a one-shot TLS "master" that sends `msg` after the handshake.
+    /// Returns its address and certificate fingerprint.
+    async fn spawn_tls_master(
+        msg: KvmMessage,
+    ) -> (SocketAddr, kvm_core::crypto::tls::CertFingerprint) {
+        let identity = TlsIdentity::generate("test-master").unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(identity.server_config().unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            if let Ok(mut tls) = acceptor.accept(tcp).await {
+                tls.write_all(&encode_message(&msg, 0, 0).unwrap())
+                    .await
+                    .unwrap();
+                tls.flush().await.unwrap();
+                // Hold the connection open until the bridge hangs up.
+                let mut sink = Vec::new();
+                let _ = tokio::io::AsyncReadExt::read_to_end(&mut tls, &mut sink).await;
+            }
+        });
+        (addr, identity.fingerprint())
+    }
+
+    #[tokio::test]
+    async fn test_connect_over_tls_reads_master_messages() {
+        // Arrange: the bridge pins the master's actual certificate.
+        let (addr, master_fp) = spawn_tls_master(KvmMessage::Pong(7)).await;
+        let bridge = TlsIdentity::generate("test-bridge").unwrap();
+        let connector = TlsConnector::from(bridge.client_config(Some(master_fp)).unwrap());
+
+        // Act
+        let conn = MasterConnection::connect(addr, Some(&connector))
+            .await
+            .expect("TLS connect must succeed");
+        assert_eq!(conn.master_fingerprint, Some(master_fp));
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        tokio::spawn(read_master_messages(conn.read_half, "tls-test", tx));
+
+        // Assert
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out");
+        assert_eq!(received, Some(KvmMessage::Pong(7)));
+    }
+
+    #[tokio::test]
+    async fn test_connect_over_tls_fails_on_master_fingerprint_mismatch() {
+        // Arrange: pin a certificate the master does not have.
+        let (addr, _) = spawn_tls_master(KvmMessage::Pong(7)).await;
+        let impostor_pin = TlsIdentity::generate("other").unwrap().fingerprint();
+        let bridge = TlsIdentity::generate("test-bridge").unwrap();
+        let connector = TlsConnector::from(bridge.client_config(Some(impostor_pin)).unwrap());
+
+        // Act
+        let result = MasterConnection::connect(addr, Some(&connector)).await;
+
+        // Assert
+        let err = result.err().expect("pin mismatch must fail the handshake");
+        assert!(format!("{err:#}").contains("TLS handshake"), "got: {err:#}");
+    }
 }
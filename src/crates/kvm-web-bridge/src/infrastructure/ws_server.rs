@@ -5,7 +5,7 @@
 //! 1. Binding a TCP listener on the configured address.
 //! 2. Accepting incoming TCP connections from browsers.
 //! 3. Upgrading each connection to a WebSocket session.
-//! 4. Opening a corresponding TCP connection to the KVM master.
+//! 4. Opening a corresponding TLS connection to the KVM master.
 //! 5. Running two concurrent forwarding tasks per session:
 //!    - **Browser → Master**: reads JSON from WebSocket, translates to binary,
 //!      writes to the master TCP stream.
@@ -32,7 +32,7 @@
 use std::net::SocketAddr;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, MutexGuard,
 };
 use std::time::Duration;
 
@@ -40,16 +40,19 @@ use anyhow::Context;
 use futures_util::{SinkExt, StreamExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::time::{interval, timeout};
+use tokio_rustls::TlsConnector;
 use tokio_tungstenite::{
     accept_async,
     tungstenite::{Error as WsError, Message as WsMessage},
 };
 use tracing::{debug, error, info, warn};
 
+use kvm_core::crypto::tls::{CertFingerprint, TlsIdentity};
 use kvm_core::protocol::codec::encode_message_now;
+use kvm_core::protocol::messages::KvmMessage;
 use kvm_core::protocol::sequence::SequenceCounter;
 
-use crate::application::{translate_browser_to_kvm, translate_kvm_to_browser};
+use crate::application::{translate_browser_to_kvm, translate_kvm_to_browser, BridgePairing};
 use crate::domain::config::BridgeConfig;
 use crate::domain::messages::BrowserToMasterMsg;
 use crate::infrastructure::master_conn::MasterConnection;
@@ -67,11 +70,30 @@ use crate::infrastructure::master_conn::MasterConnection;
 /// - `config`  – Bridge configuration (addresses, timeouts).
 /// - `running` – Shared flag; the loop exits when this is set to `false`.
 ///
+/// When `config.master_tls` is set, a throwaway TLS identity is generated for
+/// this process.  The master treats every bridge session as a new, unpaired
+/// client, so there is nothing to gain from persisting it.  Its fingerprint is
+/// still needed per session: PIN pairing binds to it.
+///
 /// # Errors
 ///
 /// Returns an error if the TCP listener cannot be bound (e.g., the port is
-/// already in use or the process lacks permission to bind).
+/// already in use or the process lacks permission to bind), or if the TLS
+/// identity cannot be created.
 pub async fn run_server(config: BridgeConfig, running: Arc<AtomicBool>) -> anyhow::Result<()> {
+    let master_tls = if config.master_tls {
+        let identity = TlsIdentity::generate("kvm-web-bridge")
+            .context("failed to create TLS identity for the master connection")?;
+        let client_config = identity.client_config(config.master_fingerprint)?;
+        Some(MasterTls {
+            connector: TlsConnector::from(client_config),
+            fingerprint: identity.fingerprint(),
+        })
+    } else {
+        warn!("connecting to the master WITHOUT TLS");
+        None
+    };
+
     // Bind the WebSocket TCP listener.
     // `TcpListener::bind` is the async equivalent of `bind()` + `listen()`.
     let listener = TcpListener::bind(config.ws_bind_addr)
@@ -106,12 +128,13 @@ pub async fn run_server(config: BridgeConfig, running: Arc<AtomicBool>) -> anyho
             Ok(Ok((stream, peer_addr))) => {
                 info!("new browser connection from {peer_addr}");
                 let cfg = Arc::clone(&config);
+                let tls = master_tls.clone();
 
                 // Spawn a dedicated Tokio task for this session.
                 // `tokio::spawn` is non-blocking: it queues the task and returns
                 // immediately, so the accept loop is never delayed by I/O.
                 tokio::spawn(async move {
-                    handle_browser_session(stream, peer_addr, cfg).await;
+                    handle_browser_session(stream, peer_addr, cfg, tls).await;
                 });
             }
             Ok(Err(e)) => {
@@ -129,6 +152,14 @@ pub async fn run_server(config: BridgeConfig, running: Arc<AtomicBool>) -> anyho
     Ok(())
 }
 
+/// TLS settings shared by every session's master connection.
+#[derive(Clone)]
+struct MasterTls {
+    connector: TlsConnector,
+    /// Fingerprint of the bridge's own certificate.
+    fingerprint: CertFingerprint,
+}
+
 // ── Per-session handler ───────────────────────────────────────────────────────
 
 /// Top-level handler for a single browser WebSocket session.
@@ -142,8 +173,9 @@ async fn handle_browser_session(
     raw_stream: TcpStream,
     peer_addr: SocketAddr,
     config: Arc<BridgeConfig>,
+    master_tls: Option<MasterTls>,
 ) {
-    match run_session(raw_stream, peer_addr, config).await {
+    match run_session(raw_stream, peer_addr, config, master_tls).await {
         Ok(()) => info!("session {peer_addr} closed normally"),
         Err(e) => warn!("session {peer_addr} closed with error: {e:#}"),
     }
@@ -154,7 +186,7 @@ async fn handle_browser_session(
 /// This function:
 ///
 /// 1. Completes the WebSocket HTTP upgrade handshake with the browser.
-/// 2. Opens a TCP connection to the KVM master.
+/// 2. Opens a (TLS) connection to the KVM master.
 /// 3. Runs three concurrent async tasks:
 ///    - Browser → Master: JSON frames → binary KVM messages
 ///    - Master → Browser: binary KVM messages → JSON frames
@@ -169,6 +201,7 @@ async fn run_session(
     raw_stream: TcpStream,
     peer_addr: SocketAddr,
     config: Arc<BridgeConfig>,
+    master_tls: Option<MasterTls>,
 ) -> anyhow::Result<()> {
     // ── Step 1: Complete the WebSocket handshake ───────────────────────────────
     //
@@ -182,7 +215,8 @@ async fn run_session(
     info!("WebSocket session established: {peer_addr}");
 
     // ── Step 2: Connect to the KVM master ─────────────────────────────────────
-    let master_conn = MasterConnection::connect(config.master_addr)
+    let connector = master_tls.as_ref().map(|tls| &tls.connector);
+    let master_conn = MasterConnection::connect(config.master_addr, connector)
         .await
         .with_context(|| {
             format!(
@@ -215,6 +249,14 @@ async fn run_session(
     // Take apart the master connection into its read and write halves.
     let (master_read, master_write) = (master_conn.read_half, master_conn.write_half);
 
+    // Pairing state shared by the two forwarders: the master→browser side
+    // records `PairingRequest`s and checks `PairingConfirm`s, the
+    // browser→master side turns the user's PIN into a `PairingResponse`.
+    let pairing = Arc::new(std::sync::Mutex::new(BridgePairing::new(
+        master_tls.as_ref().map(|tls| tls.fingerprint),
+        master_conn.master_fingerprint,
+    )));
+
     // ── Step 4: Set up the KVM message channel ────────────────────────────────
     //
     // The `read_master_messages` function sends decoded KVM messages through
@@ -250,10 +292,29 @@ async fn run_session(
     // sends them to the browser as WebSocket text frames.
     let ws_tx_m2b = Arc::clone(&ws_tx);
     let session_id_m2b = session_id.clone();
+    let pairing_m2b = Arc::clone(&pairing);
     let master_to_browser_task = tokio::spawn(async move {
         while let Some(kvm_msg) = kvm_rx.recv().await {
-            // Translate the binary KVM message into a JSON browser message.
-            if let Some(json_msg) = translate_kvm_to_browser(&kvm_msg) {
+            // Pairing messages update the session's pairing state first.
+            let json_msg = match &kvm_msg {
+                KvmMessage::PairingRequest(req) => {
+                    lock_pairing(&pairing_m2b).on_request(req);
+                    translate_kvm_to_browser(&kvm_msg)
+                }
+                KvmMessage::PairingConfirm(confirm) => {
+                    match lock_pairing(&pairing_m2b).confirm(confirm) {
+                        Ok(done) => Some(done),
+                        Err(e) => {
+                            warn!("session {session_id_m2b}: rejecting PairingConfirm: {e}");
+                            None
+                        }
+                    }
+                }
+                _ => translate_kvm_to_browser(&kvm_msg),
+            };
+
+            // Send the translated JSON browser message, if any.
+            if let Some(json_msg) = json_msg {
                 match serde_json::to_string(&json_msg) {
                     Ok(json_str) => {
                         // Send the JSON string as a WebSocket text frame.
@@ -285,6 +346,7 @@ async fn run_session(
     // Wrap it in a `tokio::sync::Mutex` so the keepalive task can also borrow it.
     let master_write = Arc::new(tokio::sync::Mutex::new(master_write));
     let master_write_b2m = Arc::clone(&master_write);
+    let pairing_b2m = Arc::clone(&pairing);
 
     let browser_to_master_task = tokio::spawn({
         // Pin `ws_rx` so it can be used in the async block.
@@ -328,8 +390,21 @@ async fn run_session(
                             browser_msg_type_name(&browser_msg)
                         );
 
-                        // Translate JSON → binary KVM message.
-                        let kvm_msg = match translate_browser_to_kvm(&browser_msg) {
+                        // Translate JSON → binary KVM message.  A PIN goes
+                        // through the key exchange instead of being forwarded.
+                        let translated = match &browser_msg {
+                            BrowserToMasterMsg::PairingResponse {
+                                pairing_session_id,
+                                pin,
+                                accepted,
+                            } => lock_pairing(&pairing_b2m).respond(
+                                pairing_session_id,
+                                pin,
+                                *accepted,
+                            ),
+                            _ => translate_browser_to_kvm(&browser_msg),
+                        };
+                        let kvm_msg = match translated {
                             Ok(m) => m,
                             Err(e) => {
                                 warn!("session {session_id_b2m}: translation error: {e}");
@@ -485,10 +560,20 @@ async fn run_session(
 
 // ── Helper ────────────────────────────────────────────────────────────────────
 
+/// Locks the session's pairing state.
+///
+/// The state is only touched for a few microseconds at a time, so a poisoned
+/// lock (a panic while holding it) is simply recovered.
+fn lock_pairing(pairing: &std::sync::Mutex<BridgePairing>) -> MutexGuard<'_, BridgePairing> {
+    pairing
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// Returns a short type-name string for a `BrowserToMasterMsg` variant.
 ///
 /// Used in debug log messages to avoid accidentally logging sensitive field
-/// values (e.g., PIN hashes from `PairingResponse`).
+/// values (e.g., the PIN in `PairingResponse`).
 fn browser_msg_type_name(msg: &BrowserToMasterMsg) -> &'static str {
     match msg {
         BrowserToMasterMsg::Hello { .. } => "Hello",
@@ -531,10 +616,10 @@ mod tests {
     fn test_browser_msg_type_name_pairing_response() {
         let msg = BrowserToMasterMsg::PairingResponse {
             pairing_session_id: "x".to_string(),
-            pin_hash: "secret!".to_string(),
+            pin: "secret!".to_string(),
             accepted: true,
         };
-        // Must not include the secret pin_hash in the output string
+        // Must not include the PIN in the output string
         let name = browser_msg_type_name(&msg);
         assert_eq!(name, "PairingResponse");
         assert!(
@@ -27,6 +27,8 @@
 //!   --master-port <PORT>   KVM master control port [default: 24800]
 //!   --ping-interval <SECS> Keepalive ping interval in seconds [default: 5]
 //!   --ping-timeout  <SECS> Ping timeout in seconds [default: 15]
+//!   --master-fingerprint <sha256:HEX>  Pin the master's TLS certificate
+//!   --master-plaintext     Connect to the master without TLS
 //! ```
 //!
 //! # Environment variable overrides
@@ -40,6 +42,8 @@
 //! | `KVM_MASTER_ADDR`    | `127.0.0.1:24800` | Master TCP address             |
 //! | `KVM_PING_INTERVAL`  | `5`               | Keepalive ping interval (secs) |
 //! | `KVM_PING_TIMEOUT`   | `15`              | Ping timeout (secs)            |
+//! | `KVM_MASTER_FINGERPRINT` | (none)        | Master certificate pin         |
+//! | `KVM_MASTER_PLAINTEXT`   | `false`       | Disable TLS to the master      |
 //!
 //! # Architecture overview
 //!
@@ -53,7 +57,7 @@
 //!     ws_server/  Accept WebSocket connections
 //!     master_conn/ TCP connection to kvm-master
 //!       ↕
-//! kvm-master  (binary KVM protocol over TCP, port 24800)
+//! kvm-master  (binary KVM protocol over TLS/TCP, port 24800)
 //! ```
 
 use std::net::SocketAddr;
@@ -65,6 +69,7 @@ use std::time::Duration;
 
 use anyhow::Context;
 use clap::Parser;
+use kvm_core::crypto::tls::CertFingerprint;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
@@ -127,6 +132,19 @@ struct Cli {
     /// bridge considers the connection dead and closes the session.
     #[arg(long, default_value_t = 15, env = "KVM_PING_TIMEOUT")]
     ping_timeout: u64,
+
+    /// Expected fingerprint of the master's TLS certificate (`sha256:<hex>`).
+    ///
+    /// The master logs its fingerprint at startup.  Without this option the
+    /// bridge accepts whatever certificate the master presents.
+    #[arg(long, env = "KVM_MASTER_FINGERPRINT")]
+    master_fingerprint: Option<String>,
+
+    /// Connect to the master without TLS.
+    ///
+    /// Only works against a master started without TLS; meant for debugging.
+    #[arg(long, env = "KVM_MASTER_PLAINTEXT")]
+    master_plaintext: bool,
 }
 
 impl Cli {
@@ -135,7 +153,8 @@ impl Cli {
     /// # Errors
     ///
     /// Returns an error if `--ws-bind` or `--master-host` is not a valid IP
-    /// address, or if the resulting socket address string cannot be parsed.
+    /// address, if the resulting socket address string cannot be parsed, or if
+    /// `--master-fingerprint` is malformed.
     fn into_bridge_config(self) -> anyhow::Result<BridgeConfig> {
         // Construct the WebSocket bind address from --ws-bind and --ws-port.
         let ws_bind_addr: SocketAddr = format!("{}:{}", self.ws_bind, self.ws_port)
@@ -157,11 +176,20 @@ impl Cli {
                 )
             })?;
 
+        let master_fingerprint = self
+            .master_fingerprint
+            .as_deref()
+            .map(str::parse::<CertFingerprint>)
+            .transpose()
+            .context("invalid --master-fingerprint")?;
+
         Ok(BridgeConfig {
             ws_bind_addr,
             master_addr,
             ping_interval: Duration::from_secs(self.ping_interval),
             ping_timeout: Duration::from_secs(self.ping_timeout),
+            master_tls: !self.master_plaintext,
+            master_fingerprint,
         })
     }
 }
@@ -374,6 +402,42 @@ mod tests {
         assert_eq!(config.ping_timeout, Duration::from_secs(30));
     }
 
+    #[test]
+    fn test_into_bridge_config_defaults_to_tls_without_pin() {
+        let cli = Cli::parse_from(["kvm-web-bridge"]);
+        let config = cli.into_bridge_config().unwrap();
+        assert!(config.master_tls);
+        assert_eq!(config.master_fingerprint, None);
+    }
+
+    #[test]
+    fn test_into_bridge_config_master_fingerprint_and_plaintext() {
+        // Arrange
+        let hex = "ab".repeat(32);
+        let cli = Cli::parse_from([
+            "kvm-web-bridge",
+            "--master-fingerprint",
+            &format!("sha256:{hex}"),
+            "--master-plaintext",
+        ]);
+
+        // Act
+        let config = cli.into_bridge_config().unwrap();
+
+        // Assert
+        assert!(!config.master_tls);
+        assert_eq!(
+            config.master_fingerprint.map(|f| f.to_string()),
+            Some(format!("sha256:{hex}"))
+        );
+    }
+
+    #[test]
+    fn test_into_bridge_config_invalid_master_fingerprint_returns_error() {
+        let cli = Cli::parse_from(["kvm-web-bridge", "--master-fingerprint", "md5:1234"]);
+        assert!(cli.into_bridge_config().is_err());
+    }
+
     #[test]
     fn test_into_bridge_config_invalid_ws_bind_returns_error() {
         // Arrange: provide an invalid IP address string
@@ -384,6 +448,8 @@ mod tests {
             master_port: 24800,
             ping_interval: 5,
             ping_timeout: 15,
+            master_fingerprint: None,
+            master_plaintext: false,
         };
 
         // Act
@@ -403,6 +469,8 @@ mod tests {
             master_port: 24800,
             ping_interval: 5,
             ping_timeout: 15,
+            master_fingerprint: None,
+            master_plaintext: false,
         };
 
         // Act
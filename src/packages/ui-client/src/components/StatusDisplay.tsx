@@ -54,9 +54,10 @@ import type { ClientConnectionStatus } from "../types";
 const STATUS_COLOURS: Record<ClientConnectionStatus, string> = {
   Disconnected: "var(--colour-muted, #888)",          // grey — inactive
   Connecting: "var(--colour-warning, #f0a500)",        // amber — in progress
-  Connected: "var(--colour-info, #0088ff)",            // blue — established
+  Handshaking: "var(--colour-info, #0088ff)",          // blue — established
   Pairing: "var(--colour-warning, #f0a500)",           // amber — waiting for PIN
   Active: "var(--colour-success, #00c853)",            // green — fully operational
+  Backoff: "var(--colour-muted, #888)",               // grey — waiting to retry
 };
 
 /** Props for StatusDisplay. */
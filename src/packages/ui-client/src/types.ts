@@ -20,16 +20,18 @@
  * |----------------|--------------------------------------------------------|
  * | Disconnected   | Not yet attempting to connect                          |
  * | Connecting     | TCP connect to master in progress                      |
- * | Connected      | Control channel established, awaiting HelloAck/pairing |
+ * | Handshaking    | Control channel established, awaiting HelloAck         |
  * | Pairing        | PIN pairing dialog is showing                          |
  * | Active         | Fully paired; receiving input events from master       |
+ * | Backoff        | Connection lost; waiting before the next attempt       |
  */
 export type ClientConnectionStatus =
   | "Disconnected"
   | "Connecting"
-  | "Connected"
+  | "Handshaking"
   | "Active"
-  | "Pairing";
+  | "Pairing"
+  | "Backoff";
 
 /**
  * Full client status snapshot returned by `get_client_status`.
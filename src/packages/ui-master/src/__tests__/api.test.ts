@@ -30,13 +30,20 @@
 import { invoke } from "@tauri-apps/api/core";
 import {
   getClients,
+  pairClient,
   getLayout,
   updateLayout,
   getNetworkConfig,
   updateNetworkConfig,
   getSharingEnabled,
 } from "../api";
-import type { ClientDto, ClientLayoutDto, CommandResult, NetworkConfigDto } from "../types";
+import type {
+  ClientDto,
+  ClientLayoutDto,
+  CommandResult,
+  NetworkConfigDto,
+  PairingDto,
+} from "../types";
 
 const mockInvoke = invoke as jest.MockedFunction<typeof invoke>;
 
@@ -67,6 +74,12 @@ const sampleClient: ClientDto = {
   eventsPerSecond: 60,
 };
 
+const samplePairing: PairingDto = {
+  clientId: "aaaa-aaaa",
+  sessionId: "bbbb-bbbb",
+  pin: "123456",
+};
+
 const sampleLayout: ClientLayoutDto = {
   clientId: "aaaa-aaaa",
   name: "dev-linux",
@@ -79,6 +92,7 @@ const sampleLayout: ClientLayoutDto = {
 const sampleNetwork: NetworkConfigDto = {
   controlPort: 24800,
   inputPort: 24801,
+  udpInput: true,
   discoveryPort: 24802,
   bindAddress: "0.0.0.0",
 };
@@ -107,6 +121,32 @@ describe("getClients", () => {
   });
 });
 
+// ── pairClient ─────────────────────────────────────────────────────────────────
+
+describe("pairClient", () => {
+  test("returns the PIN to display on success", async () => {
+    // Arrange
+    mockInvoke.mockResolvedValue(ok(samplePairing));
+
+    // Act
+    const result = await pairClient("aaaa-aaaa");
+
+    // Assert
+    expect(result).toEqual(samplePairing);
+    expect(mockInvoke).toHaveBeenCalledWith("pair_client", {
+      clientId: "aaaa-aaaa",
+    });
+  });
+
+  test("throws when the client is not connected", async () => {
+    // Arrange
+    mockInvoke.mockResolvedValue(fail("client aaaa-aaaa is not connected"));
+
+    // Act / Assert
+    await expect(pairClient("aaaa-aaaa")).rejects.toThrow("not connected");
+  });
+});
+
 // ── getLayout ──────────────────────────────────────────────────────────────────
 
 describe("getLayout", () => {
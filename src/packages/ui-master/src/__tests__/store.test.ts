@@ -128,6 +128,7 @@ describe("useMasterStore", () => {
     const cfg: NetworkConfigDto = {
       controlPort: 9000,
       inputPort: 9001,
+      udpInput: true,
       discoveryPort: 9002,
       bindAddress: "0.0.0.0",
     };
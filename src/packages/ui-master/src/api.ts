@@ -36,6 +36,7 @@ import type {
   ClientLayoutDto,
   CommandResult,
   NetworkConfigDto,
+  PairingDto,
 } from "./types";
 
 // ── Clients ───────────────────────────────────────────────────────────────────
@@ -57,6 +58,26 @@ export async function getClients(): Promise<ClientDto[]> {
   return result.data;
 }
 
+/**
+ * Starts pairing with a connected client.
+ *
+ * The client shows a PIN prompt; the returned PIN is what the user must type
+ * there.  Pairing completes in the background once the client answers.
+ *
+ * @param clientId - UUID string of the connected client to pair.
+ * @returns The pairing attempt, including the PIN to display.
+ * @throws An `Error` if the client is not connected or is locked out.
+ */
+export async function pairClient(clientId: string): Promise<PairingDto> {
+  const result = await invoke<CommandResult<PairingDto>>("pair_client", {
+    clientId,
+  });
+  if (!result.success || result.data === null) {
+    throw new Error(result.error ?? "pair_client failed");
+  }
+  return result.data;
+}
+
 // ── Layout ────────────────────────────────────────────────────────────────────
 
 /**
@@ -47,6 +47,21 @@ export interface ClientDto {
   eventsPerSecond: number;
 }
 
+/**
+ * A pairing the master has started with a connected client.
+ *
+ * Mirrors the Rust `PairingDto` struct in `kvm-master/src/infrastructure/ui_bridge/mod.rs`.
+ * The UI shows `pin`; the user types it into the client's PIN prompt.
+ */
+export interface PairingDto {
+  /** UUID string of the client being paired (matches `ClientDto.clientId`). */
+  clientId: string;
+  /** UUID string of this pairing attempt. */
+  sessionId: string;
+  /** Six-digit PIN to display until the client has paired. */
+  pin: string;
+}
+
 // ── Layout DTOs ───────────────────────────────────────────────────────────────
 
 /**
@@ -91,6 +106,8 @@ export interface NetworkConfigDto {
   controlPort: number;
   /** UDP port for high-frequency input events (mouse moves). */
   inputPort: number;
+  /** Whether mouse moves may use the UDP input channel (TCP otherwise). */
+  udpInput: boolean;
   /** UDP broadcast port used for automatic LAN discovery. */
   discoveryPort: number;
   /** IP address the master binds to (e.g. "0.0.0.0" to listen on all interfaces). */